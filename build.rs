@@ -0,0 +1,95 @@
+//! 构建时从 unicode/ 下的 UCD 摘录生成不可见字符表
+//!
+//! 收录规则: 通用类别为 Cf/Zl/Zp 或具有 Default_Ignorable_Code_Point 属性的已分配码点，
+//! 排除 Prepended_Concatenation_Mark（可见的前置数字标记）以及单独处理的 Unicode Tags 区段。
+
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const TAGS_BLOCK: std::ops::RangeInclusive<u32> = 0xE0000..=0xE007F;
+
+fn read(name: &str) -> String {
+    let path = Path::new("unicode").join(name);
+    println!("cargo:rerun-if-changed={}", path.display());
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("读取 {} 失败: {}", path.display(), e))
+}
+
+/// 解析 "XXXX" 或 "XXXX..YYYY"
+fn parse_range(s: &str) -> (u32, u32) {
+    let s = s.trim();
+    let (a, b) = s.split_once("..").unwrap_or((s, s));
+    let parse = |h: &str| u32::from_str_radix(h.trim(), 16).unwrap_or_else(|_| panic!("非法码点: {}", h));
+    (parse(a), parse(b))
+}
+
+/// 解析 "范围 ; 值 # 注释" 格式的属性文件
+fn property_lines(src: &str) -> impl Iterator<Item = ((u32, u32), &str)> {
+    src.lines()
+        .map(|l| l.split('#').next().unwrap_or(""))
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| {
+            let (range, value) = l.split_once(';').expect("属性行缺少 ';'");
+            (parse_range(range), value.trim())
+        })
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    let unicode_data = read("UnicodeData.txt");
+    let core_props = read("DerivedCoreProperties.txt");
+    let prop_list = read("PropList.txt");
+    let derived_age = read("DerivedAge.txt");
+
+    let version = unicode_data
+        .lines()
+        .find_map(|l| l.split("version ").nth(1))
+        .map(|v| v.trim().to_string())
+        .expect("UnicodeData.txt 缺少版本信息");
+
+    let mut ignorable = HashSet::new();
+    for ((lo, hi), value) in property_lines(&core_props) {
+        if value == "Default_Ignorable_Code_Point" {
+            ignorable.extend(lo..=hi);
+        }
+    }
+    let mut prepended = HashSet::new();
+    for ((lo, hi), value) in property_lines(&prop_list) {
+        if value == "Prepended_Concatenation_Mark" {
+            prepended.extend(lo..=hi);
+        }
+    }
+    let mut ages = BTreeMap::new();
+    for ((lo, hi), value) in property_lines(&derived_age) {
+        for cp in lo..=hi {
+            ages.insert(cp, value.to_string());
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str(&format!("pub const UNICODE_VERSION: &str = {:?};\n\n", version));
+    out.push_str("pub static UCD_INVISIBLES: &[UcdChar] = &[\n");
+    let mut count = 0;
+    for line in unicode_data.lines().filter(|l| !l.starts_with('#') && !l.trim().is_empty()) {
+        let fields: Vec<&str> = line.split(';').collect();
+        let cp = u32::from_str_radix(fields[0], 16).expect("非法码点");
+        let (name, gc) = (fields[1], fields[2]);
+        let default_ignorable = ignorable.contains(&cp);
+        let format_like = matches!(gc, "Cf" | "Zl" | "Zp");
+        if !(default_ignorable || format_like) || prepended.contains(&cp) || TAGS_BLOCK.contains(&cp) {
+            continue;
+        }
+        let age = ages.get(&cp).map(String::as_str).unwrap_or("?");
+        out.push_str(&format!(
+            "    UcdChar {{ codepoint: 0x{:04X}, name: {:?}, general_category: {:?}, default_ignorable: {}, age: {:?} }},\n",
+            cp, name, gc, default_ignorable, age
+        ));
+        count += 1;
+    }
+    out.push_str("];\n");
+    assert!(count > 0, "未生成任何不可见字符条目");
+
+    let dest = Path::new(&env::var("OUT_DIR").unwrap()).join("unicode_invisibles.rs");
+    fs::write(dest, out).expect("写入生成文件失败");
+}
@@ -0,0 +1,51 @@
+// 按 330k 网页版、Steganographr、zero-width-lib 公开源码的写法独立重写的编码器，fixtures.jsonl 的 expected 由它生成
+// 用法: node reference.js < fixtures.jsonl（逐条核对 expected，有不一致时以非零状态退出）
+'use strict';
+
+// 330k unicode_steganography.js：默认 4 字符集，每个 UTF-16 码元转为定长 N 进制
+function encode330k(message, cover) {
+  const chars = ['\u200c', '\u200d', '\u202c', '\ufeff'];
+  const codelength = Math.ceil(Math.log(65536) / Math.log(chars.length));
+  const hidden = [];
+  for (let i = 0; i < message.length; i++) {
+    const digits = message.charCodeAt(i).toString(chars.length).padStart(codelength, '0');
+    hidden.push(digits.split('').map(d => chars[parseInt(d, chars.length)]).join(''));
+  }
+  let out = '';
+  for (let i = 0; i < Math.max(cover.length, hidden.length); i++) {
+    if (i < cover.length) out += cover[i];
+    if (i < hidden.length) out += hidden[i];
+  }
+  return out;
+}
+
+// neatnik Steganographr (PHP)：decbin(ord(byte)) 以空格连接，空格=WJ、0=ZWSP、1=ZWNJ，FEFF 包裹，
+// mb_substr 插在第一个字符之后
+function encodeSteganographr(message, cover) {
+  const bin = Array.from(Buffer.from(message, 'utf8')).map(b => b.toString(2)).join(' ');
+  const hidden = '\ufeff' + bin.replace(/ /g, '\u2060').replace(/0/g, '\u200b').replace(/1/g, '\u200c') + '\ufeff';
+  const chars = Array.from(cover);
+  return chars.slice(0, 1).join('') + hidden + chars.slice(1).join('');
+}
+
+// zero-width-lib：charCodeAt 的二进制补零到 8 位、以空格连接，1=ZWSP、0=ZWNJ、空格=ZWJ，FEFF 连接
+function encodeZeroWidthLib(message, cover) {
+  const zeroPad = num => '00000000'.slice(String(num).length) + num;
+  const binary = message.split('').map(c => zeroPad(c.charCodeAt(0).toString(2))).join(' ');
+  const hidden = binary.split('').map(b => (b === '1' ? '\u200b' : b === '0' ? '\u200c' : '\u200d')).join('\ufeff');
+  return cover[0] + hidden + cover.slice(1);
+}
+
+const encoders = { '330k': encode330k, steganographr: encodeSteganographr, zero_width_lib: encodeZeroWidthLib };
+
+const input = require('fs').readFileSync(0, 'utf8');
+let mismatches = 0;
+for (const line of input.split('\n')) {
+  if (!line.trim() || line.trimStart().startsWith('#')) continue;
+  const f = JSON.parse(line);
+  const out = encoders[f.tool](f.message, f.cover);
+  const ok = out === f.expected;
+  if (!ok) mismatches++;
+  console.log(`${ok ? 'ok  ' : 'DIFF'} ${f.name}`);
+}
+process.exit(mismatches ? 1 : 0);
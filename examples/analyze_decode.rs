@@ -0,0 +1,39 @@
+//! 分析并自动解码一段文本中的零宽隐写信息
+//!
+//! ```bash
+//! cargo run --example analyze_decode -- suspicious.txt
+//! echo "..." | cargo run --example analyze_decode
+//! ```
+
+use std::io::Read;
+
+use zw_mcp_server::zw_core::{engine, postprocess, sanitize};
+
+fn main() {
+    let text = match std::env::args().nth(1) {
+        Some(path) => std::fs::read_to_string(&path).unwrap_or_else(|e| {
+            eprintln!("读取失败 {}: {}", path, e);
+            std::process::exit(2);
+        }),
+        None => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf).expect("读取 stdin 失败");
+            buf
+        }
+    };
+
+    let analysis = engine::analyze(&text);
+    print!("{}", engine::format_analysis(&analysis));
+    if analysis.zero_width_count == 0 {
+        return;
+    }
+
+    let results = engine::auto_decode(&text, &engine::Budget::normal());
+    println!("\n前 5 个候选:");
+    for r in results.iter().take(5) {
+        println!("  [{:.1}] {} → {}", r.score, r.method, sanitize::display(&r.decoded));
+        if let Some(chain) = postprocess::run(&r.decoded).into_iter().find(|c| !c.flags.is_empty()) {
+            println!("         后处理 {} → {}", chain.steps.join(" → "), chain.flags.join(", "));
+        }
+    }
+}
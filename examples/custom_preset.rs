@@ -0,0 +1,28 @@
+//! 注册自定义解码方案，让自动解码识别非标准字符集
+//!
+//! ```bash
+//! cargo run --example custom_preset
+//! ```
+
+use zw_mcp_server::zw_core::engine::{self, DigitOrder, Preset};
+
+fn main() {
+    // 某道题用 INVISIBLE TIMES / INVISIBLE SEPARATOR 表示 0 / 1，低位在前
+    let preset = Preset {
+        name: "题目方案".to_string(),
+        chars: vec!['\u{2062}', '\u{2063}'],
+        description: "U+2062 = 0，U+2063 = 1，低位在前".to_string(),
+        group_size: Some(8),
+        digit_order: Some(DigitOrder::LsbFirst),
+        terminator: None,
+        reference: None,
+    };
+    engine::add_preset("challenge", preset.clone()).expect("注册预设失败");
+
+    let stego = format!("看不见的{}内容", engine::encode_preset("flag{lsb_first}", &preset));
+    let results = engine::auto_decode(&stego, &engine::Budget::normal());
+    for r in results.iter().take(3) {
+        println!("[{:.1}] {} → {}", r.score, r.method, r.decoded);
+    }
+    assert!(results.iter().any(|r| r.decoded == "flag{lsb_first}"));
+}
@@ -0,0 +1,26 @@
+//! 把消息编码为零宽字符并分散插入载体文本
+//!
+//! ```bash
+//! cargo run --example encode -- "flag{hidden}" "这是一段看起来很普通的文字"
+//! ```
+
+use zw_mcp_server::zw_core::engine;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (Some(message), Some(cover)) = (args.next(), args.next()) else {
+        eprintln!("用法: encode <消息> <载体文本>");
+        std::process::exit(2);
+    };
+
+    let payload = engine::encode_binary(&message, '\u{200B}', '\u{200C}', 8);
+    let stego = engine::spread_payload(&cover, &payload);
+    println!("{}", stego);
+
+    // 自检：用自动解码还原
+    let results = engine::auto_decode(&stego, &engine::Budget::fast());
+    match results.first() {
+        Some(r) if r.decoded == message => eprintln!("✓ 往返验证通过（{}）", r.method),
+        _ => eprintln!("✗ 自动解码未能还原消息"),
+    }
+}
@@ -0,0 +1,153 @@
+//! 命令行子命令（非 MCP 模式）
+//!
+//! `zw-mcp-server check [--config zw.toml] [--findings out.json] <路径>...`
+//! 扫描文件/目录，发现策略不允许的不可见字符时以非零退出码结束，便于接入 pre-commit 或 CI。
+//! 路径为 `-` 时读取标准输入，为 http(s) 地址时下载后扫描（与工具的 url 参数同一套限制）。
+
+use std::path::PathBuf;
+
+use serde_json::json;
+
+use crate::mcp::input::InputSource;
+use crate::mcp::{config, tools};
+use crate::zw_core::{chars, check};
+
+/// 退出码: 未发现问题
+const EXIT_CLEAN: i32 = 0;
+/// 退出码: 发现不允许的不可见字符
+const EXIT_FINDINGS: i32 = 1;
+/// 退出码: 参数或配置错误
+const EXIT_USAGE: i32 = 2;
+
+const MAX_FILES: usize = 100_000;
+
+fn usage() -> i32 {
+    eprintln!("用法: zw-mcp-server check [--config zw.toml] [--findings findings.json] <文件、目录、url 或 ->...");
+    EXIT_USAGE
+}
+
+/// 运行 check 子命令，返回进程退出码
+pub fn run_check(args: &[String]) -> i32 {
+    let mut findings_path: Option<PathBuf> = None;
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((n, v)) => (n, Some(v.to_string())),
+            None => (arg.as_str(), None),
+        };
+        match name {
+            "--config" | "--findings" => {
+                let Some(value) = inline.or_else(|| it.next().cloned()) else {
+                    return usage();
+                };
+                if name == "--findings" {
+                    findings_path = Some(value.into());
+                } else if let Err(e) = config::init(value.into()) {
+                    eprintln!("{}", e);
+                    return EXIT_USAGE;
+                }
+            }
+            "-h" | "--help" => {
+                usage();
+                return EXIT_CLEAN;
+            }
+            _ if arg.starts_with("--") => {
+                eprintln!("未知参数: {}", arg);
+                return usage();
+            }
+            _ => paths.push(arg.into()),
+        }
+    }
+    if paths.is_empty() {
+        return usage();
+    }
+
+    let mut files = Vec::new();
+    let mut streams = Vec::new();
+    for p in &paths {
+        let arg = p.to_string_lossy();
+        if !matches!(InputSource::from_cli(&arg), InputSource::File(_)) {
+            streams.push(arg.into_owned());
+        } else if p.is_dir() {
+            tools::collect_files(p, &mut files, MAX_FILES);
+        } else if p.is_file() {
+            files.push(p.clone());
+        } else {
+            eprintln!("路径不存在: {}", p.display());
+            return EXIT_USAGE;
+        }
+    }
+
+    let policy = config::check_policy();
+    let mut records = Vec::new();
+    let mut scanned = 0usize;
+    for arg in &streams {
+        let source = InputSource::from_cli(arg);
+        match source.read_bytes() {
+            Ok(raw) => scan(source.label(), &raw, &policy, &mut records, &mut scanned),
+            Err(e) => eprintln!("无法读取: {}", e.content.first().map_or("", |c| c.text.as_str())),
+        }
+    }
+    for file in &files {
+        let Ok(raw) = std::fs::read(file) else {
+            eprintln!("无法读取: {}", file.display());
+            continue;
+        };
+        scan(&file.display().to_string(), &raw, &policy, &mut records, &mut scanned);
+    }
+
+    if let Some(out) = &findings_path {
+        let report = json!({
+            "files_scanned": scanned,
+            "violations": records.len(),
+            "findings": records,
+        });
+        let text = serde_json::to_string_pretty(&report).unwrap_or_default();
+        if let Err(e) = std::fs::write(out, text) {
+            eprintln!("无法写入 {}: {}", out.display(), e);
+            return EXIT_USAGE;
+        }
+    }
+
+    eprintln!("扫描 {} 个文件，发现 {} 处不允许的不可见字符", scanned, records.len());
+    if records.is_empty() {
+        EXIT_CLEAN
+    } else {
+        EXIT_FINDINGS
+    }
+}
+
+/// 检查一份输入，违规项打印到 stdout 并记入 `records`
+fn scan(label: &str, raw: &[u8], policy: &check::Policy, records: &mut Vec<serde_json::Value>, scanned: &mut usize) {
+    // 跳过二进制文件
+    if raw.contains(&0) && !is_utf16(raw) {
+        return;
+    }
+    *scanned += 1;
+    let mut text = tools::decode_bytes_auto(raw, true);
+    // 解码时去掉了 BOM，放回去交给策略判断（allow_bom）
+    if raw.starts_with(&[0xEF, 0xBB, 0xBF]) || is_utf16(raw) {
+        text.insert(0, '\u{FEFF}');
+    }
+    for v in check::check_text(&text, policy) {
+        let ch = char::from_u32(v.codepoint).unwrap_or('\u{FFFD}');
+        let name = chars::name_of(ch).unwrap_or_default();
+        let category = chars::category_of(ch).unwrap_or("");
+        println!("{}:{}:{}: U+{:04X} {} ({})", label, v.line, v.column, v.codepoint, name, category);
+        records.push(json!({
+            "file": label,
+            "line": v.line,
+            "column": v.column,
+            "codepoint": format!("U+{:04X}", v.codepoint),
+            "name": name,
+            "category": category,
+        }));
+    }
+}
+
+/// 带 BOM 的 UTF-16 文本含 NUL 字节，但不是二进制文件
+fn is_utf16(raw: &[u8]) -> bool {
+    raw.starts_with(&[0xFF, 0xFE]) || raw.starts_with(&[0xFE, 0xFF])
+}
+
@@ -0,0 +1,8 @@
+//! 零宽字符隐写术分析库
+//!
+//! `zw-mcp-server` 的 MCP 工具都建立在 [`zw_core`] 之上；需要在自己的程序中分析、解码或生成
+//! 零宽隐写文本时，可以直接把本 crate 当作库使用，不必经过 MCP 协议。
+//!
+#![doc = include_str!("../docs/library.md")]
+
+pub mod zw_core;
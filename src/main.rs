@@ -7,21 +7,61 @@
 //! ## 提供的工具
 //!
 //! - `zw_analyze`      - 分析文本中的零宽字符分布
+//! - `zw_locate_flag`  - 一次调用解题，只返回 flag 与方案链
 //! - `zw_decode`       - 自动解码零宽字符隐写信息（支持多种方案）
+//! - `zw_explain`      - 解释解码方案（映射表、分组、首字符推导过程）
+//! - `zw_decode_detail` - 按候选 ID 查看完整细节（映射、符号流、位置）
 //! - `zw_encode`       - 将消息编码为零宽字符隐写文本
+//! - `zw_mass_encode`  - 批量编码多个任务并汇总结果
+//! - `zw_random_cover` - 生成指定容量的随机载体文本
+//! - `zw_difficulty`   - 估计题目的解题难度（供出题人校准）
 //! - `zw_dump_raw`     - 导出原始零宽字符序列（调试用）
+//! - `zw_hexdump`      - 导出文件中零宽序列周围的原始字节
+//! - `zw_extract_file` - 从零宽层提取嵌入的二进制文件（zip/png/gzip/elf 等）
+//! - `zw_entropy_visual` - 按块统计不可见字符密度（sparkline + 高密度区域）
+//! - `zw_grep`         - 按码点/分类搜索零宽字符的每一处出现
+//! - `zw_git_scan`     - 扫描 git 工作区或提交范围中引入的不可见字符
+//! - `zw_summarize_scan` - 批量扫描汇总与分诊列表
+//! - `zw_strip`        - 按策略清理零宽/不可见字符
+//! - `zw_segments`     - 列出零宽字符分段及前后文本
+//! - `zw_annotate`     - 生成带脚注标记与附录的 Markdown 标注副本
+//! - `zw_check_name`   - 检查用户名中的隐形字符与冒充填充
+//! - `zw_blank_message` - 分析空白消息的技巧与是否携带数据
+//! - `zw_session_*`     - 增量解码会话（start / feed / decode）
+//! - `zw_job_*`         - 后台任务（start / status / result），用于超过单次调用超时的扫描与解码
+//! - `zw_reassemble`   - 按序号重组拆散在多份文档中的载荷
+//! - `zw_simulate_channel` - 模拟平台处理，检验载荷鲁棒性
+//! - `zw_recommend` - 按目标平台推荐编码方案与字符集
+//! - `zw_fuzz` - 生成随机受损变体，检验解码器的鲁棒性
+//! - `zw_benchmark`     - 在题目语料上回归测试自动解码
+//! - `zw_health`       - 部署自检（预设往返、字符表、文件权限、版本与限制）
 //! - `zw_list_chars`   - 列出所有已知零宽/不可见字符
 //! - `zw_list_presets` - 列出所有编码预设方案
+//! - `zw_presets_test` - 用指定预设试解样本，报告字符集覆盖率与解码结果
+//! - `zw_add_preset`   - 运行时添加自定义预设（注册 `zw_preset_<key>` 专用工具）
+//! - `zw_remove_preset` - 删除自定义预设
 //!
 //! ## 运行
 //!
 //! ```bash
 //! # 直接启动 MCP Server（stdio 模式）
 //! zw-mcp-server
+//!
+//! # 把每次工具调用记录到审计日志（JSON Lines）
+//! zw-mcp-server --audit-log audit.jsonl
+//!
+//! # 加载 TOML 配置（SIGHUP 时重载；--admin-tools 额外开放 zw_reload_config）
+//! zw-mcp-server --config zw.toml --admin-tools
+//!
+//! # 检查模式：发现不允许的不可见字符时退出码为 1（策略取自配置文件 [check] 段）
+//! zw-mcp-server check --config zw.toml --findings findings.json src/ docs/
 //! ```
 
+mod cli;
 mod mcp;
-mod zw_core;
+
+// 核心逻辑作为库构建（见 lib.rs），服务端与库使用者共用同一份实现
+use zw_mcp_server::zw_core;
 
 use tracing_subscriber::EnvFilter;
 
@@ -36,6 +76,45 @@ async fn main() {
         .with_target(false)
         .init();
 
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    if argv.first().map(String::as_str) == Some("check") {
+        std::process::exit(cli::run_check(&argv[1..]));
+    }
+
+    let mut args = argv.into_iter();
+    while let Some(arg) = args.next() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((n, v)) => (n.to_string(), Some(v.to_string())),
+            None => (arg.clone(), None),
+        };
+        match name.as_str() {
+            "--audit-log" | "--config" => {
+                let Some(path) = inline.or_else(|| args.next()) else {
+                    eprintln!("{} 需要文件路径", name);
+                    std::process::exit(2);
+                };
+                if name == "--audit-log" {
+                    if let Err(e) = mcp::audit::init(std::path::Path::new(&path)) {
+                        eprintln!("无法打开审计日志 {}: {}", path, e);
+                        std::process::exit(2);
+                    }
+                    tracing::info!("Audit log: {}", path);
+                } else {
+                    match mcp::config::init(path.into()) {
+                        Ok(summary) => tracing::info!("{}", summary),
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            std::process::exit(2);
+                        }
+                    }
+                    mcp::config::spawn_sighup_reloader();
+                }
+            }
+            "--admin-tools" => mcp::config::enable_admin_tools(),
+            _ => tracing::warn!("Unknown argument: {}", arg),
+        }
+    }
+
     if let Err(e) = mcp::server::run().await {
         tracing::error!("Server error: {}", e);
         std::process::exit(1);
@@ -0,0 +1,106 @@
+//! 审计日志 - 把每次工具调用记录为一行 JSON，满足分析环境的可追溯要求
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use super::protocol::ToolCallResult;
+
+static AUDIT_LOG: OnceLock<Mutex<File>> = OnceLock::new();
+static AUDIT_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// 打开（追加模式）审计日志文件
+pub fn init(path: &Path) -> std::io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let _ = AUDIT_LOG.set(Mutex::new(file));
+    let _ = AUDIT_PATH.set(path.to_path_buf());
+    Ok(())
+}
+
+/// 审计日志文件路径（未启用时为 None）
+pub fn path() -> Option<&'static Path> {
+    AUDIT_PATH.get().map(PathBuf::as_path)
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 需要从日志中隐去的参数名
+const SECRET_KEYS: [&str; 1] = ["password"];
+
+/// 递归隐去参数中的口令（含 `zw_job_start`、`zw_batch` 等嵌套在 `arguments` 里的），用于调试日志与审计日志
+pub fn redact_secrets(args: &Value) -> Value {
+    match args {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| {
+                    let v = if SECRET_KEYS.contains(&k.as_str()) {
+                        Value::String("<redacted>".to_string())
+                    } else {
+                        redact_secrets(v)
+                    };
+                    (k.clone(), v)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact_secrets).collect()),
+        other => other.clone(),
+    }
+}
+
+/// 输入大小: 参数序列化长度，file_path 指向的文件按实际大小计
+fn input_bytes(args: &Value) -> u64 {
+    let file_size = args
+        .get("file_path")
+        .and_then(|v| v.as_str())
+        .and_then(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .unwrap_or(0);
+    args.to_string().len() as u64 + file_size
+}
+
+/// 记录一次工具调用（未启用审计日志时不做任何事）
+pub fn record(request_id: Option<&Value>, tool: &str, args: &Value, result: &ToolCallResult, elapsed: Duration) {
+    let Some(log) = AUDIT_LOG.get() else {
+        return;
+    };
+    // 参数只记录哈希，不落盘原文；口令的哈希可被字典还原，先隐去再计算（嵌套参数中的口令同样如此）
+    let arg_hashes: Map<String, Value> = redact_secrets(args)
+        .as_object()
+        .map(|obj| {
+            obj.iter()
+                .filter(|(k, _)| !SECRET_KEYS.contains(&k.as_str()))
+                .map(|(k, v)| (k.clone(), Value::String(sha256_hex(v.to_string().as_bytes()))))
+                .collect()
+        })
+        .unwrap_or_default();
+    let text: String = result.content.iter().map(|c| c.text.as_str()).collect();
+    let summary: String = text.lines().next().unwrap_or("").chars().take(120).collect();
+    let ts_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let entry = json!({
+        "ts_ms": ts_ms,
+        "request_id": request_id,
+        "tool": tool,
+        "arg_sha256": arg_hashes,
+        "input_bytes": input_bytes(args),
+        "duration_ms": elapsed.as_secs_f64() * 1000.0,
+        "is_error": result.is_error.unwrap_or(false),
+        "error_code": result.error_code(),
+        "result_chars": text.chars().count(),
+        "result_summary": summary,
+    });
+    if let Ok(mut file) = log.lock() {
+        if let Err(e) = writeln!(file, "{}", entry) {
+            tracing::warn!("Failed to write audit log: {}", e);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! 重型工具的并发上限与排队 - 防止解码、扫描类调用无限堆积
+//!
+//! 每个重型工具一个信号量：许可在工具真正执行完毕时才归还，超时返回后仍在后台运行的调用也占用许可。
+//! 许可用尽时请求排队等待（排队的调用在独立任务中等待，不阻塞会话里的其他请求），
+//! 排队数达到上限或等待超时则返回 JSON-RPC 错误 `-32000`
+//! （`data.reason = "server_busy"`，附 `retry_after_ms`），而不是无限制地接收新任务。
+//! 后台任务（`zw_job_start`）在执行前等待同一许可。上限取自配置文件 `[concurrency]`，热重载后对新请求生效。
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use super::config;
+use super::errors::{self, ErrorCode};
+
+/// 默认限制并发的重型工具
+pub const HEAVY_TOOLS: &[&str] = &[
+    "zw_decode",
+    "zw_locate_flag",
+    "zw_git_scan",
+    "zw_summarize_scan",
+    "zw_benchmark",
+    "zw_mass_encode",
+    "zw_difficulty",
+];
+/// 重型工具默认的并发上限
+pub const DEFAULT_LIMIT: usize = 2;
+/// 每个工具默认的排队上限
+pub const DEFAULT_QUEUE_DEPTH: usize = 4;
+/// 默认的排队等待上限
+pub const DEFAULT_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+/// 还没有执行记录时建议的重试间隔
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// 单个工具的信号量与运行统计
+struct Slot {
+    limit: usize,
+    semaphore: Arc<Semaphore>,
+    queued: AtomicUsize,
+    /// 最近执行耗时的指数移动平均（毫秒），用于估计重试间隔
+    avg_ms: Mutex<Option<f64>>,
+}
+
+static SLOTS: OnceLock<Mutex<HashMap<String, Arc<Slot>>>> = OnceLock::new();
+
+/// 工具当前生效的并发上限；None 表示不限制
+pub fn limit_for(tool: &str) -> Option<usize> {
+    let settings = config::concurrency();
+    let limit = match settings.tools.get(tool) {
+        Some(&n) => n,
+        None if HEAVY_TOOLS.contains(&tool) => settings.default_limit.unwrap_or(DEFAULT_LIMIT),
+        None => return None,
+    };
+    (limit > 0).then_some(limit)
+}
+
+/// 所有受限工具及其上限（供 zw_health 展示）
+pub fn limits() -> BTreeMap<String, usize> {
+    let settings = config::concurrency();
+    HEAVY_TOOLS
+        .iter()
+        .map(|t| t.to_string())
+        .chain(settings.tools.keys().cloned())
+        .filter_map(|t| limit_for(&t).map(|n| (t, n)))
+        .collect()
+}
+
+/// 取得工具的信号量；配置的上限变化后换用新的信号量（旧许可仍归还给旧信号量）
+fn slot(tool: &str) -> Option<Arc<Slot>> {
+    let limit = limit_for(tool)?;
+    let mut slots = SLOTS.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    let slot = slots.entry(tool.to_string()).or_insert_with(|| Arc::new(Slot::new(limit)));
+    if slot.limit != limit {
+        *slot = Arc::new(Slot::new(limit));
+    }
+    Some(slot.clone())
+}
+
+impl Slot {
+    fn new(limit: usize) -> Self {
+        Self { limit, semaphore: Arc::new(Semaphore::new(limit)), queued: AtomicUsize::new(0), avg_ms: Mutex::new(None) }
+    }
+
+    fn busy(&self, tool: &str, queue_depth: usize) -> Busy {
+        let avg = *self.avg_ms.lock().unwrap_or_else(|e| e.into_inner());
+        Busy {
+            tool: tool.to_string(),
+            limit: self.limit,
+            queued: self.queued.load(Ordering::Relaxed),
+            queue_depth,
+            retry_after: avg.map_or(DEFAULT_RETRY_AFTER, |ms| Duration::from_millis(ms.max(100.0) as u64)),
+        }
+    }
+}
+
+/// 执行许可；工具执行完毕（许可被释放）时记录耗时
+pub struct Permit {
+    _permit: OwnedSemaphorePermit,
+    slot: Arc<Slot>,
+    started: Instant,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let ms = self.started.elapsed().as_secs_f64() * 1000.0;
+        let mut avg = self.slot.avg_ms.lock().unwrap_or_else(|e| e.into_inner());
+        *avg = Some(avg.map_or(ms, |a| a * 0.7 + ms * 0.3));
+    }
+}
+
+/// 服务繁忙的拒绝信息
+#[derive(Debug, Clone)]
+pub struct Busy {
+    pub tool: String,
+    pub limit: usize,
+    pub queued: usize,
+    pub queue_depth: usize,
+    pub retry_after: Duration,
+}
+
+impl Busy {
+    pub fn message(&self) -> String {
+        format!(
+            "服务繁忙: {} 已有 {} 个调用在执行、{} 个在排队（排队上限 {}），请在 {} ms 后重试",
+            self.tool,
+            self.limit,
+            self.queued,
+            self.queue_depth,
+            self.retry_after.as_millis()
+        )
+    }
+
+    /// JSON-RPC 错误的 data 字段，供客户端程序化处理
+    pub fn data(&self) -> Value {
+        let data = json!({
+            "reason": "server_busy",
+            "tool": self.tool,
+            "max_concurrent": self.limit,
+            "queued": self.queued,
+            "queue_depth": self.queue_depth,
+            "retry_after_ms": self.retry_after.as_millis() as u64,
+        });
+        errors::with_code(ErrorCode::ServerBusy, data)
+    }
+}
+
+/// 为一次工具调用申请许可：不受限的工具返回 None；许可用尽时排队等待，
+/// 排队已满或等待超时返回 `Busy`
+pub async fn acquire(tool: &str) -> Result<Option<Permit>, Busy> {
+    let Some(slot) = slot(tool) else {
+        return Ok(None);
+    };
+    let permit = |p| Some(Permit { _permit: p, slot: slot.clone(), started: Instant::now() });
+    if let Ok(p) = slot.semaphore.clone().try_acquire_owned() {
+        return Ok(permit(p));
+    }
+    let settings = config::concurrency();
+    let queue_depth = settings.queue_depth.unwrap_or(DEFAULT_QUEUE_DEPTH);
+    if slot.queued.fetch_add(1, Ordering::AcqRel) >= queue_depth {
+        slot.queued.fetch_sub(1, Ordering::AcqRel);
+        return Err(slot.busy(tool, queue_depth));
+    }
+    let wait = settings.queue_timeout_ms.map_or(DEFAULT_QUEUE_TIMEOUT, Duration::from_millis);
+    let acquired = tokio::time::timeout(wait, slot.semaphore.clone().acquire_owned()).await;
+    slot.queued.fetch_sub(1, Ordering::AcqRel);
+    match acquired {
+        Ok(Ok(p)) => Ok(permit(p)),
+        _ => Err(slot.busy(tool, queue_depth)),
+    }
+}
+
+/// 后台任务在执行线程中等待许可：与直接调用共用同一信号量，但不计入排队上限（后台任务数另有上限），
+/// 也没有等待超时。不受限的工具返回 None
+pub fn acquire_blocking(tool: &str) -> Option<Permit> {
+    loop {
+        // 每轮重新取信号量，等待期间配置热重载后按新上限排队
+        let slot = slot(tool)?;
+        if let Ok(p) = slot.semaphore.clone().try_acquire_owned() {
+            return Some(Permit { _permit: p, slot, started: Instant::now() });
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,482 @@
+//! TOML 配置文件 - 限制、并发上限、自定义预设、flag 格式、评分权重、后处理链与临时工作区，支持热重载
+//!
+//! 通过 `--config path.toml` 加载；收到 SIGHUP 或调用 `zw_reload_config`（需 `--admin-tools`）
+//! 时重新读取。新配置校验失败时保留旧配置。
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use serde::Deserialize;
+
+use super::{server, tools};
+use crate::zw_core::{chars, check, engine, postprocess};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    limits: Limits,
+    scoring: ScoringSection,
+    /// 额外的 flag 格式（正则）
+    flag_patterns: Vec<String>,
+    presets: Vec<PresetEntry>,
+    check: CheckSection,
+    workspace: Workspace,
+    postprocess: PostprocessSection,
+    concurrency: Concurrency,
+    filesystem: Filesystem,
+    network: Network,
+}
+
+/// 运行限制；未设置的项使用环境变量或内置默认值
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    pub max_output_chars: Option<usize>,
+    pub timeout_ms: Option<u64>,
+    pub max_request_bytes: Option<usize>,
+    /// 每个连接每秒允许的工具调用/资源读取次数（令牌桶速率）
+    pub requests_per_second: Option<f64>,
+    /// 令牌桶容量，即允许的突发请求数；默认取速率向上取整
+    pub burst: Option<u32>,
+    /// 每个连接累计的工具执行时间上限（秒）
+    pub session_cpu_seconds: Option<f64>,
+    /// 每个连接累计读取的字节上限（请求内容与读取的文件）
+    pub session_bytes_read: Option<u64>,
+}
+
+/// 临时工作区；未设置的项使用内置默认值
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Workspace {
+    /// 工作区根目录，默认为系统临时目录下的 zw-mcp-workspace
+    pub root: Option<PathBuf>,
+    /// 产物保留时间（秒）
+    pub ttl_secs: Option<u64>,
+    /// 工作区总大小上限（字节）
+    pub max_bytes: Option<u64>,
+}
+
+/// 重型工具的并发上限与排队；未设置的项使用内置默认值
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Concurrency {
+    /// 重型工具默认的并发上限（0 = 不限制）
+    pub default_limit: Option<usize>,
+    /// 每个工具的排队上限（0 = 不排队，许可用尽时直接拒绝）
+    pub queue_depth: Option<usize>,
+    /// 排队等待的时间上限（毫秒）
+    pub queue_timeout_ms: Option<u64>,
+    /// 按工具覆盖的并发上限（0 = 不限制），也可以给默认不受限的工具加上限
+    pub tools: HashMap<String, usize>,
+}
+
+/// 文件读写的访问限制；未设置的项使用内置默认值
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Filesystem {
+    /// 允许读写的根目录（为空时不限制；临时工作区总是允许）。载入时解析为真实路径
+    pub allowed_roots: Vec<PathBuf>,
+    /// 单个输入文件的大小上限（字节）
+    pub max_file_bytes: Option<u64>,
+}
+
+/// url 输入的网络访问；未设置的项使用内置默认值
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Network {
+    /// 是否允许工具从 http(s) 地址读取输入（默认允许）
+    pub allow_urls: Option<bool>,
+    /// 单次请求的超时（毫秒）
+    pub url_timeout_ms: Option<u64>,
+}
+
+/// 解码结果的后处理链
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PostprocessSection {
+    /// 启用的变换及顺序；省略时为全部内置变换，其后是自定义命令
+    order: Option<Vec<String>>,
+    max_depth: Option<usize>,
+    commands: Vec<CommandEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CommandEntry {
+    name: String,
+    /// 程序及参数，数据经 stdin 传入、结果从 stdout 读取
+    command: Vec<String>,
+    timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ScoringSection {
+    printable_weight: Option<f64>,
+    alnum_weight: Option<f64>,
+    space_bonus: Option<f64>,
+    flag_bonus: Option<f64>,
+    keyboard_bonus: Option<f64>,
+    numeric_bonus: Option<f64>,
+    unprintable_penalty: Option<f64>,
+}
+
+/// `check` 子命令的策略
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CheckSection {
+    allowed_categories: Vec<String>,
+    allowed_codepoints: Vec<String>,
+    allow_bom: bool,
+    allow_legit_zwnj: bool,
+}
+
+impl Default for CheckSection {
+    fn default() -> Self {
+        let policy = check::Policy::default();
+        Self {
+            allowed_categories: Vec::new(),
+            allowed_codepoints: Vec::new(),
+            allow_bom: policy.allow_bom,
+            allow_legit_zwnj: policy.allow_legit_zwnj,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PresetEntry {
+    key: String,
+    chars: Vec<String>,
+    name: Option<String>,
+    description: Option<String>,
+    group_size: Option<usize>,
+    digit_order: Option<engine::DigitOrder>,
+    terminator: Option<String>,
+    reference: Option<String>,
+}
+
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+static LIMITS: OnceLock<RwLock<Limits>> = OnceLock::new();
+static WORKSPACE: OnceLock<RwLock<Workspace>> = OnceLock::new();
+static CHECK_POLICY: OnceLock<RwLock<check::Policy>> = OnceLock::new();
+static CONCURRENCY: OnceLock<RwLock<Concurrency>> = OnceLock::new();
+static FILESYSTEM: OnceLock<RwLock<Filesystem>> = OnceLock::new();
+static NETWORK: OnceLock<RwLock<Network>> = OnceLock::new();
+/// 上次从配置文件载入的预设 key，重载时先移除
+static CONFIG_PRESETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static ADMIN_TOOLS: AtomicBool = AtomicBool::new(false);
+
+/// `--config` 指定的配置文件路径
+pub fn config_path() -> Option<&'static std::path::Path> {
+    CONFIG_PATH.get().map(PathBuf::as_path)
+}
+
+/// 当前配置中的运行限制
+pub fn limits() -> Limits {
+    LIMITS
+        .get()
+        .map(|l| l.read().unwrap_or_else(|e| e.into_inner()).clone())
+        .unwrap_or_default()
+}
+
+/// 当前配置中的工作区设置
+pub fn workspace() -> Workspace {
+    WORKSPACE
+        .get()
+        .map(|w| w.read().unwrap_or_else(|e| e.into_inner()).clone())
+        .unwrap_or_default()
+}
+
+/// 当前配置中的并发上限设置
+pub fn concurrency() -> Concurrency {
+    CONCURRENCY
+        .get()
+        .map(|c| c.read().unwrap_or_else(|e| e.into_inner()).clone())
+        .unwrap_or_default()
+}
+
+/// 当前配置中的文件访问限制
+pub fn filesystem() -> Filesystem {
+    FILESYSTEM
+        .get()
+        .map(|f| f.read().unwrap_or_else(|e| e.into_inner()).clone())
+        .unwrap_or_default()
+}
+
+/// 当前配置中的网络访问设置
+pub fn network() -> Network {
+    NETWORK
+        .get()
+        .map(|n| n.read().unwrap_or_else(|e| e.into_inner()).clone())
+        .unwrap_or_default()
+}
+
+/// 当前配置中的 check 策略（未加载配置时为默认策略）
+pub fn check_policy() -> check::Policy {
+    CHECK_POLICY
+        .get()
+        .map(|p| p.read().unwrap_or_else(|e| e.into_inner()).clone())
+        .unwrap_or_default()
+}
+
+/// 启用管理类工具（zw_reload_config）
+pub fn enable_admin_tools() {
+    ADMIN_TOOLS.store(true, Ordering::Relaxed);
+}
+
+pub fn admin_tools_enabled() -> bool {
+    ADMIN_TOOLS.load(Ordering::Relaxed)
+}
+
+/// 首次加载配置文件
+pub fn init(path: PathBuf) -> Result<String, String> {
+    let _ = CONFIG_PATH.set(path);
+    reload()
+}
+
+/// 重新读取配置文件并应用；返回生效内容摘要
+pub fn reload() -> Result<String, String> {
+    let path = CONFIG_PATH.get().ok_or("未通过 --config 指定配置文件")?;
+    let raw = std::fs::read_to_string(path).map_err(|e| format!("读取配置失败: {} ({})", path.display(), e))?;
+    let file: ConfigFile = toml::from_str(&raw).map_err(|e| format!("配置格式错误: {}", e))?;
+
+    // 先完整校验，全部通过后再应用
+    for (name, value) in [
+        ("requests_per_second", file.limits.requests_per_second),
+        ("session_cpu_seconds", file.limits.session_cpu_seconds),
+    ] {
+        if value.is_some_and(|v| !(v.is_finite() && v > 0.0)) {
+            return Err(format!("limits.{} 必须为正数", name));
+        }
+    }
+    if file.limits.burst == Some(0) {
+        return Err("limits.burst 必须大于 0".to_string());
+    }
+    if file.workspace.ttl_secs == Some(0) || file.workspace.max_bytes == Some(0) {
+        return Err("workspace.ttl_secs 与 workspace.max_bytes 必须大于 0".to_string());
+    }
+    let known: Vec<String> = tools::all_tools().into_iter().map(|t| t.name).collect();
+    for name in file.concurrency.tools.keys() {
+        if !known.contains(name) && !name.starts_with("zw_preset_") {
+            return Err(format!("concurrency.tools 中的未知工具: {}", name));
+        }
+    }
+    if file.concurrency.queue_timeout_ms == Some(0) {
+        return Err("concurrency.queue_timeout_ms 必须大于 0".to_string());
+    }
+    if file.filesystem.max_file_bytes == Some(0) {
+        return Err("filesystem.max_file_bytes 必须大于 0".to_string());
+    }
+    if file.network.url_timeout_ms == Some(0) {
+        return Err("network.url_timeout_ms 必须大于 0".to_string());
+    }
+    let mut filesystem = file.filesystem.clone();
+    for root in filesystem.allowed_roots.iter_mut() {
+        *root = root
+            .canonicalize()
+            .map_err(|e| format!("filesystem.allowed_roots 无法解析: {} ({})", root.display(), e))?;
+    }
+    let defaults = engine::Scoring::default();
+    let mut patterns = Vec::new();
+    for p in &file.flag_patterns {
+        patterns.push(regex::Regex::new(p).map_err(|e| format!("flag 正则无效 {}: {}", p, e))?);
+    }
+    let scoring = engine::Scoring {
+        printable_weight: file.scoring.printable_weight.unwrap_or(defaults.printable_weight),
+        alnum_weight: file.scoring.alnum_weight.unwrap_or(defaults.alnum_weight),
+        space_bonus: file.scoring.space_bonus.unwrap_or(defaults.space_bonus),
+        flag_bonus: file.scoring.flag_bonus.unwrap_or(defaults.flag_bonus),
+        keyboard_bonus: file.scoring.keyboard_bonus.unwrap_or(defaults.keyboard_bonus),
+        numeric_bonus: file.scoring.numeric_bonus.unwrap_or(defaults.numeric_bonus),
+        unprintable_penalty: file.scoring.unprintable_penalty.unwrap_or(defaults.unprintable_penalty),
+        flag_patterns: patterns,
+    };
+    let mut presets = Vec::new();
+    for entry in &file.presets {
+        let mut charset = Vec::new();
+        for c in &entry.chars {
+            let ch = chars::parse_codepoint(c)
+                .and_then(char::from_u32)
+                .ok_or_else(|| format!("预设 {} 的字符无法解析: {}", entry.key, c))?;
+            charset.push(ch);
+        }
+        let terminator = match &entry.terminator {
+            None => None,
+            Some(t) => Some(
+                chars::parse_codepoint(t)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("预设 {} 的 terminator 无法解析: {}", entry.key, t))?,
+            ),
+        };
+        presets.push((
+            entry.key.clone(),
+            engine::Preset {
+                name: entry.name.clone().unwrap_or_else(|| entry.key.clone()),
+                chars: charset,
+                description: entry.description.clone().unwrap_or_else(|| "配置文件预设".to_string()),
+                group_size: entry.group_size.filter(|&g| g > 0),
+                digit_order: entry.digit_order,
+                terminator,
+                reference: entry.reference.clone(),
+            },
+        ));
+    }
+
+    let names = chars::category_names();
+    let mut allowed_categories = Vec::new();
+    for name in &file.check.allowed_categories {
+        let cat = names
+            .iter()
+            .find(|n| n.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("check.allowed_categories 中的未知分类: {}（可用: {}）", name, names.join("、")))?;
+        allowed_categories.push(*cat);
+    }
+    let mut allowed_codepoints = Vec::new();
+    for c in &file.check.allowed_codepoints {
+        allowed_codepoints.push(chars::parse_codepoint(c).ok_or_else(|| format!("check.allowed_codepoints 无法解析: {}", c))?);
+    }
+    let policy = check::Policy {
+        allowed_categories,
+        allowed_codepoints,
+        allow_bom: file.check.allow_bom,
+        allow_legit_zwnj: file.check.allow_legit_zwnj,
+    };
+    let commands = file
+        .postprocess
+        .commands
+        .iter()
+        .map(|c| postprocess::CommandSpec {
+            name: c.name.clone(),
+            argv: c.command.clone(),
+            timeout: std::time::Duration::from_millis(c.timeout_ms.unwrap_or(5000).max(1)),
+        })
+        .collect();
+    let postprocess_settings =
+        postprocess::build(file.postprocess.order.as_deref(), file.postprocess.max_depth, commands)?;
+    let postprocess_summary = format!(
+        "postprocess={} (max_depth={})",
+        postprocess_settings.names().join(" → "),
+        postprocess_settings.max_depth()
+    );
+
+    // 应用
+    *CHECK_POLICY
+        .get_or_init(|| RwLock::new(check::Policy::default()))
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = policy;
+    *LIMITS
+        .get_or_init(|| RwLock::new(Limits::default()))
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = file.limits.clone();
+    *WORKSPACE
+        .get_or_init(|| RwLock::new(Workspace::default()))
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = file.workspace.clone();
+    *CONCURRENCY
+        .get_or_init(|| RwLock::new(Concurrency::default()))
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = file.concurrency.clone();
+    *FILESYSTEM
+        .get_or_init(|| RwLock::new(Filesystem::default()))
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = filesystem.clone();
+    *NETWORK
+        .get_or_init(|| RwLock::new(Network::default()))
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = file.network.clone();
+    engine::set_scoring(scoring);
+    postprocess::configure(postprocess_settings);
+
+    let mut loaded = CONFIG_PRESETS.lock().unwrap_or_else(|e| e.into_inner());
+    let before = engine::custom_preset_keys();
+    for key in loaded.drain(..) {
+        engine::remove_preset(&key);
+    }
+    let mut errors = Vec::new();
+    for (key, preset) in presets {
+        match engine::add_preset(&key, preset) {
+            Ok(_) => loaded.push(key),
+            Err(e) => errors.push(e),
+        }
+    }
+    if engine::custom_preset_keys() != before {
+        server::notify_tools_changed();
+    }
+
+    let mut summary = format!(
+        "已加载配置 {}: {} 个预设, {} 个 flag 格式",
+        path.display(),
+        loaded.len(),
+        file.flag_patterns.len()
+    );
+    if let Some(n) = file.limits.max_output_chars {
+        summary.push_str(&format!(", max_output_chars={}", n));
+    }
+    if let Some(ms) = file.limits.timeout_ms {
+        summary.push_str(&format!(", timeout_ms={}", ms));
+    }
+    if let Some(n) = file.limits.max_request_bytes {
+        summary.push_str(&format!(", max_request_bytes={}", n));
+    }
+    if let Some(rate) = file.limits.requests_per_second {
+        summary.push_str(&format!(", requests_per_second={}", rate));
+    }
+    if let Some(n) = file.limits.burst {
+        summary.push_str(&format!(", burst={}", n));
+    }
+    if let Some(s) = file.limits.session_cpu_seconds {
+        summary.push_str(&format!(", session_cpu_seconds={}", s));
+    }
+    if let Some(n) = file.limits.session_bytes_read {
+        summary.push_str(&format!(", session_bytes_read={}", n));
+    }
+    if let Some(root) = &file.workspace.root {
+        summary.push_str(&format!(", workspace.root={}", root.display()));
+    }
+    if !filesystem.allowed_roots.is_empty() {
+        let roots: Vec<String> = filesystem.allowed_roots.iter().map(|r| r.display().to_string()).collect();
+        summary.push_str(&format!(", filesystem.allowed_roots={}", roots.join(";")));
+    }
+    if let Some(n) = filesystem.max_file_bytes {
+        summary.push_str(&format!(", filesystem.max_file_bytes={}", n));
+    }
+    if file.network.allow_urls == Some(false) {
+        summary.push_str(", network.allow_urls=false");
+    }
+    if let Some(ms) = file.network.url_timeout_ms {
+        summary.push_str(&format!(", network.url_timeout_ms={}", ms));
+    }
+    summary.push_str(&format!(", {}", postprocess_summary));
+    for e in errors {
+        summary.push_str(&format!("\n⚠ 预设未加载: {}", e));
+    }
+    Ok(summary)
+}
+
+/// 收到 SIGHUP 时重载配置
+#[cfg(unix)]
+pub fn spawn_sighup_reloader() {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!("Failed to install SIGHUP handler: {}", e);
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            match tokio::task::spawn_blocking(reload).await {
+                Ok(Ok(summary)) => tracing::info!("SIGHUP: {}", summary),
+                Ok(Err(e)) => tracing::error!("SIGHUP reload failed, keeping previous config: {}", e),
+                Err(e) => tracing::error!("SIGHUP reload panicked: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn spawn_sighup_reloader() {}
@@ -0,0 +1,102 @@
+//! 文件哈希记录 - 在工具响应中附上读取的输入文件与写出文件的 SHA-256，便于取证时留存证据链
+//!
+//! 按请求开启（`report_hashes` 参数）：开启后读写文件的位置把路径与内容哈希记到线程局部表中，
+//! 工具执行完毕后统一追加到响应末尾。未开启时记录函数直接返回，不做额外的哈希计算。
+
+use std::cell::{Cell, RefCell};
+
+use sha2::{Digest, Sha256};
+
+/// 文件方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Input,
+    Output,
+}
+
+/// 一条哈希记录
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub direction: Direction,
+    /// 调用方给出的路径或工作区 URI
+    pub location: String,
+    pub size: usize,
+    pub sha256: String,
+}
+
+thread_local! {
+    /// 当前请求是否记录文件哈希；请求在单个阻塞线程中执行
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static ENTRIES: RefCell<Vec<Entry>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 在指定模式下执行 `f`，返回结果与期间记录的哈希，结束后恢复原模式
+pub fn with_hashes<T>(enabled: bool, f: impl FnOnce() -> T) -> (T, Vec<Entry>) {
+    let previous = ENABLED.with(|e| e.replace(enabled));
+    let saved = ENTRIES.with(|e| e.take());
+    let out = f();
+    ENABLED.with(|e| e.set(previous));
+    let entries = ENTRIES.with(|e| e.replace(saved));
+    (out, entries)
+}
+
+/// 当前线程是否记录文件哈希；请求内另开的工作线程用它沿用同一模式
+pub fn enabled() -> bool {
+    ENABLED.with(|e| e.get())
+}
+
+fn record(direction: Direction, location: &str, bytes: &[u8]) {
+    if !enabled() {
+        return;
+    }
+    let sha256 = format!("{:x}", Sha256::digest(bytes));
+    push(Entry { direction, location: location.to_string(), size: bytes.len(), sha256 });
+}
+
+fn push(entry: Entry) {
+    ENTRIES.with(|e| {
+        let mut entries = e.borrow_mut();
+        // 同一文件被读取多次只记一次；写出的文件以最后一次内容为准
+        let same = |x: &Entry| x.direction == entry.direction && x.location == entry.location;
+        if entry.direction == Direction::Output {
+            entries.retain(|x| !same(x));
+        } else if entries.iter().any(|x| same(x) && x.sha256 == entry.sha256) {
+            return;
+        }
+        entries.push(entry);
+    });
+}
+
+/// 把工作线程中（经 `with_hashes`）记录的哈希并入当前线程
+pub fn merge(entries: Vec<Entry>) {
+    if enabled() {
+        entries.into_iter().for_each(push);
+    }
+}
+
+/// 记录读取的输入文件
+pub fn record_input(location: &str, bytes: &[u8]) {
+    record(Direction::Input, location, bytes);
+}
+
+/// 记录写出的文件
+pub fn record_output(location: &str, bytes: &[u8]) {
+    record(Direction::Output, location, bytes);
+}
+
+/// 附加到响应末尾的哈希报告；没有读写文件时说明这一点
+pub fn report(entries: &[Entry]) -> String {
+    let mut out = String::from("\n\n文件哈希（SHA-256）:\n");
+    if entries.is_empty() {
+        out.push_str("  本次调用未读取或写出文件\n");
+        return out;
+    }
+    for e in entries {
+        let label = match e.direction {
+            Direction::Input => "输入",
+            Direction::Output => "输出",
+        };
+        out.push_str(&format!("  [{}] {}  {}（{} 字节）\n", label, e.sha256, e.location, e.size));
+    }
+    out
+}
@@ -0,0 +1,153 @@
+//! 错误分类 - 工具失败与协议层拒绝的机器可读错误码
+//!
+//! 失败的工具结果（`isError: true`）都在 `structuredContent.code` 中给出错误码，协议层的拒绝
+//! （请求过大、限流、配额、繁忙、超时）在 JSON-RPC 错误的 `data.code` 中给出同一套错误码，
+//! 客户端自动化据此分支，不必解析错误文本。错误码只增不改；`reason` 等已有字段保留，作为更细的原因。
+
+use serde_json::{json, Value};
+
+/// 错误码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    InputMissing,
+    InvalidArgument,
+    UnsupportedMethod,
+    UnknownTool,
+    NotFound,
+    FileTooLarge,
+    PathForbidden,
+    UrlDisabled,
+    IoError,
+    DecodeFailed,
+    DecodeBudgetExceeded,
+    ToolTimeout,
+    JobRunning,
+    CheckFailed,
+    RequestTooLarge,
+    RateLimited,
+    QuotaExceeded,
+    ServerBusy,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn all() -> &'static [ErrorCode] {
+        use ErrorCode::*;
+        &[
+            InputMissing,
+            InvalidArgument,
+            UnsupportedMethod,
+            UnknownTool,
+            NotFound,
+            FileTooLarge,
+            PathForbidden,
+            UrlDisabled,
+            IoError,
+            DecodeFailed,
+            DecodeBudgetExceeded,
+            ToolTimeout,
+            JobRunning,
+            CheckFailed,
+            RequestTooLarge,
+            RateLimited,
+            QuotaExceeded,
+            ServerBusy,
+            Internal,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::InputMissing => "INPUT_MISSING",
+            ErrorCode::InvalidArgument => "INVALID_ARGUMENT",
+            ErrorCode::UnsupportedMethod => "UNSUPPORTED_METHOD",
+            ErrorCode::UnknownTool => "UNKNOWN_TOOL",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::FileTooLarge => "FILE_TOO_LARGE",
+            ErrorCode::PathForbidden => "PATH_FORBIDDEN",
+            ErrorCode::UrlDisabled => "URL_DISABLED",
+            ErrorCode::IoError => "IO_ERROR",
+            ErrorCode::DecodeFailed => "DECODE_FAILED",
+            ErrorCode::DecodeBudgetExceeded => "DECODE_BUDGET_EXCEEDED",
+            ErrorCode::ToolTimeout => "TOOL_TIMEOUT",
+            ErrorCode::JobRunning => "JOB_RUNNING",
+            ErrorCode::CheckFailed => "CHECK_FAILED",
+            ErrorCode::RequestTooLarge => "REQUEST_TOO_LARGE",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::QuotaExceeded => "QUOTA_EXCEEDED",
+            ErrorCode::ServerBusy => "SERVER_BUSY",
+            ErrorCode::Internal => "INTERNAL",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ErrorCode::InputMissing => "缺少必需的输入或参数",
+            ErrorCode::InvalidArgument => "参数取值无效",
+            ErrorCode::UnsupportedMethod => "未知或不支持的编码/解码方法",
+            ErrorCode::UnknownTool => "未知工具",
+            ErrorCode::NotFound => "文件、目录、会话、候选等不存在",
+            ErrorCode::FileTooLarge => "输入超过大小上限",
+            ErrorCode::PathForbidden => "路径访问被拒绝（符号链接逃逸、不在允许的根目录内、特殊文件）",
+            ErrorCode::UrlDisabled => "配置中禁用了 url 输入",
+            ErrorCode::IoError => "读写文件或下载失败",
+            ErrorCode::DecodeFailed => "输入有效，但无法按要求解码",
+            ErrorCode::DecodeBudgetExceeded => "解码在时间上限内没有找到任何候选",
+            ErrorCode::ToolTimeout => "工具执行超过服务端硬超时",
+            ErrorCode::JobRunning => "后台任务仍在运行，稍后再取结果",
+            ErrorCode::CheckFailed => "检查未通过（fail_on_findings、基准测试回归等）",
+            ErrorCode::RequestTooLarge => "单行请求超过大小上限",
+            ErrorCode::RateLimited => "请求过于频繁",
+            ErrorCode::QuotaExceeded => "会话的执行时间或读取字节配额已用尽",
+            ErrorCode::ServerBusy => "并发许可与排队都已用尽",
+            ErrorCode::Internal => "服务端内部错误",
+        }
+    }
+
+    /// 客户端是否值得原样重试（等待后）
+    pub fn retryable(&self) -> bool {
+        matches!(self, ErrorCode::RateLimited | ErrorCode::ServerBusy | ErrorCode::ToolTimeout | ErrorCode::JobRunning)
+    }
+
+    /// 由访问检查等处给出的细分原因（`reason` 字段）归类
+    pub fn from_reason(reason: &str) -> Self {
+        match reason {
+            "file_too_large" | "input_too_large" => ErrorCode::FileTooLarge,
+            "symlink_escape" | "outside_roots" | "special_file" => ErrorCode::PathForbidden,
+            "url_disabled" => ErrorCode::UrlDisabled,
+            "rate_limited" => ErrorCode::RateLimited,
+            "cpu_quota" | "bytes_quota" => ErrorCode::QuotaExceeded,
+            "server_busy" => ErrorCode::ServerBusy,
+            _ => ErrorCode::Internal,
+        }
+    }
+}
+
+/// 在机器可读的错误数据中加上错误码与是否可重试；`data` 不是对象时包一层 `detail`
+pub fn with_code(code: ErrorCode, data: Value) -> Value {
+    let mut obj = match data {
+        Value::Object(map) => map,
+        Value::Null => serde_json::Map::new(),
+        other => [("detail".to_string(), other)].into_iter().collect(),
+    };
+    obj.insert("code".to_string(), json!(code.as_str()));
+    obj.insert("retryable".to_string(), json!(code.retryable()));
+    Value::Object(obj)
+}
+
+/// 错误码一览（Markdown），即 `zw://help/errors` 资源
+pub fn table() -> String {
+    let mut out = String::from("# 错误码\n\n");
+    out.push_str("失败的工具结果在 `structuredContent` 中给出 `code` 与 `retryable`，协议层拒绝在 JSON-RPC 错误的 `data` 中给出；");
+    out.push_str("部分错误另带 `reason`、`path`、`retry_after_ms` 等细节字段。\n\n");
+    out.push_str("| 错误码 | 含义 | 可重试 |\n|--------|------|--------|\n");
+    for code in ErrorCode::all() {
+        out.push_str(&format!(
+            "| `{}` | {} | {} |\n",
+            code.as_str(),
+            code.description(),
+            if code.retryable() { "是" } else { "否" }
+        ));
+    }
+    out
+}
@@ -0,0 +1,193 @@
+//! 部署自检 - 预设编解码往返、字符表完整性、文件读写权限，以及版本、功能与限制
+//!
+//! 运维人员可从任意 MCP 客户端调用 `zw_health` 确认部署是否正常。
+
+use std::fs::OpenOptions;
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::{audit, concurrency, config, paths, server, tools, workspace};
+use crate::zw_core::{chars, engine, postprocess, stegcloak};
+
+/// 往返自检使用的消息
+const PROBE_MESSAGE: &str = "flag{health_check}";
+
+/// 单项检查结果
+struct Check {
+    name: String,
+    ok: bool,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, ok: bool, detail: impl Into<String>) -> Self {
+        Self { name: name.into(), ok, detail: detail.into() }
+    }
+}
+
+/// 每个预设编码后再按该预设解码，检查能否还原
+fn preset_round_trips() -> Vec<Check> {
+    engine::encoding_presets()
+        .into_iter()
+        .map(|(key, preset)| {
+            let encoded = match key.as_str() {
+                "steganographr" => engine::encode_steganographr(PROBE_MESSAGE, ""),
+                "stegcloak" => stegcloak::payload(PROBE_MESSAGE, &stegcloak::Options::default()),
+                _ => engine::encode_preset(PROBE_MESSAGE, &preset),
+            };
+            let results = tools::run_decode(&encoded, &key, &engine::Budget::normal());
+            let name = format!("预设往返 {}", key);
+            match results.iter().position(|r| r.decoded == PROBE_MESSAGE) {
+                Some(0) => Check::new(name, true, "还原为最佳结果"),
+                Some(i) => Check::new(name, true, format!("已还原，但排在第 {} 位", i + 1)),
+                None => Check::new(
+                    name,
+                    false,
+                    match results.first() {
+                        Some(r) => format!("未能还原，最佳结果为 {:?}", r.decoded),
+                        None => "未能还原，没有解码结果".to_string(),
+                    },
+                ),
+            }
+        })
+        .collect()
+}
+
+fn char_table() -> Check {
+    let problems = chars::table_problems();
+    if problems.is_empty() {
+        let count = chars::all_zero_width_chars().len();
+        Check::new("字符表完整性", true, format!("{} 个字符，{} 个分类", count, chars::category_names().len()))
+    } else {
+        Check::new("字符表完整性", false, problems.join("；"))
+    }
+}
+
+fn readable(name: &str, path: &Path) -> Check {
+    match std::fs::read(path) {
+        Ok(_) => Check::new(name, true, format!("{} 可读", path.display())),
+        Err(e) => Check::new(name, false, format!("{} 无法读取: {}", path.display(), e)),
+    }
+}
+
+fn appendable(name: &str, path: &Path) -> Check {
+    match OpenOptions::new().append(true).open(path) {
+        Ok(_) => Check::new(name, true, format!("{} 可写", path.display())),
+        Err(e) => Check::new(name, false, format!("{} 无法写入: {}", path.display(), e)),
+    }
+}
+
+/// 在目录中创建并删除一个探测文件
+fn writable_dir(name: &str, dir: &Path) -> Check {
+    let probe = dir.join(format!(".zw-health-{}", std::process::id()));
+    let result = std::fs::write(&probe, b"ok").and_then(|_| std::fs::remove_file(&probe));
+    match result {
+        Ok(_) => Check::new(name, true, format!("{} 可写", dir.display())),
+        Err(e) => Check::new(name, false, format!("{} 无法写入: {}", dir.display(), e)),
+    }
+}
+
+/// 工作目录（相对 file_path 以此为准）、临时目录与工作区，以及配置文件与审计日志的读写权限
+fn file_permissions() -> Vec<Check> {
+    let mut checks = Vec::new();
+    match std::env::current_dir() {
+        Ok(cwd) => match std::fs::read_dir(&cwd) {
+            Ok(_) => checks.push(Check::new("工作目录", true, format!("{} 可读", cwd.display()))),
+            Err(e) => checks.push(Check::new("工作目录", false, format!("{} 无法列出: {}", cwd.display(), e))),
+        },
+        Err(e) => checks.push(Check::new("工作目录", false, format!("无法获取: {}", e))),
+    }
+    checks.push(writable_dir("临时目录", &std::env::temp_dir()));
+    let root = workspace::root();
+    checks.push(match std::fs::create_dir_all(&root) {
+        Ok(_) => writable_dir("临时工作区", &root),
+        Err(e) => Check::new("临时工作区", false, format!("{} 无法创建: {}", root.display(), e)),
+    });
+    if let Some(path) = config::config_path() {
+        checks.push(readable("配置文件", path));
+    }
+    if let Some(path) = audit::path() {
+        checks.push(appendable("审计日志", path));
+    }
+    checks
+}
+
+fn or_unlimited<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_else(|| "不限".to_string())
+}
+
+/// 运行全部自检并生成报告
+pub fn report() -> String {
+    let mut checks = vec![char_table()];
+    checks.extend(preset_round_trips());
+    checks.extend(file_permissions());
+    let failed = checks.iter().filter(|c| !c.ok).count();
+
+    let mut out = format!("zw-mcp-server {}\n", env!("CARGO_PKG_VERSION"));
+    out.push_str(&if failed == 0 {
+        format!("总体状态: ✓ 正常（{} 项检查全部通过）\n", checks.len())
+    } else {
+        format!("总体状态: ✗ {} / {} 项检查未通过\n", failed, checks.len())
+    });
+
+    out.push_str("\n自检:\n");
+    for c in &checks {
+        out.push_str(&format!("  {} {}: {}\n", if c.ok { "✓" } else { "✗" }, c.name, c.detail));
+    }
+
+    out.push_str("\n功能:\n");
+    out.push_str(&format!(
+        "  配置文件: {}\n",
+        config::config_path().map(|p| p.display().to_string()).unwrap_or_else(|| "未加载".to_string())
+    ));
+    out.push_str(&format!("  管理工具 (zw_reload_config): {}\n", if config::admin_tools_enabled() { "已开放" } else { "未开放" }));
+    out.push_str(&format!(
+        "  审计日志: {}\n",
+        audit::path().map(|p| p.display().to_string()).unwrap_or_else(|| "未启用".to_string())
+    ));
+    let custom = engine::custom_preset_keys();
+    out.push_str(&format!("  预设: {} 个（其中自定义 {} 个）\n", engine::encoding_presets().len(), custom.len()));
+    out.push_str(&format!("  工具: {} 个\n", tools::all_tools().len()));
+    out.push_str(&format!("  临时工作区: {}\n", workspace::root().display()));
+    out.push_str(&format!("  后处理链 (最多 {} 层):\n", postprocess::max_depth()));
+    for (name, description) in postprocess::processors() {
+        out.push_str(&format!("    {}: {}\n", name, description));
+    }
+
+    let limits = config::limits();
+    let max_output = tools::max_output_chars(&Value::Null);
+    out.push_str("\n限制:\n");
+    out.push_str(&format!(
+        "  单次响应最大字符数: {}\n",
+        if max_output == 0 { "不限".to_string() } else { max_output.to_string() }
+    ));
+    out.push_str(&format!("  解码时间上限: {} ms\n", tools::tool_timeout(&Value::Null).as_millis()));
+    out.push_str(&format!("  单行请求最大字节数: {}\n", server::max_request_bytes()));
+    out.push_str(&format!("  每秒请求数: {}\n", or_unlimited(limits.requests_per_second)));
+    out.push_str(&format!("  突发请求数: {}\n", or_unlimited(limits.burst)));
+    out.push_str(&format!("  会话执行时间配额: {}\n", or_unlimited(limits.session_cpu_seconds.map(|s| format!("{} 秒", s)))));
+    out.push_str(&format!("  会话读取字节配额: {}\n", or_unlimited(limits.session_bytes_read)));
+    let concurrency = config::concurrency();
+    let limited: Vec<String> = concurrency::limits().iter().map(|(t, n)| format!("{}={}", t, n)).collect();
+    out.push_str(&format!("  重型工具并发上限: {}\n", if limited.is_empty() { "不限".to_string() } else { limited.join(", ") }));
+    out.push_str(&format!(
+        "  排队上限: {} 个/工具，最长等待 {} ms\n",
+        concurrency.queue_depth.unwrap_or(concurrency::DEFAULT_QUEUE_DEPTH),
+        concurrency.queue_timeout_ms.unwrap_or(concurrency::DEFAULT_QUEUE_TIMEOUT.as_millis() as u64)
+    ));
+    let ws = config::workspace();
+    out.push_str(&format!("  工作区产物保留: {} 秒\n", ws.ttl_secs.unwrap_or(workspace::DEFAULT_TTL.as_secs())));
+    out.push_str(&format!("  工作区大小上限: {} 字节\n", ws.max_bytes.unwrap_or(workspace::DEFAULT_MAX_BYTES)));
+    let fs_limits = config::filesystem();
+    let roots: Vec<String> = fs_limits.allowed_roots.iter().map(|r| r.display().to_string()).collect();
+    out.push_str(&format!(
+        "  允许读写的根目录: {}\n",
+        if roots.is_empty() { "不限".to_string() } else { format!("{}（另含临时工作区）", roots.join(", ")) }
+    ));
+    out.push_str(&format!(
+        "  单个输入文件大小上限: {} 字节\n",
+        fs_limits.max_file_bytes.unwrap_or(paths::DEFAULT_MAX_FILE_BYTES)
+    ));
+    out
+}
@@ -0,0 +1,391 @@
+//! 工具帮助 - 每个工具的调用示例，以及 `zw://help/<tool>` 帮助资源
+//!
+//! 示例同时出现在 `tools/list` 的 `examples` 字段和帮助资源中。含零宽字符的样本用
+//! `text_base64` 传入（运行时由编码器生成），避免客户端剥离不可见字符导致示例失效。
+
+use serde_json::{json, Value};
+
+use super::errors;
+use super::protocol::{Tool, ToolExample};
+use super::tools;
+use crate::zw_core::chars::is_zero_width;
+use crate::zw_core::{engine, ingest};
+
+/// 帮助资源 URI 前缀
+pub const HELP_URI_PREFIX: &str = "zw://help/";
+
+/// 示例中使用的 flag
+const SAMPLE_FLAG: &str = "flag{demo}";
+
+/// 样本: "Hello" 与 " world" 之间藏有 8bit 二进制（ZWSP=0, ZWNJ=1）编码的 flag{demo}
+fn sample_base64() -> String {
+    let hidden = engine::encode_binary(SAMPLE_FLAG, '\u{200B}', '\u{200C}', 8);
+    ingest::encode_base64(format!("Hello{} world", hidden).as_bytes())
+}
+
+fn example(title: &str, arguments: Value, expected: &str) -> ToolExample {
+    ToolExample { title: title.to_string(), arguments, expected: expected.to_string() }
+}
+
+/// 工具的调用示例；没有示例的工具返回空列表
+pub fn examples(tool: &str) -> Vec<ToolExample> {
+    let sample = sample_base64();
+    match tool {
+        "zw_analyze" => vec![
+            example(
+                "统计疑似隐写文本中的零宽字符",
+                json!({ "text_base64": sample }),
+                "2 种零宽字符（U+200B、U+200C）共 80 个，基线对比判定异常，并附载体画像",
+            ),
+            example(
+                "分析文件并导出分布 CSV",
+                json!({ "file_path": "challenge.txt", "csv_path": "distribution.csv" }),
+                "同上，另把每个码点的次数与首末位置写入 distribution.csv",
+            ),
+            example(
+                "与上游干净版本对比，只看新增的不可见字符",
+                json!({ "file_path": "README.md", "reference_path": "README.upstream.md" }),
+                "排除原有的软连字符等字符后的统计，并说明排除了多少个",
+            ),
+        ],
+        "zw_decode" => vec![
+            example("自动尝试所有方案", json!({ "text_base64": sample }), "★ 最佳结果: flag{demo}"),
+            example(
+                "已知出题工具时直接指定预设，跳过暴力搜索",
+                json!({ "file_path": "challenge.txt", "method": "330k_default" }),
+                "只用 330k 默认字符集做 N 进制解码",
+            ),
+            example(
+                "显式给出码点→比特映射验证假设",
+                json!({ "text_base64": sample, "mapping": { "U+200B": 0, "U+200C": 1 }, "group_size": 8 }),
+                "按映射解出 flag{demo}",
+            ),
+            example(
+                "常规预算解不出时扩大搜索",
+                json!({ "file_path": "challenge.txt", "budget": "exhaustive", "timeout_ms": 60000 }),
+                "尝试更多字符对、进制与比特偏移，超时返回已找到的最佳候选",
+            ),
+            example(
+                "载荷本身是压缩/加密数据",
+                json!({ "file_path": "challenge.txt", "allow_binary": true, "threshold": 0 }),
+                "保留不可打印的候选，并逐字节显示为 hex",
+            ),
+            example(
+                "排查为什么没解出来",
+                json!({ "file_path": "challenge.txt", "verbose": true }),
+                "末尾列出每次尝试的方案参数、耗时与拒绝原因（如“最高得分 10.0 未超过门槛 15.0”）",
+            ),
+            example(
+                "极短的莫尔斯码载荷（自动模式要求至少 4 个字母）",
+                json!({ "file_path": "challenge.txt", "method": "morse", "threshold": 0 }),
+                "只尝试莫尔斯码的点/划/分隔分配，结果如 SOS",
+            ),
+            example(
+                "解开加了口令的 StegCloak 载荷",
+                json!({ "file_path": "challenge.txt", "method": "stegcloak", "password": "hunter2" }),
+                "解压并解密出明文，方法标注 StegCloak (加密 + HMAC 校验)；口令错误时提示 HMAC 校验失败",
+            ),
+        ],
+        "zw_locate_flag" => vec![
+            example(
+                "直接找出 flag 及其位置",
+                json!({ "file_path": "challenge.txt" }),
+                "flag{demo}，以及所在行列与解码方法链",
+            ),
+            example(
+                "自定义 flag 格式",
+                json!({ "file_path": "challenge.txt", "flag_pattern": "CTF\\{[^}]+\\}" }),
+                "只报告匹配 CTF{...} 的结果",
+            ),
+            example(
+                "载荷以 &#x200B; 等实体写在网页源码里",
+                json!({ "file_path": "index.html" }),
+                "先还原实体与百分号编码再解码，并列出每种原始写法的位置",
+            ),
+        ],
+        "zw_explain" => vec![example(
+            "为最佳结果生成 write-up 说明",
+            json!({ "file_path": "challenge.txt", "rank": 1 }),
+            "映射表、分组大小与位序，以及第一个字符的推导过程",
+        )],
+        "zw_decode_detail" => vec![example(
+            "追问某个候选的细节",
+            json!({ "text_base64": sample, "id": "<zw_decode 输出中的 ID>" }),
+            "码点映射、原始比特流、参与解码的位置，以及逐字符对齐视图",
+        )],
+        "zw_encode" => vec![
+            example(
+                "把 flag 藏进载体文本",
+                json!({ "message": SAMPLE_FLAG, "method": "binary", "cover_text": "Hello world" }),
+                "返回嵌入了 80 个零宽字符的文本（repr 形式与原文）",
+            ),
+            example(
+                "写文件并以 base64 返回，防止客户端剥离零宽字符",
+                json!({ "message": SAMPLE_FLAG, "cover_file": "cover.txt", "output_path": "challenge.txt", "output_format": "base64" }),
+                "编码文本写入 challenge.txt，响应中只给出 base64",
+            ),
+            example(
+                "出必须能被 Steganographr 网页版解出的题",
+                json!({ "message": SAMPLE_FLAG, "emulate": "steganographr", "cover_text": "Hello world" }),
+                "按 Steganographr 源码重新实现的编码输出（未与原工具实际输出逐字节核对）",
+            ),
+            example(
+                "用莫尔斯码编码（只支持字母、数字与常用标点）",
+                json!({ "message": "meet at dawn", "method": "morse", "cover_text": "Hello world" }),
+                "U+200B 为点、U+200C 为划、U+2060 分隔字母，解码得到 MEET AT DAWN",
+            ),
+            example(
+                "用 StegCloak 方式加密隐藏",
+                json!({ "message": SAMPLE_FLAG, "method": "stegcloak", "password": "hunter2", "integrity": true, "cover_text": "Hello world" }),
+                "载荷插在第一个词之后；解码时需提供同一口令",
+            ),
+        ],
+        "zw_job_start" => vec![example(
+            "在后台扫描大目录",
+            json!({ "tool": "zw_summarize_scan", "arguments": { "dir_path": "dataset/" } }),
+            "已启动后台任务: zwj-1（zw_summarize_scan）",
+        )],
+        "zw_job_status" => vec![example("查看任务进度", json!({ "job_id": "zwj-1" }), "zwj-1  zw_summarize_scan  running  4210 ms")],
+        "zw_job_result" => vec![example(
+            "等待最多 10 秒后取回结果并删除任务",
+            json!({ "job_id": "zwj-1", "wait_ms": 10000, "remove": true }),
+            "与直接调用 zw_summarize_scan 相同的响应；仍在运行时返回 JOB_RUNNING",
+        )],
+        "zw_mass_encode" => vec![example(
+            "为检测器批量生成测试向量",
+            json!({
+                "defaults": { "cover_file": "cover.txt" },
+                "jobs": [
+                    { "message": SAMPLE_FLAG, "method": "binary", "output_path": "vectors/binary.txt" },
+                    { "message": SAMPLE_FLAG, "method": "330k", "output_path": "vectors/330k.txt" },
+                    { "message": SAMPLE_FLAG, "emulate": "steganographr" }
+                ]
+            }),
+            "汇总表：每个任务的方法、状态与输出位置（第 3 个写入临时工作区）",
+        )],
+        "zw_difficulty" => vec![example(
+            "出题后检查难度",
+            json!({ "text_base64": sample }),
+            "入门：zwsp_binary 预设可直接解出 flag{demo}，各分项得分与依据",
+        )],
+        "zw_summarize_scan" => vec![example(
+            "扫描整个附件目录，先看哪些文件最可疑",
+            json!({ "dir_path": "attachments", "top": 10 }),
+            "给出有发现的文件数、分类与方案统计，分诊列表中解码出 flag 的文件排在最前",
+        )],
+        "zw_annotate" => vec![example(
+            "为工单生成可审阅的标注副本",
+            json!({ "file_path": "suspicious.txt", "output_path": "suspicious.annotated.md" }),
+            "每个不可见字符换成 [^n] 脚注，附录说明码点、行列以及它是第几组的第几位、解出哪个字符",
+        )],
+        "zw_strip" => vec![example(
+            "清理零宽字符但保留 emoji 序列",
+            json!({ "file_path": "message.txt", "policy": "keep_emoji" }),
+            "删除后的文本与各码点删除次数",
+        )],
+        "zw_hexdump" => vec![example(
+            "查看零宽字符所在的原始字节",
+            json!({ "file_path": "challenge.txt", "context": 8 }),
+            "每个零宽区域前后 8 字节的 hex 视图",
+        )],
+        "zw_extract_file" => vec![example(
+            "把零宽层里藏的压缩包提取出来",
+            json!({ "file_path": "challenge.txt", "output_dir": "carved" }),
+            "识别出 ZIP 文件头，写出 carved/carved_1.zip 并给出 MD5/SHA256",
+        )],
+        "zw_entropy_visual" => vec![example(
+            "在大文件中定位载荷区域",
+            json!({ "file_path": "big.log", "chunk_size": 4096 }),
+            "不可见字符密度的 sparkline 与密度最高的几个区块",
+        )],
+        "zw_grep" => vec![example(
+            "在目录中查找含 BiDi 控制字符的文件",
+            json!({ "dir_path": "src", "category": "方向控制" }),
+            "命中的文件、行号与上下文",
+        )],
+        "zw_git_scan" => vec![example(
+            "检查最近的提交是否引入了不可见字符",
+            json!({ "repo_path": ".", "range": "HEAD~10..HEAD", "blame": true }),
+            "新增行中的不可见字符及引入它们的提交",
+        )],
+        "zw_check_name" => vec![example(
+            "检查注册名是否隐形或冒充",
+            json!({ "names": ["\u{3164}\u{3164}", "admin\u{200B}"], "existing": ["Admin"] }),
+            "第一个名字全由韩文填充符组成；第二个规范化后与 Admin 相同，判为冒充",
+        )],
+        "zw_blank_message" => vec![example(
+            "判断一条空白消息是恶作剧填充还是藏了信息",
+            json!({ "messages": ["\u{2800}\u{2800}\u{2800}", "\u{3164}"] }),
+            "第一条由盲文空白重复组成、第二条是单个韩文填充符，均判为仅填充并给出替换文本",
+        )],
+        "zw_reassemble" => vec![example(
+            "拼回拆进多份附件的 flag",
+            json!({ "file_paths": ["part_3.txt", "part_1.txt", "part_2.txt"] }),
+            "按文件名编号排序后拼接零宽字符流解码，并报告缺失的片段",
+        )],
+        "zw_simulate_channel" => vec![example(
+            "检查载荷能否经受 NFKC 与 HTML 清洗",
+            json!({ "text_base64": sample, "expected": SAMPLE_FLAG, "channels": ["nfkc", "html_strict"] }),
+            "每种处理后零宽字符的保留数量与能否仍解出 flag{demo}",
+        )],
+        "zw_recommend" => vec![example(
+            "为聊天平台上的 32 字节 flag 选方案",
+            json!({ "profile": "chat", "payload_bytes": 32, "cover_chars": 200 }),
+            "推荐的方法与字符集、预期幸存情况、所需不可见字符数与密度，以及各字符在每种处理下的保留情况",
+        )],
+        "zw_fuzz" => vec![
+            example(
+                "检查题目能否经受单个符号丢失或替换",
+                json!({ "file_path": "challenge.txt", "expected": SAMPLE_FLAG, "count": 50, "seed": 1 }),
+                "自动解码的恢复率、按改动类型的统计，以及每个变体的改动与首位解码",
+            ),
+            example(
+                "生成变体交给第三方解码器复测",
+                json!({ "file_path": "challenge.txt", "count": 100, "edits": 2, "check": false, "output_dir": "fuzz/" }),
+                "fuzz/fuzz_001.txt … fuzz_100.txt 与记录种子和每处改动的 fuzz_manifest.json",
+            ),
+        ],
+        "zw_presets_test" => vec![
+            example("按字符集覆盖率排查所有预设", json!({ "text_base64": sample }), "各预设的覆盖率排名"),
+            example(
+                "用某个预设试解",
+                json!({ "text_base64": sample, "preset": "zwsp_binary" }),
+                "覆盖率 100%，解出 flag{demo}，结论为很可能就是该工具",
+            ),
+        ],
+        "zw_health" => vec![example(
+            "确认部署是否正常",
+            json!({}),
+            "各项自检结果（预设往返、字符表、文件权限），以及版本、功能与生效的限制",
+        )],
+        "zw_add_preset" => vec![example(
+            "注册题目使用的自定义字符集",
+            json!({ "key": "my_tool", "chars": ["U+200B", "U+200C", "U+200D"], "group_size": 6 }),
+            "新增预设并注册专用解码工具 zw_decode_preset_my_tool",
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// 把不可见字符写成 JSON 转义，帮助文本中的示例参数复制后仍然有效
+fn escape_invisible(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    for ch in json.chars() {
+        if is_zero_width(ch) {
+            let mut units = [0u16; 2];
+            for unit in ch.encode_utf16(&mut units) {
+                out.push_str(&format!("\\u{:04x}", unit));
+            }
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+fn type_of(schema: &Value) -> String {
+    match schema.get("type").and_then(|t| t.as_str()) {
+        Some("array") => {
+            let item = schema.get("items").and_then(|i| i.get("type")).and_then(|t| t.as_str()).unwrap_or("any");
+            format!("{}[]", item)
+        }
+        Some(t) => t.to_string(),
+        None => "any".to_string(),
+    }
+}
+
+/// 渲染单个工具的 Markdown 帮助
+pub fn render(tool: &Tool) -> String {
+    let mut out = format!("# {}\n\n{}\n", tool.name, tool.description);
+    let required: Vec<&str> = tool
+        .input_schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+    if let Some(props) = tool.input_schema.get("properties").and_then(|p| p.as_object()) {
+        if !props.is_empty() {
+            out.push_str("\n## 参数\n\n| 参数 | 类型 | 必填 | 说明 |\n|------|------|------|------|\n");
+            for (name, schema) in props {
+                let mut desc = schema.get("description").and_then(|d| d.as_str()).unwrap_or("").to_string();
+                if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+                    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                    desc.push_str(&format!("（可选值: {}）", values.join(", ")));
+                }
+                if let Some(default) = schema.get("default") {
+                    desc.push_str(&format!("（默认 {}）", default));
+                }
+                out.push_str(&format!(
+                    "| `{}` | {} | {} | {} |\n",
+                    name,
+                    type_of(schema),
+                    if required.contains(&name.as_str()) { "是" } else { "否" },
+                    desc.replace('|', "\\|").replace('\n', " ")
+                ));
+            }
+        }
+    }
+    if tool.examples.is_empty() {
+        return out;
+    }
+    out.push_str("\n## 示例\n");
+    for (i, ex) in tool.examples.iter().enumerate() {
+        let args = serde_json::to_string_pretty(&ex.arguments).unwrap_or_default();
+        out.push_str(&format!(
+            "\n### {}. {}\n\n```json\n{}\n```\n\n预期: {}\n",
+            i + 1,
+            ex.title,
+            escape_invisible(&args),
+            ex.expected
+        ));
+    }
+    if tool.examples.iter().any(|ex| ex.arguments.get("text_base64").is_some()) {
+        out.push_str(&format!(
+            "\n示例中的 `text_base64` 是 \"Hello\" 与 \" world\" 之间藏有 8bit 二进制（ZWSP=0, ZWNJ=1）编码的 `{}` 的文本。\n",
+            SAMPLE_FLAG
+        ));
+    }
+    out
+}
+
+/// 错误码一览的帮助资源名（`zw://help/errors`）
+const ERRORS_HELP: &str = "errors";
+
+/// 所有工具的帮助资源条目，以及错误码一览
+pub fn resources() -> Vec<Value> {
+    let mut out: Vec<Value> = tools::all_tools()
+        .iter()
+        .map(|t| {
+            json!({
+                "uri": format!("{}{}", HELP_URI_PREFIX, t.name),
+                "name": format!("{} 使用说明", t.name),
+                "description": format!("{} 的参数说明与调用示例", t.name),
+                "mimeType": "text/markdown",
+            })
+        })
+        .collect();
+    out.push(json!({
+        "uri": format!("{}{}", HELP_URI_PREFIX, ERRORS_HELP),
+        "name": "错误码",
+        "description": "失败结果中 code 字段的取值与含义",
+        "mimeType": "text/markdown",
+    }));
+    out
+}
+
+/// 读取 `zw://help/<tool>`
+pub fn read(uri: &str) -> Result<Value, String> {
+    let name = uri.strip_prefix(HELP_URI_PREFIX).ok_or_else(|| format!("不是帮助资源: {}", uri))?;
+    if name == ERRORS_HELP {
+        return Ok(json!({
+            "contents": [{ "uri": uri, "mimeType": "text/markdown", "text": errors::table() }]
+        }));
+    }
+    let tool = tools::all_tools()
+        .into_iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| format!("未知工具: {}", name))?;
+    Ok(json!({
+        "contents": [{ "uri": uri, "mimeType": "text/markdown", "text": render(&tool) }]
+    }))
+}
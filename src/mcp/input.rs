@@ -0,0 +1,212 @@
+//! 输入来源 - 各工具统一读入待处理文本：`text`、`text_base64`、`file_path`、`url`，命令行另支持标准输入
+//!
+//! 同时给出多个来源时按 file_path → url → text_base64 → text 的顺序取第一个。所有来源共用同一个
+//! 大小上限（`[filesystem] max_file_bytes`）、同一套缺参/超限错误，读取的字节都计入会话配额与证据链哈希。
+
+use std::fs;
+use std::io::{self, Read};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use super::errors::ErrorCode;
+use super::protocol::ToolCallResult;
+use super::{config, custody, paths, quota, tools, workspace};
+use crate::zw_core::ingest;
+
+/// 缺少输入时的统一提示
+pub const MISSING: &str = "缺少参数: 请提供 text、text_base64、file_path 或 url";
+/// 默认的 url 请求超时，可由配置 network.url_timeout_ms 覆盖
+const DEFAULT_URL_TIMEOUT_MS: u64 = 15_000;
+/// 最多跟随的重定向次数
+const MAX_REDIRECTS: u32 = 5;
+
+/// 一个输入来源
+#[derive(Debug, Clone, Copy)]
+pub enum InputSource<'a> {
+    /// 本地文件或 zw-tmp:// 工作区 URI
+    File(&'a str),
+    /// http(s) 地址
+    Url(&'a str),
+    /// base64 编码的原始字节（避免客户端序列化/界面层剥离零宽字符）
+    Base64(&'a str),
+    /// 直接传入的文本
+    Text(&'a str),
+    /// 标准输入（仅命令行模式；MCP 模式下标准输入是协议通道）
+    Stdin,
+}
+
+impl<'a> InputSource<'a> {
+    /// 从工具参数中取输入来源；一个都没有时返回 None
+    pub fn from_args(args: &'a Value) -> Option<Self> {
+        let get = |key| args.get(key).and_then(|v| v.as_str());
+        get("file_path")
+            .map(InputSource::File)
+            .or_else(|| get("url").map(InputSource::Url))
+            .or_else(|| get("text_base64").map(InputSource::Base64))
+            .or_else(|| get("text").map(InputSource::Text))
+    }
+
+    /// 同 `from_args`，缺少输入时返回统一的错误
+    pub fn require(args: &'a Value) -> Result<Self, ToolCallResult> {
+        Self::from_args(args).ok_or_else(|| ToolCallResult::failure(ErrorCode::InputMissing, MISSING))
+    }
+
+    /// 命令行参数：`-` 为标准输入，http(s) 地址为 url，其余为文件路径
+    pub fn from_cli(arg: &'a str) -> Self {
+        if arg == "-" {
+            InputSource::Stdin
+        } else if is_url(arg) {
+            InputSource::Url(arg)
+        } else {
+            InputSource::File(arg)
+        }
+    }
+
+    /// 报告中显示的来源名
+    pub fn label(&self) -> &'a str {
+        match self {
+            InputSource::File(p) | InputSource::Url(p) => p,
+            InputSource::Base64(_) => "<text_base64>",
+            InputSource::Text(_) => "<text>",
+            InputSource::Stdin => "<stdin>",
+        }
+    }
+
+    /// 可用于按扩展名判断格式的路径（文件路径或 url）
+    pub fn path_hint(&self) -> Option<&'a str> {
+        match self {
+            InputSource::File(p) => Some(p),
+            InputSource::Url(u) => Some(u.split(['?', '#']).next().unwrap_or(u)),
+            _ => None,
+        }
+    }
+
+    /// 报告开头的来源行；直接传入的文本不显示
+    pub fn header(&self) -> String {
+        match self {
+            InputSource::File(p) => format!("文件: {}\n", p),
+            InputSource::Url(u) => format!("URL: {}\n", u),
+            _ => String::new(),
+        }
+    }
+
+    /// 读取原始字节
+    pub fn read_bytes(&self) -> Result<Vec<u8>, ToolCallResult> {
+        let raw = match *self {
+            InputSource::File(path) => return read_file_bytes(path),
+            InputSource::Url(url) => fetch_url(url)?,
+            InputSource::Base64(b64) => {
+                ingest::decode_base64(b64).map_err(|e| ToolCallResult::error(format!("text_base64 解码失败: {}", e)))?
+            }
+            InputSource::Text(t) => t.as_bytes().to_vec(),
+            InputSource::Stdin => read_limited(io::stdin().lock(), "<stdin>")?,
+        };
+        check_size(self.label(), raw.len() as u64)?;
+        Ok(raw)
+    }
+
+    /// 读取文本：直接传入的文本原样返回，其余自动检测编码（规则同 `tools::decode_bytes_auto`）
+    pub fn read_text(&self, lossy: bool) -> Result<String, ToolCallResult> {
+        if let InputSource::Text(t) = *self {
+            check_size("<text>", t.len() as u64)?;
+            return Ok(t.to_string());
+        }
+        self.read_bytes().map(|raw| tools::decode_bytes_auto(&raw, lossy))
+    }
+}
+
+/// 工具参数中的报告来源行（见 `InputSource::header`）
+pub fn header(args: &Value) -> String {
+    InputSource::from_args(args).map(|s| s.header()).unwrap_or_default()
+}
+
+pub fn is_url(s: &str) -> bool {
+    let lower = s.get(..8).unwrap_or(s).to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// 所有输入来源共用的大小上限
+pub fn max_input_bytes() -> u64 {
+    config::filesystem().max_file_bytes.unwrap_or(paths::DEFAULT_MAX_FILE_BYTES)
+}
+
+fn check_size(label: &str, bytes: u64) -> Result<(), ToolCallResult> {
+    let limit = max_input_bytes();
+    if bytes <= limit {
+        return Ok(());
+    }
+    let detail = format!("输入超过上限 {} 字节", limit);
+    Err(ToolCallResult::error_with_data(
+        ErrorCode::FileTooLarge,
+        format!("拒绝读取: {}（{}）", label, detail),
+        json!({ "reason": "input_too_large", "path": label, "detail": detail }),
+    ))
+}
+
+/// 读到上限多一个字节为止，超出即报错，避免无界读入
+fn read_limited(reader: impl Read, label: &str) -> Result<Vec<u8>, ToolCallResult> {
+    let mut raw = Vec::new();
+    reader
+        .take(max_input_bytes() + 1)
+        .read_to_end(&mut raw)
+        .map_err(|e| ToolCallResult::failure(ErrorCode::IoError, format!("读取 {} 失败: {}", label, e)))?;
+    check_size(label, raw.len() as u64)?;
+    Ok(raw)
+}
+
+/// 下载 url 的内容，计入会话读取配额
+fn fetch_url(url: &str) -> Result<Vec<u8>, ToolCallResult> {
+    let network = config::network();
+    if !network.allow_urls.unwrap_or(true) {
+        return Err(ToolCallResult::error_with_data(
+            ErrorCode::UrlDisabled,
+            format!("拒绝读取: {}（配置中已禁用 url 输入）", url),
+            json!({ "reason": "url_disabled", "path": url, "detail": "network.allow_urls = false" }),
+        ));
+    }
+    if !is_url(url) {
+        return Err(ToolCallResult::error(format!("url 只支持 http:// 与 https://: {}", url)));
+    }
+    let timeout = Duration::from_millis(network.url_timeout_ms.unwrap_or(DEFAULT_URL_TIMEOUT_MS));
+    let agent = ureq::AgentBuilder::new().timeout(timeout).redirects(MAX_REDIRECTS).build();
+    let response = agent.get(url).call().map_err(|e| match e {
+        ureq::Error::Status(code, r) => ToolCallResult::failure(ErrorCode::IoError, format!("获取 url 失败: HTTP {} {}", code, r.status_text())),
+        e => ToolCallResult::failure(ErrorCode::IoError, format!("获取 url 失败: {}", e)),
+    })?;
+    let raw = read_limited(response.into_reader(), url)?;
+    quota::record_read(raw.len());
+    custody::record_input(url, &raw);
+    Ok(raw)
+}
+
+/// 自动检测编码读取文件
+///
+/// `lossy` 为 true 时，非法 UTF-8 按字节级容错解码（非法序列替换为 U+FFFD），
+/// 不再降级猜测 UTF-16 / Latin-1，以免整体错解丢失零宽字符。
+pub fn read_file_auto(path_str: &str, lossy: bool) -> Result<String, ToolCallResult> {
+    read_file_bytes(path_str).map(|raw| tools::decode_bytes_auto(&raw, lossy))
+}
+
+/// 读取文件的原始字节（支持 zw-tmp:// 工作区 URI），计入会话读取配额
+pub fn read_file_bytes(path_str: &str) -> Result<Vec<u8>, ToolCallResult> {
+    let target = if path_str.starts_with(workspace::URI_PREFIX) {
+        let path = workspace::resolve(path_str).map_err(ToolCallResult::error)?;
+        paths::FsPath { display: path_str.to_string(), path, unc: false }
+    } else {
+        paths::resolve(path_str)
+    };
+    target.check_read().map_err(tools::denied)?;
+
+    // 先读取原始字节
+    let raw = match fs::read(&target.path) {
+        Ok(b) => b,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !target.unc => {
+            return Err(ToolCallResult::failure(ErrorCode::NotFound, format!("文件不存在: {}", target.describe(path_str))));
+        }
+        Err(e) => return Err(ToolCallResult::failure(ErrorCode::IoError, target.io_error("读取文件", path_str, &e))),
+    };
+    quota::record_read(raw.len());
+    custody::record_input(&target.display, &raw);
+    Ok(raw)
+}
@@ -0,0 +1,230 @@
+//! 后台任务 - 超过客户端单次调用超时的目录扫描与穷举解码
+//!
+//! `zw_job_start` 在后台线程中执行任意工具调用并立即返回任务 ID，之后用 `zw_job_status` 查询进度、
+//! `zw_job_result` 取回结果。任务在整个会话内保留（已结束的任务超过上限时从最早结束的开始淘汰）。
+//! 后台执行不受服务端硬超时限制，解码类工具未给出 `timeout_ms` 时默认放宽到 10 分钟；
+//! 执行耗时与读取字节在任务结束后计入会话配额，同时运行的任务数另有上限；
+//! 重型工具与直接调用共用并发许可，许可用尽时任务处于 `queued` 状态等待。
+
+use std::collections::BTreeMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use super::errors::ErrorCode;
+use super::protocol::ToolCallResult;
+use super::{audit, concurrency, quota, tools};
+
+/// 同时运行的任务数上限
+pub const MAX_RUNNING: usize = 4;
+/// 保留的任务总数上限（含已结束的）
+pub const MAX_JOBS: usize = 64;
+/// 后台任务默认的解码时间上限
+pub const DEFAULT_JOB_TIMEOUT_MS: u64 = 600_000;
+
+/// 任务状态
+#[derive(Debug)]
+enum State {
+    /// 等待重型工具的并发许可
+    Queued,
+    Running,
+    Finished { result: ToolCallResult, elapsed: Duration, finished: Instant },
+}
+
+#[derive(Debug)]
+struct Job {
+    tool: String,
+    started: Instant,
+    state: State,
+}
+
+impl Job {
+    fn elapsed(&self) -> Duration {
+        match &self.state {
+            State::Queued | State::Running => self.started.elapsed(),
+            State::Finished { elapsed, .. } => *elapsed,
+        }
+    }
+
+    fn state_name(&self) -> &'static str {
+        match &self.state {
+            State::Queued => "queued",
+            State::Running => "running",
+            State::Finished { result, .. } if result.is_error == Some(true) => "failed",
+            State::Finished { .. } => "succeeded",
+        }
+    }
+
+    fn summary(&self, id: &str) -> Value {
+        let mut summary = json!({
+            "job_id": id,
+            "tool": self.tool,
+            "state": self.state_name(),
+            "elapsed_ms": self.elapsed().as_millis() as u64,
+        });
+        if let State::Finished { result, .. } = &self.state {
+            summary["error_code"] = json!(result.error_code());
+        }
+        summary
+    }
+}
+
+static JOBS: OnceLock<Mutex<BTreeMap<u64, Job>>> = OnceLock::new();
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+/// 已结束、尚未计入会话配额的执行耗时与读取字节
+static UNCHARGED: Mutex<(Duration, u64)> = Mutex::new((Duration::ZERO, 0));
+
+fn jobs() -> std::sync::MutexGuard<'static, BTreeMap<u64, Job>> {
+    JOBS.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn job_key(id: &str) -> Option<u64> {
+    id.strip_prefix("zwj-")?.parse().ok()
+}
+
+/// 取出后台任务累计的执行耗时与读取字节，由服务端计入会话配额
+pub fn take_usage() -> (Duration, u64) {
+    std::mem::take(&mut *UNCHARGED.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// 在后台线程启动一次工具调用，返回任务 ID
+pub fn start(tool: &str, arguments: &Value) -> Result<String, ToolCallResult> {
+    if tool.starts_with("zw_job_") {
+        return Err(ToolCallResult::error(format!("不能在后台任务中调用 {}", tool)));
+    }
+    if !tools::all_tools().iter().any(|t| t.name == tool) {
+        return Err(ToolCallResult::failure(ErrorCode::UnknownTool, format!("未知工具: {}", tool)));
+    }
+    let mut args = match arguments {
+        Value::Object(map) => Value::Object(map.clone()),
+        Value::Null => json!({}),
+        _ => return Err(ToolCallResult::error("arguments 必须是对象")),
+    };
+    // 后台任务没有进度令牌，也不受服务端硬超时限制
+    if let Some(obj) = args.as_object_mut() {
+        obj.remove("_meta");
+        obj.entry("timeout_ms").or_insert(json!(DEFAULT_JOB_TIMEOUT_MS));
+    }
+
+    let key = {
+        let mut map = jobs();
+        let running = map.values().filter(|j| !matches!(j.state, State::Finished { .. })).count();
+        if running >= MAX_RUNNING {
+            return Err(ToolCallResult::failure(
+                ErrorCode::ServerBusy,
+                format!("已有 {} 个后台任务在运行（上限 {}），请等待其结束后再启动", running, MAX_RUNNING),
+            ));
+        }
+        if map.len() >= MAX_JOBS {
+            let oldest = map
+                .iter()
+                .filter_map(|(k, j)| match j.state {
+                    State::Finished { finished, .. } => Some((finished, *k)),
+                    State::Queued | State::Running => None,
+                })
+                .min()
+                .map(|(_, k)| k);
+            match oldest {
+                Some(k) => {
+                    map.remove(&k);
+                }
+                None => {
+                    return Err(ToolCallResult::failure(
+                        ErrorCode::ServerBusy,
+                        format!("后台任务数已达上限 ({})", MAX_JOBS),
+                    ))
+                }
+            }
+        }
+        let key = NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed);
+        map.insert(key, Job { tool: tool.to_string(), started: Instant::now(), state: State::Queued });
+        key
+    };
+
+    let tool = tool.to_string();
+    let spawned = std::thread::Builder::new().name(format!("zw-job-{}", key)).spawn(move || {
+        let _span = tracing::info_span!("job", id = key, tool = %tool).entered();
+        let permit = concurrency::acquire_blocking(&tool);
+        if let Some(job) = jobs().get_mut(&key) {
+            job.started = Instant::now();
+            job.state = State::Running;
+        }
+        quota::take_bytes_read();
+        let started = Instant::now();
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| tools::call_tool(&tool, &args)))
+            .unwrap_or_else(|_| ToolCallResult::failure(ErrorCode::Internal, "后台任务执行时发生内部错误"));
+        let elapsed = started.elapsed();
+        drop(permit);
+        let bytes = quota::take_bytes_read();
+        tracing::info!(elapsed_ms = elapsed.as_secs_f64() * 1000.0, is_error = result.is_error.unwrap_or(false), "Job finished");
+        audit::record(None, &tool, &args, &result, elapsed);
+        {
+            let mut usage = UNCHARGED.lock().unwrap_or_else(|e| e.into_inner());
+            usage.0 += elapsed;
+            usage.1 += bytes;
+        }
+        if let Some(job) = jobs().get_mut(&key) {
+            job.state = State::Finished { result, elapsed, finished: Instant::now() };
+        }
+    });
+    if let Err(e) = spawned {
+        jobs().remove(&key);
+        return Err(ToolCallResult::failure(ErrorCode::Internal, format!("无法启动后台线程: {}", e)));
+    }
+    Ok(format!("zwj-{}", key))
+}
+
+/// 单个任务或全部任务的状态
+pub fn status(id: Option<&str>) -> Result<Vec<Value>, ToolCallResult> {
+    let map = jobs();
+    match id {
+        None => Ok(map.iter().map(|(k, j)| j.summary(&format!("zwj-{}", k))).collect()),
+        Some(id) => job_key(id)
+            .and_then(|k| map.get(&k))
+            .map(|j| vec![j.summary(id)])
+            .ok_or_else(|| ToolCallResult::failure(ErrorCode::NotFound, format!("任务不存在: {}", id))),
+    }
+}
+
+/// 取回任务结果：最多等待 `wait`，仍未结束时返回 JOB_RUNNING；`remove` 时取回后删除任务
+pub fn result(id: &str, wait: Duration, remove: bool) -> ToolCallResult {
+    let not_found = || ToolCallResult::failure(ErrorCode::NotFound, format!("任务不存在: {}", id));
+    let Some(key) = job_key(id) else {
+        return not_found();
+    };
+    let deadline = Instant::now() + wait;
+    loop {
+        let mut map = jobs();
+        let Some(job) = map.get(&key) else {
+            return not_found();
+        };
+        if !matches!(job.state, State::Finished { .. }) {
+            if Instant::now() < deadline {
+                drop(map);
+                std::thread::sleep(Duration::from_millis(50));
+                continue;
+            }
+            let elapsed = job.elapsed().as_millis() as u64;
+            let state = job.state_name();
+            let doing = if state == "queued" { "等待并发许可" } else { "运行" };
+            return ToolCallResult::error_with_data(
+                ErrorCode::JobRunning,
+                format!("任务 {} 仍在{}（已{} {} ms），请稍后再取结果", id, doing, doing, elapsed),
+                json!({ "job_id": id, "state": state, "elapsed_ms": elapsed }),
+            );
+        }
+        if remove {
+            let Some(Job { state: State::Finished { result, .. }, .. }) = map.remove(&key) else {
+                unreachable!("已确认任务已结束");
+            };
+            return result;
+        }
+        let State::Finished { result, .. } = &job.state else {
+            unreachable!("已确认任务已结束");
+        };
+        return result.clone();
+    }
+}
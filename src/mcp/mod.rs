@@ -1,3 +1,16 @@
+pub mod audit;
+pub mod concurrency;
+pub mod config;
+pub mod custody;
+pub mod errors;
+pub mod health;
+pub mod help;
+pub mod input;
+pub mod jobs;
+pub mod paths;
 pub mod protocol;
+pub mod quota;
+pub mod resources;
 pub mod server;
 pub mod tools;
+pub mod workspace;
@@ -0,0 +1,266 @@
+//! 路径规范化 - Windows 下的 UNC 路径、驱动器相对路径与超长路径
+//!
+//! Windows 上分析人员常直接粘贴资源管理器中的路径：可能带引号、混用 `/`、是 `C:foo` 这样的驱动器
+//! 相对路径，或是网络共享上超过 260 字符（MAX_PATH）的深层路径。这里统一转成绝对路径，超长时加上
+//! 扩展长度前缀（`\\?\C:\...`、`\\?\UNC\server\share\...`），报告时再去掉前缀显示规范化后的路径。
+//! 其他平台上路径原样使用。
+//!
+//! 读写之前另做访问检查（服务端可能被不完全可信的调用方通过目录扫描或网络传输访问）：配置了
+//! `[filesystem] allowed_roots` 时，解析符号链接后的真实路径必须位于某个根目录或临时工作区内；
+//! FIFO、设备等特殊文件一律拒绝（读取可能永久阻塞或产生无穷数据）；输入文件不能超过
+//! `max_file_bytes`。拒绝时返回带 `reason` 的结构化错误。
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use super::errors::ErrorCode;
+use super::{config, workspace};
+
+/// 不加扩展长度前缀时 Windows API 能处理的最大路径长度（MAX_PATH 减去结尾的 NUL）
+const MAX_PATH: usize = 259;
+const VERBATIM_PREFIX: &str = r"\\?\";
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+/// 解析悬空符号链接时最多跟随的层数（与 Linux 的 ELOOP 上限相同）
+const MAX_SYMLINK_HOPS: usize = 40;
+/// 单个输入文件默认的大小上限
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// 规范化后的文件路径
+#[derive(Debug, Clone)]
+pub struct FsPath {
+    /// 实际用于文件操作的路径（Windows 超长路径带扩展长度前缀）
+    pub path: PathBuf,
+    /// 报告给用户的规范化路径（不带扩展长度前缀）
+    pub display: String,
+    /// 是否为网络共享路径
+    pub unc: bool,
+}
+
+/// 规范化用户给出的路径
+pub fn resolve(input: &str) -> FsPath {
+    if !cfg!(windows) {
+        return FsPath { path: PathBuf::from(input), display: input.to_string(), unc: false };
+    }
+    let cleaned = clean_windows(input);
+    let absolute = if cleaned.starts_with(VERBATIM_PREFIX) || cleaned.starts_with(r"\\.\") {
+        cleaned
+    } else {
+        // std::path::absolute 在 Windows 上按 GetFullPathNameW 处理驱动器相对路径与 `..`
+        std::path::absolute(Path::new(&cleaned)).map(|p| p.to_string_lossy().into_owned()).unwrap_or(cleaned)
+    };
+    let display = strip_verbatim(&absolute);
+    let unc = display.starts_with(r"\\");
+    let path = if absolute.chars().count() > MAX_PATH { to_verbatim(&absolute) } else { absolute };
+    FsPath { path: PathBuf::from(path), display, unc }
+}
+
+/// 去掉首尾空白与成对引号，统一为反斜杠
+fn clean_windows(input: &str) -> String {
+    let trimmed = input.trim();
+    let unquoted = trimmed
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(trimmed);
+    unquoted.replace('/', r"\")
+}
+
+/// 绝对路径加上扩展长度前缀
+fn to_verbatim(absolute: &str) -> String {
+    if absolute.starts_with(VERBATIM_PREFIX) || absolute.starts_with(r"\\.\") {
+        absolute.to_string()
+    } else if let Some(share) = absolute.strip_prefix(r"\\") {
+        format!("{}{}", VERBATIM_UNC_PREFIX, share)
+    } else {
+        format!("{}{}", VERBATIM_PREFIX, absolute)
+    }
+}
+
+/// 报告用：去掉扩展长度前缀
+fn strip_verbatim(path: &str) -> String {
+    if let Some(share) = path.strip_prefix(VERBATIM_UNC_PREFIX) {
+        format!(r"\\{}", share)
+    } else {
+        path.strip_prefix(VERBATIM_PREFIX).unwrap_or(path).to_string()
+    }
+}
+
+impl FsPath {
+    /// 用于错误信息的路径说明：规范化后与输入不同时两者都给出
+    pub fn describe(&self, input: &str) -> String {
+        if self.display == input {
+            input.to_string()
+        } else {
+            format!("{}（规范化为 {}）", input, self.display)
+        }
+    }
+
+    /// 文件操作失败的说明；网络共享上的失败附带排查提示
+    pub fn io_error(&self, action: &str, input: &str, err: &io::Error) -> String {
+        let mut msg = format!("{}失败: {} ({})", action, self.describe(input), err);
+        if self.unc && matches!(err.kind(), io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied) {
+            msg.push_str("\n提示: 这是网络共享路径，请确认共享已连接（可先在资源管理器中打开或 net use），且运行服务端的账户有访问权限");
+        }
+        msg
+    }
+}
+
+/// 拒绝访问的说明
+#[derive(Debug, Clone)]
+pub struct Denied {
+    /// 机器可读的原因：`symlink_escape`、`outside_roots`、`special_file`、`file_too_large`
+    pub reason: &'static str,
+    pub path: String,
+    pub detail: String,
+}
+
+impl Denied {
+    pub fn message(&self) -> String {
+        format!("拒绝访问: {}（{}）", self.path, self.detail)
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::from_reason(self.reason)
+    }
+
+    /// 工具结果的 structuredContent，供客户端程序化处理
+    pub fn data(&self) -> Value {
+        json!({ "reason": self.reason, "path": self.path, "detail": self.detail })
+    }
+}
+
+/// 特殊文件的类型名；普通文件与目录返回 None
+fn special_kind(file_type: &fs::FileType) -> Option<&'static str> {
+    if file_type.is_file() || file_type.is_dir() || file_type.is_symlink() {
+        return None;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_fifo() {
+            return Some("命名管道 (FIFO)");
+        }
+        if file_type.is_char_device() {
+            return Some("字符设备");
+        }
+        if file_type.is_block_device() {
+            return Some("块设备");
+        }
+        if file_type.is_socket() {
+            return Some("套接字");
+        }
+    }
+    Some("特殊文件")
+}
+
+/// 不访问文件系统的绝对路径（按字面处理 `.` 与 `..`），用于判断是否经符号链接逃逸
+fn lexical(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut out = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// 真实路径是否位于允许的根目录内；未配置根目录时总是允许
+fn within_roots(real: &Path, roots: &[PathBuf]) -> bool {
+    if roots.is_empty() {
+        return true;
+    }
+    let workspace = workspace::root();
+    let workspace = workspace.canonicalize().unwrap_or(workspace);
+    roots.iter().chain(std::iter::once(&workspace)).any(|root| real.starts_with(root))
+}
+
+impl FsPath {
+    fn deny(&self, reason: &'static str, detail: String) -> Denied {
+        Denied { reason, path: self.display.clone(), detail }
+    }
+
+    /// 检查真实路径是否在允许的根目录内；`real` 为解析符号链接后的路径
+    fn check_roots(&self, real: &Path, roots: &[PathBuf]) -> Result<(), Denied> {
+        if within_roots(real, roots) {
+            return Ok(());
+        }
+        let (reason, what) = if within_roots(&lexical(&self.path), roots) {
+            ("symlink_escape", "经符号链接指向允许的根目录之外")
+        } else {
+            ("outside_roots", "不在允许的根目录内")
+        };
+        Err(self.deny(reason, format!("{}: {}", what, real.display())))
+    }
+
+    /// 读取前检查：根目录、特殊文件与大小上限。文件不存在时交给读取本身报错
+    pub fn check_read(&self) -> Result<(), Denied> {
+        let settings = config::filesystem();
+        let Ok(meta) = fs::metadata(&self.path) else {
+            // 不存在的文件交给读取本身报错，但不透露根目录之外的文件是否存在
+            return self.check_roots(&lexical(&self.path), &settings.allowed_roots);
+        };
+        match self.path.canonicalize() {
+            Ok(real) => self.check_roots(&real, &settings.allowed_roots)?,
+            Err(e) if !settings.allowed_roots.is_empty() => {
+                return Err(self.deny("outside_roots", format!("无法解析真实路径 ({})，不能确认位于允许的根目录内", e)));
+            }
+            Err(_) => {}
+        }
+        if let Some(kind) = special_kind(&meta.file_type()) {
+            return Err(self.deny("special_file", format!("{}，不是普通文件", kind)));
+        }
+        let limit = settings.max_file_bytes.unwrap_or(DEFAULT_MAX_FILE_BYTES);
+        if meta.is_file() && meta.len() > limit {
+            return Err(self.deny("file_too_large", format!("文件 {} 字节，超过上限 {} 字节", meta.len(), limit)));
+        }
+        Ok(())
+    }
+
+    /// 写入前检查：已存在的目标不能是特殊文件；目标（或其最近的已存在上级目录）解析符号链接后
+    /// 必须在允许的根目录内。悬空符号链接按其指向判断，因为写入会跟随链接在别处创建文件
+    pub fn check_write(&self) -> Result<(), Denied> {
+        if let Ok(meta) = fs::metadata(&self.path) {
+            if let Some(kind) = special_kind(&meta.file_type()) {
+                return Err(self.deny("special_file", format!("{}，不是普通文件", kind)));
+            }
+        }
+        let settings = config::filesystem();
+        if settings.allowed_roots.is_empty() {
+            return Ok(());
+        }
+        let absolute = lexical(&self.path);
+        let mut existing = absolute.clone();
+        let mut rest = Vec::new();
+        let mut hops = 0;
+        let real = loop {
+            if let Ok(real) = existing.canonicalize() {
+                break rest.iter().rev().fold(real, |acc: PathBuf, part| acc.join(part));
+            }
+            // canonicalize 对悬空符号链接失败，改为沿链接的指向继续解析
+            if let Ok(target) = fs::read_link(&existing) {
+                hops += 1;
+                if hops > MAX_SYMLINK_HOPS {
+                    return Err(self.deny("symlink_escape", format!("符号链接层数超过 {}", MAX_SYMLINK_HOPS)));
+                }
+                let parent = existing.parent().and_then(|p| p.canonicalize().ok()).unwrap_or_default();
+                existing = lexical(&parent.join(target));
+                continue;
+            }
+            match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    rest.push(name.to_os_string());
+                    existing = parent.to_path_buf();
+                }
+                _ => break absolute.clone(),
+            }
+        };
+        self.check_roots(&real, &settings.allowed_roots)
+    }
+}
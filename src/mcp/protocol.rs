@@ -3,12 +3,15 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::errors::{self, ErrorCode};
+
 // ============================================================
 // JSON-RPC 2.0 核心类型
 // ============================================================
 
 #[derive(Debug, Deserialize)]
 pub struct JsonRpcRequest {
+    #[allow(dead_code)]
     pub jsonrpc: String,
     #[serde(default)]
     pub id: Option<Value>,
@@ -58,6 +61,37 @@ impl JsonRpcResponse {
             }),
         }
     }
+
+    pub fn error_with_data(id: Option<Value>, code: i64, message: String, data: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code,
+                message,
+                data: Some(data),
+            }),
+        }
+    }
+}
+
+/// 服务端主动推送的通知（无 id，不需要响应）
+#[derive(Debug, Serialize)]
+pub struct JsonRpcNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    pub params: Value,
+}
+
+impl JsonRpcNotification {
+    pub fn new(method: &str, params: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+        }
+    }
 }
 
 // ============================================================
@@ -67,6 +101,7 @@ impl JsonRpcResponse {
 #[derive(Debug, Serialize)]
 pub struct ServerCapabilities {
     pub tools: ToolsCapability,
+    pub resources: ResourcesCapability,
 }
 
 #[derive(Debug, Serialize)]
@@ -75,6 +110,13 @@ pub struct ToolsCapability {
     pub list_changed: bool,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ResourcesCapability {
+    pub subscribe: bool,
+    #[serde(rename = "listChanged")]
+    pub list_changed: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct InitializeResult {
     #[serde(rename = "protocolVersion")]
@@ -96,6 +138,70 @@ pub struct Tool {
     pub description: String,
     #[serde(rename = "inputSchema")]
     pub input_schema: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+    /// 调用示例（扩展字段，供能渲染的客户端展示）；完整说明见 `zw://help/<tool>` 资源
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<ToolExample>,
+}
+
+/// 一个调用示例: 场景、参数与预期输出
+#[derive(Debug, Serialize, Clone)]
+pub struct ToolExample {
+    pub title: String,
+    pub arguments: Value,
+    pub expected: String,
+}
+
+/// 工具行为提示，供客户端决定是否需要用户确认
+#[derive(Debug, Serialize, Clone)]
+pub struct ToolAnnotations {
+    #[serde(rename = "readOnlyHint")]
+    pub read_only_hint: bool,
+    #[serde(rename = "destructiveHint")]
+    pub destructive_hint: bool,
+    #[serde(rename = "idempotentHint")]
+    pub idempotent_hint: bool,
+    #[serde(rename = "openWorldHint")]
+    pub open_world_hint: bool,
+}
+
+impl ToolAnnotations {
+    /// 只读、幂等：不修改任何状态
+    pub fn read_only() -> Self {
+        Self {
+            read_only_hint: true,
+            destructive_hint: false,
+            idempotent_hint: true,
+            open_world_hint: false,
+        }
+    }
+
+    /// 可写文件：指定输出路径时可能覆盖已有文件
+    pub fn writes_files() -> Self {
+        Self {
+            read_only_hint: false,
+            destructive_hint: true,
+            idempotent_hint: true,
+            open_world_hint: false,
+        }
+    }
+
+    /// 修改服务端会话状态，但不触碰文件
+    pub fn stateful() -> Self {
+        Self {
+            read_only_hint: false,
+            destructive_hint: false,
+            idempotent_hint: false,
+            open_world_hint: false,
+        }
+    }
+
+    /// 可访问外部网络（如接受 url 输入）
+    pub fn open_world(mut self) -> Self {
+        self.open_world_hint = true;
+        self
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -103,14 +209,17 @@ pub struct ToolsListResult {
     pub tools: Vec<Tool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ToolCallResult {
     pub content: Vec<Content>,
     #[serde(rename = "isError", skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    /// 机器可读的结果（如拒绝访问的原因），供客户端程序化处理
+    #[serde(rename = "structuredContent", skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Content {
     #[serde(rename = "type")]
     pub content_type: String,
@@ -131,13 +240,34 @@ impl ToolCallResult {
         Self {
             content: vec![Content::text(text)],
             is_error: None,
+            structured_content: None,
         }
     }
 
+    /// 参数校验类的失败（错误码 INVALID_ARGUMENT）；其他失败用 `failure` 指明错误码
     pub fn error(text: impl Into<String>) -> Self {
+        Self::failure(ErrorCode::InvalidArgument, text)
+    }
+
+    /// 带错误码的失败结果，错误码放在 structuredContent 中
+    pub fn failure(code: ErrorCode, text: impl Into<String>) -> Self {
+        Self::error_with_data(code, text, Value::Null)
+    }
+
+    /// 带错误码与机器可读细节的失败结果
+    pub fn error_with_data(code: ErrorCode, text: impl Into<String>, data: Value) -> Self {
         Self {
             content: vec![Content::text(text)],
             is_error: Some(true),
+            structured_content: Some(errors::with_code(code, data)),
+        }
+    }
+
+    /// 失败结果的错误码；成功时为 None
+    pub fn error_code(&self) -> Option<&str> {
+        if self.is_error != Some(true) {
+            return None;
         }
+        self.structured_content.as_ref()?.get("code")?.as_str()
     }
 }
@@ -0,0 +1,143 @@
+//! 速率限制与会话配额 - 防止单个客户端占满共享实例的 CPU 与 I/O
+//!
+//! 每个连接持有一个 `ClientQuota`（stdio 模式下只有一个连接）：令牌桶限制工具调用与资源读取的速率，
+//! 另外累计工具执行耗时与读取的字节数，超出会话配额后拒绝后续调用。
+//! 参数取自配置文件 `[limits]`，热重载后立即生效；未配置的项不限制。
+
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use super::config::Limits;
+use super::errors::{self, ErrorCode};
+
+thread_local! {
+    /// 当前请求读取的文件字节数；请求在单个阻塞线程中执行，结束后由 `take_bytes_read` 取出
+    static BYTES_READ: Cell<u64> = const { Cell::new(0) };
+}
+
+/// 记录工具读取的文件字节数
+pub fn record_read(bytes: usize) {
+    BYTES_READ.with(|b| b.set(b.get() + bytes as u64));
+}
+
+/// 取出并清零当前线程记录的读取字节数
+pub fn take_bytes_read() -> u64 {
+    BYTES_READ.with(|b| b.replace(0))
+}
+
+/// 计入速率与配额的方法（其余如 initialize、ping、tools/list 不受限制）
+pub fn is_limited(method: &str) -> bool {
+    matches!(method, "tools/call" | "resources/read")
+}
+
+/// 拒绝原因
+#[derive(Debug, Clone)]
+pub enum Rejection {
+    RateLimited { retry_after: Duration },
+    CpuExhausted { used: Duration, limit: f64 },
+    BytesExhausted { used: u64, limit: u64 },
+}
+
+impl Rejection {
+    pub fn message(&self) -> String {
+        match self {
+            Rejection::RateLimited { retry_after } => {
+                format!("请求过于频繁，请在 {} ms 后重试", retry_after.as_millis())
+            }
+            Rejection::CpuExhausted { used, limit } => format!(
+                "会话的工具执行时间配额已用尽（已用 {:.1} 秒，上限 {} 秒），请重新连接或联系管理员",
+                used.as_secs_f64(),
+                limit
+            ),
+            Rejection::BytesExhausted { used, limit } => {
+                format!("会话的读取字节配额已用尽（已读 {} 字节，上限 {} 字节）", used, limit)
+            }
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Rejection::RateLimited { .. } => ErrorCode::RateLimited,
+            Rejection::CpuExhausted { .. } | Rejection::BytesExhausted { .. } => ErrorCode::QuotaExceeded,
+        }
+    }
+
+    /// JSON-RPC 错误的 data 字段，供客户端程序化处理
+    pub fn data(&self) -> Value {
+        let data = match self {
+            Rejection::RateLimited { retry_after } => {
+                json!({ "reason": "rate_limited", "retry_after_ms": retry_after.as_millis() as u64 })
+            }
+            Rejection::CpuExhausted { used, limit } => {
+                json!({ "reason": "cpu_quota", "used_seconds": used.as_secs_f64(), "limit_seconds": limit })
+            }
+            Rejection::BytesExhausted { used, limit } => {
+                json!({ "reason": "bytes_quota", "used_bytes": used, "limit_bytes": limit })
+            }
+        };
+        errors::with_code(self.code(), data)
+    }
+}
+
+/// 单个客户端连接的令牌桶与配额用量
+#[derive(Debug)]
+pub struct ClientQuota {
+    /// 剩余令牌；None 表示桶是满的（首次使用或之前未启用速率限制）
+    tokens: Option<f64>,
+    last_refill: Instant,
+    cpu: Duration,
+    bytes: u64,
+}
+
+impl Default for ClientQuota {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientQuota {
+    pub fn new() -> Self {
+        Self { tokens: None, last_refill: Instant::now(), cpu: Duration::ZERO, bytes: 0 }
+    }
+
+    /// 检查配额并消耗一个令牌；通过时把请求本身的字节数计入读取量
+    pub fn admit(&mut self, limits: &Limits, request_bytes: usize) -> Result<(), Rejection> {
+        if let Some(limit) = limits.session_cpu_seconds {
+            if self.cpu.as_secs_f64() >= limit {
+                return Err(Rejection::CpuExhausted { used: self.cpu, limit });
+            }
+        }
+        if let Some(limit) = limits.session_bytes_read {
+            if self.bytes >= limit {
+                return Err(Rejection::BytesExhausted { used: self.bytes, limit });
+            }
+        }
+        match limits.requests_per_second {
+            Some(rate) => {
+                let burst = limits.burst.map(f64::from).unwrap_or_else(|| rate.ceil().max(1.0));
+                let now = Instant::now();
+                let refill = now.duration_since(self.last_refill).as_secs_f64() * rate;
+                let tokens = self.tokens.map_or(burst, |t| (t + refill).min(burst));
+                self.last_refill = now;
+                if tokens < 1.0 {
+                    self.tokens = Some(tokens);
+                    return Err(Rejection::RateLimited {
+                        retry_after: Duration::from_secs_f64((1.0 - tokens) / rate),
+                    });
+                }
+                self.tokens = Some(tokens - 1.0);
+            }
+            None => self.tokens = None,
+        }
+        self.bytes += request_bytes as u64;
+        Ok(())
+    }
+
+    /// 计入一次调用的执行时间与读取的文件字节数
+    pub fn charge(&mut self, elapsed: Duration, bytes: u64) {
+        self.cpu += elapsed;
+        self.bytes += bytes;
+    }
+}
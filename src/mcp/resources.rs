@@ -0,0 +1,210 @@
+//! MCP 资源 - 以 `file://` URI 暴露本地文件，支持订阅文件变更
+//!
+//! 订阅后服务端监视文件所在目录（兼容编辑器"写临时文件再改名"的保存方式），
+//! 文件被修改、创建或替换时推送 `notifications/resources/updated`，
+//! 客户端据此重新读取资源并检测新注入的零宽字符。
+//!
+//! 另以 `zw://help/<tool>` 暴露每个工具的参数说明与调用示例（见 `help` 模块），
+//! 以 `zw-tmp://<name>` 暴露工具写入临时工作区的产物（见 `workspace` 模块）。
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::{json, Value};
+
+use super::{help, quota, server, tools, workspace};
+
+/// 订阅状态: 被订阅文件 -> URI，以及每个监视目录的引用计数
+///
+/// watcher 单独加锁: 事件回调只访问 `state`，避免 watch() 等待事件线程时互相阻塞。
+struct Subscriptions {
+    watcher: Mutex<RecommendedWatcher>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    files: BTreeMap<PathBuf, String>,
+    dirs: HashMap<PathBuf, usize>,
+}
+
+static SUBSCRIPTIONS: OnceLock<Option<Subscriptions>> = OnceLock::new();
+
+fn on_event(res: notify::Result<notify::Event>) {
+    let event = match res {
+        Ok(e) => e,
+        Err(e) => {
+            tracing::warn!("File watch error: {}", e);
+            return;
+        }
+    };
+    if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+        return;
+    }
+    let uris: Vec<String> = {
+        let Some(subs) = subscriptions() else {
+            return;
+        };
+        let state = subs.state.lock().unwrap_or_else(|e| e.into_inner());
+        event.paths.iter().filter_map(|p| state.files.get(p).cloned()).collect()
+    };
+    for uri in uris {
+        tracing::debug!("Resource updated: {}", uri);
+        server::notify("notifications/resources/updated", json!({ "uri": uri }));
+    }
+}
+
+fn subscriptions() -> Option<&'static Subscriptions> {
+    SUBSCRIPTIONS
+        .get_or_init(|| match notify::recommended_watcher(on_event) {
+            Ok(watcher) => Some(Subscriptions {
+                watcher: Mutex::new(watcher),
+                state: Mutex::new(State::default()),
+            }),
+            Err(e) => {
+                tracing::error!("Failed to create file watcher: {}", e);
+                None
+            }
+        })
+        .as_ref()
+}
+
+/// 解析 `file://` URI 为本地路径（支持 `%XX` 转义）
+pub fn uri_to_path(uri: &str) -> Result<PathBuf, String> {
+    let rest = uri
+        .strip_prefix("file://")
+        .ok_or_else(|| format!("仅支持 file:// 资源: {}", uri))?;
+    // file://localhost/path 与 file:///path 等价
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    let bytes = rest.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() && bytes[i + 1..i + 3].iter().all(u8::is_ascii_hexdigit) {
+            if let Ok(b) = u8::from_str_radix(&rest[i + 1..i + 3], 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    let path = String::from_utf8(out).map_err(|_| format!("URI 含非法 UTF-8 转义: {}", uri))?;
+    if path.is_empty() {
+        return Err(format!("URI 缺少路径: {}", uri));
+    }
+    Ok(PathBuf::from(path))
+}
+
+/// 规范化路径，文件尚不存在时规范化其父目录
+fn canonical(path: &Path) -> Result<PathBuf, String> {
+    if let Ok(p) = path.canonicalize() {
+        return Ok(p);
+    }
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path.file_name().ok_or_else(|| format!("无效路径: {}", path.display()))?;
+    let parent = parent
+        .canonicalize()
+        .map_err(|e| format!("目录不存在: {} ({})", parent.display(), e))?;
+    Ok(parent.join(name))
+}
+
+/// resources/list: 列出当前已订阅的文件、工作区产物与各工具的帮助
+pub fn list() -> Value {
+    let mut resources: Vec<Value> = subscriptions()
+        .map(|subs| {
+            let state = subs.state.lock().unwrap_or_else(|e| e.into_inner());
+            state
+                .files
+                .iter()
+                .map(|(path, uri)| {
+                    json!({
+                        "uri": uri,
+                        "name": path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+                        "mimeType": "text/plain",
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    resources.extend(workspace::resources());
+    resources.extend(help::resources());
+    json!({ "resources": resources })
+}
+
+/// resources/read: 读取文件内容（自动检测编码）、工作区产物或工具帮助
+pub fn read(uri: &str) -> Result<Value, String> {
+    if uri.starts_with(help::HELP_URI_PREFIX) {
+        return help::read(uri);
+    }
+    if uri.starts_with(workspace::URI_PREFIX) {
+        return workspace::read(uri);
+    }
+    let path = uri_to_path(uri)?;
+    let raw = std::fs::read(&path).map_err(|e| format!("读取文件失败: {} ({})", path.display(), e))?;
+    quota::record_read(raw.len());
+    let text = tools::decode_bytes_auto(&raw, false);
+    Ok(json!({
+        "contents": [{ "uri": uri, "mimeType": "text/plain", "text": text }]
+    }))
+}
+
+/// resources/subscribe: 开始监视文件
+pub fn subscribe(uri: &str) -> Result<(), String> {
+    let path = canonical(&uri_to_path(uri)?)?;
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let subs = subscriptions().ok_or("文件监视器不可用")?;
+    // 先持有 watcher 锁，串行化订阅变更
+    let mut watcher = subs.watcher.lock().unwrap_or_else(|e| e.into_inner());
+    let need_watch = {
+        let state = subs.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.files.contains_key(&path) {
+            return Ok(());
+        }
+        !state.dirs.contains_key(&dir)
+    };
+    if need_watch {
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("无法监视目录 {}: {}", dir.display(), e))?;
+    }
+    let mut state = subs.state.lock().unwrap_or_else(|e| e.into_inner());
+    *state.dirs.entry(dir).or_insert(0) += 1;
+    state.files.insert(path.clone(), uri.to_string());
+    tracing::info!("Subscribed: {} ({})", uri, path.display());
+    Ok(())
+}
+
+/// resources/unsubscribe: 停止监视文件
+pub fn unsubscribe(uri: &str) -> Result<(), String> {
+    let path = canonical(&uri_to_path(uri)?)?;
+    let Some(subs) = subscriptions() else {
+        return Ok(());
+    };
+    let mut watcher = subs.watcher.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let unwatch = {
+        let mut state = subs.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.files.remove(&path).is_none() {
+            return Ok(());
+        }
+        let remaining = state.dirs.get_mut(&dir).map(|n| {
+            *n -= 1;
+            *n
+        });
+        if remaining == Some(0) {
+            state.dirs.remove(&dir);
+        }
+        remaining == Some(0)
+    };
+    if unwatch {
+        if let Err(e) = watcher.unwatch(&dir) {
+            tracing::warn!("Failed to unwatch {}: {}", dir.display(), e);
+        }
+    }
+    tracing::info!("Unsubscribed: {}", uri);
+    Ok(())
+}
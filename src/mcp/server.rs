@@ -1,68 +1,384 @@
 //! MCP Server - stdio 传输层实现
 
+use std::sync::{Arc, Mutex, OnceLock};
+
 use serde_json::Value;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::task::JoinSet;
 
+use super::audit;
+use super::concurrency;
+use super::config;
+use super::errors::{self, ErrorCode};
+use super::jobs;
 use super::protocol::*;
+use super::quota::{self, ClientQuota};
+use super::resources;
 use super::tools;
+use crate::zw_core::ingest;
+
+static NOTIFIER: OnceLock<Sender<String>> = OnceLock::new();
+
+/// 向客户端推送通知（资源变更、工具列表变更等后台事件）
+///
+/// 通道满时阻塞等待，因此只能在阻塞线程中调用（工具执行、后台任务与文件监视线程都是）
+pub fn notify(method: &str, params: Value) {
+    let Some(tx) = NOTIFIER.get() else {
+        return;
+    };
+    let note = JsonRpcNotification::new(method, params);
+    if let Ok(msg) = serde_json::to_string(&note) {
+        let _ = tx.blocking_send(msg);
+    }
+}
+
+/// 推送进度通知；`message` 可携带分块输出
+pub fn notify_progress(token: &Value, progress: usize, total: Option<usize>, message: &str) {
+    let mut params = serde_json::json!({ "progressToken": token, "progress": progress });
+    if let Some(total) = total {
+        params["total"] = total.into();
+    }
+    if !message.is_empty() {
+        params["message"] = message.into();
+    }
+    notify("notifications/progress", params);
+}
+
+/// 工具列表（预设、专用工具）发生变化
+pub fn notify_tools_changed() {
+    notify("notifications/tools/list_changed", serde_json::json!({}));
+}
+
+/// 写出通道最多缓冲的消息数；客户端读得慢时发送方在此等待，而不是无限堆积
+const WRITER_BUFFER: usize = 64;
+
+/// 通知与排队调用响应的通道最多缓冲的消息数；满时同样让发送方等待
+const NOTIFY_BUFFER: usize = 64;
+
+/// 独占 stdout 的写出任务: 消息逐条完整写出（不会交错），通道暂时为空时才 flush
+async fn writer_task(mut rx: Receiver<String>) -> std::io::Result<()> {
+    let mut stdout = tokio::io::stdout();
+    while let Some(msg) = rx.recv().await {
+        stdout.write_all(msg.as_bytes()).await?;
+        stdout.write_all(b"\n").await?;
+        if rx.is_empty() {
+            stdout.flush().await?;
+        }
+    }
+    stdout.flush().await
+}
+
+/// 把一条消息交给写出任务；通道满时等待（背压）
+async fn send_line(out: &Sender<String>, msg: String) -> Result<(), Box<dyn std::error::Error>> {
+    out.send(msg).await.map_err(|_| "stdout writer closed".into())
+}
+
+/// 默认的单行请求最大字节数，可由环境变量 ZW_MAX_REQUEST_BYTES 或配置 limits.max_request_bytes 覆盖
+const DEFAULT_MAX_REQUEST_BYTES: usize = 32 * 1024 * 1024;
+
+pub fn max_request_bytes() -> usize {
+    std::env::var("ZW_MAX_REQUEST_BYTES")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .or(config::limits().max_request_bytes)
+        .unwrap_or(DEFAULT_MAX_REQUEST_BYTES)
+}
+
+/// 读到的一行请求
+enum RequestLine {
+    Line(String),
+    /// 超过上限的行（已丢弃），记录其字节数
+    TooLong(usize),
+}
+
+/// 有长度上限的按行读取器
+///
+/// 超长行只计数不缓存，一直丢弃到换行为止，避免一行数百 MB 的请求占满内存。
+/// 读取状态保存在结构体中，`next_line` 在 `select!` 中被取消也不会丢数据。
+struct LineReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    len: usize,
+}
+
+impl<R: AsyncBufRead + Unpin> LineReader<R> {
+    fn new(reader: R) -> Self {
+        Self { reader, buf: Vec::new(), len: 0 }
+    }
+
+    async fn next_line(&mut self, limit: usize) -> std::io::Result<Option<RequestLine>> {
+        loop {
+            let chunk = self.reader.fill_buf().await?;
+            let (take, done) = match chunk.iter().position(|&b| b == b'\n') {
+                Some(i) => (i + 1, true),
+                None => (chunk.len(), false),
+            };
+            if take == 0 && self.len == 0 {
+                return Ok(None);
+            }
+            let part = &chunk[..take];
+            let part = if done { &part[..part.len() - 1] } else { part };
+            self.len += part.len();
+            if self.len <= limit {
+                self.buf.extend_from_slice(part);
+            } else if !self.buf.is_empty() {
+                self.buf = Vec::new();
+            }
+            self.reader.consume(take);
+            if done || take == 0 {
+                let len = std::mem::take(&mut self.len);
+                let line = std::mem::take(&mut self.buf);
+                return Ok(Some(if len > limit {
+                    RequestLine::TooLong(len)
+                } else {
+                    RequestLine::Line(String::from_utf8_lossy(&line).into_owned())
+                }));
+            }
+        }
+    }
+}
 
 /// 运行 MCP Server（stdio 模式）
 pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let stdin = tokio::io::stdin();
-    let mut stdout = tokio::io::stdout();
-    let reader = BufReader::new(stdin);
-    let mut lines = reader.lines();
+    let mut lines = LineReader::new(BufReader::new(stdin));
+    let limit = max_request_bytes();
+
+    let (out, out_rx) = mpsc::channel::<String>(WRITER_BUFFER);
+    let writer = tokio::spawn(writer_task(out_rx));
+
+    // 资源订阅等后台事件通过该通道推送通知
+    // 排队执行的工具调用也经该通道写回响应
+    let (notify_tx, mut notify_rx) = mpsc::channel::<String>(NOTIFY_BUFFER);
+    let responder = notify_tx.clone();
+    let _ = NOTIFIER.set(notify_tx);
+    let mut inflight = JoinSet::new();
+
+    // stdio 模式下整个进程只服务一个客户端，对应一份配额
+    let quota = Arc::new(Mutex::new(ClientQuota::new()));
 
     tracing::info!("MCP Server started (stdio mode)");
 
-    while let Some(line) = lines.next_line().await? {
+    loop {
+        let line = tokio::select! {
+            line = lines.next_line(limit) => match line? {
+                Some(RequestLine::Line(line)) => line,
+                Some(RequestLine::TooLong(len)) => {
+                    tracing::warn!("Rejected request line of {} bytes (limit {})", len, limit);
+                    let resp = JsonRpcResponse::error_with_data(
+                        None,
+                        -32600,
+                        format!("请求过大: {} 字节，超过上限 {} 字节；大文件请改用 file_path", len, limit),
+                        errors::with_code(
+                            ErrorCode::RequestTooLarge,
+                            serde_json::json!({ "received_bytes": len, "max_request_bytes": limit }),
+                        ),
+                    );
+                    send_line(&out, serde_json::to_string(&resp)?).await?;
+                    continue;
+                }
+                None => break,
+            },
+            Some(note) = notify_rx.recv() => {
+                tracing::debug!("Notify: {}", &note);
+                send_line(&out, note).await?;
+                continue;
+            }
+        };
         let line = line.trim().to_string();
         if line.is_empty() {
             continue;
         }
 
-        tracing::debug!("Received: {}", &line);
-
-        let request: JsonRpcRequest = match serde_json::from_str(&line) {
+        // 孤立代理项会让 serde_json 拒绝整行，先尝试修复再解析
+        let parsed = serde_json::from_str::<JsonRpcRequest>(&line).or_else(|e| {
+            let repaired = ingest::repair_lone_surrogate_escapes(&line);
+            if repaired != line {
+                tracing::warn!("Repaired lone surrogate escapes in request");
+                serde_json::from_str(&repaired)
+            } else {
+                Err(e)
+            }
+        });
+        let request: JsonRpcRequest = match parsed {
             Ok(r) => r,
             Err(e) => {
+                tracing::debug!(bytes = line.len(), "Received unparseable request");
                 let resp = JsonRpcResponse::error(
                     None,
                     -32700,
                     format!("Parse error: {}", e),
                 );
-                let msg = serde_json::to_string(&resp)?;
-                stdout.write_all(msg.as_bytes()).await?;
-                stdout.write_all(b"\n").await?;
-                stdout.flush().await?;
+                send_line(&out, serde_json::to_string(&resp)?).await?;
                 continue;
             }
         };
 
-        let response = handle_request(&request);
+        // 原始行可能含口令，只记录脱敏后的参数
+        tracing::debug!(
+            bytes = line.len(),
+            "Received: {} {}",
+            request.method,
+            request.params.as_ref().map(audit::redact_secrets).unwrap_or_default()
+        );
+
+        // 后台任务的执行耗时与读取字节在结束后补记
+        while inflight.try_join_next().is_some() {}
+        let (job_elapsed, job_bytes) = jobs::take_usage();
+        if !job_elapsed.is_zero() || job_bytes > 0 {
+            quota.lock().unwrap_or_else(|e| e.into_inner()).charge(job_elapsed, job_bytes);
+        }
+
+        let limited = quota::is_limited(&request.method);
+        if limited {
+            let admitted = quota.lock().unwrap_or_else(|e| e.into_inner()).admit(&config::limits(), line.len());
+            if let Err(rejection) = admitted {
+                tracing::warn!("Rejected {}: {}", request.method, rejection.message());
+                let resp = JsonRpcResponse::error_with_data(request.id.clone(), -32000, rejection.message(), rejection.data());
+                send_line(&out, serde_json::to_string(&resp)?).await?;
+                continue;
+            }
+        }
+
+        // 受并发限制的工具调用放到独立任务中排队执行，不阻塞后续请求（ping、tools/list、轻量工具）；
+        // 响应与通知走同一通道，保证工具发出的进度通知先于响应到达
+        if let Some(tool) = tool_name(&request).filter(|t| concurrency::limit_for(t).is_some()) {
+            let responder = responder.clone();
+            let quota = quota.clone();
+            inflight.spawn(async move {
+                let response = match concurrency::acquire(&tool).await {
+                    Ok(permit) => execute(request, limited, permit, &quota).await,
+                    Err(busy) => {
+                        tracing::warn!("Rejected {}: {}", tool, busy.message());
+                        Some(JsonRpcResponse::error_with_data(request.id.clone(), -32000, busy.message(), busy.data()))
+                    }
+                };
+                if let Some(msg) = response.and_then(|resp| serde_json::to_string(&resp).ok()) {
+                    tracing::debug!(bytes = msg.len(), "Sending: {}", &msg);
+                    let _ = responder.send(msg).await;
+                }
+            });
+            continue;
+        }
+
+        let task = execute(request, limited, None, &quota);
+        let mut task = std::pin::pin!(task);
+        // 等待期间转发工具发出的通知（进度、分块输出），保证它们先于响应到达
+        let response = loop {
+            tokio::select! {
+                response = &mut task => break response,
+                Some(note) = notify_rx.recv() => {
+                    tracing::debug!("Notify: {}", &note);
+                    send_line(&out, note).await?;
+                }
+            }
+        };
+        while let Ok(note) = notify_rx.try_recv() {
+            tracing::debug!("Notify: {}", &note);
+            send_line(&out, note).await?;
+        }
 
         if let Some(resp) = response {
             let msg = serde_json::to_string(&resp)?;
-            tracing::debug!("Sending: {}", &msg);
-            stdout.write_all(msg.as_bytes()).await?;
-            stdout.write_all(b"\n").await?;
-            stdout.flush().await?;
+            tracing::debug!(bytes = msg.len(), "Sending: {}", &msg);
+            send_line(&out, msg).await?;
         }
     }
 
+    // 输入结束后等排队与执行中的调用完成，把它们的响应写出
+    loop {
+        tokio::select! {
+            joined = inflight.join_next() => if joined.is_none() { break },
+            Some(note) = notify_rx.recv() => send_line(&out, note).await?,
+        }
+    }
+    while let Ok(note) = notify_rx.try_recv() {
+        send_line(&out, note).await?;
+    }
+
     tracing::info!("MCP Server shutting down");
+    // 关闭通道后等待写出任务把剩余消息写完
+    drop(out);
+    writer.await??;
     Ok(())
 }
 
+/// 在阻塞线程中处理一个请求并加上硬超时，避免单次调用卡住整个会话；
+/// 许可随阻塞任务一起结束，超时后仍在后台运行的调用也占用许可
+async fn execute(
+    request: JsonRpcRequest,
+    limited: bool,
+    permit: Option<concurrency::Permit>,
+    quota: &Mutex<ClientQuota>,
+) -> Option<JsonRpcResponse> {
+    let id = request.id.clone();
+    let limit = request
+        .params
+        .as_ref()
+        .and_then(|p| p.get("arguments"))
+        .map(tools::hard_timeout)
+        .unwrap_or_else(|| tools::hard_timeout(&Value::Null));
+    let task = tokio::task::spawn_blocking(move || {
+        quota::take_bytes_read();
+        let started = std::time::Instant::now();
+        let response = handle_request(&request);
+        drop(permit);
+        (response, started.elapsed(), quota::take_bytes_read())
+    });
+    let charge = |elapsed, bytes| {
+        if limited {
+            quota.lock().unwrap_or_else(|e| e.into_inner()).charge(elapsed, bytes);
+        }
+    };
+    match tokio::time::timeout(limit, task).await {
+        Ok(Ok((resp, elapsed, bytes))) => {
+            charge(elapsed, bytes);
+            resp
+        }
+        Ok(Err(e)) => Some(JsonRpcResponse::error_with_data(
+            id,
+            -32603,
+            format!("Internal error: {}", e),
+            errors::with_code(ErrorCode::Internal, Value::Null),
+        )),
+        Err(_) => {
+            // 超时的阻塞任务仍在后台运行，至少按硬超时计入执行时间
+            charge(limit, 0);
+            tracing::warn!("Request {} timed out after {:?}", id.as_ref().map(|v| v.to_string()).unwrap_or_default(), limit);
+            Some(JsonRpcResponse::error_with_data(
+                id,
+                -32603,
+                format!("工具执行超时 ({} ms)", limit.as_millis()),
+                errors::with_code(ErrorCode::ToolTimeout, serde_json::json!({ "timeout_ms": limit.as_millis() as u64 })),
+            ))
+        }
+    }
+}
+
+/// tools/call 请求的工具名
+fn tool_name(req: &JsonRpcRequest) -> Option<String> {
+    if req.method != "tools/call" {
+        return None;
+    }
+    req.params.as_ref()?.get("name")?.as_str().map(str::to_string)
+}
+
 /// 处理单个 JSON-RPC 请求
 fn handle_request(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
+    let id = req.id.as_ref().map(|v| v.to_string()).unwrap_or_default();
+    let _span = tracing::info_span!("request", id = %id, method = %req.method).entered();
     match req.method.as_str() {
         // --- MCP 握手 ---
         "initialize" => {
             let result = InitializeResult {
                 protocol_version: "2024-11-05".to_string(),
                 capabilities: ServerCapabilities {
-                    tools: ToolsCapability { list_changed: false },
+                    tools: ToolsCapability { list_changed: true },
+                    resources: ResourcesCapability {
+                        subscribe: true,
+                        list_changed: false,
+                    },
                 },
                 server_info: ServerInfo {
                     name: "zw-mcp-server".to_string(),
@@ -96,20 +412,75 @@ fn handle_request(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
                 .get("name")
                 .and_then(|v| v.as_str())
                 .unwrap_or("");
-            let arguments = params
+            let mut arguments = params
                 .get("arguments")
                 .cloned()
                 .unwrap_or(Value::Object(serde_json::Map::new()));
+            // 请求的 _meta（progressToken 等）随参数传给工具，用于推送进度与分块输出
+            if let (Some(meta), Some(obj)) = (params.get("_meta"), arguments.as_object_mut()) {
+                obj.insert("_meta".to_string(), meta.clone());
+            }
 
-            tracing::info!("Tool call: {} with args: {}", tool_name, &arguments);
+            let _tool_span = tracing::info_span!("tool", name = %tool_name).entered();
+            tracing::info!("Tool call: {} with args: {}", tool_name, audit::redact_secrets(&arguments));
 
+            let started = std::time::Instant::now();
             let result = tools::call_tool(tool_name, &arguments);
+            tracing::info!(
+                elapsed_ms = started.elapsed().as_secs_f64() * 1000.0,
+                is_error = result.is_error.unwrap_or(false),
+                "Tool finished"
+            );
+            audit::record(req.id.as_ref(), tool_name, &arguments, &result, started.elapsed());
             Some(JsonRpcResponse::success(
                 req.id.clone(),
                 serde_json::to_value(result).unwrap(),
             ))
         }
 
+        // --- 资源 ---
+        "resources/list" => Some(JsonRpcResponse::success(req.id.clone(), resources::list())),
+
+        "resources/templates/list" => Some(JsonRpcResponse::success(
+            req.id.clone(),
+            serde_json::json!({
+                "resourceTemplates": [{
+                    "uriTemplate": "file:///{path}",
+                    "name": "本地文件",
+                    "description": "读取本地文本文件；订阅后文件变化时推送 notifications/resources/updated",
+                    "mimeType": "text/plain",
+                }, {
+                    "uriTemplate": "zw-tmp://{name}",
+                    "name": "工作区产物",
+                    "description": "工具写入临时工作区的文件（截断前的完整输出、导出结果等），过期后自动删除",
+                    "mimeType": "application/octet-stream",
+                }, {
+                    "uriTemplate": "zw://help/{tool}",
+                    "name": "工具帮助",
+                    "description": "工具的参数说明与调用示例（Markdown）",
+                    "mimeType": "text/markdown",
+                }]
+            }),
+        )),
+
+        "resources/read" | "resources/subscribe" | "resources/unsubscribe" => {
+            let uri = req
+                .params
+                .as_ref()
+                .and_then(|p| p.get("uri"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            let result = match req.method.as_str() {
+                "resources/read" => resources::read(uri),
+                "resources/subscribe" => resources::subscribe(uri).map(|_| serde_json::json!({})),
+                _ => resources::unsubscribe(uri).map(|_| serde_json::json!({})),
+            };
+            Some(match result {
+                Ok(v) => JsonRpcResponse::success(req.id.clone(), v),
+                Err(e) => JsonRpcResponse::error(req.id.clone(), -32602, e),
+            })
+        }
+
         // --- Ping ---
         "ping" => Some(JsonRpcResponse::success(
             req.id.clone(),
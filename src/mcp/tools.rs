@@ -1,23 +1,108 @@
 //! MCP 工具定义 - 将零宽字符功能暴露为 MCP tools
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 use serde_json::{json, Value};
 
-use super::protocol::{Tool, ToolCallResult};
-use crate::zw_core::{chars, engine};
+use super::input::{self, read_file_auto, InputSource};
+use super::{config, custody, health, help, jobs, paths, quota, workspace};
+use super::errors::ErrorCode;
+use super::protocol::{Tool, ToolAnnotations, ToolCallResult};
+use super::server;
+use crate::zw_core::session::Session;
+use crate::zw_core::{annotate, baseline, benchmark, blank, carve, channel, chars, cluster, codecover, cover, delta, density, diagnose, difficulty, emoji, emulate, engine, explain, fuzz, gitscan, grep, hexdump, hints, html, ingest, morse, names, numeric, postprocess, profile, reassemble, recommend, repair, sanitize, sidecar, stegcloak, triage};
 
 /// 注册所有可用工具
 pub fn all_tools() -> Vec<Tool> {
-    vec![
+    let mut tools = vec![
         tool_analyze(),
+        tool_locate_flag(),
         tool_decode(),
+        tool_explain(),
+        tool_decode_detail(),
         tool_encode(),
+        tool_mass_encode(),
+        tool_random_cover(),
+        tool_difficulty(),
         tool_dump_raw(),
+        tool_hexdump(),
+        tool_extract_file(),
+        tool_entropy_visual(),
+        tool_grep(),
+        tool_git_scan(),
+        tool_summarize_scan(),
+        tool_strip(),
+        tool_segments(),
+        tool_annotate(),
+        tool_check_name(),
+        tool_blank_message(),
+        tool_session_start(),
+        tool_session_feed(),
+        tool_session_decode(),
+        tool_job_start(),
+        tool_job_status(),
+        tool_job_result(),
+        tool_reassemble(),
+        tool_simulate_channel(),
+        tool_recommend(),
+        tool_fuzz(),
+        tool_benchmark(),
+        tool_health(),
         tool_list_chars(),
         tool_list_presets(),
-    ]
+        tool_presets_test(),
+        tool_add_preset(),
+        tool_remove_preset(),
+    ];
+    if config::admin_tools_enabled() {
+        tools.push(tool_reload_config());
+    }
+    // 每个自定义预设另注册一个专用解码工具
+    tools.extend(engine::custom_preset_keys().iter().map(|key| tool_preset_decode(key)));
+    tools
+}
+
+// ============================================================
+// 共享参数
+// ============================================================
+
+/// 接受文本输入的工具共用的 url 来源；带此参数的工具应标注 `open_world()`
+fn url_property() -> Value {
+    json!({
+        "type": "string",
+        "description": "可选：从 http(s) 地址下载输入（与 text / text_base64 / file_path 同时给出时，优先级为 file_path → url → text_base64 → text）"
+    })
+}
+
+/// 读写文件的工具共用：附带本次读写文件的哈希
+fn report_hashes_property() -> Value {
+    json!({
+        "type": "boolean",
+        "description": "可选：在响应末尾附上本次读取的输入文件与写出文件的 SHA-256（证据链留存）",
+        "default": false
+    })
+}
+
+/// 解码类工具共用的 StegCloak 口令
+fn password_property() -> Value {
+    json!({
+        "type": "string",
+        "description": "可选：StegCloak 加密载荷的口令（auto 与 method=stegcloak 时使用）"
+    })
+}
+
+/// 读入文本的工具共用的容错模式
+fn lossy_property() -> Value {
+    json!({
+        "type": "boolean",
+        "description": "可选：容错模式。非法 UTF-8/孤立代理项产生的替换字符 (U+FFFD) 会被记录位置并在解码前剔除，避免把载荷切断",
+        "default": false
+    })
 }
 
 // ============================================================
@@ -35,16 +120,101 @@ fn tool_analyze() -> Tool {
                     "type": "string",
                     "description": "要分析的文本内容（可能包含不可见的零宽字符）。与 file_path 二选一"
                 },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "include_categories": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "可选：只考虑这些分类的零宽字符（如 [\"核心零宽\"]），分类名见 zw_list_chars"
+                },
+                "exclude_categories": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "可选：完全忽略这些分类的零宽字符（如 [\"变体选择器\"]，避免大量合法 VS16 干扰）"
+                },
+                "baseline": {
+                    "type": "string",
+                    "enum": ["auto", "none", "plain", "emoji_chat", "word_export", "persian_arabic"],
+                    "description": "与哪类文档的正常频率基线对比，默认 auto（按内容自动识别），none 关闭"
+                },
+                "cover_profile": {
+                    "type": "boolean",
+                    "description": "是否附加载体文本画像：文字系统构成、内容类型（代码/正文/数据）、该类载体中正常出现的不可见字符与保留建议",
+                    "default": true
+                },
+                "legit_zwnj": {
+                    "type": "string",
+                    "enum": ["exclude", "include"],
+                    "description": "夹在两个阿拉伯字母之间的单个 ZWNJ（波斯语等正字法用法）: exclude 不参与提取（默认），include 视为载荷"
+                },
                 "file_path": {
                     "type": "string",
                     "description": "要分析的文件路径（支持绝对路径和相对路径）。与 text 二选一"
+                },
+                "report_hashes": report_hashes_property(),
+                "log_mode": {
+                    "type": "boolean",
+                    "description": "可选：日志模式。按原始字节读入，拼接被换行拆开的 UTF-8 多字节序列，并还原部分转义的形式（\\xe2\\x80\\x8b、八进制 \\342\\200\\213、\\u200b / \\\\u200b、\\u{200b}、<U+200B>），再做分析",
+                    "default": false
+                },
+                "lossy": lossy_property(),
+                "html_decode": {
+                    "type": "string",
+                    "enum": ["auto", "on", "off"],
+                    "description": "可选：还原网页源码中以转义形式写入的不可见字符（&#x200B;、&#8203;、&zwnj;，href/src 中的 %E2%80%8B，CSS 的 \\200B），并报告每处的原始写法。auto（默认）在输入像 HTML 或扩展名为 html/htm/svg/xml 等时启用",
+                    "default": "auto"
+                },
+                "reference_path": {
+                    "type": "string",
+                    "description": "可选：已知干净的参照文件（如文档的上游版本）。只分析参照中不存在的不可见字符，排除原有的软连字符与排版字符"
+                },
+                "reference_text": {
+                    "type": "string",
+                    "description": "可选：直接传入参照文本，与 reference_path 二选一"
+                },
+                "csv_path": {
+                    "type": "string",
+                    "description": "可选：把完整分布与位置数据导出为 CSV（codepoint,name,count,first_index,last_index），便于用表格或 pandas 处理；下标为应用过滤参数后的字符下标。写 zw-tmp://<name> 则存入临时工作区"
                 }
             }
         }),
+        annotations: Some(ToolAnnotations::writes_files().open_world()),
+        examples: help::examples("zw_analyze"),
+    }
+}
+
+/// zw_decode 可用的解码方案：内置方案 + 每个编码预设的 key
+fn decode_methods() -> Vec<String> {
+    let mut methods: Vec<String> = ["auto", "unicode_tags", "steganographr", "binary", "330k", "morse"]
+        .iter()
+        .map(|m| m.to_string())
+        .collect();
+    for (key, _) in engine::encoding_presets() {
+        if !methods.contains(&key) {
+            methods.push(key);
+        }
+    }
+    methods
+}
+
+/// 参数 method（默认 auto）；不是已知方案或预设时返回 UNSUPPORTED_METHOD
+fn decode_method(args: &Value) -> Result<&str, ToolCallResult> {
+    let method = get_str(args, "method").unwrap_or("auto");
+    let methods = decode_methods();
+    if !methods.iter().any(|m| m == method) {
+        return Err(ToolCallResult::failure(
+            ErrorCode::UnsupportedMethod,
+            format!("未知解码方法: {}（可用: {}）", method, methods.join(", ")),
+        ));
     }
+    Ok(method)
 }
 
 fn tool_decode() -> Tool {
+    let methods = decode_methods();
     Tool {
         name: "zw_decode".to_string(),
         description: "自动解码文本中隐藏的零宽字符隐写信息。支持多种编码方案：二进制映射、N进制映射(330k)、Steganographr、Unicode Tags、莫尔斯码等。会自动尝试所有方案并按置信度排序返回结果。适用于CTF解题。支持直接传入文本或指定文件路径。".to_string(),
@@ -55,396 +225,5707 @@ fn tool_decode() -> Tool {
                     "type": "string",
                     "description": "包含零宽字符隐写信息的文本。与 file_path 二选一"
                 },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "max_output_chars": {
+                    "type": "integer",
+                    "description": "可选：响应最大字符数，超出部分截断并把完整输出写入临时文件（默认 50000，0 为不限制）"
+                },
+                "include_categories": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "可选：只考虑这些分类的零宽字符（如 [\"核心零宽\"]），分类名见 zw_list_chars"
+                },
+                "exclude_categories": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "可选：完全忽略这些分类的零宽字符（如 [\"变体选择器\"]，避免大量合法 VS16 干扰）"
+                },
+                "legit_zwnj": {
+                    "type": "string",
+                    "enum": ["exclude", "include"],
+                    "description": "夹在两个阿拉伯字母之间的单个 ZWNJ（波斯语等正字法用法）: exclude 不参与提取（默认），include 视为载荷"
+                },
                 "file_path": {
                     "type": "string",
                     "description": "包含零宽字符隐写信息的文件路径。与 text 二选一"
                 },
+                "report_hashes": report_hashes_property(),
+                "log_mode": {
+                    "type": "boolean",
+                    "description": "可选：日志模式。按原始字节读入，拼接被换行拆开的 UTF-8 多字节序列，并还原部分转义的形式（\\xe2\\x80\\x8b、八进制 \\342\\200\\213、\\u200b / \\\\u200b、\\u{200b}、<U+200B>），再做分析",
+                    "default": false
+                },
+                "lossy": lossy_property(),
+                "html_decode": {
+                    "type": "string",
+                    "enum": ["auto", "on", "off"],
+                    "description": "可选：还原网页源码中以转义形式写入的不可见字符（&#x200B;、&#8203;、&zwnj;，href/src 中的 %E2%80%8B，CSS 的 \\200B），并报告每处的原始写法。auto（默认）在输入像 HTML 或扩展名为 html/htm/svg/xml 等时启用",
+                    "default": "auto"
+                },
+                "reference_path": {
+                    "type": "string",
+                    "description": "可选：已知干净的参照文件（如文档的上游版本）。只分析参照中不存在的不可见字符，排除原有的软连字符与排版字符"
+                },
+                "reference_text": {
+                    "type": "string",
+                    "description": "可选：直接传入参照文本，与 reference_path 二选一"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "可选：解码时间上限（毫秒，默认 20000）；超时返回已找到的最佳候选并标记 truncated"
+                },
+                "budget": {
+                    "type": "string",
+                    "description": "可选：暴力解码预算。fast=快速（少量字符对/位宽），normal=默认，exhaustive=穷举（更多字符对、进制、比特偏移）",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "default": "normal"
+                },
+                "max_depth": {
+                    "type": "integer",
+                    "description": "可选：嵌套解码层数上限（默认 3，1=不递归）。解码结果中仍含零宽字符时继续解码下一层",
+                    "default": 3
+                },
+                "postprocess": {
+                    "type": "boolean",
+                    "description": "可选：对得分靠前的候选尝试后处理链（base64、hex、gzip、XOR、ROT 及配置的自定义命令，顺序与深度由配置文件 [postprocess] 决定），默认 true",
+                    "default": true
+                },
+                "threshold": {
+                    "type": "number",
+                    "description": "可选：候选得分门槛，得分高于此值才保留（默认 15）。调低可看到更多弱候选",
+                    "default": 15
+                },
+                "allow_binary": {
+                    "type": "boolean",
+                    "description": "可选：关闭可打印性过滤，保留非文本结果（如压缩/加密数据；8bit 二进制保留 0x80 以上字节）。通常需配合较低的 threshold",
+                    "default": false
+                },
+                "verbose": {
+                    "type": "boolean",
+                    "description": "可选：在结果末尾附上自动解码的逐次尝试明细（方案与参数、耗时、产生的候选或拒绝原因）及按方案族的汇总，用于排查漏解与调整尝试顺序",
+                    "default": false
+                },
+                "enumerate": {
+                    "type": "boolean",
+                    "description": "可选：穷举模式。不做得分门槛与可打印性过滤，按页列出每个解码尝试的原始输出（忽略 threshold / allow_binary，不提前结束）。用于载荷故意做成“怪异”形式、正确答案被评分过滤掉的情况",
+                    "default": false
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "可选（配合 enumerate）：从第几个解码尝试开始列出（从 0 开始）",
+                    "default": 0
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "可选（配合 enumerate）：本页最多列出的条数（默认 50，最多 500）；响应会给出下一页的 offset",
+                    "default": 50
+                },
                 "method": {
                     "type": "string",
-                    "description": "可选：指定解码方案。留空则自动尝试所有方案。可选值: auto, unicode_tags, steganographr, binary, 330k",
-                    "enum": ["auto", "unicode_tags", "steganographr", "binary", "330k"]
+                    "description": format!("可选：指定解码方案。留空则自动尝试所有方案；已知编码工具时可直接指定预设 key 跳过自动模式。可选值: {}", methods.join(", ")),
+                    "enum": methods
+                },
+                "password": password_property(),
+                "mapping": {
+                    "type": "object",
+                    "additionalProperties": { "type": "integer", "minimum": 0 },
+                    "description": "可选：显式指定码点→数字映射（如 {\"U+200B\": 0, \"U+200C\": 1, \"U+200D\": 2}），直接验证假设而不做暴力搜索；进制为最大数字+1，映射以外的字符忽略（可映射可见字符）。指定后忽略 method"
+                },
+                "group_size": {
+                    "type": "integer",
+                    "description": "可选（配合 mapping）：每组数字个数。省略时下标型字母表（hex/base64_index/letters/digits）取能表示字母表的最小分组，unicode/ascii 自动推断"
+                },
+                "alphabet": {
+                    "type": "string",
+                    "enum": ["unicode", "ascii", "hex", "base64_index", "letters", "digits"],
+                    "description": "可选（配合 mapping）：组值的解释方式。unicode=码点（默认），ascii=ASCII 码，hex=十六进制数字下标后按 hex 还原字节，base64_index=base64 字母表下标后按 base64 还原字节，letters=字母下标 (a=0)，digits=十进制数字",
+                    "default": "unicode"
+                },
+                "raw_output": {
+                    "type": "boolean",
+                    "description": "可选：原样回显解码结果。默认把解码文本自身包含的控制/方向/零宽字符写成 \\u{...}，防止载荷在输出中伪造或隐藏内容",
+                    "default": false
                 }
             }
         }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_decode"),
     }
 }
 
-fn tool_encode() -> Tool {
+fn tool_locate_flag() -> Tool {
     Tool {
-        name: "zw_encode".to_string(),
-        description: "将消息编码为零宽字符隐写文本。可选择不同编码方案，可指定载体文本将隐写信息嵌入其中。支持从文件读取载体文本，支持将编码结果写入文件。".to_string(),
+        name: "zw_locate_flag".to_string(),
+        description: "一次调用完成解题：分析零宽字符、自动解码（含分段、修复等后处理）、匹配 flag 格式（内置 flag/ctf/key{...} 含 leetspeak 写法，以及配置文件中的 flag_patterns），只返回找到的 flag 与得到它的方案链；找不到时返回结构化的失败诊断与下一步建议。LLM 客户端解零宽隐写题的首选入口。".to_string(),
         input_schema: json!({
             "type": "object",
             "properties": {
-                "message": {
+                "text": {
                     "type": "string",
-                    "description": "要隐藏的秘密消息"
+                    "description": "包含零宽字符隐写信息的文本。与 file_path 二选一"
                 },
-                "method": {
+                "text_base64": {
                     "type": "string",
-                    "description": "编码方法: binary, steganographr, tags, 330k",
-                    "enum": ["binary", "steganographr", "tags", "330k"],
-                    "default": "binary"
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
                 },
-                "cover_text": {
+                "url": url_property(),
+                "file_path": {
                     "type": "string",
-                    "description": "可选：载体文本，隐写信息会嵌入其中。与 cover_file 二选一",
-                    "default": ""
+                    "description": "包含零宽字符隐写信息的文件路径。与 text 二选一"
                 },
-                "cover_file": {
+                "report_hashes": report_hashes_property(),
+                "flag_pattern": {
                     "type": "string",
-                    "description": "可选：载体文本的文件路径。与 cover_text 二选一"
+                    "description": "可选：本次额外使用的 flag 正则（如 DASCTF\\{[0-9a-f]+\\}）"
                 },
-                "output_path": {
+                "include_categories": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "可选：只考虑这些分类的零宽字符，分类名见 zw_list_chars"
+                },
+                "exclude_categories": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "可选：完全忽略这些分类的零宽字符"
+                },
+                "log_mode": {
+                    "type": "boolean",
+                    "description": "可选：日志模式。按原始字节读入，拼接被换行拆开的 UTF-8 多字节序列，并还原部分转义的形式（\\xe2\\x80\\x8b、八进制 \\342\\200\\213、\\u200b / \\\\u200b、\\u{200b}、<U+200B>），再做分析",
+                    "default": false
+                },
+                "lossy": lossy_property(),
+                "html_decode": {
+                    "type": "string",
+                    "enum": ["auto", "on", "off"],
+                    "description": "可选：还原网页源码中以转义形式写入的不可见字符（&#x200B;、&#8203;、&zwnj;，href/src 中的 %E2%80%8B，CSS 的 \\200B），并报告每处的原始写法。auto（默认）在输入像 HTML 或扩展名为 html/htm/svg/xml 等时启用",
+                    "default": "auto"
+                },
+                "reference_path": {
                     "type": "string",
-                    "description": "可选：将编码结果写入指定文件路径"
+                    "description": "可选：已知干净的参照文件（如文档的上游版本）。只分析参照中不存在的不可见字符，排除原有的软连字符与排版字符"
+                },
+                "reference_text": {
+                    "type": "string",
+                    "description": "可选：直接传入参照文本，与 reference_path 二选一"
+                },
+                "budget": {
+                    "type": "string",
+                    "description": "可选：暴力解码预算，默认 normal；找不到时可改用 exhaustive",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "default": "normal"
+                },
+                "password": password_property(),
+                "max_depth": {
+                    "type": "integer",
+                    "description": "可选：嵌套解码层数上限（默认 3，1=不递归）。解码结果中仍含零宽字符时继续解码下一层",
+                    "default": 3
+                },
+                "postprocess": {
+                    "type": "boolean",
+                    "description": "可选：对得分靠前的候选尝试后处理链（base64、hex、gzip、XOR、ROT 及配置的自定义命令，顺序与深度由配置文件 [postprocess] 决定），默认 true",
+                    "default": true
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "可选：解码时间上限（毫秒，默认 20000）"
+                },
+                "raw_output": {
+                    "type": "boolean",
+                    "description": "可选：原样回显解码结果。默认把解码文本自身包含的控制/方向/零宽字符写成 \\u{...}，防止载荷在输出中伪造或隐藏内容",
+                    "default": false
                 }
-            },
-            "required": ["message"]
+            }
         }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_locate_flag"),
     }
 }
 
-fn tool_dump_raw() -> Tool {
+fn tool_explain() -> Tool {
+    let methods = decode_methods();
     Tool {
-        name: "zw_dump_raw".to_string(),
-        description: "导出文本中所有零宽字符的原始序列，显示每个字符的位置、Unicode码点和名称。用于调试和手动分析。支持直接传入文本或指定文件路径。".to_string(),
+        name: "zw_explain".to_string(),
+        description: "解释一次成功解码所用的方案：实际使用的映射表（码点 → 比特/数字）、分组大小与位序，并以第一个解出的字符为例展示它由哪些码点、经过怎样的计算得到。输出为 Markdown，可直接放进 write-up。".to_string(),
         input_schema: json!({
             "type": "object",
             "properties": {
                 "text": {
                     "type": "string",
-                    "description": "要分析的文本。与 file_path 二选一"
+                    "description": "包含零宽字符隐写信息的文本。与 file_path 二选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
                 },
+                "url": url_property(),
                 "file_path": {
                     "type": "string",
-                    "description": "要分析的文件路径。与 text 二选一"
+                    "description": "包含零宽字符隐写信息的文件路径。与 text 二选一"
+                },
+                "report_hashes": report_hashes_property(),
+                "method": {
+                    "type": "string",
+                    "description": "可选：解码方案，同 zw_decode 的 method（默认 auto）",
+                    "enum": methods
+                },
+                "password": password_property(),
+                "rank": {
+                    "type": "integer",
+                    "description": "可选：解释第几个候选结果（按置信度排序，从 1 开始）",
+                    "default": 1
+                },
+                "mapping": {
+                    "type": "object",
+                    "additionalProperties": { "type": "integer", "minimum": 0 },
+                    "description": "可选：显式码点→数字映射，同 zw_decode 的 mapping（可配合 group_size、alphabet）"
+                },
+                "group_size": {
+                    "type": "integer",
+                    "description": "可选（配合 mapping）：每组数字个数"
+                },
+                "alphabet": {
+                    "type": "string",
+                    "enum": ["unicode", "ascii", "hex", "base64_index", "letters", "digits"],
+                    "description": "可选（配合 mapping）：组值的解释方式"
+                },
+                "budget": {
+                    "type": "string",
+                    "description": "可选：暴力解码预算",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "default": "normal"
+                },
+                "raw_output": {
+                    "type": "boolean",
+                    "description": "可选：原样回显解码结果。默认把解码文本自身包含的控制/方向/零宽字符写成 \\u{...}，防止载荷在输出中伪造或隐藏内容",
+                    "default": false
                 }
             }
         }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_explain"),
     }
 }
 
-fn tool_list_chars() -> Tool {
+fn tool_decode_detail() -> Tool {
+    let methods = decode_methods();
     Tool {
-        name: "zw_list_chars".to_string(),
-        description: "列出所有已知的零宽/不可见Unicode字符（共182个），包括码点、名称、分类。".to_string(),
+        name: "zw_decode_detail".to_string(),
+        description: "按 ID 返回 zw_decode 某个候选的完整细节：元数据、码点映射、原始比特/数字流，以及参与解码的字符在原文中的位置。先用 zw_decode 浏览摘要，再对感兴趣的候选追问细节；文本与过滤参数需与 zw_decode 调用一致。".to_string(),
         input_schema: json!({
             "type": "object",
-            "properties": {},
-            "required": []
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "zw_decode 输出中候选的 ID"
+                },
+                "text": {
+                    "type": "string",
+                    "description": "包含零宽字符隐写信息的文本。与 file_path 二选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "包含零宽字符隐写信息的文件路径。与 text 二选一"
+                },
+                "report_hashes": report_hashes_property(),
+                "method": {
+                    "type": "string",
+                    "description": "可选：解码方案，同 zw_decode 的 method（默认 auto）",
+                    "enum": methods
+                },
+                "password": password_property(),
+                "budget": {
+                    "type": "string",
+                    "description": "可选：暴力解码预算，同 zw_decode",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "default": "normal"
+                },
+                "log_mode": {
+                    "type": "boolean",
+                    "description": "可选：日志模式。按原始字节读入，拼接被换行拆开的 UTF-8 多字节序列，并还原部分转义的形式（\\xe2\\x80\\x8b、八进制 \\342\\200\\213、\\u200b / \\\\u200b、\\u{200b}、<U+200B>），再做分析",
+                    "default": false
+                },
+                "lossy": lossy_property(),
+                "html_decode": {
+                    "type": "string",
+                    "enum": ["auto", "on", "off"],
+                    "description": "可选：还原网页源码中以转义形式写入的不可见字符（&#x200B;、&#8203;、&zwnj;，href/src 中的 %E2%80%8B，CSS 的 \\200B），并报告每处的原始写法。auto（默认）在输入像 HTML 或扩展名为 html/htm/svg/xml 等时启用",
+                    "default": "auto"
+                },
+                "reference_path": {
+                    "type": "string",
+                    "description": "可选：已知干净的参照文件（如文档的上游版本）。只分析参照中不存在的不可见字符，排除原有的软连字符与排版字符"
+                },
+                "reference_text": {
+                    "type": "string",
+                    "description": "可选：直接传入参照文本，与 reference_path 二选一"
+                },
+                "include_categories": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "可选：只考虑这些分类的零宽字符，同 zw_decode"
+                },
+                "exclude_categories": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "可选：忽略这些分类的零宽字符，同 zw_decode"
+                },
+                "legit_zwnj": {
+                    "type": "string",
+                    "enum": ["exclude", "include"],
+                    "description": "可选：合法 ZWNJ 的处理方式，同 zw_decode"
+                },
+                "threshold": {
+                    "type": "number",
+                    "description": "可选：候选得分门槛，同 zw_decode"
+                },
+                "allow_binary": {
+                    "type": "boolean",
+                    "description": "可选：关闭可打印性过滤，同 zw_decode",
+                    "default": false
+                },
+                "raw_output": {
+                    "type": "boolean",
+                    "description": "可选：原样回显解码结果。默认把解码文本自身包含的控制/方向/零宽字符写成 \\u{...}，防止载荷在输出中伪造或隐藏内容",
+                    "default": false
+                }
+            },
+            "required": ["id"]
         }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_decode_detail"),
     }
 }
 
-fn tool_list_presets() -> Tool {
+fn tool_encode() -> Tool {
     Tool {
-        name: "zw_list_presets".to_string(),
-        description: "列出所有支持的编码预设方案，包括330k、Steganographr、StegCloak、Binary等。".to_string(),
+        name: "zw_encode".to_string(),
+        description: "将消息编码为零宽字符隐写文本。可选择不同编码方案，可指定载体文本将隐写信息嵌入其中。支持从文件读取载体文本，支持将编码结果写入文件。payloads 可把多段独立消息（如诱饵 + 真 flag）分别嵌入载体的不同位置。".to_string(),
         input_schema: json!({
             "type": "object",
-            "properties": {},
+            "properties": {
+                "message": {
+                    "type": "string",
+                    "description": "要隐藏的秘密消息"
+                },
+                "payloads": {
+                    "type": "array",
+                    "description": "可选：多载荷模式，替代 message。每段独立编码并放入载体的不同位置（或依次放入 {{ZW}} 标记处）",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "label": { "type": "string", "description": "标签（仅用于报告）" },
+                            "message": { "type": "string" },
+                            "method": { "type": "string", "enum": ["binary", "steganographr", "tags", "330k", "morse", "stegcloak"] }
+                        },
+                        "required": ["message"]
+                    }
+                },
+                "method": {
+                    "type": "string",
+                    "description": "编码方法: binary, steganographr, tags, 330k, morse（莫尔斯码，只支持字母、数字与常用标点）, stegcloak（StegCloak 格式，给出 password 时加密）",
+                    "enum": ["binary", "steganographr", "tags", "330k", "morse", "stegcloak"],
+                    "default": "binary"
+                },
+                "cover_text": {
+                    "type": "string",
+                    "description": "可选：载体文本，隐写信息默认嵌入中点；含 {{ZW}} 标记时精确注入到标记处（多个标记则依次切分载荷）。与 cover_file 二选一",
+                    "default": ""
+                },
+                "cover_file": {
+                    "type": "string",
+                    "description": "可选：载体文本的文件路径，同样支持 {{ZW}} 标记。与 cover_text 二选一"
+                },
+                "report_hashes": report_hashes_property(),
+                "density": {
+                    "type": "number",
+                    "description": "可选：每 100 个可见字符最多允许的不可见字符数；载荷会均匀打散到载体各处，容量不足时报错"
+                },
+                "split_documents": {
+                    "type": "boolean",
+                    "description": "配合 density：容量不足时把载荷拆到多份载体副本中（output_path 会加 _1、_2 后缀）"
+                },
+                "password": {
+                    "type": "string",
+                    "description": "可选：method=stegcloak 或 emulate=stegcloak 时的口令，给出则按 StegCloak 方式加密（AES-256-CTR），不给则只压缩"
+                },
+                "integrity": {
+                    "type": "boolean",
+                    "description": "可选：配合 password，附加 HMAC-SHA256 完整性校验（解码时口令错误会明确报错）",
+                    "default": false
+                },
+                "emulate": {
+                    "type": "string",
+                    "enum": emulate::EmulatedTool::names(),
+                    "description": "可选：按指定第三方工具（330k 网页版、Steganographr、StegCloak、zero-width-lib）的公开源码重新实现的编码方式，用于出需要被该公开解码器解出的题目。这是重新实现，尚未与原工具的实际输出逐字节核对。指定后忽略 method/placement/density/标记"
+                },
+                "placement": {
+                    "type": "string",
+                    "enum": ["default", "code"],
+                    "description": "可选：code = 源代码载体模式，载荷分段放入注释（及字符串字面量），绝不插在标识符之间，嵌入后文件仍可编译/运行"
+                },
+                "language": {
+                    "type": "string",
+                    "enum": ["auto", "c_like", "javascript", "python", "shell", "sql", "html"],
+                    "description": "配合 placement=code：载体语言族，auto 按 cover_file 扩展名推断",
+                    "default": "auto"
+                },
+                "include_strings": {
+                    "type": "boolean",
+                    "description": "配合 placement=code：除注释外也放入字符串字面量（会改变运行时字符串内容）；没有注释时总会使用字符串",
+                    "default": false
+                },
+                "max_output_chars": {
+                    "type": "integer",
+                    "description": "可选：响应最大字符数，超出部分截断并把完整输出写入临时文件（默认 50000，0 为不限制）"
+                },
+                "include_encoded_text": {
+                    "type": "boolean",
+                    "description": "inline 的旧名称，保留兼容"
+                },
+                "output_path": {
+                    "type": "string",
+                    "description": "可选：将编码结果写入指定文件路径；写 zw-tmp://<name> 则存入临时工作区并返回资源 URI（split_documents 时每份文档各一个）"
+                },
+                "output_format": {
+                    "type": "string",
+                    "enum": ["text", "repr", "base64"],
+                    "description": "可选：内联返回结果的格式。text=原文，repr=转义显示（不可见字符可见），base64=UTF-8 的 base64（防止客户端剥离零宽字符）；默认同时给出 repr 与原文"
+                },
+                "inline": {
+                    "type": "boolean",
+                    "description": "可选：是否在响应中内联返回编码文本，默认 true（写入 output_path 时也返回）；载体很大且已写文件时可设为 false"
+                },
+                "sidecar": {
+                    "type": "boolean",
+                    "description": "可选：把编码描述（字符集、码点→数字映射、位序、插入方式与载荷位置的 JSON，响应中总会附上）另写到输出文件旁的 <output_path>.zw.json；未指定 output_path 时写入临时工作区",
+                    "default": false
+                },
+                "avoid_leading_bom": {
+                    "type": "string",
+                    "enum": ["warn", "shift", "prefix", "off"],
+                    "description": "可选：编码结果以 U+FEFF 开头时（部分编辑器会当作 BOM 删掉）的处理。warn（默认）写文件时提示；shift 把开头的不可见字符移到第一个可见字符之后；prefix 在开头加一个可见字符；off 不处理",
+                    "default": "warn"
+                },
+                "bom_prefix": {
+                    "type": "string",
+                    "description": "可选：avoid_leading_bom=prefix 时加在开头的可见字符，默认 \".\""
+                }
+            },
             "required": []
         }),
+        annotations: Some(ToolAnnotations::writes_files()),
+        examples: help::examples("zw_encode"),
     }
 }
 
-// ============================================================
-// 工具执行
-// ============================================================
-
-/// 根据工具名称和参数执行工具
-pub fn call_tool(name: &str, args: &Value) -> ToolCallResult {
-    match name {
-        "zw_analyze" => exec_analyze(args),
-        "zw_decode" => exec_decode(args),
-        "zw_encode" => exec_encode(args),
-        "zw_dump_raw" => exec_dump_raw(args),
-        "zw_list_chars" => exec_list_chars(),
-        "zw_list_presets" => exec_list_presets(),
-        _ => ToolCallResult::error(format!("未知工具: {}", name)),
+fn tool_mass_encode() -> Tool {
+    Tool {
+        name: "zw_mass_encode".to_string(),
+        description: "批量编码：一次调用处理多个编码任务（每个任务的参数同 zw_encode），内部并行执行，返回成功/失败汇总表。适合为检测器批量生成测试向量。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "jobs": {
+                    "type": "array",
+                    "description": "编码任务列表。每项为 zw_encode 的参数对象，常用 message、cover_file / cover_text、output_path、method；未指定 output_path 时写入临时工作区 zw-tmp://vector_<序号>.txt",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "message": { "type": "string" },
+                            "cover_file": { "type": "string" },
+                            "cover_text": { "type": "string" },
+                            "output_path": { "type": "string" },
+                            "method": { "type": "string", "enum": ["binary", "steganographr", "tags", "330k", "morse", "stegcloak"] },
+                            "password": { "type": "string", "description": "method=stegcloak 时的加密口令，同 zw_encode" },
+                            "integrity": { "type": "boolean", "description": "配合 password 附加 HMAC 校验，同 zw_encode" }
+                        },
+                        "required": ["message"]
+                    }
+                },
+                "defaults": {
+                    "type": "object",
+                    "description": "可选：所有任务共用的参数（如 method、cover_file、emulate），任务中同名参数优先"
+                },
+                "concurrency": {
+                    "type": "integer",
+                    "description": "可选：并行线程数（默认为 CPU 核数，最多 16）"
+                }
+            },
+            "required": ["jobs"]
+        }),
+        annotations: Some(ToolAnnotations::writes_files()),
+        examples: help::examples("zw_mass_encode"),
     }
 }
 
-fn get_str<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
-    args.get(key).and_then(|v| v.as_str())
-}
-
-/// 从参数中获取文本，支持 text 直传 或 file_path 文件导入
-/// 自动尝试多种编码: UTF-8, UTF-8 BOM, UTF-16 LE/BE, GBK, Latin-1
-fn resolve_text(args: &Value) -> Result<String, ToolCallResult> {
-    // 优先使用 file_path
-    if let Some(path_str) = get_str(args, "file_path") {
-        return read_file_auto(path_str);
-    }
-    // 其次使用 text
-    if let Some(t) = get_str(args, "text") {
-        return Ok(t.to_string());
+fn tool_random_cover() -> Tool {
+    Tool {
+        name: "zw_random_cover".to_string(),
+        description: "生成看起来自然的载体文本（lorem ipsum / 英文模板段落 / 中文模板段落），长度按编码方案与消息所需容量自动估算，可直接作为 zw_encode 的 cover_text。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "style": {
+                    "type": "string",
+                    "enum": ["lorem", "english", "chinese"],
+                    "description": "载体风格，默认 english"
+                },
+                "theme": {
+                    "type": "string",
+                    "description": "可选：主题词，替换到模板句子中（如 季度报告、the release）"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "可选：将要隐藏的消息，用于估算所需载体长度"
+                },
+                "capacity": {
+                    "type": "integer",
+                    "description": "可选：未给出 message 时，按该 ASCII 字符数估算容量"
+                },
+                "method": {
+                    "type": "string",
+                    "enum": ["binary", "steganographr", "tags", "330k", "morse", "stegcloak"],
+                    "description": "估算容量所用的编码方法，默认 binary"
+                },
+                "length": {
+                    "type": "integer",
+                    "description": "可选：直接指定载体最少字符数（覆盖自动估算）"
+                },
+                "seed": {
+                    "type": "integer",
+                    "description": "可选：随机种子，相同种子生成相同文本"
+                }
+            },
+            "required": []
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: help::examples("zw_random_cover"),
     }
-    Err(ToolCallResult::error("缺少参数: 请提供 text 或 file_path"))
 }
 
-/// 自动检测编码读取文件
-fn read_file_auto(path_str: &str) -> Result<String, ToolCallResult> {
-    let path = Path::new(path_str);
-    if !path.exists() {
-        return Err(ToolCallResult::error(format!("文件不存在: {}", path_str)));
-    }
-
-    // 先读取原始字节
-    let raw = match fs::read(path) {
-        Ok(b) => b,
-        Err(e) => return Err(ToolCallResult::error(format!("读取文件失败: {}", e))),
-    };
-
-    // 检测 BOM 并尝试对应编码
-    if raw.starts_with(&[0xEF, 0xBB, 0xBF]) {
+fn tool_difficulty() -> Tool {
+    Tool {
+        name: "zw_difficulty".to_string(),
+        description: "估计一段零宽隐写文本在没有提示时的解题难度，供出题人校准 zw_encode 生成的题目：统计字符集吻合的预设数、自动解码候选数与最佳结果的领先程度、公开工具能否直接解出、是否疑似加密或二次编码，给出分项得分与难度等级。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "要评估的题目文本。与 file_path 二选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "题目文件路径。与 text 二选一"
+                },
+                "report_hashes": report_hashes_property(),
+                "budget": {
+                    "type": "string",
+                    "description": "可选：评估时的解码预算，模拟解题者的搜索力度",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "default": "normal"
+                },
+                "password": password_property(),
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "可选：解码时间上限（毫秒）"
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_difficulty"),
+    }
+}
+
+fn tool_dump_raw() -> Tool {
+    Tool {
+        name: "zw_dump_raw".to_string(),
+        description: "导出文本中所有零宽字符的原始序列，显示每个字符的位置、Unicode码点和名称。可选分组视图：按候选分组大小切块，列出每块在候选映射下的数字、数值和字符，便于手动完成解码。用于调试和手动分析。支持直接传入文本或指定文件路径。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "要分析的文本。与 file_path 二选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "max_output_chars": {
+                    "type": "integer",
+                    "description": "可选：响应最大字符数，超出部分截断并把完整输出写入临时文件（默认 50000，0 为不限制）"
+                },
+                "file_path": {
+                    "type": "string",
+                    "description": "要分析的文件路径。与 text 二选一"
+                },
+                "report_hashes": report_hashes_property(),
+                "log_mode": {
+                    "type": "boolean",
+                    "description": "可选：日志模式。按原始字节读入，拼接被换行拆开的 UTF-8 多字节序列，并还原部分转义的形式（\\xe2\\x80\\x8b、八进制 \\342\\200\\213、\\u200b / \\\\u200b、\\u{200b}、<U+200B>），再做分析",
+                    "default": false
+                },
+                "lossy": lossy_property(),
+                "html_decode": {
+                    "type": "string",
+                    "enum": ["auto", "on", "off"],
+                    "description": "可选：还原网页源码中以转义形式写入的不可见字符（&#x200B;、&#8203;、&zwnj;，href/src 中的 %E2%80%8B，CSS 的 \\200B），并报告每处的原始写法。auto（默认）在输入像 HTML 或扩展名为 html/htm/svg/xml 等时启用",
+                    "default": "auto"
+                },
+                "reference_path": {
+                    "type": "string",
+                    "description": "可选：已知干净的参照文件（如文档的上游版本）。只分析参照中不存在的不可见字符，排除原有的软连字符与排版字符"
+                },
+                "reference_text": {
+                    "type": "string",
+                    "description": "可选：直接传入参照文本，与 reference_path 二选一"
+                },
+                "annotate": {
+                    "type": "boolean",
+                    "description": "可选：附加分组视图（自动猜测分组大小和映射）",
+                    "default": false
+                },
+                "group_size": {
+                    "type": "integer",
+                    "description": "可选：指定分组视图的分组大小（指定后自动启用分组视图，最大 64）",
+                    "minimum": 1,
+                    "maximum": 64
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "可选：从第几个零宽字符开始列出（从 0 开始），配合 limit 分页",
+                    "default": 0
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "可选：本页最多列出的零宽字符数（默认全部）；响应会给出下一页的 offset"
+                },
+                "stream": {
+                    "type": "boolean",
+                    "description": "可选：请求带 _meta.progressToken 时，把序列按 500 行一块通过 notifications/progress 的 message 分块推送，最终响应只含摘要",
+                    "default": false
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_dump_raw"),
+    }
+}
+
+fn tool_hexdump() -> Tool {
+    Tool {
+        name: "zw_hexdump".to_string(),
+        description: "对文件（或 url、base64 字节）中每段零宽字符序列周围的字节范围做经典 hex+ASCII 导出，显示磁盘上的真实字节（UTF-8/UTF-16 编码、周围的空字节等），命中字节用 ** 标注。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "file_path": {
+                    "type": "string",
+                    "description": "要导出的文件路径"
+                },
+                "report_hashes": report_hashes_property(),
+                "text": {
+                    "type": "string",
+                    "description": "可选：直接传入文本，按 UTF-8 字节导出"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的原始字节"
+                },
+                "url": url_property(),
+                "context": {
+                    "type": "integer",
+                    "description": "可选：每段命中前后显示的上下文字节数（最多 4096）",
+                    "minimum": 0,
+                    "maximum": 4096,
+                    "default": 16
+                },
+                "max_regions": {
+                    "type": "integer",
+                    "description": "可选：最多显示的区域数",
+                    "default": 20
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_hexdump"),
+    }
+}
+
+fn tool_extract_file() -> Tool {
+    Tool {
+        name: "zw_extract_file".to_string(),
+        description: "从零宽层雕刻嵌入的二进制文件：按原始字节解释零宽序列（二进制字符对、N 进制，两种位序，保留 0 字节），并把 base64/hex 形式的解码结果还原为字节，按文件头识别 ZIP/PNG/GZIP/ELF/JPEG/GIF/PDF/7Z/RAR/BZIP2，截出完整文件写到磁盘，报告类型、大小与 MD5/SHA256。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "包含零宽字符隐写信息的文本。与 file_path 二选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "包含零宽字符隐写信息的文件路径。与 text 二选一"
+                },
+                "report_hashes": report_hashes_property(),
+                "output_dir": {
+                    "type": "string",
+                    "description": "可选：提取文件的保存目录（不存在时自动创建），文件名为 carved_<序号>.<扩展名>；默认写入临时工作区"
+                },
+                "budget": {
+                    "type": "string",
+                    "description": "可选：文本解码部分的预算（用于查找 base64/hex 形式的载荷），默认 normal",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "default": "normal"
+                },
+                "password": password_property()
+            }
+        }),
+        annotations: Some(ToolAnnotations::writes_files().open_world()),
+        examples: help::examples("zw_extract_file"),
+    }
+}
+
+fn tool_entropy_visual() -> Tool {
+    Tool {
+        name: "zw_entropy_visual".to_string(),
+        description: "按固定字节块统计不可见字符密度，输出整份文本的 sparkline 与密度最高的区域（字节偏移、行号），用于在大文件中先定位载荷所在位置，再做 zw_hexdump / zw_dump_raw 等精确导出。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "要统计的文本。与 file_path 二选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "要统计的文件路径。与 text 二选一"
+                },
+                "report_hashes": report_hashes_property(),
+                "chunk_size": {
+                    "type": "integer",
+                    "description": "可选：每块字节数（按 UTF-8 计）",
+                    "default": 1024
+                },
+                "width": {
+                    "type": "integer",
+                    "description": "可选：sparkline 最大宽度，块数更多时相邻块合并（取最大值）",
+                    "default": 80
+                },
+                "top": {
+                    "type": "integer",
+                    "description": "可选：列出密度最高的区域数",
+                    "default": 5
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_entropy_visual"),
+    }
+}
+
+fn tool_grep() -> Tool {
+    Tool {
+        name: "zw_grep".to_string(),
+        description: "搜索指定码点（或某一分类，如 \"方向控制\"）的每一处出现，返回文件内字节偏移（按读入时的编码还原，可直接交给 zw_hexdump；直接传入的文本给出 UTF-8 字节偏移）、字符索引、行/列号和上下文片段。支持文本、文件或目录（递归）。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "codepoints": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "要搜索的码点列表，如 [\"U+200B\", \"200C\", \"0xFEFF\"]。与 category 至少提供一个"
+                },
+                "category": {
+                    "type": "string",
+                    "description": "要搜索的字符分类，如 核心零宽、方向控制、变体选择器、Unicode Tags"
+                },
+                "text": {
+                    "type": "string",
+                    "description": "要搜索的文本。与 file_path / dir_path 三选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "要搜索的文件路径"
+                },
+                "report_hashes": report_hashes_property(),
+                "dir_path": {
+                    "type": "string",
+                    "description": "要递归搜索的目录路径（跳过隐藏目录）"
+                },
+                "context": {
+                    "type": "integer",
+                    "description": "可选：上下文片段前后各显示的字符数（最多 200）",
+                    "minimum": 0,
+                    "maximum": 200,
+                    "default": 10
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "可选：最多返回的命中数",
+                    "default": 200
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_grep"),
+    }
+}
+
+fn tool_git_scan() -> Tool {
+    Tool {
+        name: "zw_git_scan".to_string(),
+        description: "扫描 git 仓库中的不可见字符：默认扫描工作区（遵守 .gitignore）并用 git blame 找到引入该行的提交；指定 range（如 main..HEAD）时只检查该范围内提交新增的行，报告文件、hunk 和提交作者。可作为合并前的安全门禁。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "repo_path": {
+                    "type": "string",
+                    "description": "git 工作区路径"
+                },
+                "range": {
+                    "type": "string",
+                    "description": "可选：提交范围，如 main..HEAD、v1.0..v1.1 或单个提交之后的 abc123..；不填则扫描当前工作区"
+                },
+                "blame": {
+                    "type": "boolean",
+                    "description": "工作区模式下是否用 git blame 查找引入提交",
+                    "default": true
+                },
+                "include_categories": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "可选：只报告这些分类的字符"
+                },
+                "exclude_categories": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "可选：忽略这些分类的字符（如 变体选择器）"
+                },
+                "max_findings": {
+                    "type": "integer",
+                    "description": "最多报告的行数",
+                    "default": 200
+                },
+                "fail_on_findings": {
+                    "type": "boolean",
+                    "description": "有发现时以错误返回，便于作为门禁",
+                    "default": false
+                }
+            },
+            "required": ["repo_path"]
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: help::examples("zw_git_scan"),
+    }
+}
+
+fn tool_summarize_scan() -> Tool {
+    Tool {
+        name: "zw_summarize_scan".to_string(),
+        description: "批量扫描汇总：递归扫描目录（或一组文件），对每个含不可见字符的文件做快速分析与解码，只输出聚合结果——文件总数、有发现的文件数、按字符分类统计、检测到的主要编码方案，以及按可疑程度排序的分诊列表（解码出 flag 或通过校验的排最前）。适合大规模扫描后先整体判断再逐个深入。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "dir_path": {
+                    "type": "string",
+                    "description": "要递归扫描的目录（跳过隐藏目录和 target）。与 file_paths 至少提供一个"
+                },
+                "file_paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "要扫描的文件路径列表"
+                },
+                "report_hashes": report_hashes_property(),
+                "max_files": {
+                    "type": "integer",
+                    "description": "可选：最多扫描的文件数，默认 2000",
+                    "default": 2000
+                },
+                "top": {
+                    "type": "integer",
+                    "description": "可选：分诊列表显示的文件数，默认 20",
+                    "default": 20
+                },
+                "budget": {
+                    "type": "string",
+                    "description": "可选：每个文件的解码预算，默认 fast；总耗时受 timeout_ms 限制，超时后剩余文件只做统计",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "default": "fast"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "可选：整个扫描的解码时间上限（毫秒）"
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: help::examples("zw_summarize_scan"),
+    }
+}
+
+fn tool_strip() -> Tool {
+    Tool {
+        name: "zw_strip".to_string(),
+        description: "清理文本中的零宽/不可见字符，支持多种策略：删除全部、保留合法 emoji 序列（ZWJ/VS16/以 CANCEL TAG 结束的旗帜 Tag 序列）、仅删除 BiDi 方向控制、替换为可见占位符。支持文本或文件输入，可写入输出文件。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "要清理的文本。与 file_path 二选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "max_output_chars": {
+                    "type": "integer",
+                    "description": "可选：响应最大字符数，超出部分截断并把完整输出写入临时文件（默认 50000，0 为不限制）"
+                },
+                "file_path": {
+                    "type": "string",
+                    "description": "要清理的文件路径。与 text 二选一"
+                },
+                "report_hashes": report_hashes_property(),
+                "policy": {
+                    "type": "string",
+                    "description": "清理策略: all=删除全部, keep_emoji=保留合法 emoji 序列, bidi_only=仅删除方向控制字符, placeholder=替换为可见占位符",
+                    "enum": ["all", "keep_emoji", "bidi_only", "placeholder"],
+                    "default": "all"
+                },
+                "placeholder": {
+                    "type": "string",
+                    "description": "可选：placeholder 策略使用的占位符，默认显示为 [U+XXXX]"
+                },
+                "output_path": {
+                    "type": "string",
+                    "description": "可选：将清理结果写入指定文件路径；写 zw-tmp://<name> 则存入临时工作区并返回资源 URI"
+                },
+                "output_format": {
+                    "type": "string",
+                    "enum": ["text", "repr", "base64"],
+                    "description": "可选：内联返回结果的格式。text=原文（默认），repr=转义显示，base64=UTF-8 的 base64"
+                },
+                "inline": {
+                    "type": "boolean",
+                    "description": "可选：是否在响应中内联返回结果；默认未指定 output_path 时返回，已写入文件时省略"
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::writes_files().open_world()),
+        examples: help::examples("zw_strip"),
+    }
+}
+
+fn tool_annotate() -> Tool {
+    Tool {
+        name: "zw_annotate".to_string(),
+        description: "生成标注副本（Markdown）：每个不可见字符替换为行内脚注标记 [^n]，附录逐条列出码点、名称、所在行列，以及它在最佳解码结果中的作用（第几组第几位、解出哪个字符）；另附字符汇总表。写入 output_path，便于审阅者附到工单。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "要标注的文本"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "可选：要标注的文件路径"
+                },
+                "report_hashes": report_hashes_property(),
+                "output_path": {
+                    "type": "string",
+                    "description": "可选：Markdown 输出路径；默认写入临时工作区 zw-tmp://annotated.md"
+                },
+                "decode": {
+                    "type": "boolean",
+                    "description": "可选：是否自动解码并在附录中说明每个字符的作用，默认 true；只需要码点清单时可关闭",
+                    "default": true
+                },
+                "id": {
+                    "type": "string",
+                    "description": "可选：用 zw_decode 输出中的候选 ID 指定解释所依据的结果，默认取最佳结果"
+                },
+                "method": {
+                    "type": "string",
+                    "description": "可选：解码方法，同 zw_decode 的 method，默认 auto"
+                },
+                "password": password_property(),
+                "budget": {
+                    "type": "string",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "description": "可选：解码预算，默认 normal"
+                }
+            },
+            "required": []
+        }),
+        annotations: Some(ToolAnnotations::writes_files().open_world()),
+        examples: help::examples("zw_annotate"),
+    }
+}
+
+fn tool_segments() -> Tool {
+    Tool {
+        name: "zw_segments".to_string(),
+        description: "列出引擎使用的零宽字符分段（按可见字符分割）：每段的位置、长度、字符集，以及紧邻的前后可见文本。用于查看分段二进制等方案的推理依据。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "要分析的文本。与 file_path 二选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "要分析的文件路径。与 text 二选一"
+                },
+                "report_hashes": report_hashes_property(),
+                "context": {
+                    "type": "integer",
+                    "description": "可选：前后各显示的可见字符数",
+                    "default": 8
+                },
+                "max_segments": {
+                    "type": "integer",
+                    "description": "可选：最多列出的段数",
+                    "default": 200
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_segments"),
+    }
+}
+
+fn tool_check_name() -> Tool {
+    Tool {
+        name: "zw_check_name".to_string(),
+        description: "检查用户名/昵称：识别全由不可见字符（韩文填充符、盲文空白、零宽字符等）组成的「隐形名字」，以及首尾或中间用不可见字符填充、冒充已有用户的名字。返回去除不可见字符并经 NFKC 规范化后的可见形式，可用于查重。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "要检查的名字"
+                },
+                "names": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "批量检查的名字列表，与 name 可同时使用"
+                },
+                "existing": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "可选：已注册的名字；规范化后（忽略大小写）与其中某个相同即视为冒充"
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: help::examples("zw_check_name"),
+    }
+}
+
+fn tool_blank_message() -> Tool {
+    Tool {
+        name: "zw_blank_message".to_string(),
+        description: "分析 Telegram/WhatsApp 等聊天中的「空白消息」：全由盲文空白、韩文填充符、零宽字符串或 Unicode 空格组成、界面上显示为空的消息。判断使用了哪种技巧，估计只是占位填充还是携带数据（不规则交替时尝试解码），并给出可替换原消息的清理文本。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "要分析的消息内容"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的消息（UTF-8/UTF-16 自动识别），防止客户端剥离不可见字符"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "可选：从文件读取消息"
+                },
+                "report_hashes": report_hashes_property(),
+                "messages": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "批量分析的消息列表（如从聊天记录导出），可与 text 同时使用"
+                },
+                "budget": {
+                    "type": "string",
+                    "description": "可选：疑似携带数据时的解码预算，默认 fast",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "default": "fast"
+                },
+                "password": password_property()
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_blank_message"),
+    }
+}
+
+fn tool_session_start() -> Tool {
+    Tool {
+        name: "zw_session_start".to_string(),
+        description: "开启一个增量解码会话，返回 session_id。适用于分块到达或非常大的输入（聊天记录、分页 API 响应），之后用 zw_session_feed 追加内容，用 zw_session_decode 解码累积的零宽字符流。每个会话的累积流（只保留零宽字符）上限 8 MiB，闲置 30 分钟后自动关闭。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+        annotations: Some(ToolAnnotations::stateful()),
+        examples: help::examples("zw_session_start"),
+    }
+}
+
+fn tool_session_feed() -> Tool {
+    Tool {
+        name: "zw_session_feed".to_string(),
+        description: "向解码会话追加一块文本（只保留零宽字符和分段边界），无需每次重发全部内容。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "zw_session_start 返回的会话 ID"
+                },
+                "text": {
+                    "type": "string",
+                    "description": "要追加的文本块。与 file_path 二选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "要追加的文件路径。与 text 二选一"
+                },
+                "report_hashes": report_hashes_property(),
+                "lossy": lossy_property()
+            },
+            "required": ["session_id"]
+        }),
+        annotations: Some(ToolAnnotations::stateful().open_world()),
+        examples: help::examples("zw_session_feed"),
+    }
+}
+
+fn tool_session_decode() -> Tool {
+    let methods = decode_methods();
+    Tool {
+        name: "zw_session_decode".to_string(),
+        description: "解码会话中累积的零宽字符流，参数与 zw_decode 的 method 相同。可选择解码后关闭会话。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "zw_session_start 返回的会话 ID"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "可选：解码时间上限（毫秒，默认 20000）；超时返回已找到的最佳候选并标记 truncated"
+                },
+                "budget": {
+                    "type": "string",
+                    "description": "可选：暴力解码预算。fast=快速（少量字符对/位宽），normal=默认，exhaustive=穷举（更多字符对、进制、比特偏移）",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "default": "normal"
+                },
+                "method": {
+                    "type": "string",
+                    "description": "可选：指定解码方案，同 zw_decode",
+                    "enum": methods
+                },
+                "password": password_property(),
+                "close": {
+                    "type": "boolean",
+                    "description": "可选：解码后关闭会话并释放内存",
+                    "default": false
+                },
+                "raw_output": {
+                    "type": "boolean",
+                    "description": "可选：原样回显解码结果。默认把解码文本自身包含的控制/方向/零宽字符写成 \\u{...}，防止载荷在输出中伪造或隐藏内容",
+                    "default": false
+                }
+            },
+            "required": ["session_id"]
+        }),
+        annotations: Some(ToolAnnotations::stateful()),
+        examples: help::examples("zw_session_decode"),
+    }
+}
+
+fn tool_job_start() -> Tool {
+    Tool {
+        name: "zw_job_start".to_string(),
+        description: "在后台启动一次工具调用（目录扫描、穷举解码等可能超过客户端单次调用超时的任务），立即返回 job_id；之后用 zw_job_status 查询、zw_job_result 取回结果。任务在会话内保留，解码类工具未给出 timeout_ms 时时间上限放宽到 10 分钟。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "tool": {
+                    "type": "string",
+                    "description": "要在后台执行的工具名，如 zw_summarize_scan、zw_decode（zw_job_* 除外）"
+                },
+                "arguments": {
+                    "type": "object",
+                    "description": "传给该工具的参数，与直接调用时相同"
+                }
+            },
+            "required": ["tool"]
+        }),
+        annotations: Some(ToolAnnotations::stateful()),
+        examples: help::examples("zw_job_start"),
+    }
+}
+
+fn tool_job_status() -> Tool {
+    Tool {
+        name: "zw_job_status".to_string(),
+        description: "查询后台任务的状态（queued / running / succeeded / failed）、工具名与已运行时间；省略 job_id 时列出会话内的全部任务。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "job_id": {
+                    "type": "string",
+                    "description": "可选：zw_job_start 返回的任务 ID"
+                }
+            },
+            "required": []
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: help::examples("zw_job_status"),
+    }
+}
+
+fn tool_job_result() -> Tool {
+    Tool {
+        name: "zw_job_result".to_string(),
+        description: "取回后台任务的结果，与直接调用该工具的响应相同（失败时同样带错误码）。任务仍在运行时返回 JOB_RUNNING，可用 wait_ms 在本次调用内等待一段时间。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "job_id": {
+                    "type": "string",
+                    "description": "zw_job_start 返回的任务 ID"
+                },
+                "wait_ms": {
+                    "type": "integer",
+                    "description": "可选：任务未结束时最多等待的毫秒数（默认 0，不超过本次调用的 timeout_ms）",
+                    "default": 0
+                },
+                "remove": {
+                    "type": "boolean",
+                    "description": "可选：取回结果后删除任务并释放内存（任务未结束时不删除）",
+                    "default": false
+                }
+            },
+            "required": ["job_id"]
+        }),
+        annotations: Some(ToolAnnotations::stateful()),
+        examples: help::examples("zw_job_result"),
+    }
+}
+
+fn tool_reassemble() -> Tool {
+    Tool {
+        name: "zw_reassemble".to_string(),
+        description: "重组拆散在多份文档中的零宽载荷（如 flag 被拆进几份附件）。按解码结果中的序号头（1/3:、[2] 等）或文件名编号自动排序，报告缺失与重复的片段；同时尝试零宽字符流首尾拼接后整体解码、以及逐份解码后合并（去掉相邻片段的重叠），给出更可信的一种。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "file_paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "文档路径列表（声明顺序）"
+                },
+                "report_hashes": report_hashes_property(),
+                "texts": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "直接传入的文档文本列表，排在 file_paths 之后"
+                },
+                "order": {
+                    "type": "string",
+                    "enum": ["auto", "declared"],
+                    "description": "auto=优先按序号头/文件名编号排序（默认），declared=严格按传入顺序",
+                    "default": "auto"
+                },
+                "budget": {
+                    "type": "string",
+                    "description": "可选：暴力解码预算，同 zw_decode",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "default": "normal"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "可选：解码时间上限（毫秒，默认 20000）"
+                },
+                "raw_output": {
+                    "type": "boolean",
+                    "description": "可选：原样回显解码结果。默认把解码文本自身包含的控制/方向/零宽字符写成 \\u{...}，防止载荷在输出中伪造或隐藏内容",
+                    "default": false
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: help::examples("zw_reassemble"),
+    }
+}
+
+fn tool_simulate_channel() -> Tool {
+    Tool {
+        name: "zw_simulate_channel".to_string(),
+        description: "模拟平台可能对文本做的处理（NFC/NFKC 规范化、HTML 清洗、修剪、GBK 往返转码、emoji 渲染等），逐项报告编码后的载荷是否仍能解出，用于在实际使用前验证方案的鲁棒性。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "已编码的隐写文本"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的隐写文本"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "可选：隐写文本文件路径"
+                },
+                "report_hashes": report_hashes_property(),
+                "expected": {
+                    "type": "string",
+                    "description": "可选：期望解出的载荷；默认取原文本的最佳解码结果"
+                },
+                "channels": {
+                    "type": "array",
+                    "items": {
+                        "type": "string",
+                        "enum": ["nfc", "nfd", "nfkc", "nfkd", "html_strict", "html_bidi", "strip_tags", "trim", "gbk", "emoji_render"]
+                    },
+                    "description": "可选：要模拟的处理，默认全部"
+                }
+            },
+            "required": []
+        }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_simulate_channel"),
+    }
+}
+
+fn tool_recommend() -> Tool {
+    let profile_keys: Vec<&str> = recommend::profiles().iter().map(|p| p.key).collect();
+    Tool {
+        name: "zw_recommend".to_string(),
+        description: "按目标平台画像与载荷大小推荐编码方案和字符集：对每个内置方法与预设，检查其字符在该平台的处理（规范化、清洗、修剪等模拟）下是否保留，并把探测载荷实际嵌入载体走一遍处理链验证；按能否幸存与所需不可见字符数排序，给出预期幸存情况、所需字符数与密度，以及字符幸存表。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "profile": {
+                    "type": "string",
+                    "enum": profile_keys,
+                    "description": "目标平台画像，默认 chat",
+                    "default": "chat"
+                },
+                "channels": {
+                    "type": "array",
+                    "items": {
+                        "type": "string",
+                        "enum": ["nfc", "nfd", "nfkc", "nfkd", "html_strict", "html_bidi", "strip_tags", "trim", "gbk", "emoji_render"]
+                    },
+                    "description": "可选：自定义处理链（按顺序施加），指定时代替 profile"
+                },
+                "payload_bytes": {
+                    "type": "integer",
+                    "description": "可选：要隐藏的载荷字节数，默认 32",
+                    "default": 32
+                },
+                "cover_chars": {
+                    "type": "integer",
+                    "description": "可选：载体的可见字符数，用于计算密度（每 100 个可见字符中的不可见字符数）"
+                },
+                "top": {
+                    "type": "integer",
+                    "description": "可选：列出的候选数，默认 5",
+                    "default": 5
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: help::examples("zw_recommend"),
+    }
+}
+
+fn tool_fuzz() -> Tool {
+    Tool {
+        name: "zw_fuzz".to_string(),
+        description: "对已编码样本生成可复现的随机变体（删除、替换、截断不可见符号），检验解码器在载荷受损时能否仍恢复：默认用自动解码逐个检查并按改动类型统计恢复率；全部变体连同改动清单写成清单文件（可另写为逐个文本文件），便于交给第三方解码器复测。出题时用于确认题目不是“碰巧能解”。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "已编码的隐写文本"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的隐写文本"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "可选：隐写文本文件路径"
+                },
+                "report_hashes": report_hashes_property(),
+                "expected": {
+                    "type": "string",
+                    "description": "可选：期望解出的载荷；默认取原文本的最佳解码结果"
+                },
+                "count": {
+                    "type": "integer",
+                    "description": "可选：变体个数，默认 20",
+                    "default": 20
+                },
+                "edits": {
+                    "type": "integer",
+                    "description": "可选：每个变体的改动处数，默认 1",
+                    "default": 1
+                },
+                "mutations": {
+                    "type": "array",
+                    "items": {
+                        "type": "string",
+                        "enum": ["drop", "substitute", "truncate"]
+                    },
+                    "description": "可选：允许的改动类型，默认全部。drop 删除一个符号，substitute 换成样本中的另一个符号，truncate 在符号序列的最后四分之一内截掉文本末尾"
+                },
+                "seed": {
+                    "type": "integer",
+                    "description": "可选：随机种子，相同种子与参数生成相同变体"
+                },
+                "check": {
+                    "type": "boolean",
+                    "description": "可选：是否用自动解码逐个检查变体，默认 true；只需要变体文件时可关闭",
+                    "default": true
+                },
+                "output_dir": {
+                    "type": "string",
+                    "description": "可选：把每个变体写成 fuzz_001.txt … 并附 fuzz_manifest.json 的目录（可用 zw-tmp://）；默认只把清单写入临时工作区"
+                },
+                "budget": {
+                    "type": "string",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "description": "可选：检查变体时的解码预算，默认 normal"
+                },
+                "password": password_property()
+            },
+            "required": []
+        }),
+        annotations: Some(ToolAnnotations::writes_files().open_world()),
+        examples: help::examples("zw_fuzz"),
+    }
+}
+
+fn tool_benchmark() -> Tool {
+    Tool {
+        name: "zw_benchmark".to_string(),
+        description: "在已知答案的 CTF 零宽题目语料上批量运行自动解码，报告召回率、Top-1 命中与耗时，并校验 zw_encode emulate 的第三方工具夹具。用于修改评分/暴力策略前后的回归对比。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "corpus_dir": {
+                    "type": "string",
+                    "description": "可选：语料目录（读取其中所有 .jsonl，每行 {name, source, text, answer}），默认使用内置语料"
+                },
+                "filter": {
+                    "type": "string",
+                    "description": "可选：只运行名称包含该子串的题目"
+                },
+                "budget": {
+                    "type": "string",
+                    "enum": ["fast", "normal", "exhaustive"],
+                    "description": "解码预算，默认 normal"
+                },
+                "min_recall": {
+                    "type": "number",
+                    "description": "可选：召回率下限 (0-1)，低于该值时以错误返回，便于作为回归门禁"
+                }
+            },
+            "required": []
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: help::examples("zw_benchmark"),
+    }
+}
+
+fn tool_health() -> Tool {
+    Tool {
+        name: "zw_health".to_string(),
+        description: "部署自检：对每个预设做编码→解码往返测试，检查字符表完整性与工作目录/临时目录/配置文件/审计日志的读写权限，并报告版本、已启用的功能与当前生效的限制。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: help::examples("zw_health"),
+    }
+}
+
+fn tool_list_chars() -> Tool {
+    Tool {
+        name: "zw_list_chars".to_string(),
+        description: "列出已知的零宽/不可见Unicode字符（由 UCD 生成：Cf/Zl/Zp 与 Default_Ignorable 码点 + Unicode Tags），包括码点、名称、分类、Unicode 版本。支持按分类、名称、码点区间过滤，可输出 JSON。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "category": {
+                    "type": "string",
+                    "description": "可选：只列出该分类（如 核心零宽、方向控制、Unicode Tags）"
+                },
+                "search": {
+                    "type": "string",
+                    "description": "可选：名称子串搜索（不区分大小写），如 JOINER"
+                },
+                "codepoint_range": {
+                    "type": "string",
+                    "description": "可选：码点区间，如 U+2000-U+206F 或 200B..200F"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["text", "json"],
+                    "description": "输出格式，默认 text"
+                }
+            },
+            "required": []
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: help::examples("zw_list_chars"),
+    }
+}
+
+fn tool_list_presets() -> Tool {
+    Tool {
+        name: "zw_list_presets".to_string(),
+        description: "列出所有支持的编码预设方案，包括330k、Steganographr、StegCloak、Binary等，以及各自的分组大小、位序、终止符与参考链接。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "format": {
+                    "type": "string",
+                    "enum": ["text", "json"],
+                    "description": "可选：输出格式，json 返回结构化的预设列表",
+                    "default": "text"
+                }
+            },
+            "required": []
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: help::examples("zw_list_presets"),
+    }
+}
+
+fn tool_presets_test() -> Tool {
+    let keys: Vec<String> = engine::encoding_presets().into_iter().map(|(k, _)| k).collect();
+    Tool {
+        name: "zw_presets_test".to_string(),
+        description: "用某个预设试解样本文本：报告预设字符集对文本中零宽字符的覆盖率、预设之外的码点、预设中未出现的字符，以及按该预设解码的结果，用于逐个排除、判断载荷出自哪个工具。不指定 preset 时按覆盖率列出所有预设。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "preset": {
+                    "type": "string",
+                    "description": "可选：预设 key；省略则对所有预设按覆盖率排序",
+                    "enum": keys
+                },
+                "text": {
+                    "type": "string",
+                    "description": "样本文本。与 file_path 二选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "url": url_property(),
+                "file_path": {
+                    "type": "string",
+                    "description": "样本文件路径。与 text 二选一"
+                },
+                "report_hashes": report_hashes_property()
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only().open_world()),
+        examples: help::examples("zw_presets_test"),
+    }
+}
+
+fn tool_add_preset() -> Tool {
+    Tool {
+        name: "zw_add_preset".to_string(),
+        description: "在运行时添加（或替换同名的）自定义编码预设。添加后该预设立即参与 zw_decode 自动解码，出现在 method 枚举中，并注册专用解码工具 zw_preset_<key>；服务端会推送 tools/list_changed 通知。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "key": {
+                    "type": "string",
+                    "description": "预设 key（小写字母、数字、下划线），不能与内置预设重名"
+                },
+                "chars": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "字符集，按数位 0,1,2... 顺序排列；每项为码点（U+200B / 0x200B / 200B）或字符本身"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "可选：显示名称，默认同 key"
+                },
+                "description": {
+                    "type": "string",
+                    "description": "可选：说明"
+                },
+                "group_size": {
+                    "type": "integer",
+                    "description": "可选：每个明文字符固定使用的零宽字符数；不填则解码时推断"
+                },
+                "digit_order": {
+                    "type": "string",
+                    "enum": ["msb_first", "lsb_first"],
+                    "description": "可选（配合 group_size）：分组内数字顺序；不填则两种都尝试"
+                },
+                "terminator": {
+                    "type": "string",
+                    "description": "可选：载荷起止/终止标记字符（如 U+FEFF），不参与数字映射"
+                },
+                "reference": {
+                    "type": "string",
+                    "description": "可选：原工具或算法说明的链接"
+                }
+            },
+            "required": ["key", "chars"]
+        }),
+        annotations: Some(ToolAnnotations::stateful()),
+        examples: help::examples("zw_add_preset"),
+    }
+}
+
+fn tool_remove_preset() -> Tool {
+    Tool {
+        name: "zw_remove_preset".to_string(),
+        description: "删除 zw_add_preset 添加的自定义预设（内置预设不可删除），并推送 tools/list_changed 通知。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "key": {
+                    "type": "string",
+                    "description": "要删除的自定义预设 key"
+                }
+            },
+            "required": ["key"]
+        }),
+        annotations: Some(ToolAnnotations::stateful()),
+        examples: help::examples("zw_remove_preset"),
+    }
+}
+
+fn tool_reload_config() -> Tool {
+    Tool {
+        name: "zw_reload_config".to_string(),
+        description: "管理工具：重新读取 --config 指定的 TOML 配置（限制、预设、flag 正则、评分权重），无需重启服务。新配置有误时保留旧配置。仅在以 --admin-tools 启动时可用。".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+        annotations: Some(ToolAnnotations::stateful()),
+        examples: help::examples("zw_reload_config"),
+    }
+}
+
+/// 自定义预设的专用解码工具名
+const PRESET_TOOL_PREFIX: &str = "zw_preset_";
+
+fn tool_preset_decode(key: &str) -> Tool {
+    let preset = engine::encoding_presets().into_iter().find(|(k, _)| k == key).map(|(_, p)| p);
+    let summary = preset
+        .map(|p| {
+            let chars: Vec<String> = p.chars.iter().map(|c| format!("U+{:04X}", *c as u32)).collect();
+            format!("{}（{}）", p.name, chars.join(" "))
+        })
+        .unwrap_or_default();
+    Tool {
+        name: format!("{}{}", PRESET_TOOL_PREFIX, key),
+        description: format!("用自定义预设 {} 解码文本中的零宽字符: {}。参数同 zw_decode（method 固定为该预设）。", key, summary),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "要解码的文本。与 file_path 二选一"
+                },
+                "text_base64": {
+                    "type": "string",
+                    "description": "可选：base64 编码的文本（UTF-8/UTF-16 自动识别），用于防止客户端序列化或界面层剥离/规范化零宽字符"
+                },
+                "file_path": {
+                    "type": "string",
+                    "description": "要解码的文件路径。与 text 二选一"
+                }
+            }
+        }),
+        annotations: Some(ToolAnnotations::read_only()),
+        examples: Vec::new(),
+    }
+}
+
+// ============================================================
+// 工具执行
+// ============================================================
+
+/// 根据工具名称和参数执行工具
+pub fn call_tool(name: &str, args: &Value) -> ToolCallResult {
+    let raw_output = args.get("raw_output").and_then(|v| v.as_bool()).unwrap_or(false);
+    let report_hashes = args.get("report_hashes").and_then(|v| v.as_bool()).unwrap_or(false);
+    let (mut result, hashes) = custody::with_hashes(report_hashes, || sanitize::with_raw_output(raw_output, || match name {
+        "zw_analyze" => exec_analyze(args),
+        "zw_locate_flag" => with_candidate_progress(args, || exec_locate_flag(args)),
+        "zw_decode" => with_candidate_progress(args, || exec_decode(args)),
+        "zw_explain" => exec_explain(args),
+        "zw_decode_detail" => exec_decode_detail(args),
+        "zw_encode" => exec_encode(args),
+        "zw_mass_encode" => exec_mass_encode(args),
+        "zw_random_cover" => exec_random_cover(args),
+        "zw_difficulty" => exec_difficulty(args),
+        "zw_dump_raw" => exec_dump_raw(args),
+        "zw_hexdump" => exec_hexdump(args),
+        "zw_extract_file" => exec_extract_file(args),
+        "zw_entropy_visual" => exec_entropy_visual(args),
+        "zw_grep" => exec_grep(args),
+        "zw_git_scan" => exec_git_scan(args),
+        "zw_summarize_scan" => exec_summarize_scan(args),
+        "zw_strip" => exec_strip(args),
+        "zw_segments" => exec_segments(args),
+        "zw_annotate" => exec_annotate(args),
+        "zw_check_name" => exec_check_name(args),
+        "zw_blank_message" => exec_blank_message(args),
+        "zw_session_start" => exec_session_start(),
+        "zw_session_feed" => exec_session_feed(args),
+        "zw_session_decode" => exec_session_decode(args),
+        "zw_job_start" => exec_job_start(args),
+        "zw_job_status" => exec_job_status(args),
+        "zw_job_result" => exec_job_result(args),
+        "zw_reassemble" => exec_reassemble(args),
+        "zw_simulate_channel" => exec_simulate_channel(args),
+        "zw_recommend" => exec_recommend(args),
+        "zw_fuzz" => exec_fuzz(args),
+        "zw_benchmark" => exec_benchmark(args),
+        "zw_health" => ToolCallResult::success(health::report()),
+        "zw_list_chars" => exec_list_chars(args),
+        "zw_list_presets" => exec_list_presets(args),
+        "zw_presets_test" => exec_presets_test(args),
+        "zw_add_preset" => exec_add_preset(args),
+        "zw_remove_preset" => exec_remove_preset(args),
+        "zw_reload_config" if config::admin_tools_enabled() => match config::reload() {
+            Ok(summary) => ToolCallResult::success(summary),
+            Err(e) => ToolCallResult::error(format!("{}\n（已保留原配置）", e)),
+        },
+        _ => match name.strip_prefix(PRESET_TOOL_PREFIX) {
+            Some(key) if engine::custom_preset_keys().iter().any(|k| k == key) => {
+                let mut args = args.clone();
+                if let Some(obj) = args.as_object_mut() {
+                    obj.insert("method".to_string(), json!(key));
+                }
+                exec_decode(&args)
+            }
+            _ => ToolCallResult::failure(ErrorCode::UnknownTool, format!("未知工具: {}", name)),
+        },
+    }));
+    if report_hashes {
+        if let Some(content) = result.content.first_mut() {
+            content.text.push_str(&custody::report(&hashes));
+        }
+    }
+    limit_output(result, args)
+}
+
+/// 默认的单次响应最大字符数，可由环境变量 ZW_MAX_OUTPUT_CHARS 或参数 max_output_chars 覆盖（0 表示不限制）
+const DEFAULT_MAX_OUTPUT_CHARS: usize = 50_000;
+
+pub fn max_output_chars(args: &Value) -> usize {
+    if let Some(n) = args.get("max_output_chars").and_then(|v| v.as_u64()) {
+        return n as usize;
+    }
+    std::env::var("ZW_MAX_OUTPUT_CHARS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .or(config::limits().max_output_chars)
+        .unwrap_or(DEFAULT_MAX_OUTPUT_CHARS)
+}
+
+/// 响应过长时截断，并把完整内容写入临时工作区，避免超出客户端消息大小限制
+fn limit_output(mut result: ToolCallResult, args: &Value) -> ToolCallResult {
+    let limit = max_output_chars(args);
+    if limit == 0 {
+        return result;
+    }
+    for content in result.content.iter_mut() {
+        let total = content.text.chars().count();
+        if total <= limit {
+            continue;
+        }
+        let cut = content
+            .text
+            .char_indices()
+            .nth(limit)
+            .map(|(i, _)| i)
+            .unwrap_or(content.text.len());
+        let note = match workspace::store("output.txt", content.text.as_bytes()) {
+            Ok(a) => format!("完整输出已写入: {}（{}，可用 resources/read 读取）", a.uri, a.path.display()),
+            Err(e) => format!("写入临时工作区失败: {}", e),
+        };
+        content.text.truncate(cut);
+        content.text.push_str(&format!(
+            "\n\n... [输出已截断: 显示 {}/{} 字符] {}\n",
+            limit, total, note
+        ));
+    }
+    result
+}
+
+fn get_str<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+    args.get(key).and_then(|v| v.as_str())
+}
+
+/// 从参数中获取文本，支持 text 直传、text_base64、file_path 文件导入或 url 下载（见 `input::InputSource`）
+/// 自动尝试多种编码: UTF-8, UTF-8 BOM, UTF-16 LE/BE, GBK, Latin-1
+fn resolve_text(args: &Value) -> Result<String, ToolCallResult> {
+    InputSource::require(args)?.read_text(is_lossy(args))
+}
+
+fn repetition_note(rep: &engine::Repetition) -> String {
+    format!(
+        "检测到同一载荷重复 {} 份（每份 {} 个零宽字符，常见于连同引用回复一起粘贴），解码时只解码第一份\n",
+        rep.copies, rep.block_chars
+    )
+}
+
+fn outlier_note(outliers: &engine::Outliers) -> String {
+    format!(
+        "低频杂散字符: {}（远少于主要的 {} 种字符，可能是编辑器插入的软连字符等），自动解码时会去掉它们重试，相应结果的元数据标注 ignored_outliers\n",
+        outliers.describe(),
+        outliers.dominant.len()
+    )
+}
+
+/// 日志模式读入：还原被换行拆开或部分转义的零宽字符，返回 (文本, 说明)；非日志模式同 `resolve_text`
+fn resolve_text_logged(args: &Value) -> Result<(String, String), ToolCallResult> {
+    if !args.get("log_mode").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return resolve_text(args).map(|t| (t, String::new()));
+    }
+    let raw = InputSource::require(args)?.read_bytes()?;
+    let log = ingest::ingest_log(&raw);
+    if log.rejoined == 0 && log.unescaped.is_empty() {
+        return Ok((log.text, "日志模式: 未发现被拆开或转义的零宽字符\n".to_string()));
+    }
+    let mut parts = Vec::new();
+    if log.rejoined > 0 {
+        parts.push(format!("拼接 {} 个被换行拆开的 UTF-8 序列", log.rejoined));
+    }
+    if !log.unescaped.is_empty() {
+        let forms: Vec<String> = log.unescaped.iter().map(|(f, n)| format!("{} ×{}", f, n)).collect();
+        parts.push(format!("还原转义 {}", forms.join("、")));
+    }
+    Ok((log.text, format!("日志模式: {}\n", parts.join("；"))))
+}
+
+fn is_lossy(args: &Value) -> bool {
+    args.get("lossy").and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// 容错模式下剔除替换字符 (U+FFFD) 并返回位置说明；非容错模式原样返回
+fn apply_lossy(args: &Value, text: String) -> (String, String) {
+    if !is_lossy(args) {
+        return (text, String::new());
+    }
+    let lossy = ingest::strip_replacements(&text);
+    if lossy.replacements.is_empty() {
+        return (lossy.text, String::new());
+    }
+    let shown: Vec<String> = lossy.replacements.iter().take(20).map(|i| i.to_string()).collect();
+    let note = format!(
+        "容错模式: 剔除 {} 个替换字符 (U+FFFD)，原始字符索引: {}{}\n",
+        lossy.replacements.len(),
+        shown.join(", "),
+        if lossy.replacements.len() > 20 { " ..." } else { "" }
+    );
+    (lossy.text, note)
+}
+
+/// 读取分类列表参数（字符串数组或逗号分隔字符串），并校验分类名
+fn category_list(args: &Value, key: &str) -> Result<Option<Vec<&'static str>>, ToolCallResult> {
+    let raw: Vec<String> = match args.get(key) {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::Array(items)) => items.iter().filter_map(|v| v.as_str()).map(str::to_string).collect(),
+        Some(Value::String(s)) => s.split(',').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect(),
+        Some(_) => return Err(ToolCallResult::error(format!("参数 {} 应为字符串数组", key))),
+    };
+    let names = chars::category_names();
+    let mut out = Vec::new();
+    for name in &raw {
+        let found = names
+            .iter()
+            .find(|n| n.eq_ignore_ascii_case(name) || (name.eq_ignore_ascii_case("tags") && **n == "Unicode Tags"));
+        match found {
+            Some(n) => out.push(*n),
+            None => {
+                return Err(ToolCallResult::error(format!(
+                    "未知分类: {}（可用: {}）",
+                    name,
+                    names.join("、")
+                )))
+            }
+        }
+    }
+    Ok(Some(out))
+}
+
+/// 按 include_categories / exclude_categories 剔除不关心的零宽字符，返回 (过滤后文本, 说明)
+fn apply_category_filter(args: &Value, text: String) -> Result<(String, String), ToolCallResult> {
+    let include = category_list(args, "include_categories")?;
+    let exclude = category_list(args, "exclude_categories")?;
+    if include.is_none() && exclude.is_none() {
+        return Ok((text, String::new()));
+    }
+    let mut removed: Vec<(&str, usize)> = Vec::new();
+    let filtered: String = text
+        .chars()
+        .filter(|&c| {
+            let Some(cat) = chars::category_of(c) else {
+                return true;
+            };
+            let keep = include.as_ref().is_none_or(|inc| inc.contains(&cat))
+                && exclude.as_ref().is_none_or(|exc| !exc.contains(&cat));
+            if !keep {
+                match removed.iter_mut().find(|(c, _)| *c == cat) {
+                    Some(entry) => entry.1 += 1,
+                    None => removed.push((cat, 1)),
+                }
+            }
+            keep
+        })
+        .collect();
+    if removed.is_empty() {
+        return Ok((filtered, "分类过滤: 未剔除任何字符\n".to_string()));
+    }
+    let total: usize = removed.iter().map(|(_, n)| n).sum();
+    let detail: Vec<String> = removed.iter().map(|(c, n)| format!("{} ×{}", c, n)).collect();
+    let note = format!("分类过滤: 忽略 {} 个零宽字符 ({})\n", total, detail.join(", "));
+    Ok((filtered, note))
+}
+
+/// html_decode 参数是否对 `path` 处的文本 `text` 生效（auto: 输入像 HTML 或扩展名为网页/标记语言时启用）
+fn html_decode_enabled(args: &Value, path: Option<&str>, text: &str) -> Result<bool, ToolCallResult> {
+    match get_str(args, "html_decode").unwrap_or("auto") {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        "auto" => {
+            let by_ext = path
+                .and_then(|p| Path::new(p).extension()?.to_str().map(str::to_ascii_lowercase))
+                .is_some_and(|ext| matches!(ext.as_str(), "html" | "htm" | "xhtml" | "svg" | "xml" | "php" | "vue"));
+            Ok(by_ext || html::looks_like_html(text))
+        }
+        other => Err(ToolCallResult::error(format!("未知 html_decode 取值: {}（可用: auto, on, off）", other))),
+    }
+}
+
+/// 按 html_decode 参数还原网页源码中以转义形式写入的不可见字符（默认 auto: 输入像 HTML 时启用）
+fn apply_html_decode(args: &Value, text: String) -> Result<(String, String), ToolCallResult> {
+    if !html_decode_enabled(args, InputSource::from_args(args).and_then(|s| s.path_hint()), &text)? {
+        return Ok((text, String::new()));
+    }
+    let unescaped = html::unescape_invisible(&text);
+    if unescaped.hits.is_empty() {
+        return Ok((unescaped.text, String::new()));
+    }
+
+    // 按原始写法汇总: (写法, 字符, 语法, 次数, 首次出现的偏移)
+    let mut forms: Vec<(&str, char, html::EscapeKind, usize, usize)> = Vec::new();
+    for hit in &unescaped.hits {
+        match forms.iter_mut().find(|f| f.0 == hit.escaped) {
+            Some(f) => f.3 += 1,
+            None => forms.push((&hit.escaped, hit.ch, hit.kind, 1, hit.offset)),
+        }
+    }
+    let mut kinds: Vec<(html::EscapeKind, usize)> = Vec::new();
+    for hit in &unescaped.hits {
+        match kinds.iter_mut().find(|k| k.0 == hit.kind) {
+            Some(k) => k.1 += 1,
+            None => kinds.push((hit.kind, 1)),
+        }
+    }
+    let kinds: Vec<String> = kinds.iter().map(|(k, n)| format!("{} {} 个", k.name(), n)).collect();
+    let mut note = format!(
+        "HTML 解码: 还原 {} 个以转义形式写入的不可见字符（{}；html_decode=off 可关闭）\n",
+        unescaped.hits.len(),
+        kinds.join("，")
+    );
+    for (escaped, ch, kind, count, offset) in forms.iter().take(20) {
+        let (line, col) = html::line_col(&text, *offset);
+        note.push_str(&format!(
+            "  {} → U+{:04X} {} ×{}（{}，首次: 第{}行第{}列）\n",
+            escaped,
+            *ch as u32,
+            chars::name_of(*ch).unwrap_or_default(),
+            count,
+            kind.name(),
+            line,
+            col
+        ));
+    }
+    if forms.len() > 20 {
+        note.push_str(&format!("  ... 另有 {} 种写法\n", forms.len() - 20));
+    }
+    Ok((unescaped.text, note))
+}
+
+/// 提供 reference_path / reference_text 时，去掉参照文本中已存在的不可见字符，只分析新增部分
+fn apply_reference(args: &Value, text: String) -> Result<(String, String), ToolCallResult> {
+    let (reference, label) = match (get_str(args, "reference_path"), get_str(args, "reference_text")) {
+        (Some(path), _) => (read_file_auto(path, is_lossy(args))?, path.to_string()),
+        (None, Some(t)) => (t.to_string(), "reference_text".to_string()),
+        (None, None) => return Ok((text, String::new())),
+    };
+    // 参照文本按与输入相同的方式预处理，两边的不可见字符才能对齐
+    let reference = if is_lossy(args) { ingest::strip_replacements(&reference).text } else { reference };
+    let reference = if html_decode_enabled(args, get_str(args, "reference_path"), &reference)? {
+        html::unescape_invisible(&reference).text
+    } else {
+        reference
+    };
+    let delta = delta::subtract_reference(&text, &reference);
+    let removed = delta.removed_by_line + delta.removed_by_word;
+    let note = format!(
+        "参照对比（{}）: 排除 {} 个参照中已存在的不可见字符（同行同位置 {} 个，改动行中同词同位置 {} 个），新增 {} 个\n",
+        label, removed, delta.removed_by_line, delta.removed_by_word, delta.kept
+    );
+    Ok((delta.text, note))
+}
+
+/// 按 legit_zwnj 参数处理阿拉伯字母之间的正字法 ZWNJ（默认 exclude: 不参与提取）
+fn apply_zwnj_filter(args: &Value, text: String) -> Result<(String, String), ToolCallResult> {
+    match get_str(args, "legit_zwnj").unwrap_or("exclude") {
+        "include" => Ok((text, String::new())),
+        "exclude" => {
+            let (filtered, removed) = sanitize::remove_legit_zwnj(&text);
+            if removed == 0 {
+                return Ok((filtered, String::new()));
+            }
+            let note = format!(
+                "已忽略 {} 个位于阿拉伯字母之间的 ZWNJ（波斯语等正字法用法，legit_zwnj=include 可保留）\n",
+                removed
+            );
+            Ok((filtered, note))
+        }
+        other => Err(ToolCallResult::error(format!("未知 legit_zwnj 取值: {}（可用: exclude, include）", other))),
+    }
+}
+
+/// 路径访问检查未通过：错误文本之外附带机器可读的原因
+pub fn denied(d: paths::Denied) -> ToolCallResult {
+    ToolCallResult::error_with_data(d.code(), d.message(), d.data())
+}
+
+/// 自动检测编码把原始字节解码为文本（规则同 `read_file_auto`）
+pub fn decode_bytes_auto(raw: &[u8], lossy: bool) -> String {
+    // 检测 BOM 并尝试对应编码
+    if raw.starts_with(&[0xEF, 0xBB, 0xBF]) {
         // UTF-8 BOM
         if let Ok(s) = String::from_utf8(raw[3..].to_vec()) {
-            return Ok(s);
+            return s;
+        }
+    }
+    if raw.starts_with(&[0xFF, 0xFE]) {
+        // UTF-16 LE BOM
+        let iter = raw[2..].chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]]));
+        let text: String = char::decode_utf16(iter)
+            .map(|r| r.unwrap_or('\u{FFFD}'))
+            .collect();
+        return text;
+    }
+    if raw.starts_with(&[0xFE, 0xFF]) {
+        // UTF-16 BE BOM
+        let iter = raw[2..].chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]]));
+        let text: String = char::decode_utf16(iter)
+            .map(|r| r.unwrap_or('\u{FFFD}'))
+            .collect();
+        return text;
+    }
+
+    // 尝试 UTF-8
+    if let Ok(s) = String::from_utf8(raw.to_vec()) {
+        return s;
+    }
+    if lossy {
+        return ingest::decode_utf8_lossy(raw);
+    }
+
+    // 尝试 UTF-16 LE (无BOM)
+    if raw.len().is_multiple_of(2) {
+        let iter = raw.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]]));
+        let text: String = char::decode_utf16(iter)
+            .map(|r| r.unwrap_or('\u{FFFD}'))
+            .collect();
+        // 如果解码后大部分是可打印字符，认为成功
+        let printable = text.chars().filter(|c| !c.is_control() || *c == '\n' || *c == '\r' || *c == '\t').count();
+        if text.chars().count() > 0 && printable as f64 / text.chars().count() as f64 > 0.7 {
+            return text;
+        }
+    }
+
+    // 最后降级: 使用 Latin-1 (ISO-8859-1, 不会失败)
+    raw.iter().map(|&b| b as char).collect()
+}
+
+/// 将内容写入文件
+/// 写出文件；`zw-tmp://<name>` 写入临时工作区（省略 name 时用 `default_name`）。返回实际写入位置的说明
+fn write_file(path_str: &str, content: &str, default_name: &str) -> Result<String, ToolCallResult> {
+    if let Some(name) = path_str.strip_prefix(workspace::URI_PREFIX) {
+        let name = if name.is_empty() { default_name } else { name };
+        let artifact = workspace::store(name, content.as_bytes()).map_err(ToolCallResult::error)?;
+        custody::record_output(&artifact.uri, content.as_bytes());
+        return Ok(format!("{}（{} 字节，可用 resources/read 读取）", artifact.uri, artifact.size));
+    }
+    let target = paths::resolve(path_str);
+    target.check_write().map_err(denied)?;
+    // 自动创建父目录
+    if let Some(parent) = target.path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                return Err(ToolCallResult::failure(ErrorCode::IoError, target.io_error("创建目录", path_str, &e)));
+            }
+        }
+    }
+    match fs::write(&target.path, content.as_bytes()) {
+        Ok(_) => {
+            custody::record_output(&target.display, content.as_bytes());
+            Ok(target.display)
+        }
+        Err(e) => Err(ToolCallResult::failure(ErrorCode::IoError, target.io_error("写入文件", path_str, &e))),
+    }
+}
+
+fn exec_analyze(args: &Value) -> ToolCallResult {
+    let (text, log_note) = match resolve_text_logged(args) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    let (text, lossy_note) = apply_lossy(args, text);
+    let (text, html_note) = match apply_html_decode(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, reference_note) = match apply_reference(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, filter_note) = match apply_category_filter(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, zwnj_note) = match apply_zwnj_filter(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    let mut report = String::new();
+    report.push_str(&input::header(args));
+    report.push_str(&log_note);
+    report.push_str(&lossy_note);
+    report.push_str(&html_note);
+    report.push_str(&reference_note);
+    report.push_str(&filter_note);
+    report.push_str(&zwnj_note);
+    let analysis = engine::analyze(&text);
+    report.push_str(&engine::format_analysis(&analysis));
+    if analysis.unique_zw_chars >= cluster::MIN_SYMBOLS {
+        report.push('\n');
+        report.push_str(&cluster::cluster(&text).describe());
+    }
+    if blank::is_blank_message(&text) {
+        report.push_str("\n整段文本显示为空（空白消息），可用 zw_blank_message 判断所用技巧与是否携带数据\n");
+    }
+    if let Some(rep) = engine::detect_repetition(&text) {
+        report.push('\n');
+        report.push_str(&repetition_note(&rep));
+    }
+    if let Some(outliers) = engine::outlier_symbols(&analysis) {
+        report.push('\n');
+        report.push_str(&outlier_note(&outliers));
+    }
+    let zwj_anomalies = emoji::anomalies(&text);
+    let zwj = emoji::describe(&zwj_anomalies);
+    if !zwj.is_empty() {
+        report.push('\n');
+        report.push_str(&zwj);
+    }
+    if let Some(csv_path) = get_str(args, "csv_path") {
+        let location = match write_file(csv_path, &engine::distribution_csv(&text), "distribution.csv") {
+            Ok(l) => l,
+            Err(e) => return e,
+        };
+        report.push_str(&format!("\n✓ 已导出 CSV（{} 种字符）: {}\n", analysis.unique_zw_chars, location));
+    }
+
+    // 与文档类型基线对比，避免把正常的 emoji / 波斯语 ZWNJ 等误判为隐写
+    let requested = get_str(args, "baseline").unwrap_or("auto");
+    if requested != "none" && analysis.zero_width_count > 0 {
+        let counts = baseline::BasisCounts::of(&text);
+        let key = if requested == "auto" { baseline::guess_baseline(&counts) } else { requested };
+        let all = baseline::baselines();
+        let Some(model) = all.iter().find(|b| b.key == key) else {
+            let keys: Vec<&str> = all.iter().map(|b| b.key).collect();
+            return ToolCallResult::error(format!("未知基线: {}（可用: auto, none, {}）", key, keys.join(", ")));
+        };
+        let deviations = baseline::compare(&analysis, &counts, model);
+        report.push_str(&baseline::format_comparison(&deviations, model, requested == "auto"));
+        // 基线只看数量；不渲染的 ZWJ 序列数量正常时也可能是 emoji 通道载荷
+        if zwj_anomalies.total() > 0 && deviations.iter().all(|d| d.is_normal()) {
+            report.push_str(&format!(
+                "注意: 数量虽在基线范围内，但有 {} 处异常 ZWJ 序列（见上文 emoji 序列），不能排除 emoji 通道隐写\n",
+                zwj_anomalies.total()
+            ));
+        }
+    }
+    if args.get("cover_profile").and_then(|v| v.as_bool()).unwrap_or(true) {
+        report.push_str(&profile::format_profile(&profile::profile(&text)));
+    }
+    ToolCallResult::success(report)
+}
+
+fn exec_decode(args: &Value) -> ToolCallResult {
+    if enumerate_enabled(args) {
+        return exec_decode_accepted(args, engine::Acceptance::unfiltered());
+    }
+    match parse_acceptance(args) {
+        Ok(acceptance) => exec_decode_accepted(args, acceptance),
+        Err(e) => e,
+    }
+}
+
+fn exec_decode_accepted(args: &Value, acceptance: engine::Acceptance) -> ToolCallResult {
+    let (text, log_note) = match resolve_text_logged(args) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    let method = match decode_method(args) {
+        Ok(m) => m,
+        Err(e) => return e,
+    };
+    let budget = match parse_budget(args) {
+        Ok(b) => b.with_acceptance(acceptance),
+        Err(e) => return e,
+    };
+    let (text, lossy_note) = apply_lossy(args, text);
+    let (text, html_note) = match apply_html_decode(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, reference_note) = match apply_reference(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, filter_note) = match apply_category_filter(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, zwnj_note) = match apply_zwnj_filter(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    if let Some(mapping) = args.get("mapping") {
+        return exec_decode_mapped(args, &text, mapping, &acceptance);
+    }
+    if enumerate_enabled(args) {
+        let notes = [log_note, lossy_note, html_note, reference_note, filter_note, zwnj_note].concat();
+        return exec_decode_enumerate(args, &text, method, budget, &notes);
+    }
+    let verbose = args.get("verbose").and_then(|v| v.as_bool()).unwrap_or(false);
+    let ((results, skipped), attempts) = if verbose {
+        let (report, attempts) = engine::with_attempt_log(|| run_decode_report(&text, method, &budget));
+        (report, Some(attempts))
+    } else {
+        (run_decode_report(&text, method, &budget), None)
+    };
+
+    let mut prefix = String::new();
+    prefix.push_str(&input::header(args));
+    prefix.push_str(&log_note);
+    prefix.push_str(&lossy_note);
+    prefix.push_str(&html_note);
+    prefix.push_str(&reference_note);
+    prefix.push_str(&filter_note);
+    prefix.push_str(&zwnj_note);
+    if budget.name != "normal" {
+        prefix.push_str(&format!("解码预算: {}\n", budget.name));
+    }
+    prefix.push_str(&acceptance_note(&acceptance));
+    prefix.push_str(&timeout_note(args, &budget));
+    if method == "auto" || method == "stegcloak" {
+        prefix.push_str(&stegcloak_note(&text, &budget.stegcloak));
+    }
+    if method == "auto" {
+        if let Some(rep) = engine::detect_repetition(&text) {
+            prefix.push_str(&repetition_note(&rep));
+        }
+        if let Some(outliers) = engine::outlier_symbols(&engine::analyze(&text)) {
+            prefix.push_str(&outlier_note(&outliers));
+        }
+        let sections = engine::decode_sections(&text, &budget);
+        if !sections.is_empty() {
+            prefix.push_str(&format!("检测到 {} 段独立载荷（分段解码最佳结果）:\n", sections.len()));
+            for r in &sections {
+                prefix.push_str(&format!("  {} → {:?} (得分 {:.1})\n", r.method, r.decoded, r.score));
+            }
+            prefix.push('\n');
+        }
+    }
+    if method == "auto" {
+        for (rank, layers) in nested_layers(&results, &budget, max_depth(args)) {
+            let mut chain = vec![format!("第1层 {}", results[rank].method)];
+            chain.extend(layers.iter().enumerate().map(|(i, l)| format!("第{}层 {}", i + 2, l.method)));
+            let last = layers.last().map(|l| &l.decoded).unwrap_or(&results[rank].decoded);
+            prefix.push_str(&format!(
+                "检测到嵌套零宽载荷（候选 [{}]）:\n  {} → {:?}\n\n",
+                rank + 1,
+                chain.join(" → "),
+                last
+            ));
+        }
+    }
+    if !skipped.is_empty() {
+        prefix.push_str(&format!(
+            "命中完整 flag 格式的结果后搜索已停止，未运行（或未跑完）的方案: {}（需要全部候选请使用 budget=exhaustive）\n",
+            skipped.join("、")
+        ));
+    }
+    let mut result = format_decode_results(&results, &prefix, &text, &acceptance, &budget.stegcloak);
+    // 后处理是对候选的补充，附在结果之后；已有候选含 flag 时不再展示
+    let has_flag = results.iter().any(|r| engine::is_verified(r) || !engine::find_flags(&r.decoded).is_empty());
+    if method == "auto" && postprocess_enabled(args) && !has_flag {
+        if let Some(content) = result.content.first_mut() {
+            for (rank, chain) in postprocess_chains(&results) {
+                content.text.push_str(&format!(
+                    "\n后处理（候选 [{}]）:\n  {} → {}\n",
+                    rank + 1,
+                    chain.steps.join(" → "),
+                    sanitize::display(&format!("{:?}", chain.output))
+                ));
+            }
+        }
+    }
+    if let (Some(attempts), Some(content)) = (attempts, result.content.first_mut()) {
+        content.text.push_str(&format_attempts(&attempts));
+    }
+    if results.is_empty() && budget.expired() {
+        let text = result.content.first().map(|c| c.text.clone()).unwrap_or_default();
+        return ToolCallResult::error_with_data(
+            ErrorCode::DecodeBudgetExceeded,
+            text,
+            json!({ "budget": budget.name, "timeout_ms": tool_timeout(args).as_millis() as u64 }),
+        );
+    }
+    result
+}
+
+/// verbose 模式的尝试明细：按方案族汇总，再逐次列出
+fn format_attempts(attempts: &[engine::Attempt]) -> String {
+    if attempts.is_empty() {
+        return "\n解码尝试明细: 没有记录（指定了 method 时只运行该方案）\n".to_string();
+    }
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let total: Duration = attempts.iter().map(|a| a.elapsed).sum();
+    let mut out = format!(
+        "\n解码尝试明细: 共 {} 次尝试，{} 次产生候选，合计 {:.2} ms\n\n按方案族汇总:\n",
+        attempts.len(),
+        attempts.iter().filter(|a| a.accepted > 0).count(),
+        ms(total)
+    );
+    // 按首次出现顺序汇总: (方案族, 尝试数, 产生候选的尝试数, 耗时)
+    let mut phases: Vec<(&str, usize, usize, Duration)> = Vec::new();
+    for a in attempts {
+        let phase = if a.phase.is_empty() { "?" } else { a.phase };
+        let i = match phases.iter().position(|p| p.0 == phase) {
+            Some(i) => i,
+            None => {
+                phases.push((phase, 0, 0, Duration::ZERO));
+                phases.len() - 1
+            }
+        };
+        phases[i].1 += 1;
+        phases[i].2 += usize::from(a.accepted > 0);
+        phases[i].3 += a.elapsed;
+    }
+    for (phase, n, hits, elapsed) in &phases {
+        out.push_str(&format!("  {:<18} {:>5} 次  产生候选 {:>4} 次  {:>9.2} ms\n", phase, n, hits, ms(*elapsed)));
+    }
+    out.push_str("\n逐次尝试（方案族 | 方案与参数 | 耗时 | 结果）:\n");
+    for (i, a) in attempts.iter().enumerate() {
+        let outcome = match (&a.rejection, a.best_score) {
+            (Some(reason), _) => format!("✗ {}", reason),
+            (None, Some(best)) => format!("✓ {} 个候选，最高 {:.1}", a.accepted, best),
+            (None, None) => format!("✓ {} 个候选", a.accepted),
+        };
+        out.push_str(&format!("  [{:4}] {} | {} | {:.3} ms | {}\n", i + 1, a.phase, a.method, ms(a.elapsed), outcome));
+    }
+    out
+}
+
+fn exec_locate_flag(args: &Value) -> ToolCallResult {
+    let (text, log_note) = match resolve_text_logged(args) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let extra = match get_str(args, "flag_pattern").map(regex::Regex::new) {
+        None => None,
+        Some(Ok(re)) => Some(re),
+        Some(Err(e)) => return ToolCallResult::error(format!("flag_pattern 无效: {}", e)),
+    };
+    let budget = match parse_budget(args) {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
+
+    // 方案链的前处理部分
+    let mut chain: Vec<String> = Vec::new();
+    let (text, lossy_note) = apply_lossy(args, text);
+    let (text, html_note) = match apply_html_decode(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, reference_note) = match apply_reference(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, filter_note) = match apply_category_filter(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, zwnj_note) = match apply_zwnj_filter(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    for note in [&log_note, &lossy_note, &html_note, &reference_note, &filter_note, &zwnj_note] {
+        if let Some(line) = note.lines().next().filter(|l| !l.is_empty()) {
+            chain.push(line.to_string());
+        }
+    }
+    let analysis = engine::analyze(&text);
+    if analysis.zero_width_count == 0 {
+        let trace = diagnose::diagnose_failure(&text, &budget.acceptance, &budget.stegcloak);
+        return ToolCallResult::success(format_locate_failure(&[], &trace));
+    }
+    chain.push(format!("提取 {} 个零宽字符", analysis.zero_width_count));
+
+    let mut results = engine::decode_sections(&text, &budget);
+    results.extend(run_decode(&text, "auto", &budget));
+
+    // 嵌套载荷: 把每层都当作候选，方案链记录经过的层
+    let mut layered: Vec<(engine::DecodeResult, Vec<String>)> = Vec::new();
+    for (rank, layers) in nested_layers(&results, &budget, max_depth(args)) {
+        let mut via = vec![format!("第1层 {}", results[rank].method)];
+        for (i, layer) in layers.into_iter().enumerate() {
+            let step = format!("第{}层 {}", i + 2, layer.method);
+            layered.push((layer, via.clone()));
+            via.push(step);
+        }
+    }
+    // 后处理链: 方案名记录候选的解码方案与经过的变换
+    let mut processed: Vec<engine::DecodeResult> = Vec::new();
+    if postprocess_enabled(args) {
+        for (rank, chain) in postprocess_chains(&results) {
+            let mut r = results[rank].clone();
+            r.method = format!("{} → 后处理 {}", r.method, chain.steps.join(" → "));
+            r.decoded = chain.output;
+            r.score = chain.score;
+            processed.push(r);
+        }
+    }
+    let mut candidates: Vec<(&engine::DecodeResult, Vec<String>)> = layered.iter().map(|(r, via)| (r, via.clone())).collect();
+    candidates.extend(processed.iter().map(|r| (r, Vec::new())));
+    candidates.extend(results.iter().map(|r| (r, Vec::new())));
+
+    let flags_in = |decoded: &str| -> Vec<String> {
+        let mut flags = engine::find_flags(decoded);
+        if let Some(re) = &extra {
+            for m in re.find_iter(decoded) {
+                if !flags.iter().any(|f| f == m.as_str()) {
+                    flags.push(m.as_str().to_string());
+                }
+            }
+        }
+        flags
+    };
+    let mut found: Vec<(String, &engine::DecodeResult, &[String])> = Vec::new();
+    for (r, via) in &candidates {
+        for flag in flags_in(&r.decoded) {
+            if !found.iter().any(|(f, _, _)| *f == flag) {
+                found.push((flag, r, via));
+            }
+        }
+    }
+
+    if found.is_empty() {
+        // 有候选但都不含 flag 时，逐方案的拒绝原因没有意义，只给建议
+        let trace = if results.is_empty() {
+            diagnose::diagnose_failure(&text, &budget.acceptance, &budget.stegcloak)
+        } else {
+            diagnose::FailureTrace {
+                attempts: Vec::new(),
+                suggestions: vec![
+                    "解码结果可能是二次编码（base64/hex 等）或加密数据，可对候选继续解码".to_string(),
+                    "使用 budget=exhaustive 扩大暴力范围（更多字符对、比特偏移、进制）".to_string(),
+                ],
+            }
+        };
+        let mut report = format_locate_failure(&results, &trace);
+        report.push_str(&timeout_note(args, &budget));
+        return ToolCallResult::success(report);
+    }
+
+    let mut output = format!("✓ 找到 {} 个 flag:\n", found.len());
+    for (flag, r, via) in &found {
+        let mut steps = chain.clone();
+        steps.extend(via.iter().cloned());
+        if via.is_empty() {
+            steps.push(r.method.clone());
+        } else {
+            steps.push(format!("第{}层 {}", via.len() + 1, r.method));
+        }
+        if r.decoded.trim() != flag {
+            steps.push(format!("从解码文本中匹配 flag（解码全文 {} 字符）", r.decoded.chars().count()));
+        }
+        output.push_str(&format!(
+            "\n{}\n  方案链: {}\n  得分: {:.1}\n",
+            sanitize::display(flag),
+            steps.join(" → "),
+            r.score
+        ));
+    }
+    output.push_str(&timeout_note(args, &budget));
+    ToolCallResult::success(output)
+}
+
+/// max_depth 参数（默认 3 层，至少 1 层）
+fn max_depth(args: &Value) -> usize {
+    args.get("max_depth").and_then(|v| v.as_u64()).unwrap_or(3).clamp(1, 16) as usize
+}
+
+/// enumerate 参数（穷举模式）
+fn enumerate_enabled(args: &Value) -> bool {
+    args.get("enumerate").and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// 穷举模式单条输出的最大显示字符数
+const ENUMERATE_PREVIEW_CHARS: usize = 300;
+
+/// zw_decode 穷举模式: 不评分过滤，分页列出每个解码尝试的原始输出
+fn exec_decode_enumerate(args: &Value, text: &str, method: &str, mut budget: engine::Budget, notes: &str) -> ToolCallResult {
+    budget.early_exit = false;
+    let results = run_decode(text, method, &budget);
+    let mut output = String::new();
+    output.push_str(&input::header(args));
+    output.push_str(notes);
+    output.push_str(&timeout_note(args, &budget));
+    let total = results.len();
+    if total == 0 {
+        output.push_str("穷举模式: 没有任何解码尝试产生输出（文本中可能没有零宽字符）\n");
+        return ToolCallResult::success(output);
+    }
+
+    let offset = args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+    if offset >= total {
+        return ToolCallResult::error(format!("offset {} 超出范围（共 {} 个解码尝试）", offset, total));
+    }
+    let limit = args.get("limit").and_then(|v| v.as_u64()).filter(|&n| n > 0).unwrap_or(50).min(500) as usize;
+    let end = (offset + limit).min(total);
+    output.push_str(&format!(
+        "穷举模式: 共 {} 个解码尝试（不做得分与可打印性过滤，输出相同的只保留一次），第 {}–{} 条",
+        total,
+        offset,
+        end - 1
+    ));
+    if end < total {
+        output.push_str(&format!("；下一页 offset={}", end));
+    }
+    output.push_str(":\n\n");
+    for (i, r) in results.iter().enumerate().take(end).skip(offset) {
+        let count = r.decoded.chars().count();
+        let preview: String = r.decoded.chars().take(ENUMERATE_PREVIEW_CHARS).collect();
+        output.push_str(&format!("[{}] {} (得分 {:.1}, {} 字符)\n    {}", i, r.method, r.score, count, sanitize::display(&preview)));
+        if count > ENUMERATE_PREVIEW_CHARS {
+            output.push_str(&format!("…（截断，共 {} 字符）", count));
+        }
+        output.push('\n');
+        // 单字节范围内的非文本输出另给出字节，便于交给其他工具处理
+        if r.decoded.chars().all(|c| (c as u32) < 0x100) && r.decoded.chars().any(|c| c.is_control() || !c.is_ascii()) {
+            let hex: Vec<String> = preview.chars().map(|c| format!("{:02x}", c as u32)).collect();
+            output.push_str(&format!("    字节: {}\n", hex.join(" ")));
+        }
+    }
+    ToolCallResult::success(output)
+}
+
+/// postprocess 参数（默认启用）
+fn postprocess_enabled(args: &Value) -> bool {
+    args.get("postprocess").and_then(|v| v.as_bool()).unwrap_or(true)
+}
+
+/// 不含 flag 前缀、可打印的前 3 个候选依次尝试后处理链；每个候选只取最好的一条，
+/// 且链要么找到 flag、要么比原候选更像明文，返回 (候选下标, 链)
+fn postprocess_chains(results: &[engine::DecodeResult]) -> Vec<(usize, postprocess::Chain)> {
+    results
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| !engine::has_flag_prefix(&r.decoded) && engine::is_printable(&r.decoded))
+        .take(3)
+        .filter_map(|(i, r)| {
+            postprocess::run(&r.decoded)
+                .into_iter()
+                .next()
+                .filter(|c| !c.flags.is_empty() || c.score > r.score)
+                .map(|c| (i, c))
+        })
+        // 与其他候选或前面的链结果相同的（如数字下标候选的 hex 还原）没有新信息
+        .fold(Vec::new(), |mut out: Vec<(usize, postprocess::Chain)>, (i, c)| {
+            if !results.iter().any(|r| r.decoded == c.output) && !out.iter().any(|(_, o)| o.output == c.output) {
+                out.push((i, c));
+            }
+            out
+        })
+}
+
+/// 解码结果仍含零宽字符的前 3 个候选继续逐层解码；返回 (候选下标, 第 2 层起的各层)
+fn nested_layers(
+    results: &[engine::DecodeResult],
+    budget: &engine::Budget,
+    max_depth: usize,
+) -> Vec<(usize, Vec<engine::DecodeResult>)> {
+    if max_depth < 2 {
+        return Vec::new();
+    }
+    results
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.decoded.chars().any(chars::is_zero_width))
+        .take(3)
+        .filter_map(|(i, r)| {
+            let layers = engine::decode_layers(r, budget, max_depth);
+            // 内层得分不如外层、或只解出零星几个字符的，多半是把随机数字凑成了零宽字符
+            let deeper = layers
+                .last()
+                .is_some_and(|l| l.score > r.score && l.decoded.chars().count() >= 4);
+            deeper.then_some((i, layers))
+        })
+        .collect()
+}
+
+/// zw_locate_flag 的失败报告: 最接近的候选 + 逐方案诊断 + 建议
+fn format_locate_failure(results: &[engine::DecodeResult], trace: &diagnose::FailureTrace) -> String {
+    let mut out = String::from("✗ 未找到 flag\n");
+    if !results.is_empty() {
+        out.push_str("\n最接近的解码候选（不含 flag 格式）:\n");
+        for r in results.iter().take(3) {
+            out.push_str(&format!("  - {} → {:?} (得分 {:.1})\n", r.method, r.decoded, r.score));
+        }
+    }
+    if !trace.attempts.is_empty() {
+        out.push_str("\n诊断:\n");
+        for (scheme, reason) in &trace.attempts {
+            out.push_str(&format!("  ✗ {}: {}\n", scheme, reason));
+        }
+    }
+    out.push_str("\n建议:\n");
+    if !results.is_empty() {
+        out.push_str("  - 候选可能使用了非常规 flag 格式，可用 flag_pattern 指定，或用 zw_decode 查看全部候选\n");
+    }
+    for s in &trace.suggestions {
+        out.push_str(&format!("  - {}\n", s));
+    }
+    out
+}
+
+/// 按显式映射解码（zw_decode 的 mapping 参数）
+fn exec_decode_mapped(args: &Value, text: &str, mapping: &Value, acceptance: &engine::Acceptance) -> ToolCallResult {
+    match decode_with_mapping(args, text, mapping, acceptance) {
+        Ok(result) => {
+            format_decode_results(&[result], &input::header(args), text, acceptance, &stegcloak_options(args))
+        }
+        Err(e) => e,
+    }
+}
+
+/// 解析 mapping / group_size / alphabet 参数并解码
+fn decode_with_mapping(
+    args: &Value,
+    text: &str,
+    mapping: &Value,
+    acceptance: &engine::Acceptance,
+) -> Result<engine::DecodeResult, ToolCallResult> {
+    let Some(obj) = mapping.as_object().filter(|o| !o.is_empty()) else {
+        return Err(ToolCallResult::error("mapping 必须是非空对象，如 {\"U+200B\": 0, \"U+200C\": 1}"));
+    };
+    let mut pairs = Vec::new();
+    for (key, value) in obj {
+        let Some(ch) = chars::parse_codepoint(key).and_then(char::from_u32) else {
+            return Err(ToolCallResult::error(format!("mapping 中的码点无法解析: {}", key)));
+        };
+        let Some(digit) = value.as_u64().filter(|&d| d < 1024) else {
+            return Err(ToolCallResult::error(format!("mapping[{}] 必须是 0-1023 的整数", key)));
+        };
+        pairs.push((ch, digit as usize));
+    }
+    let alphabet = match get_str(args, "alphabet") {
+        None => engine::Alphabet::Unicode,
+        Some(name) => match engine::Alphabet::parse(name) {
+            Some(a) => a,
+            None => {
+                return Err(ToolCallResult::error(format!(
+                    "未知 alphabet: {}（可用: unicode, ascii, hex, base64_index, letters, digits）",
+                    name
+                )))
+            }
+        },
+    };
+    let group_size = args.get("group_size").and_then(|v| v.as_u64()).map(|g| g as usize);
+    engine::decode_mapped(text, &pairs, group_size, alphabet, acceptance)
+        .map_err(|e| ToolCallResult::failure(ErrorCode::DecodeFailed, format!("按指定映射解码失败: {}", e)))
+}
+
+fn exec_explain(args: &Value) -> ToolCallResult {
+    let text = match resolve_text(args) {
+        Ok(t) => t,
+        Err(e) => return e,
+    };
+    let method = match decode_method(args) {
+        Ok(m) => m,
+        Err(e) => return e,
+    };
+    let rank = args.get("rank").and_then(|v| v.as_u64()).unwrap_or(1).max(1) as usize;
+    let budget = match parse_budget(args) {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
+    let results = match args.get("mapping") {
+        Some(mapping) => match decode_with_mapping(args, &text, mapping, &budget.acceptance) {
+            Ok(r) => vec![r],
+            Err(e) => return e,
+        },
+        None => run_decode(&text, method, &budget),
+    };
+    let Some(result) = results.get(rank - 1) else {
+        return ToolCallResult::failure(ErrorCode::NotFound, format!(
+            "没有第 {} 个解码结果（共 {} 个），请先用 zw_decode 确认能解出内容",
+            rank,
+            results.len()
+        ));
+    };
+    match explain::explain(&text, result) {
+        Some(report) => ToolCallResult::success(report),
+        None => ToolCallResult::failure(ErrorCode::DecodeFailed, format!(
+            "方案「{}」经过修复或分段处理，无法在原文上逐字复现映射；请对修复后的文本或单段载荷调用 zw_explain",
+            result.method
+        )),
+    }
+}
+
+fn exec_decode_detail(args: &Value) -> ToolCallResult {
+    let Some(id) = get_str(args, "id").map(str::trim) else {
+        return ToolCallResult::failure(ErrorCode::InputMissing, "缺少参数: id");
+    };
+    let (text, _) = match resolve_text_logged(args) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let budget = match parse_budget(args) {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
+    // 与 zw_decode 相同的前处理，保证 ID 对应同一个候选
+    let (text, _) = apply_lossy(args, text);
+    let (text, _) = match apply_html_decode(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, _) = match apply_reference(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, _) = match apply_category_filter(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, _) = match apply_zwnj_filter(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let acceptance = match parse_acceptance(args) {
+        Ok(a) => a,
+        Err(e) => return e,
+    };
+    let method = match decode_method(args) {
+        Ok(m) => m,
+        Err(e) => return e,
+    };
+    let budget = budget.with_acceptance(acceptance);
+    let results = run_decode(&text, method, &budget);
+    match results.iter().find(|r| r.id().eq_ignore_ascii_case(id)) {
+        Some(result) => ToolCallResult::success(explain::detail(&text, result, &acceptance)),
+        None => ToolCallResult::failure(ErrorCode::NotFound, format!(
+            "未找到 ID 为 {} 的候选（本次共 {} 个结果）；请确认文本、method、budget、threshold 与过滤参数和 zw_decode 调用一致",
+            id,
+            results.len()
+        )),
+    }
+}
+
+/// 默认的解码时间上限（毫秒），可由环境变量 ZW_TOOL_TIMEOUT_MS 或参数 timeout_ms 覆盖
+const DEFAULT_TIMEOUT_MS: u64 = 20_000;
+
+/// 超过解码时间上限后仍未返回时，服务端强制放弃等待的额外宽限
+const HARD_TIMEOUT_GRACE: Duration = Duration::from_secs(10);
+
+pub fn tool_timeout(args: &Value) -> Duration {
+    let ms = args.get("timeout_ms").and_then(|v| v.as_u64()).unwrap_or_else(|| {
+        std::env::var("ZW_TOOL_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .or(config::limits().timeout_ms)
+            .unwrap_or(DEFAULT_TIMEOUT_MS)
+    });
+    Duration::from_millis(ms)
+}
+
+/// 服务端等待一次工具调用的最长时间（解码时间上限 + 宽限）
+pub fn hard_timeout(args: &Value) -> Duration {
+    tool_timeout(args) + HARD_TIMEOUT_GRACE
+}
+
+/// 解析 budget 参数，默认 normal，并附加时间上限
+fn parse_budget(args: &Value) -> Result<engine::Budget, ToolCallResult> {
+    let budget = match get_str(args, "budget") {
+        None => engine::Budget::normal(),
+        Some(name) => engine::Budget::parse(name)
+            .ok_or_else(|| ToolCallResult::error(format!("未知解码预算: {}", name)))?,
+    };
+    Ok(budget.with_timeout(tool_timeout(args)).with_stegcloak(stegcloak_options(args)))
+}
+
+/// 解析 password / integrity 参数
+fn stegcloak_options(args: &Value) -> stegcloak::Options {
+    stegcloak::Options {
+        password: get_str(args, "password").map(str::to_string),
+        integrity: args.get("integrity").and_then(|v| v.as_bool()).unwrap_or(false),
+    }
+}
+
+/// 解析 threshold / allow_binary 参数
+fn parse_acceptance(args: &Value) -> Result<engine::Acceptance, ToolCallResult> {
+    let mut acceptance = engine::Acceptance::default();
+    if let Some(v) = args.get("threshold") {
+        match v.as_f64() {
+            Some(t) if t.is_finite() => acceptance.threshold = t,
+            _ => return Err(ToolCallResult::error(format!("threshold 必须是数字: {}", v))),
+        }
+    }
+    acceptance.allow_binary = args.get("allow_binary").and_then(|v| v.as_bool()).unwrap_or(false);
+    Ok(acceptance)
+}
+
+/// 非默认接受条件的提示
+fn acceptance_note(acceptance: &engine::Acceptance) -> String {
+    let mut note = String::new();
+    if acceptance.threshold != engine::DEFAULT_THRESHOLD {
+        note.push_str(&format!("得分门槛: {}（默认 {}）\n", acceptance.threshold, engine::DEFAULT_THRESHOLD));
+    }
+    if acceptance.allow_binary {
+        note.push_str("已关闭可打印性过滤（allow_binary），结果可能是二进制数据\n");
+    }
+    note
+}
+
+/// 超时截断提示（truncated: true）
+fn timeout_note(args: &Value, budget: &engine::Budget) -> String {
+    if !budget.expired() {
+        return String::new();
+    }
+    format!(
+        "⚠ 已达到时间上限 {} ms，以下为截至目前找到的最佳候选 (truncated: true)\n",
+        tool_timeout(args).as_millis()
+    )
+}
+
+/// 请求带 `_meta.progressToken` 时，自动解码每完成一个方案族、最佳候选变得更好就通过
+/// notifications/progress 推送，交互式客户端不必等完整搜索结束就能显示目前最佳的结果
+fn with_candidate_progress<T>(args: &Value, f: impl FnOnce() -> T) -> T {
+    let Some(token) = args.get("_meta").and_then(|m| m.get("progressToken")).cloned() else {
+        return f();
+    };
+    let mut sent = 0;
+    let mut best_so_far: Option<(bool, f64)> = None;
+    let observer = move |family: &'static str, results: &[engine::DecodeResult]| {
+        let key = |r: &engine::DecodeResult| (engine::is_verified(r), r.score);
+        let Some(best) = results.iter().max_by(|a, b| key(a).partial_cmp(&key(b)).unwrap_or(std::cmp::Ordering::Equal)) else {
+            return;
+        };
+        // 只在最佳候选变得更好时推送，同分的其他方案不重复推送
+        if best_so_far.is_some_and(|prev| key(best) <= prev) {
+            return;
+        }
+        best_so_far = Some(key(best));
+        sent += 1;
+        let message = format!(
+            "[{}] 目前最佳（共 {} 个候选）: {} → {} (得分 {:.1})",
+            family,
+            results.len(),
+            best.method,
+            sanitize::display(&format!("{:?}", best.decoded)),
+            best.score
+        );
+        server::notify_progress(&token, sent, None, &message);
+    };
+    engine::with_progress(observer, f)
+}
+
+/// 按指定方案执行解码
+pub fn run_decode(text: &str, method: &str, budget: &engine::Budget) -> Vec<engine::DecodeResult> {
+    run_decode_report(text, method, budget).0
+}
+
+/// 同 [`run_decode`]，另返回自动解码因命中完整 flag 提前停止时跳过的方案族
+fn run_decode_report(text: &str, method: &str, budget: &engine::Budget) -> (Vec<engine::DecodeResult>, Vec<&'static str>) {
+    let mut skipped = Vec::new();
+    let mut results = match method {
+        "unicode_tags" => {
+            let mut results = engine::decode_unicode_tags(text, &budget.acceptance).into_iter().collect::<Vec<_>>();
+            results.append(&mut engine::decode_unicode_tag_runs(text, &budget.acceptance));
+            results
+        }
+        "steganographr" => {
+            engine::decode_steganographr(text, &budget.acceptance).into_iter().collect::<Vec<_>>()
+        }
+        "binary" => {
+            // 暴力尝试二进制
+            let analysis = engine::analyze(text);
+            let zw_all = engine::extract_all(text);
+            let top = engine::top_chars(&analysis);
+            engine::brute_binary(&zw_all, &top, budget)
+        }
+        "morse" => engine::brute_morse(text, &engine::top_chars(&engine::analyze(text)), budget, false),
+        "stegcloak" => engine::decode_stegcloak(text, &budget.stegcloak, &budget.acceptance).into_iter().collect(),
+        "330k" => {
+            let zw_all = engine::extract_all(text);
+            engine::encoding_presets()
+                .into_iter()
+                .find(|(key, _)| key == "330k_default")
+                .map(|(_, preset)| engine::decode_preset(&zw_all, &preset, &budget.acceptance))
+                .unwrap_or_default()
+        }
+        _ => {
+            // 预设 key: 直接用该预设的字符集做 N进制解码
+            match engine::encoding_presets().into_iter().find(|(key, _)| *key == method) {
+                Some((_, preset)) => engine::decode_preset(&engine::extract_all(text), &preset, &budget.acceptance),
+                None => {
+                    let (mut results, stopped) = engine::auto_decode_report(text, budget);
+                    skipped = stopped;
+                    // 没有可信结果时，尝试修复平台造成的字符替换/剥离
+                    if !results.iter().any(engine::is_verified) && !budget.expired() {
+                        results.extend(repair::repair_decode(text, budget));
+                    }
+                    results
+                }
+            }
+        }
+    };
+    engine::rank_results(&mut results);
+    (results, skipped)
+}
+
+/// 格式化解码结果列表
+///
+/// 没有任何结果时附加诊断：每个方案的拒绝原因和下一步建议
+fn format_decode_results(
+    results: &[engine::DecodeResult],
+    prefix: &str,
+    text: &str,
+    acceptance: &engine::Acceptance,
+    options: &stegcloak::Options,
+) -> ToolCallResult {
+    if results.is_empty() {
+        let trace = diagnose::diagnose_failure(text, acceptance, options);
+        let mut output = format!("{}未找到有效解码结果。\n\n诊断:\n", prefix);
+        for (scheme, reason) in &trace.attempts {
+            output.push_str(&format!("  ✗ {}: {}\n", scheme, reason));
+        }
+        output.push_str("\n建议:\n");
+        for s in &trace.suggestions {
+            output.push_str(&format!("  - {}\n", s));
+        }
+        return ToolCallResult::success(output);
+    }
+
+    let mut output = prefix.to_string();
+    output.push_str(&format!("找到 {} 个可能的解码结果（按置信度排序）:\n\n", results.len()));
+    for (i, r) in results.iter().enumerate().take(10) {
+        output.push_str(&format!(
+            "[{}] 方案: {}\n    ID: {}\n    得分: {:.1}\n    结果: {}\n",
+            i + 1, r.method, r.id(), r.score, sanitize::display(&r.decoded)
+        ));
+        if !r.meta.is_empty() {
+            let meta: Vec<String> = r.meta.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            output.push_str(&format!("    元数据: {}\n", meta.join("; ")));
+        }
+        // allow_binary 的结果逐字节显示，避免控制字符和 Latin-1 字符难以辨认
+        if acceptance.allow_binary
+            && r.decoded.chars().all(|c| (c as u32) < 0x100)
+            && r.decoded.chars().any(|c| c.is_control() || !c.is_ascii())
+        {
+            let hex: Vec<String> = r.decoded.chars().map(|c| format!("{:02x}", c as u32)).collect();
+            output.push_str(&format!("    字节: {}\n", hex.join(" ")));
+        }
+        for reading in numeric::readings(&r.decoded) {
+            output.push_str(&format!("    数值: {}\n", reading.describe()));
+        }
+        for hint in hints::hints(text, r, acceptance) {
+            output.push_str(&format!("    提示: {}\n", hint));
+        }
+        output.push('\n');
+    }
+    if let Some(best) = results.first() {
+        output.push_str(&format!("★ 最佳结果: {}\n", sanitize::display(&best.decoded)));
+    }
+    ToolCallResult::success(output)
+}
+
+fn exec_encode(args: &Value) -> ToolCallResult {
+    if args.get("payloads").is_some() {
+        return exec_encode_multi(args);
+    }
+    let message = match get_str(args, "message") {
+        Some(m) => m,
+        None => return ToolCallResult::failure(ErrorCode::InputMissing, "缺少参数: message（或 payloads）"),
+    };
+    let method = get_str(args, "method").unwrap_or("binary");
+    if let Err(e) = check_encodable(method, message) {
+        return e;
+    }
+
+    let cover = match resolve_cover(args) {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+    let cover = cover.as_str();
+
+    if let Some(name) = get_str(args, "emulate") {
+        let Some(tool) = emulate::EmulatedTool::parse(name) else {
+            return ToolCallResult::failure(ErrorCode::UnsupportedMethod, format!(
+                "未知的仿真工具: {}（可用: {}）",
+                name,
+                emulate::EmulatedTool::names().join(", ")
+            ));
+        };
+        let encoded = match emulate::encode(tool, message, cover, &stegcloak_options(args)) {
+            Ok(e) => e,
+            Err(e) => return ToolCallResult::failure(ErrorCode::UnsupportedMethod, e),
+        };
+        let output = format!(
+            "仿真工具: {}\n消息: {}\n编码后长度: {} 字符\n",
+            tool.name(),
+            message,
+            encoded.chars().count()
+        );
+        let placement = match tool {
+            emulate::EmulatedTool::Web330k => "interleave",
+            emulate::EmulatedTool::StegCloak => "after_first_word",
+            _ => "after_first_char",
+        };
+        let sidecar = sidecar::describe(sidecar::emulated_scheme(tool, &stegcloak_options(args)), Some(message), placement, &encoded);
+        return finish_encode(args, output, &encoded, cover, sidecar);
+    }
+    if get_str(args, "placement") == Some("code") {
+        return exec_encode_code(args, message, method, cover);
+    }
+    if let Some(density) = args.get("density").and_then(|v| v.as_f64()) {
+        return exec_encode_density(args, message, method, cover, density);
+    }
+
+    // 载体含 {{ZW}} 标记时，载荷精确注入到标记处（多个标记则依次切分）
+    let markers = cover.matches(engine::COVER_MARKER).count();
+    let encoded = if markers > 0 {
+        encode_with(method, message, "", &stegcloak_options(args)).map(|payload| engine::inject_at_markers(cover, &payload, engine::COVER_MARKER))
+    } else {
+        encode_with(method, message, cover, &stegcloak_options(args))
+    };
+    let encoded = match encoded {
+        Some(e) => e,
+        None => return ToolCallResult::failure(ErrorCode::UnsupportedMethod, format!("未知编码方法: {}", method)),
+    };
+
+    let mut output = String::new();
+    output.push_str(&format!("编码方法: {}\n", method));
+    output.push_str(&format!("消息: {}\n", message));
+    output.push_str(&format!("编码后长度: {} 字符\n", encoded.chars().count()));
+    if markers > 0 {
+        output.push_str(&format!("插入位置: {} 个 {} 标记\n", markers, engine::COVER_MARKER));
+    }
+    let placement = match (markers, cover.is_empty()) {
+        (0, true) => "none",
+        (0, false) if method == "stegcloak" => "after_first_word",
+        (0, false) => "middle",
+        _ => "markers",
+    };
+    let scheme = sidecar::builtin_scheme(method, &stegcloak_options(args)).unwrap_or_default();
+    let sidecar = sidecar::describe(scheme, Some(message), placement, &encoded);
+    finish_encode(args, output, &encoded, cover, sidecar)
+}
+
+/// 源代码载体编码: 载荷只放进注释/字符串字面量
+fn exec_encode_code(args: &Value, message: &str, method: &str, cover: &str) -> ToolCallResult {
+    if args.get("density").is_some() || cover.contains(engine::COVER_MARKER) {
+        return ToolCallResult::error(format!("placement=code 不能与 density 或 {} 标记同时使用", engine::COVER_MARKER));
+    }
+    let lang = match get_str(args, "language").unwrap_or("auto") {
+        "auto" => match get_str(args, "cover_file").and_then(|p| codecover::Language::from_path(Path::new(p))) {
+            Some(l) => l,
+            None => return ToolCallResult::error("无法从 cover_file 扩展名推断语言，请指定 language"),
+        },
+        other => match codecover::Language::parse(other) {
+            Some(l) => l,
+            None => return ToolCallResult::error(format!("未知 language: {}", other)),
+        },
+    };
+    let Some(payload) = encode_with(method, message, "", &stegcloak_options(args)) else {
+        return ToolCallResult::failure(ErrorCode::UnsupportedMethod, format!("未知编码方法: {}", method));
+    };
+    let include_strings = args.get("include_strings").and_then(|v| v.as_bool()).unwrap_or(false);
+    let placed = match codecover::place_in_code(cover, &payload, lang, include_strings) {
+        Some(p) => p,
+        None => {
+            return ToolCallResult::error(format!(
+                "载体中没有可用的注释或字符串字面量（语言族 {}），无法在不破坏代码的前提下嵌入",
+                lang.name()
+            ))
+        }
+    };
+
+    let mut output = String::new();
+    output.push_str(&format!("编码方法: {}\n", method));
+    output.push_str(&format!("消息: {}\n", message));
+    output.push_str(&format!(
+        "代码载体 ({}): 载荷分 {} 段放入 {} 处注释、{} 处字符串字面量\n",
+        lang.name(),
+        placed.comments + placed.strings,
+        placed.comments,
+        placed.strings
+    ));
+    output.push_str(&format!("编码后长度: {} 字符\n", placed.text.chars().count()));
+    let scheme = sidecar::builtin_scheme(method, &stegcloak_options(args)).unwrap_or_default();
+    let mut sidecar = sidecar::describe(scheme, Some(message), "code", &placed.text);
+    sidecar["placement"]["language"] = json!(lang.name());
+    finish_encode(args, output, &placed.text, cover, sidecar)
+}
+
+/// 密度受限编码: 载荷均匀打散到载体中，超出容量时拆成多份文档或报错
+fn exec_encode_density(args: &Value, message: &str, method: &str, cover: &str, density: f64) -> ToolCallResult {
+    if density <= 0.0 {
+        return ToolCallResult::error("density 必须大于 0");
+    }
+    if cover.contains(engine::COVER_MARKER) {
+        return ToolCallResult::error(format!("density 与 {} 标记不能同时使用", engine::COVER_MARKER));
+    }
+    let Some(payload) = encode_with(method, message, "", &stegcloak_options(args)) else {
+        return ToolCallResult::failure(ErrorCode::UnsupportedMethod, format!("未知编码方法: {}", method));
+    };
+    let needed = payload.chars().count();
+    let capacity = engine::density_capacity(cover, density);
+    if capacity == 0 {
+        return ToolCallResult::error(format!("载体在密度 {} 下容量为 0，请提供更长的载体文本", density));
+    }
+
+    let split = args.get("split_documents").and_then(|v| v.as_bool()).unwrap_or(false);
+    let documents = needed.div_ceil(capacity);
+    if documents > 1 && !split {
+        let visible_needed = (needed as f64 * 100.0 / density).ceil() as usize;
+        return ToolCallResult::error(format!(
+            "载荷需要 {} 个不可见字符，载体在密度 {}/100 下只能容纳 {} 个。\n可选: 提供至少 {} 个可见字符的载体、调高 density，或设置 split_documents=true 拆成 {} 份文档",
+            needed, density, capacity, visible_needed, documents
+        ));
+    }
+
+    let payload: Vec<char> = payload.chars().collect();
+    let docs: Vec<String> = payload
+        .chunks(capacity)
+        .map(|chunk| engine::spread_payload(cover, &chunk.iter().collect::<String>()))
+        .collect();
+
+    let mut output = String::new();
+    output.push_str(&format!("编码方法: {}\n", method));
+    output.push_str(&format!("消息: {}\n", message));
+    output.push_str(&format!(
+        "密度: 每 100 个可见字符最多 {} 个不可见字符（载体容量 {}，载荷 {}）\n",
+        density, capacity, needed
+    ));
+    let scheme = sidecar::builtin_scheme(method, &stegcloak_options(args)).unwrap_or_default();
+    let describe = |doc: &str| {
+        let mut sidecar = sidecar::describe(scheme.clone(), Some(message), "density", doc);
+        sidecar["placement"]["density"] = json!(density);
+        sidecar
+    };
+    if docs.len() == 1 {
+        return finish_encode(args, output, &docs[0], cover, describe(&docs[0]));
+    }
+
+    output.push_str(&format!("载荷已拆分为 {} 份文档，解码时请按顺序拼接（可用 zw_session_feed 依次喂入）\n", docs.len()));
+    let out_path = get_str(args, "output_path");
+    for (i, doc) in docs.iter().enumerate() {
+        output.push_str(&format!("\n--- 文档 {}/{} ({} 字符) ---\n", i + 1, docs.len(), doc.chars().count()));
+        let path = out_path.map(|p| numbered_path(p, i + 1));
+        let (doc, bom_note) = match guard_leading_bom(args, doc) {
+            Ok(r) => r,
+            Err(e) => return e,
+        };
+        let doc = doc.as_str();
+        output.push_str(&bom_note);
+        output.push_str(&script_conflict_note(doc, cover));
+        let mut sidecar = describe(doc);
+        sidecar["placement"]["document"] = json!({ "index": i + 1, "total": docs.len() });
+        let emitted = emit_text_to(args, &mut output, "文档", doc, path.as_deref(), &[OutputFormat::Repr], true)
+            .and_then(|_| emit_sidecar(args, &mut output, &sidecar, path.as_deref()));
+        if let Err(e) = emitted {
+            return e;
+        }
+    }
+    ToolCallResult::success(output)
+}
+
+/// 在文件名（扩展名之前）加上 `_<n>` 后缀；工作区 URI 只改产物名
+fn numbered_path(path: &str, n: usize) -> String {
+    if let Some(name) = path.strip_prefix(workspace::URI_PREFIX) {
+        let name = if name.is_empty() { "encoded.txt" } else { name };
+        return format!("{}{}", workspace::URI_PREFIX, numbered_path(name, n));
+    }
+    let p = Path::new(path);
+    let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("encoded");
+    let name = match p.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}_{}.{}", stem, n, ext),
+        None => format!("{}_{}", stem, n),
+    };
+    p.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// 载体文本: cover_file 优先于 cover_text
+fn resolve_cover(args: &Value) -> Result<String, ToolCallResult> {
+    match get_str(args, "cover_file") {
+        Some(cover_path) => read_file_auto(cover_path, false),
+        None => Ok(get_str(args, "cover_text").unwrap_or("").to_string()),
+    }
+}
+
+/// 默认的防护前缀（avoid_leading_bom=prefix）
+const DEFAULT_BOM_PREFIX: &str = ".";
+
+/// 编码结果以 U+FEFF 开头时的处理（avoid_leading_bom）：不少编辑器与读取库会把它当作 BOM 静默删掉，
+/// 破坏载荷。`warn`（默认）写文件时提示；`shift` 把开头的不可见字符移到第一个可见字符之后；
+/// `prefix` 在开头加一个可见字符（`bom_prefix`，默认 `.`）；`off` 不处理。返回处理后的文本与说明
+fn guard_leading_bom(args: &Value, encoded: &str) -> Result<(String, String), ToolCallResult> {
+    let mode = get_str(args, "avoid_leading_bom").unwrap_or("warn");
+    if !["warn", "shift", "prefix", "off"].contains(&mode) {
+        return Err(ToolCallResult::error(format!("未知 avoid_leading_bom: {}（可用: warn, shift, prefix, off）", mode)));
+    }
+    if !encoded.starts_with('\u{FEFF}') {
+        return Ok((encoded.to_string(), String::new()));
+    }
+    match mode {
+        "shift" => {
+            let lead = encoded.chars().take_while(|&c| chars::is_zero_width(c)).count();
+            let mut rest = encoded.chars().skip(lead);
+            let Some(first) = rest.next() else {
+                return Err(ToolCallResult::error(
+                    "编码结果没有可见字符，无法把载荷移离开头；请提供载体文本或改用 avoid_leading_bom=prefix",
+                ));
+            };
+            let leading: String = encoded.chars().take(lead).collect();
+            let shifted = format!("{}{}{}", first, leading, rest.collect::<String>());
+            Ok((shifted, format!("开头的 {} 个不可见字符已移到第一个可见字符 {:?} 之后，避免 U+FEFF 被当作 BOM 删除\n", lead, first)))
+        }
+        "prefix" => {
+            let prefix = get_str(args, "bom_prefix").unwrap_or(DEFAULT_BOM_PREFIX);
+            if prefix.is_empty() || prefix.chars().all(|c| chars::is_zero_width(c) || c.is_whitespace()) {
+                return Err(ToolCallResult::error("bom_prefix 必须含可见字符"));
+            }
+            let guarded = format!("{}{}", prefix, encoded);
+            let note = format!(
+                "已在开头加上防护字符 {:?}，避免 U+FEFF 被当作 BOM 删除（编码后长度变为 {} 字符）\n",
+                prefix,
+                guarded.chars().count()
+            );
+            Ok((guarded, note))
+        }
+        "warn" if get_str(args, "output_path").is_some() => Ok((
+            encoded.to_string(),
+            "⚠ 编码结果以 U+FEFF 开头，写入文件后可能被编辑器当作 BOM 删除而破坏载荷；可设置 avoid_leading_bom=shift 或 prefix\n".to_string(),
+        )),
+        _ => Ok((encoded.to_string(), String::new())),
+    }
+}
+
+/// 追加编码文本并按需写入 output_path
+fn finish_encode(args: &Value, mut output: String, encoded: &str, cover: &str, mut sidecar: Value) -> ToolCallResult {
+    let (guarded, bom_note) = match guard_leading_bom(args, encoded) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    if guarded != encoded {
+        sidecar::update_output(&mut sidecar, &guarded);
+    }
+    let encoded = guarded;
+    output.push_str(&bom_note);
+    output.push_str(&script_conflict_note(&encoded, cover));
+    let emitted = emit_text(args, &mut output, "编码文本", &encoded, &[OutputFormat::Repr, OutputFormat::Text], true)
+        .and_then(|_| emit_sidecar(args, &mut output, &sidecar, get_str(args, "output_path")));
+    match emitted {
+        Ok(()) => ToolCallResult::success(output),
+        Err(e) => e,
+    }
+}
+
+/// 载荷字符在载体的文字环境中有含义（波斯语中的 ZWNJ、emoji 旁的 ZWJ 等）时的警告，
+/// 并给出字符集不冲突的编码方法；没有冲突时返回空串
+fn script_conflict_note(encoded: &str, cover: &str) -> String {
+    let conflicts = profile::script_conflicts(encoded, cover);
+    if conflicts.is_empty() {
+        return String::new();
+    }
+    let mut note = String::from("⚠ 载荷会改变载体的显示，容易暴露:\n");
+    for c in &conflicts {
+        note.push_str(&format!(
+            "  - U+{:04X} {} ×{}: {}\n",
+            c.ch as u32,
+            chars::name_of(c.ch).unwrap_or_default(),
+            c.count,
+            c.kind.describe()
+        ));
+    }
+    let conflicting = |ch: char| {
+        conflicts.iter().any(|c| c.ch == ch || (chars::is_unicode_tag(c.ch) && chars::is_unicode_tag(ch)))
+    };
+    let alternatives: Vec<String> = recommend::recommend(&[])
+        .into_iter()
+        .filter(|c| matches!(c.source, recommend::Source::Method(_)) && !c.charset.iter().any(|&ch| conflicting(ch)))
+        .map(|c| {
+            let charset: Vec<String> = c.charset.iter().map(|&ch| recommend::label(ch)).collect();
+            let note = c.note.map(|n| format!("，{}", n)).unwrap_or_default();
+            format!("{}（字符集 {}{}）", c.source.describe(), charset.join(", "), note)
+        })
+        .collect();
+    if !alternatives.is_empty() {
+        note.push_str(&format!("  建议改用: {}\n", alternatives.join("；")));
+    }
+    if conflicts.iter().any(|c| c.kind == profile::ConflictKind::EmojiSequence) {
+        note.push_str("  或用 {{ZW}} 标记把载荷放到不紧挨 emoji 的位置\n");
+    } else {
+        note.push_str("  或用 {{ZW}} 标记把载荷放到空格、标点旁，避开词内\n");
+    }
+    note
+}
+
+/// 附上编码描述（JSON）；sidecar=true 时另写到输出文件旁的 `<output_path>.zw.json`
+fn emit_sidecar(args: &Value, output: &mut String, sidecar: &Value, path: Option<&str>) -> Result<(), ToolCallResult> {
+    output.push_str(&format!("\n编码描述（JSON）: {}\n", sidecar));
+    if args.get("sidecar").and_then(|v| v.as_bool()).unwrap_or(false) {
+        let target = match path {
+            Some(p) => format!("{}.zw.json", p),
+            None => format!("{}encoded.zw.json", workspace::URI_PREFIX),
+        };
+        let pretty = serde_json::to_string_pretty(sidecar).unwrap_or_default();
+        let location = write_file(&target, &pretty, "encoded.zw.json")?;
+        output.push_str(&format!("✓ 已写入编码描述: {}\n", location));
+    }
+    Ok(())
+}
+
+/// 变换结果在响应中的展示格式（output_format）
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Text,
+    Repr,
+    Base64,
+}
+
+fn output_formats(args: &Value, defaults: &[OutputFormat]) -> Result<Vec<OutputFormat>, ToolCallResult> {
+    match get_str(args, "output_format") {
+        None => Ok(defaults.to_vec()),
+        Some("text") => Ok(vec![OutputFormat::Text]),
+        Some("repr") => Ok(vec![OutputFormat::Repr]),
+        Some("base64") => Ok(vec![OutputFormat::Base64]),
+        Some(other) => Err(ToolCallResult::error(format!("未知 output_format: {}（可用: text, repr, base64）", other))),
+    }
+}
+
+/// 变换类工具（zw_encode、zw_strip 等）共用的输出处理:
+/// 按 output_format 内联返回结果，并按需写入 output_path。未给出 inline 时按 `inline_default`：
+/// zw_encode 一向总是返回编码文本，zw_strip 只在未写文件时返回清理后文本
+fn emit_text(
+    args: &Value,
+    output: &mut String,
+    label: &str,
+    text: &str,
+    defaults: &[OutputFormat],
+    inline_default: bool,
+) -> Result<(), ToolCallResult> {
+    emit_text_to(args, output, label, text, get_str(args, "output_path"), defaults, inline_default)
+}
+
+fn emit_text_to(
+    args: &Value,
+    output: &mut String,
+    label: &str,
+    text: &str,
+    path: Option<&str>,
+    defaults: &[OutputFormat],
+    inline_default: bool,
+) -> Result<(), ToolCallResult> {
+    let formats = output_formats(args, defaults)?;
+    let inline = args
+        .get("inline")
+        .or_else(|| args.get("include_encoded_text"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(inline_default);
+    if inline {
+        for format in formats {
+            match format {
+                OutputFormat::Repr => output.push_str(&format!("{}（repr）: {:?}\n", label, text)),
+                OutputFormat::Text => output.push_str(&format!("\n{}:\n{}\n", label, text)),
+                OutputFormat::Base64 => output.push_str(&format!(
+                    "{}（base64）: {}\n",
+                    label,
+                    ingest::encode_base64(text.as_bytes())
+                )),
+            }
+        }
+    } else {
+        output.push_str(&format!("（已省略{}，需要时设置 inline=true）\n", label));
+    }
+    if let Some(p) = path {
+        let location = write_file(p, text, "output.txt")?;
+        output.push_str(&format!("\n✓ 已写入文件: {}\n", location));
+    }
+    Ok(())
+}
+
+/// 多载荷编码: 每段消息独立编码，分别放入载体的不同位置
+fn exec_encode_multi(args: &Value) -> ToolCallResult {
+    let items = match args.get("payloads").and_then(|v| v.as_array()) {
+        Some(items) if !items.is_empty() => items,
+        _ => return ToolCallResult::error("payloads 应为非空数组"),
+    };
+    let default_method = get_str(args, "method").unwrap_or("binary");
+    let mut sections = Vec::new();
+    let mut schemes = Vec::new();
+    let mut output = format!("多载荷编码: {} 段\n", items.len());
+    for (i, item) in items.iter().enumerate() {
+        let Some(message) = get_str(item, "message") else {
+            return ToolCallResult::error(format!("payloads[{}] 缺少 message", i));
+        };
+        let method = get_str(item, "method").unwrap_or(default_method);
+        let label = get_str(item, "label").map(str::to_string).unwrap_or_else(|| format!("#{}", i + 1));
+        if let Err(e) = check_encodable(method, message) {
+            return e;
+        }
+        let Some(section) = encode_with(method, message, "", &stegcloak_options(args)) else {
+            return ToolCallResult::failure(ErrorCode::UnsupportedMethod, format!("payloads[{}] 未知编码方法: {}", i, method));
+        };
+        output.push_str(&format!(
+            "  [{}] {} 方法={} 消息={:?} ({} 个不可见字符)\n",
+            i + 1,
+            label,
+            method,
+            message,
+            section.chars().count()
+        ));
+        sections.push(section);
+        schemes.push(json!({
+            "label": label,
+            "message": message,
+            "scheme": sidecar::builtin_scheme(method, &stegcloak_options(args)).unwrap_or_default(),
+        }));
+    }
+
+    let cover = match resolve_cover(args) {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+    let encoded = match engine::place_sections(&cover, &sections, engine::COVER_MARKER) {
+        Some(e) => e,
+        None => {
+            return ToolCallResult::error(format!(
+                "载体过短: {} 段载荷至少需要 {} 个可见字符隔开（或提供同样数量的 {} 标记）",
+                sections.len(),
+                sections.len() + 1,
+                engine::COVER_MARKER
+            ))
+        }
+    };
+    output.push_str(&format!("编码后长度: {} 字符\n", encoded.chars().count()));
+    let scheme = json!({ "scheme": "multi", "payloads": schemes });
+    let sidecar = sidecar::describe(scheme, None, "sections", &encoded);
+    finish_encode(args, output, &encoded, &cover, sidecar)
+}
+
+/// 按编码方法把消息嵌入载体，未知方法返回 None
+fn encode_with(method: &str, message: &str, cover: &str, options: &stegcloak::Options) -> Option<String> {
+    let encoded = match method {
+        "binary" => {
+            let zw = engine::encode_binary(message, '\u{200B}', '\u{200C}', 8);
+            if !cover.is_empty() {
+                let mid = cover.chars().count() / 2;
+                let prefix: String = cover.chars().take(mid).collect();
+                let suffix: String = cover.chars().skip(mid).collect();
+                format!("{}{}{}", prefix, zw, suffix)
+            } else {
+                zw
+            }
+        }
+        "steganographr" => engine::encode_steganographr(message, cover),
+        "tags" => engine::encode_tags(message, cover),
+        "330k" => {
+            let charset = vec!['\u{200C}', '\u{200D}', '\u{202C}', '\u{FEFF}'];
+            engine::encode_330k(message, cover, &charset)
+        }
+        "morse" => engine::encode_morse(message, cover),
+        "stegcloak" => stegcloak::hide(message, cover, options),
+        _ => return None,
+    };
+    Some(encoded)
+}
+
+/// 莫尔斯码只能表示码表中的字符，其余字符不静默丢弃而是报错
+fn check_encodable(method: &str, message: &str) -> Result<(), ToolCallResult> {
+    if method != "morse" {
+        return Ok(());
+    }
+    let bad = morse::unsupported(message);
+    if bad.is_empty() {
+        return Ok(());
+    }
+    let listed: Vec<String> = bad.iter().map(|c| format!("{:?}", c)).collect();
+    Err(ToolCallResult::error(format!(
+        "莫尔斯码无法表示这些字符: {}（只支持字母、数字、空格与 . , ? ' ! / ( ) & : ; = + - _ \" $ @，字母不区分大小写）",
+        listed.join(" ")
+    )))
+}
+
+/// 文本是 StegCloak 载荷但没能解开（缺少口令、HMAC 不符）时的提示；其余情况为空
+fn stegcloak_note(text: &str, options: &stegcloak::Options) -> String {
+    if !stegcloak::looks_like(text) {
+        return String::new();
+    }
+    match stegcloak::reveal(text, options.password.as_deref()) {
+        Err(e @ stegcloak::RevealError::PasswordRequired(_)) => {
+            format!("⚠ StegCloak: {}，下面的暴力候选多半是密文的误读\n\n", e.describe())
+        }
+        Err(e @ (stegcloak::RevealError::IntegrityFailed | stegcloak::RevealError::Corrupt(_))) => {
+            format!("⚠ StegCloak: {}\n\n", e.describe())
+        }
+        _ => String::new(),
+    }
+}
+
+/// zw_mass_encode 单次调用的最大任务数
+const MAX_MASS_ENCODE_JOBS: usize = 1000;
+
+/// zw_mass_encode 的最大并行线程数
+const MAX_MASS_ENCODE_THREADS: usize = 16;
+
+/// 批量编码单个任务的汇总行
+struct MassEncodeRow {
+    method: String,
+    message: String,
+    ok: bool,
+    detail: String,
+}
+
+/// 执行一个批量编码任务：合并共用参数，未指定 output_path 时写入工作区
+fn run_mass_encode_job(index: usize, job: &Value, defaults: &serde_json::Map<String, Value>) -> MassEncodeRow {
+    let mut args = defaults.clone();
+    match job.as_object() {
+        Some(obj) => args.extend(obj.iter().map(|(k, v)| (k.clone(), v.clone()))),
+        None => {
+            return MassEncodeRow {
+                method: "-".to_string(),
+                message: "-".to_string(),
+                ok: false,
+                detail: "任务应为对象".to_string(),
+            }
+        }
+    }
+    args.entry("output_path").or_insert_with(|| json!(format!("{}vector_{}.txt", workspace::URI_PREFIX, index + 1)));
+    args.entry("inline").or_insert(json!(false));
+    let args = Value::Object(args);
+    let method = get_str(&args, "emulate")
+        .map(|e| format!("emulate={}", e))
+        .unwrap_or_else(|| get_str(&args, "method").unwrap_or("binary").to_string());
+    let message = get_str(&args, "message").unwrap_or("").to_string();
+    let result = exec_encode(&args);
+    let text = result.content.first().map(|c| c.text.as_str()).unwrap_or("");
+    let ok = result.is_error != Some(true);
+    let detail = if ok {
+        let length = text.lines().find_map(|l| l.strip_prefix("编码后长度: ")).unwrap_or("");
+        let written: Vec<&str> = text.lines().filter_map(|l| l.strip_prefix("✓ 已写入文件: ")).collect();
+        format!("{}，{}", written.join(", "), length)
+    } else {
+        text.lines().next().unwrap_or("失败").to_string()
+    };
+    MassEncodeRow { method, message, ok, detail }
+}
+
+fn exec_mass_encode(args: &Value) -> ToolCallResult {
+    let jobs = match args.get("jobs").and_then(|v| v.as_array()) {
+        Some(jobs) if !jobs.is_empty() => jobs,
+        _ => return ToolCallResult::error("jobs 应为非空数组"),
+    };
+    if jobs.len() > MAX_MASS_ENCODE_JOBS {
+        return ToolCallResult::error(format!("任务数 {} 超过上限 {}", jobs.len(), MAX_MASS_ENCODE_JOBS));
+    }
+    let defaults = match args.get("defaults") {
+        None | Some(Value::Null) => serde_json::Map::new(),
+        Some(Value::Object(obj)) => obj.clone(),
+        Some(_) => return ToolCallResult::error("defaults 应为对象"),
+    };
+    let threads = args
+        .get("concurrency")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+        .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1))
+        .clamp(1, MAX_MASS_ENCODE_THREADS)
+        .min(jobs.len());
+
+    // 工作线程按序号领取任务；读取的文件字节数与文件哈希在各线程中记录，结束后计入当前请求。
+    // 回显与哈希模式是线程局部的，工作线程需沿用当前请求的设置
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let report_hashes = custody::enabled();
+    let raw_output = sanitize::raw_output();
+    let mut rows: Vec<(usize, MassEncodeRow)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let (done, hashes) = custody::with_hashes(report_hashes, || {
+                        sanitize::with_raw_output(raw_output, || {
+                            let mut done = Vec::new();
+                            loop {
+                                let i = next.fetch_add(1, Ordering::Relaxed);
+                                let Some(job) = jobs.get(i) else {
+                                    break;
+                                };
+                                done.push((i, run_mass_encode_job(i, job, &defaults)));
+                            }
+                            done
+                        })
+                    });
+                    (done, hashes, quota::take_bytes_read())
+                })
+            })
+            .collect();
+        let mut rows = Vec::new();
+        for worker in workers {
+            match worker.join() {
+                Ok((done, hashes, bytes)) => {
+                    quota::record_read(bytes as usize);
+                    custody::merge(hashes);
+                    rows.extend(done);
+                }
+                Err(_) => tracing::error!("zw_mass_encode worker panicked"),
+            }
+        }
+        rows
+    });
+    rows.sort_by_key(|(i, _)| *i);
+
+    let succeeded = rows.iter().filter(|(_, r)| r.ok).count();
+    let mut output = format!(
+        "批量编码: {} 个任务，成功 {}，失败 {}（{} 个线程）\n\n",
+        jobs.len(),
+        succeeded,
+        jobs.len() - succeeded,
+        threads
+    );
+    if rows.len() < jobs.len() {
+        output.push_str(&format!("⚠ {} 个任务因内部错误没有结果\n\n", jobs.len() - rows.len()));
+    }
+    output.push_str("| # | 方法 | 消息 | 状态 | 输出 / 错误 |\n|---|------|------|------|-------------|\n");
+    for (i, row) in &rows {
+        let message: String = row.message.chars().take(40).collect();
+        let message = if row.message.chars().count() > 40 { format!("{}…", message) } else { message };
+        output.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            i + 1,
+            row.method,
+            format!("{:?}", message).replace('|', "\\|"),
+            if row.ok { "✓" } else { "✗" },
+            row.detail.replace('|', "\\|")
+        ));
+    }
+    ToolCallResult::success(output)
+}
+
+fn exec_difficulty(args: &Value) -> ToolCallResult {
+    let text = match resolve_text(args) {
+        Ok(t) => t,
+        Err(e) => return e,
+    };
+    let budget = match parse_budget(args) {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
+    let estimate = difficulty::estimate(&text, &budget);
+    ToolCallResult::success(difficulty::format_estimate(&estimate))
+}
+
+fn exec_random_cover(args: &Value) -> ToolCallResult {
+    let style_name = get_str(args, "style").unwrap_or("english");
+    let style = match cover::Style::parse(style_name) {
+        Some(s) => s,
+        None => return ToolCallResult::error(format!("未知载体风格: {}", style_name)),
+    };
+    let method = get_str(args, "method").unwrap_or("binary");
+
+    // 载荷长度: 由 message 实际编码得到，或按 capacity 个 ASCII 字符估算
+    let payload = match (get_str(args, "message"), args.get("capacity").and_then(|v| v.as_u64())) {
+        (Some(m), _) => Some(m.to_string()),
+        (None, Some(n)) => Some("x".repeat(n as usize)),
+        (None, None) => None,
+    };
+    let zw_len = match &payload {
+        Some(p) => match encode_with(method, p, "", &stegcloak_options(args)) {
+            Some(e) => e.chars().count(),
+            None => return ToolCallResult::failure(ErrorCode::UnsupportedMethod, format!("未知编码方法: {}", method)),
+        },
+        None => 0,
+    };
+    // 默认让可见字符不少于不可见字符，且至少 80 个字符
+    let length = args
+        .get("length")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+        .unwrap_or_else(|| zw_len.max(80));
+    let seed = args.get("seed").and_then(|v| v.as_u64()).unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(1)
+    });
+
+    let text = cover::generate(style, length, get_str(args, "theme"), seed);
+    let mut output = format!("风格: {}\n", style.name());
+    if payload.is_some() {
+        output.push_str(&format!("编码方法: {} (载荷约 {} 个不可见字符)\n", method, zw_len));
+    }
+    output.push_str(&format!("载体长度: {} 字符\n", text.chars().count()));
+    output.push_str(&format!("随机种子: {}\n", seed));
+    output.push_str(&format!("\n载体文本:\n{}\n", text));
+    ToolCallResult::success(output)
+}
+
+fn exec_extract_file(args: &Value) -> ToolCallResult {
+    use md5::Md5;
+    use sha2::{Digest, Sha256};
+
+    let (text, log_note) = match resolve_text_logged(args) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let budget = match parse_budget(args) {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
+    let (text, lossy_note) = apply_lossy(args, text);
+    let (text, html_note) = match apply_html_decode(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let mut output = String::new();
+    output.push_str(&input::header(args));
+    output.push_str(&log_note);
+    output.push_str(&lossy_note);
+    output.push_str(&html_note);
+    if engine::analyze(&text).zero_width_count == 0 {
+        output.push_str("文本中未发现零宽字符。");
+        return ToolCallResult::success(output);
+    }
+
+    let streams = carve::byte_streams(&text, &budget);
+    let carved = carve::carve(&streams);
+    if carved.is_empty() {
+        output.push_str(&format!(
+            "✗ 在 {} 个字节流中未发现已知文件头（支持: {}）\n",
+            streams.len(),
+            carve::supported_types().join(", ")
+        ));
+        output.push_str("载荷可能是加密数据或文本，可用 zw_decode 的 enumerate: true 查看原始输出\n");
+        output.push_str(&timeout_note(args, &budget));
+        return ToolCallResult::success(output);
+    }
+
+    let output_dir = get_str(args, "output_dir").map(|d| (d, paths::resolve(d)));
+    if let Some((input, dir)) = &output_dir {
+        if let Err(d) = dir.check_write() {
+            return denied(d);
+        }
+        if let Err(e) = fs::create_dir_all(&dir.path) {
+            return ToolCallResult::failure(ErrorCode::IoError, dir.io_error("创建目录", input, &e));
+        }
+    }
+    output.push_str(&format!("✓ 提取到 {} 个嵌入文件:\n", carved.len()));
+    for (i, c) in carved.iter().enumerate() {
+        let name = format!("carved_{}.{}", i + 1, c.file_type.extension);
+        let saved = match &output_dir {
+            Some((input, dir)) => {
+                let file = paths::resolve(&Path::new(input).join(&name).to_string_lossy());
+                if let Err(d) = file.check_write() {
+                    output.push_str(&format!("\n[{}] 跳过: {}\n", i + 1, d.message()));
+                    continue;
+                }
+                match fs::write(&file.path, &c.bytes) {
+                    Ok(_) => {
+                        custody::record_output(&file.display, &c.bytes);
+                        file.display
+                    }
+                    Err(e) => dir.io_error("写入文件", &file.display, &e),
+                }
+            }
+            None => match workspace::store(&name, &c.bytes) {
+                Ok(artifact) => {
+                    custody::record_output(&artifact.uri, &c.bytes);
+                    format!("{}（可用 resources/read 读取）", artifact.uri)
+                }
+                Err(e) => format!("写入工作区失败: {}", e),
+            },
+        };
+        output.push_str(&format!(
+            "\n[{}] {}{}\n    来源: {}，偏移 {}\n    大小: {} 字节\n    MD5: {:x}\n    SHA256: {:x}\n    已保存: {}\n",
+            i + 1,
+            c.file_type.name,
+            if c.complete { "（找到文件尾）" } else { "（未找到文件尾，截到字节流末尾）" },
+            c.method,
+            c.offset,
+            c.bytes.len(),
+            Md5::digest(&c.bytes),
+            Sha256::digest(&c.bytes),
+            saved
+        ));
+    }
+    output.push_str(&timeout_note(args, &budget));
+    ToolCallResult::success(output)
+}
+
+fn exec_entropy_visual(args: &Value) -> ToolCallResult {
+    let text = match resolve_text(args) {
+        Ok(t) => t,
+        Err(e) => return e,
+    };
+    let chunk_size = args.get("chunk_size").and_then(|v| v.as_u64()).unwrap_or(1024).max(1) as usize;
+    let width = args.get("width").and_then(|v| v.as_u64()).unwrap_or(80).clamp(1, 1000) as usize;
+    let top = args.get("top").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
+
+    let chunks = density::profile(&text, chunk_size);
+    let total: usize = chunks.iter().map(|c| c.invisible).sum();
+    let mut output = String::new();
+    output.push_str(&input::header(args));
+    output.push_str(&format!(
+        "大小: {} 字节, {} 块 × {} 字节, 不可见字符 {} 个（分布于 {} 块）\n",
+        text.len(),
+        chunks.len(),
+        chunk_size,
+        total,
+        chunks.iter().filter(|c| c.invisible > 0).count()
+    ));
+    if total == 0 {
+        output.push_str("\n未发现不可见字符\n");
+        return ToolCallResult::success(output);
+    }
+
+    let densities: Vec<f64> = chunks.iter().map(|c| c.density()).collect();
+    let per_cell = chunks.len().div_ceil(width).max(1);
+    output.push_str(&format!(
+        "\n密度分布（每格 {} 字节，· 为无）:\n{}\n",
+        per_cell * chunk_size,
+        density::sparkline(&densities, width)
+    ));
+
+    output.push_str("\n密度最高的区域:\n");
+    for c in density::top_chunks(&chunks, top) {
+        output.push_str(&format!(
+            "  块 #{:<5} 字节 {}–{}: {} 个不可见字符（密度 {:.1}%）{}\n",
+            c.index,
+            c.start,
+            c.end,
+            c.invisible,
+            c.density() * 100.0,
+            c.first_line.map(|l| format!("，首个位于第 {} 行", l)).unwrap_or_default()
+        ));
+    }
+    ToolCallResult::success(output)
+}
+
+/// 流式输出时每个进度通知携带的行数
+const DUMP_CHUNK_LINES: usize = 500;
+
+fn exec_dump_raw(args: &Value) -> ToolCallResult {
+    let (text, log_note) = match resolve_text_logged(args) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, lossy_note) = apply_lossy(args, text);
+    let (text, html_note) = match apply_html_decode(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let (text, reference_note) = match apply_reference(args, text) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+    let mut prefix = String::new();
+    prefix.push_str(&input::header(args));
+    prefix.push_str(&log_note);
+    prefix.push_str(&lossy_note);
+    prefix.push_str(&html_note);
+    prefix.push_str(&reference_note);
+    let lines = engine::dump_raw_lines(&text);
+    if lines.is_empty() {
+        return ToolCallResult::success(format!("{}文本中未发现零宽字符。", prefix));
+    }
+
+    // 分页: offset/limit 以零宽字符条目计
+    let total = lines.len();
+    let offset = args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+    if offset >= total {
+        return ToolCallResult::error(format!("offset {} 超出范围（共 {} 个零宽字符）", offset, total));
+    }
+    let limit = args.get("limit").and_then(|v| v.as_u64()).filter(|&n| n > 0).map(|n| n as usize);
+    let end = limit.map_or(total, |n| (offset + n).min(total));
+    let page = &lines[offset..end];
+    let mut header = String::from("原始零宽字符序列");
+    if offset > 0 || end < total {
+        header.push_str(&format!(" (第 {}–{} 条，共 {} 条", offset, end - 1, total));
+        if end < total {
+            header.push_str(&format!("；下一页 offset={}", end));
         }
+        header.push(')');
     }
-    if raw.starts_with(&[0xFF, 0xFE]) {
-        // UTF-16 LE BOM
-        let iter = raw[2..].chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]]));
-        let text: String = char::decode_utf16(iter)
-            .map(|r| r.unwrap_or('\u{FFFD}'))
-            .collect();
-        return Ok(text);
+    header.push_str(":\n");
+
+    let stream = args.get("stream").and_then(|v| v.as_bool()).unwrap_or(false);
+    let token = args.get("_meta").and_then(|m| m.get("progressToken"));
+    let body = match (stream, token) {
+        (true, Some(token)) => {
+            // 分块通过 notifications/progress 推送，最终响应只含摘要
+            let chunks = page.len().div_ceil(DUMP_CHUNK_LINES);
+            for (k, chunk) in page.chunks(DUMP_CHUNK_LINES).enumerate() {
+                server::notify_progress(token, k + 1, Some(chunks), &(chunk.join("\n") + "\n"));
+            }
+            format!("已通过 {} 个 notifications/progress 分块推送 {} 行\n", chunks, page.len())
+        }
+        _ => page.iter().map(|l| format!("{}\n", l)).collect(),
+    };
+    let raw = format!("{}{}", header, body);
+
+    let group_size = match args.get("group_size").and_then(|v| v.as_u64()) {
+        Some(0) => return ToolCallResult::error("group_size 必须大于 0"),
+        g => g.map(|g| g as usize),
+    };
+    let annotate = args.get("annotate").and_then(|v| v.as_bool()).unwrap_or(false);
+    if annotate || group_size.is_some() {
+        let annotated = engine::dump_annotated(&text, group_size);
+        return ToolCallResult::success(format!("{}{}\n{}", prefix, raw, annotated));
     }
-    if raw.starts_with(&[0xFE, 0xFF]) {
-        // UTF-16 BE BOM
-        let iter = raw[2..].chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]]));
-        let text: String = char::decode_utf16(iter)
-            .map(|r| r.unwrap_or('\u{FFFD}'))
+    ToolCallResult::success(format!("{}{}", prefix, raw))
+}
+
+fn exec_hexdump(args: &Value) -> ToolCallResult {
+    let source = match InputSource::require(args) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+    let raw = match source.read_bytes() {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
+
+    let context = args.get("context").and_then(|v| v.as_u64()).unwrap_or(16).min(hexdump::MAX_CONTEXT as u64) as usize;
+    let max_regions = args.get("max_regions").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+
+    let (encoding, hits) = hexdump::locate_hits(&raw);
+    let mut output = source.header();
+    output.push_str(&format!("大小: {} 字节\n", raw.len()));
+    output.push_str(&format!("检测编码: {}\n", encoding));
+    if hits.is_empty() {
+        output.push_str("输入中未发现零宽字符。\n");
+        return ToolCallResult::success(output);
+    }
+    let total: usize = hits.iter().map(|h| h.zw_count).sum();
+    output.push_str(&format!("零宽序列: {} 段, 共 {} 个字符\n", hits.len(), total));
+    output.push_str(&hexdump::hexdump_regions(&raw, &hits, context, max_regions));
+    ToolCallResult::success(output)
+}
+
+/// 递归收集目录下的文件（跳过隐藏目录和 target 目录）
+pub fn collect_files(dir: &Path, out: &mut Vec<PathBuf>, max_files: usize) {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    let mut entries: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    entries.sort();
+    for path in entries {
+        if out.len() >= max_files {
+            return;
+        }
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        // 不跟随指向目录的符号链接（可能成环或指向扫描范围之外）；文件链接在读取时检查
+        let is_link = fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_symlink());
+        if path.is_dir() && is_link {
+            continue;
+        }
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "target" {
+                collect_files(&path, out, max_files);
+            }
+        } else if path.is_file() {
+            out.push(path);
+        }
+    }
+}
+
+fn exec_grep(args: &Value) -> ToolCallResult {
+    let mut targets: HashSet<u32> = HashSet::new();
+    if let Some(list) = args.get("codepoints").and_then(|v| v.as_array()) {
+        for item in list {
+            let cp = match item {
+                Value::String(s) => chars::parse_codepoint(s),
+                Value::Number(n) => n.as_u64().map(|n| n as u32),
+                _ => None,
+            };
+            match cp {
+                Some(cp) => {
+                    targets.insert(cp);
+                }
+                None => return ToolCallResult::error(format!("无法解析码点: {}", item)),
+            }
+        }
+    }
+    if let Some(category) = get_str(args, "category") {
+        let cps = chars::codepoints_in_category(category);
+        if cps.is_empty() {
+            return ToolCallResult::error(format!("未知分类: {}", category));
+        }
+        targets.extend(cps);
+    }
+    if targets.is_empty() {
+        return ToolCallResult::failure(ErrorCode::InputMissing, "缺少参数: 请提供 codepoints 或 category");
+    }
+
+    let context = args.get("context").and_then(|v| v.as_u64()).unwrap_or(10).min(grep::MAX_CONTEXT as u64) as usize;
+    let max_results = args.get("max_results").and_then(|v| v.as_u64()).unwrap_or(200) as usize;
+
+    // (来源名称, 文本, 各字符在原始文件中的字节偏移)
+    let mut sources: Vec<(String, String, Option<Vec<usize>>)> = Vec::new();
+    if let Some(dir) = get_str(args, "dir_path") {
+        let target = paths::resolve(dir);
+        if let Err(d) = target.check_read() {
+            return denied(d);
+        }
+        if !target.path.is_dir() {
+            return ToolCallResult::failure(ErrorCode::NotFound, format!("目录不存在: {}", target.describe(dir)));
+        }
+        let mut files = Vec::new();
+        collect_files(&target.path, &mut files, 10_000);
+        for f in files {
+            let name = f.to_string_lossy();
+            if let Ok(raw) = input::read_file_bytes(&name) {
+                let text = decode_bytes_auto(&raw, false);
+                let offsets = grep::file_offsets(&raw, &text);
+                sources.push((paths::resolve(&name).display, text, offsets));
+            }
+        }
+    } else {
+        let source = match InputSource::require(args) {
+            Ok(s) => s,
+            Err(e) => return e,
+        };
+        // 直接传入的文本没有“文件”可言，只给文本内偏移
+        let read = if let InputSource::Text(_) = source {
+            source.read_text(false).map(|t| (t, None))
+        } else {
+            source.read_bytes().map(|raw| {
+                let text = decode_bytes_auto(&raw, is_lossy(args));
+                let offsets = grep::file_offsets(&raw, &text);
+                (text, offsets)
+            })
+        };
+        match read {
+            Ok((text, offsets)) => sources.push((source.label().to_string(), text, offsets)),
+            Err(e) => return e,
+        }
+    }
+
+    let mut output = String::new();
+    let mut total = 0usize;
+    let mut files_with_hits = 0usize;
+    let mut text_offsets_shown = false;
+    for (name, text, offsets) in &sources {
+        if total >= max_results {
+            break;
+        }
+        let hits = grep::grep(text, &targets, context, max_results - total, offsets.as_deref());
+        if hits.is_empty() {
+            continue;
+        }
+        files_with_hits += 1;
+        total += hits.len();
+        // 能还原读入时的编码就给文件内偏移（可直接交给 zw_hexdump），否则给解码后文本的 UTF-8 偏移并注明
+        let unit = if offsets.is_some() { "文件字节" } else { "文本字节" };
+        text_offsets_shown |= offsets.is_none();
+        output.push_str(&format!("\n{} ({} 处)\n", name, hits.len()));
+        for h in &hits {
+            output.push_str(&format!(
+                "  U+{:04X}  {} {:>8}  字符 {:>7}  行 {:>5}:{:<4}  {}\n",
+                h.codepoint,
+                unit,
+                h.file_offset.unwrap_or(h.text_offset),
+                h.char_index,
+                h.line,
+                h.column,
+                h.context
+            ));
+        }
+    }
+
+    let mut header = format!(
+        "搜索 {} 个码点, {} 个来源中 {} 个有命中, 共 {} 处",
+        targets.len(),
+        sources.len(),
+        files_with_hits,
+        total
+    );
+    if total >= max_results {
+        header.push_str(&format!(" (已达上限 {})", max_results));
+    }
+    header.push('\n');
+    if total == 0 {
+        header.push_str("未找到指定字符。\n");
+    }
+    if text_offsets_shown {
+        output.push_str("\n注: 「文本字节」是解码后文本的 UTF-8 字节偏移（直接传入文本或无法还原文件编码时），不是文件内偏移\n");
+    }
+    ToolCallResult::success(format!("{}{}", header, output))
+}
+
+fn exec_git_scan(args: &Value) -> ToolCallResult {
+    let repo = match get_str(args, "repo_path") {
+        Some(p) => Path::new(p),
+        None => return ToolCallResult::failure(ErrorCode::InputMissing, "缺少参数: repo_path"),
+    };
+    if !repo.is_dir() {
+        return ToolCallResult::failure(ErrorCode::NotFound, format!("目录不存在: {}", repo.display()));
+    }
+    let include = match category_list(args, "include_categories") {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+    let exclude = match category_list(args, "exclude_categories") {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+    let keep = |c: char| {
+        chars::category_of(c).is_none_or(|cat| {
+            include.as_ref().is_none_or(|inc| inc.contains(&cat)) && exclude.as_ref().is_none_or(|exc| !exc.contains(&cat))
+        })
+    };
+    let max_findings = args.get("max_findings").and_then(|v| v.as_u64()).unwrap_or(200) as usize;
+
+    let range = get_str(args, "range");
+    let scanned = match range {
+        Some(r) => gitscan::scan_range(repo, r, &keep, max_findings).map(|(n, f)| (format!("提交范围 {} ({} 个提交)", r, n), f)),
+        None => {
+            let with_blame = args.get("blame").and_then(|v| v.as_bool()).unwrap_or(true);
+            gitscan::scan_worktree(repo, &keep, with_blame, max_findings)
+                .map(|(n, f)| (format!("工作区 {} ({} 个文件)", repo.display(), n), f))
+        }
+    };
+    let (scope, findings) = match scanned {
+        Ok(v) => v,
+        Err(e) => return ToolCallResult::error(e),
+    };
+
+    let mut output = format!("扫描范围: {}\n", scope);
+    if findings.is_empty() {
+        output.push_str("✓ 未发现不可见字符\n");
+        return ToolCallResult::success(output);
+    }
+    let files: HashSet<&str> = findings.iter().map(|f| f.file.as_str()).collect();
+    output.push_str(&format!("⚠ {} 个文件中共 {} 行含不可见字符", files.len(), findings.len()));
+    if findings.len() >= max_findings {
+        output.push_str(&format!("（已达上限 {}）", max_findings));
+    }
+    output.push('\n');
+
+    let mut current = "";
+    for f in &findings {
+        if f.file != current {
+            current = &f.file;
+            output.push_str(&format!("\n{}\n", f.file));
+        }
+        let cps: Vec<String> = f.codepoints.iter().map(|cp| format!("U+{:04X}", cp)).collect();
+        output.push_str(&format!("  行 {:>5}  [{}]  {}\n", f.line, cps.join(" "), f.preview));
+        if let Some(h) = &f.hunk {
+            output.push_str(&format!("           hunk: {}\n", h));
+        }
+        match &f.commit {
+            Some(c) => output.push_str(&format!(
+                "           引入提交: {} {} <{}> {} \"{}\"\n",
+                &c.hash[..c.hash.len().min(12)],
+                c.author,
+                c.email,
+                c.date,
+                c.summary
+            )),
+            None if range.is_none() && args.get("blame").and_then(|v| v.as_bool()).unwrap_or(true) => {
+                output.push_str("           引入提交: （未提交的改动）\n")
+            }
+            None => {}
+        }
+    }
+
+    if args.get("fail_on_findings").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return ToolCallResult::failure(ErrorCode::CheckFailed, output);
+    }
+    ToolCallResult::success(output)
+}
+
+fn exec_summarize_scan(args: &Value) -> ToolCallResult {
+    let max_files = args.get("max_files").and_then(|v| v.as_u64()).unwrap_or(2000) as usize;
+    let top = args.get("top").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+    let budget = match get_str(args, "budget") {
+        None => engine::Budget::fast(),
+        Some(name) => match engine::Budget::parse(name) {
+            Some(b) => b,
+            None => return ToolCallResult::error(format!("未知解码预算: {}", name)),
+        },
+    }
+    .with_timeout(tool_timeout(args));
+
+    let mut files: Vec<String> = Vec::new();
+    if let Some(list) = args.get("file_paths").and_then(|v| v.as_array()) {
+        files.extend(list.iter().filter_map(|v| v.as_str()).map(str::to_string));
+    }
+    let mut scope = Vec::new();
+    if let Some(dir) = get_str(args, "dir_path") {
+        let target = paths::resolve(dir);
+        if let Err(d) = target.check_read() {
+            return denied(d);
+        }
+        if !target.path.is_dir() {
+            return ToolCallResult::failure(ErrorCode::NotFound, format!("目录不存在: {}", target.describe(dir)));
+        }
+        let mut found = Vec::new();
+        collect_files(&target.path, &mut found, max_files);
+        files.extend(found.iter().map(|f| f.to_string_lossy().into_owned()));
+        scope.push(format!("目录 {}", target.display));
+    }
+    if args.get("file_paths").and_then(|v| v.as_array()).is_some() {
+        scope.push("指定文件".to_string());
+    }
+    if scope.is_empty() {
+        return ToolCallResult::failure(ErrorCode::InputMissing, "缺少参数: 请提供 dir_path 或 file_paths");
+    }
+    let truncated = files.len() > max_files;
+    files.truncate(max_files);
+
+    let mut reports = Vec::new();
+    let mut unreadable = Vec::new();
+    for file in &files {
+        match read_file_auto(file, false) {
+            Ok(text) => {
+                let name = paths::resolve(file).display;
+                reports.extend(triage::scan_file(&name, &text, &budget));
+            }
+            Err(e) => unreadable.push((file.clone(), e.content.first().map(|c| c.text.clone()).unwrap_or_default())),
+        }
+    }
+
+    let mut output = format!("扫描范围: {}\n", scope.join(" + "));
+    output.push_str(&format!(
+        "文件总数: {}{}，有发现: {}，无法读取: {}\n",
+        files.len(),
+        if truncated { format!("（已达上限 {}）", max_files) } else { String::new() },
+        reports.len(),
+        unreadable.len()
+    ));
+    if reports.is_empty() {
+        output.push_str("✓ 未发现不可见字符\n");
+    } else {
+        let total: usize = reports.iter().map(|r| r.zw_count).sum();
+        output.push_str(&format!("不可见字符合计: {} 个\n", total));
+
+        output.push_str("\n按分类:\n");
+        let mut categories: Vec<_> = triage::by_category(&reports).into_iter().collect();
+        categories.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then(b.1 .1.cmp(&a.1 .1)));
+        for (cat, (file_count, chars)) in categories {
+            output.push_str(&format!("  {}: {} 个文件, {} 个字符\n", cat, file_count, chars));
+        }
+
+        let schemes = triage::top_schemes(&reports);
+        if !schemes.is_empty() {
+            output.push_str("\n检测到的方案（各文件得分最高的解码）:\n");
+            for (scheme, count) in schemes.iter().take(10) {
+                output.push_str(&format!("  {}: {} 个文件\n", scheme, count));
+            }
+        }
+        let skipped = reports.iter().filter(|r| !r.decoded).count();
+        if skipped > 0 {
+            output.push_str(&format!("\n⚠ 解码时间用尽，{} 个文件只做了统计（可调大 timeout_ms）\n", skipped));
+        }
+
+        let ranked = triage::triage(&reports);
+        let mut levels: BTreeMap<triage::Priority, usize> = BTreeMap::new();
+        for r in &ranked {
+            *levels.entry(r.priority().0).or_default() += 1;
+        }
+        let level_summary: Vec<String> = levels.iter().rev().map(|(p, n)| format!("{} {}", p.label(), n)).collect();
+        output.push_str(&format!("\n分诊列表（{}；显示前 {}）:\n", level_summary.join(" / "), top.min(ranked.len())));
+        for (i, r) in ranked.iter().take(top).enumerate() {
+            let (priority, reason) = r.priority();
+            output.push_str(&format!("{:>3}. [{}] {}  ({} 个字符)\n      {}\n", i + 1, priority.label(), r.path, r.zw_count, sanitize::display(&reason)));
+            if let Some(best) = &r.best {
+                let preview: String = best.decoded.chars().take(80).collect();
+                output.push_str(&format!("      最佳解码: {}\n", sanitize::display(&preview)));
+            }
+        }
+        if ranked.len() > top {
+            output.push_str(&format!("  ... 另有 {} 个文件，可调大 top 查看\n", ranked.len() - top));
+        }
+    }
+    if !unreadable.is_empty() {
+        output.push_str("\n无法读取:\n");
+        for (file, reason) in unreadable.iter().take(10) {
+            output.push_str(&format!("  {}: {}\n", file, reason.lines().next().unwrap_or("")));
+        }
+        if unreadable.len() > 10 {
+            output.push_str(&format!("  ... 另有 {} 个\n", unreadable.len() - 10));
+        }
+    }
+    ToolCallResult::success(output)
+}
+
+fn exec_strip(args: &Value) -> ToolCallResult {
+    let text = match resolve_text(args) {
+        Ok(t) => t,
+        Err(e) => return e,
+    };
+    let policy_name = get_str(args, "policy").unwrap_or("all");
+    let policy = match sanitize::Policy::parse(policy_name) {
+        Some(p) => p,
+        None => return ToolCallResult::error(format!("未知清理策略: {}", policy_name)),
+    };
+
+    let result = sanitize::sanitize(&text, policy, get_str(args, "placeholder"));
+
+    let mut output = String::new();
+    output.push_str(&input::header(args));
+    output.push_str(&format!("清理策略: {}\n", policy.name()));
+    output.push_str(&format!(
+        "{}: {} 个, 保留: {} 个\n",
+        if policy == sanitize::Policy::Placeholder { "替换" } else { "删除" },
+        result.removed,
+        result.kept
+    ));
+    for (cp, count) in &result.removed_by_codepoint {
+        output.push_str(&format!("  U+{:04X}: {} 次\n", cp, count));
+    }
+    if result.unterminated_tag_runs > 0 {
+        output.push_str(&format!(
+            "⚠ {} 处 🏴 后的 Tag 串没有以 CANCEL TAG (U+E007F) 结束，不是完整的旗帜序列，已按可疑字符处理\n",
+            result.unterminated_tag_runs
+        ));
+    }
+
+    match emit_text(args, &mut output, "清理后文本", &result.text, &[OutputFormat::Text], get_str(args, "output_path").is_none()) {
+        Ok(()) => ToolCallResult::success(output),
+        Err(e) => e,
+    }
+}
+
+fn exec_segments(args: &Value) -> ToolCallResult {
+    let text = match resolve_text(args) {
+        Ok(t) => t,
+        Err(e) => return e,
+    };
+    let context = args.get("context").and_then(|v| v.as_u64()).unwrap_or(8) as usize;
+    let max_segments = args.get("max_segments").and_then(|v| v.as_u64()).unwrap_or(200) as usize;
+
+    let segments = engine::segment_details(&text, context);
+    let mut output = String::new();
+    output.push_str(&input::header(args));
+    if segments.is_empty() {
+        output.push_str("文本中未发现零宽字符。\n");
+        return ToolCallResult::success(output);
+    }
+
+    let lengths: Vec<usize> = segments.iter().map(|s| s.content.chars().count()).collect();
+    output.push_str(&format!(
+        "共 {} 段, 长度 最小 {} / 最大 {}\n\n",
+        segments.len(),
+        lengths.iter().min().copied().unwrap_or(0),
+        lengths.iter().max().copied().unwrap_or(0)
+    ));
+    if let Some(rep) = engine::detect_repetition(&text) {
+        output.push_str(&repetition_note(&rep));
+        output.push('\n');
+    }
+    for (n, seg) in segments.iter().enumerate().take(max_segments) {
+        let charset: Vec<String> = seg.charset.iter().map(|cp| format!("U+{:04X}", cp)).collect();
+        output.push_str(&format!(
+            "[{:4}] @{:<7} 长度 {:<4} 字符集 {}\n       前: {:?}  后: {:?}\n",
+            n + 1,
+            seg.start,
+            lengths[n],
+            charset.join("+"),
+            seg.before,
+            seg.after
+        ));
+    }
+    if segments.len() > max_segments {
+        output.push_str(&format!("\n... 另有 {} 段未显示\n", segments.len() - max_segments));
+    }
+    ToolCallResult::success(output)
+}
+
+fn exec_annotate(args: &Value) -> ToolCallResult {
+    let source = InputSource::from_args(args).map(|s| s.label().to_string()).unwrap_or_default();
+    let text = match resolve_text(args) {
+        Ok(t) => t,
+        Err(e) => return e,
+    };
+    let budget = match parse_budget(args) {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
+    if engine::extract_all(&text).is_empty() {
+        return ToolCallResult::success("文本中未发现零宽字符，无需标注。");
+    }
+    let result = if args.get("decode").and_then(|v| v.as_bool()).unwrap_or(true) {
+        let method = match decode_method(args) {
+            Ok(m) => m,
+            Err(e) => return e,
+        };
+        let results = run_decode(&text, method, &budget);
+        match get_str(args, "id").map(str::trim) {
+            Some(id) => match results.into_iter().find(|r| r.id().eq_ignore_ascii_case(id)) {
+                Some(r) => Some(r),
+                None => return ToolCallResult::failure(ErrorCode::NotFound, format!("未找到 ID 为 {} 的候选；请确认文本、method 与 budget 和 zw_decode 调用一致", id)),
+            },
+            None => results.into_iter().next(),
+        }
+    } else {
+        None
+    };
+
+    let annotation = annotate::annotate(&text, &source, result.as_ref(), &budget.acceptance);
+    let path = get_str(args, "output_path").unwrap_or("zw-tmp://annotated.md");
+    let saved = match write_file(path, &annotation.markdown, "annotated.md") {
+        Ok(saved) => saved,
+        Err(e) => return e,
+    };
+    let mut output = input::header(args);
+    output.push_str(&format!(
+        "已标注 {} 个不可见字符（{} 种）\n",
+        annotation.notes.len(),
+        annotation.distribution.len()
+    ));
+    match &result {
+        Some(r) => output.push_str(&format!(
+            "依据的解码结果: {}（{}，ID {}）\n",
+            sanitize::display(&r.decoded),
+            r.method,
+            r.id()
+        )),
+        None if args.get("decode").and_then(|v| v.as_bool()) == Some(false) => {}
+        None => output.push_str("未解出可读载荷，附录只列出码点与位置\n"),
+    }
+    if result.as_ref().is_some_and(|r| r.scheme.is_none()) {
+        output.push_str("该结果经过修复或分段处理，无法对应到原文字符，附录未说明各字符的作用\n");
+    }
+    output.push_str(&format!("Markdown 已写入: {}\n", saved));
+    ToolCallResult::success(output)
+}
+
+// ============================================================
+// 增量解码会话
+// ============================================================
+
+/// 同时存在的会话数上限
+const MAX_SESSIONS: usize = 64;
+
+/// 单个会话累积流（压缩后）的字节上限
+const MAX_SESSION_BYTES: usize = 8 * 1024 * 1024;
+
+/// 会话闲置超过该时长后自动关闭
+const SESSION_IDLE_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// 会话及其最近一次使用时间
+static SESSIONS: OnceLock<Mutex<HashMap<String, (Session, Instant)>>> = OnceLock::new();
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 取得会话表，顺带清掉闲置超时的会话
+fn sessions() -> std::sync::MutexGuard<'static, HashMap<String, (Session, Instant)>> {
+    let mut map = SESSIONS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    map.retain(|_, (_, used)| used.elapsed() < SESSION_IDLE_TTL);
+    map
+}
+
+fn session_not_found(id: &str) -> ToolCallResult {
+    ToolCallResult::failure(
+        ErrorCode::NotFound,
+        format!("会话不存在或已因闲置超过 {} 分钟被关闭: {}", SESSION_IDLE_TTL.as_secs() / 60, id),
+    )
+}
+
+fn exec_check_name(args: &Value) -> ToolCallResult {
+    let strings = |key: &str| -> Vec<String> {
+        args.get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    };
+    let mut list: Vec<String> = get_str(args, "name").map(str::to_string).into_iter().collect();
+    list.extend(strings("names"));
+    if list.is_empty() {
+        return ToolCallResult::failure(ErrorCode::InputMissing, "缺少 name 或 names 参数");
+    }
+    let existing = strings("existing");
+
+    let reports: Vec<names::NameReport> = list.iter().map(|n| names::check_name(n, &existing)).collect();
+    let flagged = reports.iter().filter(|r| !r.issues.is_empty()).count();
+    let mut output = format!("检查 {} 个名字，{} 个有问题\n", reports.len(), flagged);
+    for r in &reports {
+        output.push_str(&format!("\n{:?}\n  风险: {}\n", r.name, r.risk()));
+        output.push_str(&format!("  可见形式: {:?}\n", r.normalized));
+        for issue in &r.issues {
+            output.push_str(&format!("  ⚠ {}\n", issue.describe()));
+        }
+        if !r.blanks.is_empty() {
+            let listed: Vec<String> = r
+                .blanks
+                .iter()
+                .map(|(cp, n)| {
+                    let name = char::from_u32(*cp).map(names::blank_name).unwrap_or_default();
+                    format!("U+{:04X} {} ×{}", cp, name, n)
+                })
+                .collect();
+            output.push_str(&format!("  不可见/空白字符: {}\n", listed.join(", ")));
+        }
+    }
+    ToolCallResult::success(output)
+}
+
+fn exec_blank_message(args: &Value) -> ToolCallResult {
+    let mut messages: Vec<String> = args
+        .get("messages")
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    if ["text", "text_base64", "file_path"].iter().any(|k| args.get(*k).is_some()) {
+        match resolve_text(args) {
+            Ok(t) => messages.insert(0, t),
+            Err(e) => return e,
+        }
+    }
+    if messages.is_empty() {
+        return ToolCallResult::failure(ErrorCode::InputMissing, "缺少 text、text_base64、file_path 或 messages 参数");
+    }
+    let budget = match get_str(args, "budget") {
+        None => engine::Budget::fast(),
+        Some(name) => match engine::Budget::parse(name) {
+            Some(b) => b,
+            None => return ToolCallResult::error(format!("未知解码预算: {}", name)),
+        },
+    };
+    let budget = budget.with_timeout(tool_timeout(args)).with_stegcloak(stegcloak_options(args));
+
+    let reports: Vec<Option<blank::BlankReport>> = messages.iter().map(|m| blank::analyze(m, &budget)).collect();
+    let blanks = reports.iter().flatten().count();
+    let mut output = format!("分析 {} 条消息，{} 条是空白消息\n", messages.len(), blanks);
+    for (i, (message, report)) in messages.iter().zip(&reports).enumerate() {
+        let label = if messages.len() > 1 { format!("\n[{}] ", i + 1) } else { "\n".to_string() };
+        let Some(r) = report else {
+            let reason = if message.trim().is_empty() { "为空或只含 ASCII 空白（客户端会直接拦下）" } else { "含可见字符，不是空白消息" };
+            output.push_str(&format!("{}{}\n", label, reason));
+            continue;
+        };
+        output.push_str(&format!("{}空白消息: {} 个不可见字符\n", label, r.chars));
+        output.push_str(&format!("  技巧: {}\n", r.trick_summary()));
+        let listed: Vec<String> = r
+            .distribution
+            .iter()
+            .map(|(cp, n)| {
+                let name = char::from_u32(*cp).map(names::blank_name).unwrap_or_default();
+                format!("U+{:04X} {} ×{}", cp, name, n)
+            })
             .collect();
-        return Ok(text);
+        output.push_str(&format!("  字符: {}\n", listed.join(", ")));
+        output.push_str(&format!("  判断: {}（{}）\n", r.payload.label(), r.reason));
+        if let Some(best) = &r.best {
+            output.push_str(&format!(
+                "  最佳解码: {} → {} (得分 {:.1})\n",
+                best.method,
+                sanitize::display(&format!("{:?}", best.decoded)),
+                best.score
+            ));
+        }
+        output.push_str(&format!("  建议替换为: {}\n", sanitize::display(&r.replacement())));
+    }
+    ToolCallResult::success(output)
+}
+
+fn exec_session_start() -> ToolCallResult {
+    let mut map = sessions();
+    if map.len() >= MAX_SESSIONS {
+        return ToolCallResult::error(format!(
+            "会话数已达上限 ({})，请先用 zw_session_decode 的 close 参数关闭旧会话",
+            MAX_SESSIONS
+        ));
+    }
+    let id = format!("zws-{}", NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed));
+    map.insert(id.clone(), (Session::with_limit(MAX_SESSION_BYTES), Instant::now()));
+    ToolCallResult::success(format!(
+        "已创建会话: {}\n闲置 {} 分钟后自动关闭，累积流上限 {} 字节\n",
+        id,
+        SESSION_IDLE_TTL.as_secs() / 60,
+        MAX_SESSION_BYTES
+    ))
+}
+
+fn exec_session_feed(args: &Value) -> ToolCallResult {
+    let id = match get_str(args, "session_id") {
+        Some(id) => id,
+        None => return ToolCallResult::failure(ErrorCode::InputMissing, "缺少参数: session_id"),
+    };
+    let chunk = match resolve_text(args) {
+        Ok(t) => t,
+        Err(e) => return e,
+    };
+    let (chunk, lossy_note) = apply_lossy(args, chunk);
+    let mut map = sessions();
+    let (session, used) = match map.get_mut(id) {
+        Some(entry) => entry,
+        None => return session_not_found(id),
+    };
+    *used = Instant::now();
+    let added = match session.feed(&chunk) {
+        Ok(n) => n,
+        Err(e) => return ToolCallResult::failure(ErrorCode::FileTooLarge, e),
+    };
+    ToolCallResult::success(format!(
+        "{}会话 {}: 第 {} 块, 本块零宽字符 {} 个, 累计 {} 个 (共读入 {} 字符, 累积流 {}/{} 字节)\n",
+        lossy_note,
+        id,
+        session.chunks(),
+        added,
+        session.zw_chars(),
+        session.total_chars(),
+        session.stream_bytes(),
+        MAX_SESSION_BYTES
+    ))
+}
+
+fn exec_session_decode(args: &Value) -> ToolCallResult {
+    let id = match get_str(args, "session_id") {
+        Some(id) => id,
+        None => return ToolCallResult::failure(ErrorCode::InputMissing, "缺少参数: session_id"),
+    };
+    let close = args.get("close").and_then(|v| v.as_bool()).unwrap_or(false);
+    let session = {
+        let mut map = sessions();
+        let found = if close {
+            map.remove(id).map(|(s, _)| s)
+        } else {
+            map.get_mut(id).map(|(s, used)| {
+                *used = Instant::now();
+                s.clone()
+            })
+        };
+        match found {
+            Some(s) => s,
+            None => return session_not_found(id),
+        }
+    };
+
+    let method = match decode_method(args) {
+        Ok(m) => m,
+        Err(e) => return e,
+    };
+    let budget = match parse_budget(args) {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
+    let results = run_decode(session.text(), method, &budget);
+    let mut prefix = format!(
+        "会话 {}: {} 块, 零宽字符 {} 个{}\n",
+        id,
+        session.chunks(),
+        session.zw_chars(),
+        if close { " (已关闭)" } else { "" }
+    );
+    prefix.push_str(&timeout_note(args, &budget));
+    prefix.push('\n');
+    format_decode_results(&results, &prefix, session.text(), &budget.acceptance, &budget.stegcloak)
+}
+
+// ============================================================
+// 后台任务
+// ============================================================
+
+fn exec_job_start(args: &Value) -> ToolCallResult {
+    let Some(tool) = get_str(args, "tool") else {
+        return ToolCallResult::failure(ErrorCode::InputMissing, "缺少参数: tool");
+    };
+    let arguments = args.get("arguments").cloned().unwrap_or(Value::Null);
+    match jobs::start(tool, &arguments) {
+        Ok(id) => {
+            let mut result = ToolCallResult::success(format!(
+                "已启动后台任务: {}（{}）\n用 zw_job_status 查询进度，zw_job_result 取回结果\n",
+                id, tool
+            ));
+            result.structured_content = Some(json!({ "job_id": id, "tool": tool, "state": "queued" }));
+            result
+        }
+        Err(e) => e,
     }
+}
 
-    // 尝试 UTF-8
-    if let Ok(s) = String::from_utf8(raw.clone()) {
-        return Ok(s);
+fn exec_job_status(args: &Value) -> ToolCallResult {
+    let summaries = match jobs::status(get_str(args, "job_id")) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+    let mut output = if summaries.is_empty() {
+        "会话内没有后台任务\n".to_string()
+    } else {
+        format!("后台任务 {} 个\n", summaries.len())
+    };
+    for job in &summaries {
+        output.push_str(&format!(
+            "  {}  {}  {}  {} ms{}\n",
+            job["job_id"].as_str().unwrap_or_default(),
+            job["tool"].as_str().unwrap_or_default(),
+            job["state"].as_str().unwrap_or_default(),
+            job["elapsed_ms"],
+            job.get("error_code").and_then(|c| c.as_str()).map(|c| format!("  [{}]", c)).unwrap_or_default()
+        ));
     }
+    let mut result = ToolCallResult::success(output);
+    result.structured_content = Some(json!({ "jobs": summaries }));
+    result
+}
 
-    // 尝试 UTF-16 LE (无BOM)
-    if raw.len() % 2 == 0 {
-        let iter = raw.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]]));
-        let text: String = char::decode_utf16(iter)
-            .map(|r| r.unwrap_or('\u{FFFD}'))
-            .collect();
-        // 如果解码后大部分是可打印字符，认为成功
-        let printable = text.chars().filter(|c| !c.is_control() || *c == '\n' || *c == '\r' || *c == '\t').count();
-        if text.chars().count() > 0 && printable as f64 / text.chars().count() as f64 > 0.7 {
-            return Ok(text);
+fn exec_job_result(args: &Value) -> ToolCallResult {
+    let Some(id) = get_str(args, "job_id") else {
+        return ToolCallResult::failure(ErrorCode::InputMissing, "缺少参数: job_id");
+    };
+    let wait_ms = args.get("wait_ms").and_then(|v| v.as_u64()).unwrap_or(0);
+    let wait = Duration::from_millis(wait_ms).min(tool_timeout(args));
+    let remove = args.get("remove").and_then(|v| v.as_bool()).unwrap_or(false);
+    jobs::result(id, wait, remove)
+}
+
+fn exec_reassemble(args: &Value) -> ToolCallResult {
+    let strings = |key: &str| -> Vec<String> {
+        args.get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    };
+    let mut pieces = Vec::new();
+    for path in strings("file_paths") {
+        match read_file_auto(&path, false) {
+            Ok(text) => pieces.push(reassemble::Piece { label: path, text, from_file: true }),
+            Err(e) => return e,
         }
     }
+    for (i, text) in strings("texts").into_iter().enumerate() {
+        pieces.push(reassemble::Piece { label: format!("#{}", i + 1), text, from_file: false });
+    }
+    if pieces.len() < 2 {
+        return ToolCallResult::failure(ErrorCode::InputMissing, "至少需要 2 份文档（file_paths 或 texts）");
+    }
+    let declared_only = match get_str(args, "order").unwrap_or("auto") {
+        "auto" => false,
+        "declared" => true,
+        other => return ToolCallResult::error(format!("未知 order: {}（可用: auto, declared）", other)),
+    };
+    let budget = match parse_budget(args) {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
 
-    // 最后降级: 使用 Latin-1 (ISO-8859-1, 不会失败)
-    let text: String = raw.iter().map(|&b| b as char).collect();
-    Ok(text)
-}
+    let r = reassemble::reassemble(&pieces, declared_only, &budget);
+    let mut output = format!("共 {} 份文档，拼接顺序依据: {}\n\n", r.pieces.len(), r.source.describe());
+    for (pos, &i) in r.order.iter().enumerate() {
+        let p = &r.pieces[i];
+        output.push_str(&format!("{}. {}（零宽字符 {} 个", pos + 1, p.label, p.zw_chars));
+        if let Some((n, total)) = p.header {
+            match total {
+                Some(t) => output.push_str(&format!("，序号 {}/{}", n, t)),
+                None => output.push_str(&format!("，序号 {}", n)),
+            }
+        }
+        output.push_str("）\n");
+        match &p.best {
+            Some(best) => output.push_str(&format!("   单独解码: {:?}（{}，得分 {:.1}）\n", best.decoded, best.method, best.score)),
+            None => output.push_str("   单独解码: 无结果（可能只含载荷的一部分）\n"),
+        }
+    }
+    let dropped = r.pieces.len() - r.order.len();
+    if dropped > 0 {
+        output.push_str(&format!("\n⚠ 重复序号 {:?}，已忽略 {} 份重复文档\n", r.duplicates, dropped));
+    }
+    if !r.missing.is_empty() {
+        output.push_str(&format!("\n⚠ 缺少序号 {:?} 的片段，拼接结果不完整\n", r.missing));
+    }
 
-/// 将内容写入文件
-fn write_file(path_str: &str, content: &str) -> Result<(), ToolCallResult> {
-    let path = Path::new(path_str);
-    // 自动创建父目录
-    if let Some(parent) = path.parent() {
-        if !parent.exists() {
-            if let Err(e) = fs::create_dir_all(parent) {
-                return Err(ToolCallResult::error(format!("创建目录失败: {}", e)));
+    output.push_str("\n按流拼接（零宽字符流首尾相接后整体解码）:\n");
+    let stream_best = r.stream.first();
+    if stream_best.is_none() {
+        output.push_str("  无有效结果\n");
+    }
+    for res in r.stream.iter().take(3) {
+        output.push_str(&format!("  {} → {:?}（得分 {:.1}）\n", res.method, res.decoded, res.score));
+    }
+    output.push_str("\n按片段拼接（逐份解码后合并）:\n");
+    match &r.joined {
+        Some((text, s)) => {
+            output.push_str(&format!("  {:?}（得分 {:.1}）\n", text, s));
+            let merged: usize = r.overlaps.iter().sum();
+            if merged > 0 {
+                output.push_str(&format!("  相邻片段重叠 {} 个字符，已去重\n", merged));
             }
         }
+        None => output.push_str("  有文档单独解不出内容，无法按片段拼接\n"),
     }
-    match fs::write(path, content.as_bytes()) {
-        Ok(_) => Ok(()),
-        Err(e) => Err(ToolCallResult::error(format!("写入文件失败: {}", e))),
+
+    // 有序号头时片段本身就是完整的一段文本，按片段拼接更可信
+    let headed = r.source == reassemble::OrderSource::Header;
+    let best = match (stream_best, &r.joined) {
+        (Some(b), Some((text, s))) if headed || *s > b.score => Some(text.as_str()),
+        (Some(b), _) => Some(b.decoded.as_str()),
+        (None, Some((text, _))) => Some(text.as_str()),
+        (None, None) => None,
+    };
+    match best {
+        Some(text) => output.push_str(&format!("\n★ 重组结果: {}\n", sanitize::display(text))),
+        None => output.push_str("\n未能重组出有效载荷，可尝试 order=declared 或调整文档顺序\n"),
     }
+    output.push_str(&timeout_note(args, &budget));
+    ToolCallResult::success(output)
 }
 
-fn exec_analyze(args: &Value) -> ToolCallResult {
+fn exec_simulate_channel(args: &Value) -> ToolCallResult {
     let text = match resolve_text(args) {
         Ok(t) => t,
         Err(e) => return e,
     };
+    let channels: Vec<channel::Channel> = match args.get("channels").and_then(|v| v.as_array()) {
+        None => channel::Channel::all().to_vec(),
+        Some(items) => {
+            let mut out = Vec::new();
+            for name in items.iter().filter_map(|v| v.as_str()) {
+                match channel::Channel::parse(name) {
+                    Some(c) => out.push(c),
+                    None => return ToolCallResult::error(format!("未知通道: {}", name)),
+                }
+            }
+            out
+        }
+    };
+    let expected = match get_str(args, "expected") {
+        Some(e) => e.to_string(),
+        None => match engine::auto_decode(&text, &engine::Budget::normal()).into_iter().next() {
+            Some(r) => r.decoded,
+            None => return ToolCallResult::failure(ErrorCode::DecodeFailed, "原文本无法解码，请通过 expected 指定期望的载荷"),
+        },
+    };
 
-    let mut report = String::new();
-    if let Some(fp) = get_str(args, "file_path") {
-        report.push_str(&format!("文件: {}\n", fp));
+    let reports = channel::simulate(&text, &expected, &channels);
+    let survived = reports.iter().filter(|r| r.survived).count();
+    let mut output = format!("期望载荷: {:?}\n", expected);
+    output.push_str(&format!("幸存: {}/{} 种处理\n\n", survived, reports.len()));
+    for r in &reports {
+        output.push_str(&format!(
+            "  {} {:<13} {:<22} 零宽 {} → {}",
+            if r.survived { "✓" } else { "✗" },
+            r.channel.name(),
+            r.channel.description(),
+            r.zw_before,
+            r.zw_after
+        ));
+        if !r.survived {
+            match &r.top {
+                Some(top) => output.push_str(&format!("  首位解码: {:?}", top)),
+                None => output.push_str("  无法解码"),
+            }
+        }
+        output.push('\n');
     }
-    let analysis = engine::analyze(&text);
-    report.push_str(&engine::format_analysis(&analysis));
-    ToolCallResult::success(report)
+    ToolCallResult::success(output)
 }
 
-fn exec_decode(args: &Value) -> ToolCallResult {
+/// 单次 zw_fuzz 最多生成的变体数
+const MAX_FUZZ_VARIANTS: usize = 1000;
+/// 结果中逐个列出的变体数上限（清单文件中总是完整的）
+const FUZZ_LISTED_VARIANTS: usize = 50;
+
+fn exec_fuzz(args: &Value) -> ToolCallResult {
     let text = match resolve_text(args) {
         Ok(t) => t,
         Err(e) => return e,
     };
-
-    let method = get_str(args, "method").unwrap_or("auto");
-
-    let results = match method {
-        "unicode_tags" => {
-            engine::decode_unicode_tags(&text).into_iter().collect::<Vec<_>>()
-        }
-        "steganographr" => {
-            engine::decode_steganographr(&text).into_iter().collect::<Vec<_>>()
-        }
-        "binary" => {
-            // 暴力尝试二进制
-            let analysis = engine::analyze(&text);
-            let zw_all = engine::extract_all(&text);
-            let mut freq: Vec<(u32, usize)> = analysis.distribution.iter().map(|(&k, &v)| (k, v)).collect();
-            freq.sort_by(|a, b| b.1.cmp(&a.1));
-            let top: Vec<char> = freq.iter().filter_map(|(cp, _)| char::from_u32(*cp)).collect();
-            let limit = top.len().min(6);
-            let mut results = Vec::new();
-            for i in 0..limit {
-                for j in 0..limit {
-                    if i == j { continue; }
-                    for bits in [8, 7] {
-                        if let Some(r) = engine::decode_direct_binary(&zw_all, top[i], top[j], bits) {
-                            if r.score > 15.0 {
-                                results.push(r);
-                            }
-                        }
-                    }
+    let budget = match parse_budget(args) {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
+    let mutations: Vec<fuzz::Mutation> = match args.get("mutations").and_then(|v| v.as_array()) {
+        None => fuzz::Mutation::all().to_vec(),
+        Some(items) => {
+            let mut out = Vec::new();
+            for name in items.iter().filter_map(|v| v.as_str()) {
+                match fuzz::Mutation::parse(name) {
+                    Some(m) => out.push(m),
+                    None => return ToolCallResult::error(format!("未知改动类型: {}", name)),
                 }
             }
-            results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
-            results
-        }
-        "330k" => {
-            let zw_all = engine::extract_all(&text);
-            let charset = vec!['\u{200C}', '\u{200D}', '\u{202C}', '\u{FEFF}'];
-            engine::decode_nary(&zw_all, &charset)
+            out
         }
-        _ => engine::auto_decode(&text),
     };
+    let count = args.get("count").and_then(|v| v.as_u64()).unwrap_or(20).max(1) as usize;
+    if count > MAX_FUZZ_VARIANTS {
+        return ToolCallResult::error(format!("变体个数 {} 超过上限 {}", count, MAX_FUZZ_VARIANTS));
+    }
+    let edits = args.get("edits").and_then(|v| v.as_u64()).unwrap_or(1).max(1) as usize;
+    let check = args.get("check").and_then(|v| v.as_bool()).unwrap_or(true);
+    let seed = args.get("seed").and_then(|v| v.as_u64()).unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(1)
+    });
 
-    if results.is_empty() {
-        return ToolCallResult::success("未找到有效解码结果。请确认文本中包含零宽字符隐写信息。");
+    let alphabet = fuzz::alphabet(&text);
+    if alphabet.is_empty() {
+        return ToolCallResult::error("样本中没有不可见字符，无法生成变体");
+    }
+    let expected = match get_str(args, "expected") {
+        Some(e) => Some(e.to_string()),
+        None if check => match engine::auto_decode(&text, &budget).into_iter().next() {
+            Some(r) => Some(r.decoded),
+            None => return ToolCallResult::failure(ErrorCode::DecodeFailed, "原文本无法解码，请通过 expected 指定期望的载荷"),
+        },
+        None => None,
+    };
+    let variants = fuzz::generate(&text, &mutations, edits, count, seed);
+    if variants.is_empty() {
+        return ToolCallResult::error("样本只用到一种不可见字符，无法做替换；请加入 drop 或 truncate");
     }
 
-    let mut output = String::new();
-    if let Some(fp) = get_str(args, "file_path") {
-        output.push_str(&format!("文件: {}\n", fp));
+    // 逐个检查；到达时间上限后其余变体标为未检查
+    let outcomes: Vec<Option<(bool, Option<String>)>> = variants
+        .iter()
+        .map(|v| match &expected {
+            Some(e) if check && !budget.expired() => Some(fuzz::recovers(&v.text, e, &budget)),
+            _ => None,
+        })
+        .collect();
+
+    let mut output = input::header(args);
+    let symbols = text.chars().filter(|&c| chars::is_zero_width(c)).count();
+    let charset: Vec<String> = alphabet.iter().map(|&c| format!("U+{:04X}", c as u32)).collect();
+    output.push_str(&format!("样本: {} 个不可见符号，字符集 {} 种（{}）\n", symbols, alphabet.len(), charset.join(", ")));
+    if let Some(e) = &expected {
+        output.push_str(&format!("期望载荷: {:?}\n", e));
     }
-    output.push_str(&format!("找到 {} 个可能的解码结果（按置信度排序）:\n\n", results.len()));
-    for (i, r) in results.iter().enumerate().take(10) {
+    output.push_str(&format!("随机种子: {}\n", seed));
+    let names: Vec<&str> = mutations.iter().map(|m| m.name()).collect();
+    output.push_str(&format!("变体: {} 个，每个 {} 处改动（{}）\n", variants.len(), edits, names.join(" / ")));
+    if alphabet.len() < 2 && mutations.contains(&fuzz::Mutation::Substitute) {
+        output.push_str("样本只用到一种不可见字符，已跳过 substitute\n");
+    }
+
+    let checked = outcomes.iter().filter(|o| o.is_some()).count();
+    if checked > 0 {
+        let recovered = outcomes.iter().filter(|o| matches!(o, Some((true, _)))).count();
         output.push_str(&format!(
-            "[{}] 方案: {}\n    得分: {:.1}\n    结果: {}\n\n",
-            i + 1, r.method, r.score, r.decoded
+            "\n自动解码恢复: {}/{}（{:.0}%）\n",
+            recovered,
+            checked,
+            recovered as f64 * 100.0 / checked as f64
         ));
+        if checked < variants.len() {
+            output.push_str(&format!("⚠ 已达到时间上限，{} 个变体未检查\n", variants.len() - checked));
+        }
+        output.push_str("按改动类型（含该类改动的变体）:\n");
+        for &m in &mutations {
+            let with: Vec<bool> = variants
+                .iter()
+                .zip(&outcomes)
+                .filter(|(v, _)| v.edits.iter().any(|e| e.mutation == m))
+                .filter_map(|(_, o)| o.as_ref().map(|(ok, _)| *ok))
+                .collect();
+            if !with.is_empty() {
+                let ok = with.iter().filter(|&&b| b).count();
+                output.push_str(&format!("  {:<11} {:<8} {:>4} 个变体  恢复 {:>4}\n", m.name(), m.description(), with.len(), ok));
+            }
+        }
     }
-    if let Some(best) = results.first() {
-        output.push_str(&format!("★ 最佳结果: {}\n", best.decoded));
+
+    output.push_str("\n变体明细:\n");
+    for (i, (v, o)) in variants.iter().zip(&outcomes).enumerate().take(FUZZ_LISTED_VARIANTS) {
+        let edits: Vec<String> = v.edits.iter().map(|e| e.describe()).collect();
+        let mark = match o {
+            Some((true, _)) => "✓",
+            Some((false, _)) => "✗",
+            None => "·",
+        };
+        output.push_str(&format!("  [{:3}] {} {}", i + 1, mark, edits.join("；")));
+        if let Some((false, top)) = o {
+            match top {
+                Some(top) => output.push_str(&format!("  首位解码: {:?}", top)),
+                None => output.push_str("  无法解码"),
+            }
+        }
+        output.push('\n');
+    }
+    if variants.len() > FUZZ_LISTED_VARIANTS {
+        output.push_str(&format!("  …… 其余 {} 个见清单文件\n", variants.len() - FUZZ_LISTED_VARIANTS));
+    }
+
+    // 清单: 种子、参数与每个变体的改动和内容，供外部解码器复测
+    let mut entries = Vec::new();
+    let mut written = Vec::new();
+    let output_dir = get_str(args, "output_dir");
+    for (i, (v, o)) in variants.iter().zip(&outcomes).enumerate() {
+        let name = format!("fuzz_{:03}.txt", i + 1);
+        let file = match output_dir {
+            Some(dir) => {
+                let path = if dir.starts_with(workspace::URI_PREFIX) {
+                    format!("{}{}", workspace::URI_PREFIX, name)
+                } else {
+                    Path::new(dir).join(&name).to_string_lossy().into_owned()
+                };
+                match write_file(&path, &v.text, &name) {
+                    Ok(saved) => {
+                        written.push(saved.clone());
+                        Some(saved)
+                    }
+                    Err(e) => return e,
+                }
+            }
+            None => None,
+        };
+        let edits: Vec<Value> = v
+            .edits
+            .iter()
+            .map(|e| {
+                json!({
+                    "mutation": e.mutation.name(),
+                    "position": e.position,
+                    "from": format!("U+{:04X}", e.from as u32),
+                    "to": e.to.map(|c| format!("U+{:04X}", c as u32)),
+                    "removed": e.removed,
+                })
+            })
+            .collect();
+        entries.push(json!({
+            "index": i + 1,
+            "file": file,
+            "edits": edits,
+            "recovered": o.as_ref().map(|(ok, _)| *ok),
+            "text_base64": ingest::encode_base64(v.text.as_bytes()),
+        }));
+    }
+    let manifest = json!({
+        "seed": seed,
+        "count": variants.len(),
+        "edits": edits,
+        "mutations": names,
+        "expected": expected,
+        "variants": entries,
+    });
+    let manifest_text = serde_json::to_string_pretty(&manifest).unwrap_or_default();
+    let manifest_path = match output_dir {
+        Some(dir) if dir.starts_with(workspace::URI_PREFIX) => format!("{}fuzz_manifest.json", workspace::URI_PREFIX),
+        Some(dir) => Path::new(dir).join("fuzz_manifest.json").to_string_lossy().into_owned(),
+        None => format!("{}fuzz_manifest.json", workspace::URI_PREFIX),
+    };
+    match write_file(&manifest_path, &manifest_text, "fuzz_manifest.json") {
+        Ok(saved) => {
+            if !written.is_empty() {
+                output.push_str(&format!("\n已写出 {} 个变体文件: {} … {}\n", written.len(), written[0], written[written.len() - 1]));
+            }
+            output.push_str(&format!("清单: {}\n", saved));
+        }
+        Err(e) => return e,
     }
+    output.push_str(&timeout_note(args, &budget));
     ToolCallResult::success(output)
 }
 
-fn exec_encode(args: &Value) -> ToolCallResult {
-    let message = match get_str(args, "message") {
-        Some(m) => m,
-        None => return ToolCallResult::error("缺少参数: message"),
+fn exec_recommend(args: &Value) -> ToolCallResult {
+    let (target, channels): (String, Vec<channel::Channel>) = match args.get("channels").and_then(|v| v.as_array()) {
+        Some(items) => {
+            let mut out = Vec::new();
+            for name in items.iter().filter_map(|v| v.as_str()) {
+                match channel::Channel::parse(name) {
+                    Some(c) => out.push(c),
+                    None => return ToolCallResult::error(format!("未知通道: {}", name)),
+                }
+            }
+            ("自定义处理链".to_string(), out)
+        }
+        None => {
+            let key = get_str(args, "profile").unwrap_or("chat");
+            let Some(profile) = recommend::profile(key) else {
+                let keys: Vec<&str> = recommend::profiles().iter().map(|p| p.key).collect();
+                return ToolCallResult::error(format!("未知画像: {}（可用: {}）", key, keys.join(", ")));
+            };
+            (profile.name.to_string(), profile.channels.to_vec())
+        }
     };
-    let method = get_str(args, "method").unwrap_or("binary");
+    let payload_bytes = args.get("payload_bytes").and_then(|v| v.as_u64()).unwrap_or(32) as usize;
+    let cover_chars = args.get("cover_chars").and_then(|v| v.as_u64()).map(|n| n as usize);
+    let top = args.get("top").and_then(|v| v.as_u64()).unwrap_or(5).max(1) as usize;
 
-    // 载体文本: cover_file 优先于 cover_text
-    let cover: String = if let Some(cover_path) = get_str(args, "cover_file") {
-        match read_file_auto(cover_path) {
-            Ok(t) => t,
-            Err(e) => return e,
-        }
+    let chain = if channels.is_empty() {
+        "无".to_string()
     } else {
-        get_str(args, "cover_text").unwrap_or("").to_string()
+        channels.iter().map(|c| c.description()).collect::<Vec<_>>().join(" → ")
     };
-    let cover = cover.as_str();
+    let mut output = format!("目标: {}\n处理链: {}\n载荷: {} 字节", target, chain, payload_bytes);
+    if let Some(n) = cover_chars {
+        output.push_str(&format!("，载体 {} 个可见字符", n));
+    }
+    output.push_str("\n\n");
 
-    let encoded = match method {
-        "binary" => {
-            let zw = engine::encode_binary(message, '\u{200B}', '\u{200C}', 8);
-            if !cover.is_empty() {
-                let mid = cover.chars().count() / 2;
-                let prefix: String = cover.chars().take(mid).collect();
-                let suffix: String = cover.chars().skip(mid).collect();
-                format!("{}{}{}", prefix, zw, suffix)
-            } else {
-                zw
+    let candidates = recommend::recommend(&channels);
+    let density = |needed: usize| cover_chars.filter(|&n| n > 0).map(|n| needed as f64 * 100.0 / n as f64);
+    match candidates.first().filter(|c| c.survives()) {
+        Some(best) => {
+            let needed = best.invisible_needed(payload_bytes);
+            output.push_str(&format!("推荐: {}\n", best.source.describe()));
+            output.push_str(&format!("  字符集: {}\n", best.charset.iter().map(|&c| recommend::label(c)).collect::<Vec<_>>().join(", ")));
+            output.push_str(&format!("  预期幸存: ✓ 全部字符在该处理链下保留，探测载荷原样通过\n  需要 {} 个不可见字符", needed));
+            if let Some(d) = density(needed) {
+                output.push_str(&format!("，密度 {:.1}/100 可见字符", d));
+            }
+            output.push('\n');
+            if let Some(note) = best.note {
+                output.push_str(&format!("  限制: {}\n", note));
+            }
+            // 预设只能由对应工具生成，另给出 zw_encode 可直接生成的最佳方案
+            if matches!(best.source, recommend::Source::Preset(_)) {
+                if let Some(method) = candidates.iter().find(|c| c.survives() && matches!(c.source, recommend::Source::Method(_))) {
+                    output.push_str(&format!(
+                        "  zw_encode 可直接生成的最佳方案: {}（需要 {} 个不可见字符）\n",
+                        method.source.describe(),
+                        method.invisible_needed(payload_bytes)
+                    ));
+                }
             }
         }
-        "steganographr" => engine::encode_steganographr(message, cover),
-        "tags" => engine::encode_tags(message, cover),
-        "330k" => {
-            let charset = vec!['\u{200C}', '\u{200D}', '\u{202C}', '\u{FEFF}'];
-            engine::encode_330k(message, cover, &charset)
-        }
-        _ => return ToolCallResult::error(format!("未知编码方法: {}", method)),
-    };
-
-    let mut output = String::new();
-    output.push_str(&format!("编码方法: {}\n", method));
-    output.push_str(&format!("消息: {}\n", message));
-    output.push_str(&format!("编码后长度: {} 字符\n", encoded.chars().count()));
-    output.push_str(&format!("编码结果（repr）: {:?}\n", encoded));
-    output.push_str(&format!("\n编码文本:\n{}\n", encoded));
+        None => output.push_str("✗ 没有方案能完整通过该处理链；下表按保留字符比例排序，只能部分幸存\n"),
+    }
+    if channels.contains(&channel::Channel::Trim) {
+        output.push_str("  提示: 处理链会修剪首尾，载荷不要放在载体开头或结尾\n");
+    }
+    if channels.contains(&channel::Channel::EmojiRender) {
+        output.push_str("  提示: emoji 会被渲染为图片，载荷不要紧跟在 emoji 之后\n");
+    }
 
-    // 如果指定了输出文件，写入
-    if let Some(out_path) = get_str(args, "output_path") {
-        match write_file(out_path, &encoded) {
-            Ok(_) => output.push_str(&format!("\n✓ 已写入文件: {}\n", out_path)),
-            Err(e) => return e,
+    output.push_str("\n候选对比:\n");
+    for c in candidates.iter().take(top) {
+        let needed = c.invisible_needed(payload_bytes);
+        let status = if c.survives() {
+            "✓ 幸存".to_string()
+        } else {
+            format!("✗ 保留 {}/{} 种字符", c.surviving.len(), c.charset.len())
+        };
+        output.push_str(&format!("  {} {}  字符集 {} 种，需要 {} 个不可见字符", status, c.source.describe(), c.charset.len(), needed));
+        if let Some(d) = density(needed) {
+            output.push_str(&format!("（{:.1}/100）", d));
         }
+        if let Some(note) = c.note {
+            output.push_str(&format!("，{}", note));
+        }
+        output.push('\n');
     }
 
+    if !channels.is_empty() {
+        let mut shown: Vec<char> = Vec::new();
+        for c in candidates.iter().take(top) {
+            for &ch in &c.charset {
+                if !shown.contains(&ch) {
+                    shown.push(ch);
+                }
+            }
+        }
+        shown.sort_unstable();
+        output.push_str("\n字符幸存表:\n");
+        for ch in shown {
+            let cells: Vec<String> = channels
+                .iter()
+                .map(|&c| format!("{} {}", c.name(), if recommend::char_survives(ch, c) { "✓" } else { "✗" }))
+                .collect();
+            output.push_str(&format!("  {}: {}\n", recommend::label(ch), cells.join("  ")));
+        }
+    }
     ToolCallResult::success(output)
 }
 
-fn exec_dump_raw(args: &Value) -> ToolCallResult {
-    let text = match resolve_text(args) {
-        Ok(t) => t,
-        Err(e) => return e,
+fn exec_benchmark(args: &Value) -> ToolCallResult {
+    let cases = match get_str(args, "corpus_dir") {
+        Some(dir) => benchmark::load_corpus_dir(Path::new(dir)),
+        None => benchmark::embedded_corpus(),
     };
-    let mut prefix = String::new();
-    if let Some(fp) = get_str(args, "file_path") {
-        prefix.push_str(&format!("文件: {}\n", fp));
+    let mut cases = match cases {
+        Ok(c) => c,
+        Err(e) => return ToolCallResult::failure(ErrorCode::IoError, e),
+    };
+    if let Some(f) = get_str(args, "filter") {
+        cases.retain(|c| c.name.contains(f));
     }
-    let raw = engine::dump_raw(&text);
-    if raw.lines().count() <= 1 {
-        return ToolCallResult::success(format!("{}文本中未发现零宽字符。", prefix));
+    if cases.is_empty() {
+        return ToolCallResult::error("语料为空（或没有题目匹配 filter）");
     }
-    ToolCallResult::success(format!("{}{}", prefix, raw))
+    let budget = match get_str(args, "budget") {
+        None => engine::Budget::normal(),
+        Some(name) => match engine::Budget::parse(name) {
+            Some(b) => b,
+            None => return ToolCallResult::error(format!("未知解码预算: {}", name)),
+        },
+    };
+
+    let results = benchmark::run(&cases, &budget);
+    let mut output = format!(
+        "语料: {}\n解码预算: {}\n",
+        get_str(args, "corpus_dir").unwrap_or("内置"),
+        budget.name
+    );
+    output.push_str(&benchmark::format_report(&results));
+    // 内置语料另跑短解码误报回归：答案必须排第一，不能被 "A"、"ok" 之类的极短诱饵压过
+    let mut regressions = Vec::new();
+    if get_str(args, "corpus_dir").is_none() {
+        let mut fp_cases = match benchmark::false_positive_corpus() {
+            Ok(c) => c,
+            Err(e) => return ToolCallResult::failure(ErrorCode::IoError, e),
+        };
+        if let Some(f) = get_str(args, "filter") {
+            fp_cases.retain(|c| c.name.contains(f));
+        }
+        let fp_results = benchmark::run(&fp_cases, &budget);
+        regressions = fp_results.iter().filter(|r| r.rank != Some(1)).cloned().collect();
+        output.push_str(&format!(
+            "\n短解码误报回归: {}/{} 答案排第一\n",
+            fp_results.len() - regressions.len(),
+            fp_results.len()
+        ));
+        for r in &regressions {
+            output.push_str(&format!("  ✗ {}: 首位 {:?}\n", r.name, r.top.as_deref().unwrap_or("")));
+        }
+    }
+    let fixtures = match emulate::verify_fixtures() {
+        Ok(f) => f,
+        Err(e) => return ToolCallResult::failure(ErrorCode::IoError, e),
+    };
+    let failed: Vec<&emulate::FixtureResult> = fixtures.iter().filter(|f| !f.passed).collect();
+    output.push_str(&format!(
+        "\n编码仿真夹具: {}/{} 与参考输出一致\n",
+        fixtures.len() - failed.len(),
+        fixtures.len()
+    ));
+    for f in &failed {
+        output.push_str(&format!("  ✗ {} ({}): {}\n", f.name, f.tool, f.detail));
+    }
+    if !failed.is_empty() || !regressions.is_empty() {
+        return ToolCallResult::failure(ErrorCode::CheckFailed, output);
+    }
+    if let Some(min) = args.get("min_recall").and_then(|v| v.as_f64()) {
+        let recall = benchmark::recall(&results);
+        if recall < min {
+            output.push_str(&format!("\n✗ 召回率 {:.3} 低于下限 {:.3}\n", recall, min));
+            return ToolCallResult::failure(ErrorCode::CheckFailed, output);
+        }
+    }
+    ToolCallResult::success(output)
 }
 
-fn exec_list_chars() -> ToolCallResult {
+fn exec_list_chars(args: &Value) -> ToolCallResult {
+    let category = get_str(args, "category");
+    let search = get_str(args, "search").map(|q| q.to_uppercase());
+    let range = match get_str(args, "codepoint_range") {
+        Some(r) => match chars::parse_codepoint_range(r) {
+            Some(range) => Some(range),
+            None => return ToolCallResult::error(format!("无法解析码点区间: {}", r)),
+        },
+        None => None,
+    };
+    let as_json = get_str(args, "format") == Some("json");
     let all = chars::all_zero_width_chars();
-    let mut output = String::from("零宽/不可见 Unicode 字符大全:\n\n");
+
+    let header = format!("字符表依据 Unicode {}（构建时由 UCD 生成）\n", chars::UNICODE_VERSION);
+
+    if category.is_none() && search.is_none() && range.is_none() && !as_json {
+        let mut output = format!("零宽/不可见 Unicode 字符大全:\n{}", header);
+        let mut current_cat = "";
+        for zw in &all {
+            if zw.category != current_cat {
+                output.push_str(&format!("\n[{}]\n", zw.category));
+                current_cat = zw.category;
+            }
+            output.push_str(&format!("  U+{:04X}  {}  ({}, {})\n", zw.codepoint, zw.name, zw.general_category, zw.age));
+        }
+        output.push_str(&format!(
+            "\nUnicode Tags: U+E0001 - U+E007F (128个，映射到 ASCII)\n\n共计 {} + 128 = {} 个字符\n",
+            all.len(),
+            all.len() + 128
+        ));
+        return ToolCallResult::success(output);
+    }
+
+    // 统一条目，过滤时把 Unicode Tags 展开为逐个条目
+    struct Entry<'a> {
+        codepoint: u32,
+        name: String,
+        category: &'a str,
+        general_category: &'a str,
+        default_ignorable: bool,
+        age: &'a str,
+    }
+    let mut entries: Vec<Entry> = all
+        .iter()
+        .map(|z| Entry {
+            codepoint: z.codepoint,
+            name: z.name.to_string(),
+            category: z.category,
+            general_category: z.general_category,
+            default_ignorable: z.default_ignorable,
+            age: z.age,
+        })
+        .collect();
+    entries.extend(std::iter::once(0xE0001).chain(0xE0020..=chars::UNICODE_TAGS_END).map(|cp| Entry {
+        codepoint: cp,
+        name: chars::tag_name(cp),
+        category: "Unicode Tags",
+        general_category: "Cf",
+        default_ignorable: true,
+        age: "3.1",
+    }));
+    entries.retain(|e| {
+        category.is_none_or(|c| e.category.eq_ignore_ascii_case(c) || (c.eq_ignore_ascii_case("tags") && e.category == "Unicode Tags"))
+            && search.as_ref().is_none_or(|q| e.name.to_uppercase().contains(q.as_str()))
+            && range.is_none_or(|(lo, hi)| (lo..=hi).contains(&e.codepoint))
+    });
+
+    if as_json {
+        let list: Vec<Value> = entries
+            .iter()
+            .map(|e| {
+                json!({
+                    "codepoint": format!("U+{:04X}", e.codepoint),
+                    "value": e.codepoint,
+                    "name": e.name,
+                    "category": e.category,
+                    "general_category": e.general_category,
+                    "default_ignorable": e.default_ignorable,
+                    "unicode_version": e.age,
+                })
+            })
+            .collect();
+        let out = json!({ "unicode_version": chars::UNICODE_VERSION, "count": list.len(), "chars": list });
+        return ToolCallResult::success(serde_json::to_string_pretty(&out).unwrap_or_default());
+    }
+
+    if entries.is_empty() {
+        return ToolCallResult::success("没有符合条件的字符。");
+    }
+    let mut output = format!("{}符合条件的字符: {} 个\n", header, entries.len());
     let mut current_cat = "";
-    for zw in &all {
-        if zw.category != current_cat {
-            output.push_str(&format!("\n[{}]\n", zw.category));
-            current_cat = zw.category;
+    for e in &entries {
+        if e.category != current_cat {
+            output.push_str(&format!("\n[{}]\n", e.category));
+            current_cat = e.category;
         }
-        output.push_str(&format!("  U+{:04X}  {}\n", zw.codepoint, zw.name));
+        output.push_str(&format!("  U+{:04X}  {}  ({}, {})\n", e.codepoint, e.name, e.general_category, e.age));
     }
-    output.push_str(&format!(
-        "\nUnicode Tags: U+E0001 - U+E007F (128个，映射到 ASCII)\n\n共计 {} + 128 = {} 个字符\n",
-        all.len(),
-        all.len() + 128
-    ));
     ToolCallResult::success(output)
 }
 
-fn exec_list_presets() -> ToolCallResult {
+fn exec_list_presets(args: &Value) -> ToolCallResult {
     let presets = engine::encoding_presets();
+    if get_str(args, "format") == Some("json") {
+        let list: Vec<Value> = presets
+            .iter()
+            .map(|(key, preset)| {
+                let mut v = serde_json::to_value(preset).unwrap_or_default();
+                v["key"] = json!(key);
+                v["codepoints"] = json!(preset.chars.iter().map(|c| format!("U+{:04X}", *c as u32)).collect::<Vec<_>>());
+                v["terminator"] = json!(preset.terminator.map(|c| format!("U+{:04X}", c as u32)));
+                v["builtin"] = json!(!engine::custom_preset_keys().contains(key));
+                v
+            })
+            .collect();
+        return ToolCallResult::success(serde_json::to_string_pretty(&list).unwrap_or_default());
+    }
     let mut output = String::from("编码预设方案:\n\n");
     for (key, preset) in &presets {
         output.push_str(&format!("[{}]\n", key));
         output.push_str(&format!("  名称: {}\n", preset.name));
         output.push_str(&format!("  说明: {}\n", preset.description));
         let chars_str: Vec<String> = preset.chars.iter().map(|c| format!("U+{:04X}", *c as u32)).collect();
-        output.push_str(&format!("  字符: {}\n\n", chars_str.join(" ")));
+        output.push_str(&format!("  字符: {}\n", chars_str.join(" ")));
+        if let Some(g) = preset.group_size {
+            output.push_str(&format!("  分组: 每字符 {} 个零宽字符\n", g));
+        }
+        if let Some(order) = preset.digit_order {
+            output.push_str(&format!("  位序: {}\n", order.name()));
+        }
+        if let Some(t) = preset.terminator {
+            output.push_str(&format!("  终止符: U+{:04X}\n", t as u32));
+        }
+        if let Some(url) = &preset.reference {
+            output.push_str(&format!("  参考: {}\n", url));
+        }
+        output.push('\n');
+    }
+    ToolCallResult::success(output)
+}
+
+fn exec_presets_test(args: &Value) -> ToolCallResult {
+    let text = match resolve_text(args) {
+        Ok(t) => t,
+        Err(e) => return e,
+    };
+    let analysis = engine::analyze(&text);
+    if analysis.zero_width_count == 0 {
+        return ToolCallResult::error("文本中没有零宽字符，无法比对预设");
+    }
+    let presets = engine::encoding_presets();
+    let cp_list = |cs: &[char]| cs.iter().map(|c| format!("U+{:04X}", *c as u32)).collect::<Vec<_>>().join(" ");
+
+    let Some(key) = get_str(args, "preset") else {
+        let mut ranked: Vec<(String, engine::PresetMatch)> = presets
+            .iter()
+            .map(|(key, preset)| (key.clone(), engine::match_preset(&analysis, preset)))
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.1.coverage()
+                .partial_cmp(&a.1.coverage())
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.1.unused.len().cmp(&b.1.unused.len()))
+        });
+        let mut output = format!("零宽字符 {} 个，{} 种；各预设覆盖率:\n\n", analysis.zero_width_count, analysis.unique_zw_chars);
+        for (key, m) in &ranked {
+            output.push_str(&format!(
+                "  {:<24} {:>5.1}%  预设外 {} 种, 未出现 {} 个\n",
+                key,
+                m.coverage(),
+                m.unmatched.len(),
+                m.unused.len()
+            ));
+        }
+        output.push_str("\n对感兴趣的预设指定 preset 查看解码结果\n");
+        return ToolCallResult::success(output);
+    };
+
+    let Some((_, preset)) = presets.iter().find(|(k, _)| k == key) else {
+        return ToolCallResult::error(format!("未知预设: {}（可用: {}）", key, presets.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>().join(", ")));
+    };
+    let m = engine::match_preset(&analysis, preset);
+    let mut output = format!("预设: [{}] {}\n字符集: {}\n", key, preset.name, cp_list(&preset.chars));
+    output.push_str(&format!(
+        "\n覆盖率: {:.1}%（{}/{} 个零宽字符落在预设字符集内）\n",
+        m.coverage(),
+        m.matched,
+        m.total
+    ));
+    if m.unmatched.is_empty() {
+        output.push_str("预设之外的码点: 无\n");
+    } else {
+        output.push_str("预设之外的码点:\n");
+        for (cp, count) in &m.unmatched {
+            let name = char::from_u32(*cp).and_then(chars::name_of).unwrap_or_default();
+            output.push_str(&format!("  U+{:04X} {}: {} 次\n", cp, name, count));
+        }
+    }
+    if !m.unused.is_empty() {
+        output.push_str(&format!("预设中未出现的字符: {}\n", cp_list(&m.unused)));
+    }
+
+    let results = run_decode(&text, key, &engine::Budget::normal());
+    match results.first() {
+        Some(best) => {
+            output.push_str(&format!("\n按该预设解码（{} 个候选）:\n", results.len()));
+            for r in results.iter().take(3) {
+                output.push_str(&format!("  {} → {:?} (得分 {:.1})\n", r.method, r.decoded, r.score));
+            }
+            let verdict = if m.coverage() >= 99.0 && engine::is_verified(best) {
+                "✓ 字符集完全吻合且解出可信结果，很可能就是该工具"
+            } else if m.coverage() >= 99.0 {
+                "字符集吻合，但解码结果不可信：可能参数不同（分组、位序）或载荷经过加密/压缩"
+            } else {
+                "字符集不完全吻合：载荷可能混入了其他字符，或出自其他工具"
+            };
+            output.push_str(&format!("\n结论: {}\n", verdict));
+        }
+        None => {
+            output.push_str("\n按该预设解码: 无有效结果\n");
+            output.push_str(if m.coverage() >= 99.0 {
+                "\n结论: 字符集吻合但无法解码，可能参数不同或载荷经过加密/压缩\n"
+            } else {
+                "\n结论: 不太可能是该预设\n"
+            });
+        }
     }
     ToolCallResult::success(output)
 }
+
+fn exec_add_preset(args: &Value) -> ToolCallResult {
+    let key = match get_str(args, "key") {
+        Some(k) => k.trim(),
+        None => return ToolCallResult::failure(ErrorCode::InputMissing, "缺少 key 参数"),
+    };
+    let items = match args.get("chars").and_then(|v| v.as_array()) {
+        Some(a) => a,
+        None => return ToolCallResult::failure(ErrorCode::InputMissing, "缺少 chars 参数（字符串数组）"),
+    };
+    let mut charset: Vec<char> = Vec::new();
+    for item in items {
+        let s = item.as_str().unwrap_or("");
+        match chars::parse_codepoint(s).and_then(char::from_u32) {
+            Some(c) if !charset.contains(&c) => charset.push(c),
+            Some(c) => return ToolCallResult::error(format!("字符重复: U+{:04X}", c as u32)),
+            None => return ToolCallResult::error(format!("无法解析字符: {}", item)),
+        }
+    }
+    let group_size = match args.get("group_size").and_then(|v| v.as_u64()) {
+        Some(0) => return ToolCallResult::error("group_size 必须大于 0"),
+        g => g.map(|g| g as usize),
+    };
+    let digit_order = match get_str(args, "digit_order") {
+        None => None,
+        Some(name) => match engine::DigitOrder::parse(name) {
+            Some(order) => Some(order),
+            None => return ToolCallResult::error(format!("未知 digit_order: {}（可用: msb_first, lsb_first）", name)),
+        },
+    };
+    let terminator = match get_str(args, "terminator") {
+        None => None,
+        Some(t) => match chars::parse_codepoint(t).and_then(char::from_u32) {
+            Some(c) if !charset.contains(&c) => Some(c),
+            Some(_) => return ToolCallResult::error("terminator 不能同时出现在 chars 中"),
+            None => return ToolCallResult::error(format!("无法解析 terminator: {}", t)),
+        },
+    };
+    let preset = engine::Preset {
+        name: get_str(args, "name").unwrap_or(key).to_string(),
+        chars: charset.clone(),
+        description: get_str(args, "description").unwrap_or("自定义预设").to_string(),
+        group_size,
+        digit_order,
+        terminator,
+        reference: get_str(args, "reference").map(str::to_string),
+    };
+    let replaced = match engine::add_preset(key, preset) {
+        Ok(r) => r,
+        Err(e) => return ToolCallResult::error(e),
+    };
+    server::notify_tools_changed();
+    let chars_str: Vec<String> = charset.iter().map(|c| format!("U+{:04X}", *c as u32)).collect();
+    ToolCallResult::success(format!(
+        "已{}自定义预设 [{}]: {}\n专用解码工具: {}{}",
+        if replaced { "替换" } else { "添加" },
+        key,
+        chars_str.join(" "),
+        PRESET_TOOL_PREFIX,
+        key
+    ))
+}
+
+fn exec_remove_preset(args: &Value) -> ToolCallResult {
+    let key = match get_str(args, "key") {
+        Some(k) => k.trim(),
+        None => return ToolCallResult::failure(ErrorCode::InputMissing, "缺少 key 参数"),
+    };
+    if !engine::remove_preset(key) {
+        return ToolCallResult::failure(ErrorCode::NotFound, format!("没有名为 {} 的自定义预设（内置预设不可删除）", key));
+    }
+    server::notify_tools_changed();
+    ToolCallResult::success(format!("已删除自定义预设 [{}]", key))
+}
@@ -0,0 +1,199 @@
+//! 临时工作区 - 工具生成的大文件（截断前的完整输出、导出的报告、编码结果）统一存放在这里
+//!
+//! 产物以 `zw-tmp://<name>` URI 返回，客户端通过 `resources/read` 读取，也可作为其他工具的
+//! `file_path` 输入，不必自己选择绝对路径。超过保留时间（TTL）的产物在下次写入或列出时删除；
+//! 总大小超过上限时从最旧的产物开始删除。根目录、TTL 与大小上限见配置文件 `[workspace]`。
+//! 只有 `store` 生成的 `<pid>-<序号>-<名称>` 文件算作产物，根目录指向已有目录时其中的其他文件不受影响。
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use serde_json::{json, Value};
+
+use super::{config, quota};
+use crate::zw_core::ingest;
+
+/// 产物 URI 前缀
+pub const URI_PREFIX: &str = "zw-tmp://";
+
+/// 默认保留时间
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// 默认总大小上限
+pub const DEFAULT_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+/// 产物名称的最大长度（不含序号前缀）
+const MAX_NAME_CHARS: usize = 80;
+
+static NEXT_ARTIFACT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 写入与清理串行执行，避免并发调用时按过期的总大小淘汰
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// 写入工作区的产物
+#[derive(Debug, Clone)]
+pub struct Artifact {
+    pub uri: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// 工作区根目录：配置的 root，默认为系统临时目录下的 zw-mcp-workspace
+pub fn root() -> PathBuf {
+    config::workspace().root.unwrap_or_else(|| std::env::temp_dir().join("zw-mcp-workspace"))
+}
+
+fn ttl() -> Duration {
+    config::workspace().ttl_secs.map(Duration::from_secs).unwrap_or(DEFAULT_TTL)
+}
+
+fn max_bytes() -> u64 {
+    config::workspace().max_bytes.unwrap_or(DEFAULT_MAX_BYTES)
+}
+
+/// 只保留字母、数字与 `.-_`，其余替换为 `_`，防止通过名称跳出工作区
+fn sanitize(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .take(MAX_NAME_CHARS)
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.');
+    if cleaned.is_empty() {
+        "output.txt".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+fn mime_of(name: &str) -> &'static str {
+    match name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).as_deref() {
+        Some("txt" | "log" | "md") => "text/plain",
+        Some("csv") => "text/csv",
+        Some("json" | "jsonl") => "application/json",
+        Some("html" | "htm") => "text/html",
+        _ => "application/octet-stream",
+    }
+}
+
+/// 是否是 `store` 生成的产物名（`<pid>-<序号>-<名称>`）
+fn is_artifact_name(name: &str) -> bool {
+    let mut parts = name.splitn(3, '-');
+    let mut number = || parts.next().is_some_and(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()));
+    number() && number() && parts.next().is_some_and(|rest| !rest.is_empty())
+}
+
+/// 工作区中的产物：(文件名, 路径, 大小, 修改时间)，按修改时间从旧到新
+fn entries() -> Vec<(String, PathBuf, u64, SystemTime)> {
+    let Ok(dir) = fs::read_dir(root()) else {
+        return Vec::new();
+    };
+    let mut out: Vec<_> = dir
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let meta = e.metadata().ok().filter(|m| m.is_file())?;
+            let name = e.file_name().to_str().filter(|n| is_artifact_name(n))?.to_string();
+            Some((name, e.path(), meta.len(), meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)))
+        })
+        .collect();
+    out.sort_by_key(|e| e.3);
+    out
+}
+
+/// 删除过期产物；再从最旧的开始删除，直到总大小加上 `incoming` 不超过上限
+fn cleanup(incoming: u64) {
+    let now = SystemTime::now();
+    let ttl = ttl();
+    let mut live = Vec::new();
+    for (name, path, size, modified) in entries() {
+        if now.duration_since(modified).unwrap_or_default() > ttl {
+            match fs::remove_file(&path) {
+                Ok(_) => tracing::debug!("Workspace artifact expired: {}", name),
+                Err(e) => tracing::warn!("Failed to remove expired artifact {}: {}", path.display(), e),
+            }
+        } else {
+            live.push((path, size));
+        }
+    }
+    let cap = max_bytes();
+    let mut total: u64 = live.iter().map(|(_, s)| s).sum::<u64>() + incoming;
+    for (path, size) in live {
+        if total <= cap {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            tracing::debug!("Workspace over size cap, evicted {}", path.display());
+            total -= size;
+        }
+    }
+}
+
+/// 写入一个产物；`name` 为建议的文件名（会加上序号前缀并去除不安全字符）
+pub fn store(name: &str, bytes: &[u8]) -> Result<Artifact, String> {
+    let size = bytes.len() as u64;
+    let cap = max_bytes();
+    if size > cap {
+        return Err(format!("产物 {} 字节，超过工作区大小上限 {} 字节", size, cap));
+    }
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = root();
+    fs::create_dir_all(&dir).map_err(|e| format!("创建工作区目录失败: {} ({})", dir.display(), e))?;
+    cleanup(size);
+    let file_name = format!(
+        "{}-{}-{}",
+        std::process::id(),
+        NEXT_ARTIFACT_ID.fetch_add(1, Ordering::Relaxed),
+        sanitize(name)
+    );
+    let path = dir.join(&file_name);
+    fs::write(&path, bytes).map_err(|e| format!("写入工作区失败: {} ({})", path.display(), e))?;
+    Ok(Artifact { uri: format!("{}{}", URI_PREFIX, file_name), path, size })
+}
+
+/// 把 `zw-tmp://` URI 解析为工作区中的文件路径
+pub fn resolve(uri: &str) -> Result<PathBuf, String> {
+    let name = uri.strip_prefix(URI_PREFIX).ok_or_else(|| format!("不是工作区 URI: {}", uri))?;
+    let safe = name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if !is_artifact_name(name) || !safe {
+        return Err(format!("无效的工作区产物名: {}", name));
+    }
+    let path = root().join(name);
+    if !path.is_file() {
+        return Err(format!("工作区产物不存在或已过期: {}", uri));
+    }
+    Ok(path)
+}
+
+/// resources/list 条目（同时清理过期产物）
+pub fn resources() -> Vec<Value> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    cleanup(0);
+    entries()
+        .into_iter()
+        .map(|(name, _, size, _)| {
+            json!({
+                "uri": format!("{}{}", URI_PREFIX, name),
+                "name": name,
+                "description": format!("工具生成的产物（{} 字节）", size),
+                "mimeType": mime_of(&name),
+                "size": size,
+            })
+        })
+        .collect()
+}
+
+/// resources/read: UTF-8 内容以 text 返回，其余以 base64 blob 返回
+pub fn read(uri: &str) -> Result<Value, String> {
+    let path = resolve(uri)?;
+    let raw = fs::read(&path).map_err(|e| format!("读取产物失败: {} ({})", path.display(), e))?;
+    quota::record_read(raw.len());
+    let name = uri.strip_prefix(URI_PREFIX).unwrap_or(uri);
+    let content = match String::from_utf8(raw) {
+        Ok(text) => json!({ "uri": uri, "mimeType": mime_of(name), "text": text }),
+        Err(e) => json!({ "uri": uri, "mimeType": mime_of(name), "blob": ingest::encode_base64(e.as_bytes()) }),
+    };
+    Ok(json!({ "contents": [content] }))
+}
@@ -0,0 +1,132 @@
+//! 标注副本 - 把每个不可见字符替换为行内脚注标记，附录逐条列出码点与含义
+//!
+//! 生成的是 Markdown：正文保留原文的可见内容与换行，不可见字符换成 `[^n]`，附录中每个标记给出
+//! 码点、名称、分类、所在行列，以及在最佳解码结果中的作用（哪一组的第几位、解出哪个字符）。
+//! 审阅者可以把它直接附到工单里，不需要能显示零宽字符的编辑器。
+
+use super::chars::{category_of, is_zero_width, name_of};
+use super::engine::{Acceptance, DecodeResult};
+use super::explain::char_roles;
+use super::sanitize::display;
+
+/// 需要转义的 Markdown 特殊字符
+const MARKDOWN_SPECIAL: &str = "\\`*_[]<>#|~";
+
+/// 一个标记
+#[derive(Debug, Clone)]
+pub struct Note {
+    pub marker: usize,
+    pub ch: char,
+    /// 行号、列号（从 1 开始，列按字符计，含不可见字符）
+    pub line: usize,
+    pub column: usize,
+    /// 在解码结果中的作用；未解码时为 None
+    pub role: Option<String>,
+}
+
+/// 标注结果
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    pub notes: Vec<Note>,
+    /// 各码点及出现次数，按首次出现顺序
+    pub distribution: Vec<(char, usize)>,
+    pub markdown: String,
+}
+
+fn cp(ch: char) -> String {
+    format!("U+{:04X}", ch as u32)
+}
+
+fn escape(ch: char, out: &mut String) {
+    if MARKDOWN_SPECIAL.contains(ch) {
+        out.push('\\');
+    }
+    out.push(ch);
+}
+
+/// 生成标注副本；`result` 为用来解释字符作用的解码结果
+pub fn annotate(text: &str, source: &str, result: Option<&DecodeResult>, acceptance: &Acceptance) -> Annotation {
+    let roles = result.map(|r| char_roles(text, r, acceptance)).unwrap_or_default();
+    let mut notes = Vec::new();
+    let mut distribution: Vec<(char, usize)> = Vec::new();
+    let mut body_lines = Vec::new();
+    let mut index = 0;
+    for (l, line) in text.split('\n').enumerate() {
+        let mut out = String::new();
+        for (c, ch) in line.chars().enumerate() {
+            // CRLF 的 CR 不输出
+            if ch == '\r' {
+                index += 1;
+                continue;
+            }
+            if is_zero_width(ch) {
+                let marker = notes.len() + 1;
+                out.push_str(&format!("[^{}]", marker));
+                let role = result
+                    .map(|_| roles.get(&index).cloned().unwrap_or_else(|| "不属于该解码方案（解码时忽略）".to_string()));
+                notes.push(Note { marker, ch, line: l + 1, column: c + 1, role });
+                match distribution.iter_mut().find(|(d, _)| *d == ch) {
+                    Some((_, n)) => *n += 1,
+                    None => distribution.push((ch, 1)),
+                }
+            } else {
+                escape(ch, &mut out);
+            }
+            index += 1;
+        }
+        // 换行符本身
+        index += 1;
+        body_lines.push(out);
+    }
+
+    let mut md = String::from("# 不可见字符标注\n\n");
+    md.push_str(&format!("- 来源: {}\n", source));
+    md.push_str(&format!("- 不可见字符: {} 个（{} 种）\n", notes.len(), distribution.len()));
+    match result {
+        Some(r) => md.push_str(&format!(
+            "- 解码结果: `{}`（方案: {}，得分 {:.1}）\n",
+            display(&r.decoded).replace('`', "'"),
+            r.method,
+            r.score
+        )),
+        None => md.push_str("- 解码结果: 未解出可读载荷，附录只列出码点\n"),
+    }
+
+    md.push_str("\n## 字符汇总\n\n| 码点 | 名称 | 分类 | 次数 |\n|------|------|------|------|\n");
+    for (ch, n) in &distribution {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            cp(*ch),
+            name_of(*ch).unwrap_or_default(),
+            category_of(*ch).unwrap_or_default(),
+            n
+        ));
+    }
+
+    // 非空行之间用反斜杠硬换行，保留原文的分行
+    md.push_str("\n## 标注文本\n\n");
+    for (i, line) in body_lines.iter().enumerate() {
+        md.push_str(line);
+        if !line.is_empty() && body_lines.get(i + 1).is_some_and(|next| !next.is_empty()) {
+            md.push('\\');
+        }
+        md.push('\n');
+    }
+
+    md.push_str("\n## 附录\n\n");
+    for note in &notes {
+        md.push_str(&format!(
+            "[^{}]: `{}` {} · 第 {} 行第 {} 列",
+            note.marker,
+            cp(note.ch),
+            name_of(note.ch).unwrap_or_default(),
+            note.line,
+            note.column
+        ));
+        if let Some(role) = &note.role {
+            md.push_str(&format!(" · {}", role));
+        }
+        md.push('\n');
+    }
+    Annotation { notes, distribution, markdown: md }
+}
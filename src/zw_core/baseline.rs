@@ -0,0 +1,224 @@
+//! 不可见字符频率基线 - 判断某类文档中的零宽字符数量是否正常
+
+use std::collections::BTreeMap;
+
+use super::chars::{all_zero_width_chars, is_arabic_script, is_unicode_tag, is_zero_width};
+use super::engine::Analysis;
+use super::sanitize::is_emoji;
+
+/// 基线的计数基准
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Basis {
+    /// 每个可见字符
+    Visible,
+    /// 每个 emoji
+    Emoji,
+    /// 每个阿拉伯字母
+    ArabicLetter,
+    /// 每个黑旗 🏴（地区旗帜的 Tag 序列）
+    FlagBase,
+    /// 整篇文档固定次数
+    Document,
+}
+
+impl Basis {
+    fn name(&self) -> &'static str {
+        match self {
+            Basis::Visible => "每可见字符",
+            Basis::Emoji => "每 emoji",
+            Basis::ArabicLetter => "每阿拉伯字母",
+            Basis::FlagBase => "每个 🏴",
+            Basis::Document => "每篇",
+        }
+    }
+}
+
+/// 单条基线: 这些码点在该类文档中按基准计算的正常上限
+#[derive(Debug, Clone)]
+pub struct Expectation {
+    pub codepoints: fn(u32) -> bool,
+    pub basis: Basis,
+    pub max_rate: f64,
+    pub note: &'static str,
+}
+
+/// 一类文档的基线模型
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    pub key: &'static str,
+    pub name: &'static str,
+    pub expectations: Vec<Expectation>,
+}
+
+fn bom() -> Expectation {
+    Expectation { codepoints: |c| c == 0xFEFF, basis: Basis::Document, max_rate: 1.0, note: "文件开头的 BOM" }
+}
+
+/// 所有内置基线
+pub fn baselines() -> Vec<Baseline> {
+    vec![
+        Baseline {
+            key: "plain",
+            name: "普通文本",
+            expectations: vec![bom()],
+        },
+        Baseline {
+            key: "emoji_chat",
+            name: "含 emoji 的聊天文本",
+            expectations: vec![
+                bom(),
+                Expectation { codepoints: |c| c == 0xFE0F, basis: Basis::Emoji, max_rate: 1.0, note: "VS16 emoji 呈现" },
+                Expectation { codepoints: |c| c == 0xFE0E, basis: Basis::Emoji, max_rate: 0.5, note: "VS15 文本呈现" },
+                Expectation { codepoints: |c| c == 0x200D, basis: Basis::Emoji, max_rate: 1.0, note: "ZWJ 组合序列" },
+                Expectation { codepoints: |c| (0xE0020..=0xE007F).contains(&c), basis: Basis::FlagBase, max_rate: 6.0, note: "地区旗帜 Tag 序列" },
+            ],
+        },
+        Baseline {
+            key: "word_export",
+            name: "Word/Office 导出文档",
+            expectations: vec![
+                bom(),
+                Expectation { codepoints: |c| c == 0x00AD, basis: Basis::Visible, max_rate: 0.01, note: "可选连字符" },
+                Expectation { codepoints: |c| c == 0x200B, basis: Basis::Visible, max_rate: 0.005, note: "换行提示" },
+                Expectation { codepoints: |c| c == 0x200E || c == 0x200F, basis: Basis::Visible, max_rate: 0.005, note: "方向标记" },
+                Expectation { codepoints: |c| c == 0x2060, basis: Basis::Visible, max_rate: 0.002, note: "禁止断行" },
+            ],
+        },
+        Baseline {
+            key: "persian_arabic",
+            name: "波斯语/阿拉伯语文本",
+            expectations: vec![
+                bom(),
+                Expectation { codepoints: |c| c == 0x200C, basis: Basis::ArabicLetter, max_rate: 0.15, note: "正字法要求的 ZWNJ" },
+                Expectation { codepoints: |c| c == 0x200D, basis: Basis::ArabicLetter, max_rate: 0.02, note: "强制连写" },
+                Expectation { codepoints: |c| c == 0x061C, basis: Basis::ArabicLetter, max_rate: 0.02, note: "阿拉伯字母标记 ALM" },
+                Expectation { codepoints: |c| c == 0x200E || c == 0x200F, basis: Basis::ArabicLetter, max_rate: 0.05, note: "混排方向标记" },
+            ],
+        },
+    ]
+}
+
+/// 文档中各基准的数量
+#[derive(Debug, Clone, Default)]
+pub struct BasisCounts {
+    pub visible: usize,
+    pub emoji: usize,
+    pub arabic: usize,
+    pub flag_base: usize,
+}
+
+impl BasisCounts {
+    pub fn of(text: &str) -> Self {
+        let mut counts = BasisCounts::default();
+        for ch in text.chars() {
+            if is_zero_width(ch) || ch.is_control() {
+                continue;
+            }
+            counts.visible += 1;
+            if ch as u32 == 0x1F3F4 {
+                counts.flag_base += 1;
+            }
+            if is_emoji(ch) {
+                counts.emoji += 1;
+            } else if is_arabic_script(ch) {
+                counts.arabic += 1;
+            }
+        }
+        counts
+    }
+
+    fn get(&self, basis: Basis) -> usize {
+        match basis {
+            Basis::Visible => self.visible,
+            Basis::Emoji => self.emoji,
+            Basis::ArabicLetter => self.arabic,
+            Basis::FlagBase => self.flag_base,
+            Basis::Document => 1,
+        }
+    }
+}
+
+/// 根据文本内容猜测文档类型
+pub fn guess_baseline(counts: &BasisCounts) -> &'static str {
+    if counts.visible > 0 && counts.arabic * 5 >= counts.visible {
+        "persian_arabic"
+    } else if counts.emoji > 0 {
+        "emoji_chat"
+    } else {
+        "plain"
+    }
+}
+
+/// 单个码点的对比结果
+#[derive(Debug, Clone)]
+pub struct Deviation {
+    pub codepoint: u32,
+    pub observed: usize,
+    /// 匹配到的基线及按本文档换算的上限；None 表示该类文档中不应出现
+    pub expected: Option<(Expectation, f64)>,
+}
+
+impl Deviation {
+    pub fn is_normal(&self) -> bool {
+        match &self.expected {
+            Some((_, limit)) => self.observed as f64 <= limit.max(1.0),
+            None => false,
+        }
+    }
+}
+
+/// 将分析结果与基线对比
+pub fn compare(analysis: &Analysis, counts: &BasisCounts, baseline: &Baseline) -> Vec<Deviation> {
+    analysis
+        .distribution
+        .iter()
+        .map(|(&cp, &observed)| {
+            let expected = baseline
+                .expectations
+                .iter()
+                .find(|e| (e.codepoints)(cp))
+                .map(|e| (e.clone(), e.max_rate * counts.get(e.basis) as f64));
+            Deviation { codepoint: cp, observed, expected }
+        })
+        .collect()
+}
+
+/// 格式化基线对比报告
+pub fn format_comparison(deviations: &[Deviation], baseline: &Baseline, auto: bool) -> String {
+    let names: BTreeMap<u32, &str> = all_zero_width_chars().iter().map(|z| (z.codepoint, z.name)).collect();
+    let mut out = format!(
+        "\n基线对比 ({}{}):\n",
+        baseline.name,
+        if auto { "，自动识别" } else { "" }
+    );
+    let mut abnormal = 0;
+    for d in deviations {
+        let name = if is_unicode_tag(char::from_u32(d.codepoint).unwrap_or('\0')) {
+            "UNICODE TAG"
+        } else {
+            names.get(&d.codepoint).copied().unwrap_or("?")
+        };
+        let verdict = match &d.expected {
+            Some((e, limit)) => {
+                let limit = *limit;
+                let status = if d.is_normal() {
+                    "正常".to_string()
+                } else {
+                    format!("异常 (约为上限的 {:.1} 倍)", d.observed as f64 / limit.max(1.0))
+                };
+                format!("基线上限 ≈ {:.0} ({} {}，{}) → {}", limit.max(1.0), e.basis.name(), e.max_rate, e.note, status)
+            }
+            None => "此类文档中通常不出现 → 异常".to_string(),
+        };
+        if !d.is_normal() {
+            abnormal += 1;
+        }
+        out.push_str(&format!("  U+{:04X} {}: {} 次, {}\n", d.codepoint, name, d.observed, verdict));
+    }
+    if abnormal == 0 {
+        out.push_str(&format!("结论: 零宽字符数量符合{}的正常分布，未见隐写迹象\n", baseline.name));
+    } else {
+        out.push_str(&format!("结论: {} 种字符偏离基线，可能携带隐写数据\n", abnormal));
+    }
+    out
+}
@@ -0,0 +1,135 @@
+//! 题目语料回归测试 - 对已知答案的零宽隐写题批量运行自动解码，统计召回率与耗时
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use super::engine::{auto_decode, Budget};
+
+/// 内置语料（corpus/cases.jsonl）
+const EMBEDDED_CORPUS: &str = include_str!("../../corpus/cases.jsonl");
+/// 短解码误报回归语料（corpus/false_positives.jsonl）：另有极短诱饵载荷，答案必须排第一
+const EMBEDDED_FALSE_POSITIVES: &str = include_str!("../../corpus/false_positives.jsonl");
+
+/// 一道题目
+#[derive(Debug, Clone, Deserialize)]
+pub struct Case {
+    pub name: String,
+    #[serde(default)]
+    pub source: String,
+    pub text: String,
+    pub answer: String,
+}
+
+/// 单道题的运行结果
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    pub name: String,
+    pub source: String,
+    /// 答案出现在第几个候选（从 1 开始）
+    pub rank: Option<usize>,
+    pub candidates: usize,
+    pub elapsed: Duration,
+    pub top: Option<String>,
+}
+
+/// 解析 JSONL 语料（空行和 # 开头的行忽略）
+fn parse_corpus(src: &str, origin: &str) -> Result<Vec<Case>, String> {
+    src.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map(|(i, l)| serde_json::from_str(l).map_err(|e| format!("{} 第 {} 行解析失败: {}", origin, i + 1, e)))
+        .collect()
+}
+
+/// 内置语料
+pub fn embedded_corpus() -> Result<Vec<Case>, String> {
+    parse_corpus(EMBEDDED_CORPUS, "内置语料")
+}
+
+/// 内置的短解码误报回归语料
+pub fn false_positive_corpus() -> Result<Vec<Case>, String> {
+    parse_corpus(EMBEDDED_FALSE_POSITIVES, "误报语料")
+}
+
+/// 从目录加载语料: 目录下所有 .jsonl 文件
+pub fn load_corpus_dir(dir: &Path) -> Result<Vec<Case>, String> {
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| format!("读取目录失败: {}", e))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    files.sort();
+    let mut cases = Vec::new();
+    for file in files {
+        let src = fs::read_to_string(&file).map_err(|e| format!("读取 {} 失败: {}", file.display(), e))?;
+        cases.extend(parse_corpus(&src, &file.display().to_string())?);
+    }
+    Ok(cases)
+}
+
+/// 对每道题运行自动解码
+pub fn run(cases: &[Case], budget: &Budget) -> Vec<CaseResult> {
+    cases
+        .iter()
+        .map(|case| {
+            let start = Instant::now();
+            let results = auto_decode(&case.text, budget);
+            let elapsed = start.elapsed();
+            CaseResult {
+                name: case.name.clone(),
+                source: case.source.clone(),
+                rank: results.iter().position(|r| r.decoded.contains(&case.answer)).map(|i| i + 1),
+                candidates: results.len(),
+                elapsed,
+                top: results.first().map(|r| r.decoded.chars().take(40).collect()),
+            }
+        })
+        .collect()
+}
+
+/// 召回率（答案出现在任意候选中）
+pub fn recall(results: &[CaseResult]) -> f64 {
+    if results.is_empty() {
+        return 0.0;
+    }
+    results.iter().filter(|r| r.rank.is_some()).count() as f64 / results.len() as f64
+}
+
+/// 格式化基准报告
+pub fn format_report(results: &[CaseResult]) -> String {
+    let mut out = String::new();
+    let total: Duration = results.iter().map(|r| r.elapsed).sum();
+    let top1 = results.iter().filter(|r| r.rank == Some(1)).count();
+    let found = results.iter().filter(|r| r.rank.is_some()).count();
+    out.push_str(&format!("题目数: {}\n", results.len()));
+    out.push_str(&format!("召回率: {}/{} ({:.1}%)\n", found, results.len(), recall(results) * 100.0));
+    out.push_str(&format!("Top-1 命中: {}/{}\n", top1, results.len()));
+    out.push_str(&format!("总耗时: {:.1} ms\n\n", total.as_secs_f64() * 1000.0));
+    for r in results {
+        let status = match r.rank {
+            Some(1) => "✓".to_string(),
+            Some(k) => format!("✓ (第 {} 名)", k),
+            None => "✗".to_string(),
+        };
+        out.push_str(&format!(
+            "  {:<24} {:<12} 候选 {:>3}  {:>8.1} ms",
+            r.name,
+            status,
+            r.candidates,
+            r.elapsed.as_secs_f64() * 1000.0
+        ));
+        if r.rank != Some(1) {
+            if let Some(top) = &r.top {
+                out.push_str(&format!("  首位: {:?}", top));
+            }
+            if !r.source.is_empty() {
+                out.push_str(&format!("  [{}]", r.source));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,158 @@
+//! 空白消息分析 - 识别聊天软件中全由不可见字符组成的「空白消息」
+//!
+//! Telegram、WhatsApp 等客户端会拒绝发送空消息或只含空格的消息，于是有人用盲文空白、韩文填充符、
+//! 成串的零宽字符或全角空格等「看起来是空的」字符绕过检查，用来刷屏、恶作剧或藏信息。这里判断用了
+//! 哪种技巧、估计它只是占位填充还是可能携带数据，并给出可替换原消息的清理结果。
+
+use super::chars::{is_unicode_tag, is_zero_width};
+use super::engine::{self, Budget, DecodeResult};
+use super::names::is_blank_like;
+
+/// 盲文空白：不在默认可忽略字符中，但显示为空
+const BRAILLE_BLANK: char = '\u{2800}';
+/// 判定为周期性填充的最长周期
+const MAX_PERIOD: usize = 8;
+/// 少于这么多个不可见字符时不认为能携带数据
+const MIN_PAYLOAD_CHARS: usize = 8;
+/// 解码得分达到这个值视为解出了可读内容
+const READABLE_SCORE: f64 = 70.0;
+
+/// 空白消息使用的技巧
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Trick {
+    BrailleBlank,
+    HangulFiller,
+    ZeroWidth,
+    UnicodeTags,
+    UnicodeSpace,
+}
+
+impl Trick {
+    pub fn name(self) -> &'static str {
+        match self {
+            Trick::BrailleBlank => "盲文空白 (U+2800)",
+            Trick::HangulFiller => "韩文填充符 (U+3164 / U+115F / U+1160 / U+FFA0)",
+            Trick::ZeroWidth => "零宽/格式字符串",
+            Trick::UnicodeTags => "Unicode Tags",
+            Trick::UnicodeSpace => "Unicode 空格（NBSP、全角空格等）",
+        }
+    }
+
+    fn of(ch: char) -> Option<Trick> {
+        match ch {
+            BRAILLE_BLANK => Some(Trick::BrailleBlank),
+            '\u{3164}' | '\u{115F}' | '\u{1160}' | '\u{FFA0}' => Some(Trick::HangulFiller),
+            _ if is_unicode_tag(ch) => Some(Trick::UnicodeTags),
+            _ if is_zero_width(ch) => Some(Trick::ZeroWidth),
+            _ if ch.is_whitespace() && !ch.is_ascii() => Some(Trick::UnicodeSpace),
+            _ => None,
+        }
+    }
+}
+
+/// 是否携带数据的估计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Payload {
+    /// 单一字符或短周期重复，只是占位
+    Padding,
+    /// 多种不可见字符不规则交替，可能是编码载荷
+    Possible,
+    /// 解码出了可读内容
+    Likely,
+}
+
+impl Payload {
+    pub fn label(self) -> &'static str {
+        match self {
+            Payload::Padding => "仅为填充",
+            Payload::Possible => "可能携带数据",
+            Payload::Likely => "携带数据",
+        }
+    }
+}
+
+/// 空白消息的分析结果
+#[derive(Debug, Clone)]
+pub struct BlankReport {
+    /// 不计 ASCII 空白的字符数
+    pub chars: usize,
+    /// 各技巧的字符数，按数量降序
+    pub tricks: Vec<(Trick, usize)>,
+    /// 不可见字符（码点, 次数），按首次出现顺序
+    pub distribution: Vec<(u32, usize)>,
+    pub payload: Payload,
+    /// 估计的依据
+    pub reason: String,
+    /// 尝试解码得到的最佳结果
+    pub best: Option<DecodeResult>,
+}
+
+impl BlankReport {
+    /// 主要技巧；混用多种时依次列出
+    pub fn trick_summary(&self) -> String {
+        self.tricks.iter().map(|(t, _)| t.name()).collect::<Vec<_>>().join(" + ")
+    }
+
+    /// 建议替换原消息的文本：携带数据时保留解码结果供审阅
+    pub fn replacement(&self) -> String {
+        let mut out = format!("[空白消息: {} 个不可见字符，{}]", self.chars, self.tricks[0].0.name());
+        if let Some(best) = self.best.as_ref().filter(|_| self.payload == Payload::Likely) {
+            out = format!("{} 隐藏内容: {}", out, best.decoded);
+        }
+        out
+    }
+}
+
+/// 序列的最小周期（不超过 `MAX_PERIOD`，且至少重复两次）
+fn period(seq: &[char]) -> Option<usize> {
+    (1..=MAX_PERIOD.min(seq.len() / 2)).find(|&p| (p..seq.len()).all(|i| seq[i] == seq[i - p]))
+}
+
+/// 是否是空白消息：没有可见字符，且不只含 ASCII 空白（普通空白会被客户端直接拦下）
+pub fn is_blank_message(text: &str) -> bool {
+    text.chars().all(is_blank_like) && text.chars().any(|c| !c.is_ascii_whitespace())
+}
+
+/// 分析消息；不是空白消息时返回 None
+pub fn analyze(text: &str, budget: &Budget) -> Option<BlankReport> {
+    if !is_blank_message(text) {
+        return None;
+    }
+    let seq: Vec<char> = text.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    let mut tricks: Vec<(Trick, usize)> = Vec::new();
+    let mut distribution: Vec<(u32, usize)> = Vec::new();
+    for &ch in &seq {
+        if let Some(trick) = Trick::of(ch) {
+            match tricks.iter_mut().find(|(t, _)| *t == trick) {
+                Some((_, n)) => *n += 1,
+                None => tricks.push((trick, 1)),
+            }
+        }
+        match distribution.iter_mut().find(|(cp, _)| *cp == ch as u32) {
+            Some((_, n)) => *n += 1,
+            None => distribution.push((ch as u32, 1)),
+        }
+    }
+    tricks.sort_by_key(|&(t, n)| (std::cmp::Reverse(n), t));
+
+    let (mut payload, mut reason) = if seq.len() == 1 {
+        (Payload::Padding, "只有单个字符".to_string())
+    } else if distribution.len() == 1 {
+        (Payload::Padding, format!("只有一种字符重复 {} 次", seq.len()))
+    } else if let Some(p) = period(&seq) {
+        (Payload::Padding, format!("{} 种字符以周期 {} 重复", distribution.len(), p))
+    } else if seq.len() < MIN_PAYLOAD_CHARS {
+        (Payload::Padding, format!("只有 {} 个字符，容量太小", seq.len()))
+    } else {
+        (Payload::Possible, format!("{} 种字符不规则交替，共 {} 个，符合编码载荷特征", distribution.len(), seq.len()))
+    };
+    let mut best = None;
+    if payload == Payload::Possible || tricks.iter().any(|(t, _)| *t == Trick::UnicodeTags) {
+        best = engine::auto_decode(text, budget).into_iter().next();
+        if let Some(b) = best.as_ref().filter(|b| engine::is_verified(b) || b.score >= READABLE_SCORE) {
+            payload = Payload::Likely;
+            reason = format!("按 {} 解码出可读内容（得分 {:.1}）", b.method, b.score);
+        }
+    }
+    Some(BlankReport { chars: seq.len(), tricks, distribution, payload, reason, best })
+}
@@ -0,0 +1,191 @@
+//! 文件雕刻 - 从零宽层还原原始字节并按文件头识别嵌入的二进制文件
+//!
+//! 文本解码会丢弃 0 字节、过滤不可打印结果，不适合还原 zip/png 之类的二进制载荷。这里按原始字节
+//! 解释零宽序列（二进制按字符对、N 进制按能表示一个字节的最小分组，两种位序），另外把解码结果中
+//! 的 base64/hex 文本还原为字节，然后在每个字节流中查找已知文件头，按格式尾部截出完整文件。
+
+use std::collections::HashSet;
+
+use super::engine::{self, Budget};
+use super::ingest;
+
+/// 已知文件类型
+#[derive(Debug)]
+pub struct FileType {
+    pub name: &'static str,
+    pub extension: &'static str,
+    magic: &'static [u8],
+}
+
+const FILE_TYPES: &[FileType] = &[
+    FileType { name: "ZIP", extension: "zip", magic: b"PK\x03\x04" },
+    FileType { name: "PNG", extension: "png", magic: b"\x89PNG\r\n\x1a\n" },
+    FileType { name: "GZIP", extension: "gz", magic: b"\x1f\x8b\x08" },
+    FileType { name: "ELF", extension: "elf", magic: b"\x7fELF" },
+    FileType { name: "JPEG", extension: "jpg", magic: b"\xff\xd8\xff" },
+    FileType { name: "GIF", extension: "gif", magic: b"GIF8" },
+    FileType { name: "PDF", extension: "pdf", magic: b"%PDF-" },
+    FileType { name: "7Z", extension: "7z", magic: b"7z\xbc\xaf\x27\x1c" },
+    FileType { name: "RAR", extension: "rar", magic: b"Rar!\x1a\x07" },
+    FileType { name: "BZIP2", extension: "bz2", magic: b"BZh" },
+];
+
+/// 支持识别的文件类型名
+pub fn supported_types() -> Vec<&'static str> {
+    FILE_TYPES.iter().map(|t| t.name).collect()
+}
+
+/// 参与组合的高频字符数
+const MAX_SYMBOLS: usize = 6;
+/// 雕刻结果的数量上限
+const MAX_CARVED: usize = 16;
+
+/// 一个还原出的字节流
+#[derive(Debug, Clone)]
+pub struct ByteStream {
+    pub method: String,
+    pub bytes: Vec<u8>,
+}
+
+/// 一个雕刻出的文件
+#[derive(Debug, Clone)]
+pub struct Carved {
+    pub file_type: &'static FileType,
+    /// 还原字节流的方式
+    pub method: String,
+    /// 文件头在字节流中的偏移
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+    /// 按格式找到了文件尾（否则截到字节流末尾）
+    pub complete: bool,
+}
+
+fn describe_charset(charset: &[char]) -> String {
+    charset.iter().map(|c| format!("U+{:04X}", *c as u32)).collect::<Vec<_>>().join("+")
+}
+
+/// 能表示 0~255 的最小分组
+fn byte_group_size(base: usize) -> usize {
+    let mut size = 1;
+    while base.pow(size as u32) < 256 {
+        size += 1;
+    }
+    size
+}
+
+/// 按固定分组把数字流还原为字节；出现超过 255 的组时不是字节流
+fn digits_to_bytes(digits: &[usize], base: usize, lsb_first: bool) -> Option<Vec<u8>> {
+    digits
+        .chunks_exact(byte_group_size(base))
+        .map(|chunk| u8::try_from(engine::group_value(chunk, base, lsb_first)).ok())
+        .collect()
+}
+
+/// 把零宽层按原始字节解释出的所有字节流
+pub fn byte_streams(text: &str, budget: &Budget) -> Vec<ByteStream> {
+    let analysis = engine::analyze(text);
+    let zw_all = engine::extract_all(text);
+    let top: Vec<char> = engine::top_chars(&analysis).into_iter().take(MAX_SYMBOLS).collect();
+    let mut streams = Vec::new();
+    let mut push = |method: String, bytes: Vec<u8>| {
+        if bytes.len() >= 4 {
+            streams.push(ByteStream { method, bytes });
+        }
+    };
+
+    // 二进制: 高频字符两两组合
+    for &zero in &top {
+        for &one in &top {
+            if zero == one || budget.expired() {
+                continue;
+            }
+            let digits = engine::nary_digits(&zw_all, &[zero, one]);
+            for lsb_first in [false, true] {
+                if let Some(bytes) = digits_to_bytes(&digits, 2, lsb_first) {
+                    let order = if lsb_first { ", 低位在前" } else { "" };
+                    push(format!("二进制字节 (U+{:04X}=0, U+{:04X}=1{})", zero as u32, one as u32, order), bytes);
+                }
+            }
+        }
+    }
+
+    // N 进制: 按频率与按码点排列的前 n 个字符
+    for n in 3..=top.len() {
+        let mut sorted = top[..n].to_vec();
+        sorted.sort_unstable();
+        let orders = if sorted == top[..n] { vec![sorted] } else { vec![top[..n].to_vec(), sorted] };
+        for charset in orders {
+            let digits = engine::nary_digits(&zw_all, &charset);
+            for lsb_first in [false, true] {
+                if let Some(bytes) = digits_to_bytes(&digits, n, lsb_first) {
+                    let order = if lsb_first { ", 低位在前" } else { "" };
+                    push(format!("{}进制字节 ({}, 分组={}{})", n, describe_charset(&charset), byte_group_size(n), order), bytes);
+                }
+            }
+        }
+    }
+
+    // 文本解码结果是 base64 / hex 时还原为字节
+    for r in engine::auto_decode(text, budget).iter().take(10) {
+        let compact: String = r.decoded.chars().filter(|c| !c.is_whitespace()).collect();
+        if let Some(bytes) = engine::hex_to_bytes(&compact) {
+            push(format!("{} → hex", r.method), bytes);
+        } else if compact.len() >= 8 && compact.bytes().all(|b| b.is_ascii_alphanumeric() || b"+/-_=".contains(&b)) {
+            if let Ok(bytes) = ingest::decode_base64(&compact) {
+                push(format!("{} → base64", r.method), bytes);
+            }
+        }
+    }
+    streams
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+/// 按格式尾部确定文件长度；找不到时返回 None
+fn file_end(file_type: &FileType, data: &[u8]) -> Option<usize> {
+    match file_type.extension {
+        "png" => find(data, b"IEND").map(|i| (i + 8).min(data.len())),
+        "jpg" => rfind(data, b"\xff\xd9").map(|i| i + 2),
+        "gif" => rfind(data, b"\x3b").map(|i| i + 1),
+        "pdf" => rfind(data, b"%%EOF").map(|i| i + 5),
+        "zip" => {
+            // 中央目录结束记录: 22 字节 + 注释
+            let i = rfind(data, b"PK\x05\x06")?;
+            let comment = u16::from_le_bytes([*data.get(i + 20)?, *data.get(i + 21)?]) as usize;
+            Some((i + 22 + comment).min(data.len()))
+        }
+        _ => None,
+    }
+}
+
+/// 在字节流中查找已知文件头并截出文件
+pub fn carve(streams: &[ByteStream]) -> Vec<Carved> {
+    let mut seen = HashSet::new();
+    let mut carved = Vec::new();
+    for stream in streams {
+        for file_type in FILE_TYPES {
+            let Some(offset) = find(&stream.bytes, file_type.magic) else {
+                continue;
+            };
+            let data = &stream.bytes[offset..];
+            let end = file_end(file_type, data);
+            let bytes = data[..end.unwrap_or(data.len())].to_vec();
+            if !seen.insert(bytes.clone()) {
+                continue;
+            }
+            carved.push(Carved { file_type, method: stream.method.clone(), offset, bytes, complete: end.is_some() });
+            if carved.len() >= MAX_CARVED {
+                return carved;
+            }
+        }
+    }
+    // 文件头在开头、找到了文件尾的更可信
+    carved.sort_by_key(|c| (!c.complete, c.offset));
+    carved
+}
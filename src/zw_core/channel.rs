@@ -0,0 +1,147 @@
+//! 传输通道模拟 - 模拟平台对文本的各种处理，检验隐写载荷能否幸存
+
+use encoding_rs::GBK;
+use unicode_normalization::UnicodeNormalization;
+
+use super::chars::{is_unicode_tag, is_zero_width};
+use super::engine::{auto_decode, Budget};
+use super::sanitize::{is_bidi_control, is_emoji};
+
+/// 模拟的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+    /// 严格的 HTML/富文本清洗: 删除所有格式控制和默认可忽略字符
+    HtmlStrict,
+    /// 只删除 BiDi 方向控制（防 Trojan Source 的常见做法）
+    HtmlBidi,
+    /// 删除 Unicode Tags（部分 LLM 平台的防注入过滤）
+    StripTags,
+    /// 首尾修剪空白及零宽字符
+    Trim,
+    /// 转为 GBK 再转回（无法表示的字符变成 '?'）
+    Gbk,
+    /// emoji 替换为图片: 紧随 emoji 的扩展字符（VS、ZWJ、ZWNJ、Tags）随字形簇一并丢失
+    EmojiRender,
+}
+
+impl Channel {
+    pub fn all() -> &'static [Channel] {
+        &[
+            Channel::Nfc,
+            Channel::Nfd,
+            Channel::Nfkc,
+            Channel::Nfkd,
+            Channel::HtmlStrict,
+            Channel::HtmlBidi,
+            Channel::StripTags,
+            Channel::Trim,
+            Channel::Gbk,
+            Channel::EmojiRender,
+        ]
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|c| c.name() == s)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::Nfc => "nfc",
+            Channel::Nfd => "nfd",
+            Channel::Nfkc => "nfkc",
+            Channel::Nfkd => "nfkd",
+            Channel::HtmlStrict => "html_strict",
+            Channel::HtmlBidi => "html_bidi",
+            Channel::StripTags => "strip_tags",
+            Channel::Trim => "trim",
+            Channel::Gbk => "gbk",
+            Channel::EmojiRender => "emoji_render",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Channel::Nfc => "NFC 规范化",
+            Channel::Nfd => "NFD 规范化",
+            Channel::Nfkc => "NFKC 兼容规范化",
+            Channel::Nfkd => "NFKD 兼容规范化",
+            Channel::HtmlStrict => "严格 HTML 清洗（删除全部不可见字符）",
+            Channel::HtmlBidi => "删除 BiDi 控制字符",
+            Channel::StripTags => "删除 Unicode Tags",
+            Channel::Trim => "首尾修剪（含零宽字符）",
+            Channel::Gbk => "GBK 往返转码",
+            Channel::EmojiRender => "emoji 渲染为图片",
+        }
+    }
+}
+
+/// 对文本应用一种处理
+pub fn apply(channel: Channel, text: &str) -> String {
+    match channel {
+        Channel::Nfc => text.nfc().collect(),
+        Channel::Nfd => text.nfd().collect(),
+        Channel::Nfkc => text.nfkc().collect(),
+        Channel::Nfkd => text.nfkd().collect(),
+        Channel::HtmlStrict => text.chars().filter(|&c| !is_zero_width(c)).collect(),
+        Channel::HtmlBidi => text.chars().filter(|&c| !is_bidi_control(c)).collect(),
+        Channel::StripTags => text.chars().filter(|&c| !is_unicode_tag(c)).collect(),
+        Channel::Trim => text.trim_matches(|c: char| c.is_whitespace() || is_zero_width(c)).to_string(),
+        Channel::Gbk => {
+            let (bytes, _, _) = GBK.encode(text);
+            GBK.decode(&bytes).0.into_owned()
+        }
+        Channel::EmojiRender => {
+            let mut out = String::with_capacity(text.len());
+            let mut in_cluster = false;
+            for c in text.chars() {
+                if is_emoji(c) {
+                    in_cluster = true;
+                } else if in_cluster && is_grapheme_extend(c) {
+                    continue;
+                } else {
+                    in_cluster = false;
+                }
+                out.push(c);
+            }
+            out
+        }
+    }
+}
+
+/// 会被并入前一个 emoji 字形簇的不可见字符
+fn is_grapheme_extend(c: char) -> bool {
+    matches!(c as u32, 0x200C | 0x200D | 0xFE00..=0xFE0F | 0xE0020..=0xE007F | 0xE0100..=0xE01EF)
+}
+
+/// 单个通道的模拟结果
+#[derive(Debug, Clone)]
+pub struct ChannelReport {
+    pub channel: Channel,
+    pub zw_before: usize,
+    pub zw_after: usize,
+    pub survived: bool,
+    pub top: Option<String>,
+}
+
+/// 逐个通道处理文本并检查载荷是否仍能解出
+pub fn simulate(text: &str, expected: &str, channels: &[Channel]) -> Vec<ChannelReport> {
+    let zw_before = text.chars().filter(|&c| is_zero_width(c)).count();
+    channels
+        .iter()
+        .map(|&channel| {
+            let processed = apply(channel, text);
+            let results = auto_decode(&processed, &Budget::fast());
+            ChannelReport {
+                channel,
+                zw_before,
+                zw_after: processed.chars().filter(|&c| is_zero_width(c)).count(),
+                survived: results.iter().any(|r| r.decoded.contains(expected)),
+                top: results.first().map(|r| r.decoded.chars().take(40).collect()),
+            }
+        })
+        .collect()
+}
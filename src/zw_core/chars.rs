@@ -7,112 +7,308 @@ pub struct ZeroWidthChar {
     pub codepoint: u32,
     pub name: &'static str,
     pub category: &'static str,
+    /// Unicode 通用类别 (Cf / Mn / Lo / Zl ...)
+    pub general_category: &'static str,
+    /// 是否具有 Default_Ignorable_Code_Point 属性
+    pub default_ignorable: bool,
+    /// 引入该字符的 Unicode 版本
+    pub age: &'static str,
 }
 
+/// 由 build.rs 从 UCD 摘录生成的条目
+pub struct UcdChar {
+    pub codepoint: u32,
+    pub name: &'static str,
+    pub general_category: &'static str,
+    pub default_ignorable: bool,
+    pub age: &'static str,
+}
+
+include!(concat!(env!("OUT_DIR"), "/unicode_invisibles.rs"));
+
 /// Unicode Tags 范围
 pub const UNICODE_TAGS_START: u32 = 0xE0000;
 pub const UNICODE_TAGS_END: u32 = 0xE007F;
 
-/// 所有已知的零宽/不可见字符
-pub fn all_zero_width_chars() -> Vec<ZeroWidthChar> {
-    vec![
+/// 全网收集整理的分类与常用名（覆盖在 UCD 生成表之上）
+const CURATED: &[(u32, &str, &str)] = &[
         // --- 核心零宽字符 ---
-        ZeroWidthChar { ch: '\u{200B}', codepoint: 0x200B, name: "ZERO WIDTH SPACE (ZWSP)", category: "核心零宽" },
-        ZeroWidthChar { ch: '\u{200C}', codepoint: 0x200C, name: "ZERO WIDTH NON-JOINER (ZWNJ)", category: "核心零宽" },
-        ZeroWidthChar { ch: '\u{200D}', codepoint: 0x200D, name: "ZERO WIDTH JOINER (ZWJ)", category: "核心零宽" },
-        ZeroWidthChar { ch: '\u{FEFF}', codepoint: 0xFEFF, name: "ZERO WIDTH NO-BREAK SPACE / BOM", category: "核心零宽" },
-        ZeroWidthChar { ch: '\u{2060}', codepoint: 0x2060, name: "WORD JOINER (WJ)", category: "核心零宽" },
+        (0x200B, "ZERO WIDTH SPACE (ZWSP)", "核心零宽"),
+        (0x200C, "ZERO WIDTH NON-JOINER (ZWNJ)", "核心零宽"),
+        (0x200D, "ZERO WIDTH JOINER (ZWJ)", "核心零宽"),
+        (0xFEFF, "ZERO WIDTH NO-BREAK SPACE / BOM", "核心零宽"),
+        (0x2060, "WORD JOINER (WJ)", "核心零宽"),
 
         // --- 方向控制标记 ---
-        ZeroWidthChar { ch: '\u{200E}', codepoint: 0x200E, name: "LEFT-TO-RIGHT MARK (LRM)", category: "方向控制" },
-        ZeroWidthChar { ch: '\u{200F}', codepoint: 0x200F, name: "RIGHT-TO-LEFT MARK (RLM)", category: "方向控制" },
-        ZeroWidthChar { ch: '\u{202A}', codepoint: 0x202A, name: "LEFT-TO-RIGHT EMBEDDING (LRE)", category: "方向控制" },
-        ZeroWidthChar { ch: '\u{202B}', codepoint: 0x202B, name: "RIGHT-TO-LEFT EMBEDDING (RLE)", category: "方向控制" },
-        ZeroWidthChar { ch: '\u{202C}', codepoint: 0x202C, name: "POP DIRECTIONAL FORMATTING (PDF)", category: "方向控制" },
-        ZeroWidthChar { ch: '\u{202D}', codepoint: 0x202D, name: "LEFT-TO-RIGHT OVERRIDE (LRO)", category: "方向控制" },
-        ZeroWidthChar { ch: '\u{202E}', codepoint: 0x202E, name: "RIGHT-TO-LEFT OVERRIDE (RLO)", category: "方向控制" },
-        ZeroWidthChar { ch: '\u{2066}', codepoint: 0x2066, name: "LEFT-TO-RIGHT ISOLATE (LRI)", category: "方向控制" },
-        ZeroWidthChar { ch: '\u{2067}', codepoint: 0x2067, name: "RIGHT-TO-LEFT ISOLATE (RLI)", category: "方向控制" },
-        ZeroWidthChar { ch: '\u{2068}', codepoint: 0x2068, name: "FIRST STRONG ISOLATE (FSI)", category: "方向控制" },
-        ZeroWidthChar { ch: '\u{2069}', codepoint: 0x2069, name: "POP DIRECTIONAL ISOLATE (PDI)", category: "方向控制" },
+        (0x200E, "LEFT-TO-RIGHT MARK (LRM)", "方向控制"),
+        (0x200F, "RIGHT-TO-LEFT MARK (RLM)", "方向控制"),
+        (0x202A, "LEFT-TO-RIGHT EMBEDDING (LRE)", "方向控制"),
+        (0x202B, "RIGHT-TO-LEFT EMBEDDING (RLE)", "方向控制"),
+        (0x202C, "POP DIRECTIONAL FORMATTING (PDF)", "方向控制"),
+        (0x202D, "LEFT-TO-RIGHT OVERRIDE (LRO)", "方向控制"),
+        (0x202E, "RIGHT-TO-LEFT OVERRIDE (RLO)", "方向控制"),
+        (0x2066, "LEFT-TO-RIGHT ISOLATE (LRI)", "方向控制"),
+        (0x2067, "RIGHT-TO-LEFT ISOLATE (RLI)", "方向控制"),
+        (0x2068, "FIRST STRONG ISOLATE (FSI)", "方向控制"),
+        (0x2069, "POP DIRECTIONAL ISOLATE (PDI)", "方向控制"),
 
         // --- 不可见数学运算符 ---
-        ZeroWidthChar { ch: '\u{2061}', codepoint: 0x2061, name: "FUNCTION APPLICATION", category: "不可见数学" },
-        ZeroWidthChar { ch: '\u{2062}', codepoint: 0x2062, name: "INVISIBLE TIMES", category: "不可见数学" },
-        ZeroWidthChar { ch: '\u{2063}', codepoint: 0x2063, name: "INVISIBLE SEPARATOR", category: "不可见数学" },
-        ZeroWidthChar { ch: '\u{2064}', codepoint: 0x2064, name: "INVISIBLE PLUS", category: "不可见数学" },
+        (0x2061, "FUNCTION APPLICATION", "不可见数学"),
+        (0x2062, "INVISIBLE TIMES", "不可见数学"),
+        (0x2063, "INVISIBLE SEPARATOR", "不可见数学"),
+        (0x2064, "INVISIBLE PLUS", "不可见数学"),
 
         // --- 蒙古文元音分隔符 ---
-        ZeroWidthChar { ch: '\u{180E}', codepoint: 0x180E, name: "MONGOLIAN VOWEL SEPARATOR", category: "蒙古文" },
+        (0x180E, "MONGOLIAN VOWEL SEPARATOR", "蒙古文"),
 
         // --- 其他不可见/格式字符 ---
-        ZeroWidthChar { ch: '\u{00AD}', codepoint: 0x00AD, name: "SOFT HYPHEN (SHY)", category: "格式字符" },
-        ZeroWidthChar { ch: '\u{034F}', codepoint: 0x034F, name: "COMBINING GRAPHEME JOINER (CGJ)", category: "格式字符" },
-        ZeroWidthChar { ch: '\u{061C}', codepoint: 0x061C, name: "ARABIC LETTER MARK (ALM)", category: "格式字符" },
-        ZeroWidthChar { ch: '\u{115F}', codepoint: 0x115F, name: "HANGUL CHOSEONG FILLER", category: "格式字符" },
-        ZeroWidthChar { ch: '\u{1160}', codepoint: 0x1160, name: "HANGUL JUNGSEONG FILLER", category: "格式字符" },
-        ZeroWidthChar { ch: '\u{17B4}', codepoint: 0x17B4, name: "KHMER VOWEL INHERENT AQ", category: "格式字符" },
-        ZeroWidthChar { ch: '\u{17B5}', codepoint: 0x17B5, name: "KHMER VOWEL INHERENT AA", category: "格式字符" },
-        ZeroWidthChar { ch: '\u{3164}', codepoint: 0x3164, name: "HANGUL FILLER", category: "格式字符" },
-        ZeroWidthChar { ch: '\u{FFA0}', codepoint: 0xFFA0, name: "HALFWIDTH HANGUL FILLER", category: "格式字符" },
+        (0x00AD, "SOFT HYPHEN (SHY)", "格式字符"),
+        (0x034F, "COMBINING GRAPHEME JOINER (CGJ)", "格式字符"),
+        (0x061C, "ARABIC LETTER MARK (ALM)", "格式字符"),
+        (0x115F, "HANGUL CHOSEONG FILLER", "格式字符"),
+        (0x1160, "HANGUL JUNGSEONG FILLER", "格式字符"),
+        (0x17B4, "KHMER VOWEL INHERENT AQ", "格式字符"),
+        (0x17B5, "KHMER VOWEL INHERENT AA", "格式字符"),
+        (0x3164, "HANGUL FILLER", "格式字符"),
+        (0xFFA0, "HALFWIDTH HANGUL FILLER", "格式字符"),
 
         // --- 变体选择器 ---
-        ZeroWidthChar { ch: '\u{FE00}', codepoint: 0xFE00, name: "VARIATION SELECTOR-1", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE01}', codepoint: 0xFE01, name: "VARIATION SELECTOR-2", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE02}', codepoint: 0xFE02, name: "VARIATION SELECTOR-3", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE03}', codepoint: 0xFE03, name: "VARIATION SELECTOR-4", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE04}', codepoint: 0xFE04, name: "VARIATION SELECTOR-5", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE05}', codepoint: 0xFE05, name: "VARIATION SELECTOR-6", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE06}', codepoint: 0xFE06, name: "VARIATION SELECTOR-7", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE07}', codepoint: 0xFE07, name: "VARIATION SELECTOR-8", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE08}', codepoint: 0xFE08, name: "VARIATION SELECTOR-9", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE09}', codepoint: 0xFE09, name: "VARIATION SELECTOR-10", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE0A}', codepoint: 0xFE0A, name: "VARIATION SELECTOR-11", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE0B}', codepoint: 0xFE0B, name: "VARIATION SELECTOR-12", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE0C}', codepoint: 0xFE0C, name: "VARIATION SELECTOR-13", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE0D}', codepoint: 0xFE0D, name: "VARIATION SELECTOR-14", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE0E}', codepoint: 0xFE0E, name: "VARIATION SELECTOR-15", category: "变体选择器" },
-        ZeroWidthChar { ch: '\u{FE0F}', codepoint: 0xFE0F, name: "VARIATION SELECTOR-16", category: "变体选择器" },
+        (0xFE00, "VARIATION SELECTOR-1", "变体选择器"),
+        (0xFE01, "VARIATION SELECTOR-2", "变体选择器"),
+        (0xFE02, "VARIATION SELECTOR-3", "变体选择器"),
+        (0xFE03, "VARIATION SELECTOR-4", "变体选择器"),
+        (0xFE04, "VARIATION SELECTOR-5", "变体选择器"),
+        (0xFE05, "VARIATION SELECTOR-6", "变体选择器"),
+        (0xFE06, "VARIATION SELECTOR-7", "变体选择器"),
+        (0xFE07, "VARIATION SELECTOR-8", "变体选择器"),
+        (0xFE08, "VARIATION SELECTOR-9", "变体选择器"),
+        (0xFE09, "VARIATION SELECTOR-10", "变体选择器"),
+        (0xFE0A, "VARIATION SELECTOR-11", "变体选择器"),
+        (0xFE0B, "VARIATION SELECTOR-12", "变体选择器"),
+        (0xFE0C, "VARIATION SELECTOR-13", "变体选择器"),
+        (0xFE0D, "VARIATION SELECTOR-14", "变体选择器"),
+        (0xFE0E, "VARIATION SELECTOR-15", "变体选择器"),
+        (0xFE0F, "VARIATION SELECTOR-16", "变体选择器"),
 
         // --- 已弃用的格式字符 ---
-        ZeroWidthChar { ch: '\u{206A}', codepoint: 0x206A, name: "INHIBIT SYMMETRIC SWAPPING", category: "已弃用格式" },
-        ZeroWidthChar { ch: '\u{206B}', codepoint: 0x206B, name: "ACTIVATE SYMMETRIC SWAPPING", category: "已弃用格式" },
-        ZeroWidthChar { ch: '\u{206C}', codepoint: 0x206C, name: "INHIBIT ARABIC FORM SHAPING", category: "已弃用格式" },
-        ZeroWidthChar { ch: '\u{206D}', codepoint: 0x206D, name: "ACTIVATE ARABIC FORM SHAPING", category: "已弃用格式" },
-        ZeroWidthChar { ch: '\u{206E}', codepoint: 0x206E, name: "NATIONAL DIGIT SHAPES", category: "已弃用格式" },
-        ZeroWidthChar { ch: '\u{206F}', codepoint: 0x206F, name: "NOMINAL DIGIT SHAPES", category: "已弃用格式" },
+        (0x206A, "INHIBIT SYMMETRIC SWAPPING", "已弃用格式"),
+        (0x206B, "ACTIVATE SYMMETRIC SWAPPING", "已弃用格式"),
+        (0x206C, "INHIBIT ARABIC FORM SHAPING", "已弃用格式"),
+        (0x206D, "ACTIVATE ARABIC FORM SHAPING", "已弃用格式"),
+        (0x206E, "NATIONAL DIGIT SHAPES", "已弃用格式"),
+        (0x206F, "NOMINAL DIGIT SHAPES", "已弃用格式"),
 
         // --- 行/段落分隔符 ---
-        ZeroWidthChar { ch: '\u{2028}', codepoint: 0x2028, name: "LINE SEPARATOR", category: "分隔符" },
-        ZeroWidthChar { ch: '\u{2029}', codepoint: 0x2029, name: "PARAGRAPH SEPARATOR", category: "分隔符" },
-    ]
+        (0x2028, "LINE SEPARATOR", "分隔符"),
+        (0x2029, "PARAGRAPH SEPARATOR", "分隔符"),
+];
+
+/// 未经人工整理的 UCD 条目按名称/属性归类
+fn derived_category(u: &UcdChar) -> &'static str {
+    if u.name.starts_with("VARIATION SELECTOR") {
+        "变体选择器"
+    } else if u.name.starts_with("MONGOLIAN") {
+        "蒙古文"
+    } else if u.default_ignorable {
+        "其他默认可忽略"
+    } else {
+        "其他格式控制"
+    }
+}
+
+/// 所有已知的零宽/不可见字符（按人工分类分组，其余 UCD 条目按码点追加）
+pub fn all_zero_width_chars() -> Vec<ZeroWidthChar> {
+    let ucd = |cp: u32| UCD_INVISIBLES.iter().find(|u| u.codepoint == cp);
+    let mut out: Vec<ZeroWidthChar> = CURATED
+        .iter()
+        .filter_map(|&(cp, name, category)| {
+            let u = ucd(cp)?;
+            Some(ZeroWidthChar {
+                ch: char::from_u32(cp)?,
+                codepoint: cp,
+                name,
+                category,
+                general_category: u.general_category,
+                default_ignorable: u.default_ignorable,
+                age: u.age,
+            })
+        })
+        .collect();
+    let mut rest: Vec<ZeroWidthChar> = UCD_INVISIBLES
+        .iter()
+        .filter(|u| !CURATED.iter().any(|c| c.0 == u.codepoint))
+        .filter_map(|u| {
+            Some(ZeroWidthChar {
+                ch: char::from_u32(u.codepoint)?,
+                codepoint: u.codepoint,
+                name: u.name,
+                category: derived_category(u),
+                general_category: u.general_category,
+                default_ignorable: u.default_ignorable,
+                age: u.age,
+            })
+        })
+        .collect();
+    rest.sort_by_key(|z| z.codepoint);
+    out.extend(rest);
+    // 补充条目并入同名人工分类之后，新分类排在最后
+    let rank = |cat: &str| CURATED.iter().position(|c| c.2 == cat).unwrap_or(usize::MAX);
+    out.sort_by_key(|z| (rank(z.category), z.category));
+    out
 }
 
 /// 判断字符是否是已知的零宽/不可见字符
 pub fn is_zero_width(ch: char) -> bool {
     let cp = ch as u32;
-    matches!(cp,
-        0x200B..=0x200F |
-        0x202A..=0x202E |
-        0x2060..=0x2064 |
-        0x2066..=0x2069 |
-        0xFEFF |
-        0x180E |
-        0x00AD |
-        0x034F |
-        0x061C |
-        0x115F..=0x1160 |
-        0x17B4..=0x17B5 |
-        0x3164 |
-        0xFFA0 |
-        0xFE00..=0xFE0F |
-        0x206A..=0x206F |
-        0x2028..=0x2029
-    ) || is_unicode_tag(ch)
+    if cp < 0xAD {
+        return false;
+    }
+    is_unicode_tag(ch) || UCD_INVISIBLES.binary_search_by_key(&cp, |u| u.codepoint).is_ok()
 }
 
 /// 判断字符是否是 Unicode Tag
 pub fn is_unicode_tag(ch: char) -> bool {
     let cp = ch as u32;
-    cp >= UNICODE_TAGS_START && cp <= UNICODE_TAGS_END
+    (UNICODE_TAGS_START..=UNICODE_TAGS_END).contains(&cp)
+}
+
+/// 解析码点表示: "U+200B" / "u+200b" / "0x200B" / "200B" / 单个字符
+pub fn parse_codepoint(s: &str) -> Option<u32> {
+    let s = s.trim();
+    let mut it = s.chars();
+    if let (Some(c), None) = (it.next(), it.next()) {
+        if !c.is_ascii_hexdigit() {
+            return Some(c as u32);
+        }
+    }
+    let hex = s
+        .strip_prefix("U+")
+        .or_else(|| s.strip_prefix("u+"))
+        .or_else(|| s.strip_prefix("0x"))
+        .or_else(|| s.strip_prefix("0X"))
+        .or_else(|| s.strip_prefix("\\u"))
+        .unwrap_or(s);
+    let hex = hex.trim_start_matches('{').trim_end_matches('}');
+    u32::from_str_radix(hex, 16).ok().filter(|&cp| char::from_u32(cp).is_some())
+}
+
+/// 按分类名取出该分类下的所有码点（"Unicode Tags" 返回整个 Tag 区段）
+pub fn codepoints_in_category(category: &str) -> Vec<u32> {
+    if category.eq_ignore_ascii_case("unicode tags") || category.eq_ignore_ascii_case("tags") {
+        return (UNICODE_TAGS_START..=UNICODE_TAGS_END).collect();
+    }
+    all_zero_width_chars()
+        .into_iter()
+        .filter(|z| z.category == category)
+        .map(|z| z.codepoint)
+        .collect()
+}
+
+/// 解析码点区间: "U+2000-U+206F" / "200B..200F" / 单个码点
+pub fn parse_codepoint_range(s: &str) -> Option<(u32, u32)> {
+    let s = s.trim();
+    let (lo, hi) = match s.split_once("..") {
+        Some((a, b)) => (a, b.trim_start_matches('=')),
+        None => match s.split_once('-') {
+            Some((a, b)) => (a, b),
+            None => (s, s),
+        },
+    };
+    let (lo, hi) = (parse_codepoint(lo)?, parse_codepoint(hi)?);
+    Some((lo.min(hi), lo.max(hi)))
+}
+
+/// Unicode Tag 字符的名称（U+E0001 或 U+E0020..U+E007F）
+pub fn tag_name(cp: u32) -> String {
+    match cp {
+        0xE0001 => "LANGUAGE TAG".to_string(),
+        0xE007F => "CANCEL TAG".to_string(),
+        0xE0020 => "TAG SPACE".to_string(),
+        _ => format!("TAG '{}'", char::from((cp - UNICODE_TAGS_START) as u8)),
+    }
+}
+
+/// 取零宽/不可见字符的名称（人工整理的常用名优先），非零宽字符返回 None
+pub fn name_of(ch: char) -> Option<String> {
+    let cp = ch as u32;
+    if is_unicode_tag(ch) {
+        return Some(tag_name(cp));
+    }
+    if let Some(&(_, name, _)) = CURATED.iter().find(|c| c.0 == cp) {
+        return Some(name.to_string());
+    }
+    UCD_INVISIBLES
+        .binary_search_by_key(&cp, |u| u.codepoint)
+        .ok()
+        .map(|i| UCD_INVISIBLES[i].name.to_string())
+}
+
+/// 取零宽/不可见字符所属分类（Unicode Tags 单独成类），非零宽字符返回 None
+pub fn category_of(ch: char) -> Option<&'static str> {
+    if is_unicode_tag(ch) {
+        return Some("Unicode Tags");
+    }
+    let cp = ch as u32;
+    if let Some(&(_, _, cat)) = CURATED.iter().find(|c| c.0 == cp) {
+        return Some(cat);
+    }
+    UCD_INVISIBLES
+        .binary_search_by_key(&cp, |u| u.codepoint)
+        .ok()
+        .map(|i| derived_category(&UCD_INVISIBLES[i]))
+}
+
+/// 字符表完整性检查，返回发现的问题（为空表示正常）
+///
+/// `is_zero_width` 等查询依赖生成表按码点严格递增，人工整理的条目也必须在生成表中，否则会被静默丢弃。
+pub fn table_problems() -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(w) = UCD_INVISIBLES.windows(2).find(|w| w[0].codepoint >= w[1].codepoint) {
+        problems.push(format!("生成表未按码点递增: U+{:04X} 之后是 U+{:04X}", w[0].codepoint, w[1].codepoint));
+    }
+    for &(cp, name, _) in CURATED {
+        if !UCD_INVISIBLES.iter().any(|u| u.codepoint == cp) {
+            problems.push(format!("人工整理的 U+{:04X} {} 不在生成表中", cp, name));
+        }
+    }
+    let all = all_zero_width_chars();
+    let mut seen = std::collections::HashSet::new();
+    for z in &all {
+        if !seen.insert(z.codepoint) {
+            problems.push(format!("U+{:04X} 重复出现", z.codepoint));
+        }
+        if z.name.is_empty() || z.category.is_empty() {
+            problems.push(format!("U+{:04X} 缺少名称或分类", z.codepoint));
+        }
+        if !is_zero_width(z.ch) {
+            problems.push(format!("U+{:04X} 在字符表中但 is_zero_width 判定为否", z.codepoint));
+        }
+    }
+    problems
+}
+
+/// 全部分类名（按列表顺序，含 Unicode Tags）
+pub fn category_names() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = Vec::new();
+    for z in all_zero_width_chars() {
+        if !names.contains(&z.category) {
+            names.push(z.category);
+        }
+    }
+    names.push("Unicode Tags");
+    names
+}
+
+/// 判断字符是否属于阿拉伯字母书写系统（阿拉伯语、波斯语、乌尔都语等）
+pub fn is_arabic_script(ch: char) -> bool {
+    matches!(ch as u32,
+        0x0620..=0x064A |
+        0x066E..=0x06D3 |
+        0x06D5 |
+        0x06EE..=0x06FF |
+        0x0750..=0x077F |
+        0x08A0..=0x08C9 |
+        0xFB50..=0xFDFF |
+        0xFE70..=0xFEFC
+    )
 }
@@ -0,0 +1,64 @@
+//! 合规检查 - 按策略找出不允许出现的不可见字符（CLI check 子命令使用）
+
+use super::chars::{category_of, is_zero_width};
+use super::sanitize::is_legit_zwnj;
+
+/// 允许出现的不可见字符
+#[derive(Debug, Clone)]
+pub struct Policy {
+    /// 允许的分类（如 变体选择器）
+    pub allowed_categories: Vec<&'static str>,
+    /// 额外允许的码点
+    pub allowed_codepoints: Vec<u32>,
+    /// 允许文件开头的 BOM
+    pub allow_bom: bool,
+    /// 允许阿拉伯字母之间正字法用途的 ZWNJ
+    pub allow_legit_zwnj: bool,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            allowed_categories: Vec::new(),
+            allowed_codepoints: Vec::new(),
+            allow_bom: true,
+            allow_legit_zwnj: true,
+        }
+    }
+}
+
+/// 一处违规
+#[derive(Debug, Clone)]
+pub struct Violation {
+    /// 从 1 开始
+    pub line: usize,
+    /// 从 1 开始，按字符计
+    pub column: usize,
+    pub codepoint: u32,
+}
+
+/// 找出文本中策略不允许的不可见字符
+pub fn check_text(text: &str, policy: &Policy) -> Vec<Violation> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = Vec::new();
+    let mut line = 1usize;
+    let mut column = 1usize;
+    for (i, &ch) in chars.iter().enumerate() {
+        if is_zero_width(ch) {
+            let allowed = (policy.allow_bom && i == 0 && ch == '\u{FEFF}')
+                || (policy.allow_legit_zwnj && is_legit_zwnj(&chars, i))
+                || policy.allowed_codepoints.contains(&(ch as u32))
+                || category_of(ch).is_some_and(|c| policy.allowed_categories.contains(&c));
+            if !allowed {
+                out.push(Violation { line, column, codepoint: ch as u32 });
+            }
+        }
+        if ch == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    out
+}
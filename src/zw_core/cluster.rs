@@ -0,0 +1,211 @@
+//! 字符集推断 - 按相邻/共现关系把零宽字符分成载荷字符集、噪声与分隔符
+//!
+//! 出现的零宽字符种类较多（超过 6 种）时，按频率取前几个做暴力组合很容易漏掉真正的字符集，
+//! 穷举所有子集又不可行。这里根据每个字符的出现方式分类：
+//!
+//! - 噪声：几乎总是单独出现在可见字符之间（而其他字符成段出现），如排版残留的 ZWSP
+//! - 分隔符：从不与自身相邻、在载荷流中按固定间隔出现，或只出现在段首/段尾（起止标记）
+//! - 载荷：其余字符；按段内相邻关系再拆成互不相邻的连通分量（多个载荷使用不同字符集）
+//!
+//! 解码时只在这些划分上暴力尝试。
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::engine::extract_segments;
+
+/// 超过这么多种零宽字符时才做聚类
+pub const MIN_SYMBOLS: usize = 7;
+/// 单个候选字符集的最大大小
+const MAX_CHARSET: usize = 16;
+
+/// 单个字符的出现特征
+#[derive(Debug, Clone, Default)]
+struct SymbolStats {
+    count: usize,
+    /// 出现在长度为 1 的段中的次数
+    isolated: usize,
+    /// 出现在段首或段尾（段长 ≥ 2）的次数
+    boundary: usize,
+    /// 在去掉噪声后的字符流中的位置
+    positions: Vec<usize>,
+}
+
+/// 聚类结果
+#[derive(Debug, Clone, Default)]
+pub struct Clustering {
+    /// 载荷字符（按出现次数降序）
+    pub payload: Vec<char>,
+    pub delimiters: Vec<char>,
+    pub noise: Vec<char>,
+    /// 载荷字符按段内相邻关系划分的连通分量（只有一个分量时与 payload 相同）
+    pub groups: Vec<Vec<char>>,
+}
+
+fn codepoint(ch: char) -> String {
+    format!("U+{:04X}", ch as u32)
+}
+
+fn join(chars: &[char]) -> String {
+    chars.iter().map(|&c| codepoint(c)).collect::<Vec<_>>().join("+")
+}
+
+/// 按出现方式对文本中的零宽字符分类
+pub fn cluster(text: &str) -> Clustering {
+    let segments: Vec<Vec<char>> = extract_segments(text).into_iter().map(|s| s.chars().collect()).collect();
+    let mut stats: BTreeMap<char, SymbolStats> = BTreeMap::new();
+    for seg in &segments {
+        for (i, &c) in seg.iter().enumerate() {
+            let s = stats.entry(c).or_default();
+            s.count += 1;
+            if seg.len() == 1 {
+                s.isolated += 1;
+            } else if i == 0 || i == seg.len() - 1 {
+                s.boundary += 1;
+            }
+        }
+    }
+    if stats.is_empty() {
+        return Clustering::default();
+    }
+
+    // 噪声: 自身几乎总是单独出现，而另有字符主要成段出现（载荷被逐个分散插入时单独出现不说明问题）
+    let segmented: usize = stats.values().filter(|s| (s.isolated as f64) < s.count as f64 * 0.3).map(|s| s.count).sum();
+    let mut noise: Vec<char> = if segmented >= 8 {
+        stats.iter().filter(|(_, s)| s.isolated as f64 >= s.count as f64 * 0.7).map(|(&c, _)| c).collect()
+    } else {
+        Vec::new()
+    };
+
+    // 去掉噪声后的字符流，记录位置与段内相邻关系
+    let mut adjacency: HashMap<(char, char), usize> = HashMap::new();
+    let mut pos = 0;
+    for seg in &segments {
+        let kept: Vec<char> = seg.iter().copied().filter(|c| !noise.contains(c)).collect();
+        for &c in &kept {
+            if let Some(s) = stats.get_mut(&c) {
+                s.positions.push(pos);
+            }
+            pos += 1;
+        }
+        for w in kept.windows(2) {
+            *adjacency.entry((w[0], w[1])).or_default() += 1;
+        }
+    }
+
+    let payload_total: usize = stats.iter().filter(|(c, _)| !noise.contains(c)).map(|(_, s)| s.count).sum();
+    let mut delimiters: Vec<char> = stats
+        .iter()
+        .filter(|(c, s)| !noise.contains(c) && is_delimiter(**c, s, &adjacency, payload_total))
+        .map(|(&c, _)| c)
+        .collect();
+
+    let mut payload: Vec<char> = stats.keys().copied().filter(|c| !noise.contains(c) && !delimiters.contains(c)).collect();
+    if payload.len() < 2 {
+        // 划分过头时退回到只去掉噪声
+        payload.append(&mut delimiters);
+    }
+    if payload.len() < 2 {
+        payload.append(&mut noise);
+    }
+    let by_count = |chars: &mut Vec<char>| chars.sort_by_key(|c| (std::cmp::Reverse(stats[c].count), *c));
+    by_count(&mut payload);
+    by_count(&mut delimiters);
+    by_count(&mut noise);
+
+    let mut groups = components(&payload, &adjacency);
+    groups.retain(|g| g.len() >= 2);
+    for g in &mut groups {
+        by_count(g);
+    }
+    Clustering { payload, delimiters, noise, groups }
+}
+
+/// 分隔符/起止标记：从不与自身相邻，且在字符流中按固定间隔出现或只出现在段首段尾
+fn is_delimiter(c: char, s: &SymbolStats, adjacency: &HashMap<(char, char), usize>, payload_total: usize) -> bool {
+    if s.count < 2 || adjacency.contains_key(&(c, c)) || s.count * 3 > payload_total {
+        return false;
+    }
+    if s.isolated == 0 && s.boundary == s.count {
+        return true;
+    }
+    let gaps: Vec<usize> = s.positions.windows(2).map(|w| w[1] - w[0]).collect();
+    let mut freq: HashMap<usize, usize> = HashMap::new();
+    for &g in &gaps {
+        *freq.entry(g).or_default() += 1;
+    }
+    freq.iter().any(|(&gap, &n)| gap >= 3 && n as f64 >= gaps.len() as f64 * 0.8)
+}
+
+/// 载荷字符按段内相邻关系的连通分量（相邻至少 2 次才算连通，避免偶然相邻把两个载荷连起来）
+fn components(payload: &[char], adjacency: &HashMap<(char, char), usize>) -> Vec<Vec<char>> {
+    let linked = |a: char, b: char| {
+        adjacency.get(&(a, b)).copied().unwrap_or(0) + adjacency.get(&(b, a)).copied().unwrap_or(0) >= 2
+    };
+    let mut groups: Vec<Vec<char>> = Vec::new();
+    let mut assigned = vec![false; payload.len()];
+    for start in 0..payload.len() {
+        if assigned[start] {
+            continue;
+        }
+        assigned[start] = true;
+        let mut group = vec![payload[start]];
+        let mut i = 0;
+        while i < group.len() {
+            for (j, &c) in payload.iter().enumerate() {
+                if !assigned[j] && linked(group[i], c) {
+                    assigned[j] = true;
+                    group.push(c);
+                }
+            }
+            i += 1;
+        }
+        groups.push(group);
+    }
+    groups
+}
+
+impl Clustering {
+    /// 值得暴力尝试的字符集（去重，不超过 16 个字符）：载荷、各连通分量，再加上分隔符的载荷
+    /// （被判为分隔符的也可能是载荷的一个数字）；每个字符集另给一份按码点排序的版本，
+    /// 因为 N 进制解码按字符集顺序映射数字，而多数工具按码点顺序排列字母表
+    pub fn candidate_charsets(&self) -> Vec<Vec<char>> {
+        let mut sets: Vec<Vec<char>> = Vec::new();
+        let mut push = |set: Vec<char>| {
+            let mut sorted = set.clone();
+            sorted.sort_unstable();
+            for set in [set, sorted] {
+                if (2..=MAX_CHARSET).contains(&set.len()) && !sets.contains(&set) {
+                    sets.push(set);
+                }
+            }
+        };
+        push(self.payload.clone());
+        if self.groups.len() > 1 {
+            for g in &self.groups {
+                push(g.clone());
+            }
+        }
+        if !self.delimiters.is_empty() {
+            push(self.payload.iter().chain(&self.delimiters).copied().collect());
+        }
+        sets
+    }
+
+    /// 供分析报告展示的摘要
+    pub fn describe(&self) -> String {
+        let mut out = String::from("字符集推断（按共现关系聚类）:\n");
+        out.push_str(&format!("  载荷字符集: {}\n", join(&self.payload)));
+        if self.groups.len() > 1 {
+            for (i, g) in self.groups.iter().enumerate() {
+                out.push_str(&format!("    分量 {}: {}\n", i + 1, join(g)));
+            }
+        }
+        if !self.delimiters.is_empty() {
+            out.push_str(&format!("  分隔符/起止标记: {}\n", join(&self.delimiters)));
+        }
+        if !self.noise.is_empty() {
+            out.push_str(&format!("  噪声（单独散布）: {}\n", join(&self.noise)));
+        }
+        out
+    }
+}
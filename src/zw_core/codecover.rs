@@ -0,0 +1,301 @@
+//! 源代码载体 - 轻量分词，只把载荷放进注释和字符串字面量
+//!
+//! 不做完整语法分析，只按语言族识别注释与字符串的边界，保证载荷不会落在
+//! 标识符、关键字或运算符之间，嵌入后的文件仍能正常编译/运行。
+
+use std::path::Path;
+
+/// 语言族（按注释与字符串语法划分）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    /// `//` `/* */` 注释，`"` 字符串，`'` 字符字面量（C/C++/Java/Rust/Go/C#…）
+    CLike,
+    /// 同 CLike，但 `'` 与反引号也是字符串（JavaScript/TypeScript）
+    JavaScript,
+    /// `#` 注释，`'` `"` 与三引号字符串
+    Python,
+    /// `#` 注释（行首或空白之后），`'` `"` 字符串（Shell/Ruby/Perl/YAML/TOML…）
+    Shell,
+    /// `--` `/* */` 注释，`'` 字符串（`''` 转义）
+    Sql,
+    /// 仅 `<!-- -->` 注释（HTML/XML/SVG/Markdown）
+    Html,
+}
+
+impl Language {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "c_like" => Some(Language::CLike),
+            "javascript" => Some(Language::JavaScript),
+            "python" => Some(Language::Python),
+            "shell" => Some(Language::Shell),
+            "sql" => Some(Language::Sql),
+            "html" => Some(Language::Html),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::CLike => "c_like",
+            Language::JavaScript => "javascript",
+            Language::Python => "python",
+            Language::Shell => "shell",
+            Language::Sql => "sql",
+            Language::Html => "html",
+        }
+    }
+
+    /// 按文件扩展名推断语言族
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        Some(match ext.as_str() {
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "java" | "rs" | "go" | "cs" | "kt" | "kts"
+            | "swift" | "scala" | "dart" | "css" | "scss" | "less" | "php" => Language::CLike,
+            "js" | "mjs" | "cjs" | "jsx" | "ts" | "tsx" | "vue" => Language::JavaScript,
+            "py" | "pyw" | "pyi" => Language::Python,
+            "sh" | "bash" | "zsh" | "rb" | "pl" | "pm" | "r" | "yaml" | "yml" | "toml" | "conf" | "ini" => {
+                Language::Shell
+            }
+            "sql" => Language::Sql,
+            "html" | "htm" | "xml" | "svg" | "md" | "xhtml" => Language::Html,
+            _ => return None,
+        })
+    }
+}
+
+/// 可安全插入的区域类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind {
+    Comment,
+    Str,
+}
+
+/// 注释或字符串的内容区间（字符下标，不含定界符）
+#[derive(Debug, Clone)]
+pub struct Region {
+    pub start: usize,
+    pub end: usize,
+    pub kind: RegionKind,
+}
+
+fn starts_with(chars: &[char], i: usize, pat: &str) -> bool {
+    pat.chars().enumerate().all(|(k, p)| chars.get(i + k) == Some(&p))
+}
+
+/// 从 i 开始查找 pat，返回其起始下标
+fn find_from(chars: &[char], i: usize, pat: &str) -> Option<usize> {
+    (i..chars.len()).find(|&k| starts_with(chars, k, pat))
+}
+
+fn line_end(chars: &[char], i: usize) -> usize {
+    (i..chars.len()).find(|&k| chars[k] == '\n').unwrap_or(chars.len())
+}
+
+/// 扫描以 quote 结尾的字符串；返回 (内容结束, 下一个扫描位置)
+fn scan_string(chars: &[char], start: usize, quote: &str, escapes: bool, multiline: bool) -> (usize, usize) {
+    let mut k = start;
+    while k < chars.len() {
+        if escapes && chars[k] == '\\' {
+            k += 2;
+            continue;
+        }
+        if !multiline && chars[k] == '\n' {
+            // 未闭合的字符串: 止于行尾，且不作为可插入区域
+            return (start, k);
+        }
+        if starts_with(chars, k, quote) {
+            return (k, k + quote.chars().count());
+        }
+        k += 1;
+    }
+    (start, chars.len())
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// 识别文本中的注释与字符串字面量
+pub fn scan_regions(chars: &[char], lang: Language) -> Vec<Region> {
+    let mut regions = Vec::new();
+    let mut push = |start: usize, end: usize, kind: RegionKind| {
+        if end > start {
+            regions.push(Region { start, end, kind });
+        }
+    };
+    let n = chars.len();
+    let mut i = 0;
+    while i < n {
+        let c = chars[i];
+        let prev = if i > 0 { Some(chars[i - 1]) } else { None };
+        match lang {
+            Language::CLike | Language::JavaScript => {
+                if starts_with(chars, i, "//") {
+                    let end = line_end(chars, i + 2);
+                    push(i + 2, end, RegionKind::Comment);
+                    i = end;
+                } else if starts_with(chars, i, "/*") {
+                    let end = find_from(chars, i + 2, "*/").unwrap_or(n);
+                    push(i + 2, end, RegionKind::Comment);
+                    i = (end + 2).min(n);
+                } else if lang == Language::CLike && c == 'r' && !prev.is_some_and(is_ident) && raw_string_hashes(chars, i + 1).is_some() {
+                    // Rust 原始字符串 r"..." / r#"..."#
+                    let hashes = raw_string_hashes(chars, i + 1).unwrap_or(0);
+                    let open = i + 1 + hashes + 1;
+                    let close: String = std::iter::once('"').chain(std::iter::repeat_n('#', hashes)).collect();
+                    let (end, next) = scan_string(chars, open, &close, false, true);
+                    push(open, end, RegionKind::Str);
+                    i = next;
+                } else if c == '"' || (lang == Language::JavaScript && (c == '\'' || c == '`')) {
+                    let quote = c.to_string();
+                    let (end, next) = scan_string(chars, i + 1, &quote, true, c == '`');
+                    push(i + 1, end, RegionKind::Str);
+                    i = next;
+                } else if c == '\'' {
+                    // 字符字面量 'a' / '\n'；其余（如 Rust 生命周期 'a）只跳过引号
+                    if chars.get(i + 1) == Some(&'\\') {
+                        i = find_from(chars, i + 2, "'").map(|k| k + 1).unwrap_or(n);
+                    } else if chars.get(i + 2) == Some(&'\'') {
+                        i += 3;
+                    } else {
+                        i += 1;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            Language::Python | Language::Shell => {
+                let comment_ok = lang == Language::Python || prev.is_none_or(char::is_whitespace);
+                if c == '#' && comment_ok {
+                    let end = line_end(chars, i + 1);
+                    push(i + 1, end, RegionKind::Comment);
+                    i = end;
+                } else if lang == Language::Python && (starts_with(chars, i, "\"\"\"") || starts_with(chars, i, "'''")) {
+                    let quote: String = chars[i..i + 3].iter().collect();
+                    let (end, next) = scan_string(chars, i + 3, &quote, true, true);
+                    push(i + 3, end, RegionKind::Str);
+                    i = next;
+                } else if c == '"' || c == '\'' {
+                    let escapes = lang == Language::Python || c == '"';
+                    let (end, next) = scan_string(chars, i + 1, &c.to_string(), escapes, lang == Language::Shell);
+                    push(i + 1, end, RegionKind::Str);
+                    i = next;
+                } else {
+                    i += 1;
+                }
+            }
+            Language::Sql => {
+                if starts_with(chars, i, "--") {
+                    let end = line_end(chars, i + 2);
+                    push(i + 2, end, RegionKind::Comment);
+                    i = end;
+                } else if starts_with(chars, i, "/*") {
+                    let end = find_from(chars, i + 2, "*/").unwrap_or(n);
+                    push(i + 2, end, RegionKind::Comment);
+                    i = (end + 2).min(n);
+                } else if c == '\'' {
+                    // '' 是转义的单引号
+                    let mut k = i + 1;
+                    while k < n && !(chars[k] == '\'' && chars.get(k + 1) != Some(&'\'')) {
+                        k += if chars[k] == '\'' { 2 } else { 1 };
+                    }
+                    push(i + 1, k.min(n), RegionKind::Str);
+                    i = k + 1;
+                } else {
+                    i += 1;
+                }
+            }
+            Language::Html => {
+                if starts_with(chars, i, "<!--") {
+                    let end = find_from(chars, i + 4, "-->").unwrap_or(n);
+                    push(i + 4, end, RegionKind::Comment);
+                    i = (end + 3).min(n);
+                } else {
+                    i += 1;
+                }
+            }
+        }
+    }
+    regions
+}
+
+/// `r` 之后是否是原始字符串开头（若干 `#` + `"`），返回 `#` 个数
+fn raw_string_hashes(chars: &[char], i: usize) -> Option<usize> {
+    let hashes = chars[i.min(chars.len())..].iter().take_while(|&&c| c == '#').count();
+    (chars.get(i + hashes) == Some(&'"')).then_some(hashes)
+}
+
+/// 不适合插入的注释: shebang、Python 编码声明
+fn is_directive(chars: &[char], region: &Region) -> bool {
+    let content: String = chars[region.start..region.end].iter().collect();
+    content.starts_with('!') || content.contains("coding:") || content.contains("coding=")
+}
+
+/// 区域内的插入点: 注释放在内容中点，字符串放在开引号之后（避开转义序列与插值表达式）
+fn insertion_point(chars: &[char], region: &Region) -> usize {
+    match region.kind {
+        RegionKind::Str => region.start,
+        RegionKind::Comment => {
+            let mut pos = region.start + (region.end - region.start) / 2;
+            // 尽量落在词与词之间，不把单词拆开
+            while pos < region.end && is_ident(chars[pos]) && pos > region.start && is_ident(chars[pos - 1]) {
+                pos += 1;
+            }
+            pos
+        }
+    }
+}
+
+/// 代码载体嵌入结果
+#[derive(Debug, Clone)]
+pub struct CodePlacement {
+    pub text: String,
+    pub comments: usize,
+    pub strings: usize,
+}
+
+/// 把载荷切成若干段，依次放入注释（include_strings 时也放入字符串字面量）
+///
+/// 没有可用注释时退回到字符串字面量；两者都没有时返回 None。
+pub fn place_in_code(cover: &str, payload: &str, lang: Language, include_strings: bool) -> Option<CodePlacement> {
+    let chars: Vec<char> = cover.chars().collect();
+    let all = scan_regions(&chars, lang);
+    let has_comment = all
+        .iter()
+        .any(|r| r.kind == RegionKind::Comment && !is_directive(&chars, r));
+    let use_strings = include_strings || !has_comment;
+    let mut targets: Vec<&Region> = all
+        .iter()
+        .filter(|r| match r.kind {
+            RegionKind::Comment => !is_directive(&chars, r),
+            RegionKind::Str => use_strings,
+        })
+        .collect();
+    if targets.is_empty() {
+        return None;
+    }
+
+    let payload: Vec<char> = payload.chars().collect();
+    targets.truncate(payload.len().max(1));
+    let k = targets.len();
+    let mut out = String::with_capacity(cover.len() + payload.len() * 4);
+    let mut cursor = 0usize;
+    let mut taken = 0usize;
+    for (idx, region) in targets.iter().enumerate() {
+        let pos = insertion_point(&chars, region);
+        out.extend(&chars[cursor..pos]);
+        // 载荷按区域数均分，前面的区域多分余数
+        let size = payload.len() / k + usize::from(idx < payload.len() % k);
+        out.extend(&payload[taken..taken + size]);
+        taken += size;
+        cursor = pos;
+    }
+    out.extend(&chars[cursor..]);
+
+    Some(CodePlacement {
+        text: out,
+        comments: targets.iter().filter(|r| r.kind == RegionKind::Comment).count(),
+        strings: targets.iter().filter(|r| r.kind == RegionKind::Str).count(),
+    })
+}
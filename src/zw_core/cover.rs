@@ -0,0 +1,130 @@
+//! 随机载体文本生成 - 为编码提供看起来自然的掩护文本
+
+/// 载体文本风格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Lorem,
+    English,
+    Chinese,
+}
+
+impl Style {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "lorem" => Some(Style::Lorem),
+            "english" => Some(Style::English),
+            "chinese" => Some(Style::Chinese),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Style::Lorem => "lorem",
+            Style::English => "english",
+            Style::Chinese => "chinese",
+        }
+    }
+}
+
+const LOREM: &[&str] = &[
+    "lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit", "sed", "do",
+    "eiusmod", "tempor", "incididunt", "ut", "labore", "et", "dolore", "magna", "aliqua", "enim",
+    "ad", "minim", "veniam", "quis", "nostrud", "exercitation", "ullamco", "laboris", "nisi",
+    "aliquip", "ex", "ea", "commodo", "consequat", "duis", "aute", "irure", "in", "reprehenderit",
+    "voluptate", "velit", "esse", "cillum", "fugiat", "nulla", "pariatur",
+];
+
+/// 英文句子模板，{T} 替换为主题词
+const ENGLISH: &[&str] = &[
+    "Thanks for the update on {T}, I will take a closer look this afternoon.",
+    "The latest notes about {T} are attached for your review.",
+    "We discussed {T} during the weekly meeting and agreed on the next steps.",
+    "Please let me know if anything about {T} needs clarification.",
+    "I have summarized the main points regarding {T} below.",
+    "Our team is still collecting feedback on {T} from the other departments.",
+    "The schedule for {T} may shift slightly depending on resources.",
+    "Everything related to {T} looks good from my side so far.",
+    "Could you share the final version of the {T} document when it is ready?",
+    "A short follow-up on {T} will be sent out by the end of the week.",
+];
+
+/// 中文句子模板，{T} 替换为主题词
+const CHINESE: &[&str] = &[
+    "关于{T}的最新进展已经整理好，请查收附件。",
+    "我们在周会上讨论了{T}，并确定了下一步的计划。",
+    "如果对{T}还有任何疑问，欢迎随时联系我。",
+    "目前{T}的相关反馈仍在收集中，预计下周汇总。",
+    "{T}的时间安排可能会根据资源情况稍作调整。",
+    "从我这边看，{T}的整体情况一切正常。",
+    "麻烦在{T}的文档定稿后发我一份最终版本。",
+    "本周末前会再发一封关于{T}的跟进邮件。",
+    "下面简要总结一下{T}的几个要点。",
+    "感谢大家在{T}上的配合与支持。",
+];
+
+/// xorshift64 伪随机数，避免引入额外依赖；相同种子得到相同序列
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// `0..n` 中的一个数（`n` 须大于 0）
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+/// 生成至少 `min_chars` 个字符的载体文本
+pub fn generate(style: Style, min_chars: usize, theme: Option<&str>, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let theme = theme.unwrap_or(match style {
+        Style::Chinese => "项目",
+        _ => "the project",
+    });
+    let mut out = String::new();
+    // 模板不放回抽取，用完再补充，避免短文本内重复句子
+    let mut pool: Vec<&str> = Vec::new();
+    while out.chars().count() < min_chars {
+        match style {
+            Style::Lorem => {
+                let words = 6 + rng.below(8);
+                let mut sentence: Vec<&str> = (0..words).map(|_| rng.pick(LOREM)).collect();
+                if out.is_empty() {
+                    sentence[0] = "lorem";
+                }
+                let sentence = sentence.join(" ");
+                let mut chars = sentence.chars();
+                let first: String = chars.next().map(|c| c.to_uppercase().collect()).unwrap_or_default();
+                if !out.is_empty() {
+                    out.push(' ');
+                }
+                out.push_str(&format!("{}{}.", first, chars.as_str()));
+            }
+            Style::English | Style::Chinese => {
+                if pool.is_empty() {
+                    pool = if style == Style::English { ENGLISH.to_vec() } else { CHINESE.to_vec() };
+                }
+                let template = pool.swap_remove(rng.below(pool.len()));
+                if style == Style::English && !out.is_empty() {
+                    out.push(' ');
+                }
+                out.push_str(&template.replace("{T}", theme));
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,120 @@
+//! 参照对比 - 只保留参照文本（如文档的上游干净版本）中没有的不可见字符
+//!
+//! 先按行对齐：去掉不可见字符后内容相同的行，两边在相同可见位置上的相同字符视为原有。
+//! 内容有改动的行再按词对齐（以空白分词），同一个词里相同位置的相同字符视为原有。
+//! 这样文档原有的软连字符、排版用的 ZWJ/ZWNJ 等不会被当作新写入的载荷。
+
+use std::collections::HashMap;
+
+use super::chars::is_zero_width;
+
+/// 一个不可见字符及其定位信息
+struct Invisible {
+    ch: char,
+    /// 在所在行可见字符中的偏移
+    line_offset: usize,
+    /// 所在词（去掉不可见字符）
+    word: String,
+    /// 在所在词可见字符中的偏移
+    word_offset: usize,
+}
+
+/// 一行去掉不可见字符后的内容，以及其中的不可见字符
+fn split_line(line: &str) -> (String, Vec<Invisible>) {
+    let mut visible = String::with_capacity(line.len());
+    let mut invisibles = Vec::new();
+    let mut line_offset = 0;
+    for token in line.split_inclusive(char::is_whitespace) {
+        let word: String = token.chars().filter(|&c| !is_zero_width(c) && !c.is_whitespace()).collect();
+        let mut word_offset = 0;
+        for ch in token.chars() {
+            if is_zero_width(ch) {
+                invisibles.push(Invisible { ch, line_offset, word: word.clone(), word_offset });
+            } else {
+                visible.push(ch);
+                line_offset += 1;
+                word_offset += 1;
+            }
+        }
+    }
+    (visible, invisibles)
+}
+
+/// 参照对比结果
+#[derive(Debug, Clone)]
+pub struct Delta {
+    /// 去掉原有不可见字符后的文本
+    pub text: String,
+    /// 保留（新增）的不可见字符数
+    pub kept: usize,
+    /// 按行对齐排除的原有字符数
+    pub removed_by_line: usize,
+    /// 按词对齐排除的原有字符数
+    pub removed_by_word: usize,
+}
+
+/// 从 `text` 中去掉 `reference` 里已存在的不可见字符
+pub fn subtract_reference(text: &str, reference: &str) -> Delta {
+    let mut ref_lines: HashMap<String, Vec<Vec<(usize, char)>>> = HashMap::new();
+    let mut ref_words: HashMap<(String, usize, char), usize> = HashMap::new();
+    for line in reference.lines() {
+        let (visible, invisibles) = split_line(line);
+        for inv in &invisibles {
+            *ref_words.entry((inv.word.clone(), inv.word_offset, inv.ch)).or_default() += 1;
+        }
+        ref_lines
+            .entry(visible)
+            .or_default()
+            .push(invisibles.iter().map(|inv| (inv.line_offset, inv.ch)).collect());
+    }
+
+    // 同一内容的行在参照中出现多次时依次对应，用完后沿用最后一次
+    let mut used_lines: HashMap<String, usize> = HashMap::new();
+    let mut out = String::with_capacity(text.len());
+    let (mut kept, mut removed_by_line, mut removed_by_word) = (0, 0, 0);
+    for line in text.split_inclusive('\n') {
+        let body = line.strip_suffix('\n').unwrap_or(line);
+        let body = body.strip_suffix('\r').unwrap_or(body);
+        let (visible, invisibles) = split_line(body);
+        if invisibles.is_empty() {
+            out.push_str(line);
+            continue;
+        }
+        let mut line_ref: Vec<(usize, char)> = match ref_lines.get(&visible) {
+            Some(occurrences) => {
+                let used = used_lines.entry(visible).or_default();
+                let placements = occurrences[(*used).min(occurrences.len() - 1)].clone();
+                *used += 1;
+                placements
+            }
+            None => Vec::new(),
+        };
+        let mut keep = Vec::with_capacity(invisibles.len());
+        for inv in &invisibles {
+            let key = (inv.word.clone(), inv.word_offset, inv.ch);
+            if let Some(i) = line_ref.iter().position(|&p| p == (inv.line_offset, inv.ch)) {
+                line_ref.swap_remove(i);
+                if let Some(n) = ref_words.get_mut(&key) {
+                    *n = n.saturating_sub(1);
+                }
+                removed_by_line += 1;
+                keep.push(false);
+                continue;
+            }
+            match ref_words.get_mut(&key) {
+                Some(n) if !inv.word.is_empty() && *n > 0 => {
+                    *n -= 1;
+                    removed_by_word += 1;
+                    keep.push(false);
+                }
+                _ => {
+                    kept += 1;
+                    keep.push(true);
+                }
+            }
+        }
+        let mut keep = keep.into_iter();
+        out.extend(line.chars().filter(|&c| !is_zero_width(c) || keep.next().unwrap_or(true)));
+    }
+    Delta { text: out, kept, removed_by_line, removed_by_word }
+}
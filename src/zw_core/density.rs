@@ -0,0 +1,94 @@
+//! 载荷密度分布 - 按固定字节块统计不可见字符，定位大文件中载荷所在的区域
+//!
+//! 偏移为文本 UTF-8 编码下的字节偏移（UTF-8 文件即磁盘偏移），便于随后用
+//! `zw_hexdump` / `zw_dump_raw` 精确查看。
+
+use super::chars::is_zero_width;
+
+/// 一个块的统计
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub index: usize,
+    /// 块的起止字节偏移（左闭右开）
+    pub start: usize,
+    pub end: usize,
+    /// 块内字符数
+    pub chars: usize,
+    /// 块内不可见字符数
+    pub invisible: usize,
+    /// 块内第一个不可见字符所在行（从 1 开始）
+    pub first_line: Option<usize>,
+}
+
+impl Chunk {
+    /// 不可见字符占块内字符的比例
+    pub fn density(&self) -> f64 {
+        if self.chars == 0 {
+            0.0
+        } else {
+            self.invisible as f64 / self.chars as f64
+        }
+    }
+}
+
+/// 按 `chunk_size` 字节切块统计；字符按起始字节归属到块
+pub fn profile(text: &str, chunk_size: usize) -> Vec<Chunk> {
+    let chunk_size = chunk_size.max(1);
+    let count = text.len().div_ceil(chunk_size).max(1);
+    let mut chunks: Vec<Chunk> = (0..count)
+        .map(|index| Chunk {
+            index,
+            start: index * chunk_size,
+            end: ((index + 1) * chunk_size).min(text.len()),
+            chars: 0,
+            invisible: 0,
+            first_line: None,
+        })
+        .collect();
+    let mut line = 1;
+    for (offset, ch) in text.char_indices() {
+        let chunk = &mut chunks[offset / chunk_size];
+        chunk.chars += 1;
+        if is_zero_width(ch) {
+            chunk.invisible += 1;
+            chunk.first_line.get_or_insert(line);
+        }
+        if ch == '\n' {
+            line += 1;
+        }
+    }
+    chunks
+}
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// 把数值渲染为 sparkline；超过 `width` 个值时相邻值取最大合并。
+/// 0 显示为 `·`，其余按最大值线性映射到 8 级方块
+pub fn sparkline(values: &[f64], width: usize) -> String {
+    let width = width.max(1);
+    let per_cell = values.len().div_ceil(width).max(1);
+    let cells: Vec<f64> = values
+        .chunks(per_cell)
+        .map(|c| c.iter().copied().fold(0.0, f64::max))
+        .collect();
+    let max = cells.iter().copied().fold(0.0, f64::max);
+    cells
+        .iter()
+        .map(|&v| {
+            if v <= 0.0 || max <= 0.0 {
+                '·'
+            } else {
+                let level = ((v / max) * (BARS.len() - 1) as f64).round() as usize;
+                BARS[level.min(BARS.len() - 1)]
+            }
+        })
+        .collect()
+}
+
+/// 不可见字符最多的块（按数量降序，同数按位置），只含非空块
+pub fn top_chunks(chunks: &[Chunk], n: usize) -> Vec<&Chunk> {
+    let mut ranked: Vec<&Chunk> = chunks.iter().filter(|c| c.invisible > 0).collect();
+    ranked.sort_by_key(|c| (std::cmp::Reverse(c.invisible), c.index));
+    ranked.truncate(n);
+    ranked
+}
@@ -0,0 +1,184 @@
+//! 解码失败诊断 - 说明每个方案为何被拒绝，并给出下一步建议
+
+use super::chars::{is_unicode_tag, UNICODE_TAGS_START};
+use super::stegcloak;
+use super::engine::{analyze, encoding_presets, extract_all, extract_segments, score, top_chars, Acceptance};
+
+/// 失败诊断
+#[derive(Debug, Clone, Default)]
+pub struct FailureTrace {
+    /// (方案, 拒绝原因)
+    pub attempts: Vec<(String, String)>,
+    pub suggestions: Vec<String>,
+}
+
+/// 把解码输出的拒绝原因归类：不可打印 / 未超过本次请求的阈值
+fn rejection(output: &str, acceptance: &Acceptance) -> String {
+    let s = score(output);
+    if output.is_empty() {
+        "无有效输出".to_string()
+    } else if !acceptance.is_printable(output) {
+        format!("输出不可打印 (前几个字符: {:?})", output.chars().take(8).collect::<String>())
+    } else if s <= acceptance.threshold {
+        format!("得分 {:.1} 未超过阈值 {:.1}", s, acceptance.threshold)
+    } else {
+        format!("得分 {:.1} 已超过阈值 {:.1}，但分组或位序与诊断所用的默认方案不同", s, acceptance.threshold)
+    }
+}
+
+/// 不做任何过滤的二进制解码（仅用于诊断）
+fn raw_binary(zw_all: &[char], zero: char, one: char, bits: usize) -> String {
+    let bits_str: Vec<u32> = zw_all
+        .iter()
+        .filter_map(|&c| if c == zero { Some(0) } else if c == one { Some(1) } else { None })
+        .collect();
+    bits_str
+        .chunks_exact(bits)
+        .filter_map(|chunk| char::from_u32(chunk.iter().fold(0, |v, b| v * 2 + b)))
+        .collect()
+}
+
+/// 对未得到任何结果的输入给出逐方案的诊断
+pub fn diagnose_failure(text: &str, acceptance: &Acceptance, options: &stegcloak::Options) -> FailureTrace {
+    let mut trace = FailureTrace::default();
+    let analysis = analyze(text);
+    if analysis.zero_width_count == 0 {
+        trace.attempts.push(("全部方案".to_string(), "文本中没有零宽字符".to_string()));
+        trace.suggestions.push(
+            "确认客户端没有在传输中剥离零宽字符；可改用 file_path 直接读取原始文件".to_string(),
+        );
+        return trace;
+    }
+
+    let zw_all = extract_all(text);
+    let top = top_chars(&analysis);
+    let present = |c: char| analysis.distribution.contains_key(&(c as u32));
+
+    // Unicode Tags
+    if analysis.has_unicode_tags {
+        let raw: String = text
+            .chars()
+            .filter(|&c| is_unicode_tag(c))
+            .filter_map(|c| char::from_u32(c as u32 - UNICODE_TAGS_START))
+            .collect();
+        trace.attempts.push(("Unicode Tags".to_string(), rejection(&raw, acceptance)));
+    } else {
+        trace.attempts.push(("Unicode Tags".to_string(), "字符集不匹配: 没有 Tag 字符".to_string()));
+    }
+
+    // Steganographr
+    if present('\u{2060}') && (present('\u{200B}') || present('\u{200C}')) {
+        trace.attempts.push(("Steganographr".to_string(), "WJ 分隔的各段未能还原为可打印字符".to_string()));
+    } else {
+        trace.attempts.push((
+            "Steganographr".to_string(),
+            "字符集不匹配: 需要 WJ(U+2060) + ZWSP/ZWNJ".to_string(),
+        ));
+    }
+
+    // 预设
+    let mut best_preset: Option<(String, usize)> = None;
+    for (key, preset) in encoding_presets() {
+        let covered = preset.chars.iter().filter(|&&c| present(c)).count();
+        let foreign = top.iter().filter(|c| !preset.chars.contains(c)).count();
+        let reason = if covered < 2 {
+            format!("字符集不匹配: 仅命中 {}/{} 个预设字符", covered, preset.chars.len())
+        } else if foreign > 0 {
+            format!(
+                "命中 {}/{} 个预设字符，但文本另有 {} 种预设外字符；解码输出未通过过滤",
+                covered,
+                preset.chars.len(),
+                foreign
+            )
+        } else {
+            format!("字符集完全匹配 ({} 个)，但解码输出未通过过滤（分组/位序可能不同）", covered)
+        };
+        // StegCloak 按其格式解码，失败原因比“未通过过滤”更具体
+        let reason = if key == "stegcloak" && stegcloak::looks_like(text) {
+            match stegcloak::reveal(text, options.password.as_deref()) {
+                Err(e) => {
+                    if matches!(e, stegcloak::RevealError::PasswordRequired(_)) {
+                        trace.suggestions.push("StegCloak 载荷已加密：用 password 参数提供口令后重新解码".to_string());
+                    }
+                    e.describe()
+                }
+                Ok(_) => "解出的消息未通过过滤".to_string(),
+            }
+        } else {
+            reason
+        };
+        if covered >= 2 && best_preset.as_ref().map(|(_, c)| covered > *c).unwrap_or(true) {
+            best_preset = Some((key.clone(), covered));
+        }
+        trace.attempts.push((format!("预设 {}", key), reason));
+    }
+
+    // 二进制
+    if top.len() >= 2 {
+        let raw = raw_binary(&zw_all, top[0], top[1], 8);
+        let mapped = zw_all.iter().filter(|&&c| c == top[0] || c == top[1]).count();
+        let mut reason = rejection(&raw, acceptance);
+        if mapped % 8 != 0 && mapped % 7 != 0 {
+            reason.push_str(&format!("；两种最高频字符共 {} 个，不是 7 或 8 的倍数", mapped));
+        }
+        trace.attempts.push((
+            format!("二进制 (U+{:04X}=0, U+{:04X}=1, 8bit 及其他组合)", top[0] as u32, top[1] as u32),
+            reason,
+        ));
+    } else {
+        trace.attempts.push(("二进制".to_string(), "只有 1 种零宽字符，无法构成 0/1 映射".to_string()));
+    }
+
+    // 分段二进制
+    let segments = extract_segments(text);
+    if !segments.is_empty() {
+        let lengths: Vec<usize> = segments.iter().map(|s| s.chars().count()).collect();
+        let min = lengths.iter().min().copied().unwrap_or(0);
+        let max = lengths.iter().max().copied().unwrap_or(0);
+        trace.attempts.push((
+            "分段二进制".to_string(),
+            if lengths.iter().all(|&l| l == 7 || l == 8) {
+                "各段长度为 7/8，但映射后输出未通过过滤".to_string()
+            } else if min == max {
+                format!("{} 段长度均为 {}，不是每段 7/8 位", segments.len(), min)
+            } else {
+                format!(
+                    "{} 段长度不统一 (最小 {}, 最大 {})，不是每段一个字符",
+                    segments.len(),
+                    min,
+                    max
+                )
+            },
+        ));
+    }
+
+    // 建议
+    let distinct = analysis.unique_zw_chars;
+    match best_preset {
+        _ if distinct == 1 => trace.suggestions.push(
+            "只有 1 种零宽字符：信息可能藏在段长度或位置（用 zw_segments 查看各段长度），或只是排版残留".to_string(),
+        ),
+        Some((key, covered)) if covered == distinct => trace.suggestions.push(format!(
+            "字符集与预设 {} 完全一致，可用 method={} 单独解码，或用 zw_dump_raw 的 annotate/group_size 手动检查分组",
+            key, key
+        )),
+        _ => trace.suggestions.push(format!(
+            "{} 种零宽字符但没有完全匹配的预设 — 用 zw_dump_raw (annotate=true 或指定 group_size) 查看分组视图，手动确认映射",
+            distinct
+        )),
+    }
+    if analysis.zero_width_count < 16 {
+        trace.suggestions.push(format!(
+            "零宽字符只有 {} 个，载荷可能极短或只是排版残留（可用 zw_grep 查看具体位置）",
+            analysis.zero_width_count
+        ));
+    }
+    if distinct > 6 {
+        trace.suggestions.push(
+            "字符种类较多，可能混有排版噪声（软连字符、BOM 等）；可先用 zw_strip 去除无关分类后再解码".to_string(),
+        );
+    }
+    trace.suggestions.push("使用 budget=exhaustive 扩大暴力范围（更多字符对、比特偏移、进制）".to_string());
+    trace.suggestions.push("解码结果可能是二次编码（base64/hex 等）或加密数据，被可打印过滤器拒绝".to_string());
+    trace
+}
@@ -0,0 +1,210 @@
+//! 题目难度评估 - 估计在没有提示的情况下解出一段零宽隐写文本有多难
+//!
+//! 供出题人校准用 `zw_encode` 生成的题目：统计与符号分布相符的方案数、公开工具/预设能否直接解出、
+//! 自动解码的最佳结果是否明显领先、是否疑似加密或多层编码，汇总为分项得分与难度等级。
+
+use super::engine::{
+    analyze, auto_decode, decode_preset, decode_steganographr, decode_unicode_tags, encoding_presets, extract_all,
+    extract_segments, is_verified, match_preset, rank_results, Acceptance, Budget, DecodeResult,
+};
+use super::hints::cipher_evidence;
+
+/// 难度等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Trivial,
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Level {
+    fn from_points(points: i32) -> Self {
+        match points {
+            i32::MIN..=0 => Level::Trivial,
+            1..=2 => Level::Easy,
+            3..=4 => Level::Medium,
+            5..=6 => Level::Hard,
+            _ => Level::Expert,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Trivial => "入门（任何在线解码器都能直接解出）",
+            Level::Easy => "简单",
+            Level::Medium => "中等",
+            Level::Hard => "困难",
+            Level::Expert => "极难（没有提示基本无法解出）",
+        }
+    }
+}
+
+/// 难度评估结果
+#[derive(Debug, Clone)]
+pub struct Estimate {
+    pub zw_chars: usize,
+    /// 不同零宽字符的种类数
+    pub symbols: usize,
+    /// 零宽字符分布在几段连续序列中
+    pub segments: usize,
+    /// 字符集完全覆盖样本的预设
+    pub consistent_presets: Vec<String>,
+    /// 自动解码通过阈值的候选数（去重后）
+    pub candidates: usize,
+    pub best: Option<DecodeResult>,
+    /// 最佳结果领先第二名的分数
+    pub margin: Option<f64>,
+    /// 能直接解出最佳结果的公开工具/预设
+    pub standard_tools: Vec<String>,
+    /// 疑似加密/压缩的依据
+    pub cipher: Option<String>,
+    /// (分项说明, 分值)
+    pub factors: Vec<(String, i32)>,
+    pub points: i32,
+    pub level: Level,
+}
+
+/// 公开工具与内置预设的直接解码结果中，输出与 `best` 相同的工具名（按默认接受条件）
+fn standard_decoders(text: &str, best: &str) -> Vec<String> {
+    let acceptance = Acceptance::default();
+    let mut out = Vec::new();
+    if decode_steganographr(text, &acceptance).is_some_and(|r| r.decoded == best) {
+        out.push("Steganographr".to_string());
+    }
+    if decode_unicode_tags(text, &acceptance).is_some_and(|r| r.decoded == best) {
+        out.push("Unicode Tags".to_string());
+    }
+    let zw_all = extract_all(text);
+    for (key, preset) in encoding_presets() {
+        let mut results = decode_preset(&zw_all, &preset, &acceptance);
+        rank_results(&mut results);
+        if results.first().is_some_and(|r| r.decoded == best) && !out.contains(&key) {
+            out.push(key);
+        }
+    }
+    out
+}
+
+/// 评估解出 `text` 中载荷的难度
+pub fn estimate(text: &str, budget: &Budget) -> Estimate {
+    let analysis = analyze(text);
+    let segments = extract_segments(text).len();
+    let consistent_presets: Vec<String> = encoding_presets()
+        .into_iter()
+        .filter(|(_, p)| analysis.zero_width_count > 0 && match_preset(&analysis, p).unmatched.is_empty())
+        .map(|(key, _)| key)
+        .collect();
+
+    let mut results = auto_decode(text, budget);
+    rank_results(&mut results);
+    let best = results.first().cloned();
+    let margin = match (results.first(), results.get(1)) {
+        (Some(a), Some(b)) => Some(a.score - b.score),
+        (Some(a), None) => Some(a.score),
+        _ => None,
+    };
+    let standard_tools = best.as_ref().map(|b| standard_decoders(text, &b.decoded)).unwrap_or_default();
+
+    // 解不出可打印结果时，放开过滤看原始字节是否像密文
+    let cipher = match &best {
+        Some(b) => cipher_evidence(&b.decoded),
+        None => {
+            let binary = Acceptance { threshold: f64::MIN, allow_binary: true };
+            let mut raw = auto_decode(text, &budget.clone().with_acceptance(binary));
+            rank_results(&mut raw);
+            raw.first().and_then(|r| cipher_evidence(&r.decoded))
+        }
+    };
+
+    let mut factors: Vec<(String, i32)> = Vec::new();
+    if analysis.zero_width_count == 0 {
+        factors.push(("文本中没有零宽字符，无法评估".to_string(), 0));
+    } else {
+        match &best {
+            None => factors.push(("自动解码没有得到任何可信结果，需要额外信息（密钥、映射或出题工具）".to_string(), 4)),
+            Some(b) if !standard_tools.is_empty() => factors.push((
+                format!("公开工具/预设可直接解出（{}）", standard_tools.join("、")),
+                if is_verified(b) { -1 } else { 0 },
+            )),
+            Some(_) => factors.push(("没有公开工具能直接解出，需要自动搜索映射与分组".to_string(), 2)),
+        }
+        if analysis.unique_zw_chars > 2 {
+            factors.push((format!("使用 {} 种零宽字符（N进制），映射需要枚举", analysis.unique_zw_chars), 1));
+        }
+        if consistent_presets.is_empty() {
+            factors.push(("字符集与所有已知工具都不吻合，难以猜到出题工具".to_string(), 1));
+        }
+        if let (Some(b), Some(m)) = (&best, margin) {
+            if results.len() > 3 && m < 20.0 && !is_verified(b) {
+                factors.push((format!("{} 个候选且最佳结果只领先 {:.1} 分，容易选错", results.len(), m), 1));
+            }
+        }
+        if let Some(c) = &cipher {
+            factors.push((format!("{}，解出比特后还要破解密码", c), 3));
+        } else if best.as_ref().is_some_and(|b| {
+            !is_verified(b)
+                && b.decoded.len() >= 16
+                && b.decoded.chars().all(|c| c.is_ascii_alphanumeric() || "+/=-_".contains(c))
+        }) {
+            factors.push(("结果像 base64/hex 等二次编码，需要继续解码".to_string(), 1));
+        }
+        if segments > 1 {
+            factors.push((format!("零宽字符分散在 {} 段中", segments), 1));
+        }
+        if best.as_ref().is_some_and(|b| !is_verified(b)) {
+            factors.push(("结果不是 flag{...} 格式，难以确认是否解对".to_string(), 1));
+        }
+    }
+
+    let points = factors.iter().map(|(_, p)| p).sum();
+    Estimate {
+        zw_chars: analysis.zero_width_count,
+        symbols: analysis.unique_zw_chars,
+        segments,
+        consistent_presets,
+        candidates: results.len(),
+        best,
+        margin,
+        standard_tools,
+        cipher,
+        factors,
+        points,
+        level: Level::from_points(points),
+    }
+}
+
+/// 格式化评估报告
+pub fn format_estimate(e: &Estimate) -> String {
+    let mut out = format!("难度: {}（{} 分）\n", e.level.name(), e.points);
+    out.push_str(&format!(
+        "零宽字符 {} 个，{} 种，分布在 {} 段中\n",
+        e.zw_chars, e.symbols, e.segments
+    ));
+    out.push_str(&format!(
+        "字符集吻合的预设: {}\n",
+        if e.consistent_presets.is_empty() { "无".to_string() } else { e.consistent_presets.join(", ") }
+    ));
+    out.push_str(&format!("自动解码候选: {} 个", e.candidates));
+    if let Some(m) = e.margin {
+        out.push_str(&format!("，最佳结果领先 {:.1} 分", m));
+    }
+    out.push('\n');
+    match &e.best {
+        Some(b) => out.push_str(&format!("最佳结果: {:?}（{}，得分 {:.1}）\n", b.decoded, b.method, b.score)),
+        None => out.push_str("最佳结果: 无\n"),
+    }
+    out.push_str(&format!(
+        "公开工具直接解出: {}\n",
+        if e.standard_tools.is_empty() { "否".to_string() } else { e.standard_tools.join(", ") }
+    ));
+    if let Some(c) = &e.cipher {
+        out.push_str(&format!("疑似加密: {}\n", c));
+    }
+    out.push_str("\n评分依据:\n");
+    for (reason, points) in &e.factors {
+        out.push_str(&format!("  {:+} {}\n", points, reason));
+    }
+    out
+}
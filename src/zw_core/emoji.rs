@@ -0,0 +1,280 @@
+//! emoji ZWJ 通道 - 信息藏在 emoji 的选择/顺序与连接符里，而不是独立的零宽序列中
+//!
+//! 这类方案利用“看起来一样”的 emoji 变体携带比特：默认 emoji 呈现的字符加不加 VS16、emoji 后面
+//! 挂一个不渲染的悬空 ZWJ、相邻 emoji 之间插不插 ZWJ（不构成标准组合时仍显示为两个字形），或者
+//! 直接用从一小组 emoji 中的选择编码数字。这里把文本切成 emoji 簇，逐簇提取这些特征，按比特流或
+//! N 进制解码；同时把不渲染的异常 ZWJ 序列单独归类报告，便于与正常的组合 emoji 区分。
+
+use super::engine::{self, Acceptance, DecodeResult};
+use super::sanitize::is_emoji;
+
+const ZWJ: char = '\u{200D}';
+const VS15: char = '\u{FE0E}';
+const VS16: char = '\u{FE0F}';
+/// 至少这么多个 emoji 簇才尝试解码
+pub const MIN_CLUSTERS: usize = 8;
+/// emoji 选择通道允许的最多不同 emoji 数
+const MAX_ALPHABET: usize = 16;
+/// 不同 emoji 不超过这么多个时穷举数字映射（4! = 24 种）
+const MAX_PERMUTED: usize = 4;
+
+/// 标准 ZWJ 组合序列中 ZWJ 之后出现的字符（职业、性别、发色、家庭成员、彩虹旗等）。
+/// 不在此列且不是家庭成员组合的连接视为非标准组合，通常不会渲染为单个字形
+const ZWJ_COMPONENTS: &[u32] = &[
+    0x2194, 0x2195, 0x2620, 0x2640, 0x2642, 0x2695, 0x2696, 0x26A7, 0x2708, 0x2744, 0x2764, 0x27A1, 0x2B1B,
+    0x1F308, 0x1F32B, 0x1F33E, 0x1F373, 0x1F37C, 0x1F384, 0x1F393, 0x1F3A4, 0x1F3A8, 0x1F3EB, 0x1F3ED, 0x1F466,
+    0x1F467, 0x1F468, 0x1F469, 0x1F48B, 0x1F4A8, 0x1F4AB, 0x1F4BB, 0x1F4BC, 0x1F525, 0x1F527, 0x1F52C, 0x1F5E8,
+    0x1F680, 0x1F692, 0x1F7E9, 0x1F7EB, 0x1F91D, 0x1F9AF, 0x1F9B0, 0x1F9B1, 0x1F9B2, 0x1F9B3, 0x1F9BA, 0x1F9BC,
+    0x1F9BD, 0x1F9D1, 0x1F9D2, 0x1FA79,
+];
+
+/// 肤色修饰符
+fn is_skin_tone(ch: char) -> bool {
+    ('\u{1F3FB}'..='\u{1F3FF}').contains(&ch)
+}
+
+/// 默认以 emoji 呈现的字符：加不加 VS16 显示相同
+fn default_emoji_presentation(ch: char) -> bool {
+    ch as u32 >= 0x1F000
+}
+
+/// 一个 emoji 簇（基字符 + 修饰 + ZWJ 连接的后续 emoji）
+#[derive(Debug, Clone, Default)]
+pub struct Cluster {
+    /// 簇中的 emoji 基字符（按 ZWJ 连接顺序）
+    pub bases: Vec<char>,
+    /// 基字符后紧跟 VS16
+    pub vs16: bool,
+    /// 簇末尾不连接任何 emoji 的 ZWJ 个数
+    pub dangling_zwj: usize,
+    /// 连续出现的多余 ZWJ 个数
+    pub doubled_zwj: usize,
+    /// 非标准的 ZWJ 连接个数
+    pub nonstandard_joins: usize,
+    /// 与前一个簇之间没有其他字符
+    pub adjacent_to_prev: bool,
+}
+
+/// 把文本切成 emoji 簇
+pub fn clusters(text: &str) -> Vec<Cluster> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out: Vec<Cluster> = Vec::new();
+    let mut i = 0;
+    let mut last_end = usize::MAX;
+    while i < chars.len() {
+        if !is_emoji(chars[i]) || is_skin_tone(chars[i]) {
+            i += 1;
+            continue;
+        }
+        let mut cluster = Cluster { bases: vec![chars[i]], adjacent_to_prev: last_end == i, ..Default::default() };
+        i += 1;
+        loop {
+            // 修饰：VS15/VS16 与肤色
+            while i < chars.len() && (chars[i] == VS15 || chars[i] == VS16 || is_skin_tone(chars[i])) {
+                if chars[i] == VS16 && cluster.bases.len() == 1 {
+                    cluster.vs16 = true;
+                }
+                i += 1;
+            }
+            if i >= chars.len() || chars[i] != ZWJ {
+                break;
+            }
+            let mut zwj = 0;
+            while i < chars.len() && chars[i] == ZWJ {
+                zwj += 1;
+                i += 1;
+            }
+            match chars.get(i) {
+                Some(&next) if is_emoji(next) && !is_skin_tone(next) => {
+                    cluster.doubled_zwj += zwj - 1;
+                    let prev = *cluster.bases.last().unwrap_or(&next);
+                    if !is_standard_join(prev, next) {
+                        cluster.nonstandard_joins += 1;
+                    }
+                    cluster.bases.push(next);
+                    i += 1;
+                }
+                _ => {
+                    cluster.dangling_zwj += zwj;
+                    break;
+                }
+            }
+        }
+        last_end = i;
+        out.push(cluster);
+    }
+    out
+}
+
+fn is_family_member(ch: char) -> bool {
+    matches!(ch as u32, 0x1F466..=0x1F469 | 0x1F9D1 | 0x1F9D2)
+}
+
+/// ZWJ 连接是否属于常见的标准组合
+fn is_standard_join(prev: char, next: char) -> bool {
+    ZWJ_COMPONENTS.contains(&(next as u32)) || (is_family_member(prev) && is_family_member(next))
+}
+
+/// 异常（不渲染）ZWJ 序列的统计
+#[derive(Debug, Clone, Default)]
+pub struct Anomalies {
+    /// 含 emoji 的簇数
+    pub clusters: usize,
+    /// 标准组合序列数
+    pub standard_sequences: usize,
+    pub dangling_zwj: usize,
+    pub doubled_zwj: usize,
+    pub nonstandard_joins: usize,
+    /// 多余的 VS16（跟在默认 emoji 呈现字符后，不改变显示）
+    pub redundant_vs16: usize,
+}
+
+impl Anomalies {
+    pub fn total(&self) -> usize {
+        self.dangling_zwj + self.doubled_zwj + self.nonstandard_joins
+    }
+}
+
+/// 统计文本中的 emoji ZWJ 序列
+pub fn anomalies(text: &str) -> Anomalies {
+    let all = clusters(text);
+    let mut a = Anomalies { clusters: all.len(), ..Default::default() };
+    for c in &all {
+        a.dangling_zwj += c.dangling_zwj;
+        a.doubled_zwj += c.doubled_zwj;
+        a.nonstandard_joins += c.nonstandard_joins;
+        if c.bases.len() > 1 && c.nonstandard_joins == 0 && c.doubled_zwj == 0 {
+            a.standard_sequences += 1;
+        }
+        if c.vs16 && c.bases.len() == 1 && default_emoji_presentation(c.bases[0]) {
+            a.redundant_vs16 += 1;
+        }
+    }
+    a
+}
+
+/// 供分析报告展示；没有 emoji 时返回空串
+pub fn describe(a: &Anomalies) -> String {
+    if a.clusters == 0 {
+        return String::new();
+    }
+    let mut out = format!("emoji 序列: {} 个 emoji 簇，其中标准 ZWJ 组合 {} 个\n", a.clusters, a.standard_sequences);
+    if a.total() == 0 {
+        out.push_str("  未发现异常 ZWJ 序列\n");
+    } else {
+        out.push_str(&format!("  ⚠ 异常 ZWJ 序列（不渲染，可能携带信息）: {} 处\n", a.total()));
+        for (label, n) in [
+            ("悬空 ZWJ（后面没有 emoji）", a.dangling_zwj),
+            ("重复 ZWJ", a.doubled_zwj),
+            ("非标准组合（显示为分开的字形）", a.nonstandard_joins),
+        ] {
+            if n > 0 {
+                out.push_str(&format!("    {}: {}\n", label, n));
+            }
+        }
+    }
+    if a.redundant_vs16 > 0 {
+        out.push_str(&format!("  多余的 VS16（不改变显示）: {} 个\n", a.redundant_vs16));
+    }
+    out
+}
+
+/// 一个比特通道：名称与每簇一个比特
+fn bit_channels(all: &[Cluster]) -> Vec<(&'static str, Vec<bool>)> {
+    let mut channels = Vec::new();
+    let vs16: Vec<bool> =
+        all.iter().filter(|c| c.bases.len() == 1 && default_emoji_presentation(c.bases[0])).map(|c| c.vs16).collect();
+    channels.push(("VS16 有无", vs16));
+    channels.push(("悬空 ZWJ 有无", all.iter().map(|c| c.dangling_zwj > 0).collect()));
+    // 相邻 emoji 之间是否以 ZWJ 连接：簇内每个连接为 1，紧挨着的两个簇之间为 0
+    let mut joins = Vec::new();
+    for c in all {
+        if c.adjacent_to_prev {
+            joins.push(false);
+        }
+        joins.extend(std::iter::repeat_n(true, c.bases.len() - 1));
+    }
+    channels.push(("相邻 emoji 是否以 ZWJ 连接", joins));
+    channels
+}
+
+/// 全排列，只用于很小的字母表
+fn permutations(items: &[char]) -> Vec<Vec<char>> {
+    if items.len() <= 1 {
+        return vec![items.to_vec()];
+    }
+    let mut out = Vec::new();
+    for (i, &first) in items.iter().enumerate() {
+        let mut rest = items.to_vec();
+        rest.remove(i);
+        for mut tail in permutations(&rest) {
+            tail.insert(0, first);
+            out.push(tail);
+        }
+    }
+    out
+}
+
+fn tag(mut r: DecodeResult, method: String) -> DecodeResult {
+    r.method = method;
+    r.meta.push(("channel", "emoji_zwj".to_string()));
+    r.scheme = None;
+    r
+}
+
+/// 按 emoji 通道解码；emoji 簇不足 `MIN_CLUSTERS` 时返回空
+pub fn decode(text: &str, acceptance: &Acceptance) -> Vec<DecodeResult> {
+    let all = clusters(text);
+    if all.len() < MIN_CLUSTERS {
+        return vec![];
+    }
+    let mut results = Vec::new();
+    for (name, bits) in bit_channels(&all) {
+        // 全 0 或全 1 不携带信息
+        if bits.len() < 8 || bits.iter().all(|&b| b) || !bits.iter().any(|&b| b) {
+            continue;
+        }
+        let seq: Vec<char> = bits.iter().map(|&b| if b { '1' } else { '0' }).collect();
+        for width in [8, 7] {
+            for (zero, one) in [('0', '1'), ('1', '0')] {
+                let Some(r) = engine::decode_direct_binary(&seq, zero, one, width, 0, acceptance) else { continue };
+                if acceptance.passes(r.score) {
+                    let polarity = if zero == '0' { "有=1" } else { "有=0" };
+                    results.push(tag(r, format!("emoji 通道: {} ({}, {}bit)", name, polarity, width)));
+                }
+            }
+        }
+    }
+
+    // emoji 选择：每个簇的首个基字符是一位数字
+    let choices: Vec<char> = all.iter().map(|c| c.bases[0]).collect();
+    let mut alphabet: Vec<char> = Vec::new();
+    for &c in &choices {
+        if !alphabet.contains(&c) {
+            alphabet.push(c);
+        }
+    }
+    if (2..=MAX_ALPHABET).contains(&alphabet.len()) {
+        let mut sorted = alphabet.clone();
+        sorted.sort_unstable();
+        let mut orders = vec![("按码点", sorted.clone())];
+        if alphabet != sorted {
+            orders.push(("按首次出现", alphabet));
+        }
+        // 字母表很小时数字与 emoji 的对应关系可以穷举
+        if sorted.len() <= MAX_PERMUTED {
+            for perm in permutations(&sorted) {
+                if orders.iter().all(|(_, o)| *o != perm) {
+                    orders.push(("穷举映射", perm));
+                }
+            }
+        }
+        for (order, charset) in orders {
+            for r in engine::decode_nary(&choices, &charset, acceptance) {
+                let method = format!("emoji 通道: emoji 选择, {} ({})", order, r.method);
+                results.push(tag(r, method));
+            }
+        }
+    }
+    results
+}
@@ -0,0 +1,181 @@
+//! 第三方工具编码仿真 - 按公开工具的源码重新实现其编码算法
+//!
+//! 出题时若要求"必须能被某个公开解码器解出"，用这里的实现代替本项目自己的编码方式：
+//! 字符串按原工具的单位切分（PHP 按字节、JS 按 UTF-16 码元），填充、分隔符与插入位置
+//! 按原工具源码的写法实现。夹具见 `corpus/emulate/fixtures.jsonl`，其中的期望输出由
+//! `corpus/emulate/reference.js`（按各工具公开源码独立重写的 JS 编码器）生成，
+//! 由 `cargo test --test emulate` 和 `zw_benchmark` 校验。夹具只说明两份重写彼此一致，
+//! 尚未与原工具实际生成的向量核对，StegCloak 也没有夹具，因此不保证与原工具逐字节一致。
+
+use serde::Deserialize;
+
+use super::stegcloak;
+
+/// 内置仿真夹具
+const FIXTURES: &str = include_str!("../../corpus/emulate/fixtures.jsonl");
+
+/// 可仿真的工具
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmulatedTool {
+    /// 330k Unicode Steganography 网页版（unicode_steganography.js，默认 4 字符集）
+    Web330k,
+    /// neatnik.net Steganographr
+    Steganographr,
+    /// KuroLabs StegCloak
+    StegCloak,
+    /// zero-width-lib（ZWSP/ZWNJ/ZWJ 二进制 + FEFF 连接）
+    ZeroWidthLib,
+}
+
+impl EmulatedTool {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "330k" => Some(EmulatedTool::Web330k),
+            "steganographr" => Some(EmulatedTool::Steganographr),
+            "stegcloak" => Some(EmulatedTool::StegCloak),
+            "zero_width_lib" | "zero-width-lib" => Some(EmulatedTool::ZeroWidthLib),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmulatedTool::Web330k => "330k",
+            EmulatedTool::Steganographr => "steganographr",
+            EmulatedTool::StegCloak => "stegcloak",
+            EmulatedTool::ZeroWidthLib => "zero_width_lib",
+        }
+    }
+
+    pub fn names() -> [&'static str; 4] {
+        ["330k", "steganographr", "stegcloak", "zero_width_lib"]
+    }
+}
+
+/// 330k 网页版的默认字符集
+const CHARS_330K: [char; 4] = ['\u{200C}', '\u{200D}', '\u{202C}', '\u{FEFF}'];
+
+/// 在第一个字符之后插入（Steganographr / zero-width-lib 的做法）
+fn insert_after_first(cover: &str, hidden: &str) -> String {
+    let mut chars = cover.chars();
+    match chars.next() {
+        Some(first) => format!("{}{}{}", first, hidden, chars.as_str()),
+        None => hidden.to_string(),
+    }
+}
+
+/// 330k: 每个 UTF-16 码元转为定长 N进制（高位在前、补零），再逐个穿插在可见字符之后，
+/// 多出的部分追加在末尾
+fn encode_330k(message: &str, cover: &str) -> String {
+    let radix = CHARS_330K.len() as u32;
+    // Math.ceil(Math.log(65536) / Math.log(radix))
+    let code_length = (65536f64.ln() / (radix as f64).ln()).ceil() as usize;
+    let groups: Vec<String> = message
+        .encode_utf16()
+        .map(|unit| {
+            let mut digits = vec![0u32; code_length];
+            let mut v = unit as u32;
+            for d in digits.iter_mut().rev() {
+                *d = v % radix;
+                v /= radix;
+            }
+            digits.iter().map(|&d| CHARS_330K[d as usize]).collect()
+        })
+        .collect();
+    let mut out = String::new();
+    let mut rest = groups.iter();
+    for c in cover.chars() {
+        out.push(c);
+        if let Some(g) = rest.next() {
+            out.push_str(g);
+        }
+    }
+    for g in rest {
+        out.push_str(g);
+    }
+    out
+}
+
+/// Steganographr: 每个 UTF-8 字节 decbin（不补零），字节间用 WJ 分隔，
+/// 0=ZWSP、1=ZWNJ，整体用 U+FEFF 包裹后插在第一个字符之后
+fn encode_steganographr(message: &str, cover: &str) -> String {
+    let bits: Vec<String> = message.bytes().map(|b| format!("{:b}", b)).collect();
+    let hidden: String = bits
+        .join(" ")
+        .chars()
+        .map(|c| match c {
+            ' ' => '\u{2060}',
+            '0' => '\u{200B}',
+            _ => '\u{200C}',
+        })
+        .collect();
+    insert_after_first(cover, &format!("\u{FEFF}{}\u{FEFF}", hidden))
+}
+
+/// zero-width-lib: 每个 UTF-16 码元的二进制补零到 8 位，码元间用空格；
+/// 1=ZWSP、0=ZWNJ、空格=ZWJ，符号之间用 U+FEFF 连接，插在第一个字符之后
+fn encode_zero_width_lib(message: &str, cover: &str) -> String {
+    let binary: Vec<String> = message.encode_utf16().map(|u| format!("{:08b}", u)).collect();
+    let symbols: Vec<&str> = binary
+        .join(" ")
+        .chars()
+        .map(|c| match c {
+            '1' => "\u{200B}",
+            '0' => "\u{200C}",
+            _ => "\u{200D}",
+        })
+        .collect();
+    insert_after_first(cover, &symbols.join("\u{FEFF}"))
+}
+
+/// 按指定工具的算法编码；`options` 只对 StegCloak 有效
+pub fn encode(tool: EmulatedTool, message: &str, cover: &str, options: &stegcloak::Options) -> Result<String, String> {
+    match tool {
+        EmulatedTool::Web330k => Ok(encode_330k(message, cover)),
+        EmulatedTool::Steganographr => Ok(encode_steganographr(message, cover)),
+        EmulatedTool::ZeroWidthLib => Ok(encode_zero_width_lib(message, cover)),
+        // 加密时盐是随机的，原工具的插入位置也是随机的，这两项无法逐字节复现
+        EmulatedTool::StegCloak => Ok(stegcloak::hide(message, cover, options)),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Fixture {
+    name: String,
+    tool: String,
+    message: String,
+    cover: String,
+    expected: String,
+}
+
+/// 单个夹具的校验结果
+#[derive(Debug, Clone)]
+pub struct FixtureResult {
+    pub name: String,
+    pub tool: String,
+    pub passed: bool,
+    /// 不一致时第一个不同字符的下标或错误信息
+    pub detail: String,
+}
+
+/// 逐个运行内置夹具，比较输出与夹具中的参考输出
+pub fn verify_fixtures() -> Result<Vec<FixtureResult>, String> {
+    let mut results = Vec::new();
+    for (i, line) in FIXTURES.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let f: Fixture = serde_json::from_str(line).map_err(|e| format!("仿真夹具第 {} 行解析失败: {}", i + 1, e))?;
+        let tool = EmulatedTool::parse(&f.tool).ok_or_else(|| format!("仿真夹具 {} 的工具未知: {}", f.name, f.tool))?;
+        let (passed, detail) = match encode(tool, &f.message, &f.cover, &stegcloak::Options::default()) {
+            Ok(out) if out == f.expected => (true, String::new()),
+            Ok(out) => {
+                let at = out.chars().zip(f.expected.chars()).take_while(|(a, b)| a == b).count();
+                (false, format!("第 {} 个字符起不一致", at))
+            }
+            Err(e) => (false, e),
+        };
+        results.push(FixtureResult { name: f.name, tool: tool.name().to_string(), passed, detail });
+    }
+    Ok(results)
+}
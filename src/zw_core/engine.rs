@@ -1,8 +1,13 @@
 //! 零宽字符分析与解码引擎
 
 use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
 
-use super::chars::{all_zero_width_chars, is_unicode_tag, is_zero_width, UNICODE_TAGS_START};
+use serde::{Deserialize, Serialize};
+
+use super::chars::{all_zero_width_chars, is_unicode_tag, is_zero_width, name_of, UNICODE_TAGS_START};
+use super::{cluster, emoji, morse, numeric, stegcloak};
 
 // ============================================================
 // 分析
@@ -48,6 +53,27 @@ pub fn analyze(text: &str) -> Analysis {
     }
 }
 
+/// 导出零宽字符分布与位置为 CSV: `codepoint,name,count,first_index,last_index`
+///
+/// 下标为原文中的字符下标（从 0 开始），按码点排序
+pub fn distribution_csv(text: &str) -> String {
+    let mut stats: BTreeMap<u32, (usize, usize, usize)> = BTreeMap::new();
+    for (i, ch) in text.chars().enumerate() {
+        if is_zero_width(ch) {
+            let entry = stats.entry(ch as u32).or_insert((0, i, i));
+            entry.0 += 1;
+            entry.2 = i;
+        }
+    }
+    let mut out = String::from("codepoint,name,count,first_index,last_index\n");
+    for (cp, (count, first, last)) in stats {
+        let name = char::from_u32(cp).and_then(name_of).unwrap_or_default();
+        let name = if name.contains([',', '"']) { format!("\"{}\"", name.replace('"', "\"\"")) } else { name };
+        out.push_str(&format!("U+{:04X},{},{},{},{}\n", cp, name, count, first, last));
+    }
+    out
+}
+
 /// 格式化分析报告
 pub fn format_analysis(analysis: &Analysis) -> String {
     let mut out = String::new();
@@ -105,20 +131,357 @@ pub fn extract_segments(text: &str) -> Vec<String> {
     segments
 }
 
+/// 零宽字符段详情
+#[derive(Debug, Clone)]
+pub struct SegmentInfo {
+    /// 段首字符在原文中的字符索引
+    pub start: usize,
+    pub content: String,
+    /// 段内出现的码点（按首次出现顺序）
+    pub charset: Vec<u32>,
+    /// 段前紧邻的可见文本
+    pub before: String,
+    /// 段后紧邻的可见文本
+    pub after: String,
+}
+
+/// 提取零宽字符段及其前后可见文本（分段规则同 `extract_segments`）
+pub fn segment_details(text: &str, context: usize) -> Vec<SegmentInfo> {
+    let chars: Vec<char> = text.chars().collect();
+    let visible_window = |range: &mut dyn Iterator<Item = usize>| -> Vec<char> {
+        range
+            .map(|i| chars[i])
+            .filter(|c| !is_zero_width(*c))
+            .take(context)
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect()
+    };
+
+    let mut infos = Vec::new();
+    let mut i = 0usize;
+    while i < chars.len() {
+        if !is_zero_width(chars[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && is_zero_width(chars[i]) {
+            i += 1;
+        }
+        let content: String = chars[start..i].iter().collect();
+        let mut charset: Vec<u32> = Vec::new();
+        for c in content.chars() {
+            if !charset.contains(&(c as u32)) {
+                charset.push(c as u32);
+            }
+        }
+        let mut before = visible_window(&mut (0..start).rev());
+        before.reverse();
+        let after = visible_window(&mut (i..chars.len()));
+        infos.push(SegmentInfo {
+            start,
+            content,
+            charset,
+            before: before.into_iter().collect(),
+            after: after.into_iter().collect(),
+        });
+    }
+    infos
+}
+
+/// 重复载荷中每份至少这么多个零宽字符（更短的重复多半是填充或分隔符）
+const MIN_REPEATED_BLOCK: usize = 8;
+
+/// 复制粘贴造成的重复载荷（原消息连同引用回复一起粘贴）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repetition {
+    /// 相同载荷的份数
+    pub copies: usize,
+    /// 每份的零宽字符数
+    pub block_chars: usize,
+    /// 第一份末尾在原文中的字节偏移（只含第一份的前缀文本）
+    pub first_end: usize,
+}
+
+/// 检测零宽字符流是否由若干份完全相同的块组成，且每份之间隔着可见文本。
+/// 要求分界落在段边界上，避免把载荷内部的周期性（如重复的填充字符）误判为复制
+pub fn detect_repetition(text: &str) -> Option<Repetition> {
+    // (字符, 末尾字节偏移, 是否与下一个零宽字符之间隔着可见字符)
+    let mut zw: Vec<(char, usize, bool)> = Vec::new();
+    for (i, ch) in text.char_indices() {
+        if is_zero_width(ch) {
+            zw.push((ch, i + ch.len_utf8(), false));
+        } else if let Some(last) = zw.last_mut() {
+            last.2 = true;
+        }
+    }
+    let n = zw.len();
+    for block in MIN_REPEATED_BLOCK..=n / 2 {
+        if !n.is_multiple_of(block) || !zw[block - 1].2 {
+            continue;
+        }
+        let distinct = zw[..block].iter().any(|z| z.0 != zw[0].0);
+        if distinct && (block..n).all(|i| zw[i].0 == zw[i - block].0) && (1..n / block).all(|k| zw[k * block - 1].2) {
+            return Some(Repetition { copies: n / block, block_chars: block, first_end: zw[block - 1].1 });
+        }
+    }
+    None
+}
+
 // ============================================================
 // 解码方案
 // ============================================================
 
 /// 单条解码结果
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct DecodeResult {
     pub method: String,
     pub decoded: String,
     pub score: f64,
+    /// 附加元数据（推断出的分组大小、位序等），按插入顺序展示
+    pub meta: Vec<(&'static str, String)>,
+    /// 实际使用的映射方案（修复/分段结果为 None，因其作用于变换后的文本）
+    pub scheme: Option<Scheme>,
+}
+
+impl DecodeResult {
+    /// 稳定 ID: 方案名（已包含字符集、分组等参数）与元数据的 SHA-256 前 12 位；
+    /// 同一输入多次解码得到相同的 ID，可用于 `zw_decode_detail` 追问
+    pub fn id(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(self.method.as_bytes());
+        for (k, v) in &self.meta {
+            hasher.update([0u8]);
+            hasher.update(k.as_bytes());
+            hasher.update(b"=");
+            hasher.update(v.as_bytes());
+        }
+        hasher.finalize().iter().take(6).map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// 解码所用的映射方案，供 `explain` 复现推导过程
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scheme {
+    /// 码点减 U+E0000 即 ASCII
+    UnicodeTags,
+    /// WJ 分隔字符，ZWSP=0 / ZWNJ=1
+    Steganographr,
+    /// 连续比特流按固定位宽切块
+    Binary { zero: char, one: char, bits: usize, offset: usize },
+    /// 每段零宽序列恰好一个字符
+    SegmentedBinary { zero: char, one: char, bits: usize },
+    /// 字符集下标为数字，按固定分组求值
+    Nary { charset: Vec<char>, group_size: usize, lsb_first: bool },
+    /// 用户指定的码点→数字映射，组值按字母表解释
+    Mapped { mapping: Vec<(char, usize)>, base: usize, group_size: usize, alphabet: Alphabet },
+    /// 莫尔斯码：点、划与字母分隔符；`separator` 为 None 时每段零宽序列是一个字母
+    Morse { dot: char, dash: char, separator: Option<char> },
+    /// StegCloak：压缩（及加密）后的字节每 2 比特一个字符，常见双字符组合另有替代字符
+    StegCloak { mode: stegcloak::Mode },
+}
+
+/// 分组值的解释方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet {
+    /// 组值即 Unicode 码点
+    Unicode,
+    /// 组值即 ASCII 码（0-127）
+    Ascii,
+    /// 组值是十六进制数字下标（0-15），结果再按 hex 还原字节
+    Hex,
+    /// 组值是 base64 字母表下标（0-63），结果再按 base64 还原字节
+    Base64Index,
+    /// 组值是小写字母下标（a=0 … z=25）
+    Letters,
+    /// 组值是十进制数字（0-9）
+    Digits,
+}
+
+const HEX_ALPHABET: &[u8; 16] = b"0123456789abcdef";
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+impl Alphabet {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "unicode" => Some(Alphabet::Unicode),
+            "ascii" => Some(Alphabet::Ascii),
+            "hex" => Some(Alphabet::Hex),
+            "base64_index" | "base64 index" => Some(Alphabet::Base64Index),
+            "letters" => Some(Alphabet::Letters),
+            "digits" => Some(Alphabet::Digits),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Alphabet::Unicode => "unicode",
+            Alphabet::Ascii => "ascii",
+            Alphabet::Hex => "hex",
+            Alphabet::Base64Index => "base64_index",
+            Alphabet::Letters => "letters",
+            Alphabet::Digits => "digits",
+        }
+    }
+
+    /// 组值对应的符号；超出字母表返回 None
+    pub fn symbol(&self, value: u32) -> Option<char> {
+        match self {
+            Alphabet::Unicode => char::from_u32(value),
+            Alphabet::Ascii => (value < 128).then(|| char::from(value as u8)),
+            Alphabet::Hex => HEX_ALPHABET.get(value as usize).map(|&b| char::from(b)),
+            Alphabet::Base64Index => BASE64_ALPHABET.get(value as usize).map(|&b| char::from(b)),
+            Alphabet::Letters => (value < 26).then(|| char::from(b'a' + value as u8)),
+            Alphabet::Digits => (value < 10).then(|| char::from(b'0' + value as u8)),
+        }
+    }
+
+    /// 字母表大小（Unicode/ASCII 为 None，分组大小需推断）
+    fn size(&self) -> Option<usize> {
+        match self {
+            Alphabet::Unicode | Alphabet::Ascii => None,
+            Alphabet::Hex => Some(16),
+            Alphabet::Base64Index => Some(64),
+            Alphabet::Letters => Some(26),
+            Alphabet::Digits => Some(10),
+        }
+    }
+
+    /// 全零分组是否视为填充（下标型字母表中 0 是有效符号）
+    fn zero_is_padding(&self) -> bool {
+        self.size().is_none()
+    }
+}
+
+/// 候选得分的默认门槛
+pub const DEFAULT_THRESHOLD: f64 = 15.0;
+
+/// 候选结果的接受条件，可按单次请求调整；随 `Budget` 或参数显式传给各解码函数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Acceptance {
+    /// 得分高于此值的候选才保留
+    pub threshold: f64,
+    /// 关闭可打印性过滤，保留二进制结果（8bit 二进制保留 0x80 以上的字节）
+    pub allow_binary: bool,
+}
+
+impl Default for Acceptance {
+    fn default() -> Self {
+        Self { threshold: DEFAULT_THRESHOLD, allow_binary: false }
+    }
+}
+
+impl Acceptance {
+    /// 不做任何过滤：保留每次解码尝试的原始输出（穷举模式）
+    pub fn unfiltered() -> Self {
+        Self { threshold: f64::NEG_INFINITY, allow_binary: true }
+    }
+
+    /// 得分是否达到门槛
+    pub fn passes(&self, score: f64) -> bool {
+        let ok = score > self.threshold;
+        if !ok {
+            REJECTED.with(|r| r.set(Some(r.get().map_or(score, |best| best.max(score)))));
+        }
+        ok
+    }
+
+    /// 判断解码结果是否可能有效（allow_binary 时只要求非空）
+    pub fn is_printable(&self, text: &str) -> bool {
+        if self.allow_binary {
+            return !text.is_empty();
+        }
+        is_printable(text)
+    }
+
+    /// 二进制解码接受的字节上限（不含）：allow_binary 时保留 0x80 以上的字节
+    pub fn byte_limit(&self) -> u32 {
+        if self.allow_binary { 256 } else { 128 }
+    }
+}
+
+/// 候选进度回调：每个方案族结束后收到方案族名与目前找到的全部候选
+type ProgressFn = Box<dyn FnMut(&'static str, &[DecodeResult])>;
+
+thread_local! {
+    static PROGRESS: std::cell::RefCell<Option<ProgressFn>> = const { std::cell::RefCell::new(None) };
+}
+
+/// 执行 `f` 期间，自动解码每完成一个方案族就调用 `observer`，用于向交互式客户端推送目前最佳的候选
+pub fn with_progress<T>(observer: impl FnMut(&'static str, &[DecodeResult]) + 'static, f: impl FnOnce() -> T) -> T {
+    let previous = PROGRESS.with(|p| p.replace(Some(Box::new(observer))));
+    let out = f();
+    PROGRESS.with(|p| p.replace(previous));
+    out
+}
+
+/// verbose 模式下记录的一次解码尝试
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    /// 所属方案族（同 debug 日志中的阶段名）
+    pub phase: &'static str,
+    /// 方案与参数
+    pub method: String,
+    pub elapsed: Duration,
+    /// 通过门槛的候选数
+    pub accepted: usize,
+    /// 通过门槛的最高得分
+    pub best_score: Option<f64>,
+    /// 没有候选通过时的原因
+    pub rejection: Option<String>,
+}
+
+thread_local! {
+    static ATTEMPTS: std::cell::RefCell<Option<Vec<Attempt>>> = const { std::cell::RefCell::new(None) };
+    /// 当前尝试中未超过门槛的最高得分
+    static REJECTED: std::cell::Cell<Option<f64>> = const { std::cell::Cell::new(None) };
+}
+
+/// 执行 `f` 并记下期间自动解码的每次尝试（方案、参数、耗时、拒绝原因）
+pub fn with_attempt_log<T>(f: impl FnOnce() -> T) -> (T, Vec<Attempt>) {
+    let previous = ATTEMPTS.with(|a| a.replace(Some(Vec::new())));
+    let out = f();
+    let log = ATTEMPTS.with(|a| a.replace(previous)).unwrap_or_default();
+    (out, log)
+}
+
+fn attempt_count() -> Option<usize> {
+    ATTEMPTS.with(|a| a.borrow().as_ref().map(Vec::len))
+}
+
+/// 执行一次解码尝试；记录开启时另记下参数、耗时与结果（`method` 只在记录时求值），
+/// `threshold` 用于说明没有候选通过的原因
+fn attempt(threshold: f64, method: impl FnOnce() -> String, f: impl FnOnce() -> Vec<DecodeResult>) -> Vec<DecodeResult> {
+    if attempt_count().is_none() {
+        return f();
+    }
+    let outer = REJECTED.with(|r| r.replace(None));
+    let start = Instant::now();
+    let results = f();
+    let elapsed = start.elapsed();
+    let rejected = REJECTED.with(|r| r.replace(outer));
+    let rejection = results.is_empty().then(|| match rejected {
+        Some(best) => format!("最高得分 {:.1} 未超过门槛 {:.1}", best, threshold),
+        None => "没有输出（数据不足、分组不整或出现非法字节）".to_string(),
+    });
+    let best_score = results.iter().map(|r| r.score).reduce(f64::max);
+    let record = Attempt { phase: "", method: method(), elapsed, accepted: results.len(), best_score, rejection };
+    ATTEMPTS.with(|a| {
+        if let Some(log) = a.borrow_mut().as_mut() {
+            log.push(record);
+        }
+    });
+    results
+}
+
+fn charset_label(charset: &[char]) -> String {
+    charset.iter().map(|c| format!("U+{:04X}", *c as u32)).collect::<Vec<_>>().join("+")
 }
 
-/// 判断解码结果是否可能有效
-fn is_printable(text: &str) -> bool {
+/// 判断解码结果是否可能有效：非空且可打印字符过半（不放开二进制结果时的标准）
+pub fn is_printable(text: &str) -> bool {
     if text.is_empty() {
         return false;
     }
@@ -127,36 +490,131 @@ fn is_printable(text: &str) -> bool {
     ratio > 0.5
 }
 
+/// 评分权重与额外的 flag 格式（可由配置文件调整）
+#[derive(Debug, Clone)]
+pub struct Scoring {
+    /// 可打印字符比例的权重
+    pub printable_weight: f64,
+    /// ASCII 字母数字比例的权重
+    pub alnum_weight: f64,
+    /// 含空格的奖励
+    pub space_bonus: f64,
+    /// 含 flag 格式的奖励（含 leetspeak 写法，如 `fl4g{`）
+    pub flag_bonus: f64,
+    /// 含键盘连续按键（qwer / asdf / 1234 等常见填充）的奖励
+    pub keyboard_bonus: f64,
+    /// 整体是合理的时间戳或经纬度的奖励（见 [`numeric`](super::numeric)）
+    pub numeric_bonus: f64,
+    /// 每个连续不可打印字符的惩罚
+    pub unprintable_penalty: f64,
+    /// 额外的 flag 格式（正则）；命中加 flag_bonus，整体匹配视为已验证结果
+    pub flag_patterns: Vec<regex::Regex>,
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Self {
+            printable_weight: 30.0,
+            alnum_weight: 30.0,
+            space_bonus: 10.0,
+            flag_bonus: 50.0,
+            keyboard_bonus: 5.0,
+            numeric_bonus: 25.0,
+            unprintable_penalty: 5.0,
+            flag_patterns: Vec::new(),
+        }
+    }
+}
+
+static SCORING: OnceLock<RwLock<Scoring>> = OnceLock::new();
+
+fn scoring_lock() -> &'static RwLock<Scoring> {
+    SCORING.get_or_init(|| RwLock::new(Scoring::default()))
+}
+
+/// 当前评分配置
+pub fn scoring() -> std::sync::RwLockReadGuard<'static, Scoring> {
+    scoring_lock().read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 替换评分配置（配置重载时调用）
+pub fn set_scoring(new: Scoring) {
+    *scoring_lock().write().unwrap_or_else(|e| e.into_inner()) = new;
+}
+
+/// 信息量达到这么多比特，比例类得分才按满额计算
+pub const MIN_INFO_BITS: f64 = 16.0;
+/// 长度奖励上限
+const MAX_LENGTH_BONUS: f64 = 20.0;
+
+/// 文本的信息量（比特）：按文本自身的字符频率计算的香农熵乘以长度。
+/// 单个字符或同一字符的重复（"A"、"AAAA"）为 0，"ok" 为 2
+pub fn info_bits(text: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in text.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let len: usize = counts.values().sum();
+    counts
+        .values()
+        .map(|&n| {
+            let p = n as f64 / len as f64;
+            -(n as f64) * p.log2()
+        })
+        .sum()
+}
+
 /// 为解码结果打分
-fn score(text: &str) -> f64 {
+///
+/// 可打印/字母数字比例按信息量打折（不足 [`MIN_INFO_BITS`] 时线性衰减），长度奖励也按信息量
+/// 计算，避免 "A"、"ok" 这类极短解码凭满额比例压过真正较长的消息：
+///
+/// ```
+/// use zw_mcp_server::zw_core::engine::score;
+///
+/// assert!(score("A") < score("x=3;y=4;z=5"));
+/// assert!(score("ok") < score("p@ss:7Xq!"));
+/// assert!(score("AAAAAAAA") < score("hello"));
+/// ```
+pub fn score(text: &str) -> f64 {
     if text.is_empty() {
         return 0.0;
     }
+    let w = scoring();
     let len = text.chars().count();
+    let bits = info_bits(text);
+    let confidence = (bits / MIN_INFO_BITS).min(1.0);
     let mut s = 0.0;
 
     // 可打印比例
     let printable = text.chars().filter(|c| !c.is_control()).count();
-    s += (printable as f64 / len as f64) * 30.0;
+    s += (printable as f64 / len as f64) * w.printable_weight * confidence;
 
     // ASCII 字母数字比例
     let alnum = text.chars().filter(|c| c.is_ascii_alphanumeric()).count();
-    s += (alnum as f64 / len as f64) * 30.0;
+    s += (alnum as f64 / len as f64) * w.alnum_weight * confidence;
 
-    // 长度奖励
-    if len >= 3 {
-        s += (len.min(20)) as f64;
-    }
+    // 长度奖励：每 2 比特信息量 1 分
+    s += (bits / 2.0).min(MAX_LENGTH_BONUS);
 
-    // 空格
-    if text.contains(' ') {
-        s += 10.0;
+    // 空格（CTF 明文常用下划线代替空格，如 h3ll0_w0rld）
+    if text.contains(' ') || has_underscore_words(text) {
+        s += w.space_bonus;
     }
 
     // CTF flag 格式
-    let flags = ["flag{", "ctf{", "FLAG{", "CTF{", "key{", "KEY{"];
-    if flags.iter().any(|f| text.contains(f)) {
-        s += 50.0;
+    if has_flag_prefix(text) || w.flag_patterns.iter().any(|re| re.is_match(text)) {
+        s += w.flag_bonus;
+    }
+
+    // 键盘连按填充
+    if has_keyboard_walk(text) {
+        s += w.keyboard_bonus;
+    }
+
+    // 坐标、时间戳等数值载荷
+    if numeric::plausible(text) {
+        s += w.numeric_bonus;
     }
 
     // 连续不可打印惩罚
@@ -170,17 +628,130 @@ fn score(text: &str) -> f64 {
             cur_unp = 0;
         }
     }
-    s -= (max_unp as f64) * 5.0;
+    s -= (max_unp as f64) * w.unprintable_penalty;
 
     s.max(0.0)
 }
 
+/// leetspeak 还原为字母（4→a, 3→e, 1→l, 0→o, 5→s, 7→t, @→a, $→s）
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '3' => 'e',
+        '1' | '!' | '|' => 'l',
+        '0' => 'o',
+        '5' | '$' => 's',
+        '7' => 't',
+        '9' => 'g',
+        _ => c.to_ascii_lowercase(),
+    }
+}
+
+/// 内置 flag 前缀：(单词, 能否作为更长名称的后缀)。`ctf` 常接在赛事名之后（`DASCTF{`、`picoCTF{`），
+/// `flag`、`key` 前面必须是词边界，否则 `monkey{` 之类的普通文本也会被当作 flag
+const FLAG_PREFIXES: [(&str, bool); 3] = [("flag", false), ("ctf", true), ("key", false)];
+
+/// 是否含 `flag{` / `ctf{` / `key{` 前缀，允许大小写混写与 leetspeak（`Fl4g{`、`c7f{`）
+pub fn has_flag_prefix(text: &str) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    chars.iter().enumerate().filter(|(_, &c)| c == '{').any(|(i, _)| {
+        FLAG_PREFIXES.iter().any(|&(word, as_suffix)| {
+            let n = word.chars().count();
+            i >= n
+                && chars[i - n..i].iter().map(|&c| unleet(c)).eq(word.chars())
+                && (as_suffix || i == n || !is_word(chars[i - n - 1]))
+        })
+    })
+}
+
+/// 是否以下划线连接多个单词（至少两个下划线、每段都是字母数字且没有空格）
+fn has_underscore_words(text: &str) -> bool {
+    if text.contains(' ') {
+        return false;
+    }
+    let body = text.trim_end_matches('}');
+    let body = body.rsplit('{').next().unwrap_or(body);
+    let parts: Vec<&str> = body.split('_').collect();
+    parts.len() >= 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.len() <= 16 && p.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// 键盘同一行上连续 4 个及以上按键（正向或反向）
+fn has_keyboard_walk(text: &str) -> bool {
+    const ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+    let lower: Vec<char> = text.chars().map(|c| c.to_ascii_lowercase()).collect();
+    lower.windows(4).any(|w| {
+        let s: String = w.iter().collect();
+        let rev: String = w.iter().rev().collect();
+        ROWS.iter().any(|row| row.contains(&s) || row.contains(&rev))
+    })
+}
+
+/// 判断文本是否整体就是一个 CTF flag，如 `flag{...}` / `DASCTF{...}`；前缀须是内置的 flag/ctf/key
+/// （其他格式通过配置的 flag_patterns 识别）
+pub fn is_full_flag(text: &str) -> bool {
+    let text = text.trim();
+    let Some(open) = text.find('{') else {
+        return false;
+    };
+    let prefix = &text[..open];
+    let inner = &text[open + 1..];
+    (2..=16).contains(&prefix.len())
+        && prefix.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && has_flag_prefix(&text[..=open])
+        && inner.len() >= 2
+        && inner.ends_with('}')
+        && inner[..inner.len() - 1]
+            .chars()
+            .all(|c| c.is_ascii_graphic() && c != '{' && c != '}' || c == ' ')
+}
+
+/// 从解码文本中提取 flag: `前缀{...}`，前缀须是 flag/ctf/key（允许 leetspeak，见 [`has_flag_prefix`]），
+/// 另加配置中的 flag 格式；按出现顺序去重
+pub fn find_flags(text: &str) -> Vec<String> {
+    static BUILTIN: OnceLock<regex::Regex> = OnceLock::new();
+    let re = BUILTIN.get_or_init(|| {
+        regex::Regex::new(r"[A-Za-z0-9_\-@$!|]{2,16}\{[^{}\n]{1,256}\}").unwrap()
+    });
+    let mut found: Vec<(usize, String)> = Vec::new();
+    for m in re.find_iter(text) {
+        if has_flag_prefix(m.as_str()) {
+            found.push((m.start(), m.as_str().to_string()));
+        }
+    }
+    for pattern in &scoring().flag_patterns {
+        for m in pattern.find_iter(text) {
+            found.push((m.start(), m.as_str().to_string()));
+        }
+    }
+    found.sort_by_key(|(pos, _)| *pos);
+    let mut seen = std::collections::HashSet::new();
+    found.into_iter().map(|(_, f)| f).filter(|f| seen.insert(f.clone())).collect()
+}
+
+/// 已验证的高置信度结果：解码文本整体就是一个完整 flag
+pub fn is_verified(result: &DecodeResult) -> bool {
+    is_full_flag(&result.decoded) || is_configured_flag(&result.decoded)
+}
+
+/// 解码文本整体匹配配置中的某个 flag 格式
+fn is_configured_flag(text: &str) -> bool {
+    let text = text.trim();
+    scoring()
+        .flag_patterns
+        .iter()
+        .any(|re| re.find(text).is_some_and(|m| m.start() == 0 && m.end() == text.len()))
+}
+
 // --- 方案1: Unicode Tags ---
-pub fn decode_unicode_tags(text: &str) -> Option<DecodeResult> {
+pub fn decode_unicode_tags(text: &str, acceptance: &Acceptance) -> Option<DecodeResult> {
     let mut result = String::new();
     for ch in text.chars() {
         let cp = ch as u32;
-        if cp >= UNICODE_TAGS_START && cp <= super::chars::UNICODE_TAGS_END {
+        if (UNICODE_TAGS_START..=super::chars::UNICODE_TAGS_END).contains(&cp) {
             let ascii = cp - UNICODE_TAGS_START;
             if ascii > 0 && ascii < 128 {
                 if let Some(c) = char::from_u32(ascii) {
@@ -189,7 +760,7 @@ pub fn decode_unicode_tags(text: &str) -> Option<DecodeResult> {
             }
         }
     }
-    if result.is_empty() || !is_printable(&result) {
+    if result.is_empty() || !acceptance.is_printable(&result) {
         return None;
     }
     let s = score(&result);
@@ -197,11 +768,65 @@ pub fn decode_unicode_tags(text: &str) -> Option<DecodeResult> {
         method: "Unicode Tags (U+E0000-U+E007F)".to_string(),
         decoded: result,
         score: s,
+        scheme: Some(Scheme::UnicodeTags),
+        ..Default::default()
     })
 }
 
+/// 按载体位置分段解码 Unicode Tags
+///
+/// 附着在不同载体字符上的 Tag 串（如每个 emoji 后藏一个词）分别解码，
+/// 只有出现两段及以上时才返回结果（单段与整体拼接相同）。
+pub fn decode_unicode_tag_runs(text: &str, acceptance: &Acceptance) -> Vec<DecodeResult> {
+    // (载体字符, 载体字符索引, 解码文本)
+    let mut runs: Vec<(Option<char>, usize, String)> = Vec::new();
+    let mut carrier: (Option<char>, usize) = (None, 0);
+    let mut in_run = false;
+    for (i, ch) in text.chars().enumerate() {
+        if is_unicode_tag(ch) {
+            if !in_run {
+                runs.push((carrier.0, carrier.1, String::new()));
+                in_run = true;
+            }
+            let ascii = ch as u32 - UNICODE_TAGS_START;
+            if ascii > 0 && ascii < 127 {
+                if let (Some(c), Some(run)) = (char::from_u32(ascii), runs.last_mut()) {
+                    run.2.push(c);
+                }
+            }
+        } else {
+            in_run = false;
+            if !is_zero_width(ch) {
+                carrier = (Some(ch), i);
+            }
+        }
+    }
+
+    runs.retain(|r| !r.2.is_empty());
+    if runs.len() < 2 {
+        return vec![];
+    }
+    runs.iter()
+        .enumerate()
+        .filter(|(_, r)| acceptance.is_printable(&r.2))
+        .map(|(k, (carrier, pos, decoded))| {
+            let carrier_desc = match carrier {
+                Some(c) => format!("载体 U+{:04X} @{}", *c as u32, pos),
+                None => "文本开头".to_string(),
+            };
+            DecodeResult {
+                method: format!("Unicode Tags 第{}/{}段 ({})", k + 1, runs.len(), carrier_desc),
+                decoded: decoded.clone(),
+                score: score(decoded),
+                scheme: Some(Scheme::UnicodeTags),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
 // --- 方案2: Steganographr (neatnik.net) ---
-pub fn decode_steganographr(text: &str) -> Option<DecodeResult> {
+pub fn decode_steganographr(text: &str, acceptance: &Acceptance) -> Option<DecodeResult> {
     const WJ: char = '\u{2060}';
     const ZWSP: char = '\u{200B}';
     const ZWNJ: char = '\u{200C}';
@@ -236,7 +861,7 @@ pub fn decode_steganographr(text: &str) -> Option<DecodeResult> {
         }
     }
 
-    if result.is_empty() || !is_printable(&result) {
+    if result.is_empty() || !acceptance.is_printable(&result) {
         return None;
     }
     let s = score(&result);
@@ -244,15 +869,40 @@ pub fn decode_steganographr(text: &str) -> Option<DecodeResult> {
         method: "Steganographr (WJ+ZWSP+ZWNJ)".to_string(),
         decoded: result,
         score: s,
+        scheme: Some(Scheme::Steganographr),
+        ..Default::default()
+    })
+}
+
+// --- 方案2a: StegCloak (KuroLabs) ---
+
+/// StegCloak：零宽字符几乎都属于其 6 字符集时，按其格式解压（加密载荷用 `options` 中的口令解密）
+pub fn decode_stegcloak(text: &str, options: &stegcloak::Options, acceptance: &Acceptance) -> Option<DecodeResult> {
+    if !stegcloak::looks_like(text) {
+        return None;
+    }
+    let revealed = stegcloak::reveal(text, options.password.as_deref()).ok()?;
+    if !acceptance.is_printable(&revealed.message) {
+        return None;
+    }
+    Some(DecodeResult {
+        method: format!("StegCloak ({})", revealed.mode.describe()),
+        score: score(&revealed.message),
+        decoded: revealed.message,
+        scheme: Some(Scheme::StegCloak { mode: revealed.mode }),
+        ..Default::default()
     })
 }
 
 // --- 方案3: 直接二进制 ---
+/// `offset` 为跳过比特流开头的位数（用于前缀有噪声/错位的情况）
 pub fn decode_direct_binary(
     zw_seq: &[char],
     zero_char: char,
     one_char: char,
     bits: usize,
+    offset: usize,
+    acceptance: &Acceptance,
 ) -> Option<DecodeResult> {
     let binary: String = zw_seq
         .iter()
@@ -267,157 +917,582 @@ pub fn decode_direct_binary(
         })
         .collect();
 
-    if binary.len() < bits {
+    if binary.len() < bits + offset {
         return None;
     }
 
+    let byte_limit = acceptance.byte_limit();
     let mut result = String::new();
-    for chunk in binary.as_bytes().chunks(bits) {
+    for chunk in binary.as_bytes()[offset..].chunks(bits) {
         if chunk.len() < bits {
             break;
         }
         let s = std::str::from_utf8(chunk).unwrap_or("");
         if let Ok(value) = u32::from_str_radix(s, 2) {
-            if value > 0 && value < 128 {
+            if value > 0 && value < byte_limit {
                 result.push(char::from_u32(value).unwrap_or('?'));
             }
         }
     }
 
-    if result.is_empty() || !is_printable(&result) {
+    if result.is_empty() || !acceptance.is_printable(&result) {
         return None;
     }
 
     let s = score(&result);
     let z_code = format!("U+{:04X}", zero_char as u32);
     let o_code = format!("U+{:04X}", one_char as u32);
+    let offset_desc = if offset > 0 { format!(", 偏移={}", offset) } else { String::new() };
     Some(DecodeResult {
-        method: format!("二进制 ({}=0, {}=1, {}bit)", z_code, o_code, bits),
+        method: format!("二进制 ({}=0, {}=1, {}bit{})", z_code, o_code, bits, offset_desc),
         decoded: result,
         score: s,
+        scheme: Some(Scheme::Binary { zero: zero_char, one: one_char, bits, offset }),
+        ..Default::default()
     })
 }
 
+/// 分组大小推断结果
+#[derive(Debug, Clone)]
+pub struct GroupInference {
+    pub group_size: usize,
+    /// 分组值的字符类别分布相对典型文本的 χ²（已按样本数归一化，越小越像文本）
+    pub chi_square: f64,
+    /// 分组值落在可打印字符上的比例
+    pub printable_ratio: f64,
+    /// 序列长度能否被分组大小整除
+    pub divisible: bool,
+    /// 综合代价（越小越可能）
+    pub cost: f64,
+}
+
+impl GroupInference {
+    pub fn describe(&self) -> String {
+        format!(
+            "χ²={:.2}, 可打印={:.0}%, {}",
+            self.chi_square,
+            self.printable_ratio * 100.0,
+            if self.divisible { "整除" } else { "不整除" }
+        )
+    }
+}
+
+/// 字符类别: 空格 / 小写 / 大写 / 数字 / 标点 / 其他文字 / 无效
+fn char_class(value: u32) -> usize {
+    match char::from_u32(value) {
+        Some(' ') | Some('\n') | Some('\r') | Some('\t') => 0,
+        Some(c) if c.is_ascii_lowercase() => 1,
+        Some(c) if c.is_ascii_uppercase() => 2,
+        Some(c) if c.is_ascii_digit() => 3,
+        Some(c) if c.is_ascii_punctuation() => 4,
+        Some(c) if !c.is_control() && !is_zero_width(c) && value >= 0xA0 => 5,
+        _ => 6,
+    }
+}
+
+/// 典型文本的字符类别期望比例（与 `char_class` 对应）
+const EXPECTED_CLASS_RATIO: [f64; 7] = [0.12, 0.55, 0.08, 0.07, 0.12, 0.05, 0.01];
+
+/// 通过统计推断 N进制的分组大小
+///
+/// 对每个候选分组大小，把数字流切块求值，计算字符类别分布相对典型文本的 χ²，
+/// 再结合可打印比例和序列长度的整除性排序。候选范围从「能表示 ASCII」
+/// 到「能表示 16bit 码点 + 1」。
+pub fn infer_group_sizes(digits: &[usize], base: usize, lsb_first: bool, acceptance: &Acceptance) -> Vec<GroupInference> {
+    if base < 2 || digits.is_empty() {
+        return vec![];
+    }
+    let bits_per_digit = (base as f64).log2();
+    let min_size = (7.0 / bits_per_digit).ceil().max(1.0) as usize;
+    let max_size = (((16.0 / bits_per_digit).ceil() as usize) + 1).min(16);
+
+    let mut candidates = Vec::new();
+    for group_size in min_size..=max_size {
+        let mut counts = [0usize; 7];
+        // 解出的零宽字符是嵌套的下一层载荷，不参与类别分布，也不算无效
+        let mut nested = 0usize;
+        for chunk in digits.chunks_exact(group_size) {
+            let value = group_value(chunk, base, lsb_first);
+            // 全零分组视为填充
+            if value > 0 {
+                match char::from_u32(value) {
+                    Some(c) if is_zero_width(c) => nested += 1,
+                    _ => counts[char_class(value)] += 1,
+                }
+            }
+        }
+        let n: usize = counts.iter().sum();
+        if n + nested == 0 {
+            continue;
+        }
+        let chi_square: f64 = if n == 0 {
+            0.0
+        } else {
+            counts
+                .iter()
+                .zip(EXPECTED_CLASS_RATIO.iter())
+                .map(|(&obs, &ratio)| {
+                    let expected = ratio * n as f64;
+                    (obs as f64 - expected).powi(2) / expected
+                })
+                .sum::<f64>()
+                / n as f64
+        };
+        let printable_ratio = 1.0 - counts[6] as f64 / (n + nested) as f64;
+        let divisible = digits.len().is_multiple_of(group_size);
+        let cost = chi_square + (1.0 - printable_ratio) * 10.0 + if divisible { 0.0 } else { 1.0 };
+        candidates.push(GroupInference {
+            group_size,
+            chi_square,
+            printable_ratio,
+            divisible,
+            cost,
+        });
+    }
+    if !acceptance.allow_binary {
+        candidates.retain(|c| c.printable_ratio > 0.5);
+    }
+    candidates.sort_by(|a, b| a.cost.partial_cmp(&b.cost).unwrap_or(std::cmp::Ordering::Equal));
+    candidates
+}
+
 // --- 方案4: N进制映射 (330k 风格) ---
-pub fn decode_nary(zw_seq: &[char], charset: &[char]) -> Vec<DecodeResult> {
+pub fn decode_nary(zw_seq: &[char], charset: &[char], acceptance: &Acceptance) -> Vec<DecodeResult> {
     let base = charset.len();
     if base < 2 {
         return vec![];
     }
 
-    let char_to_digit: HashMap<char, usize> = charset.iter().enumerate().map(|(i, &c)| (c, i)).collect();
-    let digits: Vec<usize> = zw_seq.iter().filter_map(|c| char_to_digit.get(c).copied()).collect();
-
+    let digits = nary_digits(zw_seq, charset);
     if digits.is_empty() {
         return vec![];
     }
 
-    // 计算每个字符需要多少个零宽字符
-    let chars_per_unicode = ((16.0f64) / (base as f64).log2()).ceil() as usize;
-
+    let chars_desc: Vec<String> = charset.iter().map(|c| format!("U+{:04X}", *c as u32)).collect();
     let mut results = Vec::new();
-    let try_sizes: Vec<usize> = {
-        let mut s = vec![chars_per_unicode];
-        if chars_per_unicode > 1 { s.push(chars_per_unicode - 1); }
-        s.push(chars_per_unicode + 1);
-        for extra in [4, 5, 6, 7, 8] {
-            if !s.contains(&extra) { s.push(extra); }
-        }
-        s
-    };
-
-    for group_size in try_sizes {
-        if group_size < 1 || group_size > 16 {
-            continue;
-        }
-        let mut text = String::new();
-        let mut ok = true;
-        for chunk in digits.chunks(group_size) {
-            if chunk.len() < group_size {
-                break;
-            }
-            let mut value = 0u32;
-            for &d in chunk {
-                value = value.saturating_mul(base as u32).saturating_add(d as u32);
-            }
-            if value > 0 && value < 0x110000 {
-                if let Some(c) = char::from_u32(value) {
-                    text.push(c);
-                } else {
-                    ok = false;
-                    break;
+    // 先高位在前（encode_330k 的写法），再低位在前
+    for lsb_first in [false, true] {
+        for inferred in infer_group_sizes(&digits, base, lsb_first, acceptance).into_iter().take(4) {
+            let group_size = inferred.group_size;
+            if let Some(text) = nary_group_text(&digits, base, group_size, lsb_first, acceptance) {
+                let s = score(&text);
+                if acceptance.passes(s) {
+                    results.push(DecodeResult {
+                        method: format!(
+                            "{}进制 ({}, 分组={}{})",
+                            base,
+                            chars_desc.join("+"),
+                            group_size,
+                            if lsb_first { ", 低位在前" } else { "" }
+                        ),
+                        decoded: text,
+                        score: s,
+                        meta: vec![
+                            ("group_size", group_size.to_string()),
+                            ("digit_order", digit_order_name(lsb_first).to_string()),
+                            ("group_inference", inferred.describe()),
+                        ],
+                        scheme: Some(Scheme::Nary { charset: charset.to_vec(), group_size, lsb_first }),
+                    });
                 }
-            } else if value == 0 {
-                continue;
-            } else {
-                ok = false;
-                break;
-            }
-        }
-        if ok && !text.is_empty() && is_printable(&text) {
-            let s = score(&text);
-            if s > 15.0 {
-                let chars_desc: Vec<String> = charset.iter().map(|c| format!("U+{:04X}", *c as u32)).collect();
-                results.push(DecodeResult {
-                    method: format!("{}进制 ({}, 分组={})", base, chars_desc.join("+"), group_size),
-                    decoded: text,
-                    score: s,
-                });
             }
         }
     }
     results
 }
 
-// --- 方案5: 分段二进制 ---
-pub fn decode_segmented_binary(
-    segments: &[String],
-    zero_char: char,
-    one_char: char,
-    bits: usize,
+/// 已知分组大小的 N进制解码（命名预设方案使用，不做推断）
+pub fn decode_nary_fixed(
+    zw_seq: &[char],
+    charset: &[char],
+    group_size: usize,
+    lsb_first: bool,
+    scheme: &str,
+    acceptance: &Acceptance,
 ) -> Option<DecodeResult> {
-    let mut result = String::new();
-    for seg in segments {
-        let binary: String = seg
-            .chars()
-            .filter_map(|c| {
-                if c == zero_char {
-                    Some('0')
-                } else if c == one_char {
-                    Some('1')
-                } else {
-                    None
-                }
-            })
-            .collect();
-        if binary.len() != bits {
-            return None;
-        }
-        if let Ok(value) = u32::from_str_radix(&binary, 2) {
-            if value > 0 && value < 128 {
-                result.push(char::from_u32(value).unwrap_or('?'));
-            } else {
-                return None;
-            }
-        } else {
-            return None;
-        }
-    }
-
-    if result.is_empty() || !is_printable(&result) {
+    let base = charset.len();
+    if base < 2 || group_size == 0 {
         return None;
     }
-
-    let s = score(&result);
-    let z_code = format!("U+{:04X}", zero_char as u32);
-    let o_code = format!("U+{:04X}", one_char as u32);
+    let digits = nary_digits(zw_seq, charset);
+    let text = nary_group_text(&digits, base, group_size, lsb_first, acceptance)?;
+    let s = score(&text);
+    Some(DecodeResult {
+        method: format!(
+            "{} ({}进制, 分组={}{})",
+            scheme,
+            base,
+            group_size,
+            if lsb_first { ", 低位在前" } else { "" }
+        ),
+        decoded: text,
+        score: s,
+        meta: vec![
+            ("group_size", group_size.to_string()),
+            ("digit_order", digit_order_name(lsb_first).to_string()),
+        ],
+        scheme: Some(Scheme::Nary { charset: charset.to_vec(), group_size, lsb_first }),
+    })
+}
+
+/// 按预设解码: 分组大小已知时直接使用（位序未知则两种都试），否则统计推断
+pub fn decode_preset(zw_seq: &[char], preset: &Preset, acceptance: &Acceptance) -> Vec<DecodeResult> {
+    let Some(group_size) = preset.group_size else {
+        return decode_nary(zw_seq, &preset.chars, acceptance);
+    };
+    let orders = match preset.digit_order {
+        Some(order) => vec![order.lsb_first()],
+        None => vec![false, true],
+    };
+    orders
+        .into_iter()
+        .filter_map(|lsb_first| decode_nary_fixed(zw_seq, &preset.chars, group_size, lsb_first, &preset.name, acceptance))
+        .collect()
+}
+
+/// 按用户给定的码点→数字映射解码（映射以外的字符忽略，可含可见字符）
+///
+/// `group_size` 为 None 时: 下标型字母表取恰好能表示字母表的最小分组，
+/// Unicode/ASCII 取统计推断的最佳分组。hex/base64 结果能还原成可打印文本时，
+/// 还原结果作为 decoded，符号串记入元数据。
+pub fn decode_mapped(
+    text: &str,
+    mapping: &[(char, usize)],
+    group_size: Option<usize>,
+    alphabet: Alphabet,
+    acceptance: &Acceptance,
+) -> Result<DecodeResult, String> {
+    let base = mapping.iter().map(|&(_, d)| d + 1).max().unwrap_or(0).max(2);
+    let lookup: HashMap<char, usize> = mapping.iter().copied().collect();
+    let digits: Vec<usize> = text.chars().filter_map(|c| lookup.get(&c).copied()).collect();
+    if digits.is_empty() {
+        return Err("文本中没有映射表里的字符".to_string());
+    }
+    let group_size = match (group_size, alphabet.size()) {
+        (Some(g), _) => g,
+        (None, Some(size)) => {
+            let mut g = 1;
+            while base.pow(g as u32) < size {
+                g += 1;
+            }
+            g
+        }
+        (None, None) => infer_group_sizes(&digits, base, false, acceptance)
+            .first()
+            .map(|g| g.group_size)
+            .ok_or("无法推断分组大小，请指定 group_size")?,
+    };
+    if group_size == 0 {
+        return Err("group_size 必须大于 0".to_string());
+    }
+
+    let mut symbols = String::new();
+    for chunk in digits.chunks_exact(group_size) {
+        let value = group_value(chunk, base, false);
+        if value == 0 && alphabet.zero_is_padding() {
+            continue;
+        }
+        let c = alphabet
+            .symbol(value)
+            .ok_or_else(|| format!("分组值 {} 超出 {} 字母表范围", value, alphabet.name()))?;
+        symbols.push(c);
+    }
+    if symbols.is_empty() {
+        return Err("所有分组都是填充（全零）".to_string());
+    }
+
+    let mut meta = vec![
+        ("base", base.to_string()),
+        ("group_size", group_size.to_string()),
+        ("alphabet", alphabet.name().to_string()),
+    ];
+    let remainder = digits.len() % group_size;
+    if remainder != 0 {
+        meta.push(("leftover_digits", remainder.to_string()));
+    }
+    let restored = match alphabet {
+        Alphabet::Hex => hex_to_bytes(&symbols),
+        Alphabet::Base64Index => super::ingest::decode_base64(&symbols).ok(),
+        _ => None,
+    }
+    .and_then(|bytes| String::from_utf8(bytes).ok())
+    .filter(|t| acceptance.is_printable(t));
+    let decoded = match restored {
+        Some(t) => {
+            meta.push(("symbols", symbols));
+            t
+        }
+        None => symbols,
+    };
+    let desc: Vec<String> = mapping.iter().map(|(c, d)| format!("U+{:04X}={}", *c as u32, d)).collect();
+    Ok(DecodeResult {
+        method: format!("自定义映射 ({}, 分组={}, {})", desc.join(" "), group_size, alphabet.name()),
+        score: score(&decoded),
+        decoded,
+        meta,
+        scheme: Some(Scheme::Mapped { mapping: mapping.to_vec(), base, group_size, alphabet }),
+    })
+}
+
+/// 下标型字母表结果的降权系数（字母/数字串总能拿到不低的字母数字分）
+const INDEX_ALPHABET_PENALTY: f64 = 0.8;
+
+/// 把数字流按常见字母表的下标解释: hex、base64、字母 (a=0)、数字
+///
+/// 分组取能表示该字母表的最小分组，任一分组越界即放弃该字母表；
+/// hex/base64 只保留能还原成可打印文本的结果。
+pub fn decode_index_alphabets(zw_seq: &[char], charset: &[char], acceptance: &Acceptance) -> Vec<DecodeResult> {
+    if charset.len() < 2 {
+        return vec![];
+    }
+    let text: String = zw_seq.iter().collect();
+    let mapping: Vec<(char, usize)> = charset.iter().enumerate().map(|(d, &c)| (c, d)).collect();
+    let chars_desc: Vec<String> = charset.iter().map(|c| format!("U+{:04X}", *c as u32)).collect();
+    let mut results = Vec::new();
+    for alphabet in [Alphabet::Hex, Alphabet::Base64Index, Alphabet::Letters, Alphabet::Digits] {
+        let Ok(mut r) = decode_mapped(&text, &mapping, None, alphabet, acceptance) else {
+            continue;
+        };
+        match alphabet {
+            Alphabet::Hex | Alphabet::Base64Index => {
+                if !r.meta.iter().any(|(k, _)| *k == "symbols") {
+                    continue;
+                }
+            }
+            _ => {
+                if r.decoded.chars().count() < 4 {
+                    continue;
+                }
+                r.score *= INDEX_ALPHABET_PENALTY;
+            }
+        }
+        if !acceptance.passes(r.score) {
+            continue;
+        }
+        let group_size = match &r.scheme {
+            Some(Scheme::Mapped { group_size, .. }) => *group_size,
+            _ => 0,
+        };
+        r.method = format!(
+            "{}进制→{} 下标 ({}, 分组={})",
+            charset.len(),
+            alphabet.name(),
+            chars_desc.join("+"),
+            group_size
+        );
+        results.push(r);
+    }
+    results
+}
+
+pub fn hex_to_bytes(hex: &str) -> Option<Vec<u8>> {
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect()
+}
+
+/// 按字符集把零宽序列映射成数字流（字符集以外的字符忽略）
+pub fn nary_digits(zw_seq: &[char], charset: &[char]) -> Vec<usize> {
+    let char_to_digit: HashMap<char, usize> = charset.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+    zw_seq.iter().filter_map(|c| char_to_digit.get(c).copied()).collect()
+}
+
+fn digit_order_name(lsb_first: bool) -> &'static str {
+    if lsb_first { "lsb_first" } else { "msb_first" }
+}
+
+/// 一组数字的数值；`lsb_first` 为 true 时第一个数字是最低位
+pub fn group_value(chunk: &[usize], base: usize, lsb_first: bool) -> u32 {
+    let fold = |value: u32, &d: &usize| value.saturating_mul(base as u32).saturating_add(d as u32);
+    if lsb_first {
+        chunk.iter().rev().fold(0, fold)
+    } else {
+        chunk.iter().fold(0, fold)
+    }
+}
+
+/// 按固定分组把数字流还原为文本（全零分组视为填充）
+fn nary_group_text(digits: &[usize], base: usize, group_size: usize, lsb_first: bool, acceptance: &Acceptance) -> Option<String> {
+    let mut text = String::new();
+    for chunk in digits.chunks_exact(group_size) {
+        let value = group_value(chunk, base, lsb_first);
+        if value == 0 {
+            continue;
+        }
+        text.push(char::from_u32(value)?);
+    }
+    if text.is_empty() || !acceptance.is_printable(&text) {
+        return None;
+    }
+    Some(text)
+}
+
+// --- 方案5: 分段二进制 ---
+pub fn decode_segmented_binary(
+    segments: &[String],
+    zero_char: char,
+    one_char: char,
+    bits: usize,
+    acceptance: &Acceptance,
+) -> Option<DecodeResult> {
+    let byte_limit = acceptance.byte_limit();
+    let mut result = String::new();
+    for seg in segments {
+        let binary: String = seg
+            .chars()
+            .filter_map(|c| {
+                if c == zero_char {
+                    Some('0')
+                } else if c == one_char {
+                    Some('1')
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if binary.len() != bits {
+            return None;
+        }
+        if let Ok(value) = u32::from_str_radix(&binary, 2) {
+            if value > 0 && value < byte_limit {
+                result.push(char::from_u32(value).unwrap_or('?'));
+            } else {
+                return None;
+            }
+        } else {
+            return None;
+        }
+    }
+
+    if result.is_empty() || !acceptance.is_printable(&result) {
+        return None;
+    }
+
+    let s = score(&result);
+    let z_code = format!("U+{:04X}", zero_char as u32);
+    let o_code = format!("U+{:04X}", one_char as u32);
     Some(DecodeResult {
         method: format!("分段二进制 ({}=0, {}=1, {}bit/段)", z_code, o_code, bits),
         decoded: result,
         score: s,
+        scheme: Some(Scheme::SegmentedBinary { zero: zero_char, one: one_char, bits }),
+        ..Default::default()
     })
 }
 
+// --- 莫尔斯码 ---
+
+/// 点与划中较少的一种至少占点划总数的这么多分之一；文字的莫尔斯码两者都常见，
+/// 只出现一两次的“划”多半是混进二进制流的杂散字符
+const MORSE_MIN_SYMBOL_SHARE: usize = 10;
+/// 自动解码时莫尔斯码结果至少要有这么多个字符；更短的点划序列几乎总能凑出码表中的字母
+const MORSE_MIN_LETTERS: usize = 4;
+
+/// 自动解码时的合理性检查：点与划用量相当；字母数足够；以点开头和以划开头的字母都要有
+/// （Steganographr 的 WJ 被当成划时只出现在每组末尾，全部字母都以点开头）
+fn morse_plausible(symbols: &[char], codes: &[Option<String>], dot: char, dash: char) -> bool {
+    let dots = symbols.iter().filter(|&&c| c == dot).count();
+    let dashes = symbols.iter().filter(|&&c| c == dash).count();
+    let letters: Vec<&String> = codes.iter().flatten().collect();
+    dots.min(dashes) * MORSE_MIN_SYMBOL_SHARE >= dots + dashes
+        && letters.len() >= MORSE_MIN_LETTERS
+        && letters.iter().any(|c| c.starts_with('.'))
+        && letters.iter().any(|c| c.starts_with('-'))
+}
+
+fn morse_result(codes: &[Option<String>], method: String, scheme: Scheme, acceptance: &Acceptance) -> Option<DecodeResult> {
+    let decoded = morse::decode(codes)?;
+    if !acceptance.is_printable(&decoded) {
+        return None;
+    }
+    Some(DecodeResult { method, score: score(&decoded), decoded, scheme: Some(scheme), ..Default::default() })
+}
+
+fn morse_code(symbols: &[char], dot: char) -> String {
+    symbols.iter().map(|&c| if c == dot { '.' } else { '-' }).collect()
+}
+
+/// 莫尔斯码: `dot`/`dash` 为点与划，`separator` 分隔字母，连续两个分隔符为词间空格。
+/// 零宽序列中出现这三种以外的字符、或有三个以上连续分隔符时不尝试；`strict` 时另做合理性检查（自动解码用）
+pub fn decode_morse(zw_seq: &[char], dot: char, dash: char, separator: char, strict: bool, acceptance: &Acceptance) -> Option<DecodeResult> {
+    if zw_seq.iter().any(|c| ![dot, dash, separator].contains(c)) {
+        return None;
+    }
+    let codes: Vec<Option<String>> = zw_seq
+        .split(|&c| c == separator)
+        .map(|group| (!group.is_empty()).then(|| morse_code(group, dot)))
+        .collect();
+    // 分隔符最多连续两个（词间空格），更长的连续分隔符说明分配不对或这是别的编码
+    let start = zw_seq.iter().position(|&c| c != separator).unwrap_or(zw_seq.len());
+    if zw_seq[start..].windows(3).any(|w| w.iter().all(|&c| c == separator))
+        || (strict && !morse_plausible(zw_seq, &codes, dot, dash))
+    {
+        return None;
+    }
+    morse_result(
+        &codes,
+        format!("莫尔斯码 (U+{:04X}=点, U+{:04X}=划, U+{:04X}=分隔)", dot as u32, dash as u32, separator as u32),
+        Scheme::Morse { dot, dash, separator: Some(separator) },
+        acceptance,
+    )
+}
+
+/// 分段莫尔斯码: 只有点、划两种字符，被可见文本隔开的每段是一个字母
+pub fn decode_morse_segments(segments: &[String], dot: char, dash: char, strict: bool, acceptance: &Acceptance) -> Option<DecodeResult> {
+    let symbols: Vec<char> = segments.iter().flat_map(|s| s.chars()).collect();
+    if symbols.iter().any(|&c| c != dot && c != dash) {
+        return None;
+    }
+    let codes: Vec<Option<String>> = segments.iter().map(|s| Some(morse_code(&s.chars().collect::<Vec<_>>(), dot))).collect();
+    if strict && !morse_plausible(&symbols, &codes, dot, dash) {
+        return None;
+    }
+    morse_result(
+        &codes,
+        format!("分段莫尔斯码 (U+{:04X}=点, U+{:04X}=划, 每段一个字母)", dot as u32, dash as u32),
+        Scheme::Morse { dot, dash, separator: None },
+        acceptance,
+    )
+}
+
+/// 在文本实际用到的字符上暴力尝试莫尔斯码：恰好三种字符时枚举点/划/分隔的分配，
+/// 恰好两种字符时按段（可见文本分隔字母）枚举点/划。Unicode Tags 不参与。
+/// `strict` 见 `decode_morse`；指定 method=morse 时不做合理性检查，短消息（如 SOS）也能解出
+pub fn brute_morse(text: &str, top_chars: &[char], budget: &Budget, strict: bool) -> Vec<DecodeResult> {
+    let mut results = Vec::new();
+    if top_chars.iter().any(|&c| is_unicode_tag(c)) {
+        return results;
+    }
+    match *top_chars {
+        [a, b, c] => {
+            let zw_all = extract_all(text);
+            for (dot, dash, separator) in [(a, b, c), (b, a, c), (a, c, b), (c, a, b), (b, c, a), (c, b, a)] {
+                if budget.expired() {
+                    break;
+                }
+                results.append(&mut attempt(
+                    budget.acceptance.threshold,
+                    || format!("莫尔斯码 U+{:04X}=点 U+{:04X}=划 U+{:04X}=分隔", dot as u32, dash as u32, separator as u32),
+                    || decode_morse(&zw_all, dot, dash, separator, strict, &budget.acceptance).into_iter().filter(|r| budget.acceptance.passes(r.score)).collect(),
+                ));
+            }
+        }
+        [a, b] => {
+            let segments = extract_segments(text);
+            for (dot, dash) in [(a, b), (b, a)] {
+                results.append(&mut attempt(
+                    budget.acceptance.threshold,
+                    || format!("分段莫尔斯码 U+{:04X}=点 U+{:04X}=划", dot as u32, dash as u32),
+                    || decode_morse_segments(&segments, dot, dash, strict, &budget.acceptance).into_iter().filter(|r| budget.acceptance.passes(r.score)).collect(),
+                ));
+            }
+        }
+        _ => {}
+    }
+    results
+}
+
 // ============================================================
 // 编码
 // ============================================================
@@ -467,6 +1542,33 @@ pub fn encode_steganographr(message: &str, cover: &str) -> String {
     }
 }
 
+/// 莫尔斯码编码使用的点、划与分隔符
+pub const MORSE_CHARSET: [char; 3] = ['\u{200B}', '\u{200C}', '\u{2060}'];
+
+/// 莫尔斯码编码：ZWSP=点、ZWNJ=划，每个字母后跟一个 WJ，词间再多一个 WJ；
+/// 码表以外的字符被丢弃（调用方可先用 `morse::unsupported` 检查）
+pub fn encode_morse(message: &str, cover: &str) -> String {
+    let [dot, dash, separator] = MORSE_CHARSET;
+    let mut encoded = String::new();
+    for (i, word) in morse::words(message).iter().enumerate() {
+        if i > 0 {
+            encoded.push(separator);
+        }
+        for code in word {
+            encoded.extend(code.chars().map(|c| if c == '.' { dot } else { dash }));
+            encoded.push(separator);
+        }
+    }
+    if cover.len() > 1 {
+        let mid = cover.chars().count() / 2;
+        let prefix: String = cover.chars().take(mid).collect();
+        let suffix: String = cover.chars().skip(mid).collect();
+        format!("{}{}{}", prefix, encoded, suffix)
+    } else {
+        encoded
+    }
+}
+
 /// Unicode Tags 编码
 pub fn encode_tags(message: &str, cover: &str) -> String {
     let mut encoded = String::new();
@@ -517,151 +1619,971 @@ pub fn encode_330k(message: &str, cover: &str, charset: &[char]) -> String {
     }
 }
 
+/// 按预设的字符集做 N进制编码（不插入载体）
+///
+/// 每个字符固定 `group_size` 位，未指定时取能覆盖 8bit 的最少位数；位序未指定时高位在前。
+pub fn encode_preset(message: &str, preset: &Preset) -> String {
+    let base = preset.chars.len();
+    if base < 2 {
+        return String::new();
+    }
+    let group_size = preset.group_size.unwrap_or_else(|| {
+        let mut n = 1;
+        while base.pow(n as u32) < 256 {
+            n += 1;
+        }
+        n
+    });
+    let lsb_first = preset.digit_order.is_some_and(|o| o.lsb_first());
+    let mut encoded = String::new();
+    for ch in message.chars() {
+        let mut val = ch as usize;
+        let mut digits = Vec::with_capacity(group_size);
+        for _ in 0..group_size {
+            digits.push(val % base);
+            val /= base;
+        }
+        if !lsb_first {
+            digits.reverse();
+        }
+        encoded.extend(digits.into_iter().map(|d| preset.chars[d]));
+    }
+    encoded
+}
+
+/// 载体文本中的显式插入标记
+pub const COVER_MARKER: &str = "{{ZW}}";
+
+/// 把载荷按顺序平均切分后注入载体中的每个标记处（标记数多于载荷字符时，多余标记直接删除）
+pub fn inject_at_markers(cover: &str, payload: &str, marker: &str) -> String {
+    let parts: Vec<&str> = cover.split(marker).collect();
+    let slots = parts.len() - 1;
+    if slots == 0 {
+        return cover.to_string();
+    }
+    let payload: Vec<char> = payload.chars().collect();
+    let (base, extra) = (payload.len() / slots, payload.len() % slots);
+    let mut out = String::with_capacity(cover.len() + payload.len() * 4);
+    let mut pos = 0;
+    for (i, part) in parts.iter().enumerate() {
+        out.push_str(part);
+        if i < slots {
+            let take = base + usize::from(i < extra);
+            out.extend(&payload[pos..pos + take]);
+            pos += take;
+        }
+    }
+    out
+}
+
+/// 把多段独立载荷分别放入载体: 标记数与载荷数相同时逐一对应，
+/// 否则均匀分布在载体中（各段之间至少隔一个可见字符）；载体过短时返回 None
+pub fn place_sections(cover: &str, sections: &[String], marker: &str) -> Option<String> {
+    let parts: Vec<&str> = cover.split(marker).collect();
+    if parts.len() - 1 == sections.len() {
+        let mut out = String::new();
+        for (i, part) in parts.iter().enumerate() {
+            out.push_str(part);
+            if let Some(section) = sections.get(i) {
+                out.push_str(section);
+            }
+        }
+        return Some(out);
+    }
+    let cover: Vec<char> = cover.replace(marker, "").chars().collect();
+    let k = sections.len();
+    if cover.len() < k + 1 {
+        return None;
+    }
+    let mut out = String::new();
+    let mut prev = 0;
+    for (i, section) in sections.iter().enumerate() {
+        let pos = (i + 1) * cover.len() / (k + 1);
+        out.extend(&cover[prev..pos]);
+        out.push_str(section);
+        prev = pos;
+    }
+    out.extend(&cover[prev..]);
+    Some(out)
+}
+
+/// 载体在给定密度（每 100 个可见字符允许的不可见字符数）下可容纳的不可见字符数
+pub fn density_capacity(cover: &str, density: f64) -> usize {
+    let visible = cover.chars().filter(|&c| !is_zero_width(c) && !c.is_control()).count();
+    (visible as f64 * density / 100.0).floor() as usize
+}
+
+/// 把载荷均匀打散到载体的各个插入点（优先选择空白/标点后的词边界）
+pub fn spread_payload(cover: &str, payload: &str) -> String {
+    let chars: Vec<char> = cover.chars().collect();
+    let payload: Vec<char> = payload.chars().collect();
+    if chars.is_empty() || payload.is_empty() {
+        return chars.iter().chain(payload.iter()).collect();
+    }
+    // 插入点 = 在第 i 个字符之后插入
+    let boundaries: Vec<usize> = (0..chars.len() - 1)
+        .filter(|&i| chars[i].is_whitespace() || chars[i].is_ascii_punctuation() || "，。、；：！？".contains(chars[i]))
+        .collect();
+    let gaps: Vec<usize> = if boundaries.len() >= 2 {
+        boundaries
+    } else {
+        (0..chars.len() - 1).collect()
+    };
+    let slots = gaps.len().clamp(1, payload.len());
+    // 在候选插入点中均匀选取 slots 个，载荷按顺序平均分配
+    let picks: Vec<usize> = (0..slots).map(|k| gaps.get(k * gaps.len() / slots).copied().unwrap_or(0)).collect();
+    let (base, extra) = (payload.len() / slots, payload.len() % slots);
+    let mut out = String::with_capacity(cover.len() + payload.len() * 4);
+    let mut pos = 0;
+    let mut slot = 0;
+    for (i, &c) in chars.iter().enumerate() {
+        out.push(c);
+        while slot < slots && picks[slot] == i {
+            let take = base + usize::from(slot < extra);
+            out.extend(&payload[pos..pos + take]);
+            pos += take;
+            slot += 1;
+        }
+    }
+    out.extend(&payload[pos..]);
+    out
+}
+
 // ============================================================
 // 自动解码引擎
 // ============================================================
 
+/// N进制分组内的数字顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigitOrder {
+    MsbFirst,
+    LsbFirst,
+}
+
+impl DigitOrder {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "msb_first" | "msb" => Some(DigitOrder::MsbFirst),
+            "lsb_first" | "lsb" => Some(DigitOrder::LsbFirst),
+            _ => None,
+        }
+    }
+
+    pub fn lsb_first(&self) -> bool {
+        *self == DigitOrder::LsbFirst
+    }
+
+    pub fn name(&self) -> &'static str {
+        digit_order_name(self.lsb_first())
+    }
+}
+
 /// 预设编码方案
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Preset {
-    pub name: &'static str,
+    pub name: String,
     pub chars: Vec<char>,
-    pub description: &'static str,
+    pub description: String,
+    /// 每个字符固定使用的零宽字符数；None 表示需要推断
+    pub group_size: Option<usize>,
+    /// 分组内的数字顺序；None 表示两种都尝试
+    #[serde(default)]
+    pub digit_order: Option<DigitOrder>,
+    /// 载荷的起止/终止标记字符（不参与数字映射）
+    #[serde(default)]
+    pub terminator: Option<char>,
+    /// 原工具或算法说明的链接
+    #[serde(default)]
+    pub reference: Option<String>,
 }
 
-pub fn encoding_presets() -> Vec<(&'static str, Preset)> {
+/// 内置预设
+fn builtin_presets() -> Vec<(String, Preset)> {
     vec![
-        ("330k_default", Preset {
-            name: "330k Unicode Steganography (默认4字符)",
+        ("330k_default".to_string(), Preset {
+            name: "330k Unicode Steganography (默认4字符)".to_string(),
             chars: vec!['\u{200C}', '\u{200D}', '\u{202C}', '\u{FEFF}'],
-            description: "330k.github.io 默认方案: 4字符=2bit编码",
+            description: "330k.github.io 默认方案: 4字符=2bit编码，每个 UTF-16 码元 8 位四进制，高位在前".to_string(),
+            group_size: Some(8),
+            digit_order: Some(DigitOrder::MsbFirst),
+            terminator: None,
+            reference: Some("https://330k.github.io/misc_tools/unicode_steganography.html".to_string()),
         }),
-        ("steganographr", Preset {
-            name: "Steganographr (neatnik.net)",
+        ("steganographr".to_string(), Preset {
+            name: "Steganographr (neatnik.net)".to_string(),
             chars: vec!['\u{2060}', '\u{200B}', '\u{200C}'],
-            description: "WJ=分隔符, ZWSP=0, ZWNJ=1",
+            description: "WJ=分隔符, ZWSP=0, ZWNJ=1".to_string(),
+            group_size: None,
+            digit_order: Some(DigitOrder::MsbFirst),
+            terminator: Some('\u{FEFF}'),
+            reference: Some("https://neatnik.net/steganographr/".to_string()),
         }),
-        ("stegcloak", Preset {
-            name: "StegCloak",
-            chars: vec!['\u{200B}', '\u{200C}', '\u{200D}', '\u{FEFF}'],
-            description: "StegCloak 字符集",
+        ("stegcloak".to_string(), Preset {
+            name: "StegCloak".to_string(),
+            chars: stegcloak::CHARSET.to_vec(),
+            description: "ZWNJ/ZWJ/U+2061/U+2062 各 2 比特，U+2063/U+2064 代替常见双字符组合；LZUTF8 压缩，可选 AES-256-CTR 加密与 HMAC".to_string(),
+            group_size: None,
+            digit_order: None,
+            terminator: None,
+            reference: Some("https://github.com/KuroLabs/stegcloak".to_string()),
         }),
-        ("zwsp_binary", Preset {
-            name: "ZWSP Binary (基础二进制)",
+        ("zwsp_binary".to_string(), Preset {
+            name: "ZWSP Binary (基础二进制)".to_string(),
             chars: vec!['\u{200B}', '\u{200C}'],
-            description: "ZWSP=0, ZWNJ=1",
+            description: "ZWSP=0, ZWNJ=1".to_string(),
+            group_size: None,
+            digit_order: Some(DigitOrder::MsbFirst),
+            terminator: None,
+            reference: None,
         }),
-        ("common_3char", Preset {
-            name: "常见三字符方案",
+        ("common_3char".to_string(), Preset {
+            name: "常见三字符方案".to_string(),
             chars: vec!['\u{200B}', '\u{200C}', '\u{200D}'],
-            description: "ZWSP/ZWNJ/ZWJ 三字符方案",
+            description: "ZWSP/ZWNJ/ZWJ 三字符方案".to_string(),
+            group_size: None,
+            digit_order: None,
+            terminator: None,
+            reference: None,
         }),
-        ("irongeek_zw", Preset {
-            name: "Irongeek Zero-Width",
+        ("irongeek_zw".to_string(), Preset {
+            name: "Irongeek Zero-Width".to_string(),
             chars: vec!['\u{200B}', '\u{200C}', '\u{200D}', '\u{FEFF}'],
-            description: "Irongeek 推荐的最兼容零宽字符组合",
+            description: "Irongeek 推荐的最兼容零宽字符组合".to_string(),
+            group_size: None,
+            digit_order: None,
+            terminator: None,
+            reference: Some("https://www.irongeek.com/i.php?page=security/unicode-steganography-homoglyph-encoder".to_string()),
+        }),
+        ("ternary_ascii".to_string(), Preset {
+            name: "三进制 ASCII".to_string(),
+            chars: vec!['\u{200B}', '\u{200C}', '\u{200D}'],
+            description: "ZWSP=0, ZWNJ=1, ZWJ=2，每字符 5 位三进制 (3^5=243 覆盖 8bit)，高位在前".to_string(),
+            group_size: Some(5),
+            digit_order: Some(DigitOrder::MsbFirst),
+            terminator: None,
+            reference: None,
+        }),
+        ("quinary_zw".to_string(), Preset {
+            name: "五进制零宽方案".to_string(),
+            chars: vec!['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}'],
+            description: "ZWSP/ZWNJ/ZWJ/WJ/BOM 依次为 0-4，每字符 4 位五进制 (5^4=625 覆盖 8bit)，高位在前".to_string(),
+            group_size: Some(4),
+            digit_order: Some(DigitOrder::MsbFirst),
+            terminator: None,
+            reference: None,
+        }),
+        ("senary_invisible_math".to_string(), Preset {
+            name: "六进制不可见数学符号方案".to_string(),
+            chars: vec!['\u{200C}', '\u{200D}', '\u{2061}', '\u{2062}', '\u{2063}', '\u{2064}'],
+            description: "ZWNJ/ZWJ/FUNCTION APPLICATION/INVISIBLE TIMES/SEPARATOR/PLUS 依次为 0-5，\"隐形墨水\"类机器人常用字符组，每字符 4 位六进制 (6^4=1296)，高位在前".to_string(),
+            group_size: Some(4),
+            digit_order: Some(DigitOrder::MsbFirst),
+            terminator: None,
+            reference: None,
         }),
     ]
 }
 
-/// 自动尝试所有方案解码
-pub fn auto_decode(text: &str) -> Vec<DecodeResult> {
+/// 运行时添加的自定义预设数量上限
+const MAX_CUSTOM_PRESETS: usize = 64;
+
+static CUSTOM_PRESETS: OnceLock<Mutex<Vec<(String, Preset)>>> = OnceLock::new();
+
+fn custom_presets() -> std::sync::MutexGuard<'static, Vec<(String, Preset)>> {
+    CUSTOM_PRESETS
+        .get_or_init(|| Mutex::new(Vec::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// 所有预设：内置预设在前，运行时添加的自定义预设在后
+pub fn encoding_presets() -> Vec<(String, Preset)> {
+    let mut presets = builtin_presets();
+    presets.extend(custom_presets().iter().cloned());
+    presets
+}
+
+/// 运行时添加的自定义预设 key
+pub fn custom_preset_keys() -> Vec<String> {
+    custom_presets().iter().map(|(k, _)| k.clone()).collect()
+}
+
+/// 添加或替换自定义预设；返回 true 表示替换了已有的同名自定义预设
+pub fn add_preset(key: &str, preset: Preset) -> Result<bool, String> {
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        return Err(format!("预设 key 只能包含小写字母、数字和下划线: {}", key));
+    }
+    if builtin_presets().iter().any(|(k, _)| k == key) {
+        return Err(format!("不能覆盖内置预设: {}", key));
+    }
+    if preset.chars.len() < 2 {
+        return Err("预设至少需要 2 个字符".to_string());
+    }
+    let mut custom = custom_presets();
+    if let Some(slot) = custom.iter_mut().find(|(k, _)| k == key) {
+        slot.1 = preset;
+        return Ok(true);
+    }
+    if custom.len() >= MAX_CUSTOM_PRESETS {
+        return Err(format!("自定义预设已达上限 ({})", MAX_CUSTOM_PRESETS));
+    }
+    custom.push((key.to_string(), preset));
+    Ok(false)
+}
+
+/// 删除自定义预设；内置预设不可删除
+pub fn remove_preset(key: &str) -> bool {
+    let mut custom = custom_presets();
+    let before = custom.len();
+    custom.retain(|(k, _)| k != key);
+    custom.len() != before
+}
+
+/// 预设字符集与文本中实际出现的零宽字符的吻合程度
+#[derive(Debug, Clone)]
+pub struct PresetMatch {
+    /// 落在预设字符集（含终止符）内的零宽字符数
+    pub matched: usize,
+    /// 文本中的零宽字符总数
+    pub total: usize,
+    /// 预设之外的码点及次数
+    pub unmatched: Vec<(u32, usize)>,
+    /// 预设中有、文本中没出现的字符
+    pub unused: Vec<char>,
+}
+
+impl PresetMatch {
+    /// 覆盖率（0-100）
+    pub fn coverage(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.matched as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+pub fn match_preset(analysis: &Analysis, preset: &Preset) -> PresetMatch {
+    let in_preset = |cp: u32| preset.chars.iter().chain(preset.terminator.iter()).any(|&c| c as u32 == cp);
+    let mut matched = 0;
+    let mut unmatched = Vec::new();
+    for (&cp, &count) in &analysis.distribution {
+        if in_preset(cp) {
+            matched += count;
+        } else {
+            unmatched.push((cp, count));
+        }
+    }
+    let unused = preset
+        .chars
+        .iter()
+        .copied()
+        .filter(|c| !analysis.distribution.contains_key(&(*c as u32)))
+        .collect();
+    PresetMatch { matched, total: analysis.zero_width_count, unmatched, unused }
+}
+
+/// 暴力解码预算：控制尝试的字符对、字符集、偏移和位宽数量
+#[derive(Debug, Clone)]
+pub struct Budget {
+    pub name: &'static str,
+    /// 二进制暴力参与组合的高频字符数
+    pub binary_chars: usize,
+    /// 尝试的位宽
+    pub bit_widths: &'static [usize],
+    /// 二进制比特流起始偏移的尝试个数（1 = 只试偏移 0）
+    pub offsets: usize,
+    /// N进制暴力的最大进制（不含）
+    pub max_nary_base: usize,
+    /// 分段二进制参与组合的高频字符数
+    pub segment_chars: usize,
+    /// 命中已验证的高置信度结果后跳过剩余尝试
+    pub early_exit: bool,
+    /// 截止时间，超过后停止尝试并返回已找到的候选
+    pub deadline: Option<Instant>,
+    /// 候选的接受条件（门槛、是否保留二进制结果）
+    pub acceptance: Acceptance,
+    /// StegCloak 解密口令
+    pub stegcloak: stegcloak::Options,
+}
+
+impl Budget {
+    pub fn fast() -> Self {
+        Self {
+            name: "fast",
+            binary_chars: 4,
+            bit_widths: &[8],
+            offsets: 1,
+            max_nary_base: 6,
+            segment_chars: 3,
+            early_exit: true,
+            deadline: None,
+            acceptance: Acceptance::default(),
+            stegcloak: stegcloak::Options::default(),
+        }
+    }
+
+    pub fn normal() -> Self {
+        Self {
+            name: "normal",
+            binary_chars: 6,
+            bit_widths: &[8, 7],
+            offsets: 1,
+            max_nary_base: 9,
+            segment_chars: 4,
+            early_exit: true,
+            deadline: None,
+            acceptance: Acceptance::default(),
+            stegcloak: stegcloak::Options::default(),
+        }
+    }
+
+    pub fn exhaustive() -> Self {
+        Self {
+            name: "exhaustive",
+            binary_chars: 10,
+            bit_widths: &[8, 7],
+            offsets: 8,
+            max_nary_base: 12,
+            segment_chars: 6,
+            early_exit: false,
+            deadline: None,
+            acceptance: Acceptance::default(),
+            stegcloak: stegcloak::Options::default(),
+        }
+    }
+
+    /// 设置从现在起的时间上限
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    /// 设置候选的接受条件
+    pub fn with_acceptance(mut self, acceptance: Acceptance) -> Self {
+        self.acceptance = acceptance;
+        self
+    }
+
+    /// 设置 StegCloak 解密口令
+    pub fn with_stegcloak(mut self, options: stegcloak::Options) -> Self {
+        self.stegcloak = options;
+        self
+    }
+
+    /// 是否已超过时间上限
+    pub fn expired(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "fast" => Some(Self::fast()),
+            "normal" => Some(Self::normal()),
+            "exhaustive" => Some(Self::exhaustive()),
+            _ => None,
+        }
+    }
+}
+
+/// 按高频字符两两组合暴力尝试二进制
+pub fn brute_binary(zw_all: &[char], top_chars: &[char], budget: &Budget) -> Vec<DecodeResult> {
+    let mut results = Vec::new();
+    let limit = top_chars.len().min(budget.binary_chars);
+    for i in 0..limit {
+        for j in 0..limit {
+            if i == j { continue; }
+            if budget.expired() {
+                return results;
+            }
+            for &bits in budget.bit_widths {
+                for offset in 0..budget.offsets {
+                    let (zero, one) = (top_chars[i], top_chars[j]);
+                    results.append(&mut attempt(
+                        budget.acceptance.threshold,
+                        || format!("二进制 U+{:04X}=0 U+{:04X}=1, {}bit, 偏移 {}", zero as u32, one as u32, bits, offset),
+                        || decode_direct_binary(zw_all, zero, one, bits, offset, &budget.acceptance).into_iter().filter(|r| budget.acceptance.passes(r.score)).collect(),
+                    ));
+                }
+            }
+        }
+    }
+    results
+}
+
+/// 主要字符的种数范围：只有少数几种字符承载数据时才区分得出杂散字符
+const DOMINANT_SYMBOLS: std::ops::RangeInclusive<usize> = 2..=4;
+/// 主要字符中最少的一种，出现次数至少是杂散字符中最多的一种的这么多倍
+const OUTLIER_RATIO: usize = 4;
+/// 杂散字符合计最多占全部不可见字符的比例
+const OUTLIER_SHARE: f64 = 0.05;
+/// 不可见字符少于这么多个时不区分杂散字符
+const MIN_OUTLIER_SAMPLE: usize = 32;
+
+/// 低频杂散字符（编辑器插入的软连字符、零星的方向标记等）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outliers {
+    /// 承载数据的主要字符，按出现次数降序
+    pub dominant: Vec<char>,
+    /// 杂散字符及出现次数
+    pub strays: Vec<(char, usize)>,
+}
+
+impl Outliers {
+    pub fn contains(&self, ch: char) -> bool {
+        self.strays.iter().any(|&(c, _)| c == ch)
+    }
+
+    /// 形如 "U+00AD×2, U+2064×1"
+    pub fn describe(&self) -> String {
+        self.strays.iter().map(|(c, n)| format!("U+{:04X}×{}", *c as u32, n)).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// 找出低频杂散字符：2~4 种字符占绝大多数，其余每种都远少于它们
+pub fn outlier_symbols(analysis: &Analysis) -> Option<Outliers> {
+    if analysis.zero_width_count < MIN_OUTLIER_SAMPLE {
+        return None;
+    }
+    let mut freq: Vec<(char, usize)> =
+        analysis.distribution.iter().filter_map(|(&cp, &n)| Some((char::from_u32(cp)?, n))).collect();
+    freq.sort_by_key(|&(c, n)| (std::cmp::Reverse(n), c));
+    for k in DOMINANT_SYMBOLS {
+        if freq.len() <= k {
+            break;
+        }
+        let (dominant, strays) = freq.split_at(k);
+        let stray_total: usize = strays.iter().map(|&(_, n)| n).sum();
+        if dominant[k - 1].1 >= strays[0].1 * OUTLIER_RATIO
+            && (stray_total as f64) <= analysis.zero_width_count as f64 * OUTLIER_SHARE
+        {
+            return Some(Outliers { dominant: dominant.iter().map(|&(c, _)| c).collect(), strays: strays.to_vec() });
+        }
+    }
+    None
+}
+
+/// 按出现次数排序的唯一零宽字符（同频按码点，保证顺序稳定）
+pub fn top_chars(analysis: &Analysis) -> Vec<char> {
+    let mut freq: Vec<(u32, usize)> = analysis.distribution.iter().map(|(&k, &v)| (k, v)).collect();
+    freq.sort_by_key(|&(cp, count)| (std::cmp::Reverse(count), cp));
+    freq.iter().filter_map(|(cp, _)| char::from_u32(*cp)).collect()
+}
+
+/// 自动解码的方案族（`PhaseTimer` 的阶段名）与展示名，按搜索顺序排列
+const SEARCH_PHASES: [(&str, &str); 12] = [
+    ("unicode_tags", "Unicode Tags"),
+    ("steganographr", "Steganographr"),
+    ("stegcloak", "StegCloak"),
+    ("presets", "预设 N进制"),
+    ("brute_binary", "暴力二进制"),
+    ("nary", "N进制"),
+    ("morse", "莫尔斯码"),
+    ("alphabets", "字母表下标"),
+    ("clustered", "聚类字符集"),
+    ("segmented_binary", "分段二进制"),
+    ("emoji_zwj", "emoji ZWJ 通道"),
+    ("outliers", "去掉杂散字符重试"),
+];
+
+/// 在指定预算下自动尝试所有方案解码
+pub fn auto_decode(text: &str, budget: &Budget) -> Vec<DecodeResult> {
+    auto_decode_report(text, budget).0
+}
+
+/// 同 [`auto_decode`]，另返回因命中完整 flag 而提前停止时没有运行（或没有跑完）的方案族；
+/// 未提前停止时为空
+pub fn auto_decode_report(text: &str, budget: &Budget) -> (Vec<DecodeResult>, Vec<&'static str>) {
+    let mut timer = PhaseTimer::new();
     let analysis = analyze(text);
     if analysis.zero_width_count == 0 {
-        return vec![];
+        // emoji 选择通道不需要任何零宽字符
+        let mut results = emoji::decode(text, &budget.acceptance);
+        rank_results(&mut results);
+        return (results, Vec::new());
+    }
+    // 同一载荷粘贴了多份时只解码第一份，避免得到 "flag{x}flag{x}" 这样的重复明文
+    if let Some(rep) = detect_repetition(text) {
+        let (mut results, skipped) = auto_decode_report(&text[..rep.first_end], budget);
+        for r in &mut results {
+            r.meta.push(("repeated", format!("×{}", rep.copies)));
+        }
+        return (results, skipped);
     }
+    let _span = tracing::debug_span!("auto_decode", zw = analysis.zero_width_count, budget = budget.name).entered();
 
     let mut results = Vec::new();
     let zw_all = extract_all(text);
     let segments = extract_segments(text);
 
-    // 按出现次数排序的唯一零宽字符
-    let mut freq: Vec<(u32, usize)> = analysis.distribution.iter().map(|(&k, &v)| (k, v)).collect();
-    freq.sort_by(|a, b| b.1.cmp(&a.1));
-    let top_chars: Vec<char> = freq.iter().filter_map(|(cp, _)| char::from_u32(*cp)).collect();
+    let top_chars = top_chars(&analysis);
+    timer.lap("extract", &results);
 
-    // 方案1: Unicode Tags
-    if let Some(r) = decode_unicode_tags(text) {
-        results.push(r);
-    }
+    // 命中完整 flag 等高置信度结果时提前结束（exhaustive 预算除外）；超时则直接返回已有候选
+    let done = |results: &Vec<DecodeResult>| {
+        budget.expired() || (budget.early_exit && results.iter().any(is_verified))
+    };
 
-    // 方案2: Steganographr
-    if let Some(r) = decode_steganographr(text) {
-        results.push(r);
-    }
+    'search: {
+        // 方案1: Unicode Tags（整体拼接 + 按载体分段）
+        results.append(&mut attempt(budget.acceptance.threshold, || "Unicode Tags 整体拼接".to_string(), || decode_unicode_tags(text, &budget.acceptance).into_iter().collect()));
+        results.append(&mut attempt(budget.acceptance.threshold, || "Unicode Tags 按载体分段".to_string(), || decode_unicode_tag_runs(text, &budget.acceptance)));
+        timer.lap("unicode_tags", &results);
 
-    // 方案3: 预设 N进制
-    for (_, preset) in encoding_presets() {
-        let preset_in_text: Vec<char> = preset.chars.iter().copied()
-            .filter(|c| analysis.distribution.contains_key(&(*c as u32)))
-            .collect();
-        if preset_in_text.len() >= 2 {
-            let mut nary = decode_nary(&zw_all, &preset.chars);
-            results.append(&mut nary);
-        }
-    }
-
-    // 方案4: 暴力二进制
-    if top_chars.len() >= 2 {
-        let limit = top_chars.len().min(6);
-        for i in 0..limit {
-            for j in 0..limit {
-                if i == j { continue; }
-                for bits in [8, 7] {
-                    if let Some(r) = decode_direct_binary(&zw_all, top_chars[i], top_chars[j], bits) {
-                        if r.score > 15.0 {
-                            results.push(r);
-                        }
+        if done(&results) {
+            break 'search;
+        }
+
+        // 方案2: Steganographr
+        results.append(&mut attempt(budget.acceptance.threshold, || "Steganographr".to_string(), || decode_steganographr(text, &budget.acceptance).into_iter().collect()));
+        timer.lap("steganographr", &results);
+
+        // 方案2a: StegCloak（格式自带标志与压缩，几乎不会误报）
+        results.append(&mut attempt(budget.acceptance.threshold, || "StegCloak".to_string(), || decode_stegcloak(text, &budget.stegcloak, &budget.acceptance).into_iter().filter(|r| budget.acceptance.passes(r.score)).collect()));
+        timer.lap("stegcloak", &results);
+
+        if done(&results) {
+            break 'search;
+        }
+
+        // 方案3: 预设 N进制（分组已知的命名方案优先，去重时保留其方案名）
+        let mut presets = encoding_presets();
+        presets.sort_by_key(|(_, p)| p.group_size.is_none());
+        for (key, preset) in presets {
+            if budget.expired() {
+                break 'search;
+            }
+            // StegCloak 不是定长 N 进制，已在方案2a 按其格式解码
+            if key == "stegcloak" {
+                continue;
+            }
+            let preset_in_text: Vec<char> = preset.chars.iter().copied()
+                .filter(|c| analysis.distribution.contains_key(&(*c as u32)))
+                .collect();
+            if preset_in_text.len() >= 2 {
+                results.append(&mut attempt(budget.acceptance.threshold, || format!("预设 {}", preset.name), || decode_preset(&zw_all, &preset, &budget.acceptance)));
+            }
+        }
+        timer.lap("presets", &results);
+
+        if done(&results) {
+            break 'search;
+        }
+
+        // 方案4: 暴力二进制
+        if top_chars.len() >= 2 {
+            results.append(&mut brute_binary(&zw_all, &top_chars, budget));
+        }
+        timer.lap("brute_binary", &results);
+
+        if done(&results) {
+            break 'search;
+        }
+
+        // 方案5: N进制 (使用实际出现的字符)
+        if top_chars.len() >= 3 {
+            for n in 3..top_chars.len().min(budget.max_nary_base) {
+                if budget.expired() {
+                    break 'search;
+                }
+                let charset: Vec<char> = top_chars[..n].to_vec();
+                results.append(&mut attempt(
+                    budget.acceptance.threshold,
+                    || format!("{}进制 ({})", n, charset_label(&charset)),
+                    || decode_nary(&zw_all, &charset, &budget.acceptance),
+                ));
+            }
+        }
+        timer.lap("nary", &results);
+
+        if done(&results) {
+            break 'search;
+        }
+
+        // 方案5a: 莫尔斯码（只用到两三种字符时）
+        results.append(&mut brute_morse(text, &top_chars, budget, true));
+        timer.lap("morse", &results);
+
+        if done(&results) {
+            break 'search;
+        }
+
+        // 方案5b: 数字流按常见字母表下标解释（hex / base64 / 字母 / 数字）
+        if top_chars.len() >= 2 {
+            let limit = top_chars.len().min(budget.binary_chars);
+            for i in 0..limit {
+                for j in 0..limit {
+                    if i != j {
+                        let pair = [top_chars[i], top_chars[j]];
+                        results.append(&mut attempt(
+                            budget.acceptance.threshold,
+                            || format!("下标字母表 ({})", charset_label(&pair)),
+                            || decode_index_alphabets(&zw_all, &pair, &budget.acceptance),
+                        ));
                     }
                 }
             }
+            for n in 3..top_chars.len().min(budget.max_nary_base) {
+                if budget.expired() {
+                    break 'search;
+                }
+                results.append(&mut attempt(
+                    budget.acceptance.threshold,
+                    || format!("下标字母表 ({})", charset_label(&top_chars[..n])),
+                    || decode_index_alphabets(&zw_all, &top_chars[..n], &budget.acceptance),
+                ));
+            }
+            // 每个符号直接对应一个字母/数字（如 26 种零宽字符 ↔ a-z），按码点顺序排列
+            let mut sorted = top_chars.clone();
+            sorted.sort_unstable();
+            if [10, 16, 26, 64].contains(&sorted.len()) {
+                results.append(&mut attempt(
+                    budget.acceptance.threshold,
+                    || format!("下标字母表，按码点排列 ({} 种字符)", sorted.len()),
+                    || decode_index_alphabets(&zw_all, &sorted, &budget.acceptance),
+                ));
+            }
         }
-    }
+        timer.lap("alphabets", &results);
 
-    // 方案5: N进制 (使用实际出现的字符)
-    if top_chars.len() >= 3 {
-        for n in 3..top_chars.len().min(9) {
-            let charset: Vec<char> = top_chars[..n].to_vec();
-            let mut nary = decode_nary(&zw_all, &charset);
-            results.append(&mut nary);
+        if done(&results) {
+            break 'search;
         }
-    }
 
-    // 方案6: 分段二进制
-    if !segments.is_empty() && top_chars.len() >= 2 {
-        let limit = top_chars.len().min(4);
-        for i in 0..limit {
-            for j in 0..limit {
-                if i == j { continue; }
-                for bits in [8, 7] {
-                    if let Some(r) = decode_segmented_binary(&segments, top_chars[i], top_chars[j], bits) {
-                        if r.score > 15.0 {
-                            results.push(r);
-                        }
+        // 方案5c: 字符种类多时，只在按共现关系聚类出的字符集上暴力（噪声与分隔符不参与）
+        if top_chars.len() >= cluster::MIN_SYMBOLS {
+            for charset in cluster::cluster(text).candidate_charsets() {
+                if budget.expired() {
+                    break 'search;
+                }
+                let stream: Vec<char> = zw_all.iter().copied().filter(|c| charset.contains(c)).collect();
+                let mut found = brute_binary(&stream, &charset, budget);
+                if charset.len() >= 3 {
+                    found.append(&mut attempt(
+                        budget.acceptance.threshold,
+                        || format!("{}进制，共现聚类 ({})", charset.len(), charset_label(&charset)),
+                        || decode_nary(&stream, &charset, &budget.acceptance),
+                    ));
+                    found.append(&mut attempt(
+                        budget.acceptance.threshold,
+                        || format!("下标字母表，共现聚类 ({})", charset_label(&charset)),
+                        || decode_index_alphabets(&stream, &charset, &budget.acceptance),
+                    ));
+                }
+                for r in &mut found {
+                    r.meta.push(("charset_inference", "共现聚类".to_string()));
+                }
+                results.append(&mut found);
+            }
+        }
+        timer.lap("clustered", &results);
+
+        if done(&results) {
+            break 'search;
+        }
+
+        // 方案6: 分段二进制
+        if !segments.is_empty() && top_chars.len() >= 2 {
+            let limit = top_chars.len().min(budget.segment_chars);
+            for i in 0..limit {
+                for j in 0..limit {
+                    if i == j { continue; }
+                    if budget.expired() {
+                        break 'search;
+                    }
+                    for &bits in budget.bit_widths {
+                        let (zero, one) = (top_chars[i], top_chars[j]);
+                        results.append(&mut attempt(
+                            budget.acceptance.threshold,
+                            || format!("分段二进制 U+{:04X}=0 U+{:04X}=1, {}bit", zero as u32, one as u32, bits),
+                            || decode_segmented_binary(&segments, zero, one, bits, &budget.acceptance).into_iter().filter(|r| budget.acceptance.passes(r.score)).collect(),
+                        ));
                     }
                 }
             }
         }
+        timer.lap("segmented_binary", &results);
+
+        // 方案7: emoji ZWJ 通道（VS16 有无、悬空 ZWJ、相邻 emoji 是否连接、emoji 选择）
+        if !done(&results) {
+            results.append(&mut attempt(budget.acceptance.threshold, || "emoji ZWJ 通道".to_string(), || emoji::decode(text, &budget.acceptance)));
+            timer.lap("emoji_zwj", &results);
+        }
+    }
+
+    // 提前停止时，没有走到阶段末尾的方案族都算跳过（中途停止的也算）；杂散字符重试随之跳过
+    let outliers = outlier_symbols(&analysis);
+    let skipped: Vec<&'static str> = if budget.early_exit && results.iter().any(is_verified) {
+        SEARCH_PHASES
+            .iter()
+            .filter(|(family, _)| !timer.laps.contains(family) && (*family != "outliers" || outliers.is_some()))
+            .map(|(_, label)| *label)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // 方案8: 去掉低频杂散字符后重试（它们混进按频率推测的字符集会让 N 进制、下标字母表等方案失败）
+    if let Some(outliers) = outliers {
+        if !budget.expired() && !results.iter().any(is_verified) {
+            let filtered: String = text.chars().filter(|&c| !outliers.contains(c)).collect();
+            let ignored = outliers.describe();
+            let logged = attempt_count();
+            let mut retry = auto_decode(&filtered, budget);
+            // 重试中的各次尝试单独标注
+            if let Some(from) = logged {
+                ATTEMPTS.with(|a| {
+                    for record in a.borrow_mut().iter_mut().flatten().skip(from) {
+                        record.method = format!("[去掉杂散字符 {}] {}", ignored, record.method);
+                    }
+                });
+            }
+            for r in &mut retry {
+                r.meta.push(("ignored_outliers", ignored.clone()));
+            }
+            // 放在前面，去重时保留带说明的版本
+            results.splice(0..0, retry);
+        }
+    }
+    timer.lap("outliers", &results);
+
+    rank_results(&mut results);
+    tracing::debug!(
+        total_ms = timer.start.elapsed().as_secs_f64() * 1000.0,
+        candidates = results.len(),
+        expired = budget.expired(),
+        "auto_decode finished"
+    );
+    (results, skipped)
+}
+
+/// 记录各解码方案族的耗时（debug 级日志，继承调用方 span 中的请求 id 与工具名）
+struct PhaseTimer {
+    start: Instant,
+    last: Instant,
+    /// 已走到末尾的方案族
+    laps: Vec<&'static str>,
+}
+
+impl PhaseTimer {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self { start: now, last: now, laps: Vec::new() }
     }
 
-    // 去重并排序
+    fn lap(&mut self, family: &'static str, results: &[DecodeResult]) {
+        let now = Instant::now();
+        self.laps.push(family);
+        tracing::debug!(
+            family,
+            elapsed_ms = (now - self.last).as_secs_f64() * 1000.0,
+            candidates = results.len(),
+            "decode phase"
+        );
+        self.last = now;
+        // 本阶段记录的尝试归入该方案族
+        ATTEMPTS.with(|a| {
+            for record in a.borrow_mut().iter_mut().flatten().rev().take_while(|r| r.phase.is_empty()) {
+                record.phase = family;
+            }
+        });
+        PROGRESS.with(|p| {
+            if let Some(observer) = p.borrow_mut().as_mut() {
+                observer(family, results);
+            }
+        });
+    }
+}
+
+/// 多载荷分段解码: 被可见文本隔开的每段零宽序列单独自动解码
+///
+/// 只在出现 2~16 段、每段至少 16 个零宽字符时尝试（诱饵消息 + 真 flag、分层水印等）。
+pub fn decode_sections(text: &str, budget: &Budget) -> Vec<DecodeResult> {
+    // 重复粘贴的同一载荷由整体解码处理
+    if detect_repetition(text).is_some() {
+        return vec![];
+    }
+    let segments: Vec<String> = extract_segments(text)
+        .into_iter()
+        .filter(|s| s.chars().count() >= 16)
+        .collect();
+    if !(2..=16).contains(&segments.len()) {
+        return vec![];
+    }
+    let n = segments.len();
+    let mut results = Vec::new();
+    for (k, seg) in segments.iter().enumerate() {
+        if budget.expired() {
+            break;
+        }
+        if let Some(best) = auto_decode(seg, budget).into_iter().next() {
+            let mut meta = vec![("section", format!("{}/{}", k + 1, n)), ("inner_method", best.method.clone())];
+            meta.extend(best.meta);
+            results.push(DecodeResult {
+                method: format!("第{}/{}段: {}", k + 1, n, best.method),
+                decoded: best.decoded,
+                score: best.score,
+                meta,
+                scheme: None,
+            });
+        }
+    }
+    // 所有段解出同一结果时，与整体解码无异
+    if results.len() < 2 {
+        return vec![];
+    }
+    results
+}
+
+/// 嵌套解码: 解码结果里还藏着零宽字符时，对其继续自动解码
+///
+/// 返回第 2 层起的各层最佳结果（不含 `first` 本身）；`max_depth` 为总层数上限，
+/// 某层不再含零宽字符或解不出结果时停止。
+pub fn decode_layers(first: &DecodeResult, budget: &Budget, max_depth: usize) -> Vec<DecodeResult> {
+    let mut layers: Vec<DecodeResult> = Vec::new();
+    let mut current = first.decoded.clone();
+    while layers.len() + 1 < max_depth && !budget.expired() {
+        if !current.chars().any(is_zero_width) {
+            break;
+        }
+        let Some(next) = auto_decode(&current, budget).into_iter().next() else {
+            break;
+        };
+        // 防止同一载荷自我复现导致死循环
+        if next.decoded == current || layers.iter().any(|l| l.decoded == next.decoded) {
+            break;
+        }
+        current = next.decoded.clone();
+        layers.push(next);
+    }
+    layers
+}
+
+/// 按解码文本去重（保留先出现的方案），再按得分降序排序；同分按方案名排序，保证顺序稳定
+pub fn rank_results(results: &mut Vec<DecodeResult>) {
     let mut seen = std::collections::HashSet::new();
     results.retain(|r| seen.insert(r.decoded.clone()));
-    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
-    results
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.method.cmp(&b.method))
+    });
 }
 
-/// 导出原始零宽字符序列
-pub fn dump_raw(text: &str) -> String {
+/// 导出原始零宽字符序列，每个零宽字符一行（供分页/分块输出）
+pub fn dump_raw_lines(text: &str) -> Vec<String> {
     let name_map: HashMap<u32, &str> = all_zero_width_chars()
         .iter()
         .map(|z| (z.codepoint, z.name))
         .collect();
 
-    let mut out = String::from("原始零宽字符序列:\n");
+    let mut lines = Vec::new();
     for (i, ch) in text.chars().enumerate() {
         let cp = ch as u32;
         if let Some(name) = name_map.get(&cp) {
-            out.push_str(&format!("[{:4}] U+{:04X} {}\n", i, cp, name));
+            lines.push(format!("[{:4}] U+{:04X} {}", i, cp, name));
         } else if is_unicode_tag(ch) {
             let ascii = cp - UNICODE_TAGS_START;
             let display = if (32..127).contains(&ascii) {
@@ -669,7 +2591,146 @@ pub fn dump_raw(text: &str) -> String {
             } else {
                 '?'
             };
-            out.push_str(&format!("[{:4}] U+{:05X} UNICODE TAG (ASCII {} = '{}')\n", i, cp, ascii, display));
+            lines.push(format!("[{:4}] U+{:05X} UNICODE TAG (ASCII {} = '{}')", i, cp, ascii, display));
+        }
+    }
+    lines
+}
+
+/// 根据序列长度和符号种类猜测候选分组大小
+fn guess_group_sizes(len: usize, base: usize) -> Vec<usize> {
+    if base == 2 {
+        return vec![8, 7];
+    }
+    let chars_per_unicode = ((16.0f64) / (base as f64).log2()).ceil() as usize;
+    let mut sizes = vec![chars_per_unicode];
+    // 优先保留能整除序列长度的分组
+    for g in 3..=8 {
+        if len.is_multiple_of(g) && !sizes.contains(&g) {
+            sizes.push(g);
+        }
+    }
+    sizes.truncate(3);
+    sizes
+}
+
+/// 分组视图允许的最大分组大小（分组大小同时用作数字列的显示宽度）
+pub const MAX_ANNOTATE_GROUP: usize = 64;
+
+/// 带分组提示的反汇编式导出
+///
+/// 将零宽序列按候选分组大小切块，列出每块在候选映射下的数字串、数值和字符，
+/// 供人工在暴力解码「差一点」时手动收尾。指定的分组大小为 0 时改为自动猜测，
+/// 超过 [`MAX_ANNOTATE_GROUP`] 时按上限显示。
+pub fn dump_annotated(text: &str, group_size: Option<usize>) -> String {
+    let zw_all = extract_all(text);
+    let mut symbols: Vec<char> = Vec::new();
+    for &c in &zw_all {
+        if !symbols.contains(&c) {
+            symbols.push(c);
+        }
+    }
+    if symbols.len() < 2 {
+        return "分组视图: 符号种类少于 2 个，无法分组。\n".to_string();
+    }
+    if symbols.len() > 16 {
+        return format!("分组视图: 符号种类过多 ({} 个)，不像单一编码方案。\n", symbols.len());
+    }
+
+    let name_map: HashMap<char, &str> = all_zero_width_chars()
+        .iter()
+        .map(|z| (z.ch, z.name))
+        .collect();
+
+    // 候选映射: 按码点升序 / 降序赋值（二进制时即两种 0/1 指派）
+    symbols.sort();
+    let ascending = symbols.clone();
+    let mut descending = symbols.clone();
+    descending.reverse();
+
+    let base = symbols.len();
+    let sizes = match group_size {
+        Some(g) if g > 0 => vec![g.min(MAX_ANNOTATE_GROUP)],
+        _ => guess_group_sizes(zw_all.len(), base),
+    };
+
+    let mut out = format!(
+        "分组视图 (序列长度 {}, {} 种符号):\n",
+        zw_all.len(),
+        base
+    );
+    if let Some(g) = group_size.filter(|&g| g > MAX_ANNOTATE_GROUP) {
+        out.push_str(&format!("  分组大小 {} 超过上限 {}，按 {} 显示\n", g, MAX_ANNOTATE_GROUP, MAX_ANNOTATE_GROUP));
+    }
+    for &c in &ascending {
+        let name = name_map.get(&c).copied().unwrap_or("UNICODE TAG");
+        out.push_str(&format!("  U+{:04X} {}\n", c as u32, name));
+    }
+
+    for size in sizes {
+        for mapping in [&ascending, &descending] {
+            let mapping_desc: Vec<String> = mapping
+                .iter()
+                .enumerate()
+                .map(|(d, c)| format!("U+{:04X}={}", *c as u32, d))
+                .collect();
+            let remainder = zw_all.len() % size;
+            out.push_str(&format!(
+                "\n--- 分组={} 映射: {}{} ---\n",
+                size,
+                mapping_desc.join(" "),
+                if remainder > 0 {
+                    format!(" (末尾余 {} 个符号)", remainder)
+                } else {
+                    String::new()
+                }
+            ));
+            out.push_str(&format!(
+                "  {:>5}  {:>6}  {:<width$}  {:>8}  字符\n",
+                "#",
+                "偏移",
+                "数字",
+                "数值",
+                width = size
+            ));
+
+            let mut preview = String::new();
+            for (i, chunk) in zw_all.chunks(size).enumerate() {
+                let digits: String = chunk
+                    .iter()
+                    .map(|c| {
+                        let d = mapping.iter().position(|m| m == c).unwrap_or(0);
+                        std::char::from_digit(d as u32, 16).unwrap_or('?')
+                    })
+                    .collect();
+                if chunk.len() < size {
+                    out.push_str(&format!("  {:>5}  {:>6}  {:<width$}  (不完整)\n", i, i * size, digits, width = size));
+                    break;
+                }
+                // 分组很大时数值可能超出 u128，此时标注为溢出
+                let value = u128::from_str_radix(&digits, base as u32).ok();
+                let glyph = value.and_then(|v| u32::try_from(v).ok()).and_then(char::from_u32);
+                let display = match glyph {
+                    Some(c) if !c.is_control() => {
+                        preview.push(c);
+                        format!("'{}'", c)
+                    }
+                    _ => {
+                        preview.push('·');
+                        "·".to_string()
+                    }
+                };
+                out.push_str(&format!(
+                    "  {:>5}  {:>6}  {:<width$}  {:>8}  {}\n",
+                    i,
+                    i * size,
+                    digits,
+                    value.map_or_else(|| "溢出".to_string(), |v| v.to_string()),
+                    display,
+                    width = size
+                ));
+            }
+            out.push_str(&format!("  预览: {}\n", preview));
         }
     }
     out
@@ -0,0 +1,610 @@
+//! 解码方案说明 - 把一次成功的解码还原成可放进 write-up 的文字说明
+//!
+//! 按 `DecodeResult::scheme` 重放映射：列出实际使用的映射表、分组大小与位序，
+//! 并以第一个解出的字符为例，展示它由哪几个码点、经过怎样的计算得到。
+
+use std::collections::HashMap;
+
+use super::chars::{is_unicode_tag, is_zero_width, name_of, UNICODE_TAGS_START};
+use super::{morse, stegcloak};
+use super::sanitize::display;
+use super::engine::{extract_all, extract_segments, group_value, Acceptance, Alphabet, DecodeResult, Scheme};
+
+/// 推导示例中展示的单个码点（在零宽序列中的下标、字符、对应数字）
+struct Step {
+    index: usize,
+    ch: char,
+    digit: String,
+}
+
+fn cp(ch: char) -> String {
+    format!("U+{:04X}", ch as u32)
+}
+
+fn display_name(ch: char) -> String {
+    name_of(ch).unwrap_or_else(|| "未知".to_string())
+}
+
+fn mapping_table(rows: &[(char, String)]) -> String {
+    let mut out = String::from("| 码点 | 名称 | 含义 |\n|------|------|------|\n");
+    for (ch, meaning) in rows {
+        out.push_str(&format!("| {} | {} | {} |\n", cp(*ch), display_name(*ch), meaning));
+    }
+    out
+}
+
+fn render_steps(steps: &[Step]) -> String {
+    render_steps_in(steps, "零宽序列")
+}
+
+fn render_steps_in(steps: &[Step], source: &str) -> String {
+    render_symbols(steps, source, "数字")
+}
+
+fn render_symbols(steps: &[Step], source: &str, label: &str) -> String {
+    let cps: Vec<String> = steps.iter().map(|s| cp(s.ch)).collect();
+    let digits: Vec<&str> = steps.iter().map(|s| s.digit.as_str()).collect();
+    let range = match (steps.first(), steps.last()) {
+        (Some(a), Some(b)) if a.index != b.index => format!("第 {}–{} 个", a.index + 1, b.index + 1),
+        (Some(a), _) => format!("第 {} 个", a.index + 1),
+        _ => String::new(),
+    };
+    format!("{}{}字符: {}\n→ {}: {}\n", source, range, cps.join(" "), label, digits.join(" "))
+}
+
+fn char_desc(value: u32) -> String {
+    match char::from_u32(value) {
+        Some(c) if !c.is_control() => format!("{} → {:?}", value, c),
+        _ => format!("{} → (不可打印)", value),
+    }
+}
+
+/// 二进制比特流中第一个产生字符的分组
+fn binary_example(zw: &[char], zero: char, one: char, bits: usize, skip: usize) -> Option<(Vec<Step>, u32)> {
+    let stream: Vec<(usize, char, u32)> = zw
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &c)| {
+            if c == zero {
+                Some((i, c, 0))
+            } else if c == one {
+                Some((i, c, 1))
+            } else {
+                None
+            }
+        })
+        .collect();
+    stream.get(skip..)?.chunks_exact(bits).find_map(|chunk| {
+        let value = chunk.iter().fold(0u32, |v, &(_, _, b)| (v << 1) | b);
+        (value > 0 && value < 128).then(|| {
+            let steps = chunk
+                .iter()
+                .map(|&(index, ch, b)| Step { index, ch, digit: b.to_string() })
+                .collect();
+            (steps, value)
+        })
+    })
+}
+
+/// N进制数字流中第一个非填充分组，返回 (步骤, 组值, 按位展开式)
+fn group_example(
+    seq: &[(usize, char, usize)],
+    base: usize,
+    group_size: usize,
+    lsb_first: bool,
+    zero_is_padding: bool,
+) -> Option<(Vec<Step>, u32, String)> {
+    let chunk = seq.chunks_exact(group_size).find(|chunk| {
+        let digits: Vec<usize> = chunk.iter().map(|&(_, _, d)| d).collect();
+        !zero_is_padding || group_value(&digits, base, lsb_first) > 0
+    })?;
+    let digits: Vec<usize> = chunk.iter().map(|&(_, _, d)| d).collect();
+    let value = group_value(&digits, base, lsb_first);
+    let steps = chunk
+        .iter()
+        .map(|&(index, ch, d)| Step { index, ch, digit: d.to_string() })
+        .collect();
+    let n = digits.len();
+    let terms: Vec<String> = digits
+        .iter()
+        .enumerate()
+        .map(|(i, d)| {
+            let power = if lsb_first { i } else { n - 1 - i };
+            format!("{}×{}^{}", d, base, power)
+        })
+        .collect();
+    Some((steps, value, terms.join(" + ")))
+}
+
+/// 生成解码方案说明；结果没有记录方案（修复/分段结果）时返回 None
+pub fn explain(text: &str, result: &DecodeResult) -> Option<String> {
+    let scheme = result.scheme.as_ref()?;
+    let zw = extract_all(text);
+    let mut out = format!("## 解码方案: {}\n\n", result.method);
+
+    match scheme {
+        Scheme::UnicodeTags => {
+            out.push_str("每个 Unicode Tag 字符 (U+E0000–U+E007F) 直接对应一个 ASCII 字符: 码点 − 0xE0000 = ASCII 码。\n");
+            out.push_str("分组大小: 1 个码点 / 字符\n\n");
+            let first = text
+                .chars()
+                .find(|&c| is_unicode_tag(c) && (1..127).contains(&(c as u32 - UNICODE_TAGS_START)))?;
+            let value = first as u32 - UNICODE_TAGS_START;
+            out.push_str("### 推导示例\n\n");
+            out.push_str(&format!(
+                "{} ({}) → 0x{:05X} − 0xE0000 = {}\n",
+                cp(first),
+                display_name(first),
+                first as u32,
+                char_desc(value)
+            ));
+        }
+        Scheme::Steganographr => {
+            out.push_str("只保留 WJ/ZWSP/ZWNJ；以 WJ (U+2060) 分隔字符，每段内 ZWSP=0、ZWNJ=1 组成变长二进制，高位在前，值即码点。\n\n");
+            out.push_str("### 映射表\n\n");
+            out.push_str(&mapping_table(&[
+                ('\u{2060}', "字符分隔符".to_string()),
+                ('\u{200B}', "比特 0".to_string()),
+                ('\u{200C}', "比特 1".to_string()),
+            ]));
+            let mut steps = Vec::new();
+            for (i, &c) in zw.iter().enumerate() {
+                match c {
+                    '\u{200B}' | '\u{200C}' => steps.push(Step {
+                        index: i,
+                        ch: c,
+                        digit: if c == '\u{200B}' { "0" } else { "1" }.to_string(),
+                    }),
+                    '\u{2060}' if !steps.is_empty() => break,
+                    _ => {}
+                }
+            }
+            let bits: String = steps.iter().map(|s| s.digit.as_str()).collect();
+            let value = u32::from_str_radix(&bits, 2).ok()?;
+            out.push_str("\n### 推导示例\n\n");
+            out.push_str(&render_steps(&steps));
+            out.push_str(&format!("→ 0b{} = {}\n", bits, char_desc(value)));
+        }
+        Scheme::Binary { zero, one, bits, offset } => {
+            out.push_str(&format!(
+                "把零宽字符视为比特流（其他零宽字符忽略），{}每 {} 位一组，高位在前，值即 ASCII 码；全零或 ≥128 的分组跳过。\n\n",
+                if *offset > 0 { format!("跳过开头 {} 位后，", offset) } else { String::new() },
+                bits
+            ));
+            out.push_str("### 映射表\n\n");
+            out.push_str(&mapping_table(&[(*zero, "比特 0".to_string()), (*one, "比特 1".to_string())]));
+            out.push_str(&format!("\n分组大小: {} 位 / 字符\n", bits));
+            let (steps, value) = binary_example(&zw, *zero, *one, *bits, *offset)?;
+            let bin: String = steps.iter().map(|s| s.digit.as_str()).collect();
+            out.push_str("\n### 推导示例\n\n");
+            out.push_str(&render_steps(&steps));
+            out.push_str(&format!("→ 0b{} = {}\n", bin, char_desc(value)));
+        }
+        Scheme::SegmentedBinary { zero, one, bits } => {
+            out.push_str(&format!(
+                "零宽字符被可见文本分隔成若干段，每段恰好 {} 个比特编码一个字符，高位在前。\n\n",
+                bits
+            ));
+            out.push_str("### 映射表\n\n");
+            out.push_str(&mapping_table(&[(*zero, "比特 0".to_string()), (*one, "比特 1".to_string())]));
+            out.push_str(&format!("\n分组大小: {} 位 / 段\n", bits));
+            let first = extract_segments(text).into_iter().next()?;
+            let seg: Vec<char> = first.chars().collect();
+            let (steps, value) = binary_example(&seg, *zero, *one, *bits, 0)?;
+            let bin: String = steps.iter().map(|s| s.digit.as_str()).collect();
+            out.push_str("\n### 推导示例（第 1 段）\n\n");
+            out.push_str(&render_steps(&steps));
+            out.push_str(&format!("→ 0b{} = {}\n", bin, char_desc(value)));
+        }
+        Scheme::Nary { charset, group_size, lsb_first } => {
+            let base = charset.len();
+            out.push_str(&format!(
+                "{} 种零宽字符按字符集下标映射为 {} 进制数字，每 {} 个数字一组，{}，组值即码点；全零分组视为填充。\n\n",
+                base,
+                base,
+                group_size,
+                if *lsb_first { "低位在前" } else { "高位在前" }
+            ));
+            out.push_str("### 映射表\n\n");
+            let rows: Vec<(char, String)> = charset.iter().enumerate().map(|(d, &c)| (c, format!("数字 {}", d))).collect();
+            out.push_str(&mapping_table(&rows));
+            out.push_str(&format!("\n分组大小: {} 个数字 / 字符\n", group_size));
+
+            let seq: Vec<(usize, char, usize)> = zw
+                .iter()
+                .enumerate()
+                .filter_map(|(i, c)| charset.iter().position(|x| x == c).map(|d| (i, *c, d)))
+                .collect();
+            let (steps, value, terms) = group_example(&seq, base, *group_size, *lsb_first, true)?;
+            out.push_str("\n### 推导示例\n\n");
+            out.push_str(&render_steps(&steps));
+            out.push_str(&format!("→ {} = {}\n", terms, char_desc(value)));
+        }
+        Scheme::Mapped { mapping, base, group_size, alphabet } => {
+            let interpret = match alphabet {
+                Alphabet::Unicode => "组值即 Unicode 码点；全零分组视为填充",
+                Alphabet::Ascii => "组值即 ASCII 码；全零分组视为填充",
+                Alphabet::Hex => "组值是十六进制数字 0-f 的下标，拼出的 hex 串再还原为字节",
+                Alphabet::Base64Index => "组值是 base64 字母表 (A-Za-z0-9+/) 的下标，拼出的 base64 串再还原为字节",
+                Alphabet::Letters => "组值是小写字母下标（a=0 … z=25）",
+                Alphabet::Digits => "组值即十进制数字 0-9",
+            };
+            out.push_str(&format!(
+                "按指定映射把字符转为 {} 进制数字（映射以外的字符忽略），每 {} 个数字一组，高位在前；{}。\n\n",
+                base, group_size, interpret
+            ));
+            out.push_str("### 映射表\n\n");
+            let rows: Vec<(char, String)> = mapping.iter().map(|&(c, d)| (c, format!("数字 {}", d))).collect();
+            out.push_str(&mapping_table(&rows));
+            out.push_str(&format!("\n分组大小: {} 个数字 / 符号\n", group_size));
+
+            let seq: Vec<(usize, char, usize)> = text
+                .chars()
+                .enumerate()
+                .filter_map(|(i, c)| mapping.iter().find(|(m, _)| *m == c).map(|&(_, d)| (i, c, d)))
+                .collect();
+            let padding = matches!(alphabet, Alphabet::Unicode | Alphabet::Ascii);
+            let (steps, value, terms) = group_example(&seq, *base, *group_size, false, padding)?;
+            let symbol = match (alphabet, alphabet.symbol(value)) {
+                (Alphabet::Unicode | Alphabet::Ascii, _) => char_desc(value),
+                (_, Some(c)) => format!("{} → 符号 '{}'", value, c),
+                (_, None) => format!("{} → (超出字母表)", value),
+            };
+            out.push_str("\n### 推导示例\n\n");
+            out.push_str(&render_steps_in(&steps, "文本"));
+            out.push_str(&format!("→ {} = {}\n", terms, symbol));
+        }
+        Scheme::Morse { dot, dash, separator } => {
+            let mut rows = vec![(*dot, "点 .".to_string()), (*dash, "划 -".to_string())];
+            match separator {
+                Some(sep) => {
+                    out.push_str("两种零宽字符表示莫尔斯码的点与划，第三种分隔字母，连续两个分隔符表示词间空格；点划串按国际莫尔斯码表还原为大写字母、数字与标点。\n\n");
+                    rows.push((*sep, "字母分隔符".to_string()));
+                }
+                None => out.push_str("两种零宽字符表示莫尔斯码的点与划，被可见文本隔开的每段零宽序列是一个字母；点划串按国际莫尔斯码表还原为大写字母、数字与标点。\n\n"),
+            }
+            out.push_str("### 映射表\n\n");
+            out.push_str(&mapping_table(&rows));
+            // 第一个字母: 分隔符模式取第一个分隔符前的点划，分段模式取第一段
+            let (source, symbols): (&str, Vec<(usize, char)>) = match separator {
+                Some(sep) => {
+                    let start = zw.iter().position(|c| c != sep)?;
+                    let symbols = zw[start..]
+                        .iter()
+                        .take_while(|c| *c != sep)
+                        .enumerate()
+                        .map(|(i, &c)| (start + i, c))
+                        .collect();
+                    ("零宽序列", symbols)
+                }
+                None => ("第 1 段", extract_segments(text).into_iter().next()?.chars().enumerate().collect()),
+            };
+            let steps: Vec<Step> = symbols
+                .iter()
+                .map(|&(index, ch)| Step { index, ch, digit: if ch == *dot { "." } else { "-" }.to_string() })
+                .collect();
+            let code: String = steps.iter().map(|s| s.digit.as_str()).collect();
+            out.push_str("\n### 推导示例\n\n");
+            out.push_str(&render_symbols(&steps, source, "点划"));
+            out.push_str(&match morse::letter(&code) {
+                Some(c) => format!("→ {} = '{}'\n", code, c),
+                None => format!("→ {} = (码表中没有)\n", code),
+            });
+        }
+        Scheme::StegCloak { mode } => {
+            out.push_str("消息先按 LZUTF8 压缩；加密时用 PBKDF2-SHA512(口令, 16 字节盐) 派生 IV 与密钥做 AES-256-CTR，带校验时在密文前加 HMAC-SHA256。");
+            out.push_str("字节每 2 比特一个字符（高位在前），最前面是标志字符；出现最多的两种双字符组合由 U+2063 / U+2064 代替，被代替的组合写在流的最前面。\n\n");
+            out.push_str("### 映射表\n\n");
+            let mut rows: Vec<(char, String)> =
+                stegcloak::CHARSET[..4].iter().enumerate().map(|(d, &c)| (c, format!("比特 {:02b}", d))).collect();
+            rows.push((stegcloak::CHARSET[4], "代替双字符组合 1".to_string()));
+            rows.push((stegcloak::CHARSET[5], "代替双字符组合 2".to_string()));
+            out.push_str(&mapping_table(&rows));
+            let layout = stegcloak::layout(text)?;
+            let pair = |p: [char; 2]| format!("{} {}", cp(p[0]), cp(p[1]));
+            out.push_str("\n### 流结构\n\n");
+            out.push_str(&format!("- 头部: U+2063 = {}，U+2064 = {}\n", pair(layout.first), pair(layout.second)));
+            out.push_str(&format!("- 标志字符: {} → {}\n", cp(layout.flag), mode.describe()));
+            out.push_str(&format!("- 数据: {} 字节（盐、HMAC 与压缩后的消息），压缩与加密后的字节与明文字符没有一一对应关系\n", layout.data_bytes));
+        }
+    }
+
+    out.push_str(&format!("\n### 完整结果\n\n{}\n", display(&result.decoded)));
+    Some(out)
+}
+
+/// 方案中每个参与解码的字符及其对应的符号（比特/数字/分隔符）
+fn symbol_map(scheme: &Scheme, ch: char) -> Option<String> {
+    match scheme {
+        Scheme::UnicodeTags => is_unicode_tag(ch).then(|| format!("{:02X}", ch as u32 - UNICODE_TAGS_START)),
+        Scheme::Steganographr => match ch {
+            '\u{200B}' => Some("0".to_string()),
+            '\u{200C}' => Some("1".to_string()),
+            '\u{2060}' => Some("|".to_string()),
+            _ => None,
+        },
+        Scheme::Binary { zero, one, .. } | Scheme::SegmentedBinary { zero, one, .. } => {
+            if ch == *zero {
+                Some("0".to_string())
+            } else if ch == *one {
+                Some("1".to_string())
+            } else {
+                None
+            }
+        }
+        Scheme::Nary { charset, .. } => charset.iter().position(|&c| c == ch).map(|d| d.to_string()),
+        Scheme::Mapped { mapping, .. } => mapping.iter().find(|(c, _)| *c == ch).map(|(_, d)| d.to_string()),
+        Scheme::StegCloak { .. } => stegcloak::CHARSET.iter().position(|&c| c == ch).map(|d| match d {
+            4 => "A".to_string(),
+            5 => "B".to_string(),
+            d => d.to_string(),
+        }),
+        Scheme::Morse { dot, dash, separator } => {
+            if ch == *dot {
+                Some(".".to_string())
+            } else if ch == *dash {
+                Some("-".to_string())
+            } else if Some(ch) == *separator {
+                Some("|".to_string())
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// 把升序下标压缩成区间列表，如 "3-10, 15, 20-31"
+fn compress_ranges(indices: &[usize]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < indices.len() {
+        let start = indices[i];
+        let mut end = start;
+        while i + 1 < indices.len() && indices[i + 1] == end + 1 {
+            i += 1;
+            end = indices[i];
+        }
+        parts.push(if start == end { start.to_string() } else { format!("{}-{}", start, end) });
+        i += 1;
+    }
+    parts.join(", ")
+}
+
+/// 对齐视图中的一组符号及其解出的字符
+struct Aligned {
+    /// 参与的字符在原文中的下标
+    positions: Vec<usize>,
+    symbols: Vec<String>,
+    value: u32,
+    /// 解出的字符；None 表示该组被跳过（填充、超出范围或分隔）
+    output: Option<char>,
+}
+
+fn aligned(group: &[(usize, String)], value: u32, output: Option<char>) -> Aligned {
+    Aligned {
+        positions: group.iter().map(|(i, _)| *i).collect(),
+        symbols: group.iter().map(|(_, s)| s.clone()).collect(),
+        value,
+        output,
+    }
+}
+
+fn bits_value(group: &[(usize, String)]) -> u32 {
+    group.iter().fold(0u32, |v, (_, b)| (v << 1) | u32::from(b == "1"))
+}
+
+fn digits_of(group: &[(usize, String)]) -> Vec<usize> {
+    group.iter().map(|(_, d)| d.parse().unwrap_or(0)).collect()
+}
+
+/// 按段切分符号流：两个符号之间夹有可见字符即为新的一段
+fn segment_groups(text: &str, stream: &[(usize, String)]) -> Vec<Vec<(usize, String)>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut groups: Vec<Vec<(usize, String)>> = Vec::new();
+    let mut prev: Option<usize> = None;
+    for item in stream {
+        let split = prev.is_none_or(|p| chars[p + 1..item.0].iter().any(|&c| !is_zero_width(c)));
+        if split {
+            groups.push(Vec::new());
+        }
+        if let Some(g) = groups.last_mut() {
+            g.push(item.clone());
+        }
+        prev = Some(item.0);
+    }
+    groups
+}
+
+/// 按方案把符号流切成组，逐组还原出字符（与解码器的分组、跳过规则一致）
+fn alignment(text: &str, scheme: &Scheme, stream: &[(usize, String)], acceptance: &Acceptance) -> Vec<Aligned> {
+    let byte_limit = acceptance.byte_limit();
+    let binary_char = |v: u32| (v > 0 && v < byte_limit).then(|| char::from_u32(v)).flatten();
+    match scheme {
+        Scheme::UnicodeTags => stream
+            .iter()
+            .map(|item| {
+                let value = u32::from_str_radix(&item.1, 16).unwrap_or(0);
+                let output = char::from_u32(value).filter(|c| c.is_ascii() && !c.is_control());
+                aligned(std::slice::from_ref(item), value, output)
+            })
+            .collect(),
+        Scheme::Steganographr => stream
+            .split(|(_, s)| s == "|")
+            .filter(|g| !g.is_empty())
+            .map(|g| {
+                let value = bits_value(g);
+                aligned(g, value, char::from_u32(value))
+            })
+            .collect(),
+        Scheme::Binary { bits, offset, .. } => stream
+            .get(*offset..)
+            .unwrap_or_default()
+            .chunks_exact(*bits)
+            .map(|g| {
+                let value = bits_value(g);
+                aligned(g, value, binary_char(value))
+            })
+            .collect(),
+        Scheme::SegmentedBinary { .. } => segment_groups(text, stream)
+            .iter()
+            .map(|g| {
+                let value = bits_value(g);
+                aligned(g, value, binary_char(value))
+            })
+            .collect(),
+        // 压缩/加密后的字节与明文字符没有对应关系
+        Scheme::StegCloak { .. } => Vec::new(),
+        Scheme::Morse { separator, .. } => {
+            let groups: Vec<Vec<(usize, String)>> = match separator {
+                Some(_) => stream.split(|(_, s)| s == "|").filter(|g| !g.is_empty()).map(<[_]>::to_vec).collect(),
+                None => segment_groups(text, stream),
+            };
+            groups
+                .iter()
+                .map(|g| {
+                    let code: String = g.iter().map(|(_, s)| s.as_str()).collect();
+                    let output = morse::letter(&code);
+                    aligned(g, output.map_or(0, |c| c as u32), output)
+                })
+                .collect()
+        }
+        Scheme::Nary { charset, group_size, lsb_first } => stream
+            .chunks_exact(*group_size)
+            .map(|g| {
+                let value = group_value(&digits_of(g), charset.len(), *lsb_first);
+                aligned(g, value, (value > 0).then(|| char::from_u32(value)).flatten())
+            })
+            .collect(),
+        Scheme::Mapped { base, group_size, alphabet, .. } => stream
+            .chunks_exact(*group_size)
+            .map(|g| {
+                let value = group_value(&digits_of(g), *base, false);
+                let padding = value == 0 && matches!(alphabet, Alphabet::Unicode | Alphabet::Ascii);
+                aligned(g, value, if padding { None } else { alphabet.symbol(value) })
+            })
+            .collect(),
+    }
+}
+
+/// 对齐视图最多列出的组数
+const MAX_ALIGNED_ROWS: usize = 200;
+
+fn render_alignment(rows: &[Aligned]) -> String {
+    let mut out = String::from("\n逐字符对齐（[序号] 解出字符 ← 符号 = 值 @ 原文位置）:\n");
+    let mut n = 0;
+    for row in rows.iter().take(MAX_ALIGNED_ROWS) {
+        let separator = if row.symbols.iter().all(|s| s.chars().count() == 1) { "" } else { " " };
+        let symbols = row.symbols.join(separator);
+        let positions = compress_ranges(&row.positions);
+        match row.output {
+            Some(c) => {
+                n += 1;
+                out.push_str(&format!("  [{}] {:?} ← {} = {} @ {}\n", n, c, symbols, row.value, positions));
+            }
+            None => out.push_str(&format!("  [-] 跳过 ← {} = {} @ {}\n", symbols, row.value, positions)),
+        }
+    }
+    if rows.len() > MAX_ALIGNED_ROWS {
+        out.push_str(&format!("  … 其余 {} 组未列出\n", rows.len() - MAX_ALIGNED_ROWS));
+    }
+    out
+}
+
+/// 符号的含义描述
+fn symbol_meaning(scheme: &Scheme, symbol: &str) -> String {
+    match (scheme, symbol) {
+        (Scheme::UnicodeTags, hex) => format!("Tag 0x{}", hex),
+        (Scheme::Steganographr, "|") => "字符分隔符".to_string(),
+        (Scheme::Morse { .. }, "|") => "字母分隔符".to_string(),
+        (Scheme::Morse { .. }, ".") => "点".to_string(),
+        (Scheme::Morse { .. }, _) => "划".to_string(),
+        (Scheme::StegCloak { .. }, "A") => "代替双字符组合 1".to_string(),
+        (Scheme::StegCloak { .. }, "B") => "代替双字符组合 2".to_string(),
+        (Scheme::StegCloak { .. }, digit) => format!("比特 {:02b}", digit.parse::<u8>().unwrap_or(0)),
+        (Scheme::Nary { .. } | Scheme::Mapped { .. }, digit) => format!("数字 {}", digit),
+        (_, bit) => format!("比特 {}", bit),
+    }
+}
+
+/// 每个参与解码的字符在该结果中的作用（原文字符下标 → 说明），如“比特 1，第 3 组第 2/8 位 → 'a'”。
+/// 结果没有记录方案时返回空
+pub fn char_roles(text: &str, result: &DecodeResult, acceptance: &Acceptance) -> HashMap<usize, String> {
+    let mut roles = HashMap::new();
+    let Some(scheme) = &result.scheme else {
+        return roles;
+    };
+    let stream: Vec<(usize, String)> =
+        text.chars().enumerate().filter_map(|(i, ch)| symbol_map(scheme, ch).map(|sym| (i, sym))).collect();
+    for &(i, ref sym) in &stream {
+        let role = match (scheme, sym.as_str()) {
+            (Scheme::Steganographr | Scheme::Morse { .. }, "|") => symbol_meaning(scheme, sym),
+            (Scheme::StegCloak { .. }, _) => format!("{}（压缩/加密数据，不对应单个字符）", symbol_meaning(scheme, sym)),
+            _ => format!("{}（未落入任何分组，未参与解码）", symbol_meaning(scheme, sym)),
+        };
+        roles.insert(i, role);
+    }
+    for (g, row) in alignment(text, scheme, &stream, acceptance).iter().enumerate() {
+        let output = match row.output {
+            Some(c) => format!("→ {:?}", c),
+            None => "整组跳过".to_string(),
+        };
+        for (j, (&i, sym)) in row.positions.iter().zip(&row.symbols).enumerate() {
+            let place = if row.positions.len() == 1 {
+                format!("第 {} 组", g + 1)
+            } else {
+                format!("第 {} 组第 {}/{} 位", g + 1, j + 1, row.positions.len())
+            };
+            roles.insert(i, format!("{}，{} {}", symbol_meaning(scheme, sym), place, output));
+        }
+    }
+    roles
+}
+
+/// 单个候选的完整细节: 元数据、映射表、原始符号流、参与解码字符在原文中的位置，
+/// 以及每个解出字符由哪些符号、哪些位置组成
+pub fn detail(text: &str, result: &DecodeResult, acceptance: &Acceptance) -> String {
+    let mut out = format!(
+        "ID: {}\n方案: {}\n得分: {:.1}\n结果: {}\n",
+        result.id(),
+        result.method,
+        result.score,
+        display(&result.decoded)
+    );
+    for (k, v) in &result.meta {
+        out.push_str(&format!("元数据 {}: {}\n", k, v));
+    }
+    let Some(scheme) = &result.scheme else {
+        out.push_str("\n该结果经过修复或分段处理，作用于变换后的文本，无法给出原文上的符号流与位置\n");
+        return out;
+    };
+
+    let mut used: Vec<(char, String)> = Vec::new();
+    let mut stream: Vec<(usize, String)> = Vec::new();
+    for (i, ch) in text.chars().enumerate() {
+        if let Some(sym) = symbol_map(scheme, ch) {
+            if !matches!(scheme, Scheme::UnicodeTags) && !used.iter().any(|(c, _)| *c == ch) {
+                used.push((ch, sym.clone()));
+            }
+            stream.push((i, sym));
+        }
+    }
+    let symbols: Vec<&str> = stream.iter().map(|(_, s)| s.as_str()).collect();
+    let positions: Vec<usize> = stream.iter().map(|(i, _)| *i).collect();
+
+    if !used.is_empty() {
+        used.sort_by_key(|(_, sym)| (sym.parse::<usize>().unwrap_or(usize::MAX), sym.clone()));
+        out.push_str("\n映射:\n");
+        for (ch, sym) in &used {
+            out.push_str(&format!("  {} {} → {}\n", cp(*ch), display_name(*ch), sym));
+        }
+    }
+    // 单字符符号直接拼接（比特流），多字符符号用空格分隔
+    let separator = if symbols.iter().all(|s| s.chars().count() == 1) { "" } else { " " };
+    out.push_str(&format!("\n原始符号流（{} 个）:\n{}\n", symbols.len(), symbols.join(separator)));
+    out.push_str(&format!(
+        "\n参与解码的字符位置（原文字符下标，从 0 开始）:\n{}\n",
+        compress_ranges(&positions)
+    ));
+    out.push_str(&render_alignment(&alignment(text, scheme, &stream, acceptance)));
+    out
+}
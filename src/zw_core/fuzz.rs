@@ -0,0 +1,159 @@
+//! 变异测试 - 对已编码样本做可复现的随机改动，检验解码器是否仍能恢复载荷
+//!
+//! 出题人常常需要确认题目不是“碰巧能解”：复制粘贴丢了一个零宽字符、某个符号被平台替换、消息被
+//! 截断时，自己的解码脚本（或选手常用的第三方工具）还能不能解出来。这里只改动样本中的不可见符号，
+//! 可见载体保持不变；替换只在样本自身用到的符号之间进行。相同的种子与参数总是得到相同的变体，
+//! 便于把失败的变体交给外部解码器复现。
+
+use super::chars::is_zero_width;
+use super::cover::Rng;
+use super::engine::{self, Budget};
+
+/// 改动类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    /// 删除一个符号
+    Drop,
+    /// 把一个符号换成样本中的另一个符号
+    Substitute,
+    /// 从某个符号起截掉文本末尾（截断点在不可见符号序列的最后四分之一内）
+    Truncate,
+}
+
+impl Mutation {
+    pub fn all() -> &'static [Mutation] {
+        &[Mutation::Drop, Mutation::Substitute, Mutation::Truncate]
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|m| m.name() == s)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mutation::Drop => "drop",
+            Mutation::Substitute => "substitute",
+            Mutation::Truncate => "truncate",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Mutation::Drop => "删除符号",
+            Mutation::Substitute => "替换符号",
+            Mutation::Truncate => "截断末尾",
+        }
+    }
+}
+
+/// 一处改动；`position` 为该符号在原样本不可见符号序列中的序号（从 0 开始）
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub mutation: Mutation,
+    pub position: usize,
+    pub from: char,
+    /// 替换后的符号（仅 Substitute）
+    pub to: Option<char>,
+    /// 截断掉的符号数（仅 Truncate）
+    pub removed: usize,
+}
+
+impl Edit {
+    pub fn describe(&self) -> String {
+        match self.mutation {
+            Mutation::Drop => format!("删除 #{} U+{:04X}", self.position, self.from as u32),
+            Mutation::Substitute => format!(
+                "替换 #{} U+{:04X}→U+{:04X}",
+                self.position,
+                self.from as u32,
+                self.to.map_or(0, |c| c as u32)
+            ),
+            Mutation::Truncate => format!("从 #{} 起截断（丢失 {} 个符号）", self.position, self.removed),
+        }
+    }
+}
+
+/// 一个变体
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub edits: Vec<Edit>,
+    pub text: String,
+}
+
+/// 样本中的不可见符号，按首次出现顺序去重
+pub fn alphabet(text: &str) -> Vec<char> {
+    let mut out: Vec<char> = Vec::new();
+    for c in text.chars().filter(|&c| is_zero_width(c)) {
+        if !out.contains(&c) {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// 生成 `count` 个变体，每个做 `edits` 处改动（符号不够时少做）。
+/// 样本只用到一种符号时无法替换，Substitute 会被跳过；`mutations` 为空或没有不可见符号时返回空
+pub fn generate(text: &str, mutations: &[Mutation], edits: usize, count: usize, seed: u64) -> Vec<Variant> {
+    let chars: Vec<char> = text.chars().collect();
+    // 不可见符号在 chars 中的下标
+    let symbols: Vec<usize> = (0..chars.len()).filter(|&i| is_zero_width(chars[i])).collect();
+    let alphabet = alphabet(text);
+    let usable: Vec<Mutation> =
+        mutations.iter().copied().filter(|&m| m != Mutation::Substitute || alphabet.len() > 1).collect();
+    if symbols.is_empty() || usable.is_empty() {
+        return vec![];
+    }
+
+    let mut rng = Rng::new(seed);
+    let mut variants = Vec::with_capacity(count);
+    for _ in 0..count {
+        // 每个位置当前的字符；None 为已删除
+        let mut current: Vec<Option<char>> = chars.iter().map(|&c| Some(c)).collect();
+        let mut live: Vec<usize> = (0..symbols.len()).collect();
+        let mut applied = Vec::new();
+        for _ in 0..edits {
+            if live.is_empty() {
+                break;
+            }
+            let mutation = usable[rng.below(usable.len())];
+            // 截断通常只丢掉消息末尾，截断点取在剩余符号的最后四分之一内
+            let slot = match mutation {
+                Mutation::Truncate => live.len() - 1 - rng.below((live.len() / 4).max(1)),
+                _ => rng.below(live.len()),
+            };
+            let position = live[slot];
+            let index = symbols[position];
+            let from = chars[index];
+            match mutation {
+                Mutation::Drop => {
+                    current[index] = None;
+                    live.remove(slot);
+                    applied.push(Edit { mutation, position, from, to: None, removed: 0 });
+                }
+                Mutation::Substitute => {
+                    let others: Vec<char> = alphabet.iter().copied().filter(|&c| c != from).collect();
+                    let to = others[rng.below(others.len())];
+                    current[index] = Some(to);
+                    applied.push(Edit { mutation, position, from, to: Some(to), removed: 0 });
+                }
+                Mutation::Truncate => {
+                    let removed = live.iter().filter(|&&p| p >= position).count();
+                    current[index..].iter_mut().for_each(|c| *c = None);
+                    live.retain(|&p| p < position);
+                    // 截掉部分上的改动已无意义
+                    applied.retain(|e: &Edit| e.position < position);
+                    applied.push(Edit { mutation, position, from, to: None, removed });
+                }
+            }
+        }
+        applied.sort_by_key(|e| e.position);
+        variants.push(Variant { edits: applied, text: current.into_iter().flatten().collect() });
+    }
+    variants
+}
+
+/// 自动解码能否在变体中恢复期望载荷；返回是否恢复与首位解码结果
+pub fn recovers(text: &str, expected: &str, budget: &Budget) -> (bool, Option<String>) {
+    let results = engine::auto_decode(text, budget);
+    (results.iter().any(|r| r.decoded.contains(expected)), results.first().map(|r| r.decoded.chars().take(40).collect()))
+}
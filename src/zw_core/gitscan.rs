@@ -0,0 +1,235 @@
+//! Git 仓库扫描 - 检查工作区或提交范围中新引入的不可见字符
+//!
+//! 通过调用 `git` 命令完成：工作区模式用 `git ls-files` 遵守 .gitignore，
+//! 并用 `git blame` 找到引入该行的提交；提交范围模式解析 `git log -p -U0` 的新增行。
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use super::chars::is_zero_width;
+
+/// 引入问题行的提交
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    pub hash: String,
+    pub author: String,
+    pub email: String,
+    pub date: String,
+    pub summary: String,
+}
+
+/// 一处发现：某文件某行含不可见字符
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub file: String,
+    /// 新文件中的行号（从 1 开始）
+    pub line: usize,
+    /// 所在 diff hunk 头（提交范围模式）
+    pub hunk: Option<String>,
+    pub codepoints: Vec<u32>,
+    /// 行内容，不可见字符显示为 <U+XXXX>
+    pub preview: String,
+    pub commit: Option<CommitInfo>,
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<String, String> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["-c", "core.quotepath=off"])
+        .args(args)
+        .output()
+        .map_err(|e| format!("无法执行 git: {}", e))?;
+    if !out.status.success() {
+        return Err(format!(
+            "git {} 失败: {}",
+            args.first().copied().unwrap_or(""),
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+fn preview(line: &str, keep: &dyn Fn(char) -> bool) -> String {
+    let mut out = String::new();
+    for (i, c) in line.chars().enumerate() {
+        if i >= 160 {
+            out.push('…');
+            break;
+        }
+        if is_zero_width(c) && keep(c) {
+            out.push_str(&format!("<U+{:04X}>", c as u32));
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// 行内需要报告的不可见字符（去重，按出现顺序）
+fn invisible_in(line: &str, keep: &dyn Fn(char) -> bool) -> Vec<u32> {
+    let mut cps: Vec<u32> = Vec::new();
+    for c in line.chars() {
+        if is_zero_width(c) && keep(c) && !cps.contains(&(c as u32)) {
+            cps.push(c as u32);
+        }
+    }
+    cps
+}
+
+const COMMIT_FORMAT: &str = "--format=%x00%H%x1f%an%x1f%ae%x1f%ad%x1f%s";
+
+/// 解析 COMMIT_FORMAT 输出的提交头（不含开头的 NUL）
+fn parse_commit(header: &str) -> CommitInfo {
+    let parts: Vec<&str> = header.split('\u{1f}').collect();
+    let field = |i: usize| parts.get(i).copied().unwrap_or("").to_string();
+    CommitInfo {
+        hash: field(0),
+        author: field(1),
+        email: field(2),
+        date: field(3),
+        summary: field(4),
+    }
+}
+
+/// 用 git blame 找到引入某一行的提交 hash；未提交的改动返回 None
+fn blame(repo: &Path, file: &str, line: usize) -> Option<String> {
+    let range = format!("{},{}", line, line);
+    let out = git(repo, &["blame", "--porcelain", "-L", &range, "--", file]).ok()?;
+    let hash = out.lines().next()?.split_whitespace().next()?.to_string();
+    (!hash.chars().all(|c| c == '0')).then_some(hash)
+}
+
+fn commit_info(repo: &Path, hash: &str, cache: &mut HashMap<String, CommitInfo>) -> Option<CommitInfo> {
+    if let Some(info) = cache.get(hash) {
+        return Some(info.clone());
+    }
+    let out = git(repo, &["show", "-s", "--date=iso-strict", COMMIT_FORMAT, hash]).ok()?;
+    let info = parse_commit(out.trim_end().strip_prefix('\0')?);
+    cache.insert(hash.to_string(), info.clone());
+    Some(info)
+}
+
+/// 扫描工作区中受版本控制或未被忽略的文件
+pub fn scan_worktree(
+    repo: &Path,
+    keep: &dyn Fn(char) -> bool,
+    with_blame: bool,
+    max_findings: usize,
+) -> Result<(usize, Vec<Finding>), String> {
+    let listing = git(repo, &["ls-files", "-z", "--cached", "--others", "--exclude-standard"])?;
+    let mut files: Vec<&str> = listing.split('\0').filter(|f| !f.is_empty()).collect();
+    files.sort();
+    files.dedup();
+
+    let mut findings = Vec::new();
+    let mut commits: HashMap<String, CommitInfo> = HashMap::new();
+    for file in &files {
+        let Ok(raw) = std::fs::read(repo.join(file)) else {
+            continue;
+        };
+        // 跳过二进制文件
+        if raw.contains(&0) {
+            continue;
+        }
+        let text = String::from_utf8_lossy(&raw);
+        for (idx, line) in text.lines().enumerate() {
+            let cps = invisible_in(line, keep);
+            if cps.is_empty() {
+                continue;
+            }
+            if findings.len() >= max_findings {
+                return Ok((files.len(), findings));
+            }
+            let commit = if with_blame {
+                blame(repo, file, idx + 1).and_then(|h| commit_info(repo, &h, &mut commits))
+            } else {
+                None
+            };
+            findings.push(Finding {
+                file: file.to_string(),
+                line: idx + 1,
+                hunk: None,
+                codepoints: cps,
+                preview: preview(line, keep),
+                commit,
+            });
+        }
+    }
+    Ok((files.len(), findings))
+}
+
+/// 扫描提交范围（如 `main..HEAD`）中新增的行；返回 (提交数, 发现)
+pub fn scan_range(
+    repo: &Path,
+    range: &str,
+    keep: &dyn Fn(char) -> bool,
+    max_findings: usize,
+) -> Result<(usize, Vec<Finding>), String> {
+    if range.starts_with('-') {
+        return Err(format!("无效的提交范围: {}", range));
+    }
+    let log = git(
+        repo,
+        &[
+            "log",
+            "--no-color",
+            "--no-ext-diff",
+            "--reverse",
+            "-p",
+            "-U0",
+            "--date=iso-strict",
+            COMMIT_FORMAT,
+            range,
+            "--",
+        ],
+    )?;
+
+    let mut findings = Vec::new();
+    let mut commits = 0usize;
+    let mut commit: Option<CommitInfo> = None;
+    let mut file: Option<String> = None;
+    let mut hunk: Option<String> = None;
+    let mut new_line = 0usize;
+    for line in log.lines() {
+        if let Some(header) = line.strip_prefix('\0') {
+            commits += 1;
+            commit = Some(parse_commit(header));
+            file = None;
+            hunk = None;
+        } else if line.starts_with("diff --git ") {
+            file = None;
+            hunk = None;
+        } else if let Some(path) = line.strip_prefix("+++ ").filter(|_| hunk.is_none()) {
+            // 文件头只出现在第一个 hunk 之前；hunk 内以 "++" 开头的新增行不能误判
+            file = path.strip_prefix("b/").map(str::to_string);
+        } else if line.starts_with("@@") {
+            // @@ -a,b +c,d @@ 上下文
+            new_line = line
+                .split_whitespace()
+                .find_map(|t| t.strip_prefix('+'))
+                .and_then(|t| t.split(',').next())
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0);
+            hunk = Some(line.to_string());
+        } else if let (Some(added), Some(f)) = (line.strip_prefix('+'), file.as_ref()) {
+            let cps = invisible_in(added, keep);
+            if !cps.is_empty() {
+                if findings.len() >= max_findings {
+                    break;
+                }
+                findings.push(Finding {
+                    file: f.clone(),
+                    line: new_line,
+                    hunk: hunk.clone(),
+                    codepoints: cps,
+                    preview: preview(added, keep),
+                    commit: commit.clone(),
+                });
+            }
+            new_line += 1;
+        }
+    }
+    Ok((commits, findings))
+}
@@ -0,0 +1,120 @@
+//! 按码点搜索零宽字符，返回每一处出现的位置
+
+use std::collections::HashSet;
+
+use super::chars::is_zero_width;
+
+/// 上下文片段前后各显示的字符数上限
+pub const MAX_CONTEXT: usize = 200;
+
+/// 单次命中
+#[derive(Debug, Clone)]
+pub struct GrepHit {
+    pub codepoint: u32,
+    /// 解码后文本（UTF-8）中的字节偏移
+    pub text_offset: usize,
+    /// 原始文件中的字节偏移；文本输入或无法还原读入时的编码时为 None
+    pub file_offset: Option<usize>,
+    pub char_index: usize,
+    /// 从 1 开始
+    pub line: usize,
+    /// 从 1 开始，按字符计
+    pub column: usize,
+    pub context: String,
+}
+
+/// 在文本中查找指定码点的所有出现位置；`file_offsets` 为各字符在原始文件中的字节偏移（见 [`file_offsets`]）
+pub fn grep(
+    text: &str,
+    targets: &HashSet<u32>,
+    context_chars: usize,
+    max_hits: usize,
+    file_offsets: Option<&[usize]>,
+) -> Vec<GrepHit> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut hits = Vec::new();
+    let mut line = 1usize;
+    let mut column = 1usize;
+
+    for (idx, &(text_offset, ch)) in chars.iter().enumerate() {
+        if targets.contains(&(ch as u32)) {
+            if hits.len() >= max_hits {
+                break;
+            }
+            hits.push(GrepHit {
+                codepoint: ch as u32,
+                text_offset,
+                file_offset: file_offsets.and_then(|o| o.get(idx).copied()),
+                char_index: idx,
+                line,
+                column,
+                context: snippet(&chars, idx, context_chars),
+            });
+        }
+        if ch == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    hits
+}
+
+/// 解码后文本中每个字符在原始字节中的偏移
+///
+/// 依次假设文件是 UTF-8（可带 BOM）、UTF-16 LE/BE（可带 BOM）或 Latin-1，按该编码把文本重新编码，
+/// 与原始字节逐字节一致时才采用；都不一致（如读入时替换或剔除了非法字节）时返回 None。
+pub fn file_offsets(raw: &[u8], text: &str) -> Option<Vec<usize>> {
+    // UTF-8
+    for bom in [&[0xEF, 0xBB, 0xBF][..], &[][..]] {
+        if raw.strip_prefix(bom) == Some(text.as_bytes()) {
+            return Some(text.char_indices().map(|(i, _)| bom.len() + i).collect());
+        }
+    }
+    // UTF-16（读入时忽略末尾落单的字节）
+    let units: Vec<u16> = text.encode_utf16().collect();
+    let utf16 = |be: bool| -> Vec<u8> {
+        units.iter().flat_map(|u| if be { u.to_be_bytes() } else { u.to_le_bytes() }).collect()
+    };
+    for (bom, be) in [(&[0xFF, 0xFE][..], false), (&[0xFE, 0xFF][..], true), (&[][..], false)] {
+        let Some(body) = raw.strip_prefix(bom) else {
+            continue;
+        };
+        if body.len() / 2 == units.len() && body[..units.len() * 2] == utf16(be)[..] {
+            let mut offsets = Vec::new();
+            let mut at = bom.len();
+            for ch in text.chars() {
+                offsets.push(at);
+                at += ch.len_utf16() * 2;
+            }
+            return Some(offsets);
+        }
+    }
+    // Latin-1
+    if raw.len() == text.chars().count() && text.chars().zip(raw).all(|(c, &b)| c as u32 == b as u32) {
+        return Some((0..raw.len()).collect());
+    }
+    None
+}
+
+/// 生成上下文片段，命中字符显示为 [U+XXXX]，其他不可见字符显示为 <U+XXXX>
+fn snippet(chars: &[(usize, char)], center: usize, radius: usize) -> String {
+    let start = center.saturating_sub(radius);
+    let end = center.saturating_add(radius).saturating_add(1).min(chars.len());
+    let mut out = String::new();
+    for (i, &(_, c)) in chars.iter().enumerate().take(end).skip(start) {
+        if i == center {
+            out.push_str(&format!("[U+{:04X}]", c as u32));
+        } else if is_zero_width(c) {
+            out.push_str(&format!("<U+{:04X}>", c as u32));
+        } else if c == '\n' || c == '\r' {
+            out.push('⏎');
+        } else if c.is_control() {
+            out.push('·');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -0,0 +1,135 @@
+//! 字节级十六进制导出 - 查看零宽字符在磁盘上的真实字节
+
+use super::chars::is_zero_width;
+
+/// 命中前后显示的上下文字节数上限
+pub const MAX_CONTEXT: usize = 4096;
+
+/// 文件中一段连续零宽字符的字节范围
+#[derive(Debug, Clone)]
+pub struct ByteHit {
+    pub start: usize,
+    pub end: usize,
+    pub zw_count: usize,
+}
+
+/// 检测原始字节的编码，并定位所有零宽字符连续段的字节范围
+pub fn locate_hits(raw: &[u8]) -> (&'static str, Vec<ByteHit>) {
+    // (字节偏移, 字节长度, 字符)
+    let (encoding, chars): (&'static str, Vec<(usize, usize, char)>) =
+        if raw.starts_with(&[0xFF, 0xFE]) {
+            ("UTF-16 LE (BOM)", decode_utf16_offsets(raw, 2, u16::from_le_bytes))
+        } else if raw.starts_with(&[0xFE, 0xFF]) {
+            ("UTF-16 BE (BOM)", decode_utf16_offsets(raw, 2, u16::from_be_bytes))
+        } else if let Ok(s) = std::str::from_utf8(raw) {
+            // 文件开头的 BOM 是编码标记，不计入命中（UTF-16 的 BOM 在解码时已跳过）
+            let (name, skip) = if raw.starts_with(&[0xEF, 0xBB, 0xBF]) { ("UTF-8 (BOM)", 3) } else { ("UTF-8", 0) };
+            (name, s[skip..].char_indices().map(|(i, c)| (skip + i, c.len_utf8(), c)).collect())
+        } else if raw.len().is_multiple_of(2) {
+            ("UTF-16 LE", decode_utf16_offsets(raw, 0, u16::from_le_bytes))
+        } else {
+            ("Latin-1", raw.iter().enumerate().map(|(i, &b)| (i, 1, b as char)).collect())
+        };
+
+    let mut hits: Vec<ByteHit> = Vec::new();
+    let mut current: Option<ByteHit> = None;
+    for (offset, len, ch) in chars {
+        if is_zero_width(ch) {
+            match current.as_mut() {
+                Some(hit) if hit.end == offset => {
+                    hit.end = offset + len;
+                    hit.zw_count += 1;
+                }
+                _ => {
+                    if let Some(hit) = current.take() {
+                        hits.push(hit);
+                    }
+                    current = Some(ByteHit { start: offset, end: offset + len, zw_count: 1 });
+                }
+            }
+        } else if let Some(hit) = current.take() {
+            hits.push(hit);
+        }
+    }
+    if let Some(hit) = current {
+        hits.push(hit);
+    }
+    (encoding, hits)
+}
+
+fn decode_utf16_offsets(
+    raw: &[u8],
+    skip: usize,
+    to_u16: fn([u8; 2]) -> u16,
+) -> Vec<(usize, usize, char)> {
+    let units: Vec<u16> = raw[skip..].chunks_exact(2).map(|c| to_u16([c[0], c[1]])).collect();
+    let mut out = Vec::new();
+    let mut offset = skip;
+    for r in char::decode_utf16(units.iter().copied()) {
+        let (ch, len) = match r {
+            Ok(c) => (c, c.len_utf16() * 2),
+            Err(_) => ('\u{FFFD}', 2),
+        };
+        out.push((offset, len, ch));
+        offset += len;
+    }
+    out
+}
+
+/// 经典 hex+ASCII 导出，按 16 字节对齐，命中字节用 `*` 标注；`hits` 须按偏移升序排列
+pub fn hexdump_regions(raw: &[u8], hits: &[ByteHit], context: usize, max_regions: usize) -> String {
+    // 合并重叠的上下文窗口
+    let mut regions: Vec<(usize, usize)> = Vec::new();
+    for hit in hits {
+        let start = hit.start.saturating_sub(context) / 16 * 16;
+        let end = hit.end.saturating_add(context).min(raw.len());
+        match regions.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => regions.push((start, end)),
+        }
+    }
+
+    // 输出的偏移单调递增，用游标顺序走过命中段
+    let mut cursor = 0;
+    let mut is_hit = |offset: usize| {
+        while hits.get(cursor).is_some_and(|h| h.end <= offset) {
+            cursor += 1;
+        }
+        hits.get(cursor).is_some_and(|h| h.start <= offset)
+    };
+
+    let mut out = String::new();
+    for (n, &(start, end)) in regions.iter().enumerate().take(max_regions) {
+        out.push_str(&format!("\n区域 {} [0x{:08X} - 0x{:08X}]\n", n + 1, start, end));
+        let mut row = start;
+        while row < end {
+            let row_end = (row + 16).min(end);
+            let mut hex = String::new();
+            let mut marks = String::new();
+            let mut ascii = String::new();
+            for i in row..row + 16 {
+                if let Some(&b) = raw[..row_end].get(i) {
+                    hex.push_str(&format!("{:02X} ", b));
+                    marks.push_str(if is_hit(i) { "** " } else { "   " });
+                    ascii.push(if (0x20..0x7F).contains(&b) { b as char } else { '.' });
+                } else {
+                    hex.push_str("   ");
+                    marks.push_str("   ");
+                }
+                if i == row + 7 {
+                    hex.push(' ');
+                    marks.push(' ');
+                }
+            }
+            out.push_str(&format!("{:08X}  {} |{}|\n", row, hex, ascii));
+            if marks.contains('*') {
+                out.push_str(&format!("          {}\n", marks.trim_end()));
+            }
+            row += 16;
+        }
+    }
+    if regions.len() > max_regions {
+        out.push_str(&format!("\n... 另有 {} 个区域未显示\n", regions.len() - max_regions));
+    }
+    out
+}
@@ -0,0 +1,193 @@
+//! 解题提示 - 检查候选结果的结构（而不只是得分），给出下一步该尝试什么
+//!
+//! 得分只衡量「像不像明文」；这里看的是长度、字符集、熵和比特数等结构特征，
+//! 例如结果像 base64、长度是 16 的倍数且熵很高（疑似 AES 密文）、
+//! 比特数不是 8 的倍数但是 7 的倍数等。
+
+use std::collections::HashMap;
+
+use super::chars::is_zero_width;
+use super::engine::{extract_all, find_flags, hex_to_bytes, Acceptance, DecodeResult, Scheme};
+use super::ingest::decode_base64;
+
+fn is_base64_like(s: &str) -> bool {
+    let body = s.trim_end_matches('=');
+    s.len() >= 8
+        && s.len().is_multiple_of(4)
+        && s.len() - body.len() <= 2
+        && body.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/' || c == '-' || c == '_')
+        // 纯字母或纯数字更像普通单词/数字串
+        && body.chars().any(|c| c.is_ascii_digit())
+        && body.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+fn is_hex_like(s: &str) -> bool {
+    s.len() >= 8 && s.len().is_multiple_of(2) && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn is_base32_like(s: &str) -> bool {
+    let body = s.trim_end_matches('=');
+    s.len() >= 8
+        && s.len().is_multiple_of(8)
+        && body.chars().all(|c| c.is_ascii_uppercase() || ('2'..='7').contains(&c))
+        && body.chars().any(|c| c.is_ascii_digit())
+}
+
+/// 每字节的香农熵（比特）
+fn entropy(bytes: &[u8]) -> f64 {
+    let mut counts: HashMap<u8, usize> = HashMap::new();
+    for &b in bytes {
+        *counts.entry(b).or_default() += 1;
+    }
+    let n = bytes.len() as f64;
+    counts
+        .values()
+        .map(|&c| {
+            let p = c as f64 / n;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// 判断密文/压缩数据所需的最少字节数；更短的串熵值波动太大，普通单词也能接近上限
+const MIN_RANDOM_BYTES: usize = 32;
+
+/// 字节中可打印 ASCII（含空白）的比例
+fn printable_share(bytes: &[u8]) -> f64 {
+    let printable = bytes.iter().filter(|&&b| b.is_ascii_graphic() || b.is_ascii_whitespace()).count();
+    printable as f64 / bytes.len().max(1) as f64
+}
+
+/// 解码结果读起来像文本（任意文字的字母数字、标点与空白占绝大多数）或就是 flag
+fn looks_like_text(text: &str) -> bool {
+    if !find_flags(text).is_empty() {
+        return true;
+    }
+    let total = text.chars().count().max(1);
+    let readable = text
+        .chars()
+        .filter(|&c| c.is_alphanumeric() || c.is_ascii_punctuation() || c.is_whitespace())
+        .count();
+    readable * 10 >= total * 9
+}
+
+/// 看起来像随机字节: 足够长、不是可打印文本，且熵接近该长度下的上限
+fn looks_random(bytes: &[u8]) -> bool {
+    if bytes.len() < MIN_RANDOM_BYTES || printable_share(bytes) >= 0.9 {
+        return false;
+    }
+    let max = (bytes.len().min(256) as f64).log2();
+    entropy(bytes) > max * 0.85
+}
+
+fn cipher_hint(bytes: &[u8], source: &str) -> Option<String> {
+    (bytes.len().is_multiple_of(16) && looks_random(bytes)).then(|| {
+        format!(
+            "{}长度 {} 字节，是 16 的倍数且熵很高，可能是 AES 等分组密码密文，需要寻找密钥",
+            source,
+            bytes.len()
+        )
+    })
+}
+
+/// 解码结果（或其 hex/base64 还原后的字节）是否像密文或压缩数据；返回判断依据
+pub fn cipher_evidence(decoded: &str) -> Option<String> {
+    let trimmed = decoded.trim();
+    if !find_flags(trimmed).is_empty() {
+        return None;
+    }
+    let (bytes, source) = if is_hex_like(trimmed) {
+        (hex_to_bytes(trimmed)?, "hex 还原后")
+    } else if is_base64_like(trimmed) {
+        (decode_base64(trimmed).ok()?, "base64 解码后")
+    } else {
+        // 8bit 二进制结果的每个字符即一个字节
+        let bytes: Option<Vec<u8>> = decoded.chars().map(|c| u8::try_from(c as u32).ok()).collect();
+        (bytes?, "结果")
+    };
+    cipher_hint(&bytes, source).or_else(|| {
+        looks_random(&bytes).then(|| format!("{}的 {} 字节熵很高，疑似加密或压缩数据", source, bytes.len()))
+    })
+}
+
+/// 二进制方案的比特数检查
+fn bit_count_hints(text: &str, zero: char, one: char, bits: usize, out: &mut Vec<String>) {
+    let n = extract_all(text).into_iter().filter(|&c| c == zero || c == one).count();
+    if n == 0 || n.is_multiple_of(bits) {
+        return;
+    }
+    for alt in [7, 8, 5, 6] {
+        if alt != bits && n.is_multiple_of(alt) {
+            out.push(format!(
+                "共 {} 个比特，不是 {} 的倍数但是 {} 的倍数，试试 {}bit 分组",
+                n, bits, alt, alt
+            ));
+            return;
+        }
+    }
+    out.push(format!(
+        "共 {} 个比特，按 {}bit 分组多出 {} 位，可能有起始偏移或填充，试试比特偏移（budget=exhaustive）",
+        n,
+        bits,
+        n % bits
+    ));
+}
+
+/// 为一个解码候选生成提示；`text` 为解码前的原文
+pub fn hints(text: &str, result: &DecodeResult, acceptance: &Acceptance) -> Vec<String> {
+    let mut out = Vec::new();
+    let decoded = result.decoded.trim();
+
+    // 二进制结果中的 0xAD 等字节会被误当作零宽字符
+    if !acceptance.allow_binary && result.decoded.chars().any(is_zero_width) {
+        out.push("结果中仍含零宽字符，可能是嵌套载荷，可对结果再次解码（max_depth）".to_string());
+    }
+
+    if is_hex_like(decoded) {
+        out.push("结果像 hex 串，可按十六进制还原成字节".to_string());
+        if let Some(hint) = hex_to_bytes(decoded).and_then(|b| cipher_hint(&b, "hex 还原后")) {
+            out.push(hint);
+        }
+    } else if is_base32_like(decoded) {
+        out.push("结果像 base32（A-Z2-7），可继续 base32 解码".to_string());
+    } else if is_base64_like(decoded) {
+        match decode_base64(decoded) {
+            Ok(bytes) => match String::from_utf8(bytes.clone()) {
+                Ok(s) if !s.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+                    out.push(format!("结果像 base64，解码后为可读文本: {:?}", s.chars().take(60).collect::<String>()));
+                }
+                _ => {
+                    out.push("结果像 base64，可继续解码（解码后是二进制数据）".to_string());
+                    if let Some(hint) = cipher_hint(&bytes, "base64 解码后") {
+                        out.push(hint);
+                    }
+                }
+            },
+            Err(_) => out.push("结果字符集像 base64，但无法直接解码，检查是否缺失填充或混入了其他字符".to_string()),
+        }
+    } else if looks_like_text(decoded) {
+        // 可读文本或 flag 不是密文，不给出误导的 AES 提示
+    } else if let Some(hint) = cipher_hint(decoded.as_bytes(), "结果") {
+        out.push(hint);
+    }
+
+    let url_escape = |p: &str| p.chars().take(2).filter(|c| c.is_ascii_hexdigit()).count() == 2;
+    if decoded.contains('%') && decoded.split('%').skip(1).all(url_escape) {
+        out.push("结果含 %XX 序列，可能是 URL 编码".to_string());
+    }
+    if decoded.len() >= 4 && decoded.chars().all(|c| matches!(c, '.' | '-' | ' ' | '/')) {
+        out.push("结果只由 . - / 和空格组成，可能是摩尔斯电码".to_string());
+    }
+    let lower = decoded.to_ascii_lowercase();
+    if lower.contains("synt{") || lower.contains("pgs{") {
+        out.push("结果含 synt{ / pgs{，是 flag{ / ctf{ 的 ROT13，试试 ROT13".to_string());
+    }
+    if lower.contains("}galf") || lower.contains("}ftc") {
+        out.push("结果像倒序的 flag，试试反转字符串".to_string());
+    }
+
+    if let Some(Scheme::Binary { zero, one, bits, .. }) = &result.scheme {
+        bit_count_hints(text, *zero, *one, *bits, &mut out);
+    }
+    out
+}
@@ -0,0 +1,215 @@
+//! 网页源码中的转义不可见字符 - 字符引用、href/src 百分号编码与 CSS 转义
+//!
+//! 页面源码里的载荷常常从不以原始码点出现：`&#x200B;`、`&#8203;`、`&zwnj;` 只有经浏览器渲染
+//! 才会变成零宽字符，链接中则是 `%E2%80%8B`，样式表里是 `\200B`。分析前把这些转义还原为
+//! 对应字符（只还原结果为不可见字符的转义，其余保持原样），并记录每处命中的原始写法。
+
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::chars::is_zero_width;
+
+/// 转义的语法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscapeKind {
+    /// `&#x200B;` / `&#8203;` / `&zwnj;`
+    CharRef,
+    /// href/src 属性值中的 `%E2%80%8B`
+    Percent,
+    /// `<style>` 或 style 属性中的 `\200B`
+    Css,
+}
+
+impl EscapeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EscapeKind::CharRef => "字符引用",
+            EscapeKind::Percent => "href/src 百分号编码",
+            EscapeKind::Css => "CSS 转义",
+        }
+    }
+}
+
+/// 一处被还原的转义
+#[derive(Debug, Clone)]
+pub struct EscapeHit {
+    /// 源码中的原始写法
+    pub escaped: String,
+    pub ch: char,
+    pub kind: EscapeKind,
+    /// 在原始文本中的字节偏移
+    pub offset: usize,
+}
+
+/// 还原后的文本
+#[derive(Debug, Clone)]
+pub struct Unescaped {
+    pub text: String,
+    pub hits: Vec<EscapeHit>,
+}
+
+/// 对应不可见字符的 HTML5 命名实体（区分大小写）
+const NAMED_ENTITIES: &[(&str, char)] = &[
+    ("ZeroWidthSpace", '\u{200B}'),
+    ("NegativeVeryThinSpace", '\u{200B}'),
+    ("NegativeThinSpace", '\u{200B}'),
+    ("NegativeMediumSpace", '\u{200B}'),
+    ("NegativeThickSpace", '\u{200B}'),
+    ("zwnj", '\u{200C}'),
+    ("zwj", '\u{200D}'),
+    ("lrm", '\u{200E}'),
+    ("rlm", '\u{200F}'),
+    ("shy", '\u{00AD}'),
+    ("NoBreak", '\u{2060}'),
+    ("af", '\u{2061}'),
+    ("ApplyFunction", '\u{2061}'),
+    ("it", '\u{2062}'),
+    ("InvisibleTimes", '\u{2062}'),
+    ("ic", '\u{2063}'),
+    ("InvisibleComma", '\u{2063}'),
+];
+
+/// 粗略判断文本是否为 HTML/XML 源码
+pub fn looks_like_html(text: &str) -> bool {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)<(?:!doctype\s+html|html|head|body|div|span|p|a|script|style|meta|link|img|br|table|svg)\b")
+            .unwrap()
+    })
+    .is_match(text)
+}
+
+/// 解析 `&...;` 字符引用，返回 (字符, 消耗的字节数)
+fn parse_char_ref(rest: &str) -> Option<(char, usize)> {
+    let body = rest.strip_prefix('&')?;
+    if let Some(num) = body.strip_prefix('#') {
+        let (digits_start, radix) = match num.as_bytes().first() {
+            Some(b'x' | b'X') => (1, 16),
+            _ => (0, 10),
+        };
+        let digits: String = num[digits_start..].chars().take_while(|c| c.is_digit(radix)).take(8).collect();
+        if digits.is_empty() {
+            return None;
+        }
+        let ch = char::from_u32(u32::from_str_radix(&digits, radix).ok()?)?;
+        // 浏览器容忍省略分号
+        let mut len = 2 + digits_start + digits.len();
+        if rest[len..].starts_with(';') {
+            len += 1;
+        }
+        return Some((ch, len));
+    }
+    let name: String = body.chars().take_while(|c| c.is_ascii_alphanumeric()).take(32).collect();
+    if !body[name.len()..].starts_with(';') {
+        return None;
+    }
+    NAMED_ENTITIES.iter().find(|(n, _)| *n == name).map(|&(_, ch)| (ch, name.len() + 2))
+}
+
+/// 解析 CSS 十六进制转义 `\200B`（1~6 位，其后的一个空白属于转义）
+fn parse_css_escape(rest: &str) -> Option<(char, usize)> {
+    let body = rest.strip_prefix('\\')?;
+    let digits: String = body.chars().take_while(|c| c.is_ascii_hexdigit()).take(6).collect();
+    if digits.is_empty() {
+        return None;
+    }
+    let ch = char::from_u32(u32::from_str_radix(&digits, 16).ok()?)?;
+    let mut len = 1 + digits.len();
+    if rest[len..].starts_with([' ', '\t', '\n']) {
+        len += 1;
+    }
+    Some((ch, len))
+}
+
+/// 连续的 `%XX` 序列解码出的字节
+fn percent_run(rest: &str) -> Vec<u8> {
+    rest.as_bytes()
+        .chunks(3)
+        .map_while(|c| match c {
+            [b'%', h, l] => u8::from_str_radix(std::str::from_utf8(&[*h, *l]).ok()?, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// href/src 属性值与 CSS（`<style>` 内容和 style 属性）所在的字节区间
+fn escape_regions(text: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    static ATTR: OnceLock<Regex> = OnceLock::new();
+    static STYLE: OnceLock<Regex> = OnceLock::new();
+    let attr = ATTR.get_or_init(|| Regex::new(r#"(?i)\b(href|src|style)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+    let style = STYLE.get_or_init(|| Regex::new(r"(?is)<style\b[^>]*>(.*?)</style>").unwrap());
+    let mut urls = Vec::new();
+    let mut css = Vec::new();
+    for caps in attr.captures_iter(text) {
+        let Some(value) = caps.get(2).or_else(|| caps.get(3)) else {
+            continue;
+        };
+        if caps[1].eq_ignore_ascii_case("style") {
+            css.push(value.range());
+        } else {
+            urls.push(value.range());
+        }
+    }
+    css.extend(style.captures_iter(text).filter_map(|c| c.get(1)).map(|m| m.range()));
+    (urls, css)
+}
+
+/// 还原结果为不可见字符的转义；其余内容（包括解出可见字符的转义）原样保留
+pub fn unescape_invisible(text: &str) -> Unescaped {
+    let (urls, css) = escape_regions(text);
+    let inside = |ranges: &[Range<usize>], i: usize| ranges.iter().any(|r| r.contains(&i));
+    let mut out = String::with_capacity(text.len());
+    let mut hits = Vec::new();
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        let found = match rest.as_bytes()[0] {
+            b'&' => parse_char_ref(rest).map(|(ch, len)| (ch, len, EscapeKind::CharRef)),
+            b'\\' if inside(&css, i) => parse_css_escape(rest).map(|(ch, len)| (ch, len, EscapeKind::Css)),
+            b'%' if inside(&urls, i) => {
+                // 一个字符的 UTF-8 字节可能被拆成多个 %XX，按完整字符解码
+                let bytes = percent_run(rest);
+                let width = match bytes.first() {
+                    Some(0xF0..) => 4,
+                    Some(0xE0..) => 3,
+                    Some(0xC0..) => 2,
+                    _ => 1,
+                };
+                bytes
+                    .get(..width)
+                    .and_then(|b| std::str::from_utf8(b).ok())
+                    .and_then(|s| s.chars().next())
+                    .map(|ch| (ch, width * 3, EscapeKind::Percent))
+            }
+            _ => None,
+        };
+        match found {
+            Some((ch, len, kind)) if is_zero_width(ch) => {
+                hits.push(EscapeHit { escaped: rest[..len].to_string(), ch, kind, offset: i });
+                out.push(ch);
+                i += len;
+            }
+            // 解出可见字符的转义整体保留，避免其中的 `&` `%` 被再次解析
+            Some((_, len, _)) => {
+                out.push_str(&rest[..len]);
+                i += len;
+            }
+            None => {
+                let ch = rest.chars().next().unwrap_or_default();
+                out.push(ch);
+                i += ch.len_utf8();
+            }
+        }
+    }
+    Unescaped { text: out, hits }
+}
+
+/// 字节偏移对应的 (行, 列)，均从 1 开始
+pub fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let col = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    (line, col)
+}
@@ -0,0 +1,306 @@
+//! 容错输入处理 - 记录替换字符位置并在解码前剔除；日志模式下重组被拆开或转义的零宽字符
+
+use super::chars::is_zero_width;
+
+/// U+FFFD REPLACEMENT CHARACTER
+pub const REPLACEMENT: char = '\u{FFFD}';
+
+/// 容错读入的文本
+#[derive(Debug, Clone)]
+pub struct LossyText {
+    /// 剔除替换字符后的文本
+    pub text: String,
+    /// 替换字符在原始文本中的字符索引
+    pub replacements: Vec<usize>,
+}
+
+/// 按 UTF-8 容错解码字节，非法序列替换为 U+FFFD
+pub fn decode_utf8_lossy(raw: &[u8]) -> String {
+    String::from_utf8_lossy(raw).into_owned()
+}
+
+/// 剔除文本中的替换字符，同时记录它们原本的位置
+///
+/// 替换字符通常来自孤立代理项或非法字节序列，它们被当作可见字符时
+/// 会把一段连续的零宽载荷切成两半；剔除后零宽字符流保持连续。
+pub fn strip_replacements(text: &str) -> LossyText {
+    let mut out = String::with_capacity(text.len());
+    let mut replacements = Vec::new();
+    for (i, ch) in text.chars().enumerate() {
+        if ch == REPLACEMENT {
+            replacements.push(i);
+        } else {
+            out.push(ch);
+        }
+    }
+    LossyText { text: out, replacements }
+}
+
+/// 把 JSON 字符串字面量中的孤立代理项转义（`\uD800` 等）替换为 `�`
+///
+/// serde_json 会直接拒绝孤立代理项，导致整个请求解析失败；
+/// 逐个替换可以保持字符位置不变。
+pub fn repair_lone_surrogate_escapes(json: &str) -> String {
+    let bytes = json.as_bytes();
+    let mut out = String::with_capacity(json.len());
+    let mut i = 0usize;
+    let mut last = 0usize;
+
+    let parse_escape = |at: usize| -> Option<u16> {
+        if bytes.get(at) == Some(&b'\\') && bytes.get(at + 1) == Some(&b'u') {
+            let hex = json.get(at + 2..at + 6)?;
+            u16::from_str_radix(hex, 16).ok()
+        } else {
+            None
+        }
+    };
+
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            i += 1;
+            continue;
+        }
+        match parse_escape(i) {
+            Some(0xD800..=0xDBFF) => {
+                if matches!(parse_escape(i + 6), Some(0xDC00..=0xDFFF)) {
+                    // 合法代理对
+                    i += 12;
+                } else {
+                    out.push_str(&json[last..i]);
+                    out.push_str("\\uFFFD");
+                    i += 6;
+                    last = i;
+                }
+            }
+            Some(0xDC00..=0xDFFF) => {
+                out.push_str(&json[last..i]);
+                out.push_str("\\uFFFD");
+                i += 6;
+                last = i;
+            }
+            Some(_) => i += 6,
+            // 其他转义（含 \\）整体跳过，避免把 \\u 误认为转义
+            None => i += 2,
+        }
+    }
+    out.push_str(&json[last..]);
+    out
+}
+
+/// 编码为标准 base64（带填充）
+pub fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// 解码 base64（兼容标准与 URL-safe 字母表，忽略空白，填充可省略）
+pub fn decode_base64(input: &str) -> Result<Vec<u8>, String> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+            b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+            b'+' | b'-' => Some(62),
+            b'/' | b'_' => Some(63),
+            _ => None,
+        }
+    }
+
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buf = 0u32;
+    let mut bits = 0u32;
+    for (i, c) in input.bytes().enumerate() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        if c == b'=' {
+            break;
+        }
+        let v = value(c).ok_or_else(|| format!("非法 base64 字符 {:?} (位置 {})", c as char, i))?;
+        buf = (buf << 6) | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+            buf &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+/// 日志模式读入的文本
+#[derive(Debug, Clone)]
+pub struct LogText {
+    pub text: String,
+    /// 被换行拆开后重新拼接的 UTF-8 序列数
+    pub rejoined: usize,
+    /// 还原的转义形式及次数
+    pub unescaped: Vec<(&'static str, usize)>,
+}
+
+fn count_form(counts: &mut Vec<(&'static str, usize)>, form: &'static str) {
+    match counts.iter_mut().find(|(f, _)| *f == form) {
+        Some(entry) => entry.1 += 1,
+        None => counts.push((form, 1)),
+    }
+}
+
+fn hex_value(digits: &[u8]) -> Option<u32> {
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    u32::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()
+}
+
+/// 解析 `u`/`U` 之后的码点转义：`uXXXX`（含代理对）、`u{X..}`、`UXXXXXXXX`；返回 (字符, 消耗字节数, 形式)
+fn parse_unicode_escape(raw: &[u8], at: usize, backslashes: usize) -> Option<(char, usize, &'static str)> {
+    let rest = &raw[at..];
+    match rest.first()? {
+        b'u' if rest.get(1) == Some(&b'{') => {
+            let end = rest.iter().take(10).position(|&b| b == b'}')?;
+            let ch = char::from_u32(hex_value(&rest[2..end])?)?;
+            Some((ch, end + 1, "\\u{...}"))
+        }
+        b'u' => {
+            let unit = hex_value(rest.get(1..5)?)?;
+            if (0xD800..0xDC00).contains(&unit) {
+                // 代理对: 低位代理前的反斜杠数与高位相同
+                let low_at = 5 + backslashes;
+                let prefix_ok = rest.get(5..low_at)?.iter().all(|&b| b == b'\\');
+                let low = hex_value(rest.get(low_at + 1..low_at + 5)?)?;
+                if !prefix_ok || rest.get(low_at) != Some(&b'u') || !(0xDC00..0xE000).contains(&low) {
+                    return None;
+                }
+                let ch = char::from_u32(0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00))?;
+                return Some((ch, low_at + 5, "\\uXXXX\\uXXXX（代理对）"));
+            }
+            Some((char::from_u32(unit)?, 5, "\\uXXXX"))
+        }
+        b'U' => Some((char::from_u32(hex_value(rest.get(1..9)?)?)?, 9, "\\UXXXXXXXX")),
+        _ => None,
+    }
+}
+
+/// 还原日志中常见的转义：`\xHH` / 八进制 `\ooo` 字节转义（仅 0x80 以上，即多字节字符的片段，
+/// 可与未转义的原始字节混合）、码点转义（仅不可见字符，允许双重转义的 `\\u200b`）与 `<U+200B>`
+fn unescape_log(raw: &[u8]) -> (Vec<u8>, Vec<(&'static str, usize)>) {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut counts = Vec::new();
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'<' && raw[i..].starts_with(b"<U+") {
+            let end = raw[i..].iter().take(11).position(|&b| b == b'>');
+            let ch = end.and_then(|e| hex_value(&raw[i + 3..i + e])).and_then(char::from_u32);
+            if let (Some(e), Some(ch)) = (end, ch.filter(|&c| is_zero_width(c))) {
+                out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                count_form(&mut counts, "<U+XXXX>");
+                i += e + 1;
+                continue;
+            }
+        }
+        if raw[i] != b'\\' {
+            out.push(raw[i]);
+            i += 1;
+            continue;
+        }
+        let backslashes = raw[i..].iter().take_while(|&&b| b == b'\\').count();
+        let at = i + backslashes;
+        if backslashes <= 2 {
+            let byte = match raw.get(at) {
+                Some(b'x') => raw.get(at + 1..at + 3).and_then(hex_value).map(|v| (v, 3, "\\xHH")),
+                Some(b'0'..=b'3') => raw
+                    .get(at..at + 3)
+                    .filter(|d| d.iter().all(|b| (b'0'..=b'7').contains(b)))
+                    .and_then(|d| u32::from_str_radix(std::str::from_utf8(d).ok()?, 8).ok())
+                    .map(|v| (v, 3, "\\ooo（八进制）")),
+                _ => None,
+            };
+            if let Some((v, len, form)) = byte.filter(|(v, _, _)| *v >= 0x80) {
+                out.push(v as u8);
+                count_form(&mut counts, form);
+                i = at + len;
+                continue;
+            }
+            if let Some((ch, len, form)) = parse_unicode_escape(raw, at, backslashes).filter(|(c, _, _)| is_zero_width(*c)) {
+                out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                count_form(&mut counts, form);
+                i = at + len;
+                continue;
+            }
+        }
+        out.extend_from_slice(&raw[i..at]);
+        i = at;
+    }
+    (out, counts)
+}
+
+/// 拼接被换行（及续行缩进）拆开的 UTF-8 多字节序列；返回 (字节, 拼接次数)
+fn rejoin_split_utf8(bytes: &[u8]) -> (Vec<u8>, usize) {
+    let is_continuation = |b: u8| (0x80..0xC0).contains(&b);
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut rejoined = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let width = match bytes[i] {
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF4 => 4,
+            b => {
+                out.push(b);
+                i += 1;
+                continue;
+            }
+        };
+        out.push(bytes[i]);
+        i += 1;
+        let mut missing = width - 1;
+        let mut split = false;
+        while missing > 0 {
+            if bytes.get(i).is_some_and(|&b| is_continuation(b)) {
+                out.push(bytes[i]);
+                i += 1;
+                missing -= 1;
+                continue;
+            }
+            // 换行之后紧接着缺失的续字节时，跳过换行与续行缩进
+            let mut k = i;
+            if bytes.get(k) == Some(&b'\r') {
+                k += 1;
+            }
+            if bytes.get(k) != Some(&b'\n') {
+                break;
+            }
+            k += 1;
+            while matches!(bytes.get(k), Some(b' ' | b'\t')) {
+                k += 1;
+            }
+            if !bytes.get(k).is_some_and(|&b| is_continuation(b)) {
+                break;
+            }
+            i = k;
+            split = true;
+        }
+        if split && missing == 0 {
+            rejoined += 1;
+        }
+    }
+    (out, rejoined)
+}
+
+/// 日志模式读入：先还原转义，再拼接被换行拆开的 UTF-8 序列，最后按 UTF-8 容错解码
+pub fn ingest_log(raw: &[u8]) -> LogText {
+    let (bytes, unescaped) = unescape_log(raw);
+    let (bytes, rejoined) = rejoin_split_utf8(&bytes);
+    LogText { text: decode_utf8_lossy(&bytes), rejoined, unescaped }
+}
@@ -1,2 +1,39 @@
+pub mod annotate;
+pub mod baseline;
+pub mod benchmark;
+pub mod blank;
+pub mod carve;
+pub mod channel;
 pub mod chars;
+pub mod check;
+pub mod cluster;
+pub mod codecover;
+pub mod cover;
+pub mod density;
+pub mod delta;
+pub mod diagnose;
+pub mod difficulty;
+pub mod emoji;
+pub mod emulate;
 pub mod engine;
+pub mod explain;
+pub mod fuzz;
+pub mod gitscan;
+pub mod grep;
+pub mod hexdump;
+pub mod hints;
+pub mod html;
+pub mod ingest;
+pub mod morse;
+pub mod names;
+pub mod numeric;
+pub mod postprocess;
+pub mod profile;
+pub mod reassemble;
+pub mod recommend;
+pub mod repair;
+pub mod sanitize;
+pub mod session;
+pub mod sidecar;
+pub mod stegcloak;
+pub mod triage;
@@ -0,0 +1,108 @@
+//! 莫尔斯码 - 国际莫尔斯码（ITU-R M.1677）码表，以及点划序列与文本的互转
+//!
+//! 零宽字符版的莫尔斯码用两种字符表示点与划，第三种字符（或相邻载荷之间的可见文本）分隔字母，
+//! 连续两个分隔符表示词间空格。解出的字母统一为大写；码表没有花括号，`flag{...}` 只能以
+//! 原样的字母数字部分出现。
+
+/// 码表：字符 → 点划串
+const TABLE: &[(char, &str)] = &[
+    ('A', ".-"),
+    ('B', "-..."),
+    ('C', "-.-."),
+    ('D', "-.."),
+    ('E', "."),
+    ('F', "..-."),
+    ('G', "--."),
+    ('H', "...."),
+    ('I', ".."),
+    ('J', ".---"),
+    ('K', "-.-"),
+    ('L', ".-.."),
+    ('M', "--"),
+    ('N', "-."),
+    ('O', "---"),
+    ('P', ".--."),
+    ('Q', "--.-"),
+    ('R', ".-."),
+    ('S', "..."),
+    ('T', "-"),
+    ('U', "..-"),
+    ('V', "...-"),
+    ('W', ".--"),
+    ('X', "-..-"),
+    ('Y', "-.--"),
+    ('Z', "--.."),
+    ('0', "-----"),
+    ('1', ".----"),
+    ('2', "..---"),
+    ('3', "...--"),
+    ('4', "....-"),
+    ('5', "....."),
+    ('6', "-...."),
+    ('7', "--..."),
+    ('8', "---.."),
+    ('9', "----."),
+    ('.', ".-.-.-"),
+    (',', "--..--"),
+    ('?', "..--.."),
+    ('\'', ".----."),
+    ('!', "-.-.--"),
+    ('/', "-..-."),
+    ('(', "-.--."),
+    (')', "-.--.-"),
+    ('&', ".-..."),
+    (':', "---..."),
+    (';', "-.-.-."),
+    ('=', "-...-"),
+    ('+', ".-.-."),
+    ('-', "-....-"),
+    ('_', "..--.-"),
+    ('"', ".-..-."),
+    ('$', "...-..-"),
+    ('@', ".--.-."),
+];
+
+/// 点划串对应的字符
+pub fn letter(code: &str) -> Option<char> {
+    TABLE.iter().find(|(_, c)| *c == code).map(|(ch, _)| *ch)
+}
+
+/// 字符的点划串（不区分大小写）；码表中没有的字符返回 None
+pub fn code(ch: char) -> Option<&'static str> {
+    let upper = ch.to_ascii_uppercase();
+    TABLE.iter().find(|(c, _)| *c == upper).map(|(_, code)| *code)
+}
+
+/// 消息中无法用莫尔斯码表示的字符（空格除外），按首次出现顺序去重
+pub fn unsupported(message: &str) -> Vec<char> {
+    let mut out: Vec<char> = Vec::new();
+    for ch in message.chars().filter(|&c| c != ' ' && code(c).is_none()) {
+        if !out.contains(&ch) {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// 按单词切分的点划串：每个单词是字母的点划串列表；码表以外的字符跳过
+pub fn words(message: &str) -> Vec<Vec<&'static str>> {
+    message
+        .split(' ')
+        .map(|w| w.chars().filter_map(code).collect::<Vec<_>>())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// 把点划串序列还原为文本；`None` 表示词间空格。有无法识别的点划串时返回 None
+pub fn decode(codes: &[Option<String>]) -> Option<String> {
+    let mut out = String::new();
+    for code in codes {
+        match code {
+            Some(code) => out.push(letter(code)?),
+            None if !out.is_empty() && !out.ends_with(' ') => out.push(' '),
+            None => {}
+        }
+    }
+    let trimmed = out.trim_end().to_string();
+    (!trimmed.is_empty()).then_some(trimmed)
+}
@@ -0,0 +1,136 @@
+//! 用户名/昵称检查 - 识别全由不可见字符组成或用不可见字符填充来冒充他人的名字
+//!
+//! 平台通常只按字节比较名字，`admin` 与 `admin\u{200B}` 会被视为两个账号；
+//! 而韩文填充符、盲文空白等字符在界面上显示为空，可以注册出「看不见」的名字。
+//! 这里给出去除不可见字符、NFKC 规范化并合并空白后的可见形式，用于查重比较。
+
+use unicode_normalization::UnicodeNormalization;
+
+use super::chars::{category_of, is_zero_width, name_of};
+
+/// 盲文空白：不在默认可忽略字符中，但显示为空
+const BRAILLE_BLANK: char = '\u{2800}';
+
+/// 显示为空白的字符：零宽/不可见字符、盲文空白和各类空格
+pub fn is_blank_like(ch: char) -> bool {
+    is_zero_width(ch) || ch == BRAILLE_BLANK || ch.is_whitespace()
+}
+
+/// 空白类字符的名称（盲文空白与普通空格不在不可见字符表中）
+pub fn blank_name(ch: char) -> String {
+    match ch {
+        BRAILLE_BLANK => "BRAILLE PATTERN BLANK".to_string(),
+        _ => name_of(ch).unwrap_or_else(|| if ch.is_whitespace() { "WHITESPACE".to_string() } else { String::new() }),
+    }
+}
+
+/// 发现的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// 没有任何可见字符
+    AllInvisible,
+    /// 首尾有空白/不可见填充
+    Padded { leading: usize, trailing: usize },
+    /// 可见字符之间夹有不可见字符
+    HiddenInside(usize),
+    /// 含方向控制字符，显示顺序可能与实际不同
+    Bidi,
+    /// 规范化后与已有名字相同
+    Collides(String),
+}
+
+impl Issue {
+    pub fn describe(&self) -> String {
+        match self {
+            Issue::AllInvisible => "名字没有任何可见字符，界面上显示为空".to_string(),
+            Issue::Padded { leading, trailing } => {
+                format!("首尾有空白/不可见填充（开头 {} 个，结尾 {} 个）", leading, trailing)
+            }
+            Issue::HiddenInside(n) => format!("可见字符之间夹有 {} 个不可见字符", n),
+            Issue::Bidi => "含方向控制字符，显示顺序可能与实际字符顺序不同".to_string(),
+            Issue::Collides(other) => format!("规范化后与已有名字 {:?} 相同，可能是冒充", other),
+        }
+    }
+
+    /// 高风险：显示为空、与已有名字冲突或可能打乱显示顺序
+    pub fn is_severe(&self) -> bool {
+        matches!(self, Issue::AllInvisible | Issue::Collides(_) | Issue::Bidi)
+    }
+}
+
+/// 单个名字的检查结果
+#[derive(Debug, Clone)]
+pub struct NameReport {
+    pub name: String,
+    /// 去除不可见字符、NFKC 规范化并合并空白后的可见形式
+    pub normalized: String,
+    /// 名字中的不可见/空白字符（不含 ASCII 空格）（码点, 次数）
+    pub blanks: Vec<(u32, usize)>,
+    pub issues: Vec<Issue>,
+}
+
+impl NameReport {
+    pub fn risk(&self) -> &'static str {
+        if self.issues.iter().any(Issue::is_severe) {
+            "高"
+        } else if self.issues.is_empty() {
+            "无"
+        } else {
+            "中"
+        }
+    }
+}
+
+/// 名字的可见形式：删除不可见字符与盲文空白，NFKC 规范化，合并并去掉首尾空白
+pub fn normalize(name: &str) -> String {
+    let visible: String = name.chars().filter(|&c| !is_zero_width(c) && c != BRAILLE_BLANK).collect();
+    visible.nfkc().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 查重用的比较键（忽略大小写）
+fn compare_key(name: &str) -> String {
+    normalize(name).to_lowercase()
+}
+
+/// 检查一个名字；`existing` 为已注册的名字，用于发现冒充
+pub fn check_name(name: &str, existing: &[String]) -> NameReport {
+    let chars: Vec<char> = name.chars().collect();
+    let mut issues = Vec::new();
+
+    let leading = chars.iter().take_while(|&&c| is_blank_like(c)).count();
+    if leading == chars.len() {
+        issues.push(Issue::AllInvisible);
+    } else {
+        let trailing = chars.iter().rev().take_while(|&&c| is_blank_like(c)).count();
+        if leading > 0 || trailing > 0 {
+            issues.push(Issue::Padded { leading, trailing });
+        }
+        let inside = chars[leading..chars.len() - trailing]
+            .iter()
+            .filter(|&&c| is_zero_width(c) || c == BRAILLE_BLANK)
+            .count();
+        if inside > 0 {
+            issues.push(Issue::HiddenInside(inside));
+        }
+    }
+    if chars.iter().any(|&c| category_of(c) == Some("方向控制")) {
+        issues.push(Issue::Bidi);
+    }
+
+    let key = compare_key(name);
+    if !key.is_empty() {
+        if let Some(other) = existing.iter().find(|e| e.as_str() != name && compare_key(e) == key) {
+            issues.push(Issue::Collides(other.clone()));
+        }
+    }
+
+    let mut blanks: Vec<(u32, usize)> = Vec::new();
+    for &c in chars.iter().filter(|&&c| c != ' ' && is_blank_like(c)) {
+        match blanks.iter_mut().find(|(cp, _)| *cp == c as u32) {
+            Some((_, n)) => *n += 1,
+            None => blanks.push((c as u32, 1)),
+        }
+    }
+
+    NameReport { name: name.to_string(), normalized: normalize(name), blanks, issues }
+}
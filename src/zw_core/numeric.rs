@@ -0,0 +1,212 @@
+//! 数值载荷 - 把解出的数字串解读为 Unix 时间戳、经纬度或十进制数
+//!
+//! 地理寻宝（geocache）和 OSINT 类题目藏的往往是坐标或时间而不是文字，按文本打分时这类结果
+//! 字母少、信息量低，容易排在数字下标等误读之后。这里只处理整体由数字与分隔符组成的结果，
+//! 每种解读都带合理性检查（时间落在 1990–2100 年、纬度 ±90、经度 ±180 等），通过检查的结果
+//! 在评分时加分，并在解码结果中给出解读。
+
+/// 合理时间戳的范围: 1990-01-01 至 2100-01-01（UTC 秒）
+const MIN_TIMESTAMP: i64 = 631_152_000;
+const MAX_TIMESTAMP: i64 = 4_102_444_800;
+/// 坐标每个分量至少要有这么多位小数，排除 1,2 这样的普通数字对
+const MIN_COORD_DECIMALS: usize = 2;
+/// 十进制数至少这么多位才尝试按 ASCII 码或大整数还原
+const MIN_DECIMAL_DIGITS: usize = 6;
+
+/// 一种数值解读
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reading {
+    /// Unix 时间戳；`millis` 为毫秒精度
+    Timestamp { seconds: i64, millis: bool },
+    /// 十进制度数的经纬度；`swapped` 表示原文按 经度,纬度 的顺序
+    Coordinates { lat: f64, lon: f64, swapped: bool },
+    /// 十进制数字串按 ASCII 码（2–3 位一组）还原的文本
+    DecimalAscii(String),
+    /// 十进制大整数按大端字节还原的文本（CTF 常见的 long_to_bytes）
+    BigInteger(String),
+}
+
+impl Reading {
+    pub fn describe(&self) -> String {
+        match self {
+            Reading::Timestamp { seconds, millis } => format!(
+                "Unix 时间戳（{}）→ {} UTC",
+                if *millis { "毫秒" } else { "秒" },
+                format_utc(*seconds)
+            ),
+            Reading::Coordinates { lat, lon, swapped } => format!(
+                "经纬度 → {}（{:.6}, {:.6}）{}",
+                format_latlon(*lat, *lon),
+                lat,
+                lon,
+                if *swapped { "，原文为 经度,纬度 顺序" } else { "" }
+            ),
+            Reading::DecimalAscii(text) => format!("十进制 ASCII 码 → {:?}", text),
+            Reading::BigInteger(text) => format!("十进制大整数按字节还原 → {:?}", text),
+        }
+    }
+
+    /// 时间戳与坐标是载荷本身的含义；按 ASCII/字节还原只是下一步线索，不参与加分
+    pub fn is_final(&self) -> bool {
+        matches!(self, Reading::Timestamp { .. } | Reading::Coordinates { .. })
+    }
+}
+
+/// 数字串中的一个数: 原文、数值、小数位数
+struct Number<'a> {
+    raw: &'a str,
+    value: f64,
+    decimals: usize,
+}
+
+/// 把结果切成数字；含数字与分隔符（空白 , ; /）、度数符号和半球字母以外的字符时返回 None
+fn numbers(text: &str) -> Option<Vec<Number<'_>>> {
+    let mut out = Vec::new();
+    for token in text.split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '/')).filter(|t| !t.is_empty()) {
+        let (body, hemisphere) = match token.trim_end_matches('°').char_indices().last() {
+            Some((i, c @ ('N' | 'S' | 'E' | 'W'))) => (&token[..i], Some(c)),
+            _ => (token.trim_end_matches('°'), None),
+        };
+        let body = body.trim_end_matches('°');
+        let digits = body.trim_start_matches(['-', '+']);
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        if int.is_empty() || !int.bytes().all(|b| b.is_ascii_digit()) || !frac.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let mut value: f64 = body.parse().ok()?;
+        if matches!(hemisphere, Some('S' | 'W')) {
+            value = -value.abs();
+        }
+        out.push(Number { raw: body, value, decimals: frac.len() });
+    }
+    (!out.is_empty()).then_some(out)
+}
+
+/// 结果的全部数值解读；不是数字串时返回空
+pub fn readings(decoded: &str) -> Vec<Reading> {
+    let Some(nums) = numbers(decoded.trim()) else {
+        return vec![];
+    };
+    let mut out = Vec::new();
+    match nums.as_slice() {
+        [a, b] if a.decimals >= MIN_COORD_DECIMALS && b.decimals >= MIN_COORD_DECIMALS => {
+            let (lat, lon) = (a.value, b.value);
+            if lat.abs() <= 90.0 && lon.abs() <= 180.0 && (lat != 0.0 || lon != 0.0) {
+                out.push(Reading::Coordinates { lat, lon, swapped: false });
+            } else if lon.abs() <= 90.0 && lat.abs() <= 180.0 {
+                out.push(Reading::Coordinates { lat: lon, lon: lat, swapped: true });
+            }
+        }
+        [n] if n.decimals == 0 && !n.raw.starts_with(['-', '+']) => {
+            let digits = n.raw;
+            if let Some(seconds) = timestamp(digits) {
+                out.push(Reading::Timestamp { seconds, millis: digits.len() == 13 });
+            }
+            if digits.len() >= MIN_DECIMAL_DIGITS {
+                if let Some(text) = decimal_ascii(digits) {
+                    out.push(Reading::DecimalAscii(text));
+                }
+                if let Some(text) = big_integer_text(digits) {
+                    out.push(Reading::BigInteger(text));
+                }
+            }
+        }
+        _ => {}
+    }
+    out
+}
+
+/// 结果能否解读为合理的时间戳或坐标（用于评分加分）
+pub fn plausible(decoded: &str) -> bool {
+    readings(decoded).iter().any(Reading::is_final)
+}
+
+/// 10 位秒或 13 位毫秒、落在合理范围内的时间戳
+fn timestamp(digits: &str) -> Option<i64> {
+    let seconds = match digits.len() {
+        9 | 10 => digits.parse::<i64>().ok()?,
+        13 => digits.parse::<i64>().ok()? / 1000,
+        _ => return None,
+    };
+    (MIN_TIMESTAMP..MAX_TIMESTAMP).contains(&seconds).then_some(seconds)
+}
+
+/// 按 ASCII 码切分数字串: 先试定长 3 位，再试贪心 2–3 位（能组成 32–126 的最长前缀）
+fn decimal_ascii(digits: &str) -> Option<String> {
+    let printable = |v: u32| (32..127).contains(&v).then(|| char::from(v as u8));
+    let fixed = || -> Option<String> {
+        if !digits.len().is_multiple_of(3) {
+            return None;
+        }
+        (0..digits.len()).step_by(3).map(|i| printable(digits[i..i + 3].parse().ok()?)).collect()
+    };
+    let greedy = || -> Option<String> {
+        let mut out = String::new();
+        let mut i = 0;
+        while i < digits.len() {
+            let three = digits.get(i..i + 3).and_then(|s| s.parse().ok()).and_then(printable);
+            let two = digits.get(i..i + 2).and_then(|s| s.parse().ok()).and_then(printable);
+            match (three, two) {
+                (Some(c), _) if !digits[i..].starts_with('0') => {
+                    out.push(c);
+                    i += 3;
+                }
+                (_, Some(c)) => {
+                    out.push(c);
+                    i += 2;
+                }
+                _ => return None,
+            }
+        }
+        Some(out)
+    };
+    fixed().or_else(greedy).filter(|t| t.chars().filter(|c| c.is_ascii_alphanumeric()).count() * 2 >= t.len())
+}
+
+/// 十进制大整数转大端字节，全部可打印时返回文本
+fn big_integer_text(digits: &str) -> Option<String> {
+    let mut number: Vec<u32> = digits.bytes().map(|b| u32::from(b - b'0')).collect();
+    let mut bytes = Vec::new();
+    // 反复除以 256，余数即低位字节
+    while number.iter().any(|&d| d != 0) {
+        let mut rem = 0u32;
+        for d in number.iter_mut() {
+            let cur = rem * 10 + *d;
+            *d = cur / 256;
+            rem = cur % 256;
+        }
+        bytes.push(rem as u8);
+    }
+    bytes.reverse();
+    let text = String::from_utf8(bytes).ok()?;
+    (text.chars().count() >= 2 && text.chars().all(|c| !c.is_control())).then_some(text)
+}
+
+/// UTC 时间（年-月-日 时:分:秒），按公历换算天数
+fn format_utc(seconds: i64) -> String {
+    let days = seconds.div_euclid(86_400);
+    let secs = seconds.rem_euclid(86_400);
+    // 1970-03-01 起算的公历换算（Howard Hinnant 的 civil_from_days）
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+/// 度分秒与半球表示，如 48°51'30.1"N 2°17'40.1"E
+fn format_latlon(lat: f64, lon: f64) -> String {
+    let dms = |v: f64, pos: char, neg: char| {
+        let total = v.abs() * 3600.0;
+        let d = (total / 3600.0).floor();
+        let m = ((total - d * 3600.0) / 60.0).floor();
+        let s = total - d * 3600.0 - m * 60.0;
+        format!("{}°{}'{:.1}\"{}", d, m, s, if v < 0.0 { neg } else { pos })
+    };
+    format!("{} {}", dms(lat, 'N', 'S'), dms(lon, 'E', 'W'))
+}
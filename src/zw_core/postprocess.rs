@@ -0,0 +1,458 @@
+//! 解码结果的后处理 - base64、hex、gzip/zlib、单字节 XOR、ROT 与自定义命令
+//!
+//! 零宽载荷解出的往往不是 flag 本身，而是二次编码的数据。每种变换实现 [`PostProcessor`]，
+//! 注册表按配置的顺序组合它们，逐层尝试（深度优先，深度上限可配置），直到得到含 flag 的文本。
+//! 新的变换只需实现该 trait 并加入注册表，调用方不必关心具体有哪些变换。
+
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use super::engine;
+use super::ingest;
+
+/// 默认的链深度上限
+pub const DEFAULT_MAX_DEPTH: usize = 3;
+/// 允许配置的链深度上限
+pub const MAX_DEPTH_LIMIT: usize = 8;
+/// 内置变换的默认顺序
+pub const BUILTIN_NAMES: &[&str] = &["base64", "hex", "gzip", "xor", "rot"];
+/// 解压结果的大小上限，防止压缩炸弹
+const INFLATE_LIMIT: usize = 16 * 1024 * 1024;
+
+/// 一次变换的结果
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub output: Vec<u8>,
+    /// 变换参数（如 XOR 的密钥、ROT 的位移），展示在方案链中
+    pub detail: Option<String>,
+}
+
+impl Step {
+    fn plain(output: Vec<u8>) -> Self {
+        Self { output, detail: None }
+    }
+}
+
+/// 后处理变换；输入不像该变换能处理的数据时返回 None
+pub trait PostProcessor: Send + Sync {
+    fn name(&self) -> &str;
+    fn description(&self) -> String;
+    fn apply(&self, input: &[u8]) -> Option<Step>;
+}
+
+/// 可打印文本（允许常见空白）所占比例
+fn printable_ratio(bytes: &[u8]) -> f64 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+    let printable = bytes.iter().filter(|&&b| b.is_ascii_graphic() || matches!(b, b' ' | b'\n' | b'\r' | b'\t')).count();
+    printable as f64 / bytes.len() as f64
+}
+
+/// 去掉空白后的 ASCII 文本
+fn compact_ascii(input: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(input).ok()?;
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.is_ascii().then_some(compact)
+}
+
+struct Base64;
+
+impl PostProcessor for Base64 {
+    fn name(&self) -> &str {
+        "base64"
+    }
+
+    fn description(&self) -> String {
+        "base64（标准与 URL-safe 字母表）".to_string()
+    }
+
+    fn apply(&self, input: &[u8]) -> Option<Step> {
+        let text = compact_ascii(input)?;
+        let body = text.trim_end_matches('=');
+        if body.len() < 8 || body.len() % 4 == 1 || text.len() - body.len() > 2 {
+            return None;
+        }
+        if !body.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'-' | b'_')) {
+            return None;
+        }
+        // 纯十六进制串交给 hex 处理
+        if body.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        ingest::decode_base64(&text).ok().filter(|b| !b.is_empty()).map(Step::plain)
+    }
+}
+
+struct Hex;
+
+impl PostProcessor for Hex {
+    fn name(&self) -> &str {
+        "hex"
+    }
+
+    fn description(&self) -> String {
+        "十六进制（可带 0x 前缀）".to_string()
+    }
+
+    fn apply(&self, input: &[u8]) -> Option<Step> {
+        let text = compact_ascii(input)?;
+        let body = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).unwrap_or(&text);
+        if body.len() < 4 || body.len() % 2 != 0 || !body.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let bytes = (0..body.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&body[i..i + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        Some(Step::plain(bytes))
+    }
+}
+
+struct Gzip;
+
+impl Gzip {
+    /// 跳过 gzip 头部（RFC 1952），返回 deflate 数据的起始偏移
+    fn deflate_start(input: &[u8]) -> Option<usize> {
+        if input.len() < 18 || input[..3] != [0x1F, 0x8B, 0x08] {
+            return None;
+        }
+        let flags = input[3];
+        let mut pos = 10;
+        if flags & 0x04 != 0 {
+            let xlen = u16::from_le_bytes([*input.get(pos)?, *input.get(pos + 1)?]) as usize;
+            pos += 2 + xlen;
+        }
+        for bit in [0x08, 0x10] {
+            if flags & bit != 0 {
+                pos += input.get(pos..)?.iter().position(|&b| b == 0)? + 1;
+            }
+        }
+        if flags & 0x02 != 0 {
+            pos += 2;
+        }
+        (pos < input.len()).then_some(pos)
+    }
+}
+
+impl PostProcessor for Gzip {
+    fn name(&self) -> &str {
+        "gzip"
+    }
+
+    fn description(&self) -> String {
+        "gzip / zlib 解压".to_string()
+    }
+
+    fn apply(&self, input: &[u8]) -> Option<Step> {
+        use miniz_oxide::inflate::{decompress_to_vec_with_limit, decompress_to_vec_zlib_with_limit};
+        if let Some(start) = Self::deflate_start(input) {
+            return decompress_to_vec_with_limit(&input[start..], INFLATE_LIMIT)
+                .ok()
+                .map(Step::plain);
+        }
+        let zlib = input.len() >= 6 && input[0] & 0x0F == 8 && (u16::from(input[0]) << 8 | u16::from(input[1])) % 31 == 0;
+        if zlib {
+            return decompress_to_vec_zlib_with_limit(input, INFLATE_LIMIT)
+                .ok()
+                .map(|out| Step { output: out, detail: Some("zlib".to_string()) });
+        }
+        None
+    }
+}
+
+struct Xor;
+
+impl PostProcessor for Xor {
+    fn name(&self) -> &str {
+        "xor"
+    }
+
+    fn description(&self) -> String {
+        "单字节 XOR（选可打印比例最高的密钥）".to_string()
+    }
+
+    fn apply(&self, input: &[u8]) -> Option<Step> {
+        // 只处理明显不是文本的数据，否则几乎任何输入都能凑出“可打印”的结果
+        if input.len() < 4 || printable_ratio(input) > 0.7 {
+            return None;
+        }
+        // 可打印比例相同的密钥很多，依次用 flag 前缀与文本得分区分
+        let rank = |out: &[u8]| {
+            let text = String::from_utf8_lossy(out);
+            (engine::has_flag_prefix(&text), printable_ratio(out), engine::score(&text))
+        };
+        let (key, output, _) = (1..=255u8)
+            .map(|key| {
+                let out: Vec<u8> = input.iter().map(|b| b ^ key).collect();
+                let r = rank(&out);
+                (key, out, r)
+            })
+            .max_by(|(_, _, a), (_, _, b)| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)).then(a.2.total_cmp(&b.2)))?;
+        (printable_ratio(&output) >= 0.95).then(|| Step { output, detail: Some(format!("key=0x{:02X}", key)) })
+    }
+}
+
+struct Rot;
+
+impl Rot {
+    fn rotate(text: &str, n: u8) -> String {
+        text.chars()
+            .map(|c| match c {
+                'a'..='z' => ((c as u8 - b'a' + n) % 26 + b'a') as char,
+                'A'..='Z' => ((c as u8 - b'A' + n) % 26 + b'A') as char,
+                _ => c,
+            })
+            .collect()
+    }
+}
+
+impl PostProcessor for Rot {
+    fn name(&self) -> &str {
+        "rot"
+    }
+
+    fn description(&self) -> String {
+        "ROT-N 字母位移（只保留能得到 flag 前缀的位移）".to_string()
+    }
+
+    fn apply(&self, input: &[u8]) -> Option<Step> {
+        let text = std::str::from_utf8(input).ok()?;
+        if !text.chars().any(|c| c.is_ascii_alphabetic()) || engine::has_flag_prefix(text) {
+            return None;
+        }
+        // 任意位移都能得到“合法”文本，只有出现 flag 前缀时才能判断哪个是对的
+        (1..26u8).find_map(|n| {
+            let rotated = Self::rotate(text, n);
+            engine::has_flag_prefix(&rotated)
+                .then(|| Step { output: rotated.into_bytes(), detail: Some(format!("ROT-{}", n)) })
+        })
+    }
+}
+
+/// 配置文件中的自定义命令：从 stdin 读入数据，stdout 输出变换结果
+#[derive(Debug, Clone)]
+pub struct CommandSpec {
+    pub name: String,
+    pub argv: Vec<String>,
+    pub timeout: Duration,
+}
+
+struct CommandProcessor(CommandSpec);
+
+impl PostProcessor for CommandProcessor {
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    fn description(&self) -> String {
+        format!("自定义命令 {}", self.0.argv.join(" "))
+    }
+
+    fn apply(&self, input: &[u8]) -> Option<Step> {
+        let (program, rest) = self.0.argv.split_first()?;
+        let mut child = Command::new(program)
+            .args(rest)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| tracing::warn!("postprocess command {} failed to start: {}", self.0.name, e))
+            .ok()?;
+        // 读写放在独立线程，避免管道缓冲区写满时互相等待
+        let mut stdin = child.stdin.take()?;
+        let data = input.to_vec();
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&data);
+        });
+        let mut stdout = child.stdout.take()?;
+        let reader = std::thread::spawn(move || {
+            let mut out = Vec::new();
+            let _ = stdout.by_ref().take(INFLATE_LIMIT as u64).read_to_end(&mut out);
+            out
+        });
+        let deadline = Instant::now() + self.0.timeout;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(5)),
+                _ => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    tracing::warn!("postprocess command {} timed out", self.0.name);
+                    return None;
+                }
+            }
+        };
+        let output = reader.join().ok()?;
+        (status.success() && !output.is_empty() && output != input).then(|| Step::plain(output))
+    }
+}
+
+struct Registry {
+    processors: Vec<Arc<dyn PostProcessor>>,
+    max_depth: usize,
+}
+
+static REGISTRY: OnceLock<RwLock<Registry>> = OnceLock::new();
+
+fn builtin(name: &str) -> Option<Arc<dyn PostProcessor>> {
+    let p: Arc<dyn PostProcessor> = match name {
+        "base64" => Arc::new(Base64),
+        "hex" => Arc::new(Hex),
+        "gzip" => Arc::new(Gzip),
+        "xor" => Arc::new(Xor),
+        "rot" => Arc::new(Rot),
+        _ => return None,
+    };
+    Some(p)
+}
+
+fn registry() -> &'static RwLock<Registry> {
+    REGISTRY.get_or_init(|| {
+        RwLock::new(Registry {
+            processors: BUILTIN_NAMES.iter().filter_map(|n| builtin(n)).collect(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        })
+    })
+}
+
+/// 已校验、待应用的注册表配置
+pub struct Settings {
+    processors: Vec<Arc<dyn PostProcessor>>,
+    max_depth: usize,
+}
+
+impl Settings {
+    /// 启用的变换名（按执行顺序）
+    pub fn names(&self) -> Vec<String> {
+        self.processors.iter().map(|p| p.name().to_string()).collect()
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+}
+
+/// 校验配置：`order` 列出启用的变换及顺序（省略时为全部内置变换，其后是自定义命令）
+pub fn build(order: Option<&[String]>, max_depth: Option<usize>, commands: Vec<CommandSpec>) -> Result<Settings, String> {
+    let max_depth = max_depth.unwrap_or(DEFAULT_MAX_DEPTH);
+    if !(1..=MAX_DEPTH_LIMIT).contains(&max_depth) {
+        return Err(format!("postprocess.max_depth 必须在 1~{} 之间", MAX_DEPTH_LIMIT));
+    }
+    let mut custom: Vec<Arc<dyn PostProcessor>> = Vec::new();
+    for spec in commands {
+        if builtin(&spec.name).is_some() || custom.iter().any(|p| p.name() == spec.name) {
+            return Err(format!("postprocess.commands 名称重复: {}", spec.name));
+        }
+        if spec.argv.is_empty() {
+            return Err(format!("postprocess.commands {} 的 command 不能为空", spec.name));
+        }
+        custom.push(Arc::new(CommandProcessor(spec)));
+    }
+    let processors = match order {
+        None => BUILTIN_NAMES.iter().filter_map(|n| builtin(n)).chain(custom).collect(),
+        Some(order) => {
+            let mut out: Vec<Arc<dyn PostProcessor>> = Vec::new();
+            for name in order {
+                if out.iter().any(|p| p.name() == name) {
+                    return Err(format!("postprocess.order 中重复的变换: {}", name));
+                }
+                let p = builtin(name)
+                    .or_else(|| custom.iter().find(|p| p.name() == name).cloned())
+                    .ok_or_else(|| {
+                        let mut names: Vec<&str> = BUILTIN_NAMES.to_vec();
+                        names.extend(custom.iter().map(|p| p.name()));
+                        format!("postprocess.order 中的未知变换: {}（可用: {}）", name, names.join("、"))
+                    })?;
+                out.push(p);
+            }
+            out
+        }
+    };
+    Ok(Settings { processors, max_depth })
+}
+
+/// 应用已校验的配置
+pub fn configure(settings: Settings) {
+    let mut reg = registry().write().unwrap_or_else(|e| e.into_inner());
+    reg.processors = settings.processors;
+    reg.max_depth = settings.max_depth;
+}
+
+/// 当前启用的变换：(名称, 说明)
+pub fn processors() -> Vec<(String, String)> {
+    let reg = registry().read().unwrap_or_else(|e| e.into_inner());
+    reg.processors.iter().map(|p| (p.name().to_string(), p.description())).collect()
+}
+
+/// 当前的链深度上限
+pub fn max_depth() -> usize {
+    registry().read().unwrap_or_else(|e| e.into_inner()).max_depth
+}
+
+/// 一条后处理链
+#[derive(Debug, Clone)]
+pub struct Chain {
+    /// 依次经过的变换（含参数）
+    pub steps: Vec<String>,
+    pub output: String,
+    pub score: f64,
+    pub flags: Vec<String>,
+}
+
+/// 对解码文本逐层尝试已启用的变换，返回得到可读文本的链：含 flag 的在前，其余按得分降序
+pub fn run(input: &str) -> Vec<Chain> {
+    let (processors, max_depth) = {
+        let reg = registry().read().unwrap_or_else(|e| e.into_inner());
+        (reg.processors.clone(), reg.max_depth)
+    };
+    let mut seen = HashSet::new();
+    seen.insert(input.as_bytes().to_vec());
+    let mut chains = Vec::new();
+    walk(&processors, input.as_bytes(), &mut Vec::new(), max_depth, &mut seen, &mut chains);
+    chains.sort_by(|a, b| a.flags.is_empty().cmp(&b.flags.is_empty()).then(b.score.total_cmp(&a.score)));
+    chains
+}
+
+fn walk(
+    processors: &[Arc<dyn PostProcessor>],
+    input: &[u8],
+    steps: &mut Vec<String>,
+    depth_left: usize,
+    seen: &mut HashSet<Vec<u8>>,
+    chains: &mut Vec<Chain>,
+) {
+    if depth_left == 0 {
+        return;
+    }
+    for p in processors {
+        let Some(step) = p.apply(input) else {
+            continue;
+        };
+        if step.output.is_empty() || !seen.insert(step.output.clone()) {
+            continue;
+        }
+        steps.push(match &step.detail {
+            Some(d) => format!("{}({})", p.name(), d),
+            None => p.name().to_string(),
+        });
+        let mut found_flag = false;
+        if let Ok(text) = std::str::from_utf8(&step.output) {
+            let readable = text.chars().filter(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t')).count();
+            if readable as f64 >= text.chars().count() as f64 * 0.9 {
+                let flags = engine::find_flags(text);
+                found_flag = !flags.is_empty();
+                chains.push(Chain { steps: steps.clone(), output: text.to_string(), score: engine::score(text), flags });
+            }
+        }
+        // 已得到 flag 的分支不再继续变换
+        if !found_flag {
+            walk(processors, &step.output, steps, depth_left - 1, seen, chains);
+        }
+        steps.pop();
+    }
+}
@@ -0,0 +1,377 @@
+//! 载体文本画像 - 统计可见文本的文字系统构成，判断是代码、正文还是数据
+//!
+//! 载体的类型决定了哪些不可见字符属于正常现象（波斯语的 ZWNJ、天城文的 ZWJ、emoji 序列、
+//! 泰文的 ZWSP 断词），也决定了载荷经过平台转发后能否保留，因此在分析时一并给出。
+
+use super::chars::{is_arabic_script, is_unicode_tag, is_zero_width};
+use super::sanitize::is_emoji;
+
+/// 文字系统（按可见字符归类）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Script {
+    Latin,
+    Han,
+    Kana,
+    Hangul,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Indic,
+    Thai,
+    Emoji,
+    Digit,
+    Symbol,
+    Other,
+}
+
+impl Script {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Script::Latin => "拉丁字母",
+            Script::Han => "汉字",
+            Script::Kana => "日文假名",
+            Script::Hangul => "韩文",
+            Script::Cyrillic => "西里尔字母",
+            Script::Greek => "希腊字母",
+            Script::Arabic => "阿拉伯字母",
+            Script::Hebrew => "希伯来字母",
+            Script::Indic => "印度系文字",
+            Script::Thai => "泰文/老挝文/高棉文",
+            Script::Emoji => "emoji",
+            Script::Digit => "数字",
+            Script::Symbol => "标点/符号",
+            Script::Other => "其他",
+        }
+    }
+
+    /// 是否是书写语言的文字（数字、符号、emoji 除外）
+    fn is_letter(&self) -> bool {
+        !matches!(self, Script::Emoji | Script::Digit | Script::Symbol | Script::Other)
+    }
+}
+
+/// 单个可见字符的文字系统；空白与控制字符返回 None
+pub fn script_of(ch: char) -> Option<Script> {
+    if ch.is_whitespace() || ch.is_control() || is_zero_width(ch) {
+        return None;
+    }
+    if ch.is_ascii_digit() {
+        return Some(Script::Digit);
+    }
+    if ch.is_ascii_punctuation() {
+        return Some(Script::Symbol);
+    }
+    if is_emoji(ch) {
+        return Some(Script::Emoji);
+    }
+    if is_arabic_script(ch) {
+        return Some(Script::Arabic);
+    }
+    let script = match ch as u32 {
+        0x0041..=0x024F | 0x1E00..=0x1EFF => Script::Latin,
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x0400..=0x052F => Script::Cyrillic,
+        0x0590..=0x05FF => Script::Hebrew,
+        0x0900..=0x0DFF => Script::Indic,
+        0x0E00..=0x0EFF | 0x1780..=0x17FF => Script::Thai,
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Script::Hangul,
+        0x3040..=0x30FF | 0x31F0..=0x31FF => Script::Kana,
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x3134F => Script::Han,
+        0x2000..=0x2BFF | 0x3000..=0x303F | 0xFF00..=0xFF65 => Script::Symbol,
+        _ if ch.is_alphabetic() => Script::Other,
+        _ if ch.is_numeric() => Script::Digit,
+        _ => Script::Symbol,
+    };
+    Some(script)
+}
+
+/// 载体的内容类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverKind {
+    Code,
+    Prose,
+    Data,
+    Mixed,
+}
+
+impl CoverKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoverKind::Code => "源代码",
+            CoverKind::Prose => "自然语言正文",
+            CoverKind::Data => "结构化数据（CSV/JSON/日志等）",
+            CoverKind::Mixed => "混合/无法判断",
+        }
+    }
+}
+
+/// 载体画像
+#[derive(Debug, Clone)]
+pub struct CoverProfile {
+    pub visible: usize,
+    pub lines: usize,
+    /// 各文字系统的字符数，按数量降序
+    pub scripts: Vec<(Script, usize)>,
+    pub kind: CoverKind,
+    /// 判断内容类型的依据
+    pub evidence: Vec<String>,
+}
+
+impl CoverProfile {
+    fn share(&self, script: Script) -> f64 {
+        let n = self.scripts.iter().find(|(s, _)| *s == script).map(|(_, n)| *n).unwrap_or(0);
+        if self.visible == 0 {
+            0.0
+        } else {
+            n as f64 / self.visible as f64
+        }
+    }
+
+    /// 文字中占比最高的书写系统
+    pub fn dominant_letters(&self) -> Option<Script> {
+        self.scripts.iter().find(|(s, _)| s.is_letter()).map(|(s, _)| *s)
+    }
+
+    /// 该载体中属于正常现象的不可见字符
+    pub fn expected_invisibles(&self) -> Vec<&'static str> {
+        let mut out = Vec::new();
+        let present = |s: Script| self.share(s) > 0.0;
+        if present(Script::Arabic) {
+            out.push("阿拉伯字母之间的 ZWNJ（波斯语等正字法）、ALM/LRM/RLM 方向标记");
+        }
+        if present(Script::Hebrew) {
+            out.push("LRM/RLM 方向标记");
+        }
+        if present(Script::Indic) {
+            out.push("印度系文字中控制连写的 ZWJ/ZWNJ");
+        }
+        if present(Script::Thai) {
+            out.push("泰文/高棉文等无空格文字中作断词用的 ZWSP");
+        }
+        if present(Script::Emoji) {
+            out.push("emoji 组合序列中的 ZWJ、VS16/VS15 与旗帜 Tag 序列");
+        }
+        if present(Script::Han) || present(Script::Kana) {
+            out.push("排版软件偶尔插入的 ZWSP 换行提示");
+        }
+        out
+    }
+
+    /// 载荷在该类载体中的保留情况与隐蔽性建议
+    pub fn advice(&self) -> Vec<&'static str> {
+        let mut out = Vec::new();
+        match self.kind {
+            CoverKind::Code => {
+                out.push("代码中除 BOM 外几乎不应出现不可见字符，任何零宽字符都值得怀疑；编辑器与 git 会原样保留");
+                out.push("BiDi 控制字符会被编译器/代码托管平台的 Trojan Source 检查标记；插入位置应限于注释与字符串");
+            }
+            CoverKind::Prose => {
+                out.push("正文经过聊天/社交平台转发时常被 NFKC 规范化或剥离格式字符，建议先用 zw_simulate_channel 验证");
+            }
+            CoverKind::Data => {
+                out.push("结构化数据的解析器通常保留零宽字符，但 trim 与字段清洗会删掉首尾的字符；载荷应放在字段内部");
+            }
+            CoverKind::Mixed => {}
+        }
+        if self.dominant_letters().is_some_and(|s| s != Script::Latin) && !self.expected_invisibles().is_empty() {
+            out.push("上述正常出现的零宽字符可作掩护，但检测时基线对比需选用对应的文档类型");
+        }
+        out
+    }
+}
+
+const CODE_KEYWORDS: &[&str] = &[
+    "fn ", "def ", "function", "class ", "return", "import ", "#include", "let ", "const ", "var ", "public ",
+    "private ", "static ", "struct ", "=>", "->", "::", "==", "!=", "&&", "||",
+];
+
+/// 统计载体的文字构成并判断内容类型
+pub fn profile(text: &str) -> CoverProfile {
+    let mut counts: std::collections::BTreeMap<Script, usize> = std::collections::BTreeMap::new();
+    let mut visible = 0usize;
+    for ch in text.chars() {
+        if let Some(script) = script_of(ch) {
+            *counts.entry(script).or_default() += 1;
+            visible += 1;
+        }
+    }
+    let mut scripts: Vec<(Script, usize)> = counts.into_iter().collect();
+    scripts.sort_by_key(|&(s, n)| (std::cmp::Reverse(n), s));
+
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    let count = |s: Script| scripts.iter().find(|(x, _)| *x == s).map(|(_, n)| *n).unwrap_or(0);
+    let ratio = |n: usize| if visible == 0 { 0.0 } else { n as f64 / visible as f64 };
+
+    let code_symbols = text.chars().filter(|c| matches!(c, '{' | '}' | '(' | ')' | ';' | '=' | '<' | '>' | '[' | ']')).count();
+    let keyword_hits: usize = CODE_KEYWORDS.iter().map(|k| text.matches(k).count()).sum();
+    let code_endings = lines
+        .iter()
+        .filter(|l| l.trim_end().ends_with([';', '{', '}', ':', ',', ')']))
+        .count();
+    let indented = lines.iter().filter(|l| l.starts_with("    ") || l.starts_with('\t')).count();
+
+    let separators = [',', '\t', '|', ';'];
+    let delimited = separators.iter().any(|&sep| {
+        let per_line: Vec<usize> = lines.iter().map(|l| l.matches(sep).count()).collect();
+        lines.len() >= 3 && per_line[0] > 0 && per_line.iter().all(|&n| n == per_line[0])
+    });
+    let trimmed = text.trim_start();
+    let json_like = (trimmed.starts_with('{') || trimmed.starts_with('[')) && text.matches("\":").count() >= 2;
+
+    let mut evidence = Vec::new();
+    let code_score = ratio(code_symbols) * 10.0
+        + keyword_hits as f64 / lines.len().max(1) as f64
+        + if lines.is_empty() { 0.0 } else { (code_endings + indented) as f64 / lines.len() as f64 };
+    let prose_score = {
+        let words = text.split_whitespace().count();
+        let sentence_ends = text.chars().filter(|c| matches!(c, '.' | '!' | '?' | '。' | '！' | '？' | '，' | '،')).count();
+        let letters: usize = scripts.iter().filter(|(s, _)| s.is_letter()).map(|(_, n)| *n).sum();
+        ratio(letters) * 2.0 + if words > 0 { (sentence_ends as f64 / words as f64 * 5.0).min(1.0) } else { 0.0 }
+    };
+
+    let kind = if json_like || delimited {
+        evidence.push(if json_like { "JSON 结构（\"key\": 值）".to_string() } else { "每行分隔符数量一致".to_string() });
+        CoverKind::Data
+    } else if ratio(count(Script::Digit)) > 0.4 {
+        evidence.push(format!("数字占可见字符 {:.0}%", ratio(count(Script::Digit)) * 100.0));
+        CoverKind::Data
+    } else if code_score > 1.2 && code_score > prose_score * 0.6 {
+        evidence.push(format!(
+            "代码符号占 {:.0}%，关键字/运算符 {} 处，{} 行以 ; {{ }} 等结尾或缩进",
+            ratio(code_symbols) * 100.0,
+            keyword_hits,
+            code_endings.max(indented)
+        ));
+        CoverKind::Code
+    } else if prose_score > 1.5 {
+        evidence.push("以文字为主，含句读标点".to_string());
+        CoverKind::Prose
+    } else {
+        CoverKind::Mixed
+    };
+
+    CoverProfile { visible, lines: lines.len(), scripts, kind, evidence }
+}
+
+/// 格式化画像报告
+pub fn format_profile(p: &CoverProfile) -> String {
+    let mut out = String::from("\n载体文本画像:\n");
+    if p.visible == 0 {
+        out.push_str("  没有可见字符\n");
+        return out;
+    }
+    out.push_str(&format!("  可见字符 {} 个，非空行 {} 行\n", p.visible, p.lines));
+    let mix: Vec<String> = p
+        .scripts
+        .iter()
+        .take(6)
+        .map(|(s, n)| format!("{} {:.0}%", s.name(), *n as f64 * 100.0 / p.visible as f64))
+        .collect();
+    out.push_str(&format!("  文字构成: {}\n", mix.join("、")));
+    out.push_str(&format!("  内容类型: {}", p.kind.name()));
+    if !p.evidence.is_empty() {
+        out.push_str(&format!("，依据: {}", p.evidence.join("；")));
+    }
+    out.push('\n');
+    let expected = p.expected_invisibles();
+    if !expected.is_empty() {
+        out.push_str("  此类载体中正常出现的不可见字符:\n");
+        for e in expected {
+            out.push_str(&format!("    - {}\n", e));
+        }
+    }
+    for a in p.advice() {
+        out.push_str(&format!("  建议: {}\n", a));
+    }
+    out
+}
+
+/// 载荷字符与相邻文字冲突的情形
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// ZWNJ/ZWJ 夹在阿拉伯字母之间，改变连写
+    ArabicJoining,
+    /// ZWNJ/ZWJ 夹在印度系文字之间，改变连字（半字形、合体字）
+    IndicConjunct,
+    /// ZWJ、变体选择符或 Tag 挨着 emoji，与之组成序列
+    EmojiSequence,
+}
+
+impl ConflictKind {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ConflictKind::ArabicJoining => "夹在阿拉伯字母之间会断开或强制连写，字形随之改变（波斯语等正字法中 ZWNJ 是有意义的字符）",
+            ConflictKind::IndicConjunct => "夹在印度系文字之间会改变半字形与合体字的显示",
+            ConflictKind::EmojiSequence => "挨着 emoji 会与之组成 ZWJ 组合、变体或标签序列，改变 emoji 的显示",
+        }
+    }
+}
+
+/// 一类冲突：字符、情形与出现次数
+#[derive(Debug, Clone)]
+pub struct ScriptConflict {
+    pub ch: char,
+    pub kind: ConflictKind,
+    pub count: usize,
+}
+
+/// 不可见字符在前后可见字符之间是否有文字上的含义
+fn conflict(ch: char, prev: Option<char>, next: Option<char>) -> Option<ConflictKind> {
+    let script = |c: Option<char>| c.and_then(script_of);
+    let joiner = matches!(ch, '\u{200C}' | '\u{200D}');
+    let both = |s: Script| script(prev) == Some(s) && script(next) == Some(s);
+    if joiner && both(Script::Arabic) {
+        return Some(ConflictKind::ArabicJoining);
+    }
+    if joiner && both(Script::Indic) {
+        return Some(ConflictKind::IndicConjunct);
+    }
+    let after_emoji = script(prev) == Some(Script::Emoji);
+    let sequence = matches!(ch, '\u{FE0E}' | '\u{FE0F}') || is_unicode_tag(ch);
+    if (after_emoji && sequence) || (ch == '\u{200D}' && (after_emoji || script(next) == Some(Script::Emoji))) {
+        return Some(ConflictKind::EmojiSequence);
+    }
+    None
+}
+
+/// 统计文本中与相邻文字冲突的不可见字符（按字符与情形汇总）
+fn count_conflicts(text: &str) -> Vec<ScriptConflict> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out: Vec<ScriptConflict> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !is_zero_width(chars[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && is_zero_width(chars[i]) {
+            i += 1;
+        }
+        let prev = start.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i).copied();
+        for &ch in &chars[start..i] {
+            if let Some(kind) = conflict(ch, prev, next) {
+                match out.iter_mut().find(|c| c.ch == ch && c.kind == kind) {
+                    Some(c) => c.count += 1,
+                    None => out.push(ScriptConflict { ch, kind, count: 1 }),
+                }
+            }
+        }
+    }
+    out
+}
+
+/// 编码结果中由载荷引入的文字冲突：载体原有的（如正常的 emoji ZWJ 组合）不计
+pub fn script_conflicts(encoded: &str, cover: &str) -> Vec<ScriptConflict> {
+    let existing = count_conflicts(cover);
+    count_conflicts(encoded)
+        .into_iter()
+        .filter_map(|mut c| {
+            let before = existing.iter().find(|e| e.ch == c.ch && e.kind == c.kind).map_or(0, |e| e.count);
+            c.count = c.count.checked_sub(before).filter(|&n| n > 0)?;
+            Some(c)
+        })
+        .collect()
+}
@@ -0,0 +1,209 @@
+//! 多文档载荷重组 - 把拆散在多份附件中的零宽载荷按顺序拼回
+//!
+//! 两种拼法同时尝试：
+//! - 按流拼接：各文档的零宽字符流按顺序首尾相接后整体解码（`split_documents` 的拆法，
+//!   单份文档往往解不出完整字符）；
+//! - 按片段拼接：每份文档单独解码，去掉 `1/3:`、`[2]` 等序号头后合并，相邻片段的重叠部分只保留一次。
+//!
+//! 顺序优先取解码结果中的序号头，其次取文件名中的编号（`flag_1.txt`），都没有时按声明顺序。
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::chars::is_zero_width;
+use super::engine::{auto_decode, rank_results, score, Budget, DecodeResult};
+use super::session::Session;
+
+/// 一份输入文档
+#[derive(Debug, Clone)]
+pub struct Piece {
+    /// 文件路径或 `#序号`
+    pub label: String,
+    pub text: String,
+    /// 来自文件时才按文件名编号排序
+    pub from_file: bool,
+}
+
+/// 拼接顺序的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSource {
+    Declared,
+    Header,
+    Label,
+}
+
+impl OrderSource {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            OrderSource::Declared => "声明顺序",
+            OrderSource::Header => "解码结果中的序号头",
+            OrderSource::Label => "文件名中的编号",
+        }
+    }
+}
+
+/// 单份文档的解码情况
+#[derive(Debug, Clone)]
+pub struct PieceInfo {
+    pub label: String,
+    pub zw_chars: usize,
+    /// 单独解码的最佳结果
+    pub best: Option<DecodeResult>,
+    /// 序号头给出的 (序号, 总数)
+    pub header: Option<(usize, Option<usize>)>,
+    /// 文件名中的编号
+    pub label_number: Option<usize>,
+    /// 去掉序号头后的片段内容
+    pub body: String,
+}
+
+/// 重组结果
+#[derive(Debug, Clone)]
+pub struct Reassembly {
+    pub pieces: Vec<PieceInfo>,
+    /// 拼接顺序（`pieces` 的下标）
+    pub order: Vec<usize>,
+    pub source: OrderSource,
+    /// 按编号推断缺失的序号
+    pub missing: Vec<usize>,
+    /// 重复出现的序号（只保留第一份）
+    pub duplicates: Vec<usize>,
+    /// 按流拼接后的解码结果
+    pub stream: Vec<DecodeResult>,
+    /// 按片段拼接的结果与得分；有片段解不出时为 None
+    pub joined: Option<(String, f64)>,
+    /// 相邻片段合并时去掉的重叠字符数
+    pub overlaps: Vec<usize>,
+}
+
+fn header_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)^\s*(?:part\s*|p)?[\[(#]?\s*(\d{1,4})\s*(?:(?:/|of)\s*(\d{1,4}))?\s*[\])]?\s*[:：|\-]\s*").unwrap()
+    })
+}
+
+/// 解析片段开头的序号头，返回 (序号, 总数, 去掉序号头后的内容)
+fn parse_header(decoded: &str) -> Option<(usize, Option<usize>, String)> {
+    let caps = header_regex().captures(decoded)?;
+    let index = caps.get(1)?.as_str().parse().ok()?;
+    let total = caps.get(2).and_then(|m| m.as_str().parse().ok());
+    let rest = decoded[caps.get(0)?.end()..].to_string();
+    Some((index, total, rest))
+}
+
+/// 文件名（不含扩展名）中最后一组数字
+fn label_number(label: &str) -> Option<usize> {
+    let stem = std::path::Path::new(label).file_stem()?.to_str()?;
+    let end = stem.rfind(|c: char| c.is_ascii_digit())? + 1;
+    let start = stem[..end].rfind(|c: char| !c.is_ascii_digit()).map(|i| i + 1).unwrap_or(0);
+    stem[start..end].parse().ok()
+}
+
+/// 按编号排序；有文档缺少编号时返回 None
+fn order_by(numbers: &[Option<usize>]) -> Option<Vec<usize>> {
+    if numbers.iter().any(Option::is_none) {
+        return None;
+    }
+    let mut order: Vec<usize> = (0..numbers.len()).collect();
+    order.sort_by_key(|&i| numbers[i]);
+    Some(order)
+}
+
+/// 合并两个片段：`next` 的开头与 `acc` 的结尾重叠（至少 3 个字符）时只保留一次，返回重叠长度
+fn merge(acc: &mut String, next: &str) -> usize {
+    let acc_chars: Vec<char> = acc.chars().collect();
+    let next_chars: Vec<char> = next.chars().collect();
+    let max = acc_chars.len().min(next_chars.len());
+    let overlap = (3..=max)
+        .rev()
+        .find(|&k| acc_chars[acc_chars.len() - k..] == next_chars[..k])
+        .unwrap_or(0);
+    acc.extend(&next_chars[overlap..]);
+    overlap
+}
+
+/// 重组多份文档中的载荷；`declared_only` 为 true 时严格按输入顺序
+pub fn reassemble(pieces: &[Piece], declared_only: bool, budget: &Budget) -> Reassembly {
+    let infos: Vec<PieceInfo> = pieces
+        .iter()
+        .map(|p| {
+            let mut results = auto_decode(&p.text, budget);
+            rank_results(&mut results);
+            // 带序号头的候选优先：短片段的最高分候选常常是错误的分组
+            let best = results
+                .iter()
+                .position(|r| parse_header(&r.decoded).is_some())
+                .map(|i| results.swap_remove(i))
+                .or_else(|| results.into_iter().next());
+            let parsed = best.as_ref().and_then(|b| parse_header(&b.decoded));
+            let body = match &parsed {
+                Some((_, _, rest)) => rest.clone(),
+                None => best.as_ref().map(|b| b.decoded.clone()).unwrap_or_default(),
+            };
+            PieceInfo {
+                label: p.label.clone(),
+                zw_chars: p.text.chars().filter(|&c| is_zero_width(c)).count(),
+                header: parsed.map(|(i, t, _)| (i, t)),
+                label_number: p.from_file.then(|| label_number(&p.label)).flatten(),
+                best,
+                body,
+            }
+        })
+        .collect();
+
+    let headers: Vec<Option<usize>> = infos.iter().map(|i| i.header.map(|(n, _)| n)).collect();
+    let labels: Vec<Option<usize>> = infos.iter().map(|i| i.label_number).collect();
+    let (mut order, source, numbers) = if declared_only {
+        ((0..infos.len()).collect(), OrderSource::Declared, None)
+    } else if let Some(order) = order_by(&headers) {
+        (order, OrderSource::Header, Some(&headers))
+    } else if let Some(order) = order_by(&labels) {
+        (order, OrderSource::Label, Some(&labels))
+    } else {
+        ((0..infos.len()).collect(), OrderSource::Declared, None)
+    };
+
+    // 重复编号只保留第一份；按编号范围（从 0 或 1 开始，到声明总数或最大编号）找缺失
+    let mut missing = Vec::new();
+    let mut duplicates = Vec::new();
+    if let Some(numbers) = numbers {
+        let mut seen = Vec::new();
+        order.retain(|&i| {
+            let n = numbers[i].unwrap_or(0);
+            if seen.contains(&n) {
+                duplicates.push(n);
+                false
+            } else {
+                seen.push(n);
+                true
+            }
+        });
+        let first = if seen.contains(&0) { 0 } else { 1 };
+        let declared_total = infos.iter().filter_map(|i| i.header.and_then(|(_, t)| t)).max();
+        let last = seen.iter().copied().max().unwrap_or(0).max(declared_total.unwrap_or(0));
+        missing = (first..=last).filter(|n| !seen.contains(n)).collect();
+    }
+
+    let mut session = Session::new();
+    for &i in &order {
+        // 未设上限的会话不会拒收
+        let _ = session.feed(&pieces[i].text);
+    }
+    let mut stream = auto_decode(session.text(), budget);
+    rank_results(&mut stream);
+
+    let mut overlaps = Vec::new();
+    let joined = order.iter().all(|&i| infos[i].best.is_some()).then(|| {
+        let mut acc = String::new();
+        for &i in &order {
+            overlaps.push(merge(&mut acc, &infos[i].body));
+        }
+        let s = score(&acc);
+        (acc, s)
+    });
+    let joined = joined.filter(|(text, s)| !text.is_empty() && (order.len() > 1 || budget.acceptance.passes(*s)));
+
+    Reassembly { pieces: infos, order, source, missing, duplicates, stream, joined, overlaps }
+}
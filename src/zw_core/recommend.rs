@@ -0,0 +1,185 @@
+//! 方案推荐 - 按目标平台画像与载荷大小推荐编码方案和字符集
+//!
+//! 每个平台画像对应一串 `channel` 模拟的处理（规范化、清洗、修剪……）。对每个候选方案，先逐个字符
+//! 检查它在每种处理下是否保留（字符幸存表），再把探测消息按该方案实际嵌入载体、走完整条处理链，
+//! 确认零宽序列原样保留；最后按能否幸存、所需不可见字符数排序。画像是对平台行为的近似，
+//! 正式使用前仍应以 `zw_simulate_channel` 或真实平台验证。
+
+use super::channel::{self, Channel};
+use super::chars::{is_unicode_tag, is_zero_width, name_of};
+use super::engine;
+
+/// 探测用的消息与载体（载荷放在载体中部，不受首尾修剪影响）
+const PROBE_MESSAGE: &str = "flag{probe_123}";
+const PROBE_COVER: &str = "The quick brown fox jumps over the lazy dog";
+/// Unicode Tags 在各种处理下的表现一致，字符集中只用一个代表字符
+const TAG_REPRESENTATIVE: char = '\u{E0041}';
+
+/// 目标平台画像
+#[derive(Debug, Clone, Copy)]
+pub struct Profile {
+    pub key: &'static str,
+    pub name: &'static str,
+    /// 按顺序施加的处理
+    pub channels: &'static [Channel],
+}
+
+/// 内置画像
+pub fn profiles() -> &'static [Profile] {
+    &[
+        Profile { key: "plain_file", name: "纯文本文件 / 代码仓库（不做处理）", channels: &[] },
+        Profile {
+            key: "chat",
+            name: "即时通讯（Telegram/WhatsApp/微信等，近似为 NFC + 首尾修剪 + emoji 渲染）",
+            channels: &[Channel::Nfc, Channel::Trim, Channel::EmojiRender],
+        },
+        Profile {
+            key: "web_form",
+            name: "网页表单 / CMS（近似为 NFKC + 首尾修剪 + 删除 BiDi 控制）",
+            channels: &[Channel::Nfkc, Channel::Trim, Channel::HtmlBidi],
+        },
+        Profile {
+            key: "rich_text",
+            name: "富文本编辑器 / HTML 邮件（近似为删除 BiDi 控制与 Unicode Tags）",
+            channels: &[Channel::HtmlBidi, Channel::StripTags],
+        },
+        Profile {
+            key: "llm_platform",
+            name: "LLM 平台输入（近似为 NFKC + 删除 Unicode Tags 与 BiDi 控制）",
+            channels: &[Channel::Nfkc, Channel::StripTags, Channel::HtmlBidi],
+        },
+        Profile { key: "legacy_gbk", name: "GBK 编码的旧系统（GBK 往返转码）", channels: &[Channel::Gbk] },
+        Profile { key: "strict_sanitizer", name: "严格清洗（删除全部不可见字符）", channels: &[Channel::HtmlStrict] },
+    ]
+}
+
+pub fn profile(key: &str) -> Option<Profile> {
+    profiles().iter().copied().find(|p| p.key == key)
+}
+
+/// 候选方案的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// `zw_encode` 的 method
+    Method(&'static str),
+    /// 编码预设（内置或自定义）
+    Preset(String),
+}
+
+impl Source {
+    pub fn describe(&self) -> String {
+        match self {
+            Source::Method(m) => format!("zw_encode method={}", m),
+            Source::Preset(key) => format!("预设 {}（解码用 method={}）", key, key),
+        }
+    }
+}
+
+/// 单个候选方案的评估
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub source: Source,
+    /// 方案会用到的全部不可见字符
+    pub charset: Vec<char>,
+    /// 每个载荷字节需要的不可见字符数
+    pub chars_per_byte: f64,
+    /// 在画像的全部处理下保留的字符
+    pub surviving: Vec<char>,
+    /// 探测消息嵌入载体后走完整条处理链，零宽序列原样保留
+    pub verified: bool,
+    /// 只能编码 ASCII 等限制
+    pub note: Option<&'static str>,
+}
+
+impl Candidate {
+    pub fn survives(&self) -> bool {
+        self.verified && self.surviving.len() == self.charset.len()
+    }
+
+    /// 编码 `payload_bytes` 字节需要的不可见字符数
+    pub fn invisible_needed(&self, payload_bytes: usize) -> usize {
+        (self.chars_per_byte * payload_bytes as f64).ceil() as usize
+    }
+}
+
+/// 字符在某种处理下是否保留（放在两个可见字符之间检查）
+pub fn char_survives(ch: char, channel: Channel) -> bool {
+    let probe = format!("a{}b", ch);
+    channel::apply(channel, &probe).contains(ch)
+}
+
+fn zw_sequence(text: &str) -> Vec<char> {
+    text.chars().filter(|&c| is_zero_width(c)).collect()
+}
+
+fn evaluate(source: Source, encoded: &str, payload: &str, channels: &[Channel], note: Option<&'static str>) -> Candidate {
+    let mut charset: Vec<char> = Vec::new();
+    for c in zw_sequence(payload) {
+        let c = if is_unicode_tag(c) { TAG_REPRESENTATIVE } else { c };
+        if !charset.contains(&c) {
+            charset.push(c);
+        }
+    }
+    charset.sort_unstable();
+    let surviving = charset.iter().copied().filter(|&c| channels.iter().all(|&ch| char_survives(c, ch))).collect();
+    let processed = channels.iter().fold(encoded.to_string(), |text, &ch| channel::apply(ch, &text));
+    let chars_per_byte = zw_sequence(payload).len() as f64 / PROBE_MESSAGE.len() as f64;
+    Candidate {
+        source,
+        charset,
+        chars_per_byte,
+        surviving,
+        verified: zw_sequence(&processed) == zw_sequence(encoded),
+        note,
+    }
+}
+
+/// 在载体中部插入载荷
+fn embed(payload: &str) -> String {
+    let mid = PROBE_COVER.len() / 2;
+    format!("{}{}{}", &PROBE_COVER[..mid], payload, &PROBE_COVER[mid..])
+}
+
+/// 评估全部候选方案，按推荐程度排序：能幸存的在前，其次所需不可见字符少的
+pub fn recommend(channels: &[Channel]) -> Vec<Candidate> {
+    let mut out = Vec::new();
+    let methods: [(&'static str, String, Option<&'static str>); 5] = [
+        ("binary", engine::encode_binary(PROBE_MESSAGE, '\u{200B}', '\u{200C}', 8), None),
+        ("steganographr", engine::encode_steganographr(PROBE_MESSAGE, ""), None),
+        ("tags", engine::encode_tags(PROBE_MESSAGE, ""), Some("只能编码 ASCII")),
+        ("330k", engine::encode_330k(PROBE_MESSAGE, "", &['\u{200C}', '\u{200D}', '\u{202C}', '\u{FEFF}']), None),
+        ("morse", engine::encode_morse(PROBE_MESSAGE, ""), Some("只能编码字母、数字与常用标点，不区分大小写")),
+    ];
+    for (method, payload, note) in methods {
+        out.push(evaluate(Source::Method(method), &embed(&payload), &payload, channels, note));
+    }
+    for (key, preset) in engine::encoding_presets() {
+        // steganographr 预设的终止符不参与数字映射，按原方案编码
+        let payload = if key == "steganographr" {
+            engine::encode_steganographr(PROBE_MESSAGE, "")
+        } else {
+            engine::encode_preset(PROBE_MESSAGE, &preset)
+        };
+        let candidate = evaluate(Source::Preset(key), &embed(&payload), &payload, channels, None);
+        // 与已有方案字符集和长度都相同的预设（如 330k_default）不重复列出
+        if !out.iter().any(|c| c.charset == candidate.charset && c.chars_per_byte == candidate.chars_per_byte) {
+            out.push(candidate);
+        }
+    }
+    let ratio = |c: &Candidate| c.surviving.len() as f64 / c.charset.len().max(1) as f64;
+    out.sort_by(|a, b| {
+        b.survives()
+            .cmp(&a.survives())
+            .then_with(|| ratio(b).partial_cmp(&ratio(a)).unwrap_or(std::cmp::Ordering::Equal))
+            .then_with(|| a.chars_per_byte.partial_cmp(&b.chars_per_byte).unwrap_or(std::cmp::Ordering::Equal))
+    });
+    out
+}
+
+/// 字符名称，用于幸存表
+pub fn label(ch: char) -> String {
+    if ch == TAG_REPRESENTATIVE {
+        return "Unicode Tags (U+E0020–U+E007E)".to_string();
+    }
+    format!("U+{:04X} {}", ch as u32, name_of(ch).unwrap_or_default())
+}
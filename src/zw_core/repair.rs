@@ -0,0 +1,135 @@
+//! 载荷损伤修复 - 平台替换或剥离了部分载荷字符时，尝试还原后再解码，尽量部分恢复
+
+use super::chars::is_zero_width;
+use super::engine::{analyze, auto_decode, extract_segments, score, top_chars, Budget, DecodeResult};
+
+/// 常见的平台替换: (说明, 替换后的字符, 原字符)
+const SUBSTITUTIONS: &[(&str, char, char)] = &[
+    ("NBSP→ZWSP", '\u{00A0}', '\u{200B}'),
+    ("NBSP→ZWNBSP", '\u{00A0}', '\u{FEFF}'),
+    ("NNBSP→ZWSP", '\u{202F}', '\u{200B}'),
+    ("WJ→ZWNBSP", '\u{2060}', '\u{FEFF}'),
+    ("ZWNBSP→WJ", '\u{FEFF}', '\u{2060}'),
+];
+
+/// 修复结果的得分折扣，保证未损伤的正常结果排在前面
+const REPAIR_PENALTY: f64 = 0.9;
+
+/// 生成可能的修复文本: (修复说明, 修复后文本)
+fn repair_variants(text: &str) -> Vec<(String, String)> {
+    let mut variants = Vec::new();
+    for &(desc, damaged, original) in SUBSTITUTIONS {
+        if text.contains(damaged) {
+            variants.push((desc.to_string(), text.replace(damaged, &original.to_string())));
+        }
+    }
+
+    // 夹在零宽字符之间的普通空格，很可能是被转换掉的零宽空白
+    let chars: Vec<char> = text.chars().collect();
+    let sandwiched: Vec<usize> = (1..chars.len().saturating_sub(1))
+        .filter(|&i| chars[i] == ' ' && is_zero_width(chars[i - 1]) && is_zero_width(chars[i + 1]))
+        .collect();
+    if !sandwiched.is_empty() {
+        for &candidate in top_chars(&analyze(text)).iter().take(4) {
+            let mut repaired = chars.clone();
+            for &i in &sandwiched {
+                repaired[i] = candidate;
+            }
+            variants.push((
+                format!("{} 处空格→U+{:04X}", sandwiched.len(), candidate as u32),
+                repaired.into_iter().collect(),
+            ));
+        }
+    }
+    variants
+}
+
+/// 分段二进制的擦除恢复: 缺位的段枚举补位，只接受唯一的「像 flag」的候选，否则记为 '?'
+fn segmented_with_erasures(segments: &[String], zero: char, one: char, bits: usize) -> Option<(String, usize)> {
+    let mut out = String::new();
+    let mut erasures = 0;
+    for seg in segments {
+        let digits: Vec<u32> = seg
+            .chars()
+            .filter_map(|c| if c == zero { Some(0) } else if c == one { Some(1) } else { None })
+            .collect();
+        let value = |d: &[u32]| d.iter().fold(0u32, |v, b| v * 2 + b);
+        if digits.len() == bits {
+            out.push(char::from_u32(value(&digits)).filter(|c| c.is_ascii_graphic() || *c == ' ')?);
+        } else if digits.len() + 1 == bits {
+            erasures += 1;
+            let mut candidates: Vec<char> = Vec::new();
+            for pos in 0..=digits.len() {
+                for bit in [0, 1] {
+                    let mut d = digits.clone();
+                    d.insert(pos, bit);
+                    if let Some(c) = char::from_u32(value(&d)).filter(|c| c.is_ascii_alphanumeric() || "_{}-".contains(*c)) {
+                        if !candidates.contains(&c) {
+                            candidates.push(c);
+                        }
+                    }
+                }
+            }
+            out.push(if candidates.len() == 1 { candidates[0] } else { '?' });
+        } else {
+            return None;
+        }
+    }
+    (erasures > 0).then_some((out, erasures))
+}
+
+/// 对疑似损伤的文本做修复后再解码
+pub fn repair_decode(text: &str, budget: &Budget) -> Vec<DecodeResult> {
+    let mut results = Vec::new();
+    for (desc, repaired) in repair_variants(text) {
+        if budget.expired() {
+            return results;
+        }
+        for r in auto_decode(&repaired, budget).into_iter().filter(|r| budget.acceptance.passes(r.score)) {
+            let mut meta = vec![("repair", desc.clone())];
+            meta.extend(r.meta);
+            results.push(DecodeResult {
+                method: format!("修复[{}] {}", desc, r.method),
+                decoded: r.decoded,
+                score: r.score * REPAIR_PENALTY,
+                meta,
+                scheme: None,
+            });
+        }
+    }
+
+    let segments = extract_segments(text);
+    let top = top_chars(&analyze(text));
+    if segments.len() >= 4 && top.len() >= 2 {
+        let limit = top.len().min(4);
+        for i in 0..limit {
+            for j in 0..limit {
+                if i == j {
+                    continue;
+                }
+                for bits in [8, 7] {
+                    let Some((decoded, erasures)) = segmented_with_erasures(&segments, top[i], top[j], bits) else {
+                        continue;
+                    };
+                    if !budget.acceptance.is_printable(&decoded) {
+                        continue;
+                    }
+                    let s = score(&decoded) * REPAIR_PENALTY;
+                    if budget.acceptance.passes(s) {
+                        results.push(DecodeResult {
+                            method: format!(
+                                "修复[分段擦除 {} 处] 分段二进制 (U+{:04X}=0, U+{:04X}=1, {}bit)",
+                                erasures, top[i] as u32, top[j] as u32, bits
+                            ),
+                            decoded,
+                            score: s,
+                            meta: vec![("repair", format!("{} 个缺位段按擦除恢复，无法确定的字符记为 '?'", erasures))],
+                            scheme: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    results
+}
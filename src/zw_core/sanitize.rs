@@ -0,0 +1,245 @@
+//! 零宽/不可见字符清理，支持多种白名单策略
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+
+use super::chars::{is_arabic_script, is_unicode_tag, is_zero_width};
+
+/// 清理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// 删除所有不可见字符
+    All,
+    /// 删除所有不可见字符，但保留合法的 emoji ZWJ 序列、VS15/VS16 和旗帜 Tag 序列
+    KeepEmoji,
+    /// 仅删除 BiDi 方向控制字符
+    BidiOnly,
+    /// 将所有不可见字符替换为可见占位符
+    Placeholder,
+}
+
+impl Policy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "all" => Some(Policy::All),
+            "keep_emoji" => Some(Policy::KeepEmoji),
+            "bidi_only" => Some(Policy::BidiOnly),
+            "placeholder" => Some(Policy::Placeholder),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Policy::All => "all",
+            Policy::KeepEmoji => "keep_emoji",
+            Policy::BidiOnly => "bidi_only",
+            Policy::Placeholder => "placeholder",
+        }
+    }
+}
+
+/// 清理结果
+#[derive(Debug, Clone)]
+pub struct SanitizeResult {
+    pub text: String,
+    pub removed: usize,
+    pub kept: usize,
+    /// codepoint -> 被删除/替换的次数
+    pub removed_by_codepoint: BTreeMap<u32, usize>,
+    /// 跟在 🏴 后却没有以 CANCEL TAG 结束的 Tag 串（keep_emoji 策略下不视为旗帜，照常删除）
+    pub unterminated_tag_runs: usize,
+}
+
+/// 旗帜 Tag 序列的结束符 CANCEL TAG
+const CANCEL_TAG: char = '\u{E007F}';
+
+/// Tag 字符相对旗帜 Tag 序列（🏴 + tags + CANCEL TAG）的归属
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlagTag {
+    /// 位于以 CANCEL TAG 结束的旗帜序列内（含结束符本身）
+    Terminated,
+    /// 跟在 🏴 后，但之后没有 CANCEL TAG
+    Unterminated,
+    /// 不跟在 🏴 后，或在结束符之后
+    Outside,
+}
+
+/// 一次扫描给出每个位置的旗帜归属（非 Tag 字符为 Outside）
+fn flag_tags(chars: &[char]) -> Vec<FlagTag> {
+    let mut tags = vec![FlagTag::Outside; chars.len()];
+    let mut start = 0;
+    while start < chars.len() {
+        if !is_unicode_tag(chars[start]) {
+            start += 1;
+            continue;
+        }
+        let end = chars[start..].iter().position(|c| !is_unicode_tag(*c)).map_or(chars.len(), |p| start + p);
+        if start > 0 && chars[start - 1] == '\u{1F3F4}' {
+            // 结束符之后紧跟的 Tag 不属于这面旗帜
+            match chars[start..end].iter().position(|&c| c == CANCEL_TAG) {
+                Some(p) => tags[start..=start + p].fill(FlagTag::Terminated),
+                None => tags[start..end].fill(FlagTag::Unterminated),
+            }
+        }
+        start = end;
+    }
+    tags
+}
+
+/// 判断字符是否是 BiDi 方向控制字符
+pub fn is_bidi_control(ch: char) -> bool {
+    matches!(ch as u32, 0x061C | 0x200E | 0x200F | 0x202A..=0x202E | 0x2066..=0x2069)
+}
+
+/// 粗略判断字符是否是 emoji（用于识别合法的 ZWJ / VS16 上下文）
+pub fn is_emoji(ch: char) -> bool {
+    matches!(ch as u32,
+        0x00A9 | 0x00AE | 0x203C | 0x2049 | 0x2122 | 0x2139 |
+        0x2194..=0x21AA |
+        0x231A..=0x23FF |
+        0x24C2 |
+        0x25AA..=0x25FE |
+        0x2600..=0x27BF |
+        0x2934 | 0x2935 |
+        0x2B00..=0x2BFF |
+        0x3030 | 0x303D | 0x3297 | 0x3299 |
+        0x1F000..=0x1FAFF
+    )
+}
+
+/// 判断位置 i 的不可见字符是否属于合法 emoji 序列
+///
+/// `prev_visible` 为之前最近的可见字符（跳过 VS / 肤色等已合法的部分），`flag` 为该位置的旗帜归属，
+/// 都由调用方在顺序扫描中给出，避免每个字符重新扫描整串
+fn is_legit_emoji_part(chars: &[char], i: usize, prev_visible: Option<char>, flag: FlagTag) -> bool {
+    let ch = chars[i];
+    let prev_is_emoji = prev_visible.map(is_emoji).unwrap_or(false);
+    match ch as u32 {
+        // ZWJ: 两侧都是 emoji
+        0x200D => {
+            let next = chars.get(i + 1).copied();
+            prev_is_emoji && next.map(is_emoji).unwrap_or(false)
+        }
+        // VS15 / VS16: 跟在 emoji 或键帽基字符后面
+        0xFE0E | 0xFE0F => {
+            i > 0 && (is_emoji(chars[i - 1]) || matches!(chars[i - 1], '0'..='9' | '#' | '*'))
+        }
+        // 旗帜 Tag 序列: 🏴 + tags + CANCEL TAG，缺少结束符的不算
+        _ if is_unicode_tag(ch) => flag == FlagTag::Terminated,
+        _ => false,
+    }
+}
+
+/// 判断位置 i 的 ZWNJ 是否是波斯语等阿拉伯字母文字中正字法要求的用法
+///
+/// 仅当 ZWNJ 单独出现且两侧紧邻阿拉伯字母时成立；成串出现的零宽字符仍视为可疑载荷。
+pub fn is_legit_zwnj(chars: &[char], i: usize) -> bool {
+    chars[i] == '\u{200C}'
+        && i > 0
+        && is_arabic_script(chars[i - 1])
+        && chars.get(i + 1).copied().is_some_and(is_arabic_script)
+}
+
+/// 剔除正字法用途的 ZWNJ，返回 (剔除后文本, 剔除数量)
+pub fn remove_legit_zwnj(text: &str) -> (String, usize) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut removed = 0usize;
+    for (i, &ch) in chars.iter().enumerate() {
+        if is_legit_zwnj(&chars, i) {
+            removed += 1;
+        } else {
+            out.push(ch);
+        }
+    }
+    (out, removed)
+}
+
+/// 按策略清理文本
+pub fn sanitize(text: &str, policy: Policy, placeholder: Option<&str>) -> SanitizeResult {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut removed_by_codepoint: BTreeMap<u32, usize> = BTreeMap::new();
+    let mut kept = 0usize;
+    let mut unterminated_tag_runs = 0usize;
+    let flags = flag_tags(&chars);
+    let mut prev_visible = None;
+
+    for (i, &ch) in chars.iter().enumerate() {
+        if !is_zero_width(ch) {
+            out.push(ch);
+            prev_visible = Some(ch);
+            continue;
+        }
+        // 每个缺少结束符的旗帜 Tag 串只在首字符处计一次
+        if is_unicode_tag(ch) && i > 0 && chars[i - 1] == '\u{1F3F4}' && flags[i] == FlagTag::Unterminated {
+            unterminated_tag_runs += 1;
+        }
+        let remove = match policy {
+            Policy::All | Policy::Placeholder => true,
+            Policy::KeepEmoji => !is_legit_emoji_part(&chars, i, prev_visible, flags[i]),
+            Policy::BidiOnly => is_bidi_control(ch),
+        };
+        if !remove {
+            kept += 1;
+            out.push(ch);
+            continue;
+        }
+        *removed_by_codepoint.entry(ch as u32).or_insert(0) += 1;
+        if policy == Policy::Placeholder {
+            match placeholder {
+                Some(p) => out.push_str(p),
+                None => out.push_str(&format!("[U+{:04X}]", ch as u32)),
+            }
+        }
+    }
+
+    SanitizeResult {
+        text: out,
+        removed: removed_by_codepoint.values().sum(),
+        kept,
+        removed_by_codepoint,
+        unterminated_tag_runs,
+    }
+}
+
+thread_local! {
+    /// 当前请求是否回显解码文本的原始字符（raw_output 参数）
+    static RAW_OUTPUT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 当前线程的回显模式；请求内另开的工作线程用它沿用同一模式
+pub fn raw_output() -> bool {
+    RAW_OUTPUT.with(|r| r.get())
+}
+
+/// 在指定回显模式下执行 `f`，结束后恢复原模式（请求在单个线程内完成）
+pub fn with_raw_output<T>(raw: bool, f: impl FnOnce() -> T) -> T {
+    let previous = RAW_OUTPUT.with(|r| r.replace(raw));
+    let out = f();
+    RAW_OUTPUT.with(|r| r.set(previous));
+    out
+}
+
+fn needs_escape(ch: char) -> bool {
+    ch.is_control() || is_zero_width(ch) || is_bidi_control(ch) || matches!(ch, '\u{2028}' | '\u{2029}')
+}
+
+/// 回显解码结果时使用：把解码文本自身包含的控制、方向与零宽字符写成 `\u{...}`，
+/// 防止恶意载荷在工具输出中伪造行、反转显示顺序或藏起内容；raw_output 模式下原样返回
+pub fn display(text: &str) -> Cow<'_, str> {
+    if raw_output() || !text.chars().any(needs_escape) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len() + 16);
+    for ch in text.chars() {
+        if needs_escape(ch) {
+            out.push_str(&format!("\\u{{{:x}}}", ch as u32));
+        } else {
+            out.push(ch);
+        }
+    }
+    Cow::Owned(out)
+}
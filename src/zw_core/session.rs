@@ -0,0 +1,86 @@
+//! 增量解码会话 - 分块累积零宽字符流
+
+use super::chars::is_zero_width;
+
+/// 解码会话
+///
+/// 只保留零宽字符，每段连续可见文本压缩成一个空格，
+/// 这样分段边界仍然保留，但内存只随零宽字符数量增长。
+#[derive(Debug, Default, Clone)]
+pub struct Session {
+    stream: String,
+    chunks: usize,
+    total_chars: usize,
+    zw_chars: usize,
+    /// 上一个块是否以可见字符结尾
+    in_visible_run: bool,
+    /// 累积流的字节上限（压缩后），None 表示不限
+    max_bytes: Option<usize>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 累积流（压缩后）不得超过 `max_bytes` 字节的会话
+    pub fn with_limit(max_bytes: usize) -> Self {
+        Self { max_bytes: Some(max_bytes), ..Self::default() }
+    }
+
+    /// 追加一个块，返回该块中的零宽字符数
+    ///
+    /// 压缩后会使累积流超过字节上限时整块拒收，会话保持不变。
+    pub fn feed(&mut self, chunk: &str) -> Result<usize, String> {
+        let mut compressed = String::new();
+        let mut in_visible_run = self.in_visible_run;
+        let mut added = 0usize;
+        let mut total = 0usize;
+        for ch in chunk.chars() {
+            total += 1;
+            if is_zero_width(ch) {
+                compressed.push(ch);
+                in_visible_run = false;
+                added += 1;
+            } else if !in_visible_run {
+                compressed.push(' ');
+                in_visible_run = true;
+            }
+        }
+        let needed = self.stream.len() + compressed.len();
+        if let Some(max) = self.max_bytes.filter(|&max| needed > max) {
+            return Err(format!(
+                "会话累积流将达到 {} 字节，超过上限 {} 字节；请先用 zw_session_decode 解码并关闭当前会话",
+                needed, max
+            ));
+        }
+        self.stream.push_str(&compressed);
+        self.in_visible_run = in_visible_run;
+        self.total_chars += total;
+        self.chunks += 1;
+        self.zw_chars += added;
+        Ok(added)
+    }
+
+    /// 累积的压缩文本（可直接交给解码引擎）
+    pub fn text(&self) -> &str {
+        &self.stream
+    }
+
+    pub fn chunks(&self) -> usize {
+        self.chunks
+    }
+
+    pub fn total_chars(&self) -> usize {
+        self.total_chars
+    }
+
+    /// 累积流当前占用的字节数
+    pub fn stream_bytes(&self) -> usize {
+        self.stream.len()
+    }
+
+    pub fn zw_chars(&self) -> usize {
+        self.zw_chars
+    }
+}
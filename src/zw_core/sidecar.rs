@@ -0,0 +1,167 @@
+//! 编码描述（sidecar）- 记录一次编码的字符集、映射、位序与插入位置
+//!
+//! 描述为 JSON，与编码结果一起返回，也可写在输出文件旁边，生成的样本因此自带说明，
+//! 以后做回归测试时不必再猜当初的参数。`mapping` 的格式与 `zw_decode` 的 `mapping` 参数相同。
+
+use serde_json::{json, Map, Value};
+
+use super::chars::is_zero_width;
+use super::emulate::EmulatedTool;
+use super::engine;
+use super::stegcloak;
+
+/// 描述格式的版本，字段有不兼容的改动时递增
+pub const FORMAT_VERSION: u32 = 1;
+
+fn codepoint(ch: char) -> String {
+    format!("U+{:04X}", ch as u32)
+}
+
+/// 码点 → 数字值映射（下标即数字值）
+fn mapping(digits: &[char]) -> Value {
+    let map: Map<String, Value> = digits.iter().enumerate().map(|(v, &c)| (codepoint(c), json!(v))).collect();
+    Value::Object(map)
+}
+
+/// 定长 N 进制方案的公共字段
+fn positional(name: &str, unit: &str, digits: &[char], digits_per_unit: Option<usize>) -> Map<String, Value> {
+    let mut m = Map::new();
+    m.insert("scheme".to_string(), json!(name));
+    m.insert("unit".to_string(), json!(unit));
+    m.insert("charset".to_string(), json!(digits.iter().map(|&c| codepoint(c)).collect::<Vec<_>>()));
+    m.insert("mapping".to_string(), mapping(digits));
+    m.insert("base".to_string(), json!(digits.len()));
+    m.insert("digits_per_unit".to_string(), json!(digits_per_unit));
+    m.insert("digit_order".to_string(), json!("msb_first"));
+    m
+}
+
+/// `zw_encode` 内置方法（binary / steganographr / tags / 330k / morse / stegcloak）的方案描述；`options` 决定 StegCloak 的模式
+pub fn builtin_scheme(method: &str, options: &stegcloak::Options) -> Option<Value> {
+    let m = match method {
+        "binary" => {
+            let mut m = positional("binary", "codepoint_low8", &['\u{200B}', '\u{200C}'], Some(8));
+            m.insert("note".to_string(), json!("每个字符取码点低 8 位"));
+            m
+        }
+        "steganographr" => {
+            let mut m = positional("steganographr", "codepoint_low8", &['\u{200B}', '\u{200C}'], Some(8));
+            m.insert("terminator".to_string(), json!(codepoint('\u{2060}')));
+            m.insert("note".to_string(), json!("每个字符取码点低 8 位，其后跟一个 WJ"));
+            m
+        }
+        "tags" => {
+            let mut m = Map::new();
+            m.insert("scheme".to_string(), json!("tags"));
+            m.insert("unit".to_string(), json!("ascii"));
+            m.insert("offset".to_string(), json!(codepoint('\u{E0000}')));
+            m.insert("note".to_string(), json!("每个 ASCII 字符映射为 U+E0000 + 码点，非 ASCII 字符被丢弃"));
+            m
+        }
+        "330k" => positional("330k", "codepoint_low16", &['\u{200C}', '\u{200D}', '\u{202C}', '\u{FEFF}'], Some(8)),
+        "morse" => {
+            let [dot, dash, separator] = engine::MORSE_CHARSET;
+            let mut m = Map::new();
+            m.insert("scheme".to_string(), json!("morse"));
+            m.insert("unit".to_string(), json!("itu_morse"));
+            m.insert("dot".to_string(), json!(codepoint(dot)));
+            m.insert("dash".to_string(), json!(codepoint(dash)));
+            m.insert("separator".to_string(), json!(codepoint(separator)));
+            m.insert("note".to_string(), json!("国际莫尔斯码，每个字母后跟一个分隔符，词间再多一个；字母不区分大小写"));
+            m
+        }
+        "stegcloak" => stegcloak_scheme(options),
+        _ => return None,
+    };
+    Some(Value::Object(m))
+}
+
+/// StegCloak 的方案描述：压缩/加密后的字节流，数据字符不与明文字符一一对应
+fn stegcloak_scheme(options: &stegcloak::Options) -> Map<String, Value> {
+    let mode = stegcloak::Mode::for_options(options);
+    let mut m = positional("stegcloak", "lzutf8_byte", &stegcloak::CHARSET[..4], Some(4));
+    m.insert("substitutes".to_string(), json!(stegcloak::CHARSET[4..].iter().map(|&c| codepoint(c)).collect::<Vec<_>>()));
+    m.insert("mode".to_string(), json!(mode.describe()));
+    m.insert("encrypted".to_string(), json!(mode.encrypted()));
+    m.insert(
+        "note".to_string(),
+        json!("消息先按 LZUTF8 压缩（有口令时再做 AES-256-CTR 加密），首字符为模式标志；出现最多的两种双字符组合由 U+2063/U+2064 代替并写在流首；口令不写入描述"),
+    );
+    m
+}
+
+/// 第三方工具仿真的方案描述
+pub fn emulated_scheme(tool: EmulatedTool, options: &stegcloak::Options) -> Value {
+    let mut m = match tool {
+        EmulatedTool::Web330k => {
+            let mut m = positional("330k", "utf16_unit", &['\u{200C}', '\u{200D}', '\u{202C}', '\u{FEFF}'], Some(8));
+            m.insert("note".to_string(), json!("每个码元的数字组依次穿插在载体的可见字符之后，多出的追加在末尾"));
+            m
+        }
+        EmulatedTool::Steganographr => {
+            let mut m = positional("steganographr", "utf8_byte", &['\u{200B}', '\u{200C}'], None);
+            m.insert("separator".to_string(), json!(codepoint('\u{2060}')));
+            m.insert("wrapper".to_string(), json!(codepoint('\u{FEFF}')));
+            m.insert("note".to_string(), json!("每个字节的二进制不补零，字节间用 WJ 分隔，整体用 U+FEFF 包裹"));
+            m
+        }
+        EmulatedTool::ZeroWidthLib => {
+            let mut m = positional("zero_width_lib", "utf16_unit", &['\u{200C}', '\u{200B}'], Some(8));
+            m.insert("separator".to_string(), json!(codepoint('\u{200D}')));
+            m.insert("joiner".to_string(), json!(codepoint('\u{FEFF}')));
+            m.insert("note".to_string(), json!("码元的二进制至少 8 位，码元间用 ZWJ 分隔，所有符号之间用 U+FEFF 连接"));
+            m
+        }
+        EmulatedTool::StegCloak => stegcloak_scheme(options),
+    };
+    m.insert("emulate".to_string(), json!(tool.name()));
+    Value::Object(m)
+}
+
+/// 编码结果中每段连续不可见字符的位置（字符下标）与长度
+pub fn payload_positions(encoded: &str) -> Vec<Value> {
+    let mut out = Vec::new();
+    let mut start = None;
+    let mut count = 0usize;
+    for (i, ch) in encoded.chars().enumerate() {
+        if is_zero_width(ch) {
+            start.get_or_insert(i);
+            count += 1;
+        } else if let Some(s) = start.take() {
+            out.push(json!({ "start": s, "length": count }));
+            count = 0;
+        }
+    }
+    if let Some(s) = start {
+        out.push(json!({ "start": s, "length": count }));
+    }
+    out
+}
+
+/// 编码结果在描述生成后又被调整（如避开开头的 BOM）时，重新计算载荷位置与输出统计
+pub fn update_output(desc: &mut Value, encoded: &str) {
+    let fresh = describe(Value::Null, None, "", encoded);
+    desc["placement"]["positions"] = fresh["placement"]["positions"].clone();
+    desc["output"] = fresh["output"].clone();
+}
+
+/// 完整的编码描述：方案、消息（多载荷时在各段方案中）、插入方式与载荷位置
+pub fn describe(scheme: Value, message: Option<&str>, placement: &str, encoded: &str) -> Value {
+    let invisible = encoded.chars().filter(|&c| is_zero_width(c)).count();
+    json!({
+        "format": "zw-encode-sidecar",
+        "version": FORMAT_VERSION,
+        "generator": format!("zw-mcp-server {}", env!("CARGO_PKG_VERSION")),
+        "message": message,
+        "scheme": scheme,
+        "placement": {
+            "mode": placement,
+            "positions": payload_positions(encoded),
+        },
+        "output": {
+            "chars": encoded.chars().count(),
+            "invisible_chars": invisible,
+            "utf8_bytes": encoded.len(),
+        },
+    })
+}
@@ -0,0 +1,438 @@
+//! StegCloak - KuroLabs StegCloak 的隐写格式：压缩、口令加密与 HMAC 完整性校验
+//!
+//! hide 流程：消息按 LZUTF8 压缩 → 给出口令时用 AES-256-CTR 加密（PBKDF2-SHA512(口令, 16 字节盐, 10000 轮)
+//! 派生 48 字节，前 16 字节为 IV、后 32 字节为密钥），载荷为 `盐 ‖ [HMAC-SHA256(密钥, 密文)] ‖ 密文`
+//! → 最前面加一个标志字符（加密+校验 / 仅加密 / 未加密），每个字节按 2 比特一个字符写成 U+200C/U+200D/U+2061/U+2062
+//! → 出现最多的两种双字符组合分别换成 U+2063/U+2064，这两种组合写在最前面 → 插在载体第一个词之后。
+//! reveal 按相反顺序还原；口令由调用方通过 `Options` 显式传入。
+
+use std::hash::{BuildHasher, Hasher};
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use hmac::{Hmac, Mac};
+use sha2::{Sha256, Sha512};
+
+use super::chars::is_zero_width;
+
+/// StegCloak 使用的 6 个不可见字符：前 4 个各表示 2 比特，后 2 个代替最常见的双字符组合
+pub const CHARSET: [char; 6] = ['\u{200C}', '\u{200D}', '\u{2061}', '\u{2062}', '\u{2063}', '\u{2064}'];
+
+const SALT_LEN: usize = 16;
+const HMAC_LEN: usize = 32;
+const PBKDF2_ROUNDS: u32 = 10_000;
+
+/// LZUTF8 指针的长度与距离上限
+const MIN_MATCH: usize = 4;
+const MAX_MATCH: usize = 31;
+const MAX_DISTANCE: usize = 32_767;
+
+type Aes256Ctr = ctr::Ctr128BE<aes::Aes256>;
+
+/// 编码/解码选项
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// 口令；编码时给出则加密，解码加密载荷时必需
+    pub password: Option<String>,
+    /// 编码时附加 HMAC 完整性校验（仅加密时有效）
+    pub integrity: bool,
+}
+
+/// 标志字符表示的载荷模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// 加密并带 HMAC
+    EncryptedIntegrity,
+    /// 仅加密
+    Encrypted,
+    /// 未加密（只压缩）
+    Plain,
+}
+
+impl Mode {
+    /// 按编码选项决定的模式
+    pub fn for_options(options: &Options) -> Self {
+        match (&options.password, options.integrity) {
+            (Some(_), true) => Mode::EncryptedIntegrity,
+            (Some(_), false) => Mode::Encrypted,
+            (None, _) => Mode::Plain,
+        }
+    }
+
+    fn flag(self) -> char {
+        match self {
+            Mode::EncryptedIntegrity => CHARSET[0],
+            Mode::Encrypted => CHARSET[1],
+            Mode::Plain => CHARSET[2],
+        }
+    }
+
+    fn from_flag(ch: char) -> Option<Self> {
+        match CHARSET.iter().position(|&c| c == ch)? {
+            0 => Some(Mode::EncryptedIntegrity),
+            1 => Some(Mode::Encrypted),
+            2 => Some(Mode::Plain),
+            _ => None,
+        }
+    }
+
+    pub fn encrypted(self) -> bool {
+        self != Mode::Plain
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Mode::EncryptedIntegrity => "加密 + HMAC 校验",
+            Mode::Encrypted => "加密",
+            Mode::Plain => "未加密",
+        }
+    }
+}
+
+/// reveal 的失败原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevealError {
+    /// 没有 StegCloak 字符，或标志/头部不合法
+    NotStegCloak,
+    /// 载荷已加密，但没有给出口令
+    PasswordRequired(Mode),
+    /// HMAC 不符：口令错误或载荷损坏
+    IntegrityFailed,
+    /// 解密/解压后不是合法文本（口令错误、载荷损坏或不是 StegCloak）
+    Corrupt(Mode),
+}
+
+impl RevealError {
+    pub fn describe(&self) -> String {
+        match self {
+            RevealError::NotStegCloak => "字符集或头部不符合 StegCloak 格式".to_string(),
+            RevealError::PasswordRequired(mode) => format!("载荷为 StegCloak {}格式，需要 password", mode.describe()),
+            RevealError::IntegrityFailed => "HMAC 校验失败：口令错误或载荷损坏".to_string(),
+            RevealError::Corrupt(mode) if mode.encrypted() => "解密后无法解压为文本：口令错误或载荷损坏".to_string(),
+            RevealError::Corrupt(_) => "无法解压为文本：载荷损坏".to_string(),
+        }
+    }
+}
+
+/// 解出的消息
+#[derive(Debug, Clone)]
+pub struct Revealed {
+    pub message: String,
+    pub mode: Mode,
+}
+
+// --- LZUTF8 ---
+
+/// 字节串中每个 UTF-8 字符的起始位置是否为字符边界
+fn char_boundaries(bytes: &[u8]) -> Vec<bool> {
+    let mut boundary: Vec<bool> = bytes.iter().map(|&b| b & 0xC0 != 0x80).collect();
+    boundary.push(true);
+    boundary
+}
+
+/// LZUTF8 压缩：重复出现的片段写成指向前文的指针（`110lllll 0ddddddd` 或 `111lllll 0ddddddd dddddddd`），
+/// 其余字节原样保留；匹配只从字符边界开始、到字符边界结束，且不与自身重叠
+pub fn compress(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let boundary = char_boundaries(bytes);
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut last_seen: std::collections::HashMap<&[u8], usize> = std::collections::HashMap::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let mut best: Option<(usize, usize)> = None;
+        if boundary[i] && i + MIN_MATCH <= bytes.len() {
+            if let Some(&start) = last_seen.get(&bytes[i..i + MIN_MATCH]) {
+                let distance = i - start;
+                let limit = MAX_MATCH.min(distance).min(bytes.len() - i);
+                let mut len = (0..limit).take_while(|&k| bytes[start + k] == bytes[i + k]).count();
+                while len > 0 && !boundary[i + len] {
+                    len -= 1;
+                }
+                if len >= MIN_MATCH && distance <= MAX_DISTANCE {
+                    best = Some((len, distance));
+                }
+            }
+        }
+        let step = match best {
+            Some((len, distance)) => {
+                if distance < 128 {
+                    out.extend_from_slice(&[0xC0 | len as u8, distance as u8]);
+                } else {
+                    out.extend_from_slice(&[0xE0 | len as u8, (distance >> 8) as u8, distance as u8]);
+                }
+                len
+            }
+            None => {
+                out.push(bytes[i]);
+                1
+            }
+        };
+        for j in i..i + step {
+            if boundary[j] && j + MIN_MATCH <= bytes.len() {
+                last_seen.insert(&bytes[j..j + MIN_MATCH], j);
+            }
+        }
+        i += step;
+    }
+    out
+}
+
+/// LZUTF8 解压；指针越界或结果不是合法 UTF-8 时返回 None
+pub fn decompress(data: &[u8]) -> Option<String> {
+    let mut out: Vec<u8> = Vec::with_capacity(data.len() * 2);
+    let mut i = 0;
+    while i < data.len() {
+        let b = data[i];
+        let next = data.get(i + 1).copied();
+        let (len, distance, width) = match next {
+            Some(n) if b >> 6 == 3 && n >> 7 == 0 => {
+                if b >> 5 == 6 {
+                    ((b & 0x1F) as usize, n as usize, 2)
+                } else {
+                    let low = *data.get(i + 2)?;
+                    ((b & 0x1F) as usize, ((n as usize) << 8) | low as usize, 3)
+                }
+            }
+            _ => {
+                out.push(b);
+                i += 1;
+                continue;
+            }
+        };
+        if len == 0 || distance == 0 || distance > out.len() {
+            return None;
+        }
+        let start = out.len() - distance;
+        for k in 0..len {
+            out.push(out[start + k]);
+        }
+        i += width;
+    }
+    String::from_utf8(out).ok()
+}
+
+// --- 加密 ---
+
+/// PBKDF2-SHA512 派生 IV 与密钥
+fn derive(password: &str, salt: &[u8]) -> ([u8; 16], [u8; 32]) {
+    let mut derived = [0u8; 48];
+    pbkdf2::pbkdf2_hmac::<Sha512>(password.as_bytes(), salt, PBKDF2_ROUNDS, &mut derived);
+    let mut iv = [0u8; 16];
+    let mut key = [0u8; 32];
+    iv.copy_from_slice(&derived[..16]);
+    key.copy_from_slice(&derived[16..]);
+    (iv, key)
+}
+
+fn hmac(key: &[u8; 32], data: &[u8]) -> [u8; HMAC_LEN] {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// 随机盐；只需不重复，不要求密码学强度
+fn random_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    for chunk in salt.chunks_mut(8) {
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        hasher.write_u128(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_nanos()));
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes()[..chunk.len()]);
+    }
+    salt
+}
+
+fn encrypt(data: &[u8], password: &str, integrity: bool) -> Vec<u8> {
+    let salt = random_salt();
+    let (iv, key) = derive(password, &salt);
+    let mut cipher = data.to_vec();
+    Aes256Ctr::new(&key.into(), &iv.into()).apply_keystream(&mut cipher);
+    let mut out = salt.to_vec();
+    if integrity {
+        out.extend_from_slice(&hmac(&key, &cipher));
+    }
+    out.extend_from_slice(&cipher);
+    out
+}
+
+fn decrypt(data: &[u8], password: &str, mode: Mode) -> Result<Vec<u8>, RevealError> {
+    let header = SALT_LEN + if mode == Mode::EncryptedIntegrity { HMAC_LEN } else { 0 };
+    if data.len() < header {
+        return Err(RevealError::Corrupt(mode));
+    }
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (iv, key) = derive(password, salt);
+    let cipher = match mode {
+        Mode::EncryptedIntegrity => {
+            let (tag, cipher) = rest.split_at(HMAC_LEN);
+            if hmac(&key, cipher) != tag {
+                return Err(RevealError::IntegrityFailed);
+            }
+            cipher
+        }
+        _ => rest,
+    };
+    let mut plain = cipher.to_vec();
+    Aes256Ctr::new(&key.into(), &iv.into()).apply_keystream(&mut plain);
+    Ok(plain)
+}
+
+// --- 字符流 ---
+
+/// 两个数据字符组成的 16 种组合，下标即组合的 4 比特值
+fn bigram(index: usize) -> [char; 2] {
+    [CHARSET[index >> 2], CHARSET[index & 3]]
+}
+
+/// 不重叠地从左到右数出组合出现的次数
+fn count_bigram(stream: &[char], pair: [char; 2]) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i + 1 < stream.len() {
+        if stream[i..i + 2] == pair {
+            count += 1;
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    count
+}
+
+fn replace_bigram(stream: &[char], pair: [char; 2], with: char) -> Vec<char> {
+    let mut out = Vec::with_capacity(stream.len());
+    let mut i = 0;
+    while i < stream.len() {
+        if i + 1 < stream.len() && stream[i..i + 2] == pair {
+            out.push(with);
+            i += 2;
+        } else {
+            out.push(stream[i]);
+            i += 1;
+        }
+    }
+    out
+}
+
+/// 出现最多的两种组合换成 U+2063 / U+2064，两种组合本身写在最前面（4 个数据字符）
+fn shrink(stream: &[char]) -> Vec<char> {
+    let most = |s: &[char], skip: Option<usize>| -> usize {
+        (0..16).filter(|&i| Some(i) != skip).max_by_key(|&i| (count_bigram(s, bigram(i)), std::cmp::Reverse(i))).unwrap_or(0)
+    };
+    let first = most(stream, None);
+    let replaced = replace_bigram(stream, bigram(first), CHARSET[4]);
+    let second = most(&replaced, Some(first));
+    let replaced = replace_bigram(&replaced, bigram(second), CHARSET[5]);
+    let mut out: Vec<char> = bigram(first).into_iter().chain(bigram(second)).collect();
+    out.extend(replaced);
+    out
+}
+
+fn expand(stream: &[char]) -> Option<Vec<char>> {
+    if stream.len() < 4 || stream[..4].iter().any(|c| !CHARSET[..4].contains(c)) {
+        return None;
+    }
+    let (first, second) = ([stream[0], stream[1]], [stream[2], stream[3]]);
+    let mut out = Vec::with_capacity(stream.len() * 2);
+    for &c in &stream[4..] {
+        match c {
+            c if c == CHARSET[5] => out.extend(second),
+            c if c == CHARSET[4] => out.extend(first),
+            c => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+fn bytes_to_chars(bytes: &[u8]) -> impl Iterator<Item = char> + '_ {
+    bytes.iter().flat_map(|&b| [6, 4, 2, 0].map(|shift| CHARSET[((b >> shift) & 3) as usize]))
+}
+
+fn chars_to_bytes(chars: &[char]) -> Option<Vec<u8>> {
+    if !chars.len().is_multiple_of(4) {
+        return None;
+    }
+    chars
+        .chunks(4)
+        .map(|group| {
+            group.iter().try_fold(0u8, |acc, c| CHARSET[..4].iter().position(|x| x == c).map(|d| (acc << 2) | d as u8))
+        })
+        .collect()
+}
+
+/// 只由 StegCloak 字符组成的零宽载荷（不含载体）
+pub fn payload(message: &str, options: &Options) -> String {
+    let compressed = compress(message);
+    let mode = Mode::for_options(options);
+    let data = match options.password.as_deref() {
+        Some(password) => encrypt(&compressed, password, options.integrity),
+        None => compressed,
+    };
+    let stream: Vec<char> = std::iter::once(mode.flag()).chain(bytes_to_chars(&data)).collect();
+    shrink(&stream).into_iter().collect()
+}
+
+/// 按 StegCloak 的方式隐藏：载荷插在载体第一个词之后（载体没有空格时追加在末尾）
+pub fn hide(message: &str, cover: &str, options: &Options) -> String {
+    let hidden = payload(message, options);
+    match cover.find(' ') {
+        Some(i) => format!("{}{}{}", &cover[..i], hidden, &cover[i..]),
+        None => format!("{}{}", cover, hidden),
+    }
+}
+
+/// 文本中的 StegCloak 字符流
+pub fn extract(text: &str) -> Vec<char> {
+    text.chars().filter(|c| CHARSET.contains(c)).collect()
+}
+
+/// StegCloak 字符至少占全部零宽字符的这么多分之几（十分之），容许零星的杂散字符
+const MIN_SHARE: usize = 9;
+
+/// 零宽字符几乎都属于 StegCloak 字符集，且出现了它特有的替代字符 U+2063/U+2064
+pub fn looks_like(text: &str) -> bool {
+    let total = text.chars().filter(|&c| is_zero_width(c)).count();
+    let chars = extract(text);
+    !chars.is_empty() && chars.len() * 10 >= total * MIN_SHARE && chars.iter().any(|c| CHARSET[4..].contains(c))
+}
+
+/// 字符流的结构（不需要口令）
+#[derive(Debug, Clone)]
+pub struct Layout {
+    /// U+2063 代替的双字符组合
+    pub first: [char; 2],
+    /// U+2064 代替的双字符组合
+    pub second: [char; 2],
+    /// 标志字符
+    pub flag: char,
+    pub mode: Mode,
+    /// 展开后的数据字节数（含盐与 HMAC）
+    pub data_bytes: usize,
+}
+
+/// 只解析头部与标志字符
+pub fn layout(text: &str) -> Option<Layout> {
+    let chars = extract(text);
+    let stream = expand(&chars)?;
+    let flag = *stream.first()?;
+    Some(Layout {
+        first: [chars[0], chars[1]],
+        second: [chars[2], chars[3]],
+        flag,
+        mode: Mode::from_flag(flag)?,
+        data_bytes: (stream.len() - 1) / 4,
+    })
+}
+
+/// 还原 StegCloak 消息；加密载荷需要 `password`
+pub fn reveal(text: &str, password: Option<&str>) -> Result<Revealed, RevealError> {
+    let stream = expand(&extract(text)).ok_or(RevealError::NotStegCloak)?;
+    let (&flag, body) = stream.split_first().ok_or(RevealError::NotStegCloak)?;
+    let mode = Mode::from_flag(flag).ok_or(RevealError::NotStegCloak)?;
+    let data = chars_to_bytes(body).ok_or(RevealError::NotStegCloak)?;
+    let compressed = match (mode.encrypted(), password) {
+        (false, _) => data,
+        (true, None) => return Err(RevealError::PasswordRequired(mode)),
+        (true, Some(password)) => decrypt(&data, password, mode)?,
+    };
+    let message = decompress(&compressed).filter(|m| !m.is_empty()).ok_or(RevealError::Corrupt(mode))?;
+    Ok(Revealed { message, mode })
+}
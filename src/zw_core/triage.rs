@@ -0,0 +1,138 @@
+//! 批量扫描汇总 - 把目录/批量扫描的逐文件结果聚合成统计与分诊列表
+//!
+//! 大规模扫描的逐文件输出太长，难以整体判断。这里对每个含不可见字符的文件做一次快速分析与解码，
+//! 再汇总：按字符分类、按检测到的方案统计，并按可疑程度排序给出优先处理的文件。
+
+use std::collections::BTreeMap;
+
+use super::chars;
+use super::engine::{self, Budget, DecodeResult};
+
+/// 单个文件的扫描结果（只记录含不可见字符的文件）
+#[derive(Debug, Clone)]
+pub struct FileReport {
+    pub path: String,
+    pub zw_count: usize,
+    pub unique_chars: usize,
+    /// 分类 -> 字符数
+    pub categories: BTreeMap<&'static str, usize>,
+    /// 得分最高的解码结果；预算耗尽未解码时为 None
+    pub best: Option<DecodeResult>,
+    /// 解码结果中找到的 flag
+    pub flags: Vec<String>,
+    /// 是否尝试了解码（预算耗尽后的文件只做统计）
+    pub decoded: bool,
+}
+
+/// 分诊优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl Priority {
+    pub fn label(self) -> &'static str {
+        match self {
+            Priority::High => "高",
+            Priority::Medium => "中",
+            Priority::Low => "低",
+        }
+    }
+}
+
+/// 未归入已知分类的字符
+const OTHER_CATEGORY: &str = "其他";
+/// 解码得分达到这个值、且不短于 READABLE_LEN 个字符才视为可读的隐藏信息（噪声偶尔解出两三个字母）
+const READABLE_SCORE: f64 = 70.0;
+const READABLE_LEN: usize = 4;
+
+/// 扫描单个文件；没有不可见字符时返回 None
+pub fn scan_file(path: &str, text: &str, budget: &Budget) -> Option<FileReport> {
+    let analysis = engine::analyze(text);
+    if analysis.zero_width_count == 0 {
+        return None;
+    }
+    let mut categories: BTreeMap<&'static str, usize> = BTreeMap::new();
+    for (&cp, &count) in &analysis.distribution {
+        let category = char::from_u32(cp).and_then(chars::category_of).unwrap_or(OTHER_CATEGORY);
+        *categories.entry(category).or_default() += count;
+    }
+    let decoded = !budget.expired();
+    let best = if decoded { engine::auto_decode(text, budget).into_iter().next() } else { None };
+    let flags = best.as_ref().map(|r| engine::find_flags(&r.decoded)).unwrap_or_default();
+    Some(FileReport {
+        path: path.to_string(),
+        zw_count: analysis.zero_width_count,
+        unique_chars: analysis.unique_zw_chars,
+        categories,
+        best,
+        flags,
+        decoded,
+    })
+}
+
+/// 方案族名：方案名中括号前的部分（字符集、分组等参数不参与汇总）
+pub fn scheme_family(method: &str) -> &str {
+    method.split(['(', '（']).next().unwrap_or(method).trim()
+}
+
+impl FileReport {
+    /// 优先级与理由
+    pub fn priority(&self) -> (Priority, String) {
+        if let Some(flag) = self.flags.first() {
+            return (Priority::High, format!("解码出 flag: {}", flag));
+        }
+        if let Some(best) = self.best.as_ref().filter(|b| engine::is_verified(b)) {
+            return (Priority::High, format!("解码结果通过校验 ({})", scheme_family(&best.method)));
+        }
+        if let Some(best) = self.best.as_ref().filter(|b| b.score >= READABLE_SCORE && b.decoded.chars().count() >= READABLE_LEN) {
+            return (Priority::Medium, format!("解码出可读文本 ({}, 得分 {:.1})", scheme_family(&best.method), best.score));
+        }
+        if self.zw_count >= 16 && self.unique_chars >= 2 {
+            return (Priority::Medium, format!("{} 个不可见字符（{} 种），疑似编码载荷但未能解码", self.zw_count, self.unique_chars));
+        }
+        let reason = if self.decoded { "零星不可见字符，可能是排版残留" } else { "预算耗尽，未尝试解码" };
+        (Priority::Low, reason.to_string())
+    }
+
+    /// 排序键：优先级、解码得分、字符数
+    fn rank(&self) -> (Priority, i64, usize) {
+        let score = self.best.as_ref().map_or(i64::MIN, |b| (b.score * 10.0) as i64);
+        (self.priority().0, score, self.zw_count)
+    }
+}
+
+/// 按分类汇总：分类 -> (文件数, 字符数)
+pub fn by_category(reports: &[FileReport]) -> BTreeMap<&'static str, (usize, usize)> {
+    let mut out: BTreeMap<&'static str, (usize, usize)> = BTreeMap::new();
+    for r in reports {
+        for (&cat, &count) in &r.categories {
+            let entry = out.entry(cat).or_default();
+            entry.0 += 1;
+            entry.1 += count;
+        }
+    }
+    out
+}
+
+/// 按检测到的方案族汇总，按文件数降序：(方案族, 文件数)
+pub fn top_schemes(reports: &[FileReport]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for r in reports {
+        if let Some(best) = &r.best {
+            *counts.entry(scheme_family(&best.method).to_string()).or_default() += 1;
+        }
+    }
+    let mut out: Vec<(String, usize)> = counts.into_iter().collect();
+    out.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    out
+}
+
+/// 按可疑程度降序排列的分诊列表
+pub fn triage(reports: &[FileReport]) -> Vec<&FileReport> {
+    let mut sorted: Vec<&FileReport> = reports.iter().collect();
+    sorted.sort_by(|a, b| b.rank().cmp(&a.rank()).then_with(|| a.path.cmp(&b.path)));
+    sorted
+}
@@ -0,0 +1,56 @@
+//! 题目语料回归：corpus/cases.jsonl 中每道题的答案都必须排在自动解码的第一位，
+//! corpus/false_positives.jsonl 中的极短诱饵不能压过真正的答案
+
+use zw_mcp_server::zw_core::benchmark;
+use zw_mcp_server::zw_core::engine::{auto_decode, Budget};
+
+#[test]
+fn corpus_answers_rank_first() {
+    let cases = benchmark::embedded_corpus().expect("内置语料解析失败");
+    assert!(!cases.is_empty(), "内置语料为空");
+
+    let budget = Budget::normal();
+    let mut failures = Vec::new();
+    for case in &cases {
+        let results = auto_decode(&case.text, &budget);
+        match results.iter().position(|r| r.decoded.contains(&case.answer)) {
+            Some(0) => {}
+            Some(i) => failures.push(format!("{}: 答案排第 {} 名，首位 {:?}", case.name, i + 1, results[0].decoded)),
+            None => failures.push(format!("{}: {} 个候选中没有答案 {:?}", case.name, results.len(), case.answer)),
+        }
+    }
+    assert!(failures.is_empty(), "语料回归失败:\n{}", failures.join("\n"));
+}
+
+/// 极短解码（不超过 2 个字符）视为诱饵
+const DECOY_MAX_CHARS: usize = 2;
+
+#[test]
+fn short_decoys_never_outrank_answers() {
+    let cases = benchmark::false_positive_corpus().expect("误报语料解析失败");
+    assert!(!cases.is_empty(), "误报语料为空");
+
+    let budget = Budget::normal();
+    let mut failures = Vec::new();
+    for case in &cases {
+        let results = auto_decode(&case.text, &budget);
+        let Some(answer) = results.iter().position(|r| r.decoded.contains(&case.answer)) else {
+            failures.push(format!("{}: {} 个候选中没有答案 {:?}", case.name, results.len(), case.answer));
+            continue;
+        };
+        if answer != 0 {
+            failures.push(format!("{}: 答案排第 {} 名，首位 {:?}", case.name, answer + 1, results[0].decoded));
+        }
+        // 诱饵即使被接受，得分也必须严格低于答案，不能靠并列排到前面
+        let answer_score = results[answer].score;
+        for r in results.iter().filter(|r| r.decoded.trim().chars().count() <= DECOY_MAX_CHARS) {
+            if r.score >= answer_score {
+                failures.push(format!(
+                    "{}: 极短候选 {:?} 得分 {:.1} 不低于答案的 {:.1} [{}]",
+                    case.name, r.decoded, r.score, answer_score, r.method
+                ));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "短解码误报回归失败:\n{}", failures.join("\n"));
+}
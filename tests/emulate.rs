@@ -0,0 +1,24 @@
+//! 编码仿真夹具：zw_encode emulate=<tool> 的输出必须与 corpus/emulate/fixtures.jsonl 逐字节一致
+
+use zw_mcp_server::zw_core::emulate::{self, EmulatedTool};
+
+#[test]
+fn fixtures_match_reference_output() {
+    let results = emulate::verify_fixtures().expect("仿真夹具解析失败");
+    assert!(!results.is_empty(), "仿真夹具为空");
+    let failed: Vec<String> = results
+        .iter()
+        .filter(|r| !r.passed)
+        .map(|r| format!("{} ({}): {}", r.name, r.tool, r.detail))
+        .collect();
+    assert!(failed.is_empty(), "仿真夹具不一致:\n{}", failed.join("\n"));
+}
+
+#[test]
+fn fixtures_cover_every_deterministic_tool() {
+    let results = emulate::verify_fixtures().expect("仿真夹具解析失败");
+    // StegCloak 的盐与插入位置是随机的，无法逐字节复现，不要求夹具
+    for tool in [EmulatedTool::Web330k, EmulatedTool::Steganographr, EmulatedTool::ZeroWidthLib] {
+        assert!(results.iter().any(|r| r.tool == tool.name()), "{} 没有仿真夹具", tool.name());
+    }
+}